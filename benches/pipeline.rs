@@ -0,0 +1,149 @@
+//! # Pipeline Benchmarks
+//!
+//! Criterion benchmarks for each compilation phase (parse, analyze, transform,
+//! generate) and the complete pipeline, measured over synthetic sources of
+//! increasing size that mix the constructs found in typical application code.
+//!
+//! ```bash
+//! cargo bench --bench pipeline
+//! cargo bench --bench pipeline -- parse   # run a single phase
+//! cargo bench --bench pipeline -- resolve  # scope chain lookups
+//! ```
+
+use std::hint::black_box;
+
+use criterion::{criterion_group, criterion_main, BatchSize, BenchmarkId, Criterion, Throughput};
+
+use rjs_compiler::analyzer::{analyze_ast, AnalyzerConfig};
+use rjs_compiler::generator::{Generator, GeneratorConfig};
+use rjs_compiler::parser::ast_types::Program;
+use rjs_compiler::parser::{parse_js, ParserConfig};
+use rjs_compiler::transformer::transform_ast;
+
+/// Number of generated modules per benchmark size
+const SIZES: &[(&str, usize)] = &[("small", 50), ("medium", 500), ("large", 5000)];
+
+/// Builds a representative source file with `modules` independent blocks of code
+fn generate_source(modules: usize) -> String {
+    let mut source = String::new();
+    for i in 0..modules {
+        source.push_str(&format!(
+            r#"// Module {i}
+const LIMIT_{i} = Math.max({i}, 100);
+let label_{i} = "item-" + String({i});
+
+function compute_{i}(width, height) {{
+    let area = width * height;
+    let ratio = (area - {i}) / (width + 1);
+    return ratio * 2 + LIMIT_{i} % 7;
+}}
+
+function describe_{i}(name, count) {{
+    return `${{name}} has ${{count}} entries of ${{label_{i}}}`;
+}}
+
+let result_{i} = compute_{i}(LIMIT_{i}, "abc".length) - -{i};
+console.log(describe_{i}("block", result_{i}), typeof result_{i}, items[{i}].value);
+
+"#
+        ));
+    }
+    source
+}
+
+/// Builds `depth` nested functions, each declaring one variable and a parameter
+fn generate_nested_source(depth: usize) -> String {
+    let mut source = String::new();
+    for level in 0..depth {
+        source.push_str(&format!("function level_{level}(arg_{level}) {{ let value_{level} = {level};\n"));
+    }
+    source.push_str("return value_0;\n");
+    source.push_str(&"}\n".repeat(depth));
+    source
+}
+
+/// Parses a source string, panicking on syntax errors
+fn parse(source: &str) -> Program {
+    let result = parse_js(source, "bench.js", &ParserConfig::default());
+    assert!(result.errors.is_empty(), "benchmark source should parse: {:?}", result.errors);
+    result.ast.expect("benchmark source should produce an AST")
+}
+
+fn bench_phases(c: &mut Criterion) {
+    for &(label, modules) in SIZES {
+        let source = generate_source(modules);
+        let ast = parse(&source);
+        let analysis = analyze_ast(&ast, &AnalyzerConfig::default()).unwrap();
+        let transformed = transform_ast(ast.clone(), analysis.clone()).unwrap().transformed_ast;
+
+        let mut group = c.benchmark_group(format!("phases/{}", label));
+        group.throughput(Throughput::Bytes(source.len() as u64));
+        if modules >= 5000 {
+            group.sample_size(10);
+        }
+
+        group.bench_function("parse", |b| {
+            b.iter(|| parse_js(black_box(&source), "bench.js", &ParserConfig::default()))
+        });
+
+        group.bench_function("analyze", |b| {
+            b.iter(|| analyze_ast(black_box(&ast), &AnalyzerConfig::default()).unwrap())
+        });
+
+        group.bench_function("transform", |b| {
+            b.iter_batched(
+                || (ast.clone(), analysis.clone()),
+                |(ast, analysis)| transform_ast(ast, analysis).unwrap(),
+                BatchSize::LargeInput,
+            )
+        });
+
+        group.bench_function("generate", |b| {
+            let generator = Generator::new(GeneratorConfig::default());
+            b.iter(|| generator.generate(black_box(&transformed), None).unwrap())
+        });
+
+        group.finish();
+    }
+}
+
+fn bench_full_pipeline(c: &mut Criterion) {
+    let mut group = c.benchmark_group("pipeline");
+    group.sample_size(10);
+
+    for &(label, modules) in SIZES {
+        let source = generate_source(modules);
+        group.throughput(Throughput::Bytes(source.len() as u64));
+        group.bench_with_input(BenchmarkId::from_parameter(label), &source, |b, source| {
+            b.iter(|| rjs_compiler::compile_source(black_box(source), "bench.js").unwrap())
+        });
+    }
+
+    group.finish();
+}
+
+fn bench_scope_resolution(c: &mut Criterion) {
+    let mut group = c.benchmark_group("resolve");
+
+    for depth in [8, 64, 256] {
+        let ast = parse(&generate_nested_source(depth));
+        let analysis = analyze_ast(&ast, &AnalyzerConfig::default()).unwrap();
+        // Every name resolves from the innermost scope, walking up to `depth` parents
+        let innermost = analysis.scope_tree.scopes.len() as u32 - 1;
+        let names: Vec<String> = (0..depth).map(|level| format!("value_{level}")).collect();
+
+        group.throughput(Throughput::Elements(depth as u64));
+        group.bench_with_input(BenchmarkId::from_parameter(depth), &names, |b, names| {
+            b.iter(|| {
+                for name in names {
+                    black_box(analysis.scope_tree.resolve(&analysis.symbol_table, innermost, black_box(name)));
+                }
+            })
+        });
+    }
+
+    group.finish();
+}
+
+criterion_group!(benches, bench_phases, bench_full_pipeline, bench_scope_resolution);
+criterion_main!(benches);
@@ -0,0 +1,36 @@
+//! Feeds arbitrary ASTs through analysis, transformation and generation, skipping the
+//! parser: the bytes are read as the JSON form of a `Program`, as
+//! `rjs-compiler ast --format json` prints it, so the phases also see trees the parser
+//! never produces.
+//!
+//! ```bash
+//! cargo fuzz run ast tests/fuzz_regressions
+//! ```
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use rjs_compiler::parser::ast_types::Program;
+use rjs_compiler::{analyzer, generator, transformer};
+
+fuzz_target!(|data: &[u8]| {
+    let Ok(ast) = serde_json::from_slice::<Program>(data) else {
+        return;
+    };
+    let analyzer_config = analyzer::AnalyzerConfig { aggressive_optimization: true, ..Default::default() };
+    let Ok(analysis) = analyzer::analyze_ast(&ast, &analyzer_config) else {
+        return;
+    };
+    let transformer_config = transformer::TransformerConfig::default().with_aggressive_optimization(true);
+    let Ok(result) = transformer::transform_ast_with_config(ast, analysis, transformer_config) else {
+        return;
+    };
+    // Spans may point anywhere, so generate against a source they do not fit
+    for source in [None, Some("")] {
+        let generator = generator::Generator::new(generator::GeneratorConfig {
+            source_map: generator::SourceMapMode::File,
+            ..Default::default()
+        });
+        let _ = generator.generate(&result.transformed_ast, source);
+    }
+});
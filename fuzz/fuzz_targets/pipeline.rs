@@ -0,0 +1,44 @@
+//! Feeds arbitrary bytes through the whole pipeline: the default `minify` and, for
+//! input that parses, the aggressive passes with both output formats.
+//!
+//! ```bash
+//! cargo fuzz run pipeline tests/fuzz_regressions
+//! ```
+//!
+//! Every input must come back as a result or a `CompileError`; a panic is a bug. Add
+//! the input that found one to `tests/fuzz_regressions/` with the fix.
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use rjs_compiler::{analyzer, generator, parser, transformer, MinifyOptions};
+
+fuzz_target!(|data: &[u8]| {
+    let source = String::from_utf8_lossy(data);
+    let _ = rjs_compiler::minify(&source, &MinifyOptions { filename: None, source_map: true });
+
+    let Some(ast) = parser::parse_js(&source, "fuzz.js", &parser::ParserConfig::default()).ast else {
+        return;
+    };
+    let analyzer_config = analyzer::AnalyzerConfig { aggressive_optimization: true, ..Default::default() };
+    let Ok(analysis) = analyzer::analyze_ast(&ast, &analyzer_config) else {
+        return;
+    };
+    let transformer_config = transformer::TransformerConfig::default()
+        .with_aggressive_optimization(true)
+        .with_hoist_funs(true)
+        .with_hoist_vars(true)
+        .with_dedupe_strings(Some(0));
+    let Ok(result) = transformer::transform_ast_with_config(ast, analysis, transformer_config) else {
+        return;
+    };
+    for format in [generator::OutputFormat::Compact, generator::OutputFormat::Pretty] {
+        let generator = generator::Generator::new(generator::GeneratorConfig {
+            format,
+            source_map: generator::SourceMapMode::File,
+            max_line_len: Some(40),
+            ..Default::default()
+        });
+        let _ = generator.generate(&result.transformed_ast, Some(&source));
+    }
+});
@@ -0,0 +1,477 @@
+//! # API Surface
+//!
+//! The exports of a module as seen by its importers: each exported name with the kind
+//! of binding behind it and, for functions and classes, the arity (`length`: the
+//! parameters before the first default or rest parameter). Comparing the surface of the
+//! input with that of the output finds exports that minification removed, renamed or
+//! changed.
+//!
+//! The surface of the input comes from the OXC AST ([`source_api_surface`]), since the
+//! owned AST leaves out declarations it can't represent; the surface of the output comes
+//! from the program that is printed ([`api_surface`]).
+
+use std::collections::BTreeMap;
+
+use oxc_ast::ast as oxc;
+use oxc_ast::syntax_directed_operations::BoundNames;
+use serde::{Deserialize, Serialize};
+
+use crate::analyzer::module_record::{DEFAULT_EXPORT_LOCAL, ImportedName};
+use crate::analyzer::{SemanticAnalysis, SymbolType, VariableKind};
+use crate::parser::ast_types::{ClassElement, Expression, MethodKind, Pattern, Program, Statement};
+
+/// Exports of a module, sorted by name
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ApiSurface {
+    pub exports: Vec<ApiExport>,
+}
+
+/// One exported name
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ApiExport {
+    /// Name importers use; `*` for `export * from`
+    pub name:   String,
+    pub kind:   ExportKind,
+    /// Declared parameter count of functions and classes, when known
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub arity:  Option<usize>,
+    /// Module a re-export or exported import comes from
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source: Option<String>,
+}
+
+/// Kind of binding behind an export
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ExportKind {
+    Function,
+    Class,
+    Const,
+    Let,
+    Var,
+    /// A binding imported from another module
+    Import,
+    /// `export { a } from` or `export * from`
+    ReExport,
+    /// `export * as ns from`
+    Namespace,
+    /// `export default` of an expression
+    Value,
+}
+
+/// Difference between two surfaces for one exported name
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "change", rename_all = "snake_case")]
+pub enum ApiChange {
+    Removed { name: String },
+    Added { name: String },
+    KindChanged { name: String, before: ExportKind, after: ExportKind },
+    ArityChanged { name: String, before: Option<usize>, after: Option<usize> },
+}
+
+impl std::fmt::Display for ApiChange {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Removed { name } => write!(f, "export '{}' was removed", name),
+            Self::Added { name } => write!(f, "export '{}' was added", name),
+            Self::KindChanged { name, before, after } => {
+                write!(f, "export '{}' changed from {:?} to {:?}", name, before, after)
+            }
+            Self::ArityChanged { name, before, after } => write!(
+                f,
+                "export '{}' changed arity from {} to {}",
+                name,
+                before.map_or_else(|| "unknown".to_string(), |arity| arity.to_string()),
+                after.map_or_else(|| "unknown".to_string(), |arity| arity.to_string()),
+            ),
+        }
+    }
+}
+
+/// Computes the exports of `ast` from its module record and symbol table
+pub fn api_surface(ast: &Program, analysis: &SemanticAnalysis) -> ApiSurface {
+    let declarations = top_level_declarations(ast);
+    let record = &analysis.module_record;
+    let mut exports = Vec::new();
+
+    for export in &record.local_exports {
+        let symbol = analysis
+            .symbol_table
+            .lookup(analysis.scope_tree.root_scope_id, &export.local)
+            .and_then(|id| analysis.symbol_table.symbols.get(id));
+        let declaration = declarations.get(export.local.as_str());
+        let import = record.imports.iter().find(|import| import.local == export.local);
+
+        let kind = match (declaration, symbol.map(|symbol| &symbol.symbol_type)) {
+            (Some(Declaration::Function(_)), _) => ExportKind::Function,
+            (Some(Declaration::Class(_)), _) => ExportKind::Class,
+            _ if export.local == DEFAULT_EXPORT_LOCAL => ExportKind::Value,
+            (_, Some(SymbolType::Variable { kind: VariableKind::Const })) => ExportKind::Const,
+            (_, Some(SymbolType::Variable { kind: VariableKind::Let })) => ExportKind::Let,
+            (_, Some(SymbolType::Import)) => ExportKind::Import,
+            _ if import.is_some() => ExportKind::Import,
+            _ => ExportKind::Var,
+        };
+        let arity = match declaration {
+            Some(Declaration::Function(arity) | Declaration::Class(arity)) => *arity,
+            _ => None,
+        };
+        exports.push(ApiExport {
+            name: export.exported.clone(),
+            kind,
+            arity,
+            source: import.map(|import| import.source.clone()),
+        });
+    }
+    for export in &record.re_exports {
+        let kind = match export.imported {
+            ImportedName::Named(_) => ExportKind::ReExport,
+            ImportedName::Namespace => ExportKind::Namespace,
+        };
+        exports.push(ApiExport { name: export.exported.clone(), kind, arity: None, source: Some(export.source.clone()) });
+    }
+    for source in &record.star_exports {
+        exports.push(ApiExport { name: "*".to_string(), kind: ExportKind::ReExport, arity: None, source: Some(source.clone()) });
+    }
+
+    exports.sort_by(|a, b| a.name.cmp(&b.name).then_with(|| a.source.cmp(&b.source)));
+    ApiSurface { exports }
+}
+
+/// Computes the exports of a parsed source from its OXC AST
+///
+/// Type-only exports and `declare` declarations export nothing at runtime and are left
+/// out, as they are from the output.
+pub fn source_api_surface(program: &oxc::Program<'_>) -> ApiSurface {
+    let mut bindings = BTreeMap::new();
+    for statement in &program.body {
+        match statement {
+            oxc::Statement::ImportDeclaration(import) if !import.import_kind.is_type() => {
+                for specifier in import.specifiers.iter().flatten() {
+                    if matches!(specifier, oxc::ImportDeclarationSpecifier::ImportSpecifier(specifier) if specifier.import_kind.is_type()) {
+                        continue;
+                    }
+                    let name = specifier.local().name.to_string();
+                    let source = Some(import.source.value.to_string());
+                    bindings.insert(name.clone(), ApiExport { name, kind: ExportKind::Import, arity: None, source });
+                }
+            }
+            oxc::Statement::ExportNamedDeclaration(export) => {
+                if let Some(declaration) = &export.declaration {
+                    declare_source_bindings(declaration, &mut bindings);
+                }
+            }
+            oxc::Statement::ExportDefaultDeclaration(export) => {
+                if let Some(default) = source_default_export(&export.declaration)
+                    && let Some(name) = default_export_name(&export.declaration)
+                {
+                    bindings.insert(name.clone(), ApiExport { name, ..default });
+                }
+            }
+            statement => {
+                if let Some(declaration) = statement.as_declaration() {
+                    declare_source_bindings(declaration, &mut bindings);
+                }
+            }
+        }
+    }
+
+    let binding = |local: &str, name: String| match bindings.get(local) {
+        Some(binding) => ApiExport { name, ..binding.clone() },
+        None => ApiExport { name, kind: ExportKind::Var, arity: None, source: None },
+    };
+    let mut exports = Vec::new();
+    for statement in &program.body {
+        match statement {
+            oxc::Statement::ExportNamedDeclaration(export) if !export.export_kind.is_type() => {
+                if let Some(declaration) = &export.declaration
+                    && !declaration.declare()
+                {
+                    declaration.bound_names(&mut |id| exports.push(binding(&id.name, id.name.to_string())));
+                }
+                for specifier in export.specifiers.iter().filter(|specifier| !specifier.export_kind.is_type()) {
+                    let name = specifier.exported.name().to_string();
+                    exports.push(match &export.source {
+                        Some(source) => ApiExport {
+                            name,
+                            kind: ExportKind::ReExport,
+                            arity: None,
+                            source: Some(source.value.to_string()),
+                        },
+                        None => binding(&specifier.local.name(), name),
+                    });
+                }
+            }
+            oxc::Statement::ExportDefaultDeclaration(export) => {
+                exports.extend(source_default_export(&export.declaration));
+            }
+            oxc::Statement::ExportAllDeclaration(export) if !export.export_kind.is_type() => {
+                let (name, kind) = match &export.exported {
+                    Some(exported) => (exported.name().to_string(), ExportKind::Namespace),
+                    None => ("*".to_string(), ExportKind::ReExport),
+                };
+                exports.push(ApiExport { name, kind, arity: None, source: Some(export.source.value.to_string()) });
+            }
+            _ => {}
+        }
+    }
+
+    exports.sort_by(|a, b| a.name.cmp(&b.name).then_with(|| a.source.cmp(&b.source)));
+    ApiSurface { exports }
+}
+
+/// Records the top-level bindings of a declaration, keyed by name
+fn declare_source_bindings(declaration: &oxc::Declaration<'_>, bindings: &mut BTreeMap<String, ApiExport>) {
+    if declaration.declare() {
+        return;
+    }
+    let mut bind = |name: &str, kind, arity| {
+        bindings.insert(name.to_string(), ApiExport { name: name.to_string(), kind, arity, source: None });
+    };
+    match declaration {
+        oxc::Declaration::VariableDeclaration(declaration) => {
+            let kind = match declaration.kind {
+                oxc::VariableDeclarationKind::Var => ExportKind::Var,
+                oxc::VariableDeclarationKind::Let => ExportKind::Let,
+                _ => ExportKind::Const,
+            };
+            for declarator in &declaration.declarations {
+                let function_arity = match (&declarator.id.kind, &declarator.init) {
+                    (oxc::BindingPatternKind::BindingIdentifier(_), Some(oxc::Expression::FunctionExpression(function))) => {
+                        Some(source_arity(&function.params))
+                    }
+                    (oxc::BindingPatternKind::BindingIdentifier(_), Some(oxc::Expression::ArrowFunctionExpression(arrow))) => {
+                        Some(source_arity(&arrow.params))
+                    }
+                    _ => None,
+                };
+                declarator.id.bound_names(&mut |id| match function_arity {
+                    Some(arity) => bind(&id.name, ExportKind::Function, Some(arity)),
+                    None => bind(&id.name, kind, None),
+                });
+            }
+        }
+        oxc::Declaration::FunctionDeclaration(function) => {
+            if let Some(id) = &function.id {
+                bind(&id.name, ExportKind::Function, Some(source_arity(&function.params)));
+            }
+        }
+        oxc::Declaration::ClassDeclaration(class) => {
+            if let Some(id) = &class.id {
+                bind(&id.name, ExportKind::Class, source_class_arity(class));
+            }
+        }
+        _ => {}
+    }
+}
+
+/// The `default` export of an `export default` declaration, or `None` for an interface
+fn source_default_export(declaration: &oxc::ExportDefaultDeclarationKind<'_>) -> Option<ApiExport> {
+    let (kind, arity) = match declaration {
+        oxc::ExportDefaultDeclarationKind::FunctionDeclaration(function) => {
+            (ExportKind::Function, Some(source_arity(&function.params)))
+        }
+        oxc::ExportDefaultDeclarationKind::ClassDeclaration(class) => (ExportKind::Class, source_class_arity(class)),
+        oxc::ExportDefaultDeclarationKind::TSInterfaceDeclaration(_) => return None,
+        _ => (ExportKind::Value, None),
+    };
+    Some(ApiExport { name: "default".to_string(), kind, arity, source: None })
+}
+
+/// Name a default-exported function or class declares
+fn default_export_name(declaration: &oxc::ExportDefaultDeclarationKind<'_>) -> Option<String> {
+    match declaration {
+        oxc::ExportDefaultDeclarationKind::FunctionDeclaration(function) => function.id.as_ref(),
+        oxc::ExportDefaultDeclarationKind::ClassDeclaration(class) => class.id.as_ref(),
+        _ => None,
+    }
+    .map(|id| id.name.to_string())
+}
+
+/// [`arity`] of OXC parameters
+fn source_arity(params: &oxc::FormalParameters<'_>) -> usize {
+    params.items.iter().take_while(|param| !param.pattern.kind.is_assignment_pattern()).count()
+}
+
+/// Arity of an OXC class: its constructor's, or 0 without one and without a superclass
+fn source_class_arity(class: &oxc::Class<'_>) -> Option<usize> {
+    let constructor = class.body.body.iter().find_map(|element| match element {
+        oxc::ClassElement::MethodDefinition(method) if method.kind.is_constructor() => Some(source_arity(&method.value.params)),
+        _ => None,
+    });
+    constructor.or_else(|| class.super_class.is_none().then_some(0))
+}
+
+/// Changes from `before` to `after`, by name
+pub fn compare(before: &ApiSurface, after: &ApiSurface) -> Vec<ApiChange> {
+    let key = |export: &ApiExport| (export.name.clone(), export.source.clone().filter(|_| export.name == "*"));
+    let before: BTreeMap<_, _> = before.exports.iter().map(|export| (key(export), export)).collect();
+    let after: BTreeMap<_, _> = after.exports.iter().map(|export| (key(export), export)).collect();
+
+    let mut changes = Vec::new();
+    for (key, old) in &before {
+        match after.get(key) {
+            None => changes.push(ApiChange::Removed { name: old.name.clone() }),
+            Some(new) if new.kind != old.kind => {
+                changes.push(ApiChange::KindChanged { name: old.name.clone(), before: old.kind, after: new.kind });
+            }
+            Some(new) if new.arity != old.arity => {
+                changes.push(ApiChange::ArityChanged { name: old.name.clone(), before: old.arity, after: new.arity });
+            }
+            Some(_) => {}
+        }
+    }
+    for (key, new) in &after {
+        if !before.contains_key(key) {
+            changes.push(ApiChange::Added { name: new.name.clone() });
+        }
+    }
+    changes
+}
+
+/// A top-level function or class, with its arity
+enum Declaration {
+    Function(Option<usize>),
+    Class(Option<usize>),
+}
+
+/// Top-level functions and classes by name, including function-valued variables
+fn top_level_declarations(ast: &Program) -> BTreeMap<&str, Declaration> {
+    let mut declarations = BTreeMap::new();
+    for statement in &ast.body {
+        let statement = match statement {
+            Statement::ExportNamedDeclaration { declaration: Some(declaration), .. }
+            | Statement::ExportDefaultDeclaration { declaration, .. } => declaration.as_ref(),
+            statement => statement,
+        };
+        match statement {
+            Statement::FunctionDeclaration { id, params, .. } => {
+                let name = id.as_ref().map_or(DEFAULT_EXPORT_LOCAL, |id| id.name.as_str());
+                declarations.insert(name, Declaration::Function(Some(arity(params))));
+            }
+            Statement::ClassDeclaration { id, super_class, body, .. } => {
+                let constructor = body.body.iter().find_map(|element| match element {
+                    ClassElement::MethodDefinition { kind: MethodKind::Constructor, value, .. } => Some(arity(&value.params)),
+                    _ => None,
+                });
+                // Without a constructor, a subclass takes the arguments of its superclass
+                let class_arity = constructor.or_else(|| super_class.is_none().then_some(0));
+                declarations.insert(id.as_ref().map_or(DEFAULT_EXPORT_LOCAL, |id| id.name.as_str()), Declaration::Class(class_arity));
+            }
+            Statement::VariableDeclaration { declarations: declarators, .. } => {
+                for declarator in declarators {
+                    let Pattern::Identifier(id) = &declarator.id else { continue };
+                    match &declarator.init {
+                        Some(Expression::FunctionExpression(function)) => {
+                            declarations.insert(id.name.as_str(), Declaration::Function(Some(arity(&function.params))));
+                        }
+                        Some(Expression::ArrowFunctionExpression { params, .. }) => {
+                            declarations.insert(id.name.as_str(), Declaration::Function(Some(arity(params))));
+                        }
+                        _ => {}
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+    declarations
+}
+
+/// `length` of a function: its parameters before the first default or rest parameter
+fn arity(params: &[Pattern]) -> usize {
+    params
+        .iter()
+        .take_while(|param| !matches!(param, Pattern::AssignmentPattern { .. } | Pattern::RestElement { .. }))
+        .count()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::analyzer::{analyze_ast, AnalyzerConfig};
+    use crate::parser::{parse_js, ParserConfig};
+
+    fn surface(source: &str) -> ApiSurface {
+        let ast = parse_js(source, "test.js", &ParserConfig::default()).ast.unwrap();
+        let analysis = analyze_ast(&ast, &AnalyzerConfig::default()).unwrap();
+        api_surface(&ast, &analysis)
+    }
+
+    fn source_surface(source: &str) -> ApiSurface {
+        let allocator = crate::parser::Allocator::default();
+        let parsed = crate::parser::parse_js_in(source, "test.js", &allocator, &ParserConfig::default());
+        source_api_surface(&parsed.program)
+    }
+
+    #[test]
+    fn test_lists_exports_with_kinds_and_arity() {
+        let api = surface(
+            "import { helper } from './helper';\n\
+             export function add(a, b, c = 0, ...rest) { return a + b + c; }\n\
+             export class Point { constructor(x, y) { this.x = x; } }\n\
+             export const scale = (point, factor) => point;\n\
+             export const VERSION = '1.0';\n\
+             let count = 0; export { count as total, helper };\n\
+             export * from './all'; export * as utils from './utils';",
+        );
+        let summary: Vec<(&str, ExportKind, Option<usize>)> =
+            api.exports.iter().map(|export| (export.name.as_str(), export.kind, export.arity)).collect();
+
+        assert_eq!(
+            summary,
+            vec![
+                ("*", ExportKind::ReExport, None),
+                ("Point", ExportKind::Class, Some(2)),
+                ("VERSION", ExportKind::Const, None),
+                ("add", ExportKind::Function, Some(2)),
+                ("helper", ExportKind::Import, None),
+                ("scale", ExportKind::Function, Some(2)),
+                ("total", ExportKind::Let, None),
+                ("utils", ExportKind::Namespace, None),
+            ]
+        );
+        assert_eq!(api.exports[4].source.as_deref(), Some("./helper"));
+    }
+
+    #[test]
+    fn test_compares_surfaces() {
+        let before = surface("export function f(a, b) {} export const g = 1; export let h = 2;");
+        let after = surface("export function f(a) {} export function g() {} export const i = 3;");
+
+        assert_eq!(
+            compare(&before, &after),
+            vec![
+                ApiChange::ArityChanged { name: "f".to_string(), before: Some(2), after: Some(1) },
+                ApiChange::KindChanged { name: "g".to_string(), before: ExportKind::Const, after: ExportKind::Function },
+                ApiChange::Removed { name: "h".to_string() },
+                ApiChange::Added { name: "i".to_string() },
+            ]
+        );
+        assert!(compare(&before, &before).is_empty());
+    }
+
+    #[test]
+    fn test_source_surface_matches_the_printed_program() {
+        for source in [
+            "import { helper } from './helper';\n\
+             export function add(a, b, c = 0, ...rest) { return a + b + c; }\n\
+             export class Point { constructor(x, y) { this.x = x; } }\n\
+             export const scale = (point, factor) => point, { a, b: [c] } = {};\n\
+             var count = 0; export { count as total, helper, undeclared };\n\
+             export * from './all'; export * as utils from './utils'; export { z as w } from './z';",
+            "export default function main(argv) {} export class Sub extends Base {}",
+            "export default class { constructor(a, b = 1) {} }",
+            "export default async function () {}",
+            "const value = 1; export default value;",
+        ] {
+            assert_eq!(source_surface(source), surface(source), "{}", source);
+        }
+
+        let api = source_surface("export default function main(argv) {}\nexport { main };");
+        let summary: Vec<(&str, ExportKind, Option<usize>)> =
+            api.exports.iter().map(|export| (export.name.as_str(), export.kind, export.arity)).collect();
+        assert_eq!(summary, vec![("default", ExportKind::Function, Some(1)), ("main", ExportKind::Function, Some(1))]);
+        assert_eq!(source_surface("export default [1, 2];").exports[0].kind, ExportKind::Value);
+    }
+}
@@ -0,0 +1,76 @@
+//! # Index Arena
+//!
+//! Vec-backed storage for scopes and symbols. IDs are assigned densely from 0 in
+//! insertion order, so an ID is the item's index: lookups are a bounds check and
+//! iteration walks contiguous memory in ID order.
+
+use serde::{Deserialize, Serialize};
+
+/// Items addressed by dense `u32` IDs
+///
+/// Serializes as a plain list in ID order.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct Arena<T> {
+    items: Vec<T>,
+}
+
+impl<T> Arena<T> {
+    /// Creates an empty arena
+    pub fn new() -> Self {
+        Self { items: Vec::new() }
+    }
+
+    /// ID the next pushed item will receive
+    pub fn next_id(&self) -> u32 {
+        self.items.len() as u32
+    }
+
+    /// Appends `item` and returns its ID
+    pub fn push(&mut self, item: T) -> u32 {
+        let id = self.next_id();
+        self.items.push(item);
+        id
+    }
+
+    /// Gets the item with `id`
+    pub fn get(&self, id: u32) -> Option<&T> {
+        self.items.get(id as usize)
+    }
+
+    /// Gets the item with `id` mutably
+    pub fn get_mut(&mut self, id: u32) -> Option<&mut T> {
+        self.items.get_mut(id as usize)
+    }
+
+    /// Number of items
+    pub fn len(&self) -> usize {
+        self.items.len()
+    }
+
+    /// Returns whether the arena is empty
+    pub fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+
+    /// Items in ID order
+    pub fn values(&self) -> std::slice::Iter<'_, T> {
+        self.items.iter()
+    }
+
+    /// Mutable items in ID order
+    pub fn values_mut(&mut self) -> std::slice::IterMut<'_, T> {
+        self.items.iter_mut()
+    }
+
+    /// `(id, item)` pairs in ID order
+    pub fn iter(&self) -> impl Iterator<Item = (u32, &T)> {
+        self.items.iter().enumerate().map(|(index, item)| (index as u32, item))
+    }
+}
+
+impl<T> Default for Arena<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,430 @@
+//! # Correctness Checks
+//!
+//! Lint-style checks run by `rjs-compiler --check` in place of compiling. Each finds
+//! code that throws, misbehaves or cannot be optimized, and reports it as a
+//! [`Diagnostic`] named after the check:
+//!
+//! | Check                   | Severity | Finds                                                   |
+//! |-------------------------|----------|---------------------------------------------------------|
+//! | `const-assign`          | error    | Assignment to a `const` or an import                    |
+//! | `duplicate-declaration` | error    | A `let`, `const`, `class` or import name declared twice |
+//! |                         | warning  | A `var` or function declared twice                      |
+//! | `undeclared`            | error    | Assignment to an undeclared name in a module            |
+//! |                         | warning  | Use of an undeclared name in a module                   |
+//! | `unreachable`           | warning  | Statements after `return`, `throw`, `break`, `continue` |
+//! | `with`                  | warning  | A `with` statement                                      |
+//!
+//! The checks walk the OXC AST rather than the owned one, which leaves out statements
+//! such as `with` and `throw`. Scopes follow the ones OXC enters; declarations are
+//! collected during the walk and references resolved after it, so hoisted names
+//! resolve wherever they are used. Names that the usual environments define
+//! (`window`, `process`, `setTimeout`, ...) count as declared, and names in the body
+//! of a `with` statement are never reported as undeclared, since they may be
+//! properties of its object.
+
+use std::collections::HashMap;
+
+use oxc_ast::ast::{
+    AssignmentTargetPropertyIdentifier, CatchParameter, Class, FormalParameters, Function,
+    IdentifierReference, ImportDeclaration, ImportDeclarationSpecifier, Program, SimpleAssignmentTarget,
+    Statement, UnaryExpression, VariableDeclaration, VariableDeclarationKind, WithStatement,
+};
+use oxc_ast::syntax_directed_operations::BoundNames;
+use oxc_ast::visit::walk;
+use oxc_ast::Visit;
+use oxc_span::{GetSpan, Span};
+use oxc_syntax::operator::UnaryOperator;
+use oxc_syntax::scope::{ScopeFlags, ScopeId};
+
+use crate::parser::SourceSpan;
+use crate::transformer::diagnostics::{Diagnostic, Severity};
+
+/// Names defined by browsers, workers and Node.js in addition to the language globals
+const ENVIRONMENT_GLOBALS: &[&str] = &[
+    "AbortController", "AggregateError", "Array", "ArrayBuffer", "Atomics", "BigInt", "BigInt64Array",
+    "BigUint64Array", "Blob", "Boolean", "Buffer", "CustomEvent", "DataView", "Date", "Error", "EvalError",
+    "Event", "EventTarget", "FinalizationRegistry", "Float32Array", "Float64Array", "FormData", "Function",
+    "Headers", "Infinity", "Int16Array", "Int32Array", "Int8Array", "Intl", "JSON", "Map", "Math", "NaN",
+    "Number", "Object", "Promise", "Proxy", "RangeError", "ReferenceError", "Reflect", "RegExp", "Request",
+    "Response", "Set", "SharedArrayBuffer", "String", "Symbol", "SyntaxError", "TextDecoder", "TextEncoder",
+    "TypeError", "URIError", "URL", "URLSearchParams", "Uint16Array", "Uint32Array", "Uint8Array",
+    "Uint8ClampedArray", "WeakMap", "WeakRef", "WeakSet", "WebAssembly", "Worker", "XMLHttpRequest", "alert",
+    "arguments", "atob", "btoa", "cancelAnimationFrame", "clearInterval", "clearTimeout", "console", "crypto",
+    "decodeURI", "decodeURIComponent", "document", "encodeURI", "encodeURIComponent", "escape", "eval",
+    "fetch", "global", "globalThis", "history", "isFinite", "isNaN", "localStorage", "location", "navigator",
+    "parseFloat", "parseInt", "performance", "postMessage", "process", "queueMicrotask",
+    "requestAnimationFrame", "self", "sessionStorage", "setInterval", "setTimeout", "structuredClone",
+    "undefined", "unescape", "window",
+];
+
+/// How a name was declared
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum DeclarationKind {
+    Var,
+    Let,
+    Const,
+    Class,
+    Function,
+    Parameter,
+    Import,
+    CatchParameter,
+}
+
+impl DeclarationKind {
+    /// Whether redeclaring the name in the same scope is a syntax error
+    fn is_lexical(self) -> bool {
+        matches!(self, Self::Let | Self::Const | Self::Class | Self::Import)
+    }
+
+    /// Whether assigning to the name throws
+    fn is_constant(self) -> bool {
+        matches!(self, Self::Const | Self::Import)
+    }
+}
+
+/// A name bound in a scope
+#[derive(Debug, Clone)]
+struct Binding {
+    kind: DeclarationKind,
+    span: Span,
+}
+
+#[derive(Debug, Default)]
+struct Scope {
+    parent:   Option<usize>,
+    /// Whether `var` declarations in the scope belong to it rather than to a parent
+    var:      bool,
+    bindings: HashMap<String, Vec<Binding>>,
+}
+
+/// A use of a name, resolved once every declaration is known
+struct Reference {
+    name:  String,
+    span:  Span,
+    scope: usize,
+    write: bool,
+    /// Whether the name is in the body of a `with` statement
+    with:  bool,
+}
+
+/// Runs every check on `program`, returning the diagnostics in source order
+pub fn check_program(program: &Program<'_>) -> Vec<Diagnostic> {
+    let mut checker = Checker {
+        scopes:      Vec::new(),
+        current:     0,
+        pending:     Vec::new(),
+        references:  Vec::new(),
+        with_depth:  0,
+        diagnostics: Vec::new(),
+    };
+    checker.visit_program(program);
+    checker.check_declarations();
+    checker.check_references(program.source_type.is_module());
+
+    let mut diagnostics = checker.diagnostics;
+    diagnostics.sort_by_key(|diagnostic| diagnostic.span.as_ref().map(|span| span.start));
+    diagnostics
+}
+
+struct Checker {
+    scopes:      Vec<Scope>,
+    current:     usize,
+    /// Names bound in the next scope entered, such as a function expression's own name
+    pending:     Vec<(String, Binding)>,
+    references:  Vec<Reference>,
+    /// How many `with` bodies the walk is in
+    with_depth:  usize,
+    diagnostics: Vec<Diagnostic>,
+}
+
+impl Checker {
+    fn declare(&mut self, name: &str, kind: DeclarationKind, span: Span) {
+        let mut scope = self.current;
+        if kind == DeclarationKind::Var {
+            while !self.scopes[scope].var
+                && let Some(parent) = self.scopes[scope].parent
+            {
+                scope = parent;
+            }
+        }
+        self.scopes[scope].bindings.entry(name.to_string()).or_default().push(Binding { kind, span });
+    }
+
+    fn reference(&mut self, identifier: &IdentifierReference<'_>, write: bool) {
+        self.references.push(Reference {
+            name: identifier.name.to_string(),
+            span: identifier.span,
+            scope: self.current,
+            write,
+            with: self.with_depth > 0,
+        });
+    }
+
+    /// Reports names bound more than once in one scope
+    fn check_declarations(&mut self) {
+        for scope in &self.scopes {
+            for (name, bindings) in &scope.bindings {
+                let Some((first, rest)) = bindings.split_first() else {
+                    continue;
+                };
+                // Parameters and `var` may repeat each other, and a catch parameter is
+                // its own scope
+                for binding in rest {
+                    let lexical = first.kind.is_lexical() || binding.kind.is_lexical();
+                    let severity = if lexical {
+                        Severity::Error
+                    } else if matches!(first.kind, DeclarationKind::Parameter | DeclarationKind::CatchParameter) {
+                        continue;
+                    } else {
+                        Severity::Warning
+                    };
+                    let message = if lexical {
+                        format!("'{}' has already been declared", name)
+                    } else {
+                        format!("'{}' is declared more than once", name)
+                    };
+                    self.diagnostics.push(diagnostic(severity, "duplicate-declaration", message, binding.span));
+                }
+            }
+        }
+    }
+
+    /// Reports assignments to constants and, in modules, uses of undeclared names
+    fn check_references(&mut self, is_module: bool) {
+        for reference in &self.references {
+            let mut scope = Some(reference.scope);
+            let mut binding = None;
+            while let Some(id) = scope {
+                if let Some(bindings) = self.scopes[id].bindings.get(&reference.name) {
+                    binding = bindings.first();
+                    break;
+                }
+                scope = self.scopes[id].parent;
+            }
+
+            match binding {
+                Some(binding) if reference.write && binding.kind.is_constant() => {
+                    let kind = if binding.kind == DeclarationKind::Import { "an import" } else { "a constant" };
+                    let message = format!("Assignment to '{}', which is {}", reference.name, kind);
+                    self.diagnostics.push(diagnostic(Severity::Error, "const-assign", message, reference.span));
+                }
+                Some(_) => {}
+                None if reference.with || !is_module || ENVIRONMENT_GLOBALS.binary_search(&reference.name.as_str()).is_ok() => {}
+                None if reference.write => {
+                    let message = format!("Assignment to undeclared '{}' throws in a module", reference.name);
+                    self.diagnostics.push(diagnostic(Severity::Error, "undeclared", message, reference.span));
+                }
+                None => {
+                    let message = format!("'{}' is not declared", reference.name);
+                    self.diagnostics.push(diagnostic(Severity::Warning, "undeclared", message, reference.span));
+                }
+            }
+        }
+    }
+}
+
+impl<'a> Visit<'a> for Checker {
+    fn enter_scope(&mut self, flags: ScopeFlags, _scope_id: &std::cell::Cell<Option<ScopeId>>) {
+        let parent = (!self.scopes.is_empty()).then_some(self.current);
+        self.scopes.push(Scope { parent, var: flags.intersects(ScopeFlags::Var), bindings: HashMap::new() });
+        self.current = self.scopes.len() - 1;
+        for (name, binding) in std::mem::take(&mut self.pending) {
+            self.scopes[self.current].bindings.entry(name).or_default().push(binding);
+        }
+    }
+
+    fn leave_scope(&mut self) {
+        self.current = self.scopes[self.current].parent.unwrap_or(0);
+    }
+
+    fn visit_statements(&mut self, it: &oxc_allocator::Vec<'a, Statement<'a>>) {
+        let terminator = it.iter().position(|statement| {
+            matches!(
+                statement,
+                Statement::ReturnStatement(_)
+                    | Statement::ThrowStatement(_)
+                    | Statement::BreakStatement(_)
+                    | Statement::ContinueStatement(_)
+            )
+        });
+        // Function declarations are hoisted, so they still run
+        let unreachable = terminator.and_then(|terminator| {
+            it.iter()
+                .skip(terminator + 1)
+                .find(|statement| !matches!(statement, Statement::FunctionDeclaration(_) | Statement::EmptyStatement(_)))
+                .map(|statement| (&it[terminator], statement))
+        });
+        if let Some((terminator, statement)) = unreachable {
+            let keyword = match terminator {
+                Statement::ReturnStatement(_) => "return",
+                Statement::ThrowStatement(_) => "throw",
+                Statement::BreakStatement(_) => "break",
+                _ => "continue",
+            };
+            let message = format!("Unreachable code after `{}`", keyword);
+            self.diagnostics.push(diagnostic(Severity::Warning, "unreachable", message, statement.span()));
+        }
+        walk::walk_statements(self, it);
+    }
+
+    fn visit_with_statement(&mut self, it: &WithStatement<'a>) {
+        let message = "`with` makes every name in its body ambiguous and blocks renaming".to_string();
+        self.diagnostics.push(diagnostic(Severity::Warning, "with", message, it.span));
+        self.visit_expression(&it.object);
+        self.with_depth += 1;
+        self.visit_statement(&it.body);
+        self.with_depth -= 1;
+    }
+
+    fn visit_variable_declaration(&mut self, it: &VariableDeclaration<'a>) {
+        let kind = match it.kind {
+            VariableDeclarationKind::Var => DeclarationKind::Var,
+            VariableDeclarationKind::Let => DeclarationKind::Let,
+            _ => DeclarationKind::Const,
+        };
+        it.bound_names(&mut |id| self.declare(&id.name, kind, id.span));
+        walk::walk_variable_declaration(self, it);
+    }
+
+    fn visit_function(&mut self, it: &Function<'a>, flags: ScopeFlags) {
+        if let Some(id) = &it.id {
+            let binding = Binding { kind: DeclarationKind::Function, span: id.span };
+            if it.is_declaration() {
+                // Hoisted like `var` at the top of a function, block-scoped in a block
+                let kind = if self.scopes[self.current].var { DeclarationKind::Var } else { DeclarationKind::Function };
+                self.declare(&id.name, kind, id.span);
+            } else {
+                self.pending.push((id.name.to_string(), binding));
+            }
+        }
+        walk::walk_function(self, it, flags);
+    }
+
+    fn visit_class(&mut self, it: &Class<'a>) {
+        if let Some(id) = &it.id {
+            if it.is_declaration() {
+                self.declare(&id.name, DeclarationKind::Class, id.span);
+            } else {
+                self.pending.push((id.name.to_string(), Binding { kind: DeclarationKind::Class, span: id.span }));
+            }
+        }
+        walk::walk_class(self, it);
+    }
+
+    fn visit_formal_parameters(&mut self, it: &FormalParameters<'a>) {
+        it.bound_names(&mut |id| self.declare(&id.name, DeclarationKind::Parameter, id.span));
+        walk::walk_formal_parameters(self, it);
+    }
+
+    fn visit_catch_parameter(&mut self, it: &CatchParameter<'a>) {
+        it.pattern.bound_names(&mut |id| self.declare(&id.name, DeclarationKind::CatchParameter, id.span));
+        walk::walk_catch_parameter(self, it);
+    }
+
+    fn visit_import_declaration(&mut self, it: &ImportDeclaration<'a>) {
+        for specifier in it.specifiers.iter().flatten() {
+            let local = match specifier {
+                ImportDeclarationSpecifier::ImportSpecifier(specifier) => &specifier.local,
+                ImportDeclarationSpecifier::ImportDefaultSpecifier(specifier) => &specifier.local,
+                ImportDeclarationSpecifier::ImportNamespaceSpecifier(specifier) => &specifier.local,
+            };
+            self.declare(&local.name, DeclarationKind::Import, local.span);
+        }
+        walk::walk_import_declaration(self, it);
+    }
+
+    fn visit_identifier_reference(&mut self, it: &IdentifierReference<'a>) {
+        self.reference(it, false);
+    }
+
+    fn visit_simple_assignment_target(&mut self, it: &SimpleAssignmentTarget<'a>) {
+        match it {
+            SimpleAssignmentTarget::AssignmentTargetIdentifier(identifier) => self.reference(identifier, true),
+            _ => walk::walk_simple_assignment_target(self, it),
+        }
+    }
+
+    fn visit_assignment_target_property_identifier(&mut self, it: &AssignmentTargetPropertyIdentifier<'a>) {
+        self.reference(&it.binding, true);
+        if let Some(init) = &it.init {
+            self.visit_expression(init);
+        }
+    }
+
+    fn visit_unary_expression(&mut self, it: &UnaryExpression<'a>) {
+        // `typeof name` is how code tests for a global that may not exist
+        if it.operator == UnaryOperator::Typeof
+            && matches!(it.argument, oxc_ast::ast::Expression::Identifier(_))
+        {
+            return;
+        }
+        walk::walk_unary_expression(self, it);
+    }
+}
+
+fn diagnostic(severity: Severity, check: &str, message: String, span: Span) -> Diagnostic {
+    let diagnostic = match severity {
+        Severity::Error => Diagnostic::error(message),
+        Severity::Warning => Diagnostic::warning(message),
+        Severity::Note => Diagnostic::note(message),
+    };
+    diagnostic.with_pass(check).with_span(Some(SourceSpan::from(span)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::{parse_js_in, ParserConfig, SourceTypeConfig};
+
+    fn check(source: &str, source_type: SourceTypeConfig) -> Vec<(Severity, String, String)> {
+        let allocator = oxc_allocator::Allocator::default();
+        let config = ParserConfig { source_type, ..ParserConfig::default() };
+        let result = parse_js_in(source, "test.js", &allocator, &config);
+        assert!(result.errors.is_empty(), "{:?}", result.errors);
+        check_program(&result.program)
+            .into_iter()
+            .map(|diagnostic| (diagnostic.severity, diagnostic.pass.unwrap_or_default(), diagnostic.message))
+            .collect()
+    }
+
+    #[test]
+    fn test_environment_globals_are_sorted() {
+        assert!(ENVIRONMENT_GLOBALS.windows(2).all(|pair| pair[0] < pair[1]));
+    }
+
+    #[test]
+    fn test_reports_constant_assignments_and_duplicates() {
+        let source = "import x from 'x';\nconst a = 1;\nlet b;\nlet b;\nvar c; var c;\n\
+                      function f(p) { var p; a = 2; x++; [b] = [1]; return; c = 3; }\n";
+        let found = check(source, SourceTypeConfig::Module);
+        assert_eq!(
+            found,
+            vec![
+                (Severity::Error, "duplicate-declaration".to_string(), "'b' has already been declared".to_string()),
+                (Severity::Warning, "duplicate-declaration".to_string(), "'c' is declared more than once".to_string()),
+                (Severity::Error, "const-assign".to_string(), "Assignment to 'a', which is a constant".to_string()),
+                (Severity::Error, "const-assign".to_string(), "Assignment to 'x', which is an import".to_string()),
+                (Severity::Warning, "unreachable".to_string(), "Unreachable code after `return`".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_undeclared_names_only_in_modules() {
+        let source = "function g() { return h(helper) + console.length + typeof maybe; }\nleaked = 1;\n";
+        let found = check(source, SourceTypeConfig::Module);
+        let messages: Vec<&str> = found.iter().map(|(_, _, message)| message.as_str()).collect();
+        assert_eq!(messages, vec!["'h' is not declared", "'helper' is not declared", "Assignment to undeclared 'leaked' throws in a module"]);
+        assert_eq!(found[2].0, Severity::Error);
+        assert!(check(source, SourceTypeConfig::Script).is_empty());
+
+        let found = check("with (obj) { x; }", SourceTypeConfig::Script);
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].1, "with");
+
+        // Names in a `with` body may be properties of its object, the object may not
+        let found = check("with (obj) { x = y; (() => z)(); }\nw;\n", SourceTypeConfig::Module);
+        let messages: Vec<&str> = found.iter().map(|(_, _, message)| message.as_str()).collect();
+        assert_eq!(messages, vec!["`with` makes every name in its body ambiguous and blocks renaming", "'obj' is not declared", "'w' is not declared"]);
+    }
+}
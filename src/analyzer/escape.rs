@@ -0,0 +1,525 @@
+//! # Escape Analysis
+//!
+//! Finds object literals bound to a variable that never lets the object escape: every
+//! use of the variable is a static property access (`o.p`, `o["p"]`), reading or
+//! writing. Nothing outside those accesses can observe the object's property names,
+//! so they can be renamed, and properties that are never read can be removed.
+//!
+//! Only simple cases are tracked: a `const`, `let` or `var` binding declared once in the
+//! program and initialized with a literal whose properties all have static keys and
+//! no spread. Literals with methods, accessors or function expression values are
+//! skipped, since calling them passes the object as `this`. Bindings in scopes that
+//! use `eval` or `with`, or that can see code the parser dropped from the AST, exported
+//! bindings and kept bindings are skipped as well.
+//!
+//! Class declarations are tracked the same way: a class without a superclass whose name
+//! is only used for static member accesses (`C.create()`) and whose methods never use
+//! `this` other than for a static member access. Its members can then only be reached
+//! through their names, which lets dead code elimination remove unused ones.
+
+use std::collections::{BTreeSet, HashMap};
+
+use serde::{Deserialize, Serialize};
+
+use crate::analyzer::{ReferenceType, SymbolId, SymbolTable};
+use crate::interner::Name;
+use crate::parser::ast_types::{
+    ArrowFunctionBody, AssignmentOperator, ClassElement, Expression, ForInit, Literal, ObjectPatternProperty,
+    ObjectProperty, Pattern, Program, PropertyKey, PropertyKind, Statement, VariableDeclarator,
+};
+
+/// An object literal that never escapes the variable it is bound to
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LocalObject {
+    /// Symbol of the variable
+    pub symbol_id:          SymbolId,
+    /// Name of the variable
+    pub name:               String,
+    /// Properties defined by the literal
+    pub properties:         BTreeSet<String>,
+    /// Properties read through the variable, including ones the literal doesn't define
+    pub read_properties:    BTreeSet<String>,
+    /// Properties assigned through the variable
+    pub written_properties: BTreeSet<String>,
+}
+
+impl LocalObject {
+    /// Properties defined or assigned on the object, which can be safely renamed
+    pub fn own_properties(&self) -> BTreeSet<&str> {
+        self.properties.iter().chain(&self.written_properties).map(String::as_str).collect()
+    }
+
+    /// Properties the literal defines that are never read
+    pub fn unused_properties(&self) -> impl Iterator<Item = &str> {
+        self.properties
+            .iter()
+            .filter(|property| !self.read_properties.contains(*property))
+            .map(String::as_str)
+    }
+}
+
+/// A class declaration that never escapes its name
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LocalClass {
+    /// Symbol of the class
+    pub symbol_id: SymbolId,
+    /// Name of the class
+    pub name:      String,
+}
+
+/// Accesses found for one candidate binding
+#[derive(Default)]
+struct Usage {
+    properties:         BTreeSet<String>,
+    read_properties:    BTreeSet<String>,
+    written_properties: BTreeSet<String>,
+    declarations:       usize,
+    accesses:           usize,
+    /// Whether the name is declared by a class declaration
+    class:              bool,
+    /// Whether a declaration of the name is not a trackable object literal or class
+    untrackable:        bool,
+}
+
+/// Finds the object literals in `ast` that don't escape, ordered by symbol
+pub fn find_local_objects(ast: &Program, symbol_table: &SymbolTable) -> Vec<LocalObject> {
+    non_escaping_bindings(ast, symbol_table)
+        .into_iter()
+        .filter(|(_, _, usage)| !usage.class)
+        .map(|(symbol_id, name, usage)| LocalObject {
+            symbol_id,
+            name,
+            properties: usage.properties,
+            read_properties: usage.read_properties,
+            written_properties: usage.written_properties,
+        })
+        .collect()
+}
+
+/// Finds the class declarations in `ast` that don't escape, ordered by symbol
+pub fn find_local_classes(ast: &Program, symbol_table: &SymbolTable) -> Vec<LocalClass> {
+    non_escaping_bindings(ast, symbol_table)
+        .into_iter()
+        .filter(|(_, _, usage)| usage.class)
+        .map(|(symbol_id, name, _)| LocalClass { symbol_id, name })
+        .collect()
+}
+
+/// Trackable bindings whose every reference is a static member access, ordered by symbol
+fn non_escaping_bindings(ast: &Program, symbol_table: &SymbolTable) -> Vec<(SymbolId, String, Usage)> {
+    let mut visitor = EscapeVisitor { usages: HashMap::new(), this_owner: None };
+    visitor.statements(&ast.body);
+
+    let mut symbols: HashMap<Name, Vec<SymbolId>> = HashMap::new();
+    for symbol in symbol_table.symbols.values() {
+        symbols.entry(symbol.name).or_default().push(symbol.id);
+    }
+
+    let mut bindings: Vec<(SymbolId, String, Usage)> = visitor
+        .usages
+        .into_iter()
+        .filter(|(_, usage)| usage.declarations == 1 && !usage.untrackable)
+        .filter_map(|(name, usage)| {
+            let &[symbol_id] = symbols.get(&symbol_table.names.get(&name)?)?.as_slice() else {
+                return None;
+            };
+            let symbol = symbol_table.symbols.get(symbol_id)?;
+            // Identifiers used as property names are recorded as references of their own
+            let references = symbol
+                .references
+                .iter()
+                .filter(|reference| !matches!(reference.reference_type, ReferenceType::PropertyAccess))
+                .count();
+            // Any reference that isn't a static property access lets the binding escape
+            (symbol.is_renamable && !symbol.is_exported && references == usage.accesses)
+                .then_some((symbol_id, name, usage))
+        })
+        .collect();
+    bindings.sort_by_key(|(symbol_id, _, _)| *symbol_id);
+    bindings
+}
+
+/// Static name of a property key, if the literal can be tracked with it
+pub fn static_key_name(key: &PropertyKey) -> Option<&str> {
+    match key {
+        PropertyKey::Identifier(id) => Some(&id.name),
+        PropertyKey::Literal(Literal::String(string)) if !string.lone_surrogates => Some(&string.value),
+        _ => None,
+    }
+}
+
+/// Static name of a member access, for `o.p` and `o["p"]`
+pub fn static_property_name(property: &Expression, computed: bool) -> Option<&str> {
+    match property {
+        Expression::Identifier(id) if !computed => Some(&id.name),
+        Expression::Literal(Literal::String(string)) if computed && !string.lone_surrogates => Some(&string.value),
+        _ => None,
+    }
+}
+
+/// Names of the properties of a literal that can be tracked, or `None` if it can't be
+fn literal_properties(properties: &[ObjectProperty]) -> Option<BTreeSet<String>> {
+    properties
+        .iter()
+        .map(|property| match property {
+            ObjectProperty::Property { key, value, kind: PropertyKind::Init, method: false, computed: false, .. }
+                if !matches!(value, Expression::FunctionExpression(_)) =>
+            {
+                // `__proto__: value` sets the prototype rather than defining a property
+                static_key_name(key).filter(|name| *name != "__proto__").map(str::to_string)
+            }
+            _ => None,
+        })
+        .collect()
+}
+
+/// Collects candidate declarations and the static property accesses of every name
+struct EscapeVisitor {
+    usages:     HashMap<String, Usage>,
+    /// Class whose instance or constructor `this` refers to at the current position
+    this_owner: Option<String>,
+}
+
+impl EscapeVisitor {
+    fn statements(&mut self, statements: &[Statement]) {
+        for statement in statements {
+            self.statement(statement);
+        }
+    }
+
+    fn statement(&mut self, statement: &Statement) {
+        match statement {
+            Statement::VariableDeclaration { declarations, .. } => self.declarations(declarations),
+            Statement::FunctionDeclaration { params, body, .. } => {
+                let owner = self.this_owner.take();
+                self.patterns(params);
+                self.statements(&body.body);
+                self.this_owner = owner;
+            }
+            Statement::ClassDeclaration { id, super_class, body, decorators, .. } => {
+                let decorated = !decorators.is_empty() || body.body.iter().any(|element| !element.decorators().is_empty());
+                if let Some(id) = id {
+                    let usage = self.usages.entry(id.name.clone()).or_default();
+                    usage.declarations += 1;
+                    usage.class = true;
+                    // Inherited members can't be tracked, and neither can `super` calls.
+                    // Decorators are handed the class or its members
+                    usage.untrackable |= super_class.is_some() || decorated;
+                }
+                for decorator in decorators {
+                    self.expression(decorator);
+                }
+                if let Some(super_class) = super_class {
+                    self.expression(super_class);
+                }
+                let owner = std::mem::replace(&mut self.this_owner, id.as_ref().map(|id| id.name.clone()));
+                self.class_elements(&body.body);
+                self.this_owner = owner;
+            }
+            Statement::ExpressionStatement { expression, .. } => self.expression(expression),
+            Statement::BlockStatement { body, .. } => self.statements(body),
+            Statement::ReturnStatement { argument, .. } => {
+                if let Some(argument) = argument {
+                    self.expression(argument);
+                }
+            }
+            Statement::IfStatement { test, consequent, alternate, .. } => {
+                self.expression(test);
+                self.statement(consequent);
+                if let Some(alternate) = alternate {
+                    self.statement(alternate);
+                }
+            }
+            Statement::WhileStatement { test, body, .. } => {
+                self.expression(test);
+                self.statement(body);
+            }
+            Statement::ForStatement { init, test, update, body, .. } => {
+                if let Some(init) = init {
+                    self.for_init(init);
+                }
+                if let Some(test) = test {
+                    self.expression(test);
+                }
+                if let Some(update) = update {
+                    self.expression(update);
+                }
+                self.statement(body);
+            }
+            Statement::ForOfStatement { left, right, body, .. } => {
+                self.for_init(left);
+                self.expression(right);
+                self.statement(body);
+            }
+            Statement::ExportNamedDeclaration { declaration: Some(declaration), .. }
+            | Statement::ExportDefaultDeclaration { declaration, .. } => self.statement(declaration),
+            Statement::ExportNamedDeclaration { declaration: None, .. }
+            | Statement::ImportDeclaration { .. }
+            | Statement::ExportAllDeclaration { .. }
+            | Statement::EmptyStatement
+            | Statement::DebuggerStatement => {}
+        }
+    }
+
+    fn for_init(&mut self, init: &ForInit) {
+        match init {
+            ForInit::VariableDeclaration { declarations, .. } => self.declarations(declarations),
+            ForInit::Expression(expression) => self.expression(expression),
+        }
+    }
+
+    fn declarations(&mut self, declarations: &[VariableDeclarator]) {
+        for declarator in declarations {
+            if let Pattern::Identifier(id) = &declarator.id {
+                let usage = self.usages.entry(id.name.clone()).or_default();
+                usage.declarations += 1;
+                match &declarator.init {
+                    Some(Expression::ObjectExpression { properties }) => match literal_properties(properties) {
+                        Some(properties) => usage.properties = properties,
+                        None => usage.untrackable = true,
+                    },
+                    _ => usage.untrackable = true,
+                }
+            } else {
+                self.pattern(&declarator.id);
+            }
+            if let Some(init) = &declarator.init {
+                self.expression(init);
+            }
+        }
+    }
+
+    fn class_elements(&mut self, elements: &[ClassElement]) {
+        for element in elements {
+            for decorator in element.decorators() {
+                self.expression(decorator);
+            }
+            match element {
+                ClassElement::PropertyDefinition { value: Some(value), .. } => self.expression(value),
+                ClassElement::PropertyDefinition { value: None, .. } => {}
+                ClassElement::MethodDefinition { value, .. } => {
+                    self.patterns(&value.params);
+                    self.statements(&value.body.body);
+                }
+            }
+        }
+    }
+
+    fn patterns(&mut self, patterns: &[Pattern]) {
+        for pattern in patterns {
+            self.pattern(pattern);
+        }
+    }
+
+    /// Visits the default values in a binding pattern
+    fn pattern(&mut self, pattern: &Pattern) {
+        match pattern {
+            Pattern::Identifier(_) => {}
+            Pattern::ArrayPattern { elements } => elements.iter().flatten().for_each(|element| self.pattern(element)),
+            Pattern::ObjectPattern { properties } => {
+                for property in properties {
+                    match property {
+                        ObjectPatternProperty::Property { value, .. } => self.pattern(value),
+                        ObjectPatternProperty::RestElement { argument } => self.pattern(argument),
+                    }
+                }
+            }
+            Pattern::AssignmentPattern { left, right } => {
+                self.pattern(left);
+                self.expression(right);
+            }
+            Pattern::RestElement { argument } => self.pattern(argument),
+        }
+    }
+
+    /// Records `object.property` if it is a static access through a name or `this`
+    fn member(&mut self, object: &Expression, property: &Expression, computed: bool, read: bool, write: bool) -> bool {
+        let Some(name) = static_property_name(property, computed) else {
+            return false;
+        };
+        let object = match object {
+            Expression::Identifier(object) => object,
+            Expression::ThisExpression => return true,
+            _ => return false,
+        };
+        let usage = self.usages.entry(object.name.clone()).or_default();
+        usage.accesses += 1;
+        if read {
+            usage.read_properties.insert(name.to_string());
+        }
+        if write {
+            usage.written_properties.insert(name.to_string());
+        }
+        true
+    }
+
+    fn expression(&mut self, expression: &Expression) {
+        match expression {
+            Expression::Identifier(_) | Expression::Literal(_) | Expression::MetaProperty { .. } => {}
+            Expression::ThisExpression => {
+                // The instance or class itself escapes, so any of its members may be used
+                if let Some(owner) = &self.this_owner {
+                    self.usages.entry(owner.clone()).or_default().untrackable = true;
+                }
+            }
+            Expression::MemberExpression { object, property, computed, .. } => {
+                if !self.member(object, property, *computed, true, false) {
+                    self.expression(object);
+                }
+                if *computed {
+                    self.expression(property);
+                }
+            }
+            Expression::AssignmentExpression { left, right, operator } => {
+                match left.as_ref() {
+                    Expression::MemberExpression { object, property, computed, .. } => {
+                        // Compound assignments read the property before writing it
+                        let reads = !matches!(operator, AssignmentOperator::Assign);
+                        if !self.member(object, property, *computed, reads, true) {
+                            self.expression(object);
+                        }
+                        if *computed {
+                            self.expression(property);
+                        }
+                    }
+                    _ => self.expression(left),
+                }
+                self.expression(right);
+            }
+            Expression::BinaryExpression { left, right, .. } => {
+                self.expression(left);
+                self.expression(right);
+            }
+            Expression::UnaryExpression { argument, .. }
+            | Expression::UpdateExpression { argument, .. }
+            | Expression::AwaitExpression { argument }
+            | Expression::ChainExpression { expression: argument }
+            | Expression::SpreadElement { argument } => self.expression(argument),
+            Expression::YieldExpression { argument, .. } => {
+                if let Some(argument) = argument {
+                    self.expression(argument);
+                }
+            }
+            Expression::CallExpression { callee, arguments, .. } | Expression::NewExpression { callee, arguments } => {
+                self.expression(callee);
+                arguments.iter().for_each(|argument| self.expression(argument));
+            }
+            Expression::ImportExpression { source, options } => {
+                self.expression(source);
+                if let Some(options) = options {
+                    self.expression(options);
+                }
+            }
+            Expression::FunctionExpression(function) => {
+                let owner = self.this_owner.take();
+                self.patterns(&function.params);
+                self.statements(&function.body.body);
+                self.this_owner = owner;
+            }
+            Expression::ArrowFunctionExpression { params, body, .. } => {
+                self.patterns(params);
+                match body {
+                    ArrowFunctionBody::BlockStatement(block) => self.statements(&block.body),
+                    ArrowFunctionBody::Expression(expression) => self.expression(expression),
+                }
+            }
+            Expression::ObjectExpression { properties } => {
+                for property in properties {
+                    match property {
+                        ObjectProperty::Property { value, .. } => self.expression(value),
+                        ObjectProperty::SpreadElement { argument } => self.expression(argument),
+                    }
+                }
+            }
+            Expression::ArrayExpression { elements } => {
+                elements.iter().flatten().for_each(|element| self.expression(element));
+            }
+            Expression::TemplateLiteral { expressions, .. } => {
+                expressions.iter().for_each(|expression| self.expression(expression));
+            }
+            Expression::TaggedTemplateExpression { tag, expressions, .. } => {
+                self.expression(tag);
+                expressions.iter().for_each(|expression| self.expression(expression));
+            }
+            Expression::ConditionalExpression { test, consequent, alternate } => {
+                self.expression(test);
+                self.expression(consequent);
+                self.expression(alternate);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::analyzer::{analyze_ast, AnalyzerConfig};
+    use crate::parser::{parse_js, ParserConfig};
+
+    fn local_objects(source: &str) -> Vec<LocalObject> {
+        let ast = parse_js(source, "test.js", &ParserConfig::default()).ast.unwrap();
+        analyze_ast(&ast, &AnalyzerConfig::default()).unwrap().local_objects
+    }
+
+    fn names(objects: &[LocalObject]) -> Vec<&str> {
+        objects.iter().map(|object| object.name.as_str()).collect()
+    }
+
+    #[test]
+    fn test_finds_objects_only_accessed_statically() {
+        let objects = local_objects(
+            "const config = { width: 10, 'max-height': 20, unused: 1 };
+             config.depth = config.width * 2;
+             function area() { return config.width * config['max-height'] + config.depth; }",
+        );
+
+        assert_eq!(names(&objects), vec!["config"]);
+        let config = &objects[0];
+        assert_eq!(config.own_properties(), BTreeSet::from(["depth", "max-height", "unused", "width"]));
+        assert_eq!(config.unused_properties().collect::<Vec<_>>(), vec!["unused"]);
+    }
+
+    #[test]
+    fn test_objects_escape_through_other_uses() {
+        let objects = local_objects(
+            "const passed = { a: 1 }; f(passed);
+             const returned = { a: 1 }; function g() { return returned; }
+             const dynamic = { a: 1 }; dynamic[key];
+             let reassigned = { a: 1 }; reassigned = other;
+             const spread = { a: 1 }; const copy = { ...spread };
+             const method = { m() { return this.a; }, a: 1 }; method.m();
+             const kept = { a: 1 }; kept.a;",
+        );
+
+        assert_eq!(names(&objects), vec!["kept"]);
+    }
+
+    #[test]
+    fn test_finds_classes_only_accessed_statically() {
+        let ast = parse_js(
+            "class Counter { static create() { return this.start; } next() { return this.value + 1; } }
+             Counter.create();
+             class Passed {} register(Passed);
+             class Leaked { keep() { register(this); } }
+             class Derived extends Base {}
+             class Callback { bind() { return function () { return this; }; } }
+             export class Exported {}",
+            "test.js",
+            &ParserConfig::default(),
+        )
+        .ast
+        .unwrap();
+        let analysis = analyze_ast(&ast, &AnalyzerConfig::default()).unwrap();
+        let classes: Vec<&str> = analysis.local_classes.iter().map(|class| class.name.as_str()).collect();
+
+        assert_eq!(classes, vec!["Counter", "Callback"]);
+        assert!(analysis.local_objects.is_empty());
+    }
+
+    #[test]
+    fn test_skips_shadowed_names() {
+        let objects = local_objects("const o = { a: 1 }; o.a; function f(o) { return o.a; }");
+
+        assert!(objects.is_empty());
+    }
+}
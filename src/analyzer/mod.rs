@@ -12,25 +12,43 @@
 //! - **Reference Tracking**: Maps variable uses to declarations with read/write classification
 //! - **Capture Detection**: Identifies closure captures for safe minification
 //! - **Safety Classification**: Flags scopes and symbols that cannot be safely renamed
+//! - **Module Records**: Imports, exports and re-exports, for following bindings across modules
+//! - **Purity**: Classifies functions and expressions as pure, read-only or effectful
+//! - **Escape Analysis**: Finds object literals whose property names are never observed
+//! - **API Surface**: Lists the exports of a module with their kinds and arities
 //!
 //! ## Usage
 //!
 //! ```rust
-//! use crate::analyzer::{analyze_ast, AnalyzerConfig};
-//! use crate::parser::ast_types::Program;
+//! use rjs_compiler::analyzer::{analyze_ast, AnalyzerConfig};
+//! use rjs_compiler::parser::{parse_js, ParserConfig};
 //!
-//! let ast = parse_javascript_code(source)?;
+//! let ast = parse_js("let x = 1;", "example.js", &ParserConfig::default()).ast.unwrap();
 //! let config = AnalyzerConfig::default();
 //! let analysis_result = analyze_ast(&ast, &config)?;
+//! # Ok::<(), rjs_compiler::analyzer::AnalysisError>(())
 //! ```
 
-use std::collections::HashMap;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
 
 use serde::{Deserialize, Serialize};
 use thiserror::Error;
 
-use crate::parser::ast_types::Program;
-
+use crate::analyzer::arena::Arena;
+use crate::analyzer::escape::{LocalClass, LocalObject};
+use crate::analyzer::module_record::ModuleRecord;
+use crate::analyzer::purity::{Purity, PurityTable};
+use crate::cancel::{CancellationToken, Cancelled};
+use crate::interner::{Interner, Name};
+use crate::parser::ast_types::{Expression, Program};
+use crate::parser::SourceSpan;
+
+pub mod api_surface;
+pub mod arena;
+pub mod checks;
+pub mod escape;
+pub mod module_record;
+pub mod purity;
 pub mod scope_builder;
 pub mod semantic_analysis;
 
@@ -40,14 +58,27 @@ mod tests;
 /// Configuration for the analyzer component
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AnalyzerConfig {
-    /// Enable verbose analysis output
-    pub verbose:                bool,
     /// Preserve export symbols (don't rename)
     pub preserve_exports:       bool,
     /// Enable aggressive optimization (may break some edge cases)
     pub aggressive_optimization: bool,
-    /// Enable strict mode analysis
+    /// Analyze all code as strict mode code; without it, modules, class bodies and code
+    /// under a `"use strict"` directive are strict and the rest is sloppy
     pub strict_mode:            bool,
+    /// Names declared with a `/* @__KEEP__ */` annotation (never removed or renamed)
+    #[serde(default)]
+    pub kept_names:             Vec<String>,
+    /// Spans of code the parser dropped from the AST; the functions around them may use
+    /// bindings in ways the AST doesn't show, so their scopes are treated as unsafe
+    #[serde(default)]
+    pub dropped_spans:          Vec<SourceSpan>,
+    /// Binding names the parser interned ([`ParseResult::names`](crate::parser::ParseResult::names));
+    /// the symbol table extends this interner, so names keep their ids across the phases
+    #[serde(default)]
+    pub names:                  Interner,
+    /// Stops the analysis between its phases once cancelled
+    #[serde(skip)]
+    pub cancellation:           CancellationToken,
 }
 
 /// Unique identifier for scopes within the analysis
@@ -74,11 +105,27 @@ pub enum AnalysisError {
     #[error("Temporal dead zone violation: '{identifier}' used before declaration")]
     TemporalDeadZoneViolation { identifier: String },
 
+    #[error("Identifier '{identifier}' has already been declared{}: {reason}", at_offset(.span))]
+    Redeclaration {
+        identifier: String,
+        /// Why the declarations conflict, naming both kinds
+        reason:     String,
+        /// Span of the second declaration
+        span:       Option<SourceSpan>,
+    },
+
     #[error("Unsafe scope detected: {reason}")]
     UnsafeScope { reason: String },
 
     #[error("Internal analysis error: {message}")]
     InternalError { message: String },
+
+    #[error("{0}")]
+    Cancelled(#[from] Cancelled),
+}
+
+fn at_offset(span: &Option<SourceSpan>) -> String {
+    span.as_ref().map(|span| format!(" at offset {}", span.start)).unwrap_or_default()
 }
 
 /// Result type for analysis operations
@@ -95,39 +142,65 @@ pub struct SemanticAnalysis {
     pub semantic_flags: SemanticFlags,
     /// Analysis metadata and statistics
     pub metadata:       AnalysisMetadata,
+    /// Imports, exports and namespace usage of the module
+    #[serde(default)]
+    pub module_record:  ModuleRecord,
+    /// Side-effect classification of the program's functions
+    #[serde(default)]
+    pub purity:         PurityTable,
+    /// Object literals whose properties are only accessed statically through their variable
+    #[serde(default)]
+    pub local_objects:  Vec<LocalObject>,
+    /// Class declarations whose name is only used for static member accesses
+    #[serde(default)]
+    pub local_classes:  Vec<LocalClass>,
+}
+
+impl SemanticAnalysis {
+    /// Classifies evaluating a top-level `expression` as pure, read-only or effectful
+    pub fn expression_purity(&self, expression: &Expression) -> Purity {
+        self.purity.expression_purity(expression, &self.symbol_table)
+    }
 }
 
 /// Symbol table tracking all identifiers and their bindings
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SymbolTable {
-    /// All symbols indexed by their unique ID
-    pub symbols:     HashMap<SymbolId, Symbol>,
-    /// Mapping from identifier names to symbol IDs in each scope
-    pub scope_bindings: HashMap<ScopeId, HashMap<String, SymbolId>>,
-    /// Next available symbol ID
-    pub next_symbol_id: SymbolId,
+    /// All symbols, indexed by their unique ID
+    pub symbols:     Arena<Symbol>,
+    /// Mapping from interned identifier names to symbol IDs, indexed by scope ID
+    #[serde(serialize_with = "serialize_sorted_bindings")]
+    pub scope_bindings: Vec<HashMap<Name, SymbolId>>,
+    /// Interner holding the names of all symbols, and the other names the parser interned
+    #[serde(default)]
+    pub names:          Interner,
+    /// Names some scope declares
+    #[serde(default)]
+    pub declared:       BTreeSet<Name>,
 }
 
 /// Hierarchical scope tree structure
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ScopeTree {
-    /// All scopes indexed by their unique ID
-    pub scopes:        HashMap<ScopeId, Scope>,
+    /// All scopes, indexed by their unique ID
+    pub scopes:        Arena<Scope>,
     /// Root scope ID (typically global scope)
     pub root_scope_id: ScopeId,
-    /// Next available scope ID
-    pub next_scope_id: ScopeId,
 }
 
 /// Semantic flags for optimization safety
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SemanticFlags {
     /// Scopes that are unsafe for renaming due to eval, with, etc.
-    pub unsafe_scopes:     HashMap<ScopeId, UnsafeReason>,
+    pub unsafe_scopes:     BTreeMap<ScopeId, UnsafeReason>,
     /// Symbols that cannot be safely renamed
-    pub unsafe_symbols:    HashMap<SymbolId, UnsafeReason>,
+    pub unsafe_symbols:    BTreeMap<SymbolId, UnsafeReason>,
     /// Global scope references that must be preserved
     pub global_references: Vec<SymbolId>,
+    /// Properties assigned on the global object (`window.foo = …`, `globalThis["bar"] = …`);
+    /// these are intentional exports that DCE must keep and property minification must not rename
+    #[serde(default)]
+    pub global_exports:    BTreeSet<String>,
 }
 
 /// Analysis metadata and statistics
@@ -160,6 +233,21 @@ pub struct Scope {
     pub bindings:    Vec<SymbolId>,
     /// Whether this scope is safe for aggressive optimization
     pub is_safe:     bool,
+    /// Whether this function's `arguments` object is referenced, directly or from a
+    /// nested arrow function; such a function must keep its parameter list as written
+    /// and must not be inlined or turned into an arrow function
+    #[serde(default)]
+    pub uses_arguments: bool,
+    /// Whether this class scope belongs to a class that has decorators on itself or a
+    /// member; decorators receive the class and its members at runtime, so the class
+    /// must not be inlined and its name and member names must stay as written
+    #[serde(default)]
+    pub is_decorated: bool,
+    /// Whether the parser dropped code it can't represent from this function (or, for
+    /// the root scope, from outside any function); the missing code may use any binding
+    /// it can see, so none of them can be renamed or have properties removed
+    #[serde(default)]
+    pub has_dropped_code: bool,
 }
 
 /// Types of scopes in JavaScript
@@ -186,8 +274,8 @@ pub enum ScopeType {
 pub struct Symbol {
     /// Unique symbol identifier
     pub id:          SymbolId,
-    /// Original identifier name
-    pub name:        String,
+    /// Original identifier name, interned in [`SymbolTable::names`]
+    pub name:        Name,
     /// Type of symbol
     pub symbol_type: SymbolType,
     /// Scope where this symbol is declared
@@ -200,6 +288,28 @@ pub struct Symbol {
     pub is_exported: bool,
     /// Whether this symbol can be safely renamed
     pub is_renamable: bool,
+    /// Whether this symbol is annotated with `@__KEEP__` and must not be removed
+    #[serde(default)]
+    pub is_kept:     bool,
+    /// How functions nested in the declaring scope use the symbol
+    #[serde(default)]
+    pub capture:     CaptureKind,
+}
+
+/// How closures use a symbol of an enclosing function or module
+///
+/// Unlike [`Symbol::is_captured`], which any reference from another scope sets, only
+/// references from inside a nested function or class body count.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum CaptureKind {
+    /// No closure refers to the symbol
+    #[default]
+    None,
+    /// Closures refer to the symbol, and nothing assigns it after its declaration, so
+    /// every closure sees the value it was declared with
+    ReadOnly,
+    /// Closures refer to the symbol, and a closure or the declaring scope assigns it
+    Mutated,
 }
 
 /// Types of symbols in JavaScript
@@ -280,6 +390,13 @@ pub enum UnsafeReason {
     IndirectAccess,
     /// External module dependency
     ExternalDependency,
+    /// References the `arguments` object, which exposes the parameters by position
+    ArgumentsUsage,
+    /// A class or one of its members has decorators, which receive the class and its
+    /// members at runtime and may look them up by name
+    Decorators,
+    /// Contains code the parser dropped from the AST, which may use any visible binding
+    DroppedCode,
     /// Unknown safety (conservative approach)
     Unknown,
 }
@@ -287,10 +404,13 @@ pub enum UnsafeReason {
 impl Default for AnalyzerConfig {
     fn default() -> Self {
         Self {
-            verbose:                false,
             preserve_exports:       true,
             aggressive_optimization: false,
-            strict_mode:            true,
+            strict_mode:            false,
+            kept_names:             Vec::new(),
+            dropped_spans:          Vec::new(),
+            names:                  Interner::new(),
+            cancellation:           CancellationToken::new(),
         }
     }
 }
@@ -299,24 +419,66 @@ impl SymbolTable {
     /// Creates a new empty symbol table
     pub fn new() -> Self {
         Self {
-            symbols:        HashMap::new(),
-            scope_bindings: HashMap::new(),
-            next_symbol_id: 0,
+            symbols:        Arena::new(),
+            scope_bindings: Vec::new(),
+            names:          Interner::new(),
+            declared:       BTreeSet::new(),
         }
     }
 
-    /// Generates the next unique symbol ID
-    pub fn next_id(&mut self) -> SymbolId {
-        let id = self.next_symbol_id;
-        self.next_symbol_id += 1;
+    /// Creates an empty symbol table that interns names in `names`
+    pub fn with_names(names: Interner) -> Self {
+        Self { names, ..Self::new() }
+    }
+
+    /// Adds `symbol`, whose `id` must be `symbols.next_id()`, and binds its name in its scope
+    pub fn declare(&mut self, symbol: Symbol) -> SymbolId {
+        let name = symbol.name;
+        self.declared.insert(name);
+        let scope = symbol.scope_id as usize;
+        if self.scope_bindings.len() <= scope {
+            self.scope_bindings.resize_with(scope + 1, HashMap::new);
+        }
+        let id = self.symbols.push(symbol);
+        self.scope_bindings[scope].insert(name, id);
         id
     }
+
+    /// Finds the symbol bound to `name` directly in `scope_id`
+    pub fn lookup(&self, scope_id: ScopeId, name: &str) -> Option<SymbolId> {
+        let name = self.names.get(name)?;
+        self.scope_bindings.get(scope_id as usize)?.get(&name).copied()
+    }
+
+    /// Returns whether any scope declares `name`
+    pub fn is_declared(&self, name: &str) -> bool {
+        self.names.get(name).is_some_and(|name| self.declared.contains(&name))
+    }
+
+    /// Name of `symbol`
+    pub fn name(&self, symbol: &Symbol) -> &str {
+        self.names.resolve(symbol.name)
+    }
+}
+
+/// Serializes the bindings of each scope ordered by name, so the JSON is the same on every run
+fn serialize_sorted_bindings<S: serde::Serializer>(
+    scope_bindings: &[HashMap<Name, SymbolId>],
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    serializer.collect_seq(scope_bindings.iter().map(|bindings| bindings.iter().collect::<BTreeMap<_, _>>()))
+}
+
+impl Default for SymbolTable {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl ScopeTree {
     /// Creates a new scope tree with a root scope
     pub fn new(root_scope_type: ScopeType) -> Self {
-        let mut scopes = HashMap::new();
+        let mut scopes = Arena::new();
         let root_scope = Scope {
             id:          0,
             scope_type:  root_scope_type,
@@ -324,31 +486,59 @@ impl ScopeTree {
             children:    Vec::new(),
             bindings:    Vec::new(),
             is_safe:     true,
+            uses_arguments: false,
+            is_decorated: false,
+            has_dropped_code: false,
         };
-        scopes.insert(0, root_scope);
+        let root_scope_id = scopes.push(root_scope);
 
         Self {
             scopes,
-            root_scope_id: 0,
-            next_scope_id: 1,
+            root_scope_id,
         }
     }
 
-    /// Generates the next unique scope ID
-    pub fn next_id(&mut self) -> ScopeId {
-        let id = self.next_scope_id;
-        self.next_scope_id += 1;
-        id
-    }
-
     /// Gets a scope by ID
     pub fn get_scope(&self, scope_id: ScopeId) -> Option<&Scope> {
-        self.scopes.get(&scope_id)
+        self.scopes.get(scope_id)
     }
 
     /// Gets a mutable scope by ID
     pub fn get_scope_mut(&mut self, scope_id: ScopeId) -> Option<&mut Scope> {
-        self.scopes.get_mut(&scope_id)
+        self.scopes.get_mut(scope_id)
+    }
+
+    /// Whether a reference from `scope_id` to a symbol of `declaring_scope_id` reaches
+    /// across a function boundary, the way a closure refers to its enclosing scope
+    pub fn crosses_function(&self, scope_id: ScopeId, declaring_scope_id: ScopeId) -> bool {
+        let mut scope = self.get_scope(scope_id);
+        while let Some(current) = scope.filter(|current| current.id != declaring_scope_id) {
+            if matches!(current.scope_type, ScopeType::Function | ScopeType::Class) {
+                return true;
+            }
+            scope = current.parent_id.and_then(|parent_id| self.get_scope(parent_id));
+        }
+        false
+    }
+
+    /// Resolves `name` from `scope_id` outwards through the parent scopes
+    pub fn resolve(&self, symbol_table: &SymbolTable, scope_id: ScopeId, name: &str) -> Option<SymbolId> {
+        // Names that were never declared cannot resolve in any scope
+        let name = symbol_table.names.get(name)?;
+        let mut scope = self.get_scope(scope_id);
+
+        while let Some(current) = scope {
+            if let Some(&symbol_id) = symbol_table
+                .scope_bindings
+                .get(current.id as usize)
+                .and_then(|bindings| bindings.get(&name))
+            {
+                return Some(symbol_id);
+            }
+            scope = current.parent_id.and_then(|parent_id| self.get_scope(parent_id));
+        }
+
+        None
     }
 }
 
@@ -367,40 +557,46 @@ impl ScopeTree {
 /// # Examples
 ///
 /// ```rust
-/// use crate::analyzer::{analyze_ast, AnalyzerConfig};
+/// use rjs_compiler::analyzer::{analyze_ast, AnalyzerConfig};
+/// # use rjs_compiler::parser::{parse_js, ParserConfig};
+/// # let ast = parse_js("function f(a) { return a; }", "example.js", &ParserConfig::default()).ast.unwrap();
 ///
 /// let config = AnalyzerConfig::default();
 /// let analysis = analyze_ast(&ast, &config)?;
 /// println!("Found {} symbols in {} scopes", 
 ///          analysis.metadata.symbol_count,
 ///          analysis.metadata.scope_count);
+/// # Ok::<(), rjs_compiler::analyzer::AnalysisError>(())
 /// ```
 pub fn analyze_ast(ast: &Program, config: &AnalyzerConfig) -> AnalysisResult<SemanticAnalysis> {
-    let start_time = std::time::Instant::now();
+    let start_time = crate::Instant::now();
 
-    if config.verbose {
-        println!("Starting semantic analysis...");
-    }
+    tracing::debug!("Starting semantic analysis...");
 
     // Initialize analysis components
-    let mut symbol_table = SymbolTable::new();
+    let mut symbol_table = SymbolTable::with_names(config.names.clone());
     let mut scope_tree = ScopeTree::new(ScopeType::Global);
     let mut semantic_flags = SemanticFlags {
-        unsafe_scopes:     HashMap::new(),
-        unsafe_symbols:    HashMap::new(),
+        unsafe_scopes:     BTreeMap::new(),
+        unsafe_symbols:    BTreeMap::new(),
         global_references: Vec::new(),
+        global_exports:    BTreeSet::new(),
     };
+    let mut module_record = ModuleRecord::default();
 
     // Perform scope analysis
+    config.cancellation.check()?;
     scope_builder::analyze_scopes(
         ast,
         &mut scope_tree,
         &mut symbol_table,
         &mut semantic_flags,
+        &mut module_record,
         config,
     )?;
 
     // Perform semantic analysis
+    config.cancellation.check()?;
     semantic_analysis::analyze_semantics(
         ast,
         &mut scope_tree,
@@ -409,11 +605,16 @@ pub fn analyze_ast(ast: &Program, config: &AnalyzerConfig) -> AnalysisResult<Sem
         config,
     )?;
 
+    config.cancellation.check()?;
+    let purity = purity::analyze_purity(ast, &symbol_table);
+    let local_objects = escape::find_local_objects(ast, &symbol_table);
+    let local_classes = escape::find_local_classes(ast, &symbol_table);
+
     let analysis_time = start_time.elapsed().as_millis() as u64;
 
     let metadata = AnalysisMetadata {
-        scope_count:      scope_tree.next_scope_id,
-        symbol_count:     symbol_table.next_symbol_id,
+        scope_count:      scope_tree.scopes.len() as u32,
+        symbol_count:     symbol_table.symbols.len() as u32,
         capture_count:    symbol_table
             .symbols
             .values()
@@ -427,17 +628,19 @@ pub fn analyze_ast(ast: &Program, config: &AnalyzerConfig) -> AnalysisResult<Sem
         analysis_time_ms: analysis_time,
     };
 
-    if config.verbose {
-        println!(
-            "Analysis completed in {}ms: {} scopes, {} symbols",
-            analysis_time, metadata.scope_count, metadata.symbol_count
-        );
-    }
+    tracing::debug!(
+        "Analysis completed in {}ms: {} scopes, {} symbols",
+        analysis_time, metadata.scope_count, metadata.symbol_count
+    );
 
     Ok(SemanticAnalysis {
         symbol_table,
         scope_tree,
         semantic_flags,
         metadata,
+        module_record,
+        purity,
+        local_objects,
+        local_classes,
     })
 }
\ No newline at end of file
@@ -0,0 +1,498 @@
+//! # Module Records
+//!
+//! What a module imports and exports, as needed to follow bindings across modules:
+//! exports of local bindings, named and namespace re-exports (`export { a as b } from`,
+//! `export * as ns from`), star re-exports (`export * from`), the members used of each
+//! `import * as ns` namespace, whether the module uses top-level `await`, and the
+//! worker entries it starts (`new Worker(new URL("./w.js", import.meta.url))`).
+//!
+//! Module specifiers are kept as written. [`resolve_export`], [`used_exports`] and
+//! [`evaluation_order`] take the records of all modules keyed by specifier, so resolving
+//! specifiers to files is the caller's job.
+
+use std::collections::{BTreeMap, BTreeSet, HashSet};
+
+use serde::{Deserialize, Serialize};
+
+use crate::parser::ast_types::{Expression, Literal, StringLiteral};
+
+/// Imports and exports of one module
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ModuleRecord {
+    /// Bindings imported from other modules, in source order
+    pub imports:       Vec<ImportEntry>,
+    /// Exports of bindings declared or imported in this module
+    pub local_exports: Vec<LocalExport>,
+    /// Named and namespace re-exports of other modules
+    pub re_exports:    Vec<ReExport>,
+    /// Sources of `export * from "source"`
+    pub star_exports:  Vec<String>,
+    /// Use of each `import * as ns` namespace, keyed by its local name
+    pub namespaces:    BTreeMap<String, NamespaceUsage>,
+    /// Specifiers of imported and re-exported modules in source order, without duplicates
+    pub requested_modules: Vec<String>,
+    /// Whether the module body awaits outside of any function
+    pub has_top_level_await: bool,
+    /// Specifiers of the worker entries the module starts (see [`worker_entry`]), in
+    /// source order without duplicates
+    #[serde(default)]
+    pub workers: Vec<String>,
+}
+
+/// What an import or re-export takes from its source module
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum ImportedName {
+    /// A single export; default imports use `"default"`
+    Named(String),
+    /// The namespace object holding every export
+    Namespace,
+}
+
+/// `import { imported as local } from "source"` and its default and namespace forms
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ImportEntry {
+    /// Local binding name
+    pub local:         String,
+    /// Name taken from the source module
+    pub imported:      ImportedName,
+    /// Module specifier
+    pub source:        String,
+    /// Whether the local binding is referenced, including by an export
+    pub is_referenced: bool,
+}
+
+/// Local name of the default export of an expression or an anonymous function or class,
+/// which has no binding of its own (the specification's `*default*`)
+pub const DEFAULT_EXPORT_LOCAL: &str = "*default*";
+
+/// `export { local as exported }`, or an exported declaration
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LocalExport {
+    /// Name other modules import
+    pub exported: String,
+    /// Local binding name, or [`DEFAULT_EXPORT_LOCAL`]
+    pub local:    String,
+}
+
+/// `export { imported as exported } from "source"` or `export * as exported from "source"`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ReExport {
+    /// Name other modules import
+    pub exported: String,
+    /// Name taken from the source module
+    pub imported: ImportedName,
+    /// Module specifier
+    pub source:   String,
+}
+
+/// How an `import * as ns` namespace is used
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct NamespaceUsage {
+    /// Members read statically, as `ns.member` or `ns["member"]`
+    pub members:    BTreeSet<String>,
+    /// Whether the namespace is used other than through a static member access (passed
+    /// around, or indexed with a computed key), so every export may be read
+    pub is_dynamic: bool,
+}
+
+/// Where an exported name is defined
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ResolvedExport {
+    /// A local binding of `module`
+    Binding { module: String, local: String },
+    /// The namespace object of `module`
+    Namespace { module: String },
+}
+
+/// Exports of a module that other modules (or the entry point) use
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ExportUsage {
+    /// Every export, as for the entry module or a namespace used dynamically
+    All,
+    /// Only these names
+    Names(BTreeSet<String>),
+}
+
+/// One module in the order a module graph is evaluated
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EvaluationStep {
+    /// Module specifier
+    pub module:   String,
+    /// Whether evaluation finishes asynchronously: the module uses top-level `await` or
+    /// depends on a module that does, so its body has to wait for a promise
+    pub is_async: bool,
+}
+
+impl ExportUsage {
+    /// Returns whether `name` is used
+    pub fn contains(&self, name: &str) -> bool {
+        match self {
+            Self::All => true,
+            Self::Names(names) => names.contains(name),
+        }
+    }
+
+    /// Adds `other`; returns whether anything new was added
+    fn merge(&mut self, other: &ExportUsage) -> bool {
+        match (&mut *self, other) {
+            (Self::All, _) => false,
+            (_, Self::All) => {
+                *self = Self::All;
+                true
+            }
+            (Self::Names(names), Self::Names(other)) => {
+                let before = names.len();
+                names.extend(other.iter().cloned());
+                names.len() != before
+            }
+        }
+    }
+}
+
+impl ModuleRecord {
+    /// Names this module exports directly, without star re-exports
+    pub fn exported_names(&self) -> impl Iterator<Item = &str> {
+        self.local_exports
+            .iter()
+            .map(|export| export.exported.as_str())
+            .chain(self.re_exports.iter().map(|export| export.exported.as_str()))
+    }
+
+    /// Exports each source module must provide for the referenced imports of this module
+    fn imported_usage(&self) -> Vec<(&str, ExportUsage)> {
+        self.imports
+            .iter()
+            .filter(|import| import.is_referenced)
+            .map(|import| {
+                let usage = match &import.imported {
+                    ImportedName::Named(name) => ExportUsage::Names(BTreeSet::from([name.clone()])),
+                    ImportedName::Namespace => match self.namespaces.get(&import.local) {
+                        Some(namespace) if !namespace.is_dynamic => ExportUsage::Names(namespace.members.clone()),
+                        _ => ExportUsage::All,
+                    },
+                };
+                (import.source.as_str(), usage)
+            })
+            .collect()
+    }
+
+    /// Exports each source module must provide for `usage` of this module's re-exports
+    fn re_exported_usage(&self, usage: &ExportUsage) -> Vec<(&str, ExportUsage)> {
+        let mut requested = Vec::new();
+        for export in self.re_exports.iter().filter(|export| usage.contains(&export.exported)) {
+            let source_usage = match &export.imported {
+                ImportedName::Named(name) => ExportUsage::Names(BTreeSet::from([name.clone()])),
+                ImportedName::Namespace => ExportUsage::All,
+            };
+            requested.push((export.source.as_str(), source_usage));
+        }
+
+        // `export *` forwards every name this module doesn't define itself, except `default`
+        let star_usage = match usage {
+            ExportUsage::All => ExportUsage::All,
+            ExportUsage::Names(names) => {
+                let own: HashSet<&str> = self.exported_names().collect();
+                ExportUsage::Names(
+                    names.iter().filter(|name| !own.contains(name.as_str()) && *name != "default").cloned().collect(),
+                )
+            }
+        };
+        for source in &self.star_exports {
+            requested.push((source.as_str(), star_usage.clone()));
+        }
+        requested
+    }
+}
+
+/// The specifier of the worker entry `expression` starts, if it is
+/// `new Worker(new URL("specifier", import.meta.url), …)` or the same with `SharedWorker`
+///
+/// Bundlers recognize this form because the URL is resolved against the module itself,
+/// so the entry can be found, compiled and renamed without running the code. The
+/// caller checks that `Worker` or `SharedWorker` isn't a local binding.
+pub fn worker_entry(expression: &Expression) -> Option<&StringLiteral> {
+    let Expression::NewExpression { callee, arguments } = expression else {
+        return None;
+    };
+    if !matches!(callee.as_ref(), Expression::Identifier(id) if id.name == "Worker" || id.name == "SharedWorker") {
+        return None;
+    }
+    let Some(Expression::NewExpression { callee, arguments }) = arguments.first() else {
+        return None;
+    };
+    match (callee.as_ref(), arguments.as_slice()) {
+        (
+            Expression::Identifier(id),
+            [Expression::Literal(Literal::String(specifier)), Expression::MemberExpression { object, property, computed: false, .. }],
+        ) if id.name == "URL"
+            && matches!(object.as_ref(), Expression::MetaProperty { meta, property } if meta == "import" && property == "meta")
+            && matches!(property.as_ref(), Expression::Identifier(property) if property.name == "url") =>
+        {
+            Some(specifier)
+        }
+        _ => None,
+    }
+}
+
+/// Finds the binding that `name`, exported by `module`, refers to
+///
+/// Follows `export { } from`, `export * from` and re-exported imports through any number
+/// of modules. Returns `None` if the name is not exported, a module in the chain is
+/// missing from `modules`, the chain is circular, or two star re-exports provide
+/// different bindings for the name (which JavaScript treats as ambiguous).
+pub fn resolve_export(modules: &BTreeMap<String, ModuleRecord>, module: &str, name: &str) -> Option<ResolvedExport> {
+    resolve_export_inner(modules, module, name, &mut HashSet::new())
+}
+
+fn resolve_export_inner(
+    modules: &BTreeMap<String, ModuleRecord>,
+    module: &str,
+    name: &str,
+    visited: &mut HashSet<(String, String)>,
+) -> Option<ResolvedExport> {
+    if !visited.insert((module.to_string(), name.to_string())) {
+        return None;
+    }
+    let record = modules.get(module)?;
+
+    if let Some(export) = record.local_exports.iter().find(|export| export.exported == name) {
+        // `import { x } from "a"; export { x }` is a re-export as well
+        return match record.imports.iter().find(|import| import.local == export.local) {
+            Some(ImportEntry { imported: ImportedName::Named(imported), source, .. }) => {
+                resolve_export_inner(modules, source, imported, visited)
+            }
+            Some(ImportEntry { imported: ImportedName::Namespace, source, .. }) => {
+                Some(ResolvedExport::Namespace { module: source.clone() })
+            }
+            None => Some(ResolvedExport::Binding { module: module.to_string(), local: export.local.clone() }),
+        };
+    }
+
+    if let Some(export) = record.re_exports.iter().find(|export| export.exported == name) {
+        return match &export.imported {
+            ImportedName::Named(imported) => resolve_export_inner(modules, &export.source, imported, visited),
+            ImportedName::Namespace => Some(ResolvedExport::Namespace { module: export.source.clone() }),
+        };
+    }
+
+    if name == "default" {
+        return None;
+    }
+    let mut resolved = None;
+    for source in &record.star_exports {
+        if let Some(found) = resolve_export_inner(modules, source, name, &mut visited.clone()) {
+            match &resolved {
+                Some(existing) if *existing != found => return None,
+                _ => resolved = Some(found),
+            }
+        }
+    }
+    resolved
+}
+
+/// Exports of each module that are used, starting from `entry`, whose exports are all used
+///
+/// Propagates through imports (only referenced ones, and only the namespace members read
+/// statically) and through named, namespace and star re-exports. Modules reached through
+/// a specifier missing from `modules` are skipped. Modules absent from the result are
+/// unused apart from their side effects.
+pub fn used_exports(modules: &BTreeMap<String, ModuleRecord>, entry: &str) -> BTreeMap<String, ExportUsage> {
+    let mut used: BTreeMap<String, ExportUsage> = BTreeMap::from([(entry.to_string(), ExportUsage::All)]);
+    let mut imports_visited = HashSet::new();
+    let mut pending = vec![entry.to_string()];
+
+    while let Some(module) = pending.pop() {
+        let Some(record) = modules.get(&module) else {
+            continue;
+        };
+        let mut requested = record.re_exported_usage(&used[&module]);
+        if imports_visited.insert(module.clone()) {
+            requested.extend(record.imported_usage());
+        }
+
+        for (source, usage) in requested {
+            let changed = match used.get_mut(source) {
+                Some(existing) => existing.merge(&usage),
+                None => {
+                    used.insert(source.to_string(), usage);
+                    true
+                }
+            };
+            if changed {
+                pending.push(source.to_string());
+            }
+        }
+    }
+    used
+}
+
+/// Order in which the modules reachable from `entry` are evaluated
+///
+/// Dependencies come before the modules requesting them, in request order, as in
+/// ECMAScript's module evaluation: a post-order walk that skips modules already being
+/// evaluated, so cycles terminate. Modules with top-level `await` and every module that
+/// depends on one, directly or transitively, are marked async; a bundler must await
+/// their evaluation (or wrap it in an async function) before evaluating later steps that
+/// depend on them. Specifiers missing from `modules` are skipped.
+pub fn evaluation_order(modules: &BTreeMap<String, ModuleRecord>, entry: &str) -> Vec<EvaluationStep> {
+    fn visit(
+        modules: &BTreeMap<String, ModuleRecord>,
+        module: &str,
+        visited: &mut HashSet<String>,
+        order: &mut Vec<EvaluationStep>,
+    ) -> bool {
+        let Some(record) = modules.get(module) else {
+            return false;
+        };
+        if !visited.insert(module.to_string()) {
+            // Evaluated already, or in progress further up a cycle
+            return order.iter().any(|step| step.module == module && step.is_async);
+        }
+        let mut is_async = record.has_top_level_await;
+        for source in &record.requested_modules {
+            is_async |= visit(modules, source, visited, order);
+        }
+        order.push(EvaluationStep { module: module.to_string(), is_async });
+        is_async
+    }
+
+    let mut order = Vec::new();
+    visit(modules, entry, &mut HashSet::new(), &mut order);
+    order
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::analyzer::{analyze_ast, AnalyzerConfig};
+    use crate::parser::{parse_js, ParserConfig};
+
+    fn record(source: &str) -> ModuleRecord {
+        let ast = parse_js(source, "test.mjs", &ParserConfig::default()).ast.unwrap();
+        analyze_ast(&ast, &AnalyzerConfig::default()).unwrap().module_record
+    }
+
+    fn modules(sources: &[(&str, &str)]) -> BTreeMap<String, ModuleRecord> {
+        sources.iter().map(|(name, source)| (name.to_string(), record(source))).collect()
+    }
+
+    fn names(names: &[&str]) -> ExportUsage {
+        ExportUsage::Names(names.iter().map(|name| name.to_string()).collect())
+    }
+
+    #[test]
+    fn test_records_imports_exports_and_namespace_usage() {
+        let record = record(
+            "import def, * as ns from \"./a\";\nimport { x as y, unused } from \"./b\";\nexport * from \"./c\";\nexport * as all from \"./d\";\nexport { z as w } from \"./e\";\nconst local = ns.first + ns[\"second\"] + y;\nexport { local as renamed };\n",
+        );
+
+        assert_eq!(record.imports.len(), 4);
+        assert_eq!(record.imports[0].imported, ImportedName::Named("default".to_string()));
+        assert!(!record.imports[0].is_referenced);
+        assert_eq!(record.imports[1].imported, ImportedName::Namespace);
+        assert!(record.imports[2].is_referenced);
+        assert!(!record.imports[3].is_referenced);
+        assert_eq!(record.star_exports, ["./c"]);
+        assert_eq!(record.re_exports[0].imported, ImportedName::Namespace);
+        assert_eq!(record.re_exports[1].imported, ImportedName::Named("z".to_string()));
+        assert_eq!(record.local_exports, [LocalExport { exported: "renamed".to_string(), local: "local".to_string() }]);
+
+        let namespace = &record.namespaces["ns"];
+        assert_eq!(namespace.members.iter().collect::<Vec<_>>(), ["first", "second"]);
+        assert!(!namespace.is_dynamic);
+        assert!(self::record("import * as ns from \"./a\";\nconsole.log(ns);\n").namespaces["ns"].is_dynamic);
+    }
+
+    #[test]
+    fn test_records_default_exports() {
+        let default = |local: &str| [LocalExport { exported: "default".to_string(), local: local.to_string() }];
+        assert_eq!(record("export default function main() {}\n").local_exports, default("main"));
+        assert_eq!(record("export default class {}\n").local_exports, default(DEFAULT_EXPORT_LOCAL));
+        assert_eq!(record("const a = 1;\nexport default a;\n").local_exports, default(DEFAULT_EXPORT_LOCAL));
+    }
+
+    #[test]
+    fn test_resolve_export_follows_re_export_chains() {
+        let modules = modules(&[
+            ("./index", "export * from \"./api\";\nexport { helper as util } from \"./helpers\";\nexport * as shapes from \"./shapes\";\n"),
+            ("./api", "import { parse } from \"./parser\";\nexport { parse };\nexport * from \"./more\";\n"),
+            ("./parser", "export function parse() {}\n"),
+            ("./helpers", "export const helper = 1;\n"),
+            ("./more", "export const extra = 2;\nexport * from \"./index\";\n"),
+            ("./shapes", "export class Circle {}\n"),
+        ]);
+        let binding = |module: &str, local: &str| {
+            Some(ResolvedExport::Binding { module: module.to_string(), local: local.to_string() })
+        };
+
+        assert_eq!(resolve_export(&modules, "./index", "parse"), binding("./parser", "parse"));
+        assert_eq!(resolve_export(&modules, "./index", "util"), binding("./helpers", "helper"));
+        assert_eq!(resolve_export(&modules, "./index", "extra"), binding("./more", "extra"));
+        assert_eq!(
+            resolve_export(&modules, "./index", "shapes"),
+            Some(ResolvedExport::Namespace { module: "./shapes".to_string() })
+        );
+        // The cycle through ./more back to ./index terminates
+        assert_eq!(resolve_export(&modules, "./index", "missing"), None);
+
+        // Two star exports providing different bindings are ambiguous
+        let ambiguous = self::modules(&[
+            ("./main", "export * from \"./a\";\nexport * from \"./b\";\n"),
+            ("./a", "export const value = 1;\n"),
+            ("./b", "export const value = 2;\n"),
+        ]);
+        assert_eq!(resolve_export(&ambiguous, "./main", "value"), None);
+    }
+
+    #[test]
+    fn test_used_exports_through_re_exports_and_namespaces() {
+        let modules = modules(&[
+            ("./main", "import { parse } from \"./index\";\nimport * as shapes from \"./shapes\";\nparse(shapes.Circle);\n"),
+            ("./index", "export * from \"./api\";\nexport { helper as util } from \"./helpers\";\n"),
+            ("./api", "export function parse() {}\nexport function format() {}\n"),
+            ("./helpers", "export const helper = 1;\n"),
+            ("./shapes", "export class Circle {}\nexport class Square {}\n"),
+        ]);
+        let used = used_exports(&modules, "./main");
+
+        assert_eq!(used["./main"], ExportUsage::All);
+        assert_eq!(used["./index"], names(&["parse"]));
+        assert_eq!(used["./api"], names(&["parse"]));
+        assert_eq!(used["./shapes"], names(&["Circle"]));
+        assert!(!used.contains_key("./helpers"));
+    }
+
+    #[test]
+    fn test_evaluation_order_marks_async_modules() {
+        let modules = modules(&[
+            ("./main", "import \"./polyfill\";\nimport { config } from \"./config\";\nexport * from \"./view\";\nconsole.log(config);\n"),
+            ("./polyfill", "globalThis.ready = true;\n"),
+            ("./config", "import \"./polyfill\";\nexport const config = await load();\n"),
+            ("./view", "import { config } from \"./config\";\nimport \"./main\";\nfunction render() { return config; }\n"),
+        ]);
+        let order = evaluation_order(&modules, "./main");
+        let order: Vec<(&str, bool)> = order
+            .iter()
+            .map(|step| (step.module.as_str(), step.is_async))
+            .collect();
+
+        assert_eq!(order, [("./polyfill", false), ("./config", true), ("./view", true), ("./main", true)]);
+        assert!(record("export const value = await fetch(\"./data\");\n").has_top_level_await);
+        assert!(!record("export async function load() { await fetch(\"./data\"); }\n").has_top_level_await);
+    }
+
+    #[test]
+    fn test_records_worker_entries() {
+        let record = record(
+            "const a = new Worker(new URL(\"./a.js\", import.meta.url), { type: \"module\" });\n\
+             function start() { return new SharedWorker(new URL(\"./shared.js\", import.meta.url)); }\n\
+             const again = new Worker(new URL(\"./a.js\", import.meta.url));\n\
+             const page = new Worker(new URL(\"./b.js\", location.href));\n\
+             const named = new Worker(\"./c.js\");\n",
+        );
+        assert_eq!(record.workers, ["./a.js", "./shared.js"]);
+
+        let shadowed = self::record("class Worker {}\nnew Worker(new URL(\"./a.js\", import.meta.url));\n");
+        assert!(shadowed.workers.is_empty());
+    }
+}
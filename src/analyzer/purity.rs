@@ -0,0 +1,680 @@
+//! # Side-Effect Analysis
+//!
+//! Classifies functions and expressions by what evaluating them can observe or change:
+//!
+//! - [`Purity::Pure`]: the result depends only on the operands and nothing is changed,
+//!   so the expression can be dropped when unused and moved past other code
+//! - [`Purity::ReadOnly`]: reads state outside the expression (non-local variables,
+//!   properties, `this`) but changes nothing, so it can be dropped when unused but not
+//!   moved past writes
+//! - [`Purity::Effectful`]: may write state, throw, suspend or run unknown code
+//!
+//! Calls are classified by their callee: a conservative whitelist of pure globals, or
+//! a function declared exactly once in the program (a function declaration or a `const`
+//! bound function or arrow). The globals convert their arguments, which runs an object's
+//! own `toString` or `valueOf`, so their calls only count as pure when every argument is
+//! known to be a primitive: a literal, the result of an operator that always yields one,
+//! or a `const` declared once and initialized with one. Functions are classified from their bodies, where writes
+//! to their own parameters and local variables don't count, by iterating to a fixed
+//! point so that recursive and mutually calling functions are handled. Anything else
+//! is effectful.
+
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+
+use serde::{Deserialize, Serialize};
+
+use crate::analyzer::scope_builder::pattern_names;
+use crate::analyzer::SymbolTable;
+use crate::interner::Name;
+use crate::parser::ast_types::{
+    ArrowFunctionBody, BinaryOperator, BlockStatement, Expression, ForInit, Literal, ObjectPatternProperty,
+    ObjectProperty, Pattern, Program, Statement, UnaryOperator, VariableDeclarationKind, VariableDeclarator,
+};
+
+/// Global functions whose calls have no side effects when their arguments are primitives
+const PURE_GLOBAL_FUNCTIONS: &[&str] = &["String", "Number", "Boolean", "parseInt", "parseFloat", "isNaN", "isFinite"];
+
+/// Static methods of global objects that have no side effects when their arguments are primitives
+const PURE_GLOBAL_METHODS: &[(&str, &str)] = &[
+    ("Math", "abs"),
+    ("Math", "acos"),
+    ("Math", "asin"),
+    ("Math", "atan"),
+    ("Math", "atan2"),
+    ("Math", "ceil"),
+    ("Math", "cos"),
+    ("Math", "exp"),
+    ("Math", "floor"),
+    ("Math", "hypot"),
+    ("Math", "log"),
+    ("Math", "max"),
+    ("Math", "min"),
+    ("Math", "pow"),
+    ("Math", "round"),
+    ("Math", "sign"),
+    ("Math", "sin"),
+    ("Math", "sqrt"),
+    ("Math", "tan"),
+    ("Math", "trunc"),
+    ("Array", "isArray"),
+];
+
+/// Globals holding primitives that aren't BigInts or symbols
+const PRIMITIVE_GLOBALS: &[&str] = &["undefined", "NaN", "Infinity"];
+
+/// Globals that always exist, so reading them can't throw a `ReferenceError`
+const KNOWN_GLOBALS: &[&str] = &[
+    "undefined", "NaN", "Infinity", "globalThis", "Math", "JSON", "Object", "Array", "String", "Number",
+    "Boolean", "Symbol",
+];
+
+/// What evaluating a function or expression can observe or change, from least to most
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub enum Purity {
+    /// Depends only on its operands and changes nothing
+    Pure,
+    /// Reads outside state but changes nothing
+    ReadOnly,
+    /// May change state, throw, suspend or run unknown code
+    Effectful,
+}
+
+impl Purity {
+    /// Classification of evaluating both `self` and `other`
+    pub fn join(self, other: Purity) -> Purity {
+        self.max(other)
+    }
+
+    /// Whether the expression can be removed when its value is unused
+    pub fn is_removable(self) -> bool {
+        self != Purity::Effectful
+    }
+
+    /// Whether the expression can be evaluated in a different order relative to other code
+    pub fn can_reorder(self) -> bool {
+        self == Purity::Pure
+    }
+}
+
+/// Side-effect classification of the functions declared in a program
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PurityTable {
+    /// Classification of calling each function declared exactly once, keyed by name
+    pub functions: BTreeMap<String, Purity>,
+    /// Names of the `const` bindings declared exactly once whose value is a primitive
+    /// other than a BigInt or symbol
+    #[serde(default)]
+    pub primitives: BTreeSet<String>,
+}
+
+impl PurityTable {
+    /// Classifies evaluating `expression` outside of any function body
+    pub fn expression_purity(&self, expression: &Expression, symbol_table: &SymbolTable) -> Purity {
+        Classifier { functions: &self.functions, primitives: &self.primitives, symbol_table, locals: None }
+            .expression(expression)
+    }
+}
+
+/// Builds the purity table of `ast`
+pub fn analyze_purity(ast: &Program, symbol_table: &SymbolTable) -> PurityTable {
+    let mut declared: HashMap<Name, usize> = HashMap::new();
+    for symbol in symbol_table.symbols.values() {
+        *declared.entry(symbol.name).or_default() += 1;
+    }
+    let declared_once = |name: &str| symbol_table.names.get(name).and_then(|name| declared.get(&name)) == Some(&1);
+
+    let mut constants = Vec::new();
+    collect_constants(&ast.body, &mut constants);
+    constants.retain(|(name, _)| declared_once(name));
+    // A constant may be initialized from another, so grow the set until it settles
+    let mut primitives = BTreeSet::new();
+    loop {
+        let functions = BTreeMap::new();
+        let classifier = Classifier { functions: &functions, primitives: &primitives, symbol_table, locals: None };
+        let found: Vec<String> = constants
+            .iter()
+            .filter(|(name, init)| !primitives.contains(name) && classifier.is_primitive(init))
+            .map(|(name, _)| name.clone())
+            .collect();
+        if found.is_empty() {
+            break;
+        }
+        primitives.extend(found);
+    }
+
+    let mut candidates = Vec::new();
+    collect_functions(&ast.body, &mut candidates);
+    candidates.retain(|function| declared_once(&function.name));
+
+    // Start optimistic and only ever weaken, so recursion settles on the least classification
+    let mut functions: BTreeMap<String, Purity> =
+        candidates.iter().map(|function| (function.name.clone(), Purity::Pure)).collect();
+    loop {
+        let mut changed = false;
+        for function in &candidates {
+            let purity = function_purity(function, &functions, &primitives, symbol_table);
+            let current = functions.get_mut(&function.name).expect("candidate is in the table");
+            if purity > *current {
+                *current = purity;
+                changed = true;
+            }
+        }
+        if !changed {
+            break;
+        }
+    }
+
+    PurityTable { functions, primitives }
+}
+
+/// A named function whose calls can be classified
+struct FunctionCandidate<'a> {
+    name:   String,
+    params: &'a [Pattern],
+    body:   FunctionBody<'a>,
+}
+
+/// Body of a function declaration, function expression or arrow
+enum FunctionBody<'a> {
+    Block(&'a BlockStatement),
+    Expression(&'a Expression),
+}
+
+/// Finds function declarations and `const` bound functions at any depth
+fn collect_functions<'a>(statements: &'a [Statement], candidates: &mut Vec<FunctionCandidate<'a>>) {
+    for statement in statements {
+        match statement {
+            Statement::FunctionDeclaration { id: Some(id), params, body, .. } => {
+                candidates.push(FunctionCandidate { name: id.name.clone(), params, body: FunctionBody::Block(body) });
+                collect_functions(&body.body, candidates);
+            }
+            Statement::FunctionDeclaration { body, .. } => collect_functions(&body.body, candidates),
+            Statement::VariableDeclaration { declarations, kind, .. } => {
+                for declarator in declarations {
+                    match (&declarator.id, &declarator.init) {
+                        (Pattern::Identifier(id), Some(Expression::FunctionExpression(function)))
+                            if matches!(kind, VariableDeclarationKind::Const) =>
+                        {
+                            candidates.push(FunctionCandidate {
+                                name:   id.name.clone(),
+                                params: &function.params,
+                                body:   FunctionBody::Block(&function.body),
+                            });
+                            collect_functions(&function.body.body, candidates);
+                        }
+                        (Pattern::Identifier(id), Some(Expression::ArrowFunctionExpression { params, body, .. }))
+                            if matches!(kind, VariableDeclarationKind::Const) =>
+                        {
+                            let body = match body {
+                                ArrowFunctionBody::BlockStatement(block) => {
+                                    collect_functions(&block.body, candidates);
+                                    FunctionBody::Block(block)
+                                }
+                                ArrowFunctionBody::Expression(expression) => FunctionBody::Expression(expression),
+                            };
+                            candidates.push(FunctionCandidate { name: id.name.clone(), params, body });
+                        }
+                        _ => {}
+                    }
+                }
+            }
+            Statement::BlockStatement { body, .. } => collect_functions(body, candidates),
+            Statement::IfStatement { consequent, alternate, .. } => {
+                collect_functions(std::slice::from_ref(consequent.as_ref()), candidates);
+                if let Some(alternate) = alternate {
+                    collect_functions(std::slice::from_ref(alternate.as_ref()), candidates);
+                }
+            }
+            Statement::WhileStatement { body, .. }
+            | Statement::ForStatement { body, .. }
+            | Statement::ForOfStatement { body, .. } => {
+                collect_functions(std::slice::from_ref(body.as_ref()), candidates);
+            }
+            Statement::ExportNamedDeclaration { declaration: Some(declaration), .. }
+            | Statement::ExportDefaultDeclaration { declaration, .. } => {
+                collect_functions(std::slice::from_ref(declaration.as_ref()), candidates);
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Finds `const` declarations of single names at any depth, with their initializers
+fn collect_constants<'a>(statements: &'a [Statement], constants: &mut Vec<(String, &'a Expression)>) {
+    for statement in statements {
+        match statement {
+            Statement::VariableDeclaration { declarations, kind: VariableDeclarationKind::Const, .. } => {
+                for declarator in declarations {
+                    match (&declarator.id, &declarator.init) {
+                        (Pattern::Identifier(id), Some(init)) => constants.push((id.name.clone(), init)),
+                        (_, Some(init)) => collect_expression_constants(init, constants),
+                        _ => {}
+                    }
+                }
+            }
+            Statement::VariableDeclaration { declarations, .. } => {
+                for init in declarations.iter().filter_map(|declarator| declarator.init.as_ref()) {
+                    collect_expression_constants(init, constants);
+                }
+            }
+            Statement::FunctionDeclaration { body, .. } => collect_constants(&body.body, constants),
+            Statement::BlockStatement { body, .. } => collect_constants(body, constants),
+            Statement::IfStatement { consequent, alternate, .. } => {
+                collect_constants(std::slice::from_ref(consequent.as_ref()), constants);
+                if let Some(alternate) = alternate {
+                    collect_constants(std::slice::from_ref(alternate.as_ref()), constants);
+                }
+            }
+            Statement::WhileStatement { body, .. }
+            | Statement::ForStatement { body, .. }
+            | Statement::ForOfStatement { body, .. } => {
+                collect_constants(std::slice::from_ref(body.as_ref()), constants);
+            }
+            Statement::ExportNamedDeclaration { declaration: Some(declaration), .. }
+            | Statement::ExportDefaultDeclaration { declaration, .. } => {
+                collect_constants(std::slice::from_ref(declaration.as_ref()), constants);
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Finds the constants declared in the bodies of functions initializing a variable
+fn collect_expression_constants<'a>(expression: &'a Expression, constants: &mut Vec<(String, &'a Expression)>) {
+    match expression {
+        Expression::FunctionExpression(function) => collect_constants(&function.body.body, constants),
+        Expression::ArrowFunctionExpression { body: ArrowFunctionBody::BlockStatement(block), .. } => {
+            collect_constants(&block.body, constants);
+        }
+        _ => {}
+    }
+}
+
+/// Classifies calling `function`, given the current classification of the others
+fn function_purity(
+    function: &FunctionCandidate,
+    functions: &BTreeMap<String, Purity>,
+    primitives: &BTreeSet<String>,
+    symbol_table: &SymbolTable,
+) -> Purity {
+    let mut names = Vec::new();
+    for param in function.params {
+        pattern_names(param, &mut names);
+    }
+    names.push("arguments".to_string());
+    if let FunctionBody::Block(block) = function.body {
+        collect_local_names(&block.body, &mut names);
+    }
+    let locals: HashSet<String> = names.into_iter().collect();
+    let classifier = Classifier { functions, primitives, symbol_table, locals: Some(&locals) };
+
+    let params = function.params.iter().fold(Purity::Pure, |purity, param| purity.join(classifier.pattern(param)));
+    let body = match function.body {
+        FunctionBody::Block(block) => classifier.statements(&block.body),
+        FunctionBody::Expression(expression) => classifier.expression(expression),
+    };
+    params.join(body)
+}
+
+/// Adds the names declared directly in a function body, excluding nested functions' bodies
+fn collect_local_names(statements: &[Statement], names: &mut Vec<String>) {
+    for statement in statements {
+        match statement {
+            Statement::VariableDeclaration { declarations, .. } => {
+                declarations.iter().for_each(|declarator| pattern_names(&declarator.id, names));
+            }
+            Statement::FunctionDeclaration { id: Some(id), .. } | Statement::ClassDeclaration { id: Some(id), .. } => {
+                names.push(id.name.clone());
+            }
+            Statement::BlockStatement { body, .. } => collect_local_names(body, names),
+            Statement::IfStatement { consequent, alternate, .. } => {
+                collect_local_names(std::slice::from_ref(consequent.as_ref()), names);
+                if let Some(alternate) = alternate {
+                    collect_local_names(std::slice::from_ref(alternate.as_ref()), names);
+                }
+            }
+            Statement::ForStatement { init, body, .. } => {
+                if let Some(ForInit::VariableDeclaration { declarations, .. }) = init {
+                    declarations.iter().for_each(|declarator| pattern_names(&declarator.id, names));
+                }
+                collect_local_names(std::slice::from_ref(body.as_ref()), names);
+            }
+            Statement::ForOfStatement { left, body, .. } => {
+                if let ForInit::VariableDeclaration { declarations, .. } = left {
+                    declarations.iter().for_each(|declarator| pattern_names(&declarator.id, names));
+                }
+                collect_local_names(std::slice::from_ref(body.as_ref()), names);
+            }
+            Statement::WhileStatement { body, .. } => collect_local_names(std::slice::from_ref(body.as_ref()), names),
+            _ => {}
+        }
+    }
+}
+
+/// Classifies statements and expressions against a function table
+struct Classifier<'a> {
+    functions:    &'a BTreeMap<String, Purity>,
+    primitives:   &'a BTreeSet<String>,
+    symbol_table: &'a SymbolTable,
+    /// Bindings of the function being classified, whose reads and writes are unobservable
+    locals:       Option<&'a HashSet<String>>,
+}
+
+impl Classifier<'_> {
+    fn is_local(&self, name: &str) -> bool {
+        self.locals.is_some_and(|locals| locals.contains(name))
+    }
+
+    fn statements(&self, statements: &[Statement]) -> Purity {
+        statements.iter().fold(Purity::Pure, |purity, statement| purity.join(self.statement(statement)))
+    }
+
+    fn statement(&self, statement: &Statement) -> Purity {
+        match statement {
+            Statement::VariableDeclaration { declarations, .. } => self.declarations(declarations),
+            // Declaring a function only creates a closure
+            Statement::FunctionDeclaration { .. } => Purity::Pure,
+            Statement::ExpressionStatement { expression, .. } => self.expression(expression),
+            Statement::BlockStatement { body, .. } => self.statements(body),
+            Statement::ReturnStatement { argument, .. } => argument.as_ref().map_or(Purity::Pure, |argument| self.expression(argument)),
+            Statement::IfStatement { test, consequent, alternate, .. } => {
+                let alternate = alternate.as_ref().map_or(Purity::Pure, |alternate| self.statement(alternate));
+                self.expression(test).join(self.statement(consequent)).join(alternate)
+            }
+            Statement::WhileStatement { test, body, .. } => self.expression(test).join(self.statement(body)),
+            Statement::ForStatement { init, test, update, body, .. } => {
+                let init = match init {
+                    Some(ForInit::VariableDeclaration { declarations, .. }) => self.declarations(declarations),
+                    Some(ForInit::Expression(expression)) => self.expression(expression),
+                    None => Purity::Pure,
+                };
+                let test = test.as_ref().map_or(Purity::Pure, |test| self.expression(test));
+                let update = update.as_ref().map_or(Purity::Pure, |update| self.expression(update));
+                init.join(test).join(update).join(self.statement(body))
+            }
+            Statement::EmptyStatement => Purity::Pure,
+            // Iteration runs the iterable's own `next`, and classes run static initializers;
+            // `debugger` pauses an attached debugger, which callers may rely on
+            Statement::ForOfStatement { .. }
+            | Statement::DebuggerStatement
+            | Statement::ClassDeclaration { .. }
+            | Statement::ImportDeclaration { .. }
+            | Statement::ExportNamedDeclaration { .. }
+            | Statement::ExportDefaultDeclaration { .. }
+            | Statement::ExportAllDeclaration { .. } => Purity::Effectful,
+        }
+    }
+
+    fn declarations(&self, declarations: &[VariableDeclarator]) -> Purity {
+        declarations.iter().fold(Purity::Pure, |purity, declarator| {
+            let init = declarator.init.as_ref().map_or(Purity::Pure, |init| self.expression(init));
+            purity.join(init).join(self.pattern(&declarator.id))
+        })
+    }
+
+    /// Classifies binding `pattern`: destructuring reads properties, defaults are evaluated
+    fn pattern(&self, pattern: &Pattern) -> Purity {
+        match pattern {
+            Pattern::Identifier(_) => Purity::Pure,
+            Pattern::ArrayPattern { elements } => {
+                // Array destructuring runs the iterator protocol
+                if elements.is_empty() { Purity::ReadOnly } else { Purity::Effectful }
+            }
+            Pattern::ObjectPattern { properties } => properties.iter().fold(Purity::ReadOnly, |purity, property| {
+                purity.join(match property {
+                    ObjectPatternProperty::Property { value, .. } => self.pattern(value),
+                    ObjectPatternProperty::RestElement { argument } => self.pattern(argument),
+                })
+            }),
+            Pattern::AssignmentPattern { left, right } => self.pattern(left).join(self.expression(right)),
+            Pattern::RestElement { argument } => self.pattern(argument),
+        }
+    }
+
+    fn expression(&self, expression: &Expression) -> Purity {
+        match expression {
+            Expression::Literal(_) | Expression::FunctionExpression(_) | Expression::ArrowFunctionExpression { .. } => {
+                Purity::Pure
+            }
+            Expression::Identifier(id) => {
+                if self.is_local(&id.name) {
+                    Purity::Pure
+                } else if self.symbol_table.is_declared(&id.name) || KNOWN_GLOBALS.contains(&id.name.as_str()) {
+                    Purity::ReadOnly
+                } else {
+                    // Reading an undeclared global throws when it doesn't exist
+                    Purity::Effectful
+                }
+            }
+            Expression::ThisExpression | Expression::MetaProperty { .. } => Purity::ReadOnly,
+            Expression::UnaryExpression { operator: UnaryOperator::Delete, .. } => Purity::Effectful,
+            Expression::UnaryExpression { operator: UnaryOperator::Typeof, argument, .. }
+                if matches!(argument.as_ref(), Expression::Identifier(_)) =>
+            {
+                // `typeof undeclared` doesn't throw
+                Purity::ReadOnly
+            }
+            Expression::UnaryExpression { argument, .. } | Expression::SpreadElement { argument } => self.expression(argument),
+            Expression::BinaryExpression { left, right, .. } => self.expression(left).join(self.expression(right)),
+            Expression::ConditionalExpression { test, consequent, alternate } => {
+                self.expression(test).join(self.expression(consequent)).join(self.expression(alternate))
+            }
+            Expression::ArrayExpression { elements } => elements
+                .iter()
+                .flatten()
+                .fold(Purity::Pure, |purity, element| purity.join(self.expression(element))),
+            Expression::ObjectExpression { properties } => properties.iter().fold(Purity::Pure, |purity, property| {
+                purity.join(match property {
+                    ObjectProperty::Property { value, .. } => self.expression(value),
+                    ObjectProperty::SpreadElement { argument } => self.expression(argument).join(Purity::ReadOnly),
+                })
+            }),
+            Expression::TemplateLiteral { expressions, .. } => expressions
+                .iter()
+                .fold(Purity::Pure, |purity, expression| purity.join(self.expression(expression))),
+            Expression::MemberExpression { object, property, computed, .. } => {
+                let property = if *computed { self.expression(property) } else { Purity::Pure };
+                Purity::ReadOnly.join(self.expression(object)).join(property)
+            }
+            Expression::ChainExpression { expression } => self.expression(expression),
+            Expression::AssignmentExpression { left, right, .. } => match left.as_ref() {
+                Expression::Identifier(id) if self.is_local(&id.name) => self.expression(right),
+                _ => Purity::Effectful,
+            },
+            Expression::UpdateExpression { argument, .. } => match argument.as_ref() {
+                Expression::Identifier(id) if self.is_local(&id.name) => Purity::Pure,
+                _ => Purity::Effectful,
+            },
+            Expression::CallExpression { callee, arguments, .. } => {
+                let evaluation = arguments
+                    .iter()
+                    .fold(Purity::Pure, |purity, argument| purity.join(self.expression(argument)));
+                self.call(callee, arguments).join(evaluation)
+            }
+            // Loading a module runs it, and `new` runs a constructor
+            Expression::TaggedTemplateExpression { .. }
+            | Expression::AwaitExpression { .. }
+            | Expression::YieldExpression { .. }
+            | Expression::ImportExpression { .. }
+            | Expression::NewExpression { .. } => Purity::Effectful,
+        }
+    }
+
+    /// Whether `expression` evaluates to a primitive other than a BigInt or symbol, so
+    /// converting its value can't run user code or throw
+    ///
+    /// Arithmetic only counts with such operands, since BigInt operands give a BigInt.
+    fn is_primitive(&self, expression: &Expression) -> bool {
+        match expression {
+            Expression::Literal(literal) => !matches!(literal, Literal::RegExp(_)),
+            Expression::TemplateLiteral { .. } => true,
+            Expression::Identifier(id) if self.is_local(&id.name) => false,
+            Expression::Identifier(id) => {
+                self.primitives.contains(&id.name)
+                    || (PRIMITIVE_GLOBALS.contains(&id.name.as_str()) && !self.symbol_table.is_declared(&id.name))
+            }
+            Expression::UnaryExpression { operator, argument, .. } => match operator {
+                UnaryOperator::Minus | UnaryOperator::BitwiseNot => self.is_primitive(argument),
+                _ => true,
+            },
+            Expression::BinaryExpression { operator, left, right, .. } => match operator {
+                BinaryOperator::Equal
+                | BinaryOperator::NotEqual
+                | BinaryOperator::StrictEqual
+                | BinaryOperator::StrictNotEqual
+                | BinaryOperator::LessThan
+                | BinaryOperator::LessThanEqual
+                | BinaryOperator::GreaterThan
+                | BinaryOperator::GreaterThanEqual
+                | BinaryOperator::In
+                | BinaryOperator::Instanceof => true,
+                _ => self.is_primitive(left) && self.is_primitive(right),
+            },
+            Expression::ConditionalExpression { consequent, alternate, .. } => {
+                self.is_primitive(consequent) && self.is_primitive(alternate)
+            }
+            _ => false,
+        }
+    }
+
+    /// Classifies running the function `callee` refers to with `arguments`, excluding the
+    /// evaluation of the arguments themselves
+    fn call(&self, callee: &Expression, arguments: &[Expression]) -> Purity {
+        let converts_safely = || arguments.iter().all(|argument| self.is_primitive(argument));
+        match callee {
+            // Names in the table are declared once, so a local binding of the name is the function itself
+            Expression::Identifier(id) => match self.functions.get(&id.name) {
+                Some(&purity) => purity,
+                None if PURE_GLOBAL_FUNCTIONS.contains(&id.name.as_str())
+                    && !self.is_local(&id.name)
+                    && !self.symbol_table.is_declared(&id.name)
+                    && converts_safely() =>
+                {
+                    Purity::Pure
+                }
+                None => Purity::Effectful,
+            },
+            Expression::MemberExpression { object, property, computed: false, .. } => {
+                match (object.as_ref(), property.as_ref()) {
+                    (Expression::Identifier(object), Expression::Identifier(method))
+                        if !self.is_local(&object.name)
+                            && !self.symbol_table.is_declared(&object.name)
+                            && PURE_GLOBAL_METHODS.contains(&(object.name.as_str(), method.name.as_str()))
+                            && converts_safely() =>
+                    {
+                        Purity::Pure
+                    }
+                    _ => Purity::Effectful,
+                }
+            }
+            _ => Purity::Effectful,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::analyzer::{analyze_ast, AnalyzerConfig, SemanticAnalysis};
+    use crate::parser::{parse_js, ParserConfig};
+
+    fn analyze(source: &str) -> SemanticAnalysis {
+        let ast = parse_js(source, "test.js", &ParserConfig::default()).ast.unwrap();
+        analyze_ast(&ast, &AnalyzerConfig::default()).unwrap()
+    }
+
+    fn function_purity(source: &str, name: &str) -> Option<Purity> {
+        analyze(source).purity.functions.get(name).copied()
+    }
+
+    #[test]
+    fn test_classifies_function_bodies() {
+        let source = "let total = 0;
+            function square(x) { const y = x * x; return y; }
+            function current() { return total; }
+            function add(x) { total = total + x; }
+            const half = (x) => x / 2;";
+        let analysis = analyze(source);
+        let functions = &analysis.purity.functions;
+
+        assert_eq!(functions.get("square"), Some(&Purity::Pure));
+        assert_eq!(functions.get("current"), Some(&Purity::ReadOnly));
+        assert_eq!(functions.get("add"), Some(&Purity::Effectful));
+        assert_eq!(functions.get("half"), Some(&Purity::Pure));
+    }
+
+    #[test]
+    fn test_propagates_through_recursive_calls() {
+        let source = "function even(n) { return n == 0 ? true : odd(n - 1); }
+            function odd(n) { return n == 0 ? false : even(n - 1); }
+            function log(n) { console.log(n); }
+            function outer(n) { return even(n) ? log(n) : n; }";
+
+        assert_eq!(function_purity(source, "even"), Some(Purity::Pure));
+        assert_eq!(function_purity(source, "odd"), Some(Purity::Pure));
+        assert_eq!(function_purity(source, "log"), Some(Purity::Effectful));
+        assert_eq!(function_purity(source, "outer"), Some(Purity::Effectful));
+    }
+
+    #[test]
+    fn test_skips_names_declared_more_than_once() {
+        let source = "function f() { return 1; } function g() { function f() { sideEffect(); } }";
+
+        assert_eq!(function_purity(source, "f"), None);
+        assert_eq!(function_purity(source, "g"), Some(Purity::Pure));
+    }
+
+    #[test]
+    fn test_classifies_expressions() {
+        let source = "const Math = { max() { return 0; } }; let a = 1; Math.max(1, 2); Number('1'); a; a = 2; missing;";
+        let analysis = analyze(source);
+        let ast = parse_js(source, "test.js", &ParserConfig::default()).ast.unwrap();
+        let purities: Vec<Purity> = ast
+            .body
+            .iter()
+            .filter_map(|statement| match statement {
+                Statement::ExpressionStatement { expression, .. } => Some(analysis.expression_purity(expression)),
+                _ => None,
+            })
+            .collect();
+
+        // A shadowed `Math` is an ordinary object, and reading an undeclared global may throw
+        assert_eq!(
+            purities,
+            vec![Purity::Effectful, Purity::Pure, Purity::ReadOnly, Purity::Effectful, Purity::Effectful]
+        );
+    }
+
+    #[test]
+    fn test_pure_globals_need_primitive_arguments() {
+        let source = "const o = {}; const n = 1; const s = `${n}`; let x = 2;
+            Number(o); Number(n); Number(s); Number(x); Number(n + 1); Number(-x); Number(n > x); Number(/a/);";
+        let analysis = analyze(source);
+        let ast = parse_js(source, "test.js", &ParserConfig::default()).ast.unwrap();
+        let purities: Vec<Purity> = ast
+            .body
+            .iter()
+            .filter_map(|statement| match statement {
+                Statement::ExpressionStatement { expression, .. } => Some(analysis.expression_purity(expression)),
+                _ => None,
+            })
+            .collect();
+
+        // Objects convert through their own `valueOf`, a `let` may be reassigned to one, and
+        // reading a binding is read-only rather than pure
+        assert_eq!(analysis.purity.primitives, BTreeSet::from(["n".to_string(), "s".to_string()]));
+        assert_eq!(
+            purities,
+            vec![
+                Purity::Effectful,
+                Purity::ReadOnly,
+                Purity::ReadOnly,
+                Purity::Effectful,
+                Purity::ReadOnly,
+                Purity::Effectful,
+                Purity::ReadOnly,
+                Purity::Effectful,
+            ]
+        );
+    }
+}
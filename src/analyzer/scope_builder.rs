@@ -10,19 +10,28 @@
 //! - Enter new scope at functions, blocks, catch clauses, classes, modules
 //! - Maintain symbol bindings for variables, functions, classes, parameters
 //! - Track references to identifiers (read/write/declare)
-//! - Resolve shadowing and redeclarations correctly
-//! - Detect captures: mark when an inner scope closes over an outer variable
+//! - Resolve shadowing and redeclarations correctly, rejecting the ones the language
+//!   forbids: a `let`, `const`, `class` or import sharing a name with another
+//!   declaration in its scope, and duplicate parameters in strict code
+//! - Detect captures: mark when an inner scope closes over an outer variable, and
+//!   whether closures only read it or it is assigned as well
+//! - Record imports, exports, namespace member use and worker entries in the [`ModuleRecord`]
 
+use std::collections::HashSet;
 
+use crate::analyzer::module_record::{
+    DEFAULT_EXPORT_LOCAL, ImportEntry, ImportedName, LocalExport, ModuleRecord, NamespaceUsage, ReExport, worker_entry,
+};
 use crate::analyzer::{
-    AnalysisResult, AnalyzerConfig, ReferenceType, Scope, ScopeId, ScopeTree,
+    AnalysisError, AnalysisResult, AnalyzerConfig, CaptureKind, ReferenceType, Scope, ScopeId, ScopeTree,
     ScopeType, SemanticFlags, SourceLocation, Symbol, SymbolId, SymbolReference, SymbolTable,
     SymbolType, VariableKind,
 };
 use crate::parser::ast_types::{
-    ClassElement, Expression, ForInit, Identifier, ImportSpecifier, Pattern, Program, Statement,
-    VariableDeclarationKind,
+    ClassElement, ExportSpecifier, Expression, ForInit, Identifier, ImportSpecifier, Literal,
+    ObjectPatternProperty, ObjectProperty, Pattern, Program, ProgramSourceType, Statement, StringLiteral, VariableDeclarationKind,
 };
+use crate::parser::SourceSpan;
 
 /// Context for scope analysis traversal
 pub struct ScopeAnalysisContext<'a> {
@@ -35,10 +44,24 @@ pub struct ScopeAnalysisContext<'a> {
     /// Reference to semantic flags
     #[allow(dead_code)]
     pub semantic_flags: &'a mut SemanticFlags,
+    /// Imports and exports found so far
+    pub module_record:  &'a mut ModuleRecord,
     /// Analysis configuration
     pub config:         &'a AnalyzerConfig,
     /// Current source location (for error reporting)
     pub current_location: SourceLocation,
+    /// Innermost non-arrow function, whose `arguments` object an undeclared `arguments`
+    /// refers to
+    pub arguments_scope: Option<ScopeId>,
+    /// Functions hoisted out of nested blocks, whose name a lexical declaration of the
+    /// enclosing scope may reuse
+    pub block_functions: HashSet<SymbolId>,
+    /// Parameters, which are bound like `var` but named as parameters in errors
+    pub parameters:      HashSet<SymbolId>,
+    /// Whether the code being analyzed is strict mode code
+    pub strict:          bool,
+    /// Innermost function found so far around each of `config.dropped_spans`
+    pub dropped_code:    Vec<ScopeId>,
 }
 
 /// Analyzes scopes throughout the AST and builds the scope tree
@@ -49,6 +72,7 @@ pub struct ScopeAnalysisContext<'a> {
 /// * `scope_tree` - Mutable reference to the scope tree being built
 /// * `symbol_table` - Mutable reference to the symbol table being populated
 /// * `semantic_flags` - Mutable reference to semantic flags being collected
+/// * `module_record` - Mutable reference to the module's imports and exports being collected
 /// * `config` - Analysis configuration
 ///
 /// # Returns
@@ -59,23 +83,29 @@ pub fn analyze_scopes(
     scope_tree: &mut ScopeTree,
     symbol_table: &mut SymbolTable,
     semantic_flags: &mut SemanticFlags,
+    module_record: &mut ModuleRecord,
     config: &AnalyzerConfig,
 ) -> AnalysisResult<()> {
-    if config.verbose {
-        println!("Building scope tree...");
-    }
+    tracing::debug!("Building scope tree...");
 
+    let root_scope_id = scope_tree.root_scope_id;
     let mut context = ScopeAnalysisContext {
-        current_scope:    scope_tree.root_scope_id,
+        current_scope:    root_scope_id,
         scope_tree,
         symbol_table,
         semantic_flags,
+        module_record,
         config,
         current_location: SourceLocation {
             line:   1,
             column: 0,
             offset: 0,
         },
+        arguments_scope:  None,
+        block_functions:  HashSet::new(),
+        parameters:       HashSet::new(),
+        strict:           is_strict_program(ast, config),
+        dropped_code:     vec![root_scope_id; config.dropped_spans.len()],
     };
 
     // PHASE 1: Hoist var declarations and function declarations
@@ -87,14 +117,25 @@ pub fn analyze_scopes(
         analyze_statement(statement, &mut context)?;
     }
 
-    if config.verbose {
-        println!(
-            "Scope tree built: {} scopes, {} symbols",
-            context.scope_tree.next_scope_id,
-            context.symbol_table.next_symbol_id
-        );
+    // PHASE 3: Resolve `export { local }` lists, which may precede the declarations
+    resolve_local_exports(&mut context);
+
+    // PHASE 4: Classify captures once every reference is known
+    classify_captures(&mut context);
+
+    // PHASE 5: Flag the functions the parser dropped code from
+    for scope_id in std::mem::take(&mut context.dropped_code) {
+        if let Some(scope) = context.scope_tree.get_scope_mut(scope_id) {
+            scope.has_dropped_code = true;
+        }
     }
 
+    tracing::debug!(
+        "Scope tree built: {} scopes, {} symbols",
+        context.scope_tree.scopes.len(),
+        context.symbol_table.symbols.len()
+    );
+
     Ok(())
 }
 
@@ -116,9 +157,18 @@ fn hoist_declarations(
 fn hoist_statement_declarations(
     statement: &Statement,
     context: &mut ScopeAnalysisContext,
+) -> AnalysisResult<()> {
+    hoist_nested_declarations(statement, false, context)
+}
+
+/// Hoists declarations within a statement; `nested` is set inside blocks and loop bodies
+fn hoist_nested_declarations(
+    statement: &Statement,
+    nested: bool,
+    context: &mut ScopeAnalysisContext,
 ) -> AnalysisResult<()> {
     match statement {
-        Statement::VariableDeclaration { declarations, kind } => {
+        Statement::VariableDeclaration { declarations, kind, .. } => {
             // Only hoist var declarations (let/const are block-scoped)
             if matches!(kind, VariableDeclarationKind::Var) {
                 for declarator in declarations {
@@ -126,37 +176,55 @@ fn hoist_statement_declarations(
                 }
             }
         }
+        Statement::ImportDeclaration { specifiers, source, .. } => {
+            // Import bindings are initialized before any code runs
+            analyze_import_declaration(specifiers, source, context)?;
+        }
+        Statement::ExportNamedDeclaration { declaration: Some(declaration), .. }
+        | Statement::ExportDefaultDeclaration { declaration, .. } => {
+            // `export function` and `export var` hoist like their unexported forms
+            hoist_nested_declarations(declaration, nested, context)?;
+        }
+        Statement::FunctionDeclaration { id: Some(function_id), .. } if nested => {
+            // A function in a block only gets a function-wide binding when no lexical
+            // declaration already holds the name
+            let existing = context.symbol_table.lookup(context.current_scope, &function_id.name);
+            if existing.is_none_or(|symbol_id| !is_lexical_symbol(symbol_id, context)) {
+                let symbol_id = declare_symbol(function_id, SymbolType::Function, context.current_scope, context)?;
+                context.block_functions.insert(symbol_id);
+            }
+        }
         Statement::FunctionDeclaration { id, .. } => {
             // Function declarations are fully hoisted
             if let Some(function_id) = id {
                 declare_symbol(
-                    &function_id.name,
+                    function_id,
                     SymbolType::Function,
                     context.current_scope,
                     context,
                 )?;
             }
         }
-        Statement::BlockStatement { body } => {
+        Statement::BlockStatement { body, .. } => {
             // Recursively hoist within block statements
             for stmt in body {
-                hoist_statement_declarations(stmt, context)?;
+                hoist_nested_declarations(stmt, true, context)?;
             }
         }
         Statement::IfStatement { consequent, alternate, .. } => {
             // Hoist within if statement branches
-            hoist_statement_declarations(consequent, context)?;
+            hoist_nested_declarations(consequent, true, context)?;
             if let Some(alt) = alternate {
-                hoist_statement_declarations(alt, context)?;
+                hoist_nested_declarations(alt, true, context)?;
             }
         }
         Statement::WhileStatement { body, .. } => {
             // Hoist within while loop body
-            hoist_statement_declarations(body, context)?;
+            hoist_nested_declarations(body, true, context)?;
         }
-        Statement::ForStatement { body, .. } => {
+        Statement::ForStatement { body, .. } | Statement::ForOfStatement { body, .. } => {
             // Hoist within for loop body
-            hoist_statement_declarations(body, context)?;
+            hoist_nested_declarations(body, true, context)?;
         }
         _ => {
             // Other statements don't participate in hoisting
@@ -173,14 +241,27 @@ fn hoist_pattern_declaration(
     match pattern {
         Pattern::Identifier(id) => {
             declare_symbol(
-                &id.name,
+                id,
                 SymbolType::Variable { kind: VariableKind::Var },
                 context.current_scope,
                 context,
             )?;
         }
-        // TODO: Handle other pattern types (destructuring)
-        _ => {}
+        Pattern::ArrayPattern { elements } => {
+            for element in elements.iter().flatten() {
+                hoist_pattern_declaration(element, context)?;
+            }
+        }
+        Pattern::ObjectPattern { properties } => {
+            for property in properties {
+                match property {
+                    ObjectPatternProperty::Property { value, .. } => hoist_pattern_declaration(value, context)?,
+                    ObjectPatternProperty::RestElement { argument } => hoist_pattern_declaration(argument, context)?,
+                }
+            }
+        }
+        Pattern::AssignmentPattern { left, .. } => hoist_pattern_declaration(left, context)?,
+        Pattern::RestElement { argument } => hoist_pattern_declaration(argument, context)?,
     }
     Ok(())
 }
@@ -191,7 +272,7 @@ fn analyze_statement(
     context: &mut ScopeAnalysisContext,
 ) -> AnalysisResult<()> {
     match statement {
-        Statement::VariableDeclaration { declarations, kind } => {
+        Statement::VariableDeclaration { declarations, kind, .. } => {
             analyze_variable_declaration(declarations, kind, context)
         }
         Statement::FunctionDeclaration {
@@ -200,17 +281,20 @@ fn analyze_statement(
             body,
             is_async: _,
             is_generator: _,
-        } => analyze_function_declaration(id, params, body, context),
+            span,
+        } => analyze_function_declaration(id, params, body, span.as_ref(), context),
         Statement::ClassDeclaration {
             id,
             super_class,
             body,
-        } => analyze_class_declaration(id, super_class, body, context),
-        Statement::ExpressionStatement { expression } => {
+            decorators,
+            ..
+        } => analyze_class_declaration(id, super_class, body, decorators, context),
+        Statement::ExpressionStatement { expression, .. } => {
             analyze_expression(expression, context)
         }
-        Statement::BlockStatement { body } => analyze_block_statement(body, context),
-        Statement::ReturnStatement { argument } => {
+        Statement::BlockStatement { body, .. } => analyze_block_statement(body, context),
+        Statement::ReturnStatement { argument, .. } => {
             if let Some(expr) = argument {
                 analyze_expression(expr, context)?;
             }
@@ -220,6 +304,7 @@ fn analyze_statement(
             test,
             consequent,
             alternate,
+            ..
         } => {
             analyze_expression(test, context)?;
             analyze_statement(consequent, context)?;
@@ -228,7 +313,7 @@ fn analyze_statement(
             }
             Ok(())
         }
-        Statement::WhileStatement { test, body } => {
+        Statement::WhileStatement { test, body, .. } => {
             analyze_expression(test, context)?;
             analyze_statement(body, context)
         }
@@ -237,22 +322,71 @@ fn analyze_statement(
             test,
             update,
             body,
+            ..
         } => analyze_for_statement(init, test, update, body, context),
-        Statement::ImportDeclaration { specifiers, source: _ } => {
-            analyze_import_declaration(specifiers, context)
+        Statement::ForOfStatement { left, right, body, .. } => analyze_for_of_statement(left, right, body, context),
+        // Declared while hoisting
+        Statement::ImportDeclaration { source, .. } => {
+            request_module(source, context);
+            Ok(())
         }
         Statement::ExportNamedDeclaration {
             declaration,
-            specifiers: _,
-            source: _,
+            specifiers,
+            source,
+            ..
         } => {
             if let Some(decl) = declaration {
                 analyze_statement(decl, context)?;
                 // Mark exported symbols
-                mark_last_declaration_as_exported(context);
+                for name in declared_names(decl) {
+                    mark_symbol_as_exported(&name, context);
+                    context.module_record.local_exports.push(LocalExport { exported: name.clone(), local: name });
+                }
+            }
+            if let Some(source) = source {
+                request_module(source, context);
+            }
+            for ExportSpecifier::ExportSpecifier { local, exported } in specifiers {
+                match source {
+                    Some(source) => context.module_record.re_exports.push(ReExport {
+                        exported: exported.name.clone(),
+                        imported: ImportedName::Named(local.name.clone()),
+                        source:   source.value.clone(),
+                    }),
+                    None => context.module_record.local_exports.push(LocalExport {
+                        exported: exported.name.clone(),
+                        local:    local.name.clone(),
+                    }),
+                }
             }
             Ok(())
         }
+        Statement::ExportDefaultDeclaration { declaration, .. } => {
+            analyze_statement(declaration, context)?;
+            let local = match declared_names(declaration).pop() {
+                Some(name) => {
+                    mark_symbol_as_exported(&name, context);
+                    name
+                }
+                None => DEFAULT_EXPORT_LOCAL.to_string(),
+            };
+            context.module_record.local_exports.push(LocalExport { exported: "default".to_string(), local });
+            Ok(())
+        }
+        Statement::ExportAllDeclaration { exported, source, .. } => {
+            request_module(source, context);
+            match exported {
+                Some(exported) => context.module_record.re_exports.push(ReExport {
+                    exported: exported.name.clone(),
+                    imported: ImportedName::Namespace,
+                    source:   source.value.clone(),
+                }),
+                None => context.module_record.star_exports.push(source.value.clone()),
+            }
+            Ok(())
+        }
+        Statement::EmptyStatement | Statement::DebuggerStatement => Ok(()),
     }
 }
 
@@ -265,13 +399,21 @@ fn analyze_variable_declaration(
     let var_kind = match kind {
         VariableDeclarationKind::Var => VariableKind::Var,
         VariableDeclarationKind::Let => VariableKind::Let,
-        VariableDeclarationKind::Const => VariableKind::Const,
+        // `using` bindings can't be reassigned either
+        VariableDeclarationKind::Const | VariableDeclarationKind::Using | VariableDeclarationKind::AwaitUsing => {
+            VariableKind::Const
+        }
     };
 
     for declarator in declarations {
         // For var declarations, the symbol was already hoisted
         // For let/const, we need to declare it now
-        if !matches!(kind, VariableDeclarationKind::Var) {
+        if matches!(kind, VariableDeclarationKind::Var) {
+            for id in pattern_identifiers(&declarator.id) {
+                check_var_crosses_lexical(id, context)?;
+            }
+            analyze_pattern_defaults(&declarator.id, context)?;
+        } else {
             analyze_pattern_binding(&declarator.id, var_kind.clone(), context)?;
         }
 
@@ -289,16 +431,21 @@ fn analyze_function_declaration(
     _id: &Option<Identifier>,
     params: &[Pattern],
     body: &crate::parser::ast_types::BlockStatement,
+    span: Option<&SourceSpan>,
     context: &mut ScopeAnalysisContext,
 ) -> AnalysisResult<()> {
     // Function name was already hoisted, so no need to re-declare
     
     // Create new function scope
     let function_scope_id = create_scope(ScopeType::Function, Some(context.current_scope), context);
+    claim_dropped_code(function_scope_id, span, context);
 
     // Enter function scope
     let previous_scope = context.current_scope;
     context.current_scope = function_scope_id;
+    let previous_arguments_scope = context.arguments_scope.replace(function_scope_id);
+    let previous_strict = context.strict;
+    context.strict |= Statement::is_strict_body(&body.body);
 
     // First, hoist all function declarations and var declarations in this function scope
     for statement in &body.body {
@@ -306,9 +453,7 @@ fn analyze_function_declaration(
     }
 
     // Bind parameters in function scope
-    for param in params {
-        analyze_pattern_binding(param, VariableKind::Var, context)?; // Parameters are var-like
-    }
+    bind_parameters(params, false, context)?;
 
     // Analyze function body
     for statement in &body.body {
@@ -317,6 +462,9 @@ fn analyze_function_declaration(
 
     // Restore previous scope
     context.current_scope = previous_scope;
+    context.arguments_scope = previous_arguments_scope;
+    context.strict = previous_strict;
+    pin_parameters_if_arguments_used(function_scope_id, params, context);
 
     Ok(())
 }
@@ -326,16 +474,27 @@ fn analyze_class_declaration(
     id: &Option<Identifier>,
     super_class: &Option<Box<Expression>>,
     body: &crate::parser::ast_types::ClassBody,
+    decorators: &[Expression],
     context: &mut ScopeAnalysisContext,
 ) -> AnalysisResult<()> {
+    for decorator in decorators {
+        analyze_expression(decorator, context)?;
+    }
+
+    let is_decorated = !decorators.is_empty() || body.body.iter().any(|element| !element.decorators().is_empty());
+
     // Bind class name in current scope
     if let Some(class_id) = id {
-        declare_symbol(
-            &class_id.name,
+        let symbol_id = declare_symbol(
+            class_id,
             SymbolType::Class,
             context.current_scope,
             context,
         )?;
+        // Decorators can read the class name as `target.name`
+        if is_decorated && let Some(symbol) = context.symbol_table.symbols.get_mut(symbol_id) {
+            symbol.is_renamable = false;
+        }
     }
 
     // Analyze super class expression
@@ -345,10 +504,14 @@ fn analyze_class_declaration(
 
     // Create new class scope
     let class_scope_id = create_scope(ScopeType::Class, Some(context.current_scope), context);
+    if is_decorated && let Some(scope) = context.scope_tree.get_scope_mut(class_scope_id) {
+        scope.is_decorated = true;
+    }
 
-    // Enter class scope
+    // Enter class scope; class bodies are always strict
     let previous_scope = context.current_scope;
     context.current_scope = class_scope_id;
+    let previous_strict = std::mem::replace(&mut context.strict, true);
 
     // Analyze class body
     for element in &body.body {
@@ -357,6 +520,7 @@ fn analyze_class_declaration(
 
     // Restore previous scope
     context.current_scope = previous_scope;
+    context.strict = previous_strict;
 
     Ok(())
 }
@@ -372,7 +536,11 @@ fn analyze_class_element(
             value,
             is_static: _,
             is_private: _,
+            decorators,
         } => {
+            for decorator in decorators {
+                analyze_expression(decorator, context)?;
+            }
             if let Some(expr) = value {
                 analyze_expression(expr, context)?;
             }
@@ -384,7 +552,11 @@ fn analyze_class_element(
             kind: _,
             is_static: _,
             is_private: _,
+            decorators,
         } => {
+            for decorator in decorators {
+                analyze_expression(decorator, context)?;
+            }
             // Analyze method as function
             analyze_function_expression(value, context)
         }
@@ -396,15 +568,11 @@ fn analyze_block_statement(
     body: &[Statement],
     context: &mut ScopeAnalysisContext,
 ) -> AnalysisResult<()> {
-    // Check if we need a new block scope (if there are let/const declarations)
-    let needs_block_scope = body.iter().any(|stmt| {
-        matches!(
-            stmt,
-            Statement::VariableDeclaration {
-                kind: VariableDeclarationKind::Let | VariableDeclarationKind::Const,
-                ..
-            }
-        )
+    // Check if we need a new block scope (if there are let/const/class declarations)
+    let needs_block_scope = body.iter().any(|stmt| match stmt {
+        Statement::VariableDeclaration { kind, .. } => !matches!(kind, VariableDeclarationKind::Var),
+        Statement::ClassDeclaration { .. } => true,
+        _ => false,
     });
 
     if needs_block_scope {
@@ -448,10 +616,7 @@ fn analyze_for_statement(
     // Check if we need a new scope for loop variables
     let needs_loop_scope = matches!(
         init,
-        Some(ForInit::VariableDeclaration {
-            kind: VariableDeclarationKind::Let | VariableDeclarationKind::Const,
-            ..
-        })
+        Some(ForInit::VariableDeclaration { kind, .. }) if !matches!(kind, VariableDeclarationKind::Var)
     );
 
     if needs_loop_scope {
@@ -499,6 +664,36 @@ fn analyze_for_statement(
     Ok(())
 }
 
+/// Analyzes a for-of loop (including `for await`)
+fn analyze_for_of_statement(
+    left: &ForInit,
+    right: &Expression,
+    body: &Statement,
+    context: &mut ScopeAnalysisContext,
+) -> AnalysisResult<()> {
+    // The iterated expression is evaluated outside the loop bindings
+    analyze_expression(right, context)?;
+
+    let needs_loop_scope = matches!(
+        left,
+        ForInit::VariableDeclaration { kind, .. } if !matches!(kind, VariableDeclarationKind::Var)
+    );
+    let previous_scope = context.current_scope;
+    if needs_loop_scope {
+        context.current_scope = create_scope(ScopeType::Block, Some(context.current_scope), context);
+    }
+
+    match left {
+        // Each iteration assigns the next value to the target
+        ForInit::Expression(Expression::Identifier(id)) => reference_symbol(&id.name, ReferenceType::Write, context),
+        _ => analyze_for_init(left, context)?,
+    }
+    analyze_statement(body, context)?;
+
+    context.current_scope = previous_scope;
+    Ok(())
+}
+
 /// Analyzes for loop initialization
 fn analyze_for_init(init: &ForInit, context: &mut ScopeAnalysisContext) -> AnalysisResult<()> {
     match init {
@@ -512,20 +707,25 @@ fn analyze_for_init(init: &ForInit, context: &mut ScopeAnalysisContext) -> Analy
 /// Analyzes import declarations and creates import symbols
 fn analyze_import_declaration(
     specifiers: &[ImportSpecifier],
+    source: &StringLiteral,
     context: &mut ScopeAnalysisContext,
 ) -> AnalysisResult<()> {
     for specifier in specifiers {
-        match specifier {
-            ImportSpecifier::ImportDefaultSpecifier { local } => {
-                declare_symbol(&local.name, SymbolType::Import, context.current_scope, context)?;
-            }
+        let (local, imported) = match specifier {
+            ImportSpecifier::ImportDefaultSpecifier { local } => (local, ImportedName::Named("default".to_string())),
             ImportSpecifier::ImportNamespaceSpecifier { local } => {
-                declare_symbol(&local.name, SymbolType::Import, context.current_scope, context)?;
+                context.module_record.namespaces.insert(local.name.clone(), NamespaceUsage::default());
+                (local, ImportedName::Namespace)
             }
-            ImportSpecifier::ImportSpecifier { imported: _, local } => {
-                declare_symbol(&local.name, SymbolType::Import, context.current_scope, context)?;
-            }
-        }
+            ImportSpecifier::ImportSpecifier { imported, local } => (local, ImportedName::Named(imported.name.clone())),
+        };
+        declare_symbol(local, SymbolType::Import, context.current_scope, context)?;
+        context.module_record.imports.push(ImportEntry {
+            local:         local.name.clone(),
+            imported,
+            source:        source.value.clone(),
+            is_referenced: false,
+        });
     }
     Ok(())
 }
@@ -535,13 +735,33 @@ fn analyze_expression(expression: &Expression, context: &mut ScopeAnalysisContex
     match expression {
         Expression::Identifier(id) => {
             reference_symbol(&id.name, ReferenceType::Read, context);
+            record_namespace_use(&id.name, None, context);
             Ok(())
         }
         Expression::BinaryExpression { left, right, .. } => {
             analyze_expression(left, context)?;
             analyze_expression(right, context)
         }
-        Expression::UnaryExpression { argument, .. } => analyze_expression(argument, context),
+        Expression::UnaryExpression { argument, .. } | Expression::SpreadElement { argument } => {
+            analyze_expression(argument, context)
+        }
+        Expression::ChainExpression { expression } => analyze_expression(expression, context),
+        Expression::YieldExpression { argument: Some(argument), .. } => analyze_expression(argument, context),
+        Expression::AwaitExpression { argument } => {
+            if !is_inside_function(context) {
+                context.module_record.has_top_level_await = true;
+            }
+            analyze_expression(argument, context)
+        }
+        Expression::UpdateExpression { argument, .. } => {
+            // `x++` assigns `x` as well as reading it
+            if let Expression::Identifier(id) = argument.as_ref() {
+                reference_symbol(&id.name, ReferenceType::Write, context);
+                Ok(())
+            } else {
+                analyze_expression(argument, context)
+            }
+        }
         Expression::AssignmentExpression {
             left,
             right,
@@ -555,7 +775,7 @@ fn analyze_expression(expression: &Expression, context: &mut ScopeAnalysisContex
             }
             analyze_expression(right, context)
         }
-        Expression::CallExpression { callee, arguments } => {
+        Expression::CallExpression { callee, arguments, .. } => {
             if let Expression::Identifier(id) = callee.as_ref() {
                 reference_symbol(&id.name, ReferenceType::Call, context);
             } else {
@@ -570,8 +790,15 @@ fn analyze_expression(expression: &Expression, context: &mut ScopeAnalysisContex
         Expression::ArrowFunctionExpression { params, body, .. } => {
             analyze_arrow_function(params, body, context)
         }
-        Expression::MemberExpression { object, property, .. } => {
-            analyze_expression(object, context)?;
+        Expression::MemberExpression { object, property, computed, .. } => {
+            // `ns.member` reads one export of a namespace import; other uses read them all
+            match (object.as_ref(), static_member_name(property, *computed)) {
+                (Expression::Identifier(id), Some(member)) => {
+                    reference_symbol(&id.name, ReferenceType::Read, context);
+                    record_namespace_use(&id.name, Some(member), context);
+                }
+                _ => analyze_expression(object, context)?,
+            }
             if let Expression::Identifier(id) = property.as_ref() {
                 reference_symbol(&id.name, ReferenceType::PropertyAccess, context);
             } else {
@@ -579,6 +806,64 @@ fn analyze_expression(expression: &Expression, context: &mut ScopeAnalysisContex
             }
             Ok(())
         }
+        Expression::ArrayExpression { elements } => {
+            for element in elements.iter().flatten() {
+                analyze_expression(element, context)?;
+            }
+            Ok(())
+        }
+        Expression::ObjectExpression { properties } => {
+            for property in properties {
+                match property {
+                    ObjectProperty::Property { value, .. } => analyze_expression(value, context)?,
+                    ObjectProperty::SpreadElement { argument } => analyze_expression(argument, context)?,
+                }
+            }
+            Ok(())
+        }
+        Expression::TemplateLiteral { expressions, .. } => {
+            for expr in expressions {
+                analyze_expression(expr, context)?;
+            }
+            Ok(())
+        }
+        Expression::TaggedTemplateExpression { tag, expressions, .. } => {
+            // The tag is called with the template's strings and values
+            if let Expression::Identifier(id) = tag.as_ref() {
+                reference_symbol(&id.name, ReferenceType::Call, context);
+            } else {
+                analyze_expression(tag, context)?;
+            }
+            for expr in expressions {
+                analyze_expression(expr, context)?;
+            }
+            Ok(())
+        }
+        Expression::ConditionalExpression { test, consequent, alternate } => {
+            analyze_expression(test, context)?;
+            analyze_expression(consequent, context)?;
+            analyze_expression(alternate, context)
+        }
+        Expression::ImportExpression { source, options } => {
+            analyze_expression(source, context)?;
+            if let Some(options) = options {
+                analyze_expression(options, context)?;
+            }
+            Ok(())
+        }
+        Expression::NewExpression { callee, arguments } => {
+            if let Some(specifier) = worker_entry(expression)
+                && let Expression::Identifier(id) = callee.as_ref()
+                && resolve_symbol(&id.name, context.current_scope, context).is_none()
+            {
+                record_worker(specifier, context);
+            }
+            analyze_expression(callee, context)?;
+            for arg in arguments {
+                analyze_expression(arg, context)?;
+            }
+            Ok(())
+        }
         Expression::Literal(_) => Ok(()), // Literals don't affect scope
         _ => Ok(()), // Handle other expression types as needed
     }
@@ -591,10 +876,14 @@ fn analyze_function_expression(
 ) -> AnalysisResult<()> {
     // Create new function scope
     let function_scope_id = create_scope(ScopeType::Function, Some(context.current_scope), context);
+    claim_dropped_code(function_scope_id, func_expr.span.as_ref(), context);
 
     // Enter function scope
     let previous_scope = context.current_scope;
     context.current_scope = function_scope_id;
+    let previous_arguments_scope = context.arguments_scope.replace(function_scope_id);
+    let previous_strict = context.strict;
+    context.strict |= Statement::is_strict_body(&func_expr.body.body);
 
     // First, hoist all function declarations and var declarations in this function scope
     for statement in &func_expr.body.body {
@@ -602,9 +891,7 @@ fn analyze_function_expression(
     }
 
     // Bind parameters
-    for param in &func_expr.params {
-        analyze_pattern_binding(param, VariableKind::Var, context)?;
-    }
+    bind_parameters(&func_expr.params, false, context)?;
 
     // Analyze function body
     for statement in &func_expr.body.body {
@@ -613,11 +900,17 @@ fn analyze_function_expression(
 
     // Restore previous scope
     context.current_scope = previous_scope;
+    context.arguments_scope = previous_arguments_scope;
+    context.strict = previous_strict;
+    pin_parameters_if_arguments_used(function_scope_id, &func_expr.params, context);
 
     Ok(())
 }
 
 /// Analyzes arrow function expressions
+///
+/// Arrows have no `arguments` of their own, so `arguments` inside one still refers to
+/// the enclosing function's.
 fn analyze_arrow_function(
     params: &[Pattern],
     body: &crate::parser::ast_types::ArrowFunctionBody,
@@ -630,17 +923,18 @@ fn analyze_arrow_function(
     let previous_scope = context.current_scope;
     context.current_scope = function_scope_id;
 
+    let previous_strict = context.strict;
+
     // Hoist declarations if it's a block statement
     if let crate::parser::ast_types::ArrowFunctionBody::BlockStatement(block) = body {
+        context.strict |= Statement::is_strict_body(&block.body);
         for statement in &block.body {
             hoist_statement_declarations(statement, context)?;
         }
     }
 
     // Bind parameters
-    for param in params {
-        analyze_pattern_binding(param, VariableKind::Var, context)?;
-    }
+    bind_parameters(params, true, context)?;
 
     // Analyze arrow function body
     match body {
@@ -656,6 +950,7 @@ fn analyze_arrow_function(
 
     // Restore previous scope
     context.current_scope = previous_scope;
+    context.strict = previous_strict;
 
     Ok(())
 }
@@ -669,15 +964,119 @@ fn analyze_pattern_binding(
     match pattern {
         Pattern::Identifier(id) => {
             declare_symbol(
-                &id.name,
+                id,
                 SymbolType::Variable { kind: var_kind },
                 context.current_scope,
                 context,
             )?;
             Ok(())
         }
-        // TODO: Handle other pattern types (destructuring, etc.)
-        _ => Ok(()),
+        Pattern::ArrayPattern { elements } => {
+            for element in elements.iter().flatten() {
+                analyze_pattern_binding(element, var_kind.clone(), context)?;
+            }
+            Ok(())
+        }
+        Pattern::ObjectPattern { properties } => {
+            for property in properties {
+                match property {
+                    ObjectPatternProperty::Property { value, .. } => {
+                        analyze_pattern_binding(value, var_kind.clone(), context)?
+                    }
+                    ObjectPatternProperty::RestElement { argument } => {
+                        analyze_pattern_binding(argument, var_kind.clone(), context)?
+                    }
+                }
+            }
+            Ok(())
+        }
+        Pattern::AssignmentPattern { left, right } => {
+            analyze_pattern_binding(left, var_kind, context)?;
+            analyze_expression(right, context)
+        }
+        Pattern::RestElement { argument } => analyze_pattern_binding(argument, var_kind, context),
+    }
+}
+
+/// Whether the top level of `ast` is strict mode code
+///
+/// Scripts are parsed with the module grammar too, so a program only counts as a module
+/// once it imports or exports something.
+fn is_strict_program(ast: &Program, config: &AnalyzerConfig) -> bool {
+    let is_module = matches!(ast.source_type, ProgramSourceType::Module)
+        && ast.body.iter().any(|statement| {
+            matches!(
+                statement,
+                Statement::ImportDeclaration { .. }
+                    | Statement::ExportNamedDeclaration { .. }
+                    | Statement::ExportDefaultDeclaration { .. }
+                    | Statement::ExportAllDeclaration { .. }
+            )
+        });
+    config.strict_mode || is_module || Statement::is_strict_body(&ast.body)
+}
+
+/// Binds the parameters of the function whose scope is current
+///
+/// Parameters are var-like, so a `var` in the body may repeat one. The same name twice
+/// in the list is only allowed in sloppy-mode functions with a plain list of names.
+fn bind_parameters(params: &[Pattern], is_arrow: bool, context: &mut ScopeAnalysisContext) -> AnalysisResult<()> {
+    let is_simple = params.iter().all(|param| matches!(param, Pattern::Identifier(_)));
+    if context.strict || is_arrow || !is_simple {
+        let mut seen = HashSet::new();
+        for id in params.iter().flat_map(pattern_identifiers) {
+            if !seen.insert(id.name.as_str()) {
+                let reason = if context.strict {
+                    "duplicate parameter names are not allowed in strict mode"
+                } else if is_arrow {
+                    "arrow functions cannot repeat a parameter name"
+                } else {
+                    "a parameter list with defaults, rest or destructuring cannot repeat a name"
+                };
+                return Err(AnalysisError::Redeclaration {
+                    identifier: id.name.clone(),
+                    reason:     reason.to_string(),
+                    span:       id.span.clone(),
+                });
+            }
+        }
+    }
+
+    for param in params {
+        analyze_pattern_binding(param, VariableKind::Var, context)?;
+    }
+    for id in params.iter().flat_map(pattern_identifiers) {
+        if let Some(symbol_id) = context.symbol_table.lookup(context.current_scope, &id.name) {
+            context.parameters.insert(symbol_id);
+        }
+    }
+    Ok(())
+}
+
+/// Analyzes the default values of a pattern whose bindings were already hoisted
+fn analyze_pattern_defaults(pattern: &Pattern, context: &mut ScopeAnalysisContext) -> AnalysisResult<()> {
+    match pattern {
+        Pattern::Identifier(_) => Ok(()),
+        Pattern::ArrayPattern { elements } => {
+            for element in elements.iter().flatten() {
+                analyze_pattern_defaults(element, context)?;
+            }
+            Ok(())
+        }
+        Pattern::ObjectPattern { properties } => {
+            for property in properties {
+                match property {
+                    ObjectPatternProperty::Property { value, .. } => analyze_pattern_defaults(value, context)?,
+                    ObjectPatternProperty::RestElement { argument } => analyze_pattern_defaults(argument, context)?,
+                }
+            }
+            Ok(())
+        }
+        Pattern::AssignmentPattern { left, right } => {
+            analyze_pattern_defaults(left, context)?;
+            analyze_expression(right, context)
+        }
+        Pattern::RestElement { argument } => analyze_pattern_defaults(argument, context),
     }
 }
 
@@ -687,7 +1086,7 @@ fn create_scope(
     parent_id: Option<ScopeId>,
     context: &mut ScopeAnalysisContext,
 ) -> ScopeId {
-    let scope_id = context.scope_tree.next_id();
+    let scope_id = context.scope_tree.scopes.next_id();
 
     let scope = Scope {
         id: scope_id,
@@ -696,9 +1095,12 @@ fn create_scope(
         children: Vec::new(),
         bindings: Vec::new(),
         is_safe: true,
+        uses_arguments: false,
+        is_decorated: false,
+        has_dropped_code: false,
     };
 
-    context.scope_tree.scopes.insert(scope_id, scope);
+    context.scope_tree.scopes.push(scope);
 
     // Add as child to parent scope
     if let Some(parent) = parent_id
@@ -709,67 +1111,165 @@ fn create_scope(
     scope_id
 }
 
+/// Records the function `scope_id` spanning `span` as the innermost one found so far
+/// around each dropped span it contains
+///
+/// Functions are entered outermost first, so the last function to claim a span is the
+/// innermost. Arrow functions have no span and leave the claim to the function around
+/// them.
+fn claim_dropped_code(scope_id: ScopeId, span: Option<&SourceSpan>, context: &mut ScopeAnalysisContext) {
+    let Some(span) = span else {
+        return;
+    };
+    for (dropped, owner) in context.config.dropped_spans.iter().zip(&mut context.dropped_code) {
+        if span.start <= dropped.start && dropped.end <= span.end {
+            *owner = scope_id;
+        }
+    }
+}
+
 /// Declares a new symbol in the specified scope
+///
+/// # Errors
+///
+/// Returns `AnalysisError::Redeclaration` if the scope already binds the name and
+/// either declaration is a `let`, `const`, `class` or import
 fn declare_symbol(
-    name: &str,
+    id: &Identifier,
     symbol_type: SymbolType,
     scope_id: ScopeId,
     context: &mut ScopeAnalysisContext,
 ) -> AnalysisResult<SymbolId> {
+    let name = id.name.as_str();
     // Check if symbol already exists in this scope (for hoisting)
-    if let Some(scope_bindings) = context.symbol_table.scope_bindings.get(&scope_id)
-        && let Some(&existing_symbol_id) = scope_bindings.get(name) {
-            // Symbol already exists (hoisted), return existing ID
-            if context.config.verbose {
-                println!("Symbol '{}' already declared in scope {} (hoisted)", name, scope_id);
-            }
-            return Ok(existing_symbol_id);
-        }
+    if let Some(existing_symbol_id) = context.symbol_table.lookup(scope_id, name) {
+        check_redeclaration(id, &symbol_type, existing_symbol_id, context)?;
+        // Symbol already exists (hoisted), return existing ID
+        tracing::trace!("Symbol '{}' already declared in scope {} (hoisted)", name, scope_id);
+        return Ok(existing_symbol_id);
+    }
 
-    let symbol_id = context.symbol_table.next_id();
+    let symbol_id = context.symbol_table.symbols.next_id();
+    let is_kept = context.config.kept_names.iter().any(|kept| kept == name);
 
     let symbol = Symbol {
         id: symbol_id,
-        name: name.to_string(),
+        name: context.symbol_table.names.intern(name),
         symbol_type,
         scope_id,
         references: Vec::new(),
         is_captured: false,
         is_exported: false,
-        is_renamable: true,
+        is_renamable: !is_kept,
+        is_kept,
+        capture: CaptureKind::None,
     };
 
-    context.symbol_table.symbols.insert(symbol_id, symbol);
-
-    // Add to scope bindings
-    context
-        .symbol_table
-        .scope_bindings
-        .entry(scope_id)
-        .or_default()
-        .insert(name.to_string(), symbol_id);
+    // Add to the symbol arena and the scope's name bindings
+    context.symbol_table.declare(symbol);
 
     // Add to scope's bindings list
     if let Some(scope) = context.scope_tree.get_scope_mut(scope_id) {
         scope.bindings.push(symbol_id);
     }
 
-    if context.config.verbose {
-        println!("Declared symbol '{}' in scope {}", name, scope_id);
-    }
+    tracing::trace!("Declared symbol '{}' in scope {}", name, scope_id);
 
     Ok(symbol_id)
 }
 
+/// Fails if declaring `id` as `symbol_type` conflicts with the symbol already bound to
+/// its name
+///
+/// `var`, functions and parameters may share a name with each other, and a function
+/// hoisted out of a block gives way to a lexical declaration of the same name.
+fn check_redeclaration(
+    id: &Identifier,
+    symbol_type: &SymbolType,
+    existing_symbol_id: SymbolId,
+    context: &ScopeAnalysisContext,
+) -> AnalysisResult<()> {
+    if !is_lexical(symbol_type) && !is_lexical_symbol(existing_symbol_id, context) {
+        return Ok(());
+    }
+    if context.block_functions.contains(&existing_symbol_id) {
+        return Ok(());
+    }
+    let Some(existing) = context.symbol_table.symbols.get(existing_symbol_id) else {
+        return Ok(());
+    };
+    let existing_kind = if context.parameters.contains(&existing_symbol_id) {
+        "parameter"
+    } else {
+        declaration_kind(&existing.symbol_type)
+    };
+    Err(AnalysisError::Redeclaration {
+        identifier: id.name.clone(),
+        reason:     format!(
+            "{} declaration conflicts with the earlier {} of the same name in this scope",
+            declaration_kind(symbol_type),
+            existing_kind
+        ),
+        span:       id.span.clone(),
+    })
+}
+
+/// Fails if a `var` named `id`, hoisted from the current scope to its function, passes a
+/// block or loop head that declares the name with `let`, `const` or `class`
+///
+/// Conflicts within the function scope itself are found when the `var` is hoisted.
+fn check_var_crosses_lexical(id: &Identifier, context: &ScopeAnalysisContext) -> AnalysisResult<()> {
+    let mut scope_id = context.current_scope;
+    while let Some(scope) = context.scope_tree.get_scope(scope_id)
+        && matches!(scope.scope_type, ScopeType::Block | ScopeType::Catch | ScopeType::With)
+    {
+        if let Some(existing_symbol_id) = context.symbol_table.lookup(scope_id, &id.name)
+            && is_lexical_symbol(existing_symbol_id, context)
+        {
+            return check_redeclaration(id, &SymbolType::Variable { kind: VariableKind::Var }, existing_symbol_id, context);
+        }
+        let Some(parent_id) = scope.parent_id else {
+            break;
+        };
+        scope_id = parent_id;
+    }
+    Ok(())
+}
+
+/// Whether a declaration of `symbol_type` cannot share its name within a scope
+fn is_lexical(symbol_type: &SymbolType) -> bool {
+    matches!(
+        symbol_type,
+        SymbolType::Variable { kind: VariableKind::Let | VariableKind::Const } | SymbolType::Class | SymbolType::Import
+    )
+}
+
+fn is_lexical_symbol(symbol_id: SymbolId, context: &ScopeAnalysisContext) -> bool {
+    context.symbol_table.symbols.get(symbol_id).is_some_and(|symbol| is_lexical(&symbol.symbol_type))
+}
+
+/// The keyword or kind of declaration that binds a symbol of `symbol_type`, for errors
+fn declaration_kind(symbol_type: &SymbolType) -> &'static str {
+    match symbol_type {
+        SymbolType::Variable { kind: VariableKind::Var } => "`var`",
+        SymbolType::Variable { kind: VariableKind::Let } => "`let`",
+        SymbolType::Variable { kind: VariableKind::Const } => "`const`",
+        SymbolType::Function => "function",
+        SymbolType::Class => "class",
+        SymbolType::Parameter => "parameter",
+        SymbolType::Import => "import",
+        SymbolType::Export => "export",
+        SymbolType::Property => "property",
+    }
+}
+
 /// Records a reference to a symbol
 fn reference_symbol(
     name: &str,
     reference_type: ReferenceType,
     context: &mut ScopeAnalysisContext,
 ) {
-    if context.config.verbose {
-        println!("[DEBUG] Recording reference to symbol '{}' of type {:?}", name, reference_type);
-    }
+    tracing::trace!("Recording reference to symbol '{}' of type {:?}", name, reference_type);
     
     // Find the symbol by resolving through scope chain
     if let Some(symbol_id) = resolve_symbol(name, context.current_scope, context) {
@@ -779,23 +1279,43 @@ fn reference_symbol(
             scope_id: context.current_scope,
         };
 
-        if let Some(symbol) = context.symbol_table.symbols.get_mut(&symbol_id) {
+        if let Some(symbol) = context.symbol_table.symbols.get_mut(symbol_id) {
             symbol.references.push(reference);
             
-            if context.config.verbose {
-                println!("[DEBUG] Added reference to symbol '{}' (id: {}), total references: {}", name, symbol_id, symbol.references.len());
-            }
+            tracing::trace!("Added reference to symbol '{}' (id: {}), total references: {}", name, symbol_id, symbol.references.len());
 
             // Check for closure capture
             if symbol.scope_id != context.current_scope {
                 symbol.is_captured = true;
-                if context.config.verbose {
-                    println!("Symbol '{}' captured by closure", name);
-                }
+                tracing::trace!("Symbol '{}' captured by closure", name);
             }
         }
-    } else if context.config.verbose {
-        println!("Unresolved symbol reference: '{}'", name);
+    } else {
+        if name == "arguments"
+            && let Some(scope) = context.arguments_scope.and_then(|id| context.scope_tree.get_scope_mut(id))
+        {
+            scope.uses_arguments = true;
+        }
+        tracing::trace!("Unresolved symbol reference: '{}'", name);
+    }
+}
+
+/// Keeps the parameters of a function that uses `arguments` from being renamed, since
+/// the parameter list is then observable through the arguments object
+fn pin_parameters_if_arguments_used(scope_id: ScopeId, params: &[Pattern], context: &mut ScopeAnalysisContext) {
+    if !context.scope_tree.get_scope(scope_id).is_some_and(|scope| scope.uses_arguments) {
+        return;
+    }
+    let mut names = Vec::new();
+    params.iter().for_each(|param| pattern_names(param, &mut names));
+    for name in names {
+        if let Some(symbol) = context
+            .symbol_table
+            .lookup(scope_id, &name)
+            .and_then(|symbol_id| context.symbol_table.symbols.get_mut(symbol_id))
+        {
+            symbol.is_renamable = false;
+        }
     }
 }
 
@@ -805,39 +1325,162 @@ fn resolve_symbol(
     current_scope: ScopeId,
     context: &ScopeAnalysisContext,
 ) -> Option<SymbolId> {
-    let mut scope_id = current_scope;
+    context.scope_tree.resolve(context.symbol_table, current_scope, name)
+}
 
-    loop {
-        // Check if symbol exists in current scope
-        if let Some(scope_bindings) = context.symbol_table.scope_bindings.get(&scope_id)
-            && let Some(&symbol_id) = scope_bindings.get(name) {
-                return Some(symbol_id);
+/// Marks the symbol `name` declared in the current scope as exported
+fn mark_symbol_as_exported(name: &str, context: &mut ScopeAnalysisContext) {
+    if let Some(symbol_id) = context.symbol_table.lookup(context.current_scope, name)
+        && let Some(symbol) = context.symbol_table.symbols.get_mut(symbol_id) {
+            symbol.is_exported = true;
+            if context.config.preserve_exports {
+                symbol.is_renamable = false; // Exported symbols shouldn't be renamed
             }
+            tracing::trace!("Marked symbol '{}' as exported", name);
+        }
+}
 
-        // Move to parent scope
-        if let Some(scope) = context.scope_tree.get_scope(scope_id) {
-            if let Some(parent_id) = scope.parent_id {
-                scope_id = parent_id;
-            } else {
-                break; // Reached root scope
+/// Marks the locals of `export { local }` lists as exported and referenced, then records
+/// which imports are referenced
+fn resolve_local_exports(context: &mut ScopeAnalysisContext) {
+    let locals: Vec<String> = context.module_record.local_exports.iter().map(|export| export.local.clone()).collect();
+    for local in locals {
+        mark_symbol_as_exported(&local, context);
+        reference_symbol(&local, ReferenceType::Read, context);
+        record_namespace_use(&local, None, context);
+    }
+
+    let root_scope = context.scope_tree.root_scope_id;
+    for import in &mut context.module_record.imports {
+        import.is_referenced = context
+            .symbol_table
+            .lookup(root_scope, &import.local)
+            .and_then(|symbol_id| context.symbol_table.symbols.get(symbol_id))
+            .is_some_and(|symbol| !symbol.references.is_empty());
+    }
+}
+
+/// Sets [`Symbol::capture`](crate::analyzer::Symbol::capture) from the scopes of each
+/// symbol's references
+fn classify_captures(context: &mut ScopeAnalysisContext) {
+    for symbol in context.symbol_table.symbols.values_mut() {
+        let is_captured = symbol.references.iter().any(|reference| {
+            !matches!(reference.reference_type, ReferenceType::PropertyAccess)
+                && context.scope_tree.crosses_function(reference.scope_id, symbol.scope_id)
+        });
+        let is_written = symbol.references.iter().any(|reference| matches!(reference.reference_type, ReferenceType::Write));
+        symbol.capture = match (is_captured, is_written) {
+            (false, _) => CaptureKind::None,
+            (true, false) => CaptureKind::ReadOnly,
+            (true, true) => CaptureKind::Mutated,
+        };
+    }
+}
+
+/// Adds every name bound by `pattern` to `names`
+pub(crate) fn pattern_names(pattern: &Pattern, names: &mut Vec<String>) {
+    match pattern {
+        Pattern::Identifier(id) => names.push(id.name.clone()),
+        Pattern::ArrayPattern { elements } => {
+            elements.iter().flatten().for_each(|element| pattern_names(element, names));
+        }
+        Pattern::ObjectPattern { properties } => {
+            for property in properties {
+                match property {
+                    ObjectPatternProperty::Property { value, .. } => pattern_names(value, names),
+                    ObjectPatternProperty::RestElement { argument } => pattern_names(argument, names),
+                }
             }
-        } else {
-            break;
         }
+        Pattern::AssignmentPattern { left, .. } => pattern_names(left, names),
+        Pattern::RestElement { argument } => pattern_names(argument, names),
     }
+}
 
-    None
+/// Every identifier bound by `pattern`, in order
+fn pattern_identifiers(pattern: &Pattern) -> Vec<&Identifier> {
+    match pattern {
+        Pattern::Identifier(id) => vec![id],
+        Pattern::ArrayPattern { elements } => elements.iter().flatten().flat_map(pattern_identifiers).collect(),
+        Pattern::ObjectPattern { properties } => properties
+            .iter()
+            .flat_map(|property| match property {
+                ObjectPatternProperty::Property { value, .. } => pattern_identifiers(value),
+                ObjectPatternProperty::RestElement { argument } => pattern_identifiers(argument),
+            })
+            .collect(),
+        Pattern::AssignmentPattern { left, .. } => pattern_identifiers(left),
+        Pattern::RestElement { argument } => pattern_identifiers(argument),
+    }
 }
 
-/// Marks the last declared symbol as exported
-fn mark_last_declaration_as_exported(context: &mut ScopeAnalysisContext) {
-    if let Some(scope) = context.scope_tree.get_scope(context.current_scope)
-        && let Some(&last_symbol_id) = scope.bindings.last()
-            && let Some(symbol) = context.symbol_table.symbols.get_mut(&last_symbol_id) {
-                symbol.is_exported = true;
-                symbol.is_renamable = false; // Exported symbols shouldn't be renamed
-                if context.config.verbose {
-                    println!("Marked symbol '{}' as exported", symbol.name);
-                }
+/// Names bound by an exported declaration
+fn declared_names(declaration: &Statement) -> Vec<String> {
+    let mut names = Vec::new();
+    match declaration {
+        Statement::FunctionDeclaration { id: Some(id), .. } | Statement::ClassDeclaration { id: Some(id), .. } => {
+            names.push(id.name.clone());
+        }
+        Statement::VariableDeclaration { declarations, .. } => {
+            declarations.iter().for_each(|declarator| pattern_names(&declarator.id, &mut names));
+        }
+        _ => {}
+    }
+    names
+}
+
+/// Property name of a member access that is known without running the code
+fn static_member_name(property: &Expression, computed: bool) -> Option<String> {
+    match property {
+        Expression::Identifier(id) if !computed => Some(id.name.clone()),
+        Expression::Literal(Literal::String(string)) if computed => Some(string.value.clone()),
+        _ => None,
+    }
+}
+
+/// Records a use of `name` if it resolves to a namespace import: a read of `member`, or
+/// with `None` a use that may read any export
+fn record_namespace_use(name: &str, member: Option<String>, context: &mut ScopeAnalysisContext) {
+    let root_scope = context.scope_tree.root_scope_id;
+    let resolves_to_import = resolve_symbol(name, context.current_scope, context)
+        .and_then(|symbol_id| context.symbol_table.symbols.get(symbol_id))
+        .is_some_and(|symbol| symbol.scope_id == root_scope && matches!(symbol.symbol_type, SymbolType::Import));
+    if !resolves_to_import {
+        return;
+    }
+    if let Some(namespace) = context.module_record.namespaces.get_mut(name) {
+        match member {
+            Some(member) => {
+                namespace.members.insert(member);
             }
-}
\ No newline at end of file
+            None => namespace.is_dynamic = true,
+        }
+    }
+}
+/// Adds `source` to the modules this module requests, keeping the first request's position
+fn request_module(source: &StringLiteral, context: &mut ScopeAnalysisContext) {
+    let requested = &mut context.module_record.requested_modules;
+    if !requested.contains(&source.value) {
+        requested.push(source.value.clone());
+    }
+}
+
+/// Adds `specifier` to the worker entries of the module, keeping the first one's position
+fn record_worker(specifier: &StringLiteral, context: &mut ScopeAnalysisContext) {
+    let workers = &mut context.module_record.workers;
+    if !workers.contains(&specifier.value) {
+        workers.push(specifier.value.clone());
+    }
+}
+
+/// Returns whether the current scope is a function scope or nested in one
+fn is_inside_function(context: &ScopeAnalysisContext) -> bool {
+    let mut scope_id = Some(context.current_scope);
+    while let Some(scope) = scope_id.and_then(|id| context.scope_tree.get_scope(id)) {
+        if matches!(scope.scope_type, ScopeType::Function) {
+            return true;
+        }
+        scope_id = scope.parent_id;
+    }
+    false
+}
@@ -9,6 +9,8 @@
 //! - Detect eval() calls and mark containing scopes as unsafe
 //! - Detect with statements and mark scope resolution as dynamic
 //! - Classify this usage as lexical (arrow functions) or dynamic (regular functions)
+//! - Flag functions that use their `arguments` object
+//! - Record properties written onto the global object as global exports
 //! - Propagate unsafe flags upward through the scope chain
 //! - Mark scope safety classification for optimization decisions
 
@@ -17,9 +19,13 @@ use crate::analyzer::{
     SymbolTable, UnsafeReason,
 };
 use crate::parser::ast_types::{
-    ClassElement, Expression, ForInit, Program, Statement, UnaryOperator,
+    ClassElement, Expression, ForInit, Literal, ObjectProperty, Program, Statement, UnaryOperator,
+    VariableDeclarationKind,
 };
 
+/// Names under which scripts reach the global object
+const GLOBAL_OBJECT_NAMES: [&str; 4] = ["window", "globalThis", "self", "global"];
+
 /// Context for semantic analysis traversal
 pub struct SemanticAnalysisContext<'a> {
     /// Current scope being analyzed
@@ -59,9 +65,7 @@ pub fn analyze_semantics(
     semantic_flags: &mut SemanticFlags,
     config: &AnalyzerConfig,
 ) -> AnalysisResult<()> {
-    if config.verbose {
-        println!("Performing semantic analysis...");
-    }
+    tracing::debug!("Performing semantic analysis...");
 
     let mut context = SemanticAnalysisContext {
         current_scope:     scope_tree.root_scope_id,
@@ -78,17 +82,18 @@ pub fn analyze_semantics(
         analyze_statement_semantics(statement, &mut context)?;
     }
 
+    mark_arguments_scopes(&mut context);
+    mark_decorated_scopes(&mut context);
+    mark_dropped_code_scopes(&mut context);
+
     // Propagate unsafe flags upward through scope chain
     propagate_unsafe_flags(&mut context)?;
 
-    if config.verbose {
-        let unsafe_scope_count = context.semantic_flags.unsafe_scopes.len();
-        let unsafe_symbol_count = context.semantic_flags.unsafe_symbols.len();
-        println!(
-            "Semantic analysis completed: {} unsafe scopes, {} unsafe symbols",
-            unsafe_scope_count, unsafe_symbol_count
-        );
-    }
+    tracing::debug!(
+        "Semantic analysis completed: {} unsafe scopes, {} unsafe symbols",
+        context.semantic_flags.unsafe_scopes.len(),
+        context.semantic_flags.unsafe_symbols.len()
+    );
 
     Ok(())
 }
@@ -130,7 +135,10 @@ fn analyze_statement_semantics(
             }
             Ok(())
         }
-        Statement::ClassDeclaration { body, super_class, .. } => {
+        Statement::ClassDeclaration { body, super_class, decorators, .. } => {
+            for decorator in decorators {
+                analyze_expression_semantics(decorator, context)?;
+            }
             if let Some(super_expr) = super_class {
                 analyze_expression_semantics(super_expr, context)?;
             }
@@ -154,10 +162,10 @@ fn analyze_statement_semantics(
             }
             Ok(())
         }
-        Statement::ExpressionStatement { expression } => {
+        Statement::ExpressionStatement { expression, .. } => {
             analyze_expression_semantics(expression, context)
         }
-        Statement::BlockStatement { body } => {
+        Statement::BlockStatement { body, .. } => {
             // Enter block scope if it exists
             let block_scope = find_child_scope_of_type(
                 context.current_scope,
@@ -181,7 +189,7 @@ fn analyze_statement_semantics(
             }
             Ok(())
         }
-        Statement::ReturnStatement { argument } => {
+        Statement::ReturnStatement { argument, .. } => {
             if let Some(expr) = argument {
                 analyze_expression_semantics(expr, context)?;
             }
@@ -191,6 +199,7 @@ fn analyze_statement_semantics(
             test,
             consequent,
             alternate,
+            ..
         } => {
             analyze_expression_semantics(test, context)?;
             analyze_statement_semantics(consequent, context)?;
@@ -199,7 +208,7 @@ fn analyze_statement_semantics(
             }
             Ok(())
         }
-        Statement::WhileStatement { test, body } => {
+        Statement::WhileStatement { test, body, .. } => {
             analyze_expression_semantics(test, context)?;
             analyze_statement_semantics(body, context)
         }
@@ -208,6 +217,7 @@ fn analyze_statement_semantics(
             test,
             update,
             body,
+            ..
         } => {
             // Enter for loop scope if it exists
             let loop_scope = find_child_scope_of_type(
@@ -246,13 +256,38 @@ fn analyze_statement_semantics(
             }
             Ok(())
         }
-        Statement::ImportDeclaration { .. } => Ok(()), // Imports don't affect semantics
+        Statement::ForOfStatement { left, right, body, .. } => {
+            analyze_expression_semantics(right, context)?;
+
+            // Enter the loop scope of `let`/`const` bindings if it exists
+            let loop_scope = if matches!(
+                left,
+                ForInit::VariableDeclaration { kind, .. } if !matches!(kind, VariableDeclarationKind::Var)
+            ) {
+                find_child_scope_of_type(context.current_scope, crate::analyzer::ScopeType::Block, context)
+            } else {
+                None
+            };
+            let previous_scope = context.current_scope;
+            if let Some(scope_id) = loop_scope {
+                context.current_scope = scope_id;
+            }
+
+            analyze_for_init_semantics(left, context)?;
+            analyze_statement_semantics(body, context)?;
+
+            context.current_scope = previous_scope;
+            Ok(())
+        }
+        Statement::ImportDeclaration { .. } | Statement::ExportAllDeclaration { .. } => Ok(()), // Imports and re-exports don't affect semantics
+        Statement::EmptyStatement | Statement::DebuggerStatement => Ok(()),
         Statement::ExportNamedDeclaration { declaration, .. } => {
             if let Some(decl) = declaration {
                 analyze_statement_semantics(decl, context)?;
             }
             Ok(())
         }
+        Statement::ExportDefaultDeclaration { declaration, .. } => analyze_statement_semantics(declaration, context),
     }
 }
 
@@ -261,6 +296,9 @@ fn analyze_class_element_semantics(
     element: &ClassElement,
     context: &mut SemanticAnalysisContext,
 ) -> AnalysisResult<()> {
+    for decorator in element.decorators() {
+        analyze_expression_semantics(decorator, context)?;
+    }
     match element {
         ClassElement::PropertyDefinition { value, .. } => {
             if let Some(expr) = value {
@@ -303,28 +341,22 @@ fn analyze_expression_semantics(
             match id.name.as_str() {
                 "eval" => {
                     mark_scope_unsafe(context.current_scope, UnsafeReason::EvalUsage, context);
-                    if context.config.verbose {
-                        println!("Detected eval usage in scope {}", context.current_scope);
-                    }
+                    tracing::trace!("Detected eval usage in scope {}", context.current_scope);
                 }
                 "arguments" => {
                     // arguments object usage can affect optimization
-                    if context.config.verbose {
-                        println!("Detected arguments usage in scope {}", context.current_scope);
-                    }
+                    tracing::trace!("Detected arguments usage in scope {}", context.current_scope);
                 }
                 _ => {}
             }
             Ok(())
         }
-        Expression::CallExpression { callee, arguments } => {
+        Expression::CallExpression { callee, arguments, .. } => {
             // Check for eval() calls
             if let Expression::Identifier(id) = callee.as_ref()
                 && id.name == "eval" {
                     mark_scope_unsafe(context.current_scope, UnsafeReason::EvalUsage, context);
-                    if context.config.verbose {
-                        println!("Detected eval() call in scope {}", context.current_scope);
-                    }
+                    tracing::trace!("Detected eval() call in scope {}", context.current_scope);
                 }
 
             analyze_expression_semantics(callee, context)?;
@@ -337,15 +369,11 @@ fn analyze_expression_semantics(
             // this usage classification
             if context.in_arrow_function {
                 // Arrow functions have lexical this binding
-                if context.config.verbose {
-                    println!("Detected lexical this usage in scope {}", context.current_scope);
-                }
+                tracing::trace!("Detected lexical this usage in scope {}", context.current_scope);
             } else {
                 // Regular functions have dynamic this binding
                 mark_scope_unsafe(context.current_scope, UnsafeReason::DynamicThis, context);
-                if context.config.verbose {
-                    println!("Detected dynamic this usage in scope {}", context.current_scope);
-                }
+                tracing::trace!("Detected dynamic this usage in scope {}", context.current_scope);
             }
             Ok(())
         }
@@ -358,20 +386,43 @@ fn analyze_expression_semantics(
             if matches!(operator, UnaryOperator::Typeof)
                 && let Expression::Identifier(_) = argument.as_ref() {
                     // typeof identifier - might be checking for undefined globals
-                    if context.config.verbose {
-                        println!("Detected typeof usage in scope {}", context.current_scope);
-                    }
+                    tracing::trace!("Detected typeof usage in scope {}", context.current_scope);
                 }
             analyze_expression_semantics(argument, context)
         }
+        Expression::AwaitExpression { argument }
+        | Expression::ChainExpression { expression: argument }
+        | Expression::SpreadElement { argument } => analyze_expression_semantics(argument, context),
+        Expression::ArrayExpression { elements } => {
+            for element in elements.iter().flatten() {
+                analyze_expression_semantics(element, context)?;
+            }
+            Ok(())
+        }
+        Expression::ObjectExpression { properties } => {
+            for property in properties {
+                match property {
+                    ObjectProperty::Property { value, .. } => analyze_expression_semantics(value, context)?,
+                    ObjectProperty::SpreadElement { argument } => analyze_expression_semantics(argument, context)?,
+                }
+            }
+            Ok(())
+        }
+        Expression::YieldExpression { argument: Some(argument), .. } => analyze_expression_semantics(argument, context),
         Expression::AssignmentExpression { left, right, .. } => {
+            record_global_write(left, context);
             analyze_expression_semantics(left, context)?;
             analyze_expression_semantics(right, context)
         }
+        Expression::UpdateExpression { argument, .. } => {
+            record_global_write(argument, context);
+            analyze_expression_semantics(argument, context)
+        }
         Expression::MemberExpression {
             object,
             property,
             computed,
+            ..
         } => {
             analyze_expression_semantics(object, context)?;
             
@@ -381,18 +432,16 @@ fn analyze_expression_semantics(
                 
                 // Check for window['property'] pattern
                 if let Expression::Identifier(obj_id) = object.as_ref()
-                    && (obj_id.name == "window" || obj_id.name == "global") {
+                    && GLOBAL_OBJECT_NAMES.contains(&obj_id.name.as_str()) {
                         mark_scope_unsafe(
                             context.current_scope,
                             UnsafeReason::IndirectAccess,
                             context,
                         );
-                        if context.config.verbose {
-                            println!(
-                                "Detected indirect global access in scope {}",
-                                context.current_scope
-                            );
-                        }
+                        tracing::trace!(
+                            "Detected indirect global access in scope {}",
+                            context.current_scope
+                        );
                     }
             } else if let Expression::Identifier(_) = property.as_ref() {
                 // Static property access obj.prop - generally safe
@@ -441,6 +490,20 @@ fn analyze_expression_semantics(
             analyze_expression_semantics(consequent, context)?;
             analyze_expression_semantics(alternate, context)
         }
+        Expression::ImportExpression { source, options } => {
+            analyze_expression_semantics(source, context)?;
+            if let Some(options) = options {
+                analyze_expression_semantics(options, context)?;
+            }
+            Ok(())
+        }
+        Expression::NewExpression { callee, arguments } => {
+            analyze_expression_semantics(callee, context)?;
+            for arg in arguments {
+                analyze_expression_semantics(arg, context)?;
+            }
+            Ok(())
+        }
         Expression::Literal(_) => Ok(()), // Literals are safe
         _ => Ok(()), // Handle other expression types as needed
     }
@@ -488,16 +551,81 @@ fn mark_scope_unsafe(
     }
 
     // Mark all symbols in this scope as unsafe for renaming
-    if let Some(scope_bindings) = context.symbol_table.scope_bindings.get(&scope_id) {
+    if let Some(scope_bindings) = context.symbol_table.scope_bindings.get(scope_id as usize) {
         for &symbol_id in scope_bindings.values() {
             context.semantic_flags.unsafe_symbols.insert(symbol_id, reason.clone());
-            if let Some(symbol) = context.symbol_table.symbols.get_mut(&symbol_id) {
+            if let Some(symbol) = context.symbol_table.symbols.get_mut(symbol_id) {
                 symbol.is_renamable = false;
             }
         }
     }
 }
 
+/// Records `target` as a global export when it is a static property of the global object
+///
+/// `window.foo`, `globalThis.bar` and `self["x"]` qualify; a computed key that isn't a
+/// string literal can't be named and is already flagged as indirect access, and a
+/// global object name shadowed by a local binding is an ordinary object.
+fn record_global_write(target: &Expression, context: &mut SemanticAnalysisContext) {
+    let Expression::MemberExpression { object, property, computed, .. } = target else {
+        return;
+    };
+    let Expression::Identifier(object) = object.as_ref() else {
+        return;
+    };
+    if !GLOBAL_OBJECT_NAMES.contains(&object.name.as_str())
+        || context.scope_tree.resolve(context.symbol_table, context.current_scope, &object.name).is_some()
+    {
+        return;
+    }
+
+    let name = match (property.as_ref(), computed) {
+        (Expression::Identifier(property), false) => &property.name,
+        (Expression::Literal(Literal::String(property)), true) => &property.value,
+        _ => return,
+    };
+    tracing::trace!("Detected global export {}.{} in scope {}", object.name, name, context.current_scope);
+    context.semantic_flags.global_exports.insert(name.clone());
+}
+
+/// Flags the functions the scope builder found using `arguments`
+///
+/// Only the scope is flagged: its parameters were already pinned, and its other
+/// bindings stay renamable.
+fn mark_arguments_scopes(context: &mut SemanticAnalysisContext) {
+    for scope in context.scope_tree.scopes.values_mut().filter(|scope| scope.uses_arguments) {
+        scope.is_safe = false;
+        context.semantic_flags.unsafe_scopes.entry(scope.id).or_insert(UnsafeReason::ArgumentsUsage);
+    }
+}
+
+/// Flags the class scopes the scope builder found decorated
+///
+/// Like [`mark_arguments_scopes`], only the scope is flagged; the scope builder already
+/// pinned the class name.
+fn mark_decorated_scopes(context: &mut SemanticAnalysisContext) {
+    for scope in context.scope_tree.scopes.values_mut().filter(|scope| scope.is_decorated) {
+        scope.is_safe = false;
+        context.semantic_flags.unsafe_scopes.entry(scope.id).or_insert(UnsafeReason::Decorators);
+    }
+}
+
+/// Flags the functions the scope builder found the parser dropped code from
+///
+/// The dropped code may read or write any binding it can see, so the function and the
+/// scopes nested in it are flagged, with all their symbols; propagation then flags the
+/// enclosing scopes.
+fn mark_dropped_code_scopes(context: &mut SemanticAnalysisContext) {
+    let mut pending: Vec<ScopeId> =
+        context.scope_tree.scopes.values().filter(|scope| scope.has_dropped_code).map(|scope| scope.id).collect();
+    while let Some(scope_id) = pending.pop() {
+        mark_scope_unsafe(scope_id, UnsafeReason::DroppedCode, context);
+        if let Some(scope) = context.scope_tree.get_scope(scope_id) {
+            pending.extend(&scope.children);
+        }
+    }
+}
+
 /// Propagates unsafe flags upward through the scope chain
 fn propagate_unsafe_flags(context: &mut SemanticAnalysisContext) -> AnalysisResult<()> {
     let unsafe_scopes: Vec<_> = context.semantic_flags.unsafe_scopes.keys().copied().collect();
@@ -525,6 +653,13 @@ fn propagate_unsafe_flag_upward(scope_id: ScopeId, context: &mut SemanticAnalysi
                         }
                         current_scope = parent_id;
                     }
+                    UnsafeReason::DroppedCode => {
+                        // the dropped code can see every enclosing scope
+                        if !context.semantic_flags.unsafe_scopes.contains_key(&parent_id) {
+                            mark_scope_unsafe(parent_id, UnsafeReason::DroppedCode, context);
+                        }
+                        current_scope = parent_id;
+                    }
                     UnsafeReason::WithStatement => {
                         // with affects parent scope
                         if !context.semantic_flags.unsafe_scopes.contains_key(&parent_id) {
@@ -532,7 +667,10 @@ fn propagate_unsafe_flag_upward(scope_id: ScopeId, context: &mut SemanticAnalysi
                         }
                         current_scope = parent_id;
                     }
-                    UnsafeReason::DynamicThis | UnsafeReason::IndirectAccess => {
+                    UnsafeReason::DynamicThis
+                    | UnsafeReason::IndirectAccess
+                    | UnsafeReason::ArgumentsUsage
+                    | UnsafeReason::Decorators => {
                         // These don't necessarily propagate upward
                         break;
                     }
@@ -5,8 +5,8 @@
 //! analysis, and edge cases to ensure correct implementation.
 
 use crate::analyzer::{
-    analyze_ast, AnalyzerConfig, ScopeType, SemanticAnalysis, SymbolType, UnsafeReason,
-    VariableKind,
+    analyze_ast, AnalyzerConfig, CaptureKind, ScopeType, SemanticAnalysis, SymbolType,
+    UnsafeReason, VariableKind,
 };
 use crate::parser::{parse_js, ParserConfig};
 
@@ -20,7 +20,7 @@ fn parse_and_analyze(source: &str) -> Result<SemanticAnalysis, Box<dyn std::erro
     }
     
     let ast = parse_result.ast.ok_or("No AST produced")?;
-    let analyzer_config = AnalyzerConfig::default();
+    let analyzer_config = AnalyzerConfig { names: parse_result.names, ..AnalyzerConfig::default() };
     let analysis = analyze_ast(&ast, &analyzer_config)?;
     
     Ok(analysis)
@@ -28,16 +28,12 @@ fn parse_and_analyze(source: &str) -> Result<SemanticAnalysis, Box<dyn std::erro
 
 /// Helper function to get symbol by name from analysis
 fn find_symbol_by_name<'a>(analysis: &'a SemanticAnalysis, name: &str) -> Option<&'a crate::analyzer::Symbol> {
-    analysis.symbol_table.symbols.values().find(|s| s.name == name)
+    analysis.symbol_table.symbols.values().find(|s| analysis.symbol_table.name(s) == name)
 }
 
 /// Helper function to check if a scope contains a binding
 fn scope_has_binding(analysis: &SemanticAnalysis, scope_id: u32, name: &str) -> bool {
-    if let Some(bindings) = analysis.symbol_table.scope_bindings.get(&scope_id) {
-        bindings.contains_key(name)
-    } else {
-        false
-    }
+    analysis.symbol_table.lookup(scope_id, name).is_some()
 }
 
 #[cfg(test)]
@@ -134,7 +130,7 @@ mod scope_builder_tests {
 
         // Should have two x symbols in different scopes
         let x_symbols: Vec<_> = analysis.symbol_table.symbols.values()
-            .filter(|s| s.name == "x")
+            .filter(|s| analysis.symbol_table.name(s) == "x")
             .collect();
         assert_eq!(x_symbols.len(), 2);
         
@@ -147,6 +143,50 @@ mod scope_builder_tests {
         assert_eq!(function_x.scope_id, function_scope_id);
     }
 
+    #[test]
+    fn should_intern_names_in_the_parser_interner() {
+        let source = "let x = 1; function f(y) { return x + z; }\ntry {} catch (e) {}";
+        let parse_result = parse_js(source, "test.js", &ParserConfig::default());
+        let parsed_names = parse_result.names.clone();
+        let config = AnalyzerConfig { names: parse_result.names, ..AnalyzerConfig::default() };
+        let analysis = analyze_ast(parse_result.ast.as_ref().unwrap(), &config).unwrap();
+
+        // Symbols keep the ids the parser gave their names
+        for symbol in analysis.symbol_table.symbols.values() {
+            assert_eq!(parsed_names.get(analysis.symbol_table.name(symbol)), Some(symbol.name));
+        }
+        assert!(analysis.symbol_table.is_declared("x") && analysis.symbol_table.is_declared("y"));
+        assert!(!analysis.symbol_table.is_declared("z"));
+        // The owned AST drops `try`, so its catch parameter is interned but not declared
+        assert!(parsed_names.get("e").is_some());
+        assert!(!analysis.symbol_table.is_declared("e"));
+    }
+
+    #[test]
+    fn should_resolve_names_through_parent_scopes() {
+        let source = "let x = 1; function foo() { let x = 2; function bar() { return x + y; } }";
+        let analysis = parse_and_analyze(source).expect("Analysis should succeed");
+
+        // IDs index the arenas directly
+        for (id, symbol) in analysis.symbol_table.symbols.iter() {
+            assert_eq!(symbol.id, id);
+        }
+        for (id, scope) in analysis.scope_tree.scopes.iter() {
+            assert_eq!(scope.id, id);
+        }
+
+        let foo_scope_id = analysis.scope_tree.get_scope(0).expect("Global scope").children[0];
+        let bar_scope_id = analysis.scope_tree.get_scope(foo_scope_id).expect("foo scope").children[0];
+        let resolved = analysis
+            .scope_tree
+            .resolve(&analysis.symbol_table, bar_scope_id, "x")
+            .expect("x should resolve");
+
+        // The nearest declaration wins over the global one
+        assert_eq!(analysis.symbol_table.symbols.get(resolved).expect("x symbol").scope_id, foo_scope_id);
+        assert_eq!(analysis.scope_tree.resolve(&analysis.symbol_table, bar_scope_id, "y"), None);
+    }
+
     #[test]
     fn should_detect_closure_capture() {
         let source = "function outer() { let x = 1; function inner() { return x; } }";
@@ -161,6 +201,102 @@ mod scope_builder_tests {
         // x should have references
         assert!(!x_symbol.references.is_empty(), "Variable x should have references");
     }
+
+    #[test]
+    fn should_classify_read_only_and_mutated_captures() {
+        let source = "const limit = 10; let count = 0; let total = 0; let local = 1;\n\
+                      function check(x) { return x > limit; }\n\
+                      function bump() { count++; }\n\
+                      const add = (n) => { total += n; };\n\
+                      { local = local + 1; }";
+        let analysis = parse_and_analyze(source).expect("Analysis should succeed");
+        let capture = |name| find_symbol_by_name(&analysis, name).expect("symbol should exist").capture;
+
+        assert_eq!(capture("limit"), CaptureKind::ReadOnly);
+        assert_eq!(capture("count"), CaptureKind::Mutated);
+        assert_eq!(capture("total"), CaptureKind::Mutated);
+        // Written, but only in the declaring function
+        assert_eq!(capture("local"), CaptureKind::None);
+    }
+
+    #[test]
+    fn should_reject_lexical_redeclarations() {
+        for source in [
+            "var x = 1; let x = 2;",
+            "let x; { var x; }",
+            "function f(a) { const a = 1; }",
+            "import { x } from 'x'; class x {}",
+            "function x() {} let x;",
+        ] {
+            let error = parse_and_analyze(source).expect_err(source).to_string();
+            assert!(error.contains("has already been declared"), "{}", error);
+        }
+
+        let error = parse_and_analyze("var total; let total;").unwrap_err().to_string();
+        assert!(
+            error.ends_with("'total' has already been declared at offset 15: `let` declaration conflicts with the earlier `var` of the same name in this scope"),
+            "{}",
+            error
+        );
+        let error = parse_and_analyze("function f(a) { let a; }").unwrap_err().to_string();
+        assert!(error.contains("earlier parameter"), "{}", error);
+
+        // A `var` hoists through the loop heads and blocks around it
+        for source in ["for (let i = 0; ; ) { var i; }", "{ let x; { var x; } }", "switch (x) { case 1: let q; case 2: let q; }"] {
+            let error = parse_and_analyze(source).expect_err(source).to_string();
+            assert!(error.contains("has already been declared"), "{}", error);
+        }
+
+        // The owned AST drops `try`, so the parser checks the catch parameter
+        for source in [
+            "try {} catch (e) { let e; }",
+            "try {} catch (e) { function e() {} }",
+            "try {} catch ({ e }) { class e {} }",
+            "try {} catch ([e]) { { var e; } }",
+        ] {
+            let error = parse_and_analyze(source).expect_err(source).to_string();
+            assert!(error.contains("'e' has already been declared"), "{}", error);
+        }
+
+        // Repeated `var` and functions, blocks and shadowing stay allowed
+        for source in [
+            "var x; var x; function x() {}",
+            "function f(a) { var a; }",
+            "let x; { let x; } { class x {} }",
+            "let x; { function x() {} }",
+            "try {} catch (e) { var e; { let e; } }",
+            "try {} catch ([e]) { function f() { var e; } }",
+        ] {
+            parse_and_analyze(source).expect(source);
+        }
+    }
+
+    #[test]
+    fn should_reject_duplicate_parameters_where_forbidden() {
+        let error = parse_and_analyze("'use strict'; function f(a, b, a) {}").unwrap_err().to_string();
+        assert!(error.contains("'a' has already been declared at offset 31: duplicate parameter names"), "{}", error);
+
+        // Strictness comes from the source: modules, directives and class bodies
+        for source in [
+            "export function f(a, a) {}",
+            "function f(a, a) { 'use strict'; }",
+            "class A { m(a, a) {} }",
+            "'use strict'; const f = function (a, a) {};",
+        ] {
+            let error = parse_and_analyze(source).expect_err(source).to_string();
+            assert!(error.contains("strict mode"), "{}", error);
+        }
+
+        let parse = |source| parse_js(source, "test.js", &ParserConfig::default()).ast.unwrap();
+        for source in ["function f(a, a) {}", "function f(a, a) {} function g() { 'use strict'; }"] {
+            parse_and_analyze(source).expect(source);
+        }
+        let strict = AnalyzerConfig { strict_mode: true, ..AnalyzerConfig::default() };
+        assert!(analyze_ast(&parse("function f(a, a) {}"), &strict).is_err());
+        for source in ["const f = (a, a) => a;", "function f(a, [a]) {}", "function f(a, a = 1) {}"] {
+            assert!(parse_and_analyze(source).is_err(), "{}", source);
+        }
+    }
 }
 
 #[cfg(test)]
@@ -217,12 +353,106 @@ mod semantic_analysis_tests {
         // For now, just verify the function exists
         assert!(scope_has_binding(&analysis, 0, "f"));
     }
+
+    #[test]
+    fn should_flag_functions_using_arguments() {
+        let source = "function f(a, b) { let c = 1; return arguments.length + c; }";
+        let analysis = parse_and_analyze(source).expect("Analysis should succeed");
+
+        let function_scope_id = analysis.scope_tree.get_scope(0).expect("Global scope").children[0];
+        let function_scope = analysis.scope_tree.get_scope(function_scope_id).expect("Function scope should exist");
+        assert!(function_scope.uses_arguments);
+        assert!(!function_scope.is_safe);
+        assert!(matches!(
+            analysis.semantic_flags.unsafe_scopes.get(&function_scope_id),
+            Some(UnsafeReason::ArgumentsUsage)
+        ));
+
+        // Parameters alias `arguments` and keep their names and positions
+        assert!(!find_symbol_by_name(&analysis, "a").expect("Symbol a should exist").is_renamable);
+        assert!(!find_symbol_by_name(&analysis, "b").expect("Symbol b should exist").is_renamable);
+    }
+
+    #[test]
+    fn should_attribute_arguments_in_arrow_to_enclosing_function() {
+        let source = "function outer(x) { const g = () => arguments[0]; return g(); }";
+        let analysis = parse_and_analyze(source).expect("Analysis should succeed");
+
+        let outer_scope_id = analysis.scope_tree.get_scope(0).expect("Global scope").children[0];
+        let outer_scope = analysis.scope_tree.get_scope(outer_scope_id).expect("Function scope should exist");
+        assert!(outer_scope.uses_arguments);
+        assert!(!find_symbol_by_name(&analysis, "x").expect("Symbol x should exist").is_renamable);
+
+        let arrow_scope_id = outer_scope.children[0];
+        let arrow_scope = analysis.scope_tree.get_scope(arrow_scope_id).expect("Arrow scope should exist");
+        assert!(!arrow_scope.uses_arguments);
+    }
+
+    #[test]
+    fn should_not_flag_local_arguments_binding() {
+        let source = "function f(a) { var arguments = [a]; return arguments.length; }";
+        let analysis = parse_and_analyze(source).expect("Analysis should succeed");
+
+        let function_scope_id = analysis.scope_tree.get_scope(0).expect("Global scope").children[0];
+        let function_scope = analysis.scope_tree.get_scope(function_scope_id).expect("Function scope should exist");
+        assert!(!function_scope.uses_arguments);
+        assert!(find_symbol_by_name(&analysis, "a").expect("Symbol a should exist").is_renamable);
+    }
+
+    #[test]
+    fn should_record_global_object_writes() {
+        let source = "window.foo = 1; function init() { globalThis.bar = 2; self[\"baz\"] += 3; } window.config.debug = true; window[key] = 3;";
+        let analysis = parse_and_analyze(source).expect("Analysis should succeed");
+
+        let exports: Vec<&str> = analysis.semantic_flags.global_exports.iter().map(String::as_str).collect();
+        assert_eq!(exports, vec!["bar", "baz", "foo"]);
+    }
+
+    #[test]
+    fn should_ignore_writes_to_shadowed_global_object() {
+        let source = "function f(window) { window.foo = 1; }";
+        let analysis = parse_and_analyze(source).expect("Analysis should succeed");
+
+        assert!(analysis.semantic_flags.global_exports.is_empty());
+    }
 }
 
 #[cfg(test)]
 mod edge_case_tests {
     use super::*;
 
+    #[test]
+    fn should_mark_kept_symbols() {
+        let parse_result = parse_js("/* @__KEEP__ */ function hook() {}\nlet other = 1;", "test.js", &ParserConfig::default());
+        let config = AnalyzerConfig { kept_names: parse_result.kept_names, ..AnalyzerConfig::default() };
+        let analysis = analyze_ast(&parse_result.ast.unwrap(), &config).expect("Analysis should succeed");
+
+        let hook = find_symbol_by_name(&analysis, "hook").expect("hook symbol should exist");
+        assert!(hook.is_kept);
+        assert!(!hook.is_renamable);
+
+        let other = find_symbol_by_name(&analysis, "other").expect("other symbol should exist");
+        assert!(!other.is_kept);
+        assert!(other.is_renamable);
+    }
+
+    #[test]
+    fn should_pin_decorated_classes() {
+        let source = "class Plain { run() {} }\nclass Widget { @bound click() {} }";
+        let config = ParserConfig::default().with_decorators(true);
+        let ast = parse_js(source, "test.js", &config).ast.expect("Parse should succeed");
+        let analysis = analyze_ast(&ast, &AnalyzerConfig::default()).expect("Analysis should succeed");
+
+        assert!(find_symbol_by_name(&analysis, "Plain").expect("Plain symbol should exist").is_renamable);
+        assert!(!find_symbol_by_name(&analysis, "Widget").expect("Widget symbol should exist").is_renamable);
+
+        let class_scopes: Vec<_> = analysis.scope_tree.get_scope(0).expect("Global scope").children.clone();
+        let reasons: Vec<_> =
+            class_scopes.iter().map(|id| analysis.semantic_flags.unsafe_scopes.get(id).cloned()).collect();
+        assert!(matches!(reasons.as_slice(), [None, Some(UnsafeReason::Decorators)]));
+        assert!(!analysis.scope_tree.get_scope(class_scopes[1]).expect("Class scope").is_safe);
+    }
+
     #[test]
     
     fn should_handle_var_hoisting() {
@@ -280,6 +510,24 @@ mod edge_case_tests {
         assert!(matches!(a_symbol.symbol_type, SymbolType::Variable { kind: VariableKind::Var }));
     }
 
+    #[test]
+    fn should_bind_destructured_and_rest_names() {
+        let source = "function test({ a, b: [c, ...d] = [] }, e = a, ...f) { var { g = c } = e; return [...d, ...f, g]; }";
+        let analysis = parse_and_analyze(source).expect("Analysis should succeed");
+
+        let function_scope_id = analysis.scope_tree.get_scope(0).expect("Global scope").children[0];
+        for name in ["a", "c", "d", "e", "f", "g"] {
+            assert!(scope_has_binding(&analysis, function_scope_id, name), "{name} should be bound");
+        }
+        assert!(!scope_has_binding(&analysis, function_scope_id, "b"));
+
+        // Default values and spread arguments are references
+        let c_symbol = find_symbol_by_name(&analysis, "c").expect("c symbol should exist");
+        assert_eq!(c_symbol.references.len(), 1);
+        let f_symbol = find_symbol_by_name(&analysis, "f").expect("f symbol should exist");
+        assert_eq!(f_symbol.references.len(), 1);
+    }
+
     #[test]
     fn should_handle_import_declarations() {
         let source = "import { foo, bar } from 'module';";
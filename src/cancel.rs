@@ -0,0 +1,102 @@
+//! # Cancellation
+//!
+//! A [`CancellationToken`] lets the caller of a long compilation stop it cleanly, with
+//! an error instead of a result: an editor integration can drop a compilation whose
+//! input has changed again, and the JSON-RPC server gives up on a request that runs
+//! past its `timeoutMs`.
+//!
+//! The token travels in the configuration of each phase
+//! ([`AnalyzerConfig`](crate::analyzer::AnalyzerConfig),
+//! [`TransformerConfig`](crate::transformer::TransformerConfig) and
+//! [`GeneratorConfig`](crate::generator::GeneratorConfig)), which check it between
+//! their steps: the analyzer between its phases, the transformer before every pass and
+//! plugin, and the printer before every top-level statement. Parsing is a single call
+//! into OXC, so it is only checked before and after.
+//!
+//! Clones share the cancellation flag, so a clone kept by another thread can cancel a
+//! compilation running with the original. The default token is never cancelled.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::Instant;
+
+/// Why a compilation stopped early
+#[derive(Error, Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Cancelled {
+    #[error("cancelled")]
+    Requested,
+    #[error("timed out after {timeout_ms}ms")]
+    DeadlineExceeded { timeout_ms: u64 },
+}
+
+/// Shared flag and optional deadline that stop a compilation between steps
+#[derive(Debug, Clone, Default)]
+pub struct CancellationToken {
+    cancelled: Arc<AtomicBool>,
+    /// When the compilation has to be finished, with the timeout it was set from
+    deadline:  Option<(Instant, Duration)>,
+}
+
+impl CancellationToken {
+    /// Creates a token that is only cancelled by [`cancel`](Self::cancel)
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Also cancels the compilation once `timeout` has passed from now
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.deadline = Some((Instant::now() + timeout, timeout));
+        self
+    }
+
+    /// Cancels every compilation running with this token or a clone of it
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+    }
+
+    /// Whether the compilation should stop
+    pub fn is_cancelled(&self) -> bool {
+        self.check().is_err()
+    }
+
+    /// Returns why the compilation should stop, if it should
+    ///
+    /// # Errors
+    ///
+    /// Returns `Cancelled::Requested` after [`cancel`](Self::cancel), or
+    /// `Cancelled::DeadlineExceeded` once the timeout has passed.
+    pub fn check(&self) -> Result<(), Cancelled> {
+        if self.cancelled.load(Ordering::Relaxed) {
+            return Err(Cancelled::Requested);
+        }
+        match self.deadline {
+            Some((deadline, timeout)) if Instant::now() >= deadline => {
+                Err(Cancelled::DeadlineExceeded { timeout_ms: timeout.as_millis() as u64 })
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_clones_share_cancellation() {
+        let token = CancellationToken::new();
+        let clone = token.clone();
+        assert_eq!(clone.check(), Ok(()));
+        token.cancel();
+        assert_eq!(clone.check(), Err(Cancelled::Requested));
+
+        let expired = CancellationToken::new().with_timeout(Duration::ZERO);
+        assert_eq!(expired.check(), Err(Cancelled::DeadlineExceeded { timeout_ms: 0 }));
+        assert!(!CancellationToken::new().with_timeout(Duration::from_secs(60)).is_cancelled());
+    }
+}
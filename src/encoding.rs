@@ -0,0 +1,122 @@
+//! # Source Encoding
+//!
+//! The pipeline works on UTF-8 text. [`decode_source`] turns the bytes of an input file
+//! into it: a UTF-8 byte order mark is dropped, so it neither shifts the columns of the
+//! source map nor reaches the output, and anything else that is not UTF-8 is reported
+//! with where it goes wrong instead of being read as garbage.
+//!
+//! UTF-16, as some Windows editors save files, is recognized by its byte order mark or,
+//! without one, by the zero bytes ASCII text has in every other position. It is only
+//! converted when the caller asks for it; otherwise it is an error naming the encoding.
+
+use std::fmt;
+
+use thiserror::Error;
+
+/// Byte order mark of a UTF-8 file
+const UTF8_BOM: &[u8] = b"\xEF\xBB\xBF";
+
+/// An encoding the input can be in
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Encoding {
+    Utf8,
+    Utf16Le,
+    Utf16Be,
+}
+
+impl fmt::Display for Encoding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Utf8 => "UTF-8",
+            Self::Utf16Le => "UTF-16 LE",
+            Self::Utf16Be => "UTF-16 BE",
+        })
+    }
+}
+
+/// Why the bytes of an input are not usable source text
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum EncodingError {
+    #[error("the file is {0} encoded, not UTF-8")]
+    Utf16(Encoding),
+    #[error("invalid {encoding} at line {line}, column {column}")]
+    Invalid { encoding: Encoding, line: usize, column: usize },
+}
+
+/// The encoding of `bytes`, from its byte order mark or the zero bytes of UTF-16 text
+pub fn detect_encoding(bytes: &[u8]) -> Encoding {
+    match bytes {
+        [0xFF, 0xFE, ..] | [_, 0, ..] if bytes[0] != 0 => Encoding::Utf16Le,
+        [0xFE, 0xFF, ..] | [0, _, ..] if bytes[1] != 0 => Encoding::Utf16Be,
+        _ => Encoding::Utf8,
+    }
+}
+
+/// Decodes the contents of a source file, without its byte order mark
+///
+/// UTF-16 input is converted when `transcode_utf16` is set.
+///
+/// # Errors
+///
+/// Returns `EncodingError::Utf16` for UTF-16 input that is not to be converted, or
+/// `EncodingError::Invalid` with the position of the first malformed character.
+pub fn decode_source(bytes: Vec<u8>, transcode_utf16: bool) -> Result<String, EncodingError> {
+    let encoding = detect_encoding(&bytes);
+    match encoding {
+        Encoding::Utf8 => {
+            let mut bytes = bytes;
+            if bytes.starts_with(UTF8_BOM) {
+                bytes.drain(..UTF8_BOM.len());
+            }
+            String::from_utf8(bytes).map_err(|error| {
+                let valid = &error.as_bytes()[..error.utf8_error().valid_up_to()];
+                // Everything before the error is valid, so this cannot fail
+                let (line, column) = position(std::str::from_utf8(valid).unwrap_or_default());
+                EncodingError::Invalid { encoding, line, column }
+            })
+        }
+        _ if !transcode_utf16 => Err(EncodingError::Utf16(encoding)),
+        _ => {
+            let units = bytes.chunks(2).map(|pair| {
+                let pair = [pair[0], pair.get(1).copied().unwrap_or(0)];
+                if encoding == Encoding::Utf16Le { u16::from_le_bytes(pair) } else { u16::from_be_bytes(pair) }
+            });
+            let mut source = String::with_capacity(bytes.len() / 2);
+            for unit in char::decode_utf16(units) {
+                let Ok(character) = unit else {
+                    let (line, column) = position(&source);
+                    return Err(EncodingError::Invalid { encoding, line, column });
+                };
+                source.push(character);
+            }
+            Ok(source.strip_prefix('\u{FEFF}').map(str::to_string).unwrap_or(source))
+        }
+    }
+}
+
+/// One-based line and column of the character after `text`
+fn position(text: &str) -> (usize, usize) {
+    let line_start = text.rfind('\n').map_or(0, |newline| newline + 1);
+    (text.matches('\n').count() + 1, text[line_start..].chars().count() + 1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_decodes_boms_and_reports_other_encodings() {
+        assert_eq!(decode_source(b"\xEF\xBB\xBFlet a;".to_vec(), false).unwrap(), "let a;");
+        assert_eq!(
+            decode_source(b"let a;\nlet \xC3\xA9 = \xFF;".to_vec(), false),
+            Err(EncodingError::Invalid { encoding: Encoding::Utf8, line: 2, column: 9 })
+        );
+
+        let utf16: Vec<u8> = "\u{FEFF}let é;".encode_utf16().flat_map(u16::to_le_bytes).collect();
+        assert_eq!(decode_source(utf16.clone(), false), Err(EncodingError::Utf16(Encoding::Utf16Le)));
+        assert_eq!(decode_source(utf16, true).unwrap(), "let é;");
+        let without_bom: Vec<u8> = "a;".encode_utf16().flat_map(u16::to_be_bytes).collect();
+        assert_eq!(detect_encoding(&without_bom), Encoding::Utf16Be);
+        assert_eq!(decode_source(without_bom, true).unwrap(), "a;");
+    }
+}
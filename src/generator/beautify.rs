@@ -0,0 +1,223 @@
+//! # Beautify
+//!
+//! Turns minified code back into something readable for debugging: the code is parsed,
+//! names are restored where the build left a record of them, and the program is printed
+//! with the Pretty format.
+//!
+//! Two records are read. The source map of the minified code names every renamed
+//! identifier at the position it was printed, in its `names` array; the name map of
+//! `--emit-name-map` lists renamed identifiers, properties (by the variable holding
+//! their object) and exports. A name the source map gives wins over the name map, since
+//! it is tied to one occurrence rather than to a spelling.
+
+use std::collections::{BTreeMap, HashMap};
+
+use serde_json::Value;
+
+use crate::generator::source_maps::{decode_mappings, SourceMap};
+use crate::generator::{GeneratorResult, LineIndex};
+use crate::parser::ast_types::Program;
+use crate::transformer::NameMap;
+
+/// What an identifier names, which decides the records it is looked up in
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Role {
+    /// A binding or a reference to one
+    Variable,
+    /// A property name, of the object held by the given variable when known
+    Property,
+    /// The exported name of an export specifier
+    Export,
+    /// A name that belongs to another module, such as an imported name
+    Foreign,
+}
+
+/// Restores original names in `program`, parsed from the minified `code`
+///
+/// Returns the number of identifiers renamed back.
+///
+/// # Errors
+///
+/// Returns `GeneratorError::JsonError` if the program cannot be rebuilt after renaming,
+/// which means the AST and its serialized form disagree.
+pub fn restore_names(
+    program: &mut Program,
+    code: &str,
+    source_map: Option<&SourceMap>,
+    name_map: Option<&NameMap>,
+) -> GeneratorResult<usize> {
+    let mut restorer = NameRestorer::new(code, source_map, name_map);
+    let mut tree = serde_json::to_value(&*program)?;
+    restorer.visit(&mut tree, Role::Variable, None);
+    *program = serde_json::from_value(tree)?;
+    Ok(restorer.restored)
+}
+
+/// Original names by the name they were printed under
+struct NameRestorer {
+    /// Original name of each identifier the source map names, by its byte offset in the code
+    mapped:      HashMap<usize, String>,
+    identifiers: HashMap<String, String>,
+    /// Property names, by the original name of the variable holding the object
+    properties:  HashMap<String, HashMap<String, String>>,
+    exports:     HashMap<String, String>,
+    restored:    usize,
+}
+
+impl NameRestorer {
+    fn new(code: &str, source_map: Option<&SourceMap>, name_map: Option<&NameMap>) -> Self {
+        let mut mapped = HashMap::new();
+        if let Some(source_map) = source_map {
+            let lines = LineIndex::new(code);
+            for mapping in decode_mappings(&source_map.mappings).unwrap_or_default() {
+                if let Some(name) = mapping.name_index.and_then(|index| source_map.names.get(index as usize)) {
+                    mapped.insert(lines.offset(code, mapping.generated), name.clone());
+                }
+            }
+        }
+
+        let name_map = name_map.cloned().unwrap_or_default();
+        Self {
+            mapped,
+            identifiers: invert(&name_map.identifiers),
+            properties: name_map.properties.iter().map(|(owner, names)| (owner.clone(), invert(names))).collect(),
+            exports: invert(&name_map.exports),
+            restored: 0,
+        }
+    }
+
+    /// Visits a serialized node in `role`; `owner` is the variable holding the object
+    /// of the properties below, if any
+    fn visit(&mut self, node: &mut Value, role: Role, owner: Option<&str>) {
+        match node {
+            Value::Array(items) => items.iter_mut().for_each(|item| self.visit(item, role, owner)),
+            Value::Object(fields) => {
+                if is_identifier(fields) {
+                    self.restore(fields, role, owner);
+                    return;
+                }
+                let node_type = fields.get("type").and_then(Value::as_str).unwrap_or_default().to_string();
+                let computed = fields.get("computed").and_then(Value::as_bool).unwrap_or(false);
+                // The variable a declarator or member expression refers to, once restored
+                let owner = match node_type.as_str() {
+                    "MemberExpression" | "VariableDeclarator" | "" => {
+                        let key = if node_type == "MemberExpression" { "object" } else { "id" };
+                        if let Some(child) = fields.get_mut(key) {
+                            self.visit(child, Role::Variable, None);
+                        }
+                        fields.get(key).and_then(|child| child.get("name")).and_then(Value::as_str).map(str::to_string)
+                    }
+                    _ => owner.map(str::to_string),
+                };
+                for (key, child) in fields.iter_mut() {
+                    let child_role = match (node_type.as_str(), key.as_str()) {
+                        ("MemberExpression", "object") | ("VariableDeclarator" | "", "id") => continue,
+                        ("MemberExpression", "property") if !computed => Role::Property,
+                        ("Property" | "MethodDefinition" | "PropertyDefinition", "key") if !computed => Role::Property,
+                        ("ExportSpecifier", "exported") => Role::Export,
+                        ("ImportSpecifier", "imported") => Role::Foreign,
+                        ("MemberExpression", _) => Role::Variable,
+                        _ => role,
+                    };
+                    let child_owner = match node_type.as_str() {
+                        "MemberExpression" => owner.as_deref(),
+                        "VariableDeclarator" | "" => owner.as_deref().filter(|_| key == "init"),
+                        _ if matches!(child_role, Role::Property) => owner.as_deref(),
+                        // Nested objects hold their own properties
+                        "ObjectExpression" | "Property" => owner.as_deref(),
+                        _ => None,
+                    };
+                    self.visit(child, child_role, child_owner);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn restore(&mut self, fields: &mut serde_json::Map<String, Value>, role: Role, owner: Option<&str>) {
+        let Some(Value::String(name)) = fields.get("name") else {
+            return;
+        };
+        let offset = fields.get("span").and_then(|span| span.get("start")).and_then(Value::as_u64);
+        let original = offset
+            .and_then(|offset| self.mapped.get(&(offset as usize)))
+            .or_else(|| match role {
+                Role::Variable => self.identifiers.get(name),
+                Role::Property => owner.and_then(|owner| self.properties.get(owner)).and_then(|names| names.get(name)),
+                Role::Export => self.exports.get(name),
+                Role::Foreign => None,
+            })
+            .filter(|original| *original != name)
+            .cloned();
+        if let Some(original) = original {
+            fields.insert("name".to_string(), Value::String(original));
+            self.restored += 1;
+        }
+    }
+}
+
+/// Whether a serialized node is an identifier; function and class names have no `type`
+fn is_identifier(fields: &serde_json::Map<String, Value>) -> bool {
+    matches!(fields.get("name"), Some(Value::String(_)))
+        && fields.keys().all(|key| matches!(key.as_str(), "type" | "name" | "span"))
+        && fields.get("type").is_none_or(|node_type| node_type == "Identifier")
+}
+
+/// A rename map turned around, from the new name to the original one
+fn invert(names: &BTreeMap<String, String>) -> HashMap<String, String> {
+    names.iter().map(|(original, renamed)| (renamed.clone(), original.clone())).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::generator::{Generator, GeneratorConfig, OutputFormat, SourceMapMode};
+    use crate::parser::{parse_js, ParserConfig};
+
+    fn parse(code: &str) -> Program {
+        parse_js(code, "min.js", &ParserConfig::default()).ast.unwrap()
+    }
+
+    fn print(program: &Program) -> String {
+        Generator::new(GeneratorConfig::default()).generate(program, None).unwrap().code
+    }
+
+    #[test]
+    fn test_restores_names_from_the_name_map() {
+        let code = "const o={a:1,b:2};function f(c){return o.a+o.b+c}export{f as d};";
+        let mut name_map = NameMap::default();
+        name_map.identifiers.insert("value".to_string(), "c".to_string());
+        name_map.properties.insert("o".to_string(), BTreeMap::from([
+            ("width".to_string(), "a".to_string()),
+            ("height".to_string(), "b".to_string()),
+        ]));
+        name_map.exports.insert("area".to_string(), "d".to_string());
+
+        let mut program = parse(code);
+        let restored = restore_names(&mut program, code, None, Some(&name_map)).unwrap();
+        assert_eq!(
+            print(&program),
+            "const o={width:1,height:2};function f(value){return o.width+o.height+value;}export{f as area};"
+        );
+        assert_eq!(restored, 7);
+    }
+
+    #[test]
+    fn test_source_map_names_win_over_the_name_map() {
+        let source = "let count = 1;\nf(count);\n";
+        let json = serde_json::to_string(&parse(source)).unwrap().replace("\"name\":\"count\"", "\"name\":\"c\"");
+        let renamed: Program = serde_json::from_str(&json).unwrap();
+        let config = GeneratorConfig { source_map: SourceMapMode::File, ..GeneratorConfig::default() };
+        let result = Generator::new(config).generate(&renamed, Some(source)).unwrap();
+        assert_eq!(result.code, "let c=1;f(c);");
+
+        let mut name_map = NameMap::default();
+        name_map.identifiers.insert("counter".to_string(), "c".to_string());
+        let mut program = parse(&result.code);
+        restore_names(&mut program, &result.code, result.source_map.as_ref(), Some(&name_map)).unwrap();
+        let pretty = GeneratorConfig { format: OutputFormat::Pretty, ..GeneratorConfig::default() };
+        let code = Generator::new(pretty).generate(&program, None).unwrap().code;
+        assert!(code.contains("let count = 1;"), "{}", code);
+        assert!(code.contains("f(count);"), "{}", code);
+    }
+}
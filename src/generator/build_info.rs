@@ -0,0 +1,106 @@
+//! # Build Metadata
+//!
+//! An optional trailing comment that records what produced an artifact:
+//!
+//! ```text
+//! /*! rjs-compiler 0.1.0 cfg:1a2b3c4d src:5e6f7a8b */
+//! ```
+//!
+//! `cfg` hashes the options that affect the output and `src` hashes the input, so two
+//! artifacts with the same comment came from the same build parameters. Options that
+//! only change how the build runs (verbosity, worker threads, size limits) are left out
+//! of the hash. The `/*!` form survives later minifiers that keep license comments.
+
+use serde::{Deserialize, Serialize};
+
+use crate::generator::GeneratorConfig;
+use crate::transformer::TransformerConfig;
+
+/// Hash of the build options, printed with the input hash after the code
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BuildInfo {
+    pub config_hash: u64,
+}
+
+impl BuildInfo {
+    /// Hashes the output-affecting options of `transformer` and `generator`
+    pub fn new(transformer: &TransformerConfig, generator: &GeneratorConfig) -> Self {
+        let transformer = TransformerConfig {
+            worker_threads: 0,
+            explain: false,
+            ..transformer.clone()
+        };
+        let generator = GeneratorConfig {
+            max_output_size: None,
+            max_memory_usage: None,
+            stream_buffer_size: 0,
+            build_info: None,
+            ..generator.clone()
+        };
+        // Both serialize fields in declaration order, so the JSON is stable
+        let options = serde_json::json!({ "transformer": transformer, "generator": generator });
+        Self { config_hash: fnv1a(options.to_string().as_bytes()) }
+    }
+
+    /// The comment for an artifact built from `source` (`None` leaves out the input hash)
+    pub fn comment(&self, source: Option<&str>) -> String {
+        let source = source.map(|source| format!(" src:{}", short_hash(fnv1a(source.as_bytes())))).unwrap_or_default();
+        format!(
+            "/*! {} {} cfg:{}{} */",
+            env!("CARGO_PKG_NAME"),
+            env!("CARGO_PKG_VERSION"),
+            short_hash(self.config_hash),
+            source
+        )
+    }
+}
+
+/// 64-bit FNV-1a, which is the same on every platform and compiler version
+pub fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf2_9ce4_8422_2325_u64, |hash, &byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(0x0000_0100_0000_01b3)
+    })
+}
+
+/// First 8 hex digits of `hash`
+fn short_hash(hash: u64) -> String {
+    format!("{:016x}", hash)[..8].to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::generator::{Generator, OutputFormat};
+    use crate::parser::{parse_js, ParserConfig};
+
+    #[test]
+    fn test_build_comment_tracks_options_and_input() {
+        let transformer = TransformerConfig::default();
+        let generator = GeneratorConfig::default();
+        let info = BuildInfo::new(&transformer, &generator);
+
+        let comment = info.comment(Some("let a = 1;"));
+        assert!(comment.starts_with(&format!("/*! rjs-compiler {} cfg:", env!("CARGO_PKG_VERSION"))), "{}", comment);
+        assert!(comment.ends_with(" */"), "{}", comment);
+        assert_ne!(comment, info.comment(Some("let a = 2;")));
+        assert!(!info.comment(None).contains("src:"));
+
+        // Only options that change the output change the hash
+        assert_eq!(info, BuildInfo::new(&transformer.clone().with_worker_threads(4), &generator));
+        assert_ne!(info, BuildInfo::new(&transformer.clone().with_name_seed(Some(1)), &generator));
+        let pretty = GeneratorConfig { format: OutputFormat::Pretty, ..GeneratorConfig::default() };
+        assert_ne!(info, BuildInfo::new(&transformer, &pretty));
+    }
+
+    #[test]
+    fn test_generator_appends_build_comment() {
+        let source = "f(1);";
+        let ast = parse_js(source, "test.js", &ParserConfig::default()).ast.unwrap();
+        let info = BuildInfo { config_hash: 0xab12_0000_0000_0000 };
+        let config = GeneratorConfig { build_info: Some(info), ..GeneratorConfig::default() };
+        let code = Generator::new(config).generate(&ast, Some(source)).unwrap().code;
+
+        assert_eq!(code, format!("f(1);{}", info.comment(Some(source))));
+        assert!(code.contains("cfg:ab120000 src:"), "{}", code);
+    }
+}
@@ -0,0 +1,213 @@
+//! # Bundle Report
+//!
+//! Where the bytes of an output come from, as a tree: the output file, the source
+//! modules in it and the top-level declarations of each module, every node sized in
+//! output bytes. Sizes come from the source map segments through
+//! [`size_budget::attribute`](crate::generator::size_budget::attribute).
+//!
+//! The JSON form is the `{ name, value, children }` hierarchy that treemap tools such
+//! as d3-hierarchy read directly. The HTML form is a single page that draws the same
+//! tree as a treemap, with no external scripts.
+
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+
+use crate::generator::size_budget::{Contributor, OutputSize};
+
+/// Format of a bundle report
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum BundleReportFormat {
+    /// Treemap hierarchy as JSON
+    #[default]
+    Json,
+    /// Self-contained page with a treemap
+    Html,
+}
+
+impl FromStr for BundleReportFormat {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "json" => Ok(Self::Json),
+            "html" => Ok(Self::Html),
+            _ => Err(format!("unknown bundle report format '{}' (expected json or html)", value)),
+        }
+    }
+}
+
+/// One node of the report tree, sized in output bytes
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TreemapNode {
+    pub name:      String,
+    /// Output bytes, the sum of the children for inner nodes
+    pub value:     usize,
+    /// Size after gzip, on the root only
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub gzip_size: Option<usize>,
+    /// One-based line in the module, for declarations
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub line:      Option<usize>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub children:  Vec<TreemapNode>,
+}
+
+/// The report tree of the output `output_name`
+///
+/// `modules` pairs each source module with the contributors attributed to it;
+/// contributors without a line (unmapped bytes, imports added by the compiler) become
+/// children of the root rather than of their module.
+pub fn bundle_report(output_name: &str, size: &OutputSize, modules: Vec<(String, Vec<Contributor>)>) -> TreemapNode {
+    let mut children = Vec::new();
+    let mut loose = Vec::new();
+    for (module, contributors) in modules {
+        let (declarations, other): (Vec<_>, Vec<_>) =
+            contributors.into_iter().partition(|contributor| contributor.line.is_some());
+        loose.extend(other.into_iter().map(leaf));
+        if !declarations.is_empty() {
+            let declarations: Vec<TreemapNode> = declarations.into_iter().map(leaf).collect();
+            children.push(TreemapNode {
+                name:      module,
+                value:     declarations.iter().map(|node| node.value).sum(),
+                gzip_size: None,
+                line:      None,
+                children:  declarations,
+            });
+        }
+    }
+    children.sort_by_key(|node| std::cmp::Reverse(node.value));
+    children.extend(loose);
+
+    TreemapNode {
+        name:      output_name.to_string(),
+        value:     size.bytes,
+        gzip_size: Some(size.gzip_bytes),
+        line:      None,
+        children,
+    }
+}
+
+/// The report in `format`
+pub fn render_report(report: &TreemapNode, format: BundleReportFormat) -> String {
+    let json = serde_json::to_string_pretty(report).unwrap_or_default();
+    match format {
+        BundleReportFormat::Json => json + "\n",
+        // `<\/` keeps a name like `</script>` from closing the script element
+        BundleReportFormat::Html => HTML_TEMPLATE
+            .replace("{{title}}", &escape_html(&report.name))
+            .replace("{{report}}", &json.replace("</", "<\\/")),
+    }
+}
+
+fn leaf(contributor: Contributor) -> TreemapNode {
+    TreemapNode {
+        name:      contributor.name,
+        value:     contributor.bytes,
+        gzip_size: None,
+        line:      contributor.line,
+        children:  Vec::new(),
+    }
+}
+
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;").replace('"', "&quot;")
+}
+
+/// Page that lays the report out as a slice-and-dice treemap, alternating the
+/// direction at each level
+const HTML_TEMPLATE: &str = r#"<!DOCTYPE html>
+<html lang="en">
+<head>
+<meta charset="utf-8">
+<title>Bundle report: {{title}}</title>
+<style>
+  body { margin: 0; font: 13px system-ui, sans-serif; }
+  header { padding: 8px 12px; background: #222; color: #eee; }
+  #map { position: relative; height: calc(100vh - 36px); }
+  .node { position: absolute; box-sizing: border-box; border: 1px solid #fff; overflow: hidden; padding: 2px 4px; }
+  .depth-1 { background: #8ab4f8; } .depth-2 { background: #c6dafc; } .loose { background: #ddd; }
+</style>
+</head>
+<body>
+<header id="summary"></header>
+<div id="map"></div>
+<script>
+const report = {{report}};
+const format = (bytes) => bytes >= 1024 ? (bytes / 1024).toFixed(1) + ' KB' : bytes + ' B';
+document.getElementById('summary').textContent =
+  report.name + ': ' + format(report.value) + ' (' + format(report.gzipSize) + ' gzip)';
+const map = document.getElementById('map');
+function layout(node, x, y, width, height, depth) {
+  if (depth > 0) {
+    const box = document.createElement('div');
+    box.className = 'node ' + (node.line === undefined && !node.children ? 'loose' : 'depth-' + Math.min(depth, 2));
+    Object.assign(box.style, { left: x + '%', top: y + '%', width: width + '%', height: height + '%' });
+    box.title = node.name + (node.line ? ' (line ' + node.line + ')' : '') + ': ' + format(node.value);
+    box.textContent = node.name;
+    map.appendChild(box);
+  }
+  const total = (node.children || []).reduce((sum, child) => sum + child.value, 0);
+  let offset = 0;
+  for (const child of node.children || []) {
+    const share = total ? child.value / total : 0;
+    if (depth % 2 === 0) {
+      layout(child, x + offset * width, y, share * width, height, depth + 1);
+    } else {
+      layout(child, x, y + offset * height, width, share * height, depth + 1);
+    }
+    offset += share;
+  }
+}
+layout(report, 0, 0, 100, 100, 0);
+</script>
+</body>
+</html>
+"#;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn contributor(name: &str, line: Option<usize>, bytes: usize) -> Contributor {
+        Contributor { name: name.to_string(), line, bytes }
+    }
+
+    #[test]
+    fn test_report_groups_declarations_under_modules() {
+        let size = OutputSize { bytes: 120, gzip_bytes: 90 };
+        let contributors = vec![
+            contributor("function render", Some(3), 80),
+            contributor("const a", Some(1), 30),
+            contributor("(unmapped)", None, 10),
+        ];
+        let report = bundle_report("app.min.js", &size, vec![("src/app.js".to_string(), contributors)]);
+
+        assert_eq!(report.value, 120);
+        assert_eq!(report.children.len(), 2);
+        assert_eq!(report.children[0].name, "src/app.js");
+        assert_eq!(report.children[0].value, 110);
+        assert_eq!(report.children[0].children[0].line, Some(3));
+        assert_eq!(report.children[1].name, "(unmapped)");
+
+        let json: serde_json::Value = serde_json::from_str(&render_report(&report, BundleReportFormat::Json)).unwrap();
+        assert_eq!(json["gzipSize"], 90);
+        assert_eq!(json["children"][0]["children"][1]["name"], "const a");
+        assert!(json["children"][1].get("children").is_none());
+        assert!("xml".parse::<BundleReportFormat>().is_err());
+    }
+
+    #[test]
+    fn test_html_report_embeds_escaped_tree() {
+        let size = OutputSize { bytes: 10, gzip_bytes: 20 };
+        let contributors = vec![contributor("const s = '</script>'", Some(1), 10)];
+        let report = bundle_report("<out>.js", &size, vec![("a.js".to_string(), contributors)]);
+        let html = render_report(&report, BundleReportFormat::Html);
+
+        assert!(html.contains("<title>Bundle report: &lt;out&gt;.js</title>"), "{}", html);
+        assert!(html.contains("<\\/script>"), "{}", html);
+        assert_eq!(html.matches("</script>").count(), 1);
+        assert!(!html.contains("{{"), "{}", html);
+    }
+}
@@ -0,0 +1,157 @@
+//! # Declaration Passthrough
+//!
+//! Libraries written in JavaScript often ship hand-written TypeScript declarations next
+//! to their modules (`util.js` and `util.d.ts`). Once the modules are compiled to other
+//! names (`util.min.js`), TypeScript looks for `util.min.d.ts` instead, and the relative
+//! specifiers inside the declarations still name the sources.
+//!
+//! [`declaration_path`] names the declaration file TypeScript looks for next to a
+//! module, and [`rewrite_specifiers`] replaces the module specifiers of a declaration
+//! file (`from "./util.js"`, `import("./util")`, `require("./util")`,
+//! `declare module "./util"` and `/// <reference path="./util.d.ts" />`) without
+//! parsing its types.
+
+use std::path::{Path, PathBuf};
+
+/// The declaration file TypeScript reads for the module at `path`: `a.js` and
+/// `a.min.js` have `a.d.ts` and `a.min.d.ts`, and `.mjs` and `.cjs` modules have
+/// `.d.mts` and `.d.cts` declarations
+///
+/// ```rust
+/// use std::path::Path;
+/// use rjs_compiler::generator::declarations::declaration_path;
+///
+/// assert_eq!(declaration_path(Path::new("dist/util.min.js")), Path::new("dist/util.min.d.ts"));
+/// assert_eq!(declaration_path(Path::new("src/index.mjs")), Path::new("src/index.d.mts"));
+/// ```
+pub fn declaration_path(path: &Path) -> PathBuf {
+    let extension = match path.extension().and_then(|extension| extension.to_str()) {
+        Some("mjs") => "d.mts",
+        Some("cjs") => "d.cts",
+        _ => "d.ts",
+    };
+    path.with_extension(extension)
+}
+
+/// Returns `source` with each module specifier for which `rewrite` returns a
+/// replacement replaced
+///
+/// Specifiers are the string literals after `from`, `import`, `import(`, `require(`
+/// and `module`, and the `path` of `/// <reference />` directives. Quotes are kept, so
+/// a replacement must not contain the quote character of its literal.
+pub fn rewrite_specifiers(source: &str, mut rewrite: impl FnMut(&str) -> Option<String>) -> String {
+    let bytes = source.as_bytes();
+    let mut output = String::with_capacity(source.len());
+    let mut copied = 0;
+    let mut replace = |start: usize, end: usize, output: &mut String, copied: &mut usize| {
+        if let Some(replacement) = rewrite(&source[start..end]) {
+            output.push_str(&source[*copied..start]);
+            output.push_str(&replacement);
+            *copied = end;
+        }
+    };
+
+    // The word or punctuation before the current position, which decides whether a
+    // string literal is a specifier
+    let mut previous = "";
+    let mut index = 0;
+    while index < bytes.len() {
+        let rest = &source[index..];
+        if rest.starts_with("//") {
+            let end = rest.find('\n').map_or(bytes.len(), |end| index + end);
+            if rest.starts_with("///")
+                && let Some((start, value_end)) = reference_path(&source[index..end])
+            {
+                replace(index + start, index + value_end, &mut output, &mut copied);
+            }
+            index = end;
+        } else if rest.starts_with("/*") {
+            index = rest.find("*/").map_or(bytes.len(), |end| index + end + 2);
+        } else if matches!(bytes[index], b'"' | b'\'' | b'`') {
+            let end = string_end(bytes, index);
+            if matches!(previous, "from" | "import" | "import(" | "require(" | "module") && bytes[index] != b'`' {
+                replace(index + 1, end - 1, &mut output, &mut copied);
+            }
+            previous = "";
+            index = end;
+        } else if bytes[index].is_ascii_alphanumeric() || matches!(bytes[index], b'_' | b'$') {
+            let length = rest
+                .bytes()
+                .take_while(|byte| byte.is_ascii_alphanumeric() || matches!(byte, b'_' | b'$'))
+                .count();
+            previous = &source[index..index + length];
+            index += length;
+        } else if bytes[index].is_ascii_whitespace() {
+            index += 1;
+        } else {
+            previous = match (previous, bytes[index]) {
+                ("import", b'(') => "import(",
+                ("require", b'(') => "require(",
+                _ => "",
+            };
+            // Keep multi-byte characters whole
+            index += rest.chars().next().map_or(1, char::len_utf8);
+        }
+    }
+    output.push_str(&source[copied..]);
+    output
+}
+
+/// Position of the closing quote of the string literal starting at `start`, plus one
+fn string_end(bytes: &[u8], start: usize) -> usize {
+    let quote = bytes[start];
+    let mut index = start + 1;
+    while index < bytes.len() {
+        match bytes[index] {
+            b'\\' => index += 2,
+            byte if byte == quote => return index + 1,
+            _ => index += 1,
+        }
+    }
+    bytes.len()
+}
+
+/// Range of the `path` value in a `/// <reference path="..." />` comment
+fn reference_path(comment: &str) -> Option<(usize, usize)> {
+    let directive = comment.find("<reference")?;
+    let attribute = directive + comment[directive..].find("path=")?;
+    let quote = *comment.as_bytes().get(attribute + 5)?;
+    if !matches!(quote, b'"' | b'\'') {
+        return None;
+    }
+    let start = attribute + 6;
+    let end = start + comment[start..].find(quote as char)?;
+    Some((start, end))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rewrites_module_specifiers() {
+        let source = "/// <reference path=\"./globals.d.ts\" />\n\
+                      import { Size } from './util.js';\n\
+                      import './setup';\n\
+                      export * from \"./shapes\";\n\
+                      export type Lazy = typeof import('./util.js');\n\
+                      import fs = require('./fs');\n\
+                      declare module './shapes' { interface Shape { area(): number } }\n\
+                      // from './comment'\n\
+                      export declare const label: 'from ./label';\n\
+                      export declare function scale(size: Size, by: number): Size;\n";
+        let mut seen = Vec::new();
+        let rewritten = rewrite_specifiers(source, |specifier| {
+            seen.push(specifier.to_string());
+            (specifier != "./setup").then(|| specifier.replace("./", "./min/"))
+        });
+
+        assert_eq!(seen, ["./globals.d.ts", "./util.js", "./setup", "./shapes", "./util.js", "./fs", "./shapes"]);
+        assert!(rewritten.starts_with("/// <reference path=\"./min/globals.d.ts\" />\nimport { Size } from './min/util.js';\nimport './setup';\n"), "{}", rewritten);
+        assert!(rewritten.contains("export * from \"./min/shapes\";"), "{}", rewritten);
+        assert!(rewritten.contains("typeof import('./min/util.js')"), "{}", rewritten);
+        assert!(rewritten.contains("import fs = require('./min/fs');"), "{}", rewritten);
+        assert!(rewritten.contains("declare module './min/shapes'"), "{}", rewritten);
+        assert!(rewritten.contains("// from './comment'\nexport declare const label: 'from ./label';"), "{}", rewritten);
+    }
+}
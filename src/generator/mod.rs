@@ -24,11 +24,21 @@
 //! - **Precedence Correctness**: Accurate operator precedence and associativity
 //! - **Unicode Safety**: Proper handling of all Unicode characters and escapes
 
+use crate::cancel::{CancellationToken, Cancelled};
 use crate::parser::ast_types::*;
+use crate::parser::comments::AttachedComments;
+use crate::target::Targets;
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use thiserror::Error;
 
+pub mod beautify;
+pub mod build_info;
+pub mod bundle_report;
+pub mod declarations;
+pub mod module_format;
 pub mod printer;
+pub mod size_budget;
 pub mod source_maps;
 
 #[cfg(test)]
@@ -37,10 +47,23 @@ mod tests;
 /// Generator configuration for output formatting and optimization
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct GeneratorConfig {
-    /// ECMAScript target version
-    pub ecma: EcmaScriptVersion,
+    /// Target environments; syntax they lack is rejected instead of printed
+    pub target: Targets,
     /// Output format style
     pub format: OutputFormat,
+    /// Module system of the output (`import`/`export`, `require`, or a wrapper function)
+    #[serde(default)]
+    pub module_format: module_format::ModuleFormat,
+    /// Global variable that IIFE and UMD output store the module's exports in
+    #[serde(default)]
+    pub global_name: Option<String>,
+    /// Turn dynamic `import()` into `require()` for CommonJS output, and top-level
+    /// `require()` calls into imports for ESM output (see [`module_format`])
+    #[serde(default)]
+    pub module_interop: bool,
+    /// Append a comment with the tool version and the config and input hashes
+    #[serde(default)]
+    pub build_info: Option<build_info::BuildInfo>,
     /// Semicolon insertion strategy
     pub semicolon: SemicolonStrategy,
     /// Quote character preference
@@ -57,19 +80,31 @@ pub struct GeneratorConfig {
     pub mapping_granularity: MappingGranularity,
     /// Output newline style
     pub newline: NewlineStyle,
-    /// Maximum line length for wrapping
+    /// Column limit for readable and pretty output; longer argument lists, parameter
+    /// lists and binary expressions are wrapped (`None` never wraps)
     pub max_line_len: Option<usize>,
+    /// Indentation unit of pretty output
+    pub indent: IndentStyle,
+    /// Whether number literals keep their source spelling
+    pub numbers: NumberStyle,
     /// Character set escape mode
     pub charset_escapes: CharsetEscapes,
+    /// Maximum total size of the generated output in bytes (`None` for unlimited)
+    pub max_output_size: Option<usize>,
+    /// Maximum size of the in-memory output buffer in bytes (`None` for unlimited)
+    pub max_memory_usage: Option<usize>,
+    /// Buffer size at which streaming generation flushes to the writer
+    pub stream_buffer_size: usize,
+    /// Stops printing before the next top-level statement once cancelled
+    #[serde(skip)]
+    pub cancellation: CancellationToken,
 }
 
-/// ECMAScript version target
-#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
-pub enum EcmaScriptVersion {
-    ES5,
-    ES2015,
-    Latest,
-}
+/// Default limit for the total generated output size (10MB)
+pub const DEFAULT_MAX_OUTPUT_SIZE: usize = 10 * 1024 * 1024;
+
+/// Default flush threshold for streaming generation (64KB)
+pub const DEFAULT_STREAM_BUFFER_SIZE: usize = 64 * 1024;
 
 /// Output format style
 #[derive(Debug, Clone, Copy, Serialize, Deserialize)]
@@ -104,6 +139,83 @@ pub enum QuoteStrategy {
     Double,
 }
 
+impl std::str::FromStr for SemicolonStrategy {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "auto" => Ok(Self::Auto),
+            "always" => Ok(Self::Always),
+            "remove" => Ok(Self::Remove),
+            _ => Err(format!("unknown semicolon strategy '{}' (expected auto, always or remove)", value)),
+        }
+    }
+}
+
+impl std::str::FromStr for QuoteStrategy {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "auto" => Ok(Self::Auto),
+            "single" => Ok(Self::Single),
+            "double" => Ok(Self::Double),
+            _ => Err(format!("unknown quote strategy '{}' (expected auto, single or double)", value)),
+        }
+    }
+}
+
+/// Indentation unit of pretty output
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum IndentStyle {
+    /// The given number of spaces per level
+    Spaces(usize),
+    /// One tab per level
+    Tabs,
+}
+
+impl IndentStyle {
+    /// Text of one indentation level
+    pub fn unit(self) -> String {
+        match self {
+            IndentStyle::Spaces(width) => " ".repeat(width),
+            IndentStyle::Tabs => "\t".to_string(),
+        }
+    }
+}
+
+impl Default for IndentStyle {
+    fn default() -> Self {
+        IndentStyle::Spaces(2)
+    }
+}
+
+impl std::str::FromStr for IndentStyle {
+    type Err = String;
+
+    /// Parses a number of spaces (at most 16) or `tab`
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "tab" | "tabs" => Ok(Self::Tabs),
+            _ => match value.parse::<usize>() {
+                Ok(width) if width <= 16 => Ok(Self::Spaces(width)),
+                _ => Err(format!("invalid indent '{}' (expected a number of spaces up to 16, or tab)", value)),
+            },
+        }
+    }
+}
+
+/// How number literals are printed
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum NumberStyle {
+    /// Shortest spelling of the value: `1e6`, `.5`, `0xffffffffff`
+    Shortest,
+    /// Source spelling (`0x1F`, `0b1010`, `1_000_000`) where known, else the shortest.
+    /// Legacy octal (`017`) is re-canonicalized, and separators are dropped for
+    /// targets without them.
+    Preserve,
+}
+
 /// Comment preservation level
 #[derive(Debug, Clone, Copy, Serialize, Deserialize)]
 pub enum CommentPreservation {
@@ -111,7 +223,9 @@ pub enum CommentPreservation {
     None,
     /// Preserve license comments only
     License,
-    /// Preserve all comments
+    /// Preserve JSDoc blocks of exported functions and classes (see [`Generator::with_doc_comments`])
+    JsDoc,
+    /// Preserve all comments, next to the statements they are attached to (see [`Generator::with_comments`])
     All,
 }
 
@@ -242,6 +356,8 @@ pub enum GeneratorError {
     },
     #[error("Generation timeout: operation exceeded {timeout_ms}ms")]
     GenerationTimeout { timeout_ms: u64 },
+    #[error("Generation cancelled")]
+    Cancelled,
     #[error("Invalid configuration: {message}")]
     InvalidConfiguration { message: String },
     #[error("Template literal error: {message} in template: {template}")]
@@ -254,6 +370,19 @@ pub enum GeneratorError {
         message: String,
         identifier: String,
     },
+    #[error("Top-level await requires module output, but the program is a script (parse it as a module or move the await into an async function)")]
+    TopLevelAwaitInScript,
+    #[error("Top-level await cannot be emitted as {} output, which runs as a script (use --output-format esm or move the await into an async function)", .format.display_name())]
+    TopLevelAwaitInFormat { format: module_format::ModuleFormat },
+}
+
+impl From<Cancelled> for GeneratorError {
+    fn from(cancelled: Cancelled) -> Self {
+        match cancelled {
+            Cancelled::Requested => Self::Cancelled,
+            Cancelled::DeadlineExceeded { timeout_ms } => Self::GenerationTimeout { timeout_ms },
+        }
+    }
 }
 
 /// Result type alias for generator operations
@@ -262,13 +391,21 @@ pub type GeneratorResult<T> = Result<T, GeneratorError>;
 /// Main generator implementation
 pub struct Generator {
     config: GeneratorConfig,
+    /// JSDoc blocks of exported declarations, by declared name
+    doc_comments: HashMap<String, String>,
+    /// Source comments attached to statements
+    comments: AttachedComments,
 }
 
 impl Default for GeneratorConfig {
     fn default() -> Self {
         Self {
-            ecma: EcmaScriptVersion::Latest,
+            target: Targets::latest(),
             format: OutputFormat::Compact,
+            module_format: module_format::ModuleFormat::Esm,
+            global_name: None,
+            module_interop: false,
+            build_info: None,
             semicolon: SemicolonStrategy::Auto,
             quote: QuoteStrategy::Auto,
             preserve_comments: CommentPreservation::None,
@@ -278,7 +415,13 @@ impl Default for GeneratorConfig {
             mapping_granularity: MappingGranularity::Token,
             newline: NewlineStyle::Lf,
             max_line_len: None,
+            indent: IndentStyle::default(),
+            numbers: NumberStyle::Shortest,
             charset_escapes: CharsetEscapes::Minimal,
+            max_output_size: Some(DEFAULT_MAX_OUTPUT_SIZE),
+            max_memory_usage: None,
+            stream_buffer_size: DEFAULT_STREAM_BUFFER_SIZE,
+            cancellation: CancellationToken::new(),
         }
     }
 }
@@ -299,7 +442,26 @@ impl Generator {
     /// let generator = Generator::new(config);
     /// ```
     pub fn new(config: GeneratorConfig) -> Self {
-        Self { config }
+        Self { config, doc_comments: HashMap::new(), comments: AttachedComments::default() }
+    }
+
+    /// Sets the JSDoc blocks to print before exported functions and classes
+    ///
+    /// `doc_comments` maps declared names to comment text, as returned by
+    /// [`ArenaParseResult::doc_comments`](crate::parser::ArenaParseResult::doc_comments).
+    /// They are printed only with [`CommentPreservation::JsDoc`].
+    pub fn with_doc_comments(mut self, doc_comments: HashMap<String, String>) -> Self {
+        self.doc_comments = doc_comments;
+        self
+    }
+
+    /// Sets the source comments to print around the statements they are attached to
+    ///
+    /// `comments` is usually [`ArenaParseResult::attached_comments`](crate::parser::ArenaParseResult::attached_comments).
+    /// They are printed only with [`CommentPreservation::All`].
+    pub fn with_comments(mut self, comments: AttachedComments) -> Self {
+        self.comments = comments;
+        self
     }
 
     /// Generate JavaScript code from an AST
@@ -325,7 +487,7 @@ impl Generator {
     ///
     /// ```rust,no_run
     /// use rjs_compiler::generator::{Generator, GeneratorConfig};
-    /// use rjs_compiler::parser::ast_types::Program;
+    /// use rjs_compiler::parser::ast_types::{Program, ProgramSourceType};
     ///
     /// let config = GeneratorConfig::default();
     /// let generator = Generator::new(config);
@@ -341,19 +503,37 @@ impl Generator {
         program: &Program,
         original_source: Option<&str>,
     ) -> GeneratorResult<GenerationResult> {
-        let start_time = std::time::Instant::now();
+        let start_time = crate::Instant::now();
+        
+        // Convert the module syntax for the output format
+        let wrapper = module_format::wrapper(program, &self.config);
+        let format_warnings = module_format::warnings(program, &self.config)?;
+        let from_module = matches!(program.source_type, ProgramSourceType::Module);
+        let converted = module_format::convert(program, &self.config)?;
+        let program = converted.as_ref().unwrap_or(program);
         
         // Initialize printer with configuration
-        let mut printer = printer::Printer::new(&self.config);
+        let mut printer = printer::Printer::new(&self.config)
+            .with_doc_comments(&self.doc_comments)
+            .with_comments(&self.comments);
         
         // Generate code from AST
-        let code = printer.print_program(program)?;
+        let code = printer.print_program(program).map_err(|error| self.format_error(error, from_module))?;
+        let code = format!("{}{}{}{}", wrapper.prefix, code, wrapper.suffix, self.build_comment(original_source));
         
-        // Generate source map if requested
+        // Generate source map if requested, with positions shifted past the wrapper
         let source_map = if matches!(self.config.source_map, SourceMapMode::None) {
             None
         } else {
-            Some(self.generate_source_map(program, &code, original_source)?)
+            let positions: Vec<_> = printer
+                .source_positions()
+                .iter()
+                .map(|position| printer::SourcePosition {
+                    generated: position.generated + wrapper.prefix.len(),
+                    ..*position
+                })
+                .collect();
+            Some(self.generate_source_map(&positions, &code, original_source))
         };
         
         // Calculate diagnostics
@@ -371,8 +551,8 @@ impl Generator {
             generated_size,
             compression_ratio,
             generation_time_ms: generation_time.as_secs_f64() * 1000.0,
-            warning_count: printer.get_warnings().len(),
-            warnings: printer.get_warnings(),
+            warning_count: printer.get_warnings().len() + format_warnings.len(),
+            warnings: printer.get_warnings().into_iter().chain(format_warnings).collect(),
         };
         
         Ok(GenerationResult {
@@ -382,16 +562,209 @@ impl Generator {
         })
     }
     
-    /// Generate source map for the given program and generated code
+    /// Generate JavaScript code from an AST, streaming it to a writer
+    ///
+    /// Output is flushed to `writer` whenever the internal buffer reaches
+    /// `stream_buffer_size`, so memory use stays bounded for very large bundles.
+    ///
+    /// # Arguments
+    ///
+    /// * `program` - The AST program to generate code from
+    /// * `original_source` - Optional original source used for size diagnostics
+    /// * `writer` - Destination for the generated code
+    ///
+    /// # Returns
+    ///
+    /// Returns the generation diagnostics; the code itself has been written to `writer`.
+    ///
+    /// # Errors
+    ///
+    /// This function returns an error if:
+    /// - Source map generation is requested, as it needs the complete output
+    /// - Writing to `writer` fails
+    /// - The configured output size or memory limits are exceeded
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use std::fs::File;
+    /// use std::io::BufWriter;
+    /// use rjs_compiler::generator::{Generator, GeneratorConfig};
+    /// # use rjs_compiler::parser::{parse_js, ParserConfig};
+    /// # let program = parse_js("let x = 1;", "bundle.js", &ParserConfig::default()).ast.unwrap();
+    ///
+    /// let generator = Generator::new(GeneratorConfig::default());
+    /// let mut writer = BufWriter::new(File::create("bundle.min.js")?);
+    /// let diagnostics = generator.generate_to_writer(&program, None, &mut writer)?;
+    /// println!("Wrote {} bytes", diagnostics.generated_size);
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    pub fn generate_to_writer(
+        &self,
+        program: &Program,
+        original_source: Option<&str>,
+        writer: &mut dyn std::io::Write,
+    ) -> GeneratorResult<GeneratorDiagnostics> {
+        if !matches!(self.config.source_map, SourceMapMode::None) {
+            return Err(GeneratorError::InvalidConfiguration {
+                message: "source maps are not supported with streaming output".to_string(),
+            });
+        }
+
+        let start_time = crate::Instant::now();
+
+        let wrapper = module_format::wrapper(program, &self.config);
+        let format_warnings = module_format::warnings(program, &self.config)?;
+        let from_module = matches!(program.source_type, ProgramSourceType::Module);
+        let converted = module_format::convert(program, &self.config)?;
+        let program = converted.as_ref().unwrap_or(program);
+
+        let mut printer = printer::Printer::new(&self.config)
+            .with_doc_comments(&self.doc_comments)
+            .with_comments(&self.comments);
+        writer.write_all(wrapper.prefix.as_bytes())?;
+        let generated_size =
+            printer.print_program_to_writer(program, writer).map_err(|error| self.format_error(error, from_module))?;
+        writer.write_all(wrapper.suffix.as_bytes())?;
+        let build_comment = self.build_comment(original_source);
+        writer.write_all(build_comment.as_bytes())?;
+        writer.flush()?;
+        let generated_size = generated_size + wrapper.prefix.len() + wrapper.suffix.len() + build_comment.len();
+
+        let original_size = original_source.map(|s| s.len()).unwrap_or(0);
+        let compression_ratio = if original_size > 0 {
+            1.0 - (generated_size as f64 / original_size as f64)
+        } else {
+            0.0
+        };
+
+        Ok(GeneratorDiagnostics {
+            original_size,
+            generated_size,
+            compression_ratio,
+            generation_time_ms: start_time.elapsed().as_secs_f64() * 1000.0,
+            warning_count: printer.get_warnings().len() + format_warnings.len(),
+            warnings: printer.get_warnings().into_iter().chain(format_warnings).collect(),
+        })
+    }
+    
+    /// Blames top-level await in a module on the output format it was converted for,
+    /// since the module itself allows it
+    fn format_error(&self, error: GeneratorError, from_module: bool) -> GeneratorError {
+        match error {
+            GeneratorError::TopLevelAwaitInScript if from_module => {
+                GeneratorError::TopLevelAwaitInFormat { format: self.config.module_format }
+            }
+            error => error,
+        }
+    }
+
+    /// The build metadata comment to print after the code, if enabled
+    ///
+    /// Compact output keeps it on the last line; formatted output, which ends with a
+    /// newline, gets a line of its own.
+    fn build_comment(&self, original_source: Option<&str>) -> String {
+        match &self.config.build_info {
+            Some(info) if matches!(self.config.format, OutputFormat::Compact) => info.comment(original_source),
+            Some(info) => format!("{}\n", info.comment(original_source)),
+            None => String::new(),
+        }
+    }
+
+    /// Generate the source map of `code` from the positions the printer recorded
+    ///
+    /// Positions are resolved to lines and UTF-16 columns in `code` and `original_source`;
+    /// without the original source the map lists the source but has no mappings.
     fn generate_source_map(
         &self,
-        _program: &Program,
-        _code: &str,
-        _original_source: Option<&str>,
-    ) -> GeneratorResult<source_maps::SourceMap> {
-        // TODO: Implement source map generation
-        // This is a placeholder implementation
-        Ok(source_maps::SourceMap::new())
+        positions: &[printer::SourcePosition],
+        code: &str,
+        original_source: Option<&str>,
+    ) -> source_maps::SourceMap {
+        let mut builder = source_maps::SourceMapBuilder::new();
+        let source_index = builder.add_source("<unknown>");
+
+        if let Some(source) = original_source {
+            if self.config.include_sources_content {
+                builder.add_source_content(source);
+            }
+
+            let generated_lines = LineIndex::new(code);
+            let original_lines = LineIndex::new(source);
+            let mut mappings: Vec<source_maps::Mapping> = Vec::new();
+            for position in positions {
+                let generated = generated_lines.position(code, position.generated);
+                let original = original_lines.position(source, position.original as usize);
+                let name_index = renamed_from(position, code, source).map(|name| builder.add_name(name));
+                // Nested nodes often start where their parent does; an identifier there
+                // still carries its original name
+                if let Some(previous) = mappings.last_mut()
+                    && previous.generated == generated
+                {
+                    if name_index.is_some() {
+                        previous.original = Some(original);
+                        previous.name_index = name_index;
+                    }
+                    continue;
+                }
+                mappings.push(source_maps::Mapping {
+                    generated,
+                    original: Some(original),
+                    source_index: Some(source_index),
+                    name_index,
+                });
+            }
+            mappings.into_iter().for_each(|mapping| builder.add_mapping(mapping));
+        }
+
+        let mut source_map = builder.build();
+        source_map.source_root = self.config.source_root.clone();
+        source_map
+    }
+}
+
+/// The original name of an identifier printed under a different one
+fn renamed_from<'a>(position: &printer::SourcePosition, code: &str, source: &'a str) -> Option<&'a str> {
+    let original = source.get(position.original as usize..position.original_end? as usize)?;
+    let printed = code.get(position.generated..position.generated + position.printed_len)?;
+    (original != printed).then_some(original)
+}
+
+/// Byte offsets of the line starts of a text, for resolving offsets to source map positions
+struct LineIndex {
+    starts: Vec<usize>,
+}
+
+impl LineIndex {
+    fn new(text: &str) -> Self {
+        let starts = std::iter::once(0).chain(text.match_indices('\n').map(|(offset, _)| offset + 1)).collect();
+        Self { starts }
+    }
+
+    /// Zero-based line and UTF-16 column of the byte `offset` in `text`
+    fn position(&self, text: &str, offset: usize) -> source_maps::Position {
+        let offset = offset.min(text.len());
+        let line = self.starts.partition_point(|&start| start <= offset) - 1;
+        let column = text
+            .get(self.starts[line]..offset)
+            .map_or(0, |prefix| prefix.encode_utf16().count());
+        source_maps::Position { line: line as u32, column: column as u32 }
+    }
+
+    /// Byte offset in `text` of a zero-based line and UTF-16 column, the inverse of
+    /// [`position`](Self::position); positions past the end resolve to the end
+    fn offset(&self, text: &str, position: source_maps::Position) -> usize {
+        let Some(&start) = self.starts.get(position.line as usize) else {
+            return text.len();
+        };
+        let mut column = 0;
+        for (offset, c) in text[start..].char_indices() {
+            if column >= position.column as usize || c == '\n' {
+                return start + offset;
+            }
+            column += c.len_utf16();
+        }
+        text.len()
     }
 }
 
@@ -401,11 +774,11 @@ impl GeneratorConfig {
     ///
     /// # Arguments
     ///
-    /// * `ecma` - ECMAScript version string ("es5", "es2015", "latest")
+    /// * `target` - Target list ("es5", "es2015", "latest", "chrome90,node16"); invalid lists fall back to latest
     /// * `format` - Format string ("compact", "readable", "pretty")
     /// * `semicolon` - Semicolon strategy ("auto", "always", "remove")
     /// * `quote` - Quote strategy ("auto", "single", "double")
-    /// * `preserve_comments` - Comment preservation ("none", "license", "all")
+    /// * `preserve_comments` - Comment preservation ("none", "license", "jsdoc", "all")
     /// * `source_map` - Source map mode ("none", "file", "inline", "indexed")
     ///
     /// # Examples
@@ -423,7 +796,7 @@ impl GeneratorConfig {
     /// );
     /// ```
     pub fn from_cli_args(
-        ecma: &str,
+        target: &str,
         format: &str,
         semicolon: &str,
         quote: &str,
@@ -432,11 +805,7 @@ impl GeneratorConfig {
     ) -> Self {
         let mut config = Self::default();
         
-        config.ecma = match ecma {
-            "es5" => EcmaScriptVersion::ES5,
-            "es2015" | "2015" => EcmaScriptVersion::ES2015,
-            "latest" | _ => EcmaScriptVersion::Latest,
-        };
+        config.target = Targets::parse(target).unwrap_or_default();
         
         config.format = match format {
             "compact" => OutputFormat::Compact,
@@ -445,23 +814,14 @@ impl GeneratorConfig {
             _ => OutputFormat::Compact,
         };
         
-        config.semicolon = match semicolon {
-            "auto" => SemicolonStrategy::Auto,
-            "always" => SemicolonStrategy::Always,
-            "remove" => SemicolonStrategy::Remove,
-            _ => SemicolonStrategy::Auto,
-        };
+        config.semicolon = semicolon.parse().unwrap_or(SemicolonStrategy::Auto);
         
-        config.quote = match quote {
-            "auto" => QuoteStrategy::Auto,
-            "single" => QuoteStrategy::Single,
-            "double" => QuoteStrategy::Double,
-            _ => QuoteStrategy::Auto,
-        };
+        config.quote = quote.parse().unwrap_or(QuoteStrategy::Auto);
         
         config.preserve_comments = match preserve_comments {
             "none" => CommentPreservation::None,
             "license" => CommentPreservation::License,
+            "jsdoc" => CommentPreservation::JsDoc,
             "all" => CommentPreservation::All,
             _ => CommentPreservation::None,
         };
@@ -0,0 +1,1075 @@
+//! # Module Format
+//!
+//! Converts the module syntax of the output for `--output-format`:
+//!
+//! - **esm**: `import` and `export` are printed as written
+//! - **cjs**: Imports become `require()` calls and exports become properties of `exports`
+//! - **iife**: The CommonJS form of a module without imports, run by a function
+//!   expression that receives a fresh `exports` object, for `<script>` tags
+//! - **umd**: The CommonJS form of a module without imports, run by a factory that
+//!   works with CommonJS, AMD, or as a browser script, where there is no `require`
+//!
+//! IIFE and UMD output can expose the exports on a global variable
+//! (`GeneratorConfig::global_name`).
+//!
+//! Exports are defined after the module body has run. `const` bindings and imports are
+//! assigned to `exports`, since their values can't change; other bindings are exported
+//! through getters, so importers see later assignments like live bindings. Default
+//! imports take `default` from modules marked with `__esModule` and the whole
+//! `module.exports` otherwise, and `export * from` defines a getter for every
+//! enumerable export of the source except `default` and the names the module exports
+//! itself. Scripts have no module syntax and are only wrapped.
+//!
+//! ## Interop
+//!
+//! With `GeneratorConfig::module_interop`, calls between the two module systems are
+//! converted as well:
+//!
+//! - **cjs**: `import(source)` becomes `Promise.resolve(require(source))`. The module
+//!   is then loaded right away rather than on a later tick, and a missing module throws
+//!   instead of rejecting. `require` takes no options, so an `import()` with import
+//!   attributes is kept, and so is one in a function that declares its own `require`
+//!   or `Promise`.
+//! - **esm**: A top-level `require('m');`, `const x = require('m')` or
+//!   `const { a, b: c } = require('m')` becomes `import 'm'`, `import * as x from 'm'`
+//!   or `import { a, b as c } from 'm'`. Imports run before the module body, so only
+//!   requires with nothing but imports, function declarations and declarations of
+//!   literals or functions before them are converted. Other `require()` calls, such as
+//!   those inside functions or of a computed name, are kept, and so are all of them in
+//!   a module that declares its own `require`.
+//!
+//! The calls left as written are reported as warnings.
+
+use std::collections::HashSet;
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::generator::{GeneratorConfig, GeneratorError, GeneratorResult, QuoteStrategy, SemicolonStrategy};
+use crate::parser::ast_types::{
+    AssignmentOperator, BinaryOperator, BlockStatement, BooleanLiteral, ExportSpecifier, Expression, ForInit, FunctionExpression,
+    Identifier, ImportSpecifier, Literal, ObjectPatternProperty, ObjectProperty, Pattern, Program, ProgramSourceType,
+    PropertyKey, PropertyKind, Statement, StringLiteral, UnaryOperator, VariableDeclarationKind, VariableDeclarator,
+};
+use crate::parser::visit::{self, VisitMut};
+use crate::transformer::identifier_renaming::{NameContext, NameGenerator};
+
+/// Module system of the generated code
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ModuleFormat {
+    /// ECMAScript modules, printed as written
+    #[default]
+    Esm,
+    /// CommonJS (`require` and `exports`)
+    Cjs,
+    /// Immediately invoked function expression for browser scripts
+    Iife,
+    /// Universal module definition: CommonJS, AMD or browser script
+    Umd,
+}
+
+impl ModuleFormat {
+    /// Human-readable name used in messages
+    pub fn display_name(self) -> &'static str {
+        match self {
+            Self::Esm => "ES module",
+            Self::Cjs => "CommonJS",
+            Self::Iife => "IIFE",
+            Self::Umd => "UMD",
+        }
+    }
+}
+
+impl FromStr for ModuleFormat {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "esm" => Ok(Self::Esm),
+            "cjs" => Ok(Self::Cjs),
+            "iife" => Ok(Self::Iife),
+            "umd" => Ok(Self::Umd),
+            _ => Err(format!("unknown output format '{}' (expected esm, cjs, iife or umd)", value)),
+        }
+    }
+}
+
+/// Code printed around the program for a module format
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Wrapper {
+    pub prefix: String,
+    pub suffix: String,
+}
+
+/// Rewrites the module syntax of `program` for `config.module_format`, or returns
+/// `None` when it can be printed as is
+///
+/// # Errors
+///
+/// Returns an error for imports in IIFE or UMD output, which have no module loader in
+/// browsers, and for a global name that isn't an identifier or is given for `esm` or `cjs` output.
+pub fn convert(program: &Program, config: &GeneratorConfig) -> GeneratorResult<Option<Program>> {
+    let format = config.module_format;
+    if let Some(name) = &config.global_name {
+        if matches!(format, ModuleFormat::Esm | ModuleFormat::Cjs) {
+            return Err(GeneratorError::InvalidConfiguration {
+                message: "a global name needs --output-format iife or umd".to_string(),
+            });
+        }
+        if !is_identifier(name) {
+            return Err(GeneratorError::InvalidConfiguration {
+                message: format!("global name '{}' is not a valid identifier", name),
+            });
+        }
+    }
+    let is_module = matches!(program.source_type, ProgramSourceType::Module);
+    let interop = config.module_interop;
+    if format == ModuleFormat::Esm {
+        return Ok(if interop && is_module { requires_to_imports(program) } else { None });
+    }
+    if !is_module {
+        return Ok(if interop && format == ModuleFormat::Cjs { dynamic_imports_to_requires(program)?.0 } else { None });
+    }
+    if matches!(format, ModuleFormat::Iife | ModuleFormat::Umd) && program.body.iter().any(imports_module) {
+        return Err(GeneratorError::InvalidConfiguration {
+            message: "imports need a module loader, which browser scripts lack; use --output-format cjs".to_string(),
+        });
+    }
+    let converted = to_commonjs(program, format != ModuleFormat::Iife);
+    if interop && format == ModuleFormat::Cjs {
+        return Ok(Some(dynamic_imports_to_requires(&converted)?.0.unwrap_or(converted)));
+    }
+    Ok(Some(converted))
+}
+
+/// Code around the converted form of `program`
+///
+/// Modules are strict, so their CommonJS form starts with `'use strict'`. With a global
+/// name, IIFE output is assigned to a `var` and returns its exports, and the browser
+/// branch of UMD output stores them on `root`.
+pub fn wrapper(program: &Program, config: &GeneratorConfig) -> Wrapper {
+    let q = if matches!(config.quote, QuoteStrategy::Double) { '"' } else { '\'' };
+    let strict = if matches!(program.source_type, ProgramSourceType::Module) {
+        format!("{q}use strict{q};")
+    } else {
+        String::new()
+    };
+    let (prefix, suffix) = match (config.module_format, config.global_name.as_deref()) {
+        (ModuleFormat::Esm, _) => (String::new(), String::new()),
+        (ModuleFormat::Cjs, _) => (strict, String::new()),
+        // A function expression in an initializer needs no parentheses
+        (ModuleFormat::Iife, Some(name)) => {
+            let separator = if matches!(config.semicolon, SemicolonStrategy::Remove) { ";" } else { "" };
+            (format!("var {name}=function(exports){{{strict}"), format!("{separator}return exports}}({{}});"))
+        }
+        (ModuleFormat::Iife, None) if has_exports(program) => {
+            (format!("(function(exports){{{strict}"), "})({});".to_string())
+        }
+        (ModuleFormat::Iife, None) => (format!("(function(){{{strict}"), "})();".to_string()),
+        (ModuleFormat::Umd, name) => {
+            let global = name.map_or_else(|| "{}".to_string(), |name| format!("root.{name}={{}}"));
+            (
+                format!(
+                    "(function(root,factory){{typeof exports=={q}object{q}&&typeof module!={q}undefined{q}?factory(require,exports):\
+                     typeof define=={q}function{q}&&define.amd?define([{q}require{q},{q}exports{q}],factory):factory(null,{global})}})\
+                     (this,function(require,exports){{{strict}"
+                ),
+                "});".to_string(),
+            )
+        }
+    };
+    Wrapper { prefix, suffix }
+}
+
+/// Warnings about the global name of `config`, and about the calls that module interop
+/// leaves as written
+///
+/// The global replaces any other global of that name, and a module that mentions the
+/// name may have meant that other global.
+///
+/// # Errors
+///
+/// Propagates the errors of the traversals that find the calls.
+pub fn warnings(program: &Program, config: &GeneratorConfig) -> GeneratorResult<Vec<String>> {
+    let mut warnings = if config.module_interop { interop_warnings(program, config.module_format)? } else { Vec::new() };
+    let Some(name) = config.global_name.as_deref() else {
+        return Ok(warnings);
+    };
+    if config.module_format == ModuleFormat::Esm || config.module_format == ModuleFormat::Cjs {
+        return Ok(warnings);
+    }
+
+    if BUILTIN_GLOBALS.contains(&name) {
+        warnings.push(format!("global name '{}' replaces the built-in global of the same name", name));
+    }
+    let mut used = HashSet::new();
+    collect_names(&serde_json::to_value(&program.body).unwrap_or(Value::Null), &mut used);
+    if used.contains(name) {
+        warnings.push(format!("global name '{}' is also used in the module, which may refer to the global it replaces", name));
+    }
+    if !has_exports(program) {
+        warnings.push(format!("the module has no exports, so global '{}' will be an empty object", name));
+    }
+    Ok(warnings)
+}
+
+/// The `require()` calls ESM output keeps and the `import()` calls CommonJS output keeps
+fn interop_warnings(program: &Program, format: ModuleFormat) -> GeneratorResult<Vec<String>> {
+    match format {
+        ModuleFormat::Esm if matches!(program.source_type, ProgramSourceType::Module) => {
+            if program.body.iter().any(binds_require) {
+                return Ok(vec!["the module declares its own `require`, so its require() calls are kept".to_string()]);
+            }
+            let converted = requires_to_imports(program);
+            let body = converted.as_ref().map_or(&program.body, |converted| &converted.body);
+            let mut warnings = Vec::new();
+            for statement in body {
+                if let Some(imports) = required_imports(statement) {
+                    warnings.extend(imports.iter().filter_map(|import| match import {
+                        Statement::ImportDeclaration { source, .. } => Some(format!(
+                            "require() of '{}' is kept, since an import would run before the statements \
+                             with side effects above it",
+                            source.value
+                        )),
+                        _ => None,
+                    }));
+                    continue;
+                }
+                let mut calls = RequireCalls::default();
+                visit::walk_statement(&mut calls, &mut statement.clone())?;
+                warnings.extend(calls.modules.into_iter().map(|module| {
+                    format!(
+                        "require() of {} is kept; only a top-level `require('m');` or `const` initialized \
+                         with one becomes an import",
+                        module
+                    )
+                }));
+            }
+            Ok(warnings)
+        }
+        ModuleFormat::Cjs => Ok(dynamic_imports_to_requires(program)?.1),
+        _ => Ok(Vec::new()),
+    }
+}
+
+/// Collects the module names of the `require()` calls it visits
+#[derive(Default)]
+struct RequireCalls {
+    modules: Vec<String>,
+}
+
+impl VisitMut for RequireCalls {
+    type Error = GeneratorError;
+
+    fn enter_expression(&mut self, expr: &mut Expression) -> GeneratorResult<()> {
+        if let Expression::CallExpression { callee, arguments, .. } = expr
+            && matches!(callee.as_ref(), Expression::Identifier(id) if id.name == "require")
+        {
+            self.modules.push(module_name(arguments.first()));
+        }
+        Ok(())
+    }
+}
+
+/// `'m'` for a string literal module name
+fn module_name(source: Option<&Expression>) -> String {
+    match source {
+        Some(Expression::Literal(Literal::String(source))) => format!("'{}'", source.value),
+        _ => "a computed module name".to_string(),
+    }
+}
+
+/// Globals of browsers and module loaders that a library global shouldn't replace
+const BUILTIN_GLOBALS: &[&str] = &[
+    "Array", "Boolean", "Date", "Error", "Function", "JSON", "Map", "Math", "Number", "Object", "Promise",
+    "Proxy", "Reflect", "RegExp", "Set", "String", "Symbol", "URL", "WeakMap", "WeakSet", "console", "define",
+    "document", "exports", "fetch", "globalThis", "history", "location", "module", "navigator", "require",
+    "self", "undefined", "window",
+];
+
+/// Whether `name` is a plain identifier
+fn is_identifier(name: &str) -> bool {
+    let mut chars = name.chars();
+    chars.next().is_some_and(|first| first.is_alphabetic() || first == '_' || first == '$')
+        && chars.all(|c| c.is_alphanumeric() || c == '_' || c == '$')
+}
+
+/// Whether `statement` loads another module
+fn imports_module(statement: &Statement) -> bool {
+    matches!(
+        statement,
+        Statement::ImportDeclaration { .. }
+            | Statement::ExportAllDeclaration { .. }
+            | Statement::ExportNamedDeclaration { source: Some(_), .. }
+    )
+}
+
+/// Whether `program` has export declarations
+fn has_exports(program: &Program) -> bool {
+    matches!(program.source_type, ProgramSourceType::Module)
+        && program.body.iter().any(|statement| {
+            matches!(
+                statement,
+                Statement::ExportNamedDeclaration { .. }
+                    | Statement::ExportDefaultDeclaration { .. }
+                    | Statement::ExportAllDeclaration { .. }
+            )
+        })
+}
+
+/// The CommonJS form of a module, marked with `__esModule` for importers if `mark` is set
+fn to_commonjs(program: &Program, mark: bool) -> Program {
+    let mut used = HashSet::new();
+    collect_names(&serde_json::to_value(&program.body).unwrap_or(Value::Null), &mut used);
+    let mut fresh_names = NameGenerator::new(NameContext::Module).filter(|name| !used.contains(name));
+
+    // Bindings whose value can't change after they are initialized
+    let mut constants: HashSet<String> = HashSet::new();
+    for statement in &program.body {
+        match statement {
+            Statement::VariableDeclaration { kind: VariableDeclarationKind::Const, .. } => {
+                constants.extend(declared_names(statement));
+            }
+            Statement::ExportNamedDeclaration { declaration: Some(declaration), .. }
+                if matches!(declaration.as_ref(), Statement::VariableDeclaration { kind: VariableDeclarationKind::Const, .. }) =>
+            {
+                constants.extend(declared_names(declaration));
+            }
+            Statement::ImportDeclaration { specifiers, .. } => {
+                constants.extend(specifiers.iter().map(|specifier| local_name(specifier).to_string()));
+            }
+            _ => {}
+        }
+    }
+    let explicit = explicit_exports(program);
+
+    let mut body = Vec::with_capacity(program.body.len() + 1);
+    // Exported names with the local binding they export
+    let mut exports: Vec<(String, String)> = Vec::new();
+    for statement in &program.body {
+        match statement {
+            Statement::ImportDeclaration { specifiers, source, .. } => {
+                body.push(import_statement(specifiers, source, &mut fresh_names));
+            }
+            Statement::ExportNamedDeclaration { declaration: Some(declaration), .. } => {
+                for name in declared_names(declaration) {
+                    exports.push((name.clone(), name));
+                }
+                body.push(declaration.as_ref().clone());
+            }
+            Statement::ExportNamedDeclaration { declaration: None, specifiers, source: None, .. } => {
+                for ExportSpecifier::ExportSpecifier { local, exported } in specifiers {
+                    exports.push((exported.name.clone(), local.name.clone()));
+                }
+            }
+            // The value of `export default expression` is taken where the statement runs
+            Statement::ExportDefaultDeclaration { declaration, .. } => match declaration.as_ref() {
+                Statement::ExpressionStatement { expression, .. } => {
+                    body.push(expression_statement(assign_export("default", expression.clone())));
+                }
+                declaration => {
+                    // An anonymous function or class gets a name to export it by
+                    let mut declaration = declaration.clone();
+                    if let Statement::FunctionDeclaration { id, .. } | Statement::ClassDeclaration { id, .. } = &mut declaration
+                        && id.is_none()
+                    {
+                        let name = fresh_names.next().expect("name generator is unbounded");
+                        *id = Some(Identifier { name, span: None });
+                    }
+                    for name in declared_names(&declaration) {
+                        exports.push(("default".to_string(), name));
+                    }
+                    body.push(declaration);
+                }
+            },
+            Statement::ExportNamedDeclaration { declaration: None, specifiers, source: Some(source), .. } => {
+                for ExportSpecifier::ExportSpecifier { local, exported } in specifiers {
+                    let value = member(require(source), &local.name);
+                    body.push(expression_statement(assign_export(&exported.name, value)));
+                }
+            }
+            Statement::ExportAllDeclaration { exported: Some(exported), source, .. } => {
+                body.push(expression_statement(assign_export(&exported.name, require(source))));
+            }
+            Statement::ExportAllDeclaration { exported: None, source, .. } => {
+                body.push(export_star(source, &explicit));
+            }
+            _ => body.push(statement.clone()),
+        }
+    }
+
+    if mark && has_exports(program) {
+        // `Object.defineProperty(exports,'__esModule',{value:true})` tells importers
+        // converted from modules to read `default`
+        let define = member(identifier("Object"), "defineProperty");
+        let descriptor = Expression::ObjectExpression {
+            properties: vec![ObjectProperty::Property {
+                key:       PropertyKey::Identifier(Identifier { name: "value".to_string(), span: None }),
+                value:     Expression::Literal(Literal::Boolean(BooleanLiteral { value: true, span: None })),
+                kind:      PropertyKind::Init,
+                method:    false,
+                shorthand: false,
+                computed:  false,
+            }],
+        };
+        body.insert(0, expression_statement(call(define, vec![identifier("exports"), string("__esModule"), descriptor])));
+    }
+    body.extend(exports.into_iter().map(|(name, local)| {
+        if constants.contains(&local) {
+            expression_statement(assign_export(&name, identifier(&local)))
+        } else {
+            expression_statement(define_export(string(&name), getter(identifier(&local))))
+        }
+    }));
+
+    Program { body, source_type: ProgramSourceType::Script }
+}
+
+/// Names the module exports by name, which `export * from` doesn't replace: `default`
+/// and those of its export declarations other than `export *`
+fn explicit_exports(program: &Program) -> Vec<String> {
+    let mut names = vec!["default".to_string()];
+    for statement in &program.body {
+        match statement {
+            Statement::ExportNamedDeclaration { declaration: Some(declaration), .. } => {
+                names.extend(declared_names(declaration));
+            }
+            Statement::ExportNamedDeclaration { declaration: None, specifiers, .. } => {
+                names.extend(specifiers.iter().map(|ExportSpecifier::ExportSpecifier { exported, .. }| exported.name.clone()));
+            }
+            Statement::ExportAllDeclaration { exported: Some(exported), .. } => names.push(exported.name.clone()),
+            _ => {}
+        }
+    }
+    names
+}
+
+/// `export * from source`:
+///
+/// ```js
+/// (function(m){Object.keys(m).forEach(function(k){k!=='default'&&…&&
+///   !Object.prototype.hasOwnProperty.call(exports,k)&&
+///   Object.defineProperty(exports,k,{enumerable:true,get:function(){return m[k]}})})})(require(source));
+/// ```
+///
+/// Names `explicit` lists are skipped, and so are those an earlier `export *` defined.
+fn export_star(source: &StringLiteral, explicit: &[String]) -> Statement {
+    let and = |left: Expression, right: Expression| Expression::BinaryExpression {
+        left:     Box::new(left),
+        operator: BinaryOperator::LogicalAnd,
+        right:    Box::new(right),
+    };
+    let has_own = call(
+        member(member(member(identifier("Object"), "prototype"), "hasOwnProperty"), "call"),
+        vec![identifier("exports"), identifier("k")],
+    );
+    let not_defined = Expression::UnaryExpression {
+        operator: UnaryOperator::LogicalNot,
+        argument: Box::new(has_own),
+        prefix:   true,
+    };
+    let value = Expression::MemberExpression {
+        object:   Box::new(identifier("m")),
+        property: Box::new(identifier("k")),
+        computed: true,
+        optional: false,
+    };
+    let differs = explicit.iter().map(|name| Expression::BinaryExpression {
+        left:     Box::new(identifier("k")),
+        operator: BinaryOperator::StrictNotEqual,
+        right:    Box::new(string(name)),
+    });
+    let define = differs
+        .chain([not_defined, define_export(identifier("k"), getter(value))])
+        .reduce(and)
+        .expect("the definition is always there");
+    let for_each = call(
+        member(call(member(identifier("Object"), "keys"), vec![identifier("m")]), "forEach"),
+        vec![function(&["k"], vec![expression_statement(define)])],
+    );
+    let copy = function(&["m"], vec![expression_statement(for_each)]);
+    expression_statement(call(copy, vec![require(source)]))
+}
+
+/// `const` declaration binding the specifiers of an import to `require(source)`
+fn import_statement(
+    specifiers: &[ImportSpecifier],
+    source: &StringLiteral,
+    fresh_names: &mut impl Iterator<Item = String>,
+) -> Statement {
+    if specifiers.is_empty() {
+        return expression_statement(require(source));
+    }
+
+    let namespace = specifiers.iter().find_map(|specifier| match specifier {
+        ImportSpecifier::ImportNamespaceSpecifier { local } => Some(local.name.clone()),
+        _ => None,
+    });
+    let has_default = specifiers.iter().any(|specifier| matches!(specifier, ImportSpecifier::ImportDefaultSpecifier { .. }));
+    let module = namespace.or_else(|| has_default.then(|| fresh_names.next().expect("name generator is unbounded")));
+
+    let mut declarations = Vec::new();
+    if let Some(module) = &module {
+        declarations.push(declarator(Pattern::Identifier(Identifier { name: module.clone(), span: None }), require(source)));
+    }
+    let module_value = || module.as_deref().map_or_else(|| require(source), identifier);
+
+    for specifier in specifiers {
+        if let ImportSpecifier::ImportDefaultSpecifier { local } = specifier {
+            // `m && m.__esModule ? m.default : m`
+            let module = module.as_deref().expect("default imports bind the module");
+            let test = Expression::BinaryExpression {
+                left:     Box::new(identifier(module)),
+                operator: BinaryOperator::LogicalAnd,
+                right:    Box::new(member(identifier(module), "__esModule")),
+            };
+            let value = Expression::ConditionalExpression {
+                test:       Box::new(test),
+                consequent: Box::new(member(identifier(module), "default")),
+                alternate:  Box::new(identifier(module)),
+            };
+            declarations.push(declarator(Pattern::Identifier(local.clone()), value));
+        }
+    }
+
+    let properties: Vec<ObjectPatternProperty> = specifiers
+        .iter()
+        .filter_map(|specifier| match specifier {
+            ImportSpecifier::ImportSpecifier { imported, local } => Some(ObjectPatternProperty::Property {
+                key:       PropertyKey::Identifier(imported.clone()),
+                value:     Pattern::Identifier(local.clone()),
+                computed:  false,
+                shorthand: imported.name == local.name,
+            }),
+            _ => None,
+        })
+        .collect();
+    if !properties.is_empty() {
+        declarations.push(declarator(Pattern::ObjectPattern { properties }, module_value()));
+    }
+
+    Statement::VariableDeclaration { declarations, kind: VariableDeclarationKind::Const, span: None }
+}
+
+/// `program` with each dynamic `import(source)` replaced by
+/// `Promise.resolve(require(source))`, or `None` if there is none to replace, and a
+/// warning for each `import()` that is kept
+///
+/// # Errors
+///
+/// Propagates the errors of the traversal.
+fn dynamic_imports_to_requires(program: &Program) -> GeneratorResult<(Option<Program>, Vec<String>)> {
+    let mut bound = HashSet::new();
+    collect_bindings(&program.body, &mut bound);
+    let mut rewriter = DynamicImportRewriter { scopes: vec![bound], replaced: 0, kept: Vec::new() };
+    let mut converted = program.clone();
+    visit::walk_program(&mut rewriter, &mut converted)?;
+    Ok(((rewriter.replaced > 0).then_some(converted), rewriter.kept))
+}
+
+/// Replaces the dynamic imports without options, innermost first
+///
+/// An import is kept where `require` or `Promise` is declared in one of the scopes
+/// around it.
+struct DynamicImportRewriter {
+    /// Names declared in each function scope around the node, the innermost last
+    scopes:   Vec<HashSet<String>>,
+    replaced: usize,
+    /// Warnings about the imports kept as written
+    kept:     Vec<String>,
+}
+
+impl VisitMut for DynamicImportRewriter {
+    type Error = GeneratorError;
+
+    fn enter_function(&mut self, id: Option<&Identifier>, params: &[Pattern], body: &[Statement]) -> GeneratorResult<()> {
+        let mut names = self.scopes.last().cloned().unwrap_or_default();
+        names.extend(id.map(|id| id.name.clone()));
+        names.extend(params.iter().flat_map(pattern_names));
+        collect_bindings(body, &mut names);
+        self.scopes.push(names);
+        Ok(())
+    }
+
+    fn exit_function(&mut self) -> GeneratorResult<()> {
+        self.scopes.pop();
+        Ok(())
+    }
+
+    fn exit_expression(&mut self, expr: &mut Expression) -> GeneratorResult<()> {
+        let Expression::ImportExpression { source, options } = expr else {
+            return Ok(());
+        };
+        let bound = self.scopes.last().is_some_and(|names| names.contains("require") || names.contains("Promise"));
+        if options.is_some() {
+            self.kept.push(format!(
+                "import() of {} is kept, since require() takes no import attributes",
+                module_name(Some(source))
+            ));
+        } else if bound {
+            self.kept.push(format!(
+                "import() of {} is kept, since `require` or `Promise` is declared in its scope",
+                module_name(Some(source))
+            ));
+        } else {
+            let source = std::mem::replace(source.as_mut(), Expression::ThisExpression);
+            let resolve = member(identifier("Promise"), "resolve");
+            *expr = call(resolve, vec![call(identifier("require"), vec![source])]);
+            self.replaced += 1;
+        }
+        Ok(())
+    }
+}
+
+/// Adds the names the declarations in `statements` bind to `names`, without entering
+/// nested functions. Block-scoped declarations count for the whole function, which
+/// only keeps more calls as written.
+fn collect_bindings(statements: &[Statement], names: &mut HashSet<String>) {
+    for statement in statements {
+        match statement {
+            Statement::ImportDeclaration { specifiers, .. } => {
+                names.extend(specifiers.iter().map(|specifier| local_name(specifier).to_string()));
+            }
+            Statement::VariableDeclaration { .. } | Statement::FunctionDeclaration { .. } | Statement::ClassDeclaration { .. } => {
+                names.extend(declared_names(statement));
+            }
+            Statement::ExportNamedDeclaration { declaration: Some(declaration), .. }
+            | Statement::ExportDefaultDeclaration { declaration, .. } => {
+                collect_bindings(std::slice::from_ref(declaration.as_ref()), names);
+            }
+            Statement::BlockStatement { body, .. } => collect_bindings(body, names),
+            Statement::IfStatement { consequent, alternate, .. } => {
+                collect_bindings(std::slice::from_ref(consequent.as_ref()), names);
+                if let Some(alternate) = alternate {
+                    collect_bindings(std::slice::from_ref(alternate.as_ref()), names);
+                }
+            }
+            Statement::WhileStatement { body, .. } => collect_bindings(std::slice::from_ref(body.as_ref()), names),
+            Statement::ForStatement { init, body, .. } => {
+                if let Some(ForInit::VariableDeclaration { declarations, .. }) = init {
+                    names.extend(declarations.iter().flat_map(|declarator| pattern_names(&declarator.id)));
+                }
+                collect_bindings(std::slice::from_ref(body.as_ref()), names);
+            }
+            Statement::ForOfStatement { left, body, .. } => {
+                if let ForInit::VariableDeclaration { declarations, .. } = left {
+                    names.extend(declarations.iter().flat_map(|declarator| pattern_names(&declarator.id)));
+                }
+                collect_bindings(std::slice::from_ref(body.as_ref()), names);
+            }
+            _ => {}
+        }
+    }
+}
+
+/// `program` with its top-level requires of string literals turned into imports, or
+/// `None` if it has none or declares its own `require`
+///
+/// Imports run before the module body, so conversion stops at the first statement that
+/// could have side effects.
+fn requires_to_imports(program: &Program) -> Option<Program> {
+    if program.body.iter().any(binds_require) {
+        return None;
+    }
+    let mut converted = false;
+    let mut after_effects = false;
+    let body = program
+        .body
+        .iter()
+        .flat_map(|statement| {
+            if !after_effects && let Some(imports) = required_imports(statement) {
+                converted = true;
+                return imports;
+            }
+            after_effects |= !is_inert(statement);
+            vec![statement.clone()]
+        })
+        .collect();
+    converted.then(|| Program { body, source_type: program.source_type.clone() })
+}
+
+/// Whether running a top-level statement can't have side effects an import hoisted
+/// above it would observe
+fn is_inert(statement: &Statement) -> bool {
+    match statement {
+        Statement::ImportDeclaration { .. }
+        | Statement::ExportAllDeclaration { .. }
+        | Statement::ExportNamedDeclaration { declaration: None, .. }
+        | Statement::FunctionDeclaration { .. }
+        | Statement::EmptyStatement => true,
+        Statement::ExportNamedDeclaration { declaration: Some(declaration), .. }
+        | Statement::ExportDefaultDeclaration { declaration, .. } => is_inert(declaration),
+        Statement::VariableDeclaration { declarations, .. } => declarations.iter().all(|declarator| {
+            matches!(declarator.id, Pattern::Identifier(_))
+                && declarator.init.as_ref().is_none_or(|init| {
+                    matches!(
+                        init,
+                        Expression::Literal(_)
+                            | Expression::FunctionExpression(_)
+                            | Expression::ArrowFunctionExpression { .. }
+                    )
+                })
+        }),
+        _ => statement.directive().is_some(),
+    }
+}
+
+/// The imports replacing `require('m');` or a `const` declaration whose declarators
+/// all bind `require('m')` to a name or to names of its properties
+fn required_imports(statement: &Statement) -> Option<Vec<Statement>> {
+    match statement {
+        Statement::ExpressionStatement { expression, .. } => {
+            Some(vec![import_declaration(Vec::new(), required_module(expression)?)])
+        }
+        Statement::VariableDeclaration { declarations, kind: VariableDeclarationKind::Const, .. } => declarations
+            .iter()
+            .map(|declarator| {
+                let source = required_module(declarator.init.as_ref()?)?;
+                Some(import_declaration(import_specifiers(&declarator.id)?, source))
+            })
+            .collect(),
+        _ => None,
+    }
+}
+
+/// The module of a `require('m')` call
+fn required_module(expression: &Expression) -> Option<&StringLiteral> {
+    let Expression::CallExpression { callee, arguments, optional: false } = expression else {
+        return None;
+    };
+    match (callee.as_ref(), arguments.as_slice()) {
+        (Expression::Identifier(id), [Expression::Literal(Literal::String(source))]) if id.name == "require" => {
+            Some(source)
+        }
+        _ => None,
+    }
+}
+
+/// Import specifiers binding the names `pattern` binds: the module namespace for a
+/// name, named exports for `{ a, b: c }`
+fn import_specifiers(pattern: &Pattern) -> Option<Vec<ImportSpecifier>> {
+    match pattern {
+        Pattern::Identifier(local) => Some(vec![ImportSpecifier::ImportNamespaceSpecifier { local: local.clone() }]),
+        Pattern::ObjectPattern { properties } => properties
+            .iter()
+            .map(|property| match property {
+                ObjectPatternProperty::Property {
+                    key: PropertyKey::Identifier(imported),
+                    value: Pattern::Identifier(local),
+                    computed: false,
+                    ..
+                } => Some(ImportSpecifier::ImportSpecifier { imported: imported.clone(), local: local.clone() }),
+                _ => None,
+            })
+            .collect(),
+        _ => None,
+    }
+}
+
+/// Whether a top-level statement binds the name `require`
+fn binds_require(statement: &Statement) -> bool {
+    match statement {
+        Statement::ImportDeclaration { specifiers, .. } => {
+            specifiers.iter().any(|specifier| local_name(specifier) == "require")
+        }
+        Statement::ExportNamedDeclaration { declaration: Some(declaration), .. }
+        | Statement::ExportDefaultDeclaration { declaration, .. } => binds_require(declaration),
+        _ => declared_names(statement).iter().any(|name| name == "require"),
+    }
+}
+
+/// Names bound by an exported declaration
+fn declared_names(declaration: &Statement) -> Vec<String> {
+    match declaration {
+        Statement::FunctionDeclaration { id: Some(id), .. } | Statement::ClassDeclaration { id: Some(id), .. } => {
+            vec![id.name.clone()]
+        }
+        Statement::VariableDeclaration { declarations, .. } => {
+            declarations.iter().flat_map(|declarator| pattern_names(&declarator.id)).collect()
+        }
+        _ => Vec::new(),
+    }
+}
+
+/// Name an import specifier binds
+fn local_name(specifier: &ImportSpecifier) -> &str {
+    let (ImportSpecifier::ImportSpecifier { local, .. }
+    | ImportSpecifier::ImportDefaultSpecifier { local }
+    | ImportSpecifier::ImportNamespaceSpecifier { local }) = specifier;
+    &local.name
+}
+
+/// Names bound by a pattern
+fn pattern_names(pattern: &Pattern) -> Vec<String> {
+    let mut names = Vec::new();
+    crate::analyzer::scope_builder::pattern_names(pattern, &mut names);
+    names
+}
+
+/// Every name in the JSON form of a node, so generated names never shadow one
+fn collect_names(node: &Value, names: &mut HashSet<String>) {
+    match node {
+        Value::Object(fields) => {
+            if let Some(Value::String(name)) = fields.get("name") {
+                names.insert(name.clone());
+            }
+            fields.values().for_each(|child| collect_names(child, names));
+        }
+        Value::Array(items) => items.iter().for_each(|item| collect_names(item, names)),
+        _ => {}
+    }
+}
+
+fn identifier(name: &str) -> Expression {
+    Expression::Identifier(Identifier { name: name.to_string(), span: None })
+}
+
+fn string(value: &str) -> Expression {
+    Expression::Literal(Literal::String(StringLiteral::new(value.to_string(), None)))
+}
+
+fn member(object: Expression, property: &str) -> Expression {
+    Expression::MemberExpression {
+        object:   Box::new(object),
+        property: Box::new(identifier(property)),
+        computed: false,
+        optional: false,
+    }
+}
+
+fn call(callee: Expression, arguments: Vec<Expression>) -> Expression {
+    Expression::CallExpression { callee: Box::new(callee), arguments, optional: false }
+}
+
+/// `require(source)`
+fn require(source: &StringLiteral) -> Expression {
+    call(identifier("require"), vec![string(&source.value)])
+}
+
+/// `exports.name = value`
+fn assign_export(name: &str, value: Expression) -> Expression {
+    Expression::AssignmentExpression {
+        left:     Box::new(member(identifier("exports"), name)),
+        operator: AssignmentOperator::Assign,
+        right:    Box::new(value),
+    }
+}
+
+/// `Object.defineProperty(exports,name,{enumerable:true,get:getter})`
+fn define_export(name: Expression, getter: Expression) -> Expression {
+    let property = |key: &str, value| ObjectProperty::Property {
+        key: PropertyKey::Identifier(Identifier { name: key.to_string(), span: None }),
+        value,
+        kind: PropertyKind::Init,
+        method: false,
+        shorthand: false,
+        computed: false,
+    };
+    let enumerable = Expression::Literal(Literal::Boolean(BooleanLiteral { value: true, span: None }));
+    let descriptor = Expression::ObjectExpression {
+        properties: vec![property("enumerable", enumerable), property("get", getter)],
+    };
+    call(member(identifier("Object"), "defineProperty"), vec![identifier("exports"), name, descriptor])
+}
+
+/// `function(){return value}`
+fn getter(value: Expression) -> Expression {
+    function(&[], vec![Statement::ReturnStatement { argument: Some(value), span: None }])
+}
+
+/// An anonymous function expression
+fn function(params: &[&str], body: Vec<Statement>) -> Expression {
+    Expression::FunctionExpression(FunctionExpression {
+        id:           None,
+        params:       params.iter().map(|name| Pattern::Identifier(Identifier { name: name.to_string(), span: None })).collect(),
+        body:         BlockStatement { body, span: None },
+        is_async:     false,
+        is_generator: false,
+        span:         None,
+    })
+}
+
+fn import_declaration(specifiers: Vec<ImportSpecifier>, source: &StringLiteral) -> Statement {
+    Statement::ImportDeclaration { specifiers, source: source.clone(), attributes: Vec::new(), span: None }
+}
+
+fn declarator(id: Pattern, init: Expression) -> VariableDeclarator {
+    VariableDeclarator { id, init: Some(init), span: None }
+}
+
+fn expression_statement(expression: Expression) -> Statement {
+    Statement::ExpressionStatement { expression, span: None }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::generator::{Generator, GeneratorConfig};
+    use crate::parser::{parse_js, ParserConfig};
+
+    fn generate(source: &str, module_format: ModuleFormat) -> GeneratorResult<String> {
+        generate_global(source, module_format, None).map(|(code, _)| code)
+    }
+
+    fn generate_global(
+        source: &str,
+        module_format: ModuleFormat,
+        global_name: Option<&str>,
+    ) -> GeneratorResult<(String, Vec<String>)> {
+        let ast = parse_js(source, "test.js", &ParserConfig::default()).ast.unwrap();
+        let config = GeneratorConfig {
+            module_format,
+            global_name: global_name.map(str::to_string),
+            ..GeneratorConfig::default()
+        };
+        Generator::new(config).generate(&ast, None).map(|result| (result.code, result.diagnostics.warnings))
+    }
+
+    fn generate_interop(source: &str, module_format: ModuleFormat) -> (String, Vec<String>) {
+        let ast = parse_js(source, "test.js", &ParserConfig::default()).ast.unwrap();
+        let config = GeneratorConfig { module_format, module_interop: true, ..GeneratorConfig::default() };
+        let result = Generator::new(config).generate(&ast, None).unwrap();
+        (result.code, result.diagnostics.warnings)
+    }
+
+    #[test]
+    fn test_converts_modules_to_commonjs() {
+        let source = "import d, { a as b } from './dep'; import * as ns from 'ns'; import 'side';\n\
+                      export const x = d + b + ns.c; export { x as y }; export * from 'all'; export { z } from 'zz';";
+        let code = generate(source, ModuleFormat::Cjs).unwrap();
+
+        assert!(code.starts_with("'use strict';Object.defineProperty(exports,'__esModule',{value:true});"), "{}", code);
+        // `a`, `b`, `c` and `d` are taken, so the default import reads the module through `e`
+        assert!(code.contains("const e=require('./dep'), d=e&&e.__esModule?e.default:e, {a:b}=e;"), "{}", code);
+        assert!(code.contains("const ns=require('ns');require('side');const x=d+b+ns.c;"), "{}", code);
+        assert!(code.contains("k!=='default'&&k!=='x'&&k!=='y'&&k!=='z'&&!Object.prototype.hasOwnProperty.call(exports,k)&&"), "{}", code);
+        assert!(code.contains("get:function(){return m[k];}});});}(require('all')));exports.z=require('zz').z;"), "{}", code);
+        assert!(code.ends_with("exports.x=x;exports.y=x;"), "{}", code);
+    }
+
+    #[test]
+    fn test_converts_default_exports() {
+        let code = generate("export default function main() { return 1; }", ModuleFormat::Cjs).unwrap();
+        assert!(code.ends_with("function main(){return 1;}Object.defineProperty(exports,'default',{enumerable:true,get:function(){return main;}});"), "{}", code);
+
+        // Anonymous declarations get a name that isn't used elsewhere
+        let code = generate("const a = 1; export default class extends Base { x = a; }", ModuleFormat::Iife).unwrap();
+        assert_eq!(code, "(function(exports){'use strict';const a=1;class b extends Base{x=a;}Object.defineProperty(exports,'default',{enumerable:true,get:function(){return b;}});})({});");
+
+        let code = generate("const helper = () => 2; export default helper; export const x = 3;", ModuleFormat::Umd).unwrap();
+        assert!(code.contains("const helper=()=>2;exports.default=helper;const x=3;exports.x=x;"), "{}", code);
+        assert!(code.contains("Object.defineProperty(exports,'__esModule',{value:true})"), "{}", code);
+    }
+
+    #[test]
+    fn test_exports_mutable_bindings_through_getters() {
+        let source = "export let counter = 0; export function inc() { counter++; } const limit = 3; var v; export { limit, v as w };";
+        let code = generate(source, ModuleFormat::Cjs).unwrap();
+        assert!(code.ends_with(
+            "Object.defineProperty(exports,'counter',{enumerable:true,get:function(){return counter;}});\
+             Object.defineProperty(exports,'inc',{enumerable:true,get:function(){return inc;}});\
+             exports.limit=limit;Object.defineProperty(exports,'w',{enumerable:true,get:function(){return v;}});"
+        ), "{}", code);
+
+        // Star exports skip `default`, and names exported explicitly or by an earlier `export *`
+        let code = generate("export * from 'a'; export * from 'b'; export default 1;", ModuleFormat::Cjs).unwrap();
+        let star = |source: &str| {
+            format!(
+                "(function(m){{Object.keys(m).forEach(function(k){{k!=='default'&&!Object.prototype.hasOwnProperty.call(exports,k)&&\
+                 Object.defineProperty(exports,k,{{enumerable:true,get:function(){{return m[k];}}}});}});}}(require('{}')));",
+                source
+            )
+        };
+        assert!(code.ends_with(&format!("{}{}exports.default=1;", star("a"), star("b"))), "{}", code);
+    }
+
+    #[test]
+    fn test_wraps_modules_in_functions() {
+        let code = generate("const a = 1; export const g = () => a;", ModuleFormat::Iife).unwrap();
+        assert_eq!(code, "(function(exports){'use strict';const a=1;const g=()=>a;exports.g=g;})({});");
+
+        let code = generate("const a = 1; f(a);", ModuleFormat::Iife).unwrap();
+        assert_eq!(code, "(function(){'use strict';const a=1;f(a);})();");
+
+        let code = generate("const a = 1; export const b = a;", ModuleFormat::Umd).unwrap();
+        assert!(code.starts_with("(function(root,factory){typeof exports=='object'"), "{}", code);
+        assert!(code.ends_with("(this,function(require,exports){'use strict';Object.defineProperty(exports,'__esModule',{value:true});const a=1;const b=a;exports.b=b;});"), "{}", code);
+
+        // The browser branch of UMD output has no `require` to load imports with
+        for format in [ModuleFormat::Iife, ModuleFormat::Umd] {
+            for source in ["import { a } from 'a'; f(a);", "export * from 'a';", "export { a } from 'a';"] {
+                assert!(matches!(generate(source, format), Err(GeneratorError::InvalidConfiguration { .. })), "{}", source);
+            }
+        }
+        assert_eq!(generate("import { a } from 'a'; f(a);", ModuleFormat::Esm).unwrap(), "import{a}from'a';f(a);");
+    }
+
+    #[test]
+    fn test_exposes_exports_on_a_global() {
+        let source = "const a = 1; export const g = () => a;";
+        let (code, warnings) = generate_global(source, ModuleFormat::Iife, Some("MyLib")).unwrap();
+        assert_eq!(code, "var MyLib=function(exports){'use strict';const a=1;const g=()=>a;exports.g=g;return exports}({});");
+        assert!(warnings.is_empty(), "{:?}", warnings);
+
+        let (code, _) = generate_global(source, ModuleFormat::Umd, Some("MyLib")).unwrap();
+        assert!(code.contains("factory(null,root.MyLib={})"), "{}", code);
+
+        let (_, warnings) = generate_global("export const a = window.a;", ModuleFormat::Iife, Some("window")).unwrap();
+        assert_eq!(warnings.len(), 2, "{:?}", warnings);
+        assert!(warnings[0].contains("built-in global"), "{:?}", warnings);
+
+        for (format, name) in [(ModuleFormat::Cjs, "MyLib"), (ModuleFormat::Iife, "my-lib")] {
+            assert!(matches!(
+                generate_global(source, format, Some(name)),
+                Err(GeneratorError::InvalidConfiguration { .. })
+            ));
+        }
+    }
+
+    #[test]
+    fn test_converts_between_import_and_require() {
+        let source = "export async function load(name) { const a = await import('./a.js'); \
+                      const b = await import('./b.json', { with: { type: 'json' } }); return [a, b, import(name)]; }";
+        let (code, warnings) = generate_interop(source, ModuleFormat::Cjs);
+        assert!(code.contains("const a=await Promise.resolve(require('./a.js'));"), "{}", code);
+        assert!(code.contains("import('./b.json',{with:{type:'json'}})"), "{}", code);
+        assert!(code.contains("Promise.resolve(require(name))"), "{}", code);
+        assert_eq!(warnings.len(), 1, "{:?}", warnings);
+        assert!(warnings[0].contains("'./b.json'"), "{:?}", warnings);
+
+        let source = "require('side'); const fs = require('fs'), { join, resolve: r } = require('path');\n\
+                      export function f() { return require('late'); } export const g = require(fs.name);";
+        let (code, warnings) = generate_interop(source, ModuleFormat::Esm);
+        assert!(code.starts_with("import'side';import*as fs from'fs';import{join,resolve as r}from'path';"), "{}", code);
+        assert!(code.contains("require('late')") && code.contains("require(fs.name)"), "{}", code);
+        assert_eq!(warnings.len(), 2, "{:?}", warnings);
+        assert!(warnings[0].contains("'late'") && warnings[1].contains("computed"), "{:?}", warnings);
+
+        let (code, warnings) = generate_interop("const require = f(); const a = require('a');", ModuleFormat::Esm);
+        assert_eq!(code, "const require=f();const a=require('a');");
+        assert_eq!(warnings.len(), 1, "{:?}", warnings);
+    }
+
+    #[test]
+    fn test_keeps_conversions_that_would_change_behavior() {
+        let source = "export function load(require) { return import('./m.js'); }\n\
+                      export const later = () => { const Promise = f(); return import('./p.js'); };\n\
+                      export const other = () => import('./o.js');\n\
+                      export const bound = require => import('./q.js');";
+        let (code, warnings) = generate_interop(source, ModuleFormat::Cjs);
+        assert!(code.contains("return import('./m.js')") && code.contains("return import('./p.js')"), "{}", code);
+        assert!(code.contains("Promise.resolve(require('./o.js'))"), "{}", code);
+        assert!(code.contains("import('./q.js')") && !code.contains("require('./q.js')"), "{}", code);
+        assert_eq!(warnings.len(), 3, "{:?}", warnings);
+        assert!(warnings.iter().all(|warning| warning.contains("declared in its scope")), "{:?}", warnings);
+
+        let source = "const version = 1; const fs = require('fs');\n\
+                      globalThis.flag = 1; const x = require('./d'); export { fs, x, version };";
+        let (code, warnings) = generate_interop(source, ModuleFormat::Esm);
+        assert!(code.starts_with("const version=1;import*as fs from'fs';globalThis.flag=1;"), "{}", code);
+        assert!(code.contains("const x=require('./d');"), "{}", code);
+        assert_eq!(warnings.len(), 1, "{:?}", warnings);
+        assert!(warnings[0].contains("'./d'") && warnings[0].contains("side effects"), "{:?}", warnings);
+    }
+}
@@ -4,8 +4,17 @@
 //! It handles operator precedence, ASI hazards, string/number canonicalization, and tracks positions
 //! for source map generation.
 
-use crate::generator::{GeneratorConfig, GeneratorResult};
+use std::collections::HashMap;
+
+use oxc_syntax::identifier::{is_identifier_part, is_identifier_start};
+
+use crate::generator::{
+    CommentPreservation, GeneratorConfig, GeneratorError, GeneratorResult, MappingGranularity, SourceMapMode,
+};
 use crate::parser::ast_types::*;
+use crate::parser::comments::AttachedComments;
+use crate::parser::{CommentKind, SourceSpan};
+use crate::target::Feature;
 
 /// Operator precedence levels (higher number = higher precedence)
 #[derive(Debug, Clone, Copy, PartialEq, PartialOrd)]
@@ -29,6 +38,30 @@ pub enum Precedence {
     Member = 19,         // ., [], ?., ?.[], ?.(), ()
 }
 
+impl Precedence {
+    /// Returns the next tighter-binding precedence level
+    pub fn tighter(self) -> Precedence {
+        match self {
+            Precedence::Sequence => Precedence::Assignment,
+            Precedence::Assignment => Precedence::Conditional,
+            Precedence::Conditional => Precedence::LogicalOr,
+            Precedence::LogicalOr => Precedence::LogicalAnd,
+            Precedence::LogicalAnd => Precedence::BitwiseOr,
+            Precedence::BitwiseOr => Precedence::BitwiseXor,
+            Precedence::BitwiseXor => Precedence::BitwiseAnd,
+            Precedence::BitwiseAnd => Precedence::Equality,
+            Precedence::Equality => Precedence::Relational,
+            Precedence::Relational => Precedence::Shift,
+            Precedence::Shift => Precedence::Additive,
+            Precedence::Additive => Precedence::Multiplicative,
+            Precedence::Multiplicative => Precedence::Exponentiation,
+            Precedence::Exponentiation => Precedence::Unary,
+            Precedence::Unary => Precedence::Postfix,
+            Precedence::Postfix | Precedence::Member => Precedence::Member,
+        }
+    }
+}
+
 /// Associativity for operators
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub enum Associativity {
@@ -50,6 +83,19 @@ enum TokenType {
     Regex,
 }
 
+/// Start of a printed node in the output and in the original source, for source maps
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SourcePosition {
+    /// Byte offset in the generated code
+    pub generated:    usize,
+    /// Byte offset in the original source
+    pub original:     u32,
+    /// End of the original name in the source, for identifiers
+    pub original_end: Option<u32>,
+    /// Byte length of the printed name, for identifiers
+    pub printed_len:  usize,
+}
+
 /// Printer implementation for Component 12
 pub struct Printer {
     config: GeneratorConfig,
@@ -63,6 +109,20 @@ pub struct Printer {
     indent_cache: Vec<String>,
     /// Performance metrics
     chars_written: usize,
+    /// Bytes already flushed to a streaming writer
+    bytes_flushed: usize,
+    /// JSDoc blocks of exported declarations, by declared name
+    doc_comments: HashMap<String, String>,
+    /// Whether the program being printed is a module
+    is_module: bool,
+    /// Number of function bodies enclosing the current position
+    function_depth: usize,
+    /// Positions of the printed nodes that have a source span, when source maps are on
+    source_positions: Vec<SourcePosition>,
+    /// Source comments attached to statements
+    comments: AttachedComments,
+    /// Whether the output ends with the line break after a trailing line comment
+    after_line_comment: bool,
 }
 
 impl Printer {
@@ -77,6 +137,13 @@ impl Printer {
             string_buffer: String::with_capacity(1024), // Pre-allocate buffer
             indent_cache: Vec::new(),
             chars_written: 0,
+            bytes_flushed: 0,
+            doc_comments: HashMap::new(),
+            is_module: false,
+            function_depth: 0,
+            source_positions: Vec::new(),
+            comments: AttachedComments::default(),
+            after_line_comment: false,
         };
         
         // Pre-populate indent cache for performance
@@ -88,19 +155,66 @@ impl Printer {
         printer
     }
 
+    /// Sets the JSDoc blocks printed before exported functions and classes
+    pub fn with_doc_comments(mut self, doc_comments: &HashMap<String, String>) -> Self {
+        self.doc_comments = doc_comments.clone();
+        self
+    }
+
+    /// Sets the source comments printed around the statements they are attached to
+    pub fn with_comments(mut self, comments: &AttachedComments) -> Self {
+        self.comments = comments.clone();
+        self
+    }
+
     /// Print a complete program
     pub fn print_program(&mut self, program: &Program) -> GeneratorResult<String> {
+        self.print_program_with_sink(program, None)?;
+        Ok(self.output.clone())
+    }
+
+    /// Print a complete program, flushing output to `writer` as the buffer fills
+    ///
+    /// Returns the total number of bytes written.
+    pub fn print_program_to_writer(
+        &mut self,
+        program: &Program,
+        writer: &mut dyn std::io::Write,
+    ) -> GeneratorResult<usize> {
+        self.print_program_with_sink(program, Some(writer))?;
+        Ok(self.bytes_flushed)
+    }
+
+    /// Shared program printing loop; flushes between statements when a sink is given
+    fn print_program_with_sink(
+        &mut self,
+        program: &Program,
+        mut sink: Option<&mut dyn std::io::Write>,
+    ) -> GeneratorResult<()> {
         self.output.clear();
         self.warnings.clear();
+        self.bytes_flushed = 0;
+        self.source_positions.clear();
+        self.is_module = matches!(program.source_type, ProgramSourceType::Module);
+        self.function_depth = 0;
         
         // Validate the program structure first
         self.validate_program(program)?;
+
+        if matches!(self.config.preserve_comments, CommentPreservation::All) {
+            for comment in self.comments.dangling().to_vec() {
+                self.write(&comment.text)?;
+                self.write_newline()?;
+            }
+        }
         
         for (i, stmt) in program.body.iter().enumerate() {
             if i > 0 {
                 self.print_statement_separator()?;
             }
             
+            self.config.cancellation.check()?;
+
             // Check for ASI hazards
             self.check_asi_hazard(stmt)?;
             
@@ -110,6 +224,14 @@ impl Printer {
             }
             
             self.print_statement(stmt)?;
+
+            // Statement boundaries are safe flush points: no later token looks back past them
+            if let Some(writer) = sink.as_deref_mut()
+                && self.output.len() >= self.config.stream_buffer_size
+            {
+                self.check_memory_limits()?;
+                self.flush_output(writer)?;
+            }
         }
         
         // Final memory check
@@ -124,8 +246,20 @@ impl Printer {
             }
             _ => {}
         }
+
+        if let Some(writer) = sink {
+            self.flush_output(writer)?;
+        }
         
-        Ok(self.output.clone())
+        Ok(())
+    }
+
+    /// Write the buffered output to `writer` and clear the buffer
+    fn flush_output(&mut self, writer: &mut dyn std::io::Write) -> GeneratorResult<()> {
+        writer.write_all(self.output.as_bytes())?;
+        self.bytes_flushed += self.output.len();
+        self.output.clear();
+        Ok(())
     }
 
     /// Get warnings generated during printing
@@ -133,30 +267,138 @@ impl Printer {
         self.warnings.clone()
     }
 
+    /// Where the printed nodes with a source span start, in output order
+    ///
+    /// Empty unless the configuration asks for a source map. Statements are always
+    /// recorded; identifiers and literals only with token granularity.
+    pub fn source_positions(&self) -> &[SourcePosition] {
+        &self.source_positions
+    }
+
+    /// Records that the node spanning `span` in the source starts at the current output position
+    fn mark_source(&mut self, span: Option<&SourceSpan>, granularity: MappingGranularity) {
+        if matches!(self.config.source_map, SourceMapMode::None)
+            || (matches!(granularity, MappingGranularity::Token)
+                && matches!(self.config.mapping_granularity, MappingGranularity::Statement))
+        {
+            return;
+        }
+        if let Some(span) = span {
+            self.source_positions.push(SourcePosition {
+                generated:    self.bytes_flushed + self.output.len(),
+                original:     span.start,
+                original_end: None,
+                printed_len:  0,
+            });
+        }
+    }
+
     /// Print a statement
     fn print_statement(&mut self, stmt: &Statement) -> GeneratorResult<()> {
-        match stmt {
-            Statement::VariableDeclaration { declarations, kind } => {
+        self.print_leading_comments(stmt.span())?;
+        self.mark_source(stmt.span(), MappingGranularity::Statement);
+        let printed = match stmt {
+            Statement::VariableDeclaration { declarations, kind, .. } => {
                 self.print_variable_declaration(declarations, kind)
             }
-            Statement::FunctionDeclaration { id, params, body, is_async, is_generator } => {
+            Statement::FunctionDeclaration { id, params, body, is_async, is_generator, .. } => {
                 self.print_function_declaration(id, params, body, *is_async, *is_generator)
             }
-            Statement::ExpressionStatement { expression } => {
+            Statement::ExpressionStatement { expression, .. } => {
                 self.print_expression_statement(expression)
             }
-            Statement::BlockStatement { body } => {
+            Statement::BlockStatement { body, .. } => {
                 self.print_block_statement(body)
             }
-            Statement::ReturnStatement { argument } => {
+            Statement::ReturnStatement { argument, .. } => {
                 self.print_return_statement(argument)
             }
-            _ => {
-                // TODO: Implement remaining statement types
-                self.write("/* STMT */")?;
-                Ok(())
+            Statement::ClassDeclaration { id, super_class, body, decorators, .. } => {
+                self.print_decorators(decorators)?;
+                self.print_class(id, super_class, body)
+            }
+            Statement::ExportNamedDeclaration { declaration, specifiers, source, attributes, .. } => {
+                self.print_export_named_declaration(declaration, specifiers, source, attributes)
+            }
+            Statement::ExportDefaultDeclaration { declaration, .. } => self.print_export_default_declaration(declaration),
+            Statement::ExportAllDeclaration { exported, source, attributes, .. } => {
+                self.write("export*")?;
+                if let Some(exported) = exported {
+                    self.write("as ")?;
+                    self.print_identifier(exported)?;
+                    self.write(" ")?;
+                }
+                self.write("from")?;
+                self.print_string_literal(source)?;
+                self.print_import_attributes(attributes)?;
+                self.print_semicolon_if_needed()
+            }
+            Statement::ImportDeclaration { specifiers, source, attributes, .. } => {
+                self.print_import_declaration(specifiers, source, attributes)
+            }
+            Statement::WhileStatement { test, body, .. } => {
+                self.write("while(")?;
+                self.print_expression(test, Precedence::Sequence)?;
+                self.write(")")?;
+                self.print_statement(body)
+            }
+            Statement::ForStatement { init, test, update, body, .. } => {
+                self.print_for_statement(init.as_ref(), test.as_ref(), update.as_ref(), body)
+            }
+            Statement::ForOfStatement { left, right, body, is_await, .. } => {
+                self.print_for_of_statement(left, right, body, *is_await)
+            }
+            Statement::IfStatement { test, consequent, alternate, .. } => {
+                self.print_if_statement(test, consequent, alternate.as_deref())
+            }
+            Statement::EmptyStatement => self.write(";"),
+            Statement::DebuggerStatement => {
+                self.write("debugger")?;
+                self.prev_token = Some(TokenType::Identifier);
+                self.print_semicolon_if_needed()
+            }
+        };
+        printed?;
+        self.print_trailing_comments(stmt.span())
+    }
+
+    /// Print the comments attached before a statement, each ending its own line outside
+    /// compact output
+    fn print_leading_comments(&mut self, span: Option<&SourceSpan>) -> GeneratorResult<()> {
+        let Some(span) = span.filter(|_| matches!(self.config.preserve_comments, CommentPreservation::All)) else {
+            return Ok(());
+        };
+        for comment in self.comments.leading(span).to_vec() {
+            self.write(&comment.text)?;
+            if matches!(comment.kind, CommentKind::Line)
+                || !matches!(self.config.format, crate::generator::OutputFormat::Compact)
+            {
+                self.write_newline()?;
+                self.print_indent_if_needed()?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Print the comments attached after a statement on the same line
+    ///
+    /// A line comment ends the line, and the line break that would follow the statement
+    /// is not repeated.
+    fn print_trailing_comments(&mut self, span: Option<&SourceSpan>) -> GeneratorResult<()> {
+        let Some(span) = span.filter(|_| matches!(self.config.preserve_comments, CommentPreservation::All)) else {
+            return Ok(());
+        };
+        for comment in self.comments.trailing(span).to_vec() {
+            if !matches!(self.config.format, crate::generator::OutputFormat::Compact) {
+                self.write(" ")?;
+            }
+            self.write(&comment.text)?;
+            if matches!(comment.kind, CommentKind::Line) {
+                self.write_newline()?;
+                self.after_line_comment = true;
             }
         }
+        Ok(())
     }
 
     /// Print a variable declaration
@@ -165,12 +407,11 @@ impl Printer {
         declarations: &[VariableDeclarator],
         kind: &VariableDeclarationKind,
     ) -> GeneratorResult<()> {
-        match kind {
-            VariableDeclarationKind::Var => self.write("var")?,
-            VariableDeclarationKind::Let => self.write("let")?,
-            VariableDeclarationKind::Const => self.write("const")?,
+        if !matches!(kind, VariableDeclarationKind::Var) {
+            self.require_feature(Feature::BlockScoping)?;
         }
 
+        self.print_declaration_kind(kind)?;
         self.print_space_if_needed()?;
 
         for (i, declarator) in declarations.iter().enumerate() {
@@ -185,6 +426,17 @@ impl Printer {
         Ok(())
     }
 
+    /// Print the keyword of a variable declaration
+    fn print_declaration_kind(&mut self, kind: &VariableDeclarationKind) -> GeneratorResult<()> {
+        self.write(match kind {
+            VariableDeclarationKind::Var => "var",
+            VariableDeclarationKind::Let => "let",
+            VariableDeclarationKind::Const => "const",
+            VariableDeclarationKind::Using => "using",
+            VariableDeclarationKind::AwaitUsing => "await using",
+        })
+    }
+
     /// Print a variable declarator
     fn print_variable_declarator(&mut self, declarator: &VariableDeclarator) -> GeneratorResult<()> {
         self.print_pattern(&declarator.id)?;
@@ -206,6 +458,13 @@ impl Printer {
         is_async: bool,
         is_generator: bool,
     ) -> GeneratorResult<()> {
+        match (is_async, is_generator) {
+            (true, true) => self.require_feature(Feature::AsyncGenerators)?,
+            (true, false) => self.require_feature(Feature::AsyncFunctions)?,
+            (false, true) => self.require_feature(Feature::Generators)?,
+            (false, false) => {}
+        }
+
         if is_async {
             self.write("async")?;
             self.print_space_if_needed()?;
@@ -226,14 +485,452 @@ impl Printer {
         self.print_parameter_list(params)?;
         self.write(")")?;
 
-        self.print_block_statement_body(&body.body)?;
+        self.print_space_before_body()?;
+        self.print_function_body(&body.body)?;
+        Ok(())
+    }
+
+    /// Print a `for (left of right)` or `for await (left of right)` loop
+    fn print_for_of_statement(
+        &mut self,
+        left: &ForInit,
+        right: &Expression,
+        body: &Statement,
+        is_await: bool,
+    ) -> GeneratorResult<()> {
+        self.write("for")?;
+        if is_await {
+            self.require_feature(Feature::AsyncGenerators)?;
+            if self.function_depth == 0 {
+                if !self.is_module {
+                    return Err(GeneratorError::TopLevelAwaitInScript);
+                }
+                self.require_feature(Feature::TopLevelAwait)?;
+            }
+            self.write(" await")?;
+        }
+        self.write("(")?;
+        self.print_for_init(left, Precedence::Member)?;
+        self.write(" of ")?;
+        self.print_expression(right, Precedence::Assignment)?;
+        self.write(")")?;
+        self.print_statement(body)
+    }
+
+    /// Print a `for(init;test;update)` loop
+    fn print_for_statement(
+        &mut self,
+        init: Option<&ForInit>,
+        test: Option<&Expression>,
+        update: Option<&Expression>,
+        body: &Statement,
+    ) -> GeneratorResult<()> {
+        self.write("for(")?;
+        if let Some(init) = init {
+            self.print_for_init(init, Precedence::Sequence)?;
+        }
+        self.write(";")?;
+        if let Some(test) = test {
+            self.print_expression(test, Precedence::Sequence)?;
+        }
+        self.write(";")?;
+        if let Some(update) = update {
+            self.print_expression(update, Precedence::Sequence)?;
+        }
+        self.write(")")?;
+        self.print_statement(body)
+    }
+
+    /// Print the declaration or expression at the head of a loop
+    fn print_for_init(&mut self, init: &ForInit, precedence: Precedence) -> GeneratorResult<()> {
+        match init {
+            ForInit::VariableDeclaration { declarations, kind } => {
+                if !matches!(kind, VariableDeclarationKind::Var) {
+                    self.require_feature(Feature::BlockScoping)?;
+                }
+                self.print_declaration_kind(kind)?;
+                self.write(" ")?;
+                for (i, declarator) in declarations.iter().enumerate() {
+                    if i > 0 {
+                        self.write(",")?;
+                    }
+                    self.print_variable_declarator(declarator)?;
+                }
+                Ok(())
+            }
+            ForInit::Expression(expression) => self.print_expression(expression, precedence),
+        }
+    }
+
+    /// Print an `if` statement, bracing the consequent when an `else` would bind to a
+    /// nested `if` instead
+    fn print_if_statement(
+        &mut self,
+        test: &Expression,
+        consequent: &Statement,
+        alternate: Option<&Statement>,
+    ) -> GeneratorResult<()> {
+        match self.config.format {
+            crate::generator::OutputFormat::Compact => self.write("if(")?,
+            _ => self.write("if (")?,
+        }
+        self.print_expression(test, Precedence::Sequence)?;
+        self.write(")")?;
+
+        if alternate.is_some() && ends_with_open_if(consequent) {
+            self.print_space_before_body()?;
+            self.print_block_statement_body(std::slice::from_ref(consequent))?;
+        } else {
+            self.print_nested_statement(consequent)?;
+        }
+
+        if let Some(alternate) = alternate {
+            if !matches!(self.config.format, crate::generator::OutputFormat::Compact) {
+                self.write(" ")?;
+            }
+            self.write("else")?;
+            match alternate {
+                Statement::BlockStatement { .. } => self.print_nested_statement(alternate)?,
+                _ => {
+                    // `else` must not run into an identifier or keyword
+                    self.write(" ")?;
+                    self.print_statement(alternate)?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Print the body of an `if` or loop, separated from its head outside compact output
+    fn print_nested_statement(&mut self, stmt: &Statement) -> GeneratorResult<()> {
+        match stmt {
+            Statement::BlockStatement { .. } => self.print_space_before_body()?,
+            _ if !matches!(self.config.format, crate::generator::OutputFormat::Compact) => self.write(" ")?,
+            _ => {}
+        }
+        self.print_statement(stmt)
+    }
+
+    /// Print the body of a function or method, where `await` is not top-level
+    fn print_function_body(&mut self, body: &[Statement]) -> GeneratorResult<()> {
+        self.function_depth += 1;
+        let result = self.print_block_statement_body(body);
+        self.function_depth -= 1;
+        result
+    }
+
+    /// Print a class declaration
+    fn print_class(
+        &mut self,
+        id: &Option<Identifier>,
+        super_class: &Option<Box<Expression>>,
+        body: &ClassBody,
+    ) -> GeneratorResult<()> {
+        self.require_feature(Feature::Classes)?;
+        self.write("class")?;
+
+        if let Some(id) = id {
+            self.print_space_if_needed()?;
+            self.print_identifier(id)?;
+        }
+
+        if let Some(super_class) = super_class {
+            self.print_space_if_needed()?;
+            self.write("extends")?;
+            self.print_space_if_needed()?;
+            self.print_expression(super_class, Precedence::Member)?;
+        }
+
+        self.print_space_before_body()?;
+        self.write("{")?;
+        if !body.body.is_empty() {
+            self.print_newline_if_needed()?;
+            self.indent_level += 1;
+
+            for element in &body.body {
+                self.print_indent_if_needed()?;
+                self.print_class_element(element)?;
+                self.print_newline_if_needed()?;
+            }
+
+            self.indent_level -= 1;
+            self.print_indent_if_needed()?;
+        }
+        self.write("}")?;
+        Ok(())
+    }
+
+    /// Print the decorators of a class or class member, each followed by a space
+    ///
+    /// Anything but a dotted name, optionally called once, has to be parenthesized.
+    fn print_decorators(&mut self, decorators: &[Expression]) -> GeneratorResult<()> {
+        if !decorators.is_empty() {
+            self.require_feature(Feature::Decorators)?;
+        }
+        for decorator in decorators {
+            self.write("@")?;
+            if is_plain_decorator(decorator) {
+                self.print_expression(decorator, Precedence::Member)?;
+            } else {
+                self.write("(")?;
+                self.print_expression(decorator, Precedence::Sequence)?;
+                self.write(")")?;
+            }
+            self.print_space_if_needed()?;
+        }
         Ok(())
     }
 
+    /// Print a class method or field
+    fn print_class_element(&mut self, element: &ClassElement) -> GeneratorResult<()> {
+        self.print_decorators(element.decorators())?;
+        match element {
+            ClassElement::MethodDefinition { key, value, kind, is_static, .. } => {
+                if *is_static {
+                    self.write("static ")?;
+                }
+                match kind {
+                    MethodKind::Get => self.write("get ")?,
+                    MethodKind::Set => self.write("set ")?,
+                    MethodKind::Constructor | MethodKind::Method => {}
+                }
+                self.print_method(key, value)
+            }
+            ClassElement::PropertyDefinition { key, value, is_static, .. } => {
+                self.require_feature(Feature::ClassFields)?;
+                if *is_static {
+                    self.write("static ")?;
+                }
+                self.print_property_key(key)?;
+                if let Some(value) = value {
+                    self.print_assignment_operator()?;
+                    self.print_expression(value, Precedence::Assignment)?;
+                }
+                // Fields always need a terminator before the next member
+                self.write(";")
+            }
+        }
+    }
+
+    /// Print a method's modifiers, key, parameters and body (after `static`, `get` or `set`)
+    fn print_method(&mut self, key: &PropertyKey, value: &FunctionExpression) -> GeneratorResult<()> {
+        if value.is_async {
+            self.write("async ")?;
+        }
+        if value.is_generator {
+            self.write("*")?;
+        }
+        self.print_property_key(key)?;
+        self.write("(")?;
+        self.print_parameter_list(&value.params)?;
+        self.write(")")?;
+        self.print_space_before_body()?;
+        self.print_function_body(&value.body.body)
+    }
+
+    /// Print a property key
+    fn print_property_key(&mut self, key: &PropertyKey) -> GeneratorResult<()> {
+        match key {
+            PropertyKey::Identifier(id) => self.print_identifier(id),
+            PropertyKey::Literal(literal) => self.print_literal(literal),
+            PropertyKey::PrivateName(name) => {
+                self.write("#")?;
+                self.write(&name.name)
+            }
+        }
+    }
+
+    /// Print an `export` declaration or export list
+    fn print_export_default_declaration(&mut self, declaration: &Statement) -> GeneratorResult<()> {
+        if let Statement::ClassDeclaration { decorators, .. } = declaration {
+            self.print_decorators(decorators)?;
+        }
+        self.write("export default")?;
+        match declaration {
+            Statement::ClassDeclaration { id, super_class, body, .. } => {
+                self.print_space_if_needed()?;
+                self.mark_source(declaration.span(), MappingGranularity::Statement);
+                self.print_class(id, super_class, body)
+            }
+            Statement::ExpressionStatement { expression, .. } => {
+                // `function` here would begin a declaration, which isn't callable in place
+                let needs_wrapping = matches!(leftmost_expression(expression), Expression::FunctionExpression(_));
+                if needs_wrapping {
+                    self.write("(")?;
+                } else if !matches!(expression, Expression::ObjectExpression { .. } | Expression::ArrayExpression { .. }) {
+                    self.print_space_if_needed()?;
+                }
+                self.print_expression(expression, Precedence::Assignment)?;
+                if needs_wrapping {
+                    self.write(")")?;
+                }
+                self.print_semicolon_if_needed()
+            }
+            declaration => {
+                self.print_space_if_needed()?;
+                self.print_statement(declaration)
+            }
+        }
+    }
+
+    fn print_export_named_declaration(
+        &mut self,
+        declaration: &Option<Box<Statement>>,
+        specifiers: &[ExportSpecifier],
+        source: &Option<StringLiteral>,
+        attributes: &[ImportAttribute],
+    ) -> GeneratorResult<()> {
+        if let Some(declaration) = declaration {
+            self.print_doc_comment(declaration)?;
+            // Decorators go before `export`, the one placement that TypeScript and the
+            // legacy Babel transform accept as well
+            if let Statement::ClassDeclaration { id, super_class, body, decorators, .. } = declaration.as_ref()
+                && !decorators.is_empty()
+            {
+                self.print_decorators(decorators)?;
+                self.write("export")?;
+                self.print_space_if_needed()?;
+                self.mark_source(declaration.span(), MappingGranularity::Statement);
+                return self.print_class(id, super_class, body);
+            }
+            self.write("export")?;
+            self.print_space_if_needed()?;
+            return self.print_statement(declaration);
+        }
+
+        self.write("export{")?;
+        for (i, specifier) in specifiers.iter().enumerate() {
+            if i > 0 {
+                self.write(",")?;
+            }
+            let ExportSpecifier::ExportSpecifier { local, exported } = specifier;
+            self.print_identifier(local)?;
+            if exported.name != local.name {
+                self.write(" as ")?;
+                self.print_identifier(exported)?;
+            }
+        }
+        self.write("}")?;
+
+        if let Some(source) = source {
+            self.write("from")?;
+            self.print_string_literal(source)?;
+            self.print_import_attributes(attributes)?;
+        }
+        self.print_semicolon_if_needed()
+    }
+
+    /// Print an import declaration; without specifiers it only loads the module
+    fn print_import_declaration(
+        &mut self,
+        specifiers: &[ImportSpecifier],
+        source: &StringLiteral,
+        attributes: &[ImportAttribute],
+    ) -> GeneratorResult<()> {
+        self.write("import")?;
+        let named: Vec<_> = specifiers
+            .iter()
+            .filter_map(|specifier| match specifier {
+                ImportSpecifier::ImportSpecifier { imported, local } => Some((imported, local)),
+                _ => None,
+            })
+            .collect();
+
+        let mut needs_comma = false;
+        for specifier in specifiers {
+            match specifier {
+                ImportSpecifier::ImportDefaultSpecifier { local } => {
+                    self.write(if needs_comma { "," } else { " " })?;
+                    self.print_identifier(local)?;
+                }
+                ImportSpecifier::ImportNamespaceSpecifier { local } => {
+                    self.write(if needs_comma { ",*as " } else { "*as " })?;
+                    self.print_identifier(local)?;
+                }
+                ImportSpecifier::ImportSpecifier { .. } => continue,
+            }
+            needs_comma = true;
+        }
+        if !named.is_empty() {
+            self.write(if needs_comma { ",{" } else { "{" })?;
+            for (i, (imported, local)) in named.into_iter().enumerate() {
+                if i > 0 {
+                    self.write(",")?;
+                }
+                self.print_identifier(imported)?;
+                if imported.name != local.name {
+                    self.write(" as ")?;
+                    self.print_identifier(local)?;
+                }
+            }
+            self.write("}")?;
+        } else if needs_comma {
+            self.write(" ")?;
+        }
+
+        if !specifiers.is_empty() {
+            self.write("from")?;
+        }
+        self.print_string_literal(source)?;
+        self.print_import_attributes(attributes)?;
+        self.print_semicolon_if_needed()
+    }
+
+    /// Print the `with { … }` clause after a module specifier, if there are attributes
+    fn print_import_attributes(&mut self, attributes: &[ImportAttribute]) -> GeneratorResult<()> {
+        if attributes.is_empty() {
+            return Ok(());
+        }
+        let compact = matches!(self.config.format, crate::generator::OutputFormat::Compact);
+        self.write(if compact { "with{" } else { " with { " })?;
+        for (i, attribute) in attributes.iter().enumerate() {
+            if i > 0 {
+                self.write(if compact { "," } else { ", " })?;
+            }
+            if is_identifier_name(&attribute.key.value) {
+                self.write(&attribute.key.value)?;
+            } else {
+                self.print_string_literal(&attribute.key)?;
+            }
+            self.write(if compact { ":" } else { ": " })?;
+            self.print_string_literal(&attribute.value)?;
+        }
+        self.write(if compact { "}" } else { " }" })
+    }
+
+    /// Print the JSDoc block of an exported function or class on its own lines
+    ///
+    /// Continuation lines are re-indented to the current level so the block lines up
+    /// with the declaration it documents.
+    fn print_doc_comment(&mut self, declaration: &Statement) -> GeneratorResult<()> {
+        if !matches!(self.config.preserve_comments, CommentPreservation::JsDoc) {
+            return Ok(());
+        }
+        let (Statement::FunctionDeclaration { id: Some(id), .. } | Statement::ClassDeclaration { id: Some(id), .. }) =
+            declaration
+        else {
+            return Ok(());
+        };
+        let Some(doc) = self.doc_comments.get(&id.name).cloned() else {
+            return Ok(());
+        };
+
+        for (i, line) in doc.lines().enumerate() {
+            if i > 0 {
+                self.print_indent_if_needed()?;
+                self.write(" ")?;
+            }
+            self.write(line.trim())?;
+            self.write_newline()?;
+        }
+        self.print_indent_if_needed()
+    }
+
     /// Print an expression statement
     fn print_expression_statement(&mut self, expression: &Expression) -> GeneratorResult<()> {
+        // `{` and `function` at the start of a statement would begin a block or declaration
         let needs_wrapping = matches!(
-            expression,
+            leftmost_expression(expression),
             Expression::ObjectExpression { .. } | Expression::FunctionExpression(_)
         );
 
@@ -273,68 +970,458 @@ impl Printer {
             self.indent_level -= 1;
             self.print_indent_if_needed()?;
         }
-        
-        self.write("}")?;
+        
+        self.write("}")?;
+        Ok(())
+    }
+
+    /// Print a return statement
+    fn print_return_statement(&mut self, argument: &Option<Expression>) -> GeneratorResult<()> {
+        self.write("return")?;
+        self.prev_token = Some(TokenType::Identifier);
+
+        if let Some(arg) = argument {
+            self.print_space_if_needed()?;
+            self.print_expression(arg, Precedence::Sequence)?;
+        }
+
+        self.print_semicolon_if_needed()?;
+        Ok(())
+    }
+
+    /// Print an expression with precedence context
+    fn print_expression(&mut self, expr: &Expression, parent_precedence: Precedence) -> GeneratorResult<()> {
+        match expr {
+            Expression::Identifier(id) => self.print_identifier(id),
+            Expression::Literal(lit) => self.print_literal(lit),
+            Expression::BinaryExpression { left, operator, right } => {
+                self.print_binary_expression(left, operator, right, parent_precedence)
+            }
+            Expression::TemplateLiteral { quasis, expressions } => {
+                self.print_template_literal(quasis, expressions)
+            }
+            Expression::TaggedTemplateExpression { tag, quasis, expressions } => {
+                self.print_tagged_template(tag, quasis, expressions)
+            }
+            Expression::ThisExpression => self.print_this_expression(),
+            Expression::UnaryExpression { operator, argument, .. } => {
+                self.print_unary_expression(operator, argument, parent_precedence)
+            }
+            Expression::MemberExpression { object, property, computed, optional } => {
+                self.print_member_expression(object, property, *computed, *optional)
+            }
+            Expression::CallExpression { callee, arguments, optional } => {
+                self.print_call_expression(callee, arguments, *optional)
+            }
+            Expression::ImportExpression { source, options } => {
+                self.print_import_expression(source, options.as_deref())
+            }
+            Expression::NewExpression { callee, arguments } => self.print_new_expression(callee, arguments),
+            Expression::MetaProperty { meta, property } => {
+                self.write(&format!("{}.{}", meta, property))?;
+                self.prev_token = Some(TokenType::Identifier);
+                Ok(())
+            }
+            Expression::ChainExpression { expression } => {
+                self.print_chain_expression(expression, parent_precedence)
+            }
+            Expression::AssignmentExpression { left, operator, right } => {
+                self.print_assignment_expression(left, operator, right, parent_precedence)
+            }
+            Expression::UpdateExpression { operator, argument, prefix } => {
+                self.print_update_expression(operator, argument, *prefix, parent_precedence)
+            }
+            Expression::ConditionalExpression { test, consequent, alternate } => {
+                self.print_conditional_expression(test, consequent, alternate, parent_precedence)
+            }
+            Expression::AwaitExpression { argument } => {
+                self.print_await_expression(argument, parent_precedence)
+            }
+            Expression::YieldExpression { argument, delegate } => {
+                self.print_yield_expression(argument.as_deref(), *delegate, parent_precedence)
+            }
+            Expression::SpreadElement { argument } => self.print_spread_element(argument),
+            Expression::ArrayExpression { elements } => self.print_array_expression(elements),
+            Expression::ObjectExpression { properties } => self.print_object_expression(properties),
+            Expression::FunctionExpression(function) => self.print_function_declaration(
+                &function.id,
+                &function.params,
+                &function.body,
+                function.is_async,
+                function.is_generator,
+            ),
+            Expression::ArrowFunctionExpression { params, body, is_async } => {
+                self.print_arrow_function(params, body, *is_async, parent_precedence)
+            }
+        }
+    }
+
+    /// Print a binary expression with precedence handling
+    fn print_binary_expression(
+        &mut self,
+        left: &Expression,
+        operator: &BinaryOperator,
+        right: &Expression,
+        parent_precedence: Precedence,
+    ) -> GeneratorResult<()> {
+        match operator {
+            BinaryOperator::Exponentiation => self.require_feature(Feature::Exponentiation)?,
+            BinaryOperator::NullishCoalescing => self.require_feature(Feature::NullishCoalescing)?,
+            _ => {}
+        }
+
+        let precedence = self.get_binary_operator_precedence(operator);
+        let is_nullish = matches!(operator, BinaryOperator::NullishCoalescing);
+        // `??` can't be mixed with `&&` or `||` without parentheses, so it is wrapped
+        // everywhere a logical operand is expected
+        let needs_parens = if is_nullish {
+            parent_precedence > Precedence::Conditional
+        } else {
+            precedence < parent_precedence
+        };
+
+        if needs_parens {
+            self.write("(")?;
+        }
+
+        // The operand on the non-associating side must bind tighter than the operator,
+        // so `a - (b - c)` and `(a ** b) ** c` keep their parentheses
+        let (left_precedence, right_precedence) = match self.get_binary_operator_associativity(operator) {
+            // `a ?? b ?? c` chains to the left; other operands must not be logical
+            _ if is_nullish => match left {
+                Expression::BinaryExpression { operator: BinaryOperator::NullishCoalescing, .. } => {
+                    (Precedence::Conditional, Precedence::BitwiseOr)
+                }
+                _ => (Precedence::BitwiseOr, Precedence::BitwiseOr),
+            },
+            // A unary operand of `**` must be parenthesized as well: `(-a) ** b`
+            Associativity::Right => (Precedence::Postfix, precedence),
+            Associativity::Left | Associativity::None => (precedence, precedence.tighter()),
+        };
+
+        self.print_expression(left, left_precedence)?;
+        self.print_binary_operator(operator)?;
+        self.print_wrapped_item(|printer| printer.print_expression(right, right_precedence))?;
+
+        if needs_parens {
+            self.write(")")?;
+        }
+
+        Ok(())
+    }
+
+    /// Print a unary expression, separating repeated signs (`- -x`) with a space
+    fn print_unary_expression(
+        &mut self,
+        operator: &UnaryOperator,
+        argument: &Expression,
+        parent_precedence: Precedence,
+    ) -> GeneratorResult<()> {
+        let needs_parens = Precedence::Unary < parent_precedence;
+
+        if needs_parens {
+            self.write("(")?;
+        }
+
+        let op_str = match operator {
+            UnaryOperator::LogicalNot => "!",
+            UnaryOperator::BitwiseNot => "~",
+            UnaryOperator::Plus => "+",
+            UnaryOperator::Minus => "-",
+            UnaryOperator::Typeof => "typeof",
+            UnaryOperator::Void => "void",
+            UnaryOperator::Delete => "delete",
+        };
+
+        // Avoid fusing `a - -b` into `a--b` or `+ +x` into `++x`
+        if matches!(operator, UnaryOperator::Plus | UnaryOperator::Minus)
+            && self.output.ends_with(op_str)
+        {
+            self.write(" ")?;
+        }
+
+        self.write(op_str)?;
+
+        if matches!(operator, UnaryOperator::Typeof | UnaryOperator::Void | UnaryOperator::Delete) {
+            self.write(" ")?;
+        }
+
+        self.print_expression(argument, Precedence::Unary)?;
+
+        if needs_parens {
+            self.write(")")?;
+        }
+
+        Ok(())
+    }
+
+    /// Print a `++` or `--` expression
+    fn print_update_expression(
+        &mut self,
+        operator: &UpdateOperator,
+        argument: &Expression,
+        prefix: bool,
+        parent_precedence: Precedence,
+    ) -> GeneratorResult<()> {
+        let precedence = if prefix { Precedence::Unary } else { Precedence::Postfix };
+        let needs_parens = precedence < parent_precedence;
+
+        if needs_parens {
+            self.write("(")?;
+        }
+
+        let op_str = match operator {
+            UpdateOperator::Increment => "++",
+            UpdateOperator::Decrement => "--",
+        };
+
+        if prefix {
+            // Avoid fusing `a + ++b` into `a+++b`
+            if self.output.ends_with(&op_str[..1]) {
+                self.write(" ")?;
+            }
+            self.write(op_str)?;
+            self.print_expression(argument, Precedence::Postfix)?;
+        } else {
+            self.print_expression(argument, Precedence::Postfix)?;
+            self.write(op_str)?;
+        }
+
+        if needs_parens {
+            self.write(")")?;
+        }
+
+        Ok(())
+    }
+
+    /// Print an await expression
+    ///
+    /// Outside of any function this is top-level await, which only modules allow.
+    fn print_await_expression(&mut self, argument: &Expression, parent_precedence: Precedence) -> GeneratorResult<()> {
+        if self.function_depth == 0 {
+            if !self.is_module {
+                return Err(GeneratorError::TopLevelAwaitInScript);
+            }
+            self.require_feature(Feature::TopLevelAwait)?;
+        }
+
+        let needs_parens = Precedence::Unary < parent_precedence;
+        if needs_parens {
+            self.write("(")?;
+        }
+        self.write("await")?;
+        self.write(" ")?;
+        self.print_expression(argument, Precedence::Unary)?;
+        if needs_parens {
+            self.write(")")?;
+        }
+        Ok(())
+    }
+
+    /// Print a yield expression, which binds as loosely as an assignment
+    fn print_yield_expression(
+        &mut self,
+        argument: Option<&Expression>,
+        delegate: bool,
+        parent_precedence: Precedence,
+    ) -> GeneratorResult<()> {
+        let needs_parens = Precedence::Assignment < parent_precedence;
+        if needs_parens {
+            self.write("(")?;
+        }
+        self.write("yield")?;
+        if delegate {
+            self.write("*")?;
+        }
+        if let Some(argument) = argument {
+            if !delegate {
+                self.write(" ")?;
+            }
+            self.print_expression(argument, Precedence::Assignment)?;
+        }
+        if needs_parens {
+            self.write(")")?;
+        }
+        Ok(())
+    }
+
+    /// Print an optional chain, parenthesized when it is itself the object of a member
+    /// access or call (`(a?.b).c`), which would otherwise join the chain
+    fn print_chain_expression(&mut self, expression: &Expression, parent_precedence: Precedence) -> GeneratorResult<()> {
+        self.require_feature(Feature::OptionalChaining)?;
+        let needs_parens = parent_precedence >= Precedence::Member;
+        if needs_parens {
+            self.write("(")?;
+        }
+        self.print_expression(expression, Precedence::Member)?;
+        if needs_parens {
+            self.write(")")?;
+        }
+        Ok(())
+    }
+
+    /// Print an assignment, including compound and logical assignment operators
+    fn print_assignment_expression(
+        &mut self,
+        left: &Expression,
+        operator: &AssignmentOperator,
+        right: &Expression,
+        parent_precedence: Precedence,
+    ) -> GeneratorResult<()> {
+        let op_str = match operator {
+            AssignmentOperator::Assign => "=",
+            AssignmentOperator::AddAssign => "+=",
+            AssignmentOperator::SubtractAssign => "-=",
+            AssignmentOperator::MultiplyAssign => "*=",
+            AssignmentOperator::DivideAssign => "/=",
+            AssignmentOperator::RemainderAssign => "%=",
+            AssignmentOperator::ExponentiationAssign => "**=",
+            AssignmentOperator::LeftShiftAssign => "<<=",
+            AssignmentOperator::RightShiftAssign => ">>=",
+            AssignmentOperator::UnsignedRightShiftAssign => ">>>=",
+            AssignmentOperator::BitwiseAndAssign => "&=",
+            AssignmentOperator::BitwiseOrAssign => "|=",
+            AssignmentOperator::BitwiseXorAssign => "^=",
+            AssignmentOperator::LogicalAndAssign => "&&=",
+            AssignmentOperator::LogicalOrAssign => "||=",
+            AssignmentOperator::NullishCoalescingAssign => "??=",
+        };
+        match operator {
+            AssignmentOperator::ExponentiationAssign => self.require_feature(Feature::Exponentiation)?,
+            AssignmentOperator::LogicalAndAssign | AssignmentOperator::LogicalOrAssign => {
+                self.require_feature(Feature::LogicalAssignment)?
+            }
+            AssignmentOperator::NullishCoalescingAssign => {
+                self.require_feature(Feature::LogicalAssignment)?;
+                self.require_feature(Feature::NullishCoalescing)?;
+            }
+            _ => {}
+        }
+
+        let needs_parens = Precedence::Assignment < parent_precedence;
+        if needs_parens {
+            self.write("(")?;
+        }
+        self.print_expression(left, Precedence::Member)?;
+        self.print_spaced_operator(op_str)?;
+        self.print_expression(right, Precedence::Assignment)?;
+        if needs_parens {
+            self.write(")")?;
+        }
+        Ok(())
+    }
+
+    /// Print a conditional expression (`test ? consequent : alternate`)
+    fn print_conditional_expression(
+        &mut self,
+        test: &Expression,
+        consequent: &Expression,
+        alternate: &Expression,
+        parent_precedence: Precedence,
+    ) -> GeneratorResult<()> {
+        let needs_parens = Precedence::Conditional < parent_precedence;
+        if needs_parens {
+            self.write("(")?;
+        }
+        self.print_expression(test, Precedence::LogicalOr)?;
+        self.print_spaced_operator("?")?;
+        self.print_expression(consequent, Precedence::Assignment)?;
+        self.print_spaced_operator(":")?;
+        self.print_expression(alternate, Precedence::Assignment)?;
+        if needs_parens {
+            self.write(")")?;
+        }
+        Ok(())
+    }
+
+    /// Print a member access expression (`a.b`, `a[b]`, `a?.b` or `a?.[b]`)
+    fn print_member_expression(
+        &mut self,
+        object: &Expression,
+        property: &Expression,
+        computed: bool,
+        optional: bool,
+    ) -> GeneratorResult<()> {
+        // `1.toString` would lex as a malformed number, so wrap numeric objects
+        let wrap_object = matches!(object, Expression::Literal(Literal::Number(_)));
+
+        if wrap_object {
+            self.write("(")?;
+        }
+        self.print_expression(object, Precedence::Member)?;
+        if wrap_object {
+            self.write(")")?;
+        }
+
+        if optional {
+            self.write("?.")?;
+        }
+        if computed {
+            self.write("[")?;
+            self.print_expression(property, Precedence::Sequence)?;
+            self.write("]")?;
+            self.prev_token = Some(TokenType::CloseBracket);
+        } else {
+            if !optional {
+                self.write(".")?;
+            }
+            self.print_expression(property, Precedence::Member)?;
+        }
+
         Ok(())
     }
 
-    /// Print a return statement
-    fn print_return_statement(&mut self, argument: &Option<Expression>) -> GeneratorResult<()> {
-        self.write("return")?;
-        self.prev_token = Some(TokenType::Identifier);
+    /// Print a call expression with its argument list
+    fn print_call_expression(&mut self, callee: &Expression, arguments: &[Expression], optional: bool) -> GeneratorResult<()> {
+        self.print_expression(callee, Precedence::Member)?;
+        if optional {
+            self.write("?.")?;
+        }
+        self.write("(")?;
 
-        if let Some(arg) = argument {
-            self.print_space_if_needed()?;
-            self.print_expression(arg, Precedence::Sequence)?;
+        for (i, argument) in arguments.iter().enumerate() {
+            if i > 0 {
+                self.print_list_separator()?;
+            }
+            self.print_wrapped_item(|printer| printer.print_expression(argument, Precedence::Assignment))?;
         }
 
-        self.print_semicolon_if_needed()?;
+        self.write(")")?;
+        self.prev_token = Some(TokenType::CloseParen);
         Ok(())
     }
 
-    /// Print an expression with precedence context
-    fn print_expression(&mut self, expr: &Expression, parent_precedence: Precedence) -> GeneratorResult<()> {
-        match expr {
-            Expression::Identifier(id) => self.print_identifier(id),
-            Expression::Literal(lit) => self.print_literal(lit),
-            Expression::BinaryExpression { left, operator, right } => {
-                self.print_binary_expression(left, operator, right, parent_precedence)
-            }
-            Expression::TemplateLiteral { quasis, expressions } => {
-                self.print_template_literal(quasis, expressions)
-            }
-            Expression::ThisExpression => self.print_this_expression(),
-            _ => {
-                // TODO: Implement remaining expression types
-                self.write("/* EXPR */")?;
-                Ok(())
+    /// Print a `new` expression, always with an argument list
+    fn print_new_expression(&mut self, callee: &Expression, arguments: &[Expression]) -> GeneratorResult<()> {
+        // `new f()()` would construct `f`, so a callee with a call in it is wrapped
+        let wrap_callee = !is_constructor_reference(callee);
+        self.write(if wrap_callee { "new(" } else { "new " })?;
+        self.print_expression(callee, if wrap_callee { Precedence::Sequence } else { Precedence::Member })?;
+        if wrap_callee {
+            self.write(")")?;
+        }
+        self.write("(")?;
+        for (i, argument) in arguments.iter().enumerate() {
+            if i > 0 {
+                self.print_list_separator()?;
             }
+            self.print_wrapped_item(|printer| printer.print_expression(argument, Precedence::Assignment))?;
         }
+        self.write(")")?;
+        self.prev_token = Some(TokenType::CloseParen);
+        Ok(())
     }
 
-    /// Print a binary expression with precedence handling
-    fn print_binary_expression(
-        &mut self,
-        left: &Expression,
-        operator: &BinaryOperator,
-        right: &Expression,
-        parent_precedence: Precedence,
-    ) -> GeneratorResult<()> {
-        let precedence = self.get_binary_operator_precedence(operator);
-        let needs_parens = precedence < parent_precedence;
-
-        if needs_parens {
-            self.write("(")?;
-        }
-
-        self.print_expression(left, precedence)?;
-        self.print_binary_operator(operator)?;
-        self.print_expression(right, precedence)?;
-
-        if needs_parens {
-            self.write(")")?;
+    /// Print a dynamic `import()`
+    fn print_import_expression(&mut self, source: &Expression, options: Option<&Expression>) -> GeneratorResult<()> {
+        self.write("import(")?;
+        self.print_expression(source, Precedence::Assignment)?;
+        if let Some(options) = options {
+            self.print_list_separator()?;
+            self.print_expression(options, Precedence::Assignment)?;
         }
-
+        self.write(")")?;
+        self.prev_token = Some(TokenType::CloseParen);
         Ok(())
     }
 
@@ -355,12 +1442,27 @@ impl Printer {
             BinaryOperator::LessThanEqual => "<=",
             BinaryOperator::GreaterThan => ">",
             BinaryOperator::GreaterThanEqual => ">=",
+            BinaryOperator::LeftShift => "<<",
+            BinaryOperator::RightShift => ">>",
+            BinaryOperator::UnsignedRightShift => ">>>",
+            BinaryOperator::BitwiseAnd => "&",
+            BinaryOperator::BitwiseOr => "|",
+            BinaryOperator::BitwiseXor => "^",
             BinaryOperator::LogicalAnd => "&&",
             BinaryOperator::LogicalOr => "||",
-            _ => "/* OP */",
+            BinaryOperator::NullishCoalescing => "??",
+            BinaryOperator::In => "in",
+            BinaryOperator::Instanceof => "instanceof",
         };
 
         match self.config.format {
+            // Keyword operators always need separating spaces
+            _ if matches!(op, BinaryOperator::In | BinaryOperator::Instanceof) => {
+                self.write(" ")?;
+                self.write(op_str)?;
+                self.write(" ")?;
+                Ok(())
+            }
             crate::generator::OutputFormat::Compact => self.write(op_str),
             _ => {
                 self.write(" ")?;
@@ -383,15 +1485,41 @@ impl Printer {
             | BinaryOperator::NotEqual
             | BinaryOperator::StrictEqual
             | BinaryOperator::StrictNotEqual => Precedence::Equality,
+            BinaryOperator::LessThan
+            | BinaryOperator::LessThanEqual
+            | BinaryOperator::GreaterThan
+            | BinaryOperator::GreaterThanEqual
+            | BinaryOperator::In
+            | BinaryOperator::Instanceof => Precedence::Relational,
+            BinaryOperator::LeftShift
+            | BinaryOperator::RightShift
+            | BinaryOperator::UnsignedRightShift => Precedence::Shift,
+            BinaryOperator::BitwiseAnd => Precedence::BitwiseAnd,
+            BinaryOperator::BitwiseXor => Precedence::BitwiseXor,
+            BinaryOperator::BitwiseOr => Precedence::BitwiseOr,
             BinaryOperator::LogicalAnd => Precedence::LogicalAnd,
-            BinaryOperator::LogicalOr => Precedence::LogicalOr,
-            _ => Precedence::Equality,
+            BinaryOperator::LogicalOr | BinaryOperator::NullishCoalescing => Precedence::LogicalOr,
+        }
+    }
+
+    /// Get binary operator associativity
+    fn get_binary_operator_associativity(&self, op: &BinaryOperator) -> Associativity {
+        match op {
+            BinaryOperator::Exponentiation => Associativity::Right,
+            _ => Associativity::Left,
         }
     }
 
     /// Helper methods
 
     fn print_identifier(&mut self, id: &Identifier) -> GeneratorResult<()> {
+        let marked = self.source_positions.len();
+        self.mark_source(id.span.as_ref(), MappingGranularity::Token);
+        // Renamed identifiers get their original name in the source map
+        if let (Some(position), Some(span)) = (self.source_positions.get_mut(marked), id.span.as_ref()) {
+            position.original_end = Some(span.end);
+            position.printed_len = id.name.len();
+        }
         self.write(&id.name)?;
         self.prev_token = Some(TokenType::Identifier);
         Ok(())
@@ -409,21 +1537,32 @@ impl Printer {
 
     fn print_string_literal(&mut self, lit: &StringLiteral) -> GeneratorResult<()> {
         let quote_char = self.choose_quote_character(&lit.value);
-        let escaped = self.escape_string(&lit.value, quote_char);
+        let escaped = if lit.lone_surrogates {
+            self.escape_string_with_surrogates(&lit.value, quote_char)
+        } else {
+            self.escape_string(&lit.value, quote_char)
+        };
         
+        self.mark_source(lit.span.as_ref(), MappingGranularity::Token);
         self.write(&format!("{}{}{}", quote_char, escaped, quote_char))?;
         self.prev_token = Some(TokenType::String);
         Ok(())
     }
 
     fn print_number_literal(&mut self, lit: &NumberLiteral) -> GeneratorResult<()> {
-        let canonical = self.canonicalize_number(lit.value);
+        let text = match (self.config.numbers, &lit.raw) {
+            (crate::generator::NumberStyle::Preserve, Some(raw)) => self.preserved_number(raw, lit.value),
+            _ => None,
+        };
+        let canonical = text.unwrap_or_else(|| self.canonicalize_number(lit.value));
+        self.mark_source(lit.span.as_ref(), MappingGranularity::Token);
         self.write(&canonical)?;
         self.prev_token = Some(TokenType::Number);
         Ok(())
     }
 
     fn print_boolean_literal(&mut self, lit: &BooleanLiteral) -> GeneratorResult<()> {
+        self.mark_source(lit.span.as_ref(), MappingGranularity::Token);
         self.write(if lit.value { "true" } else { "false" })?;
         self.prev_token = Some(TokenType::Identifier);
         Ok(())
@@ -436,6 +1575,7 @@ impl Printer {
     }
 
     fn print_regexp_literal(&mut self, lit: &RegExpLiteral) -> GeneratorResult<()> {
+        self.mark_source(lit.span.as_ref(), MappingGranularity::Token);
         self.write(&format!("/{}/{}", lit.pattern, lit.flags))?;
         self.prev_token = Some(TokenType::Regex);
         Ok(())
@@ -450,7 +1590,52 @@ impl Printer {
     fn print_pattern(&mut self, pattern: &Pattern) -> GeneratorResult<()> {
         match pattern {
             Pattern::Identifier(id) => self.print_identifier(id),
-            _ => self.write("/* PATTERN */"),
+            Pattern::ArrayPattern { elements } => {
+                self.require_feature(Feature::Destructuring)?;
+                self.write("[")?;
+                self.print_elements(elements, |printer, element| printer.print_pattern(element))?;
+                self.write("]")?;
+                self.prev_token = Some(TokenType::CloseBracket);
+                Ok(())
+            }
+            Pattern::ObjectPattern { properties } => {
+                self.require_feature(Feature::Destructuring)?;
+                self.write("{")?;
+                for (i, property) in properties.iter().enumerate() {
+                    if i > 0 {
+                        self.print_list_separator()?;
+                    }
+                    match property {
+                        ObjectPatternProperty::Property { key, value, .. } => {
+                            // `{ a = 1 }` binds `a`; a renamed binding needs `{ a: b = 1 }`
+                            let binding = match value {
+                                Pattern::AssignmentPattern { left, .. } => left.as_ref(),
+                                value => value,
+                            };
+                            if !is_shorthand(key, binding) {
+                                self.print_property_key(key)?;
+                                self.print_property_colon()?;
+                            }
+                            self.print_pattern(value)?;
+                        }
+                        ObjectPatternProperty::RestElement { argument } => {
+                            self.require_feature(Feature::ObjectRestSpread)?;
+                            self.write("...")?;
+                            self.print_pattern(argument)?;
+                        }
+                    }
+                }
+                self.write("}")
+            }
+            Pattern::AssignmentPattern { left, right } => {
+                self.print_pattern(left)?;
+                self.print_assignment_operator()?;
+                self.print_expression(right, Precedence::Assignment)
+            }
+            Pattern::RestElement { argument } => {
+                self.write("...")?;
+                self.print_pattern(argument)
+            }
         }
     }
 
@@ -460,7 +1645,146 @@ impl Printer {
                 self.write(",")?;
                 self.print_space_if_needed()?;
             }
-            self.print_pattern(param)?;
+            if matches!(param, Pattern::AssignmentPattern { .. } | Pattern::RestElement { .. }) {
+                self.require_feature(Feature::Parameters)?;
+            }
+            self.print_wrapped_item(|printer| printer.print_pattern(param))?;
+        }
+        Ok(())
+    }
+
+    /// Print the elements of an array literal or pattern, where `None` is a hole
+    fn print_elements<T>(
+        &mut self,
+        elements: &[Option<T>],
+        print: impl Fn(&mut Self, &T) -> GeneratorResult<()>,
+    ) -> GeneratorResult<()> {
+        for (i, element) in elements.iter().enumerate() {
+            if i > 0 {
+                self.print_list_separator()?;
+            }
+            if let Some(element) = element {
+                self.print_wrapped_item(|printer| print(printer, element))?;
+            }
+        }
+        // `[a,]` has one element, so a trailing hole needs its own comma
+        if let Some(None) = elements.last() {
+            self.write(",")?;
+        }
+        Ok(())
+    }
+
+    /// Print a spread argument or array element (`...a`)
+    fn print_spread_element(&mut self, argument: &Expression) -> GeneratorResult<()> {
+        self.require_feature(Feature::Spread)?;
+        self.write("...")?;
+        self.print_expression(argument, Precedence::Assignment)
+    }
+
+    /// Print an array literal
+    fn print_array_expression(&mut self, elements: &[Option<Expression>]) -> GeneratorResult<()> {
+        self.write("[")?;
+        self.print_elements(elements, |printer, element| printer.print_expression(element, Precedence::Assignment))?;
+        self.write("]")?;
+        self.prev_token = Some(TokenType::CloseBracket);
+        Ok(())
+    }
+
+    /// Print an object literal
+    fn print_object_expression(&mut self, properties: &[ObjectProperty]) -> GeneratorResult<()> {
+        self.write("{")?;
+        for (i, property) in properties.iter().enumerate() {
+            if i > 0 {
+                self.print_list_separator()?;
+            }
+            self.print_wrapped_item(|printer| printer.print_object_property(property))?;
+        }
+        self.write("}")
+    }
+
+    /// Print a property, method, accessor or spread of an object literal
+    fn print_object_property(&mut self, property: &ObjectProperty) -> GeneratorResult<()> {
+        let (key, value, kind, method) = match property {
+            ObjectProperty::Property { key, value, kind, method, .. } => (key, value, kind, *method),
+            ObjectProperty::SpreadElement { argument } => {
+                self.require_feature(Feature::ObjectRestSpread)?;
+                self.write("...")?;
+                return self.print_expression(argument, Precedence::Assignment);
+            }
+        };
+
+        match (kind, value) {
+            (PropertyKind::Get, Expression::FunctionExpression(function)) => {
+                self.write("get ")?;
+                self.print_method(key, function)
+            }
+            (PropertyKind::Set, Expression::FunctionExpression(function)) => {
+                self.write("set ")?;
+                self.print_method(key, function)
+            }
+            (_, Expression::FunctionExpression(function)) if method => self.print_method(key, function),
+            (_, Expression::Identifier(id)) if is_shorthand(key, &Pattern::Identifier(id.clone())) => {
+                self.print_identifier(id)
+            }
+            _ => {
+                self.print_property_key(key)?;
+                self.print_property_colon()?;
+                self.print_expression(value, Precedence::Assignment)
+            }
+        }
+    }
+
+    /// Print an arrow function, parenthesized inside operators that bind tighter than `=`
+    fn print_arrow_function(
+        &mut self,
+        params: &[Pattern],
+        body: &ArrowFunctionBody,
+        is_async: bool,
+        parent_precedence: Precedence,
+    ) -> GeneratorResult<()> {
+        self.require_feature(Feature::ArrowFunctions)?;
+        if is_async {
+            self.require_feature(Feature::AsyncFunctions)?;
+        }
+
+        let needs_parens = parent_precedence > Precedence::Assignment;
+        if needs_parens {
+            self.write("(")?;
+        }
+        if is_async {
+            self.write("async ")?;
+        }
+        match params {
+            [Pattern::Identifier(id)] => self.print_identifier(id)?,
+            params => {
+                self.write("(")?;
+                self.print_parameter_list(params)?;
+                self.write(")")?;
+            }
+        }
+        self.print_spaced_operator("=>")?;
+
+        match body {
+            ArrowFunctionBody::BlockStatement(block) => self.print_function_body(&block.body)?,
+            ArrowFunctionBody::Expression(expr) => {
+                // A leading `{` would be read as a block body
+                let wrap = matches!(leftmost_expression(expr), Expression::ObjectExpression { .. });
+                if wrap {
+                    self.write("(")?;
+                }
+                self.function_depth += 1;
+                let result = self.print_expression(expr, Precedence::Assignment);
+                self.function_depth -= 1;
+                result?;
+                if wrap {
+                    self.write(")")?;
+                }
+            }
+        }
+
+        if needs_parens {
+            self.write(")")?;
+            self.prev_token = Some(TokenType::CloseParen);
         }
         Ok(())
     }
@@ -471,12 +1795,18 @@ impl Printer {
         quasis: &[crate::parser::ast_types::TemplateElement],
         expressions: &[Expression],
     ) -> GeneratorResult<()> {
+        self.require_feature(Feature::TemplateLiterals)?;
         self.write("`")?;
         
         for (i, quasi) in quasis.iter().enumerate() {
-            // Print the template element (escape backticks and ${ sequences)
-            let escaped = self.escape_template_element(&quasi.value);
-            self.write(&escaped)?;
+            // Source text is printed as written; text built by a pass needs escaping
+            match &quasi.raw {
+                Some(raw) => self.write(raw)?,
+                None => {
+                    let escaped = self.escape_template_element(&quasi.value);
+                    self.write(&escaped)?;
+                }
+            }
             
             // If this is not the tail element, print the expression
             if !quasi.tail && i < expressions.len() {
@@ -490,9 +1820,73 @@ impl Printer {
         Ok(())
     }
 
+    /// Print a tagged template, whose quasis reach the tag unchanged
+    fn print_tagged_template(
+        &mut self,
+        tag: &Expression,
+        quasis: &[crate::parser::ast_types::TemplateElement],
+        expressions: &[Expression],
+    ) -> GeneratorResult<()> {
+        self.print_expression(tag, Precedence::Member)?;
+        self.print_template_literal(quasis, expressions)
+    }
+
     /// Utility methods
 
+    /// Prints an argument, parameter or right operand, moving it to a new line one level
+    /// deeper when its first line would end past `max_line_len`
+    ///
+    /// Only readable and pretty output wrap, and an item that already starts its line
+    /// stays put. Breaks follow `(`, `,` or a binary operator, where a newline never
+    /// triggers automatic semicolon insertion.
+    fn print_wrapped_item(&mut self, print: impl Fn(&mut Self) -> GeneratorResult<()>) -> GeneratorResult<()> {
+        let max_line_len = match (self.config.max_line_len, self.config.format) {
+            (Some(max_line_len), crate::generator::OutputFormat::Readable | crate::generator::OutputFormat::Pretty) => {
+                max_line_len
+            }
+            _ => return print(self),
+        };
+
+        let start = self.output.len();
+        let line_start = self.output[..start].rfind('\n').map_or(0, |index| index + 1);
+        let (prev_token, warning_count) = (self.prev_token, self.warnings.len());
+        print(self)?;
+
+        let first_line_end = self.output[start..].find('\n').map_or(self.output.len(), |index| start + index);
+        let starts_line = self.output[line_start..start].trim().is_empty();
+        if starts_line || self.output[line_start..first_line_end].chars().count() <= max_line_len {
+            return Ok(());
+        }
+
+        // Reprint on a fresh line, dropping the space after the preceding `,` or operator
+        let break_at = self.output[..start].trim_end_matches(' ').len();
+        self.chars_written -= self.output.len() - break_at;
+        self.output.truncate(break_at);
+        self.prev_token = prev_token;
+        self.warnings.truncate(warning_count);
+
+        self.write_newline()?;
+        self.indent_level += 1;
+        self.print_indent_if_needed()?;
+        let result = print(self);
+        self.indent_level -= 1;
+        result
+    }
+
+    /// Fails with `UnsupportedNode` when a configured target lacks `feature`
+    fn require_feature(&self, feature: Feature) -> GeneratorResult<()> {
+        if self.config.target.supports(feature) {
+            Ok(())
+        } else {
+            Err(GeneratorError::UnsupportedNode {
+                target: self.config.target.to_string(),
+                node_type: feature.to_string(),
+            })
+        }
+    }
+
     fn write(&mut self, s: &str) -> GeneratorResult<()> {
+        self.after_line_comment = false;
         self.output.push_str(s);
         self.chars_written += s.len();
         Ok(())
@@ -512,26 +1906,47 @@ impl Printer {
         Ok(())
     }
 
+    /// Separates `)` or a class head from the following `{` in pretty output
+    fn print_space_before_body(&mut self) -> GeneratorResult<()> {
+        match self.config.format {
+            crate::generator::OutputFormat::Pretty => self.write(" "),
+            _ => Ok(()),
+        }
+    }
+
     fn print_newline_if_needed(&mut self) -> GeneratorResult<()> {
+        if self.after_line_comment {
+            return Ok(());
+        }
         match self.config.format {
             crate::generator::OutputFormat::Compact => {
                 // No newlines in compact mode
             }
             crate::generator::OutputFormat::Readable | crate::generator::OutputFormat::Pretty => {
-                match self.config.newline {
-                    crate::generator::NewlineStyle::Lf => self.write("\n")?,
-                    crate::generator::NewlineStyle::Crlf => self.write("\r\n")?,
-                }
+                self.write_newline()?;
             }
         }
         Ok(())
     }
 
+    /// Writes a line break in the configured newline style, whatever the format
+    fn write_newline(&mut self) -> GeneratorResult<()> {
+        match self.config.newline {
+            crate::generator::NewlineStyle::Lf => self.write("\n"),
+            crate::generator::NewlineStyle::Crlf => self.write("\r\n"),
+        }
+    }
+
     fn print_indent_if_needed(&mut self) -> GeneratorResult<()> {
         match self.config.format {
             crate::generator::OutputFormat::Pretty => {
-                let indent = "  ".repeat(self.indent_level);
-                self.write(&indent)?;
+                if let Some(indent) = self.indent_cache.get(self.indent_level) {
+                    let indent = indent.clone();
+                    self.write(&indent)?;
+                } else {
+                    let indent = self.config.indent.unit().repeat(self.indent_level);
+                    self.write(&indent)?;
+                }
             }
             _ => {}
         }
@@ -562,6 +1977,35 @@ impl Printer {
         }
     }
 
+    /// Print the `,` between list items, followed by a space outside of compact output
+    fn print_list_separator(&mut self) -> GeneratorResult<()> {
+        self.write(",")?;
+        if !matches!(self.config.format, crate::generator::OutputFormat::Compact) {
+            self.write(" ")?;
+        }
+        Ok(())
+    }
+
+    /// Print the `:` between a property key and its value
+    fn print_property_colon(&mut self) -> GeneratorResult<()> {
+        match self.config.format {
+            crate::generator::OutputFormat::Compact => self.write(":"),
+            _ => self.write(": "),
+        }
+    }
+
+    /// Print an operator surrounded by spaces outside of compact output
+    fn print_spaced_operator(&mut self, op: &str) -> GeneratorResult<()> {
+        match self.config.format {
+            crate::generator::OutputFormat::Compact => self.write(op),
+            _ => {
+                self.write(" ")?;
+                self.write(op)?;
+                self.write(" ")
+            }
+        }
+    }
+
     fn print_statement_separator(&mut self) -> GeneratorResult<()> {
         match self.config.format {
             crate::generator::OutputFormat::Compact => {
@@ -589,14 +2033,28 @@ impl Printer {
         }
     }
 
+    /// Escapes `content` for a string literal delimited by `quote_char`
+    ///
+    /// Line and paragraph separators are always escaped: they end a string literal
+    /// before ES2019. Other control characters use their short escape or `\xHH`, and
+    /// NUL is `\0` unless a digit follows, where that would read as an octal escape.
     fn escape_string(&self, content: &str, quote_char: char) -> String {
         let mut result = String::new();
-        for ch in content.chars() {
+        let mut chars = content.chars().peekable();
+        while let Some(ch) = chars.next() {
             match ch {
                 '\n' => result.push_str("\\n"),
                 '\r' => result.push_str("\\r"),
                 '\t' => result.push_str("\\t"),
+                '\u{8}' => result.push_str("\\b"),
+                '\u{b}' => result.push_str("\\v"),
+                '\u{c}' => result.push_str("\\f"),
                 '\\' => result.push_str("\\\\"),
+                '\0' if chars.peek().is_some_and(char::is_ascii_digit) => result.push_str("\\x00"),
+                '\0' => result.push_str("\\0"),
+                '\u{2028}' => result.push_str("\\u2028"),
+                '\u{2029}' => result.push_str("\\u2029"),
+                c if c.is_ascii_control() => result.push_str(&format!("\\x{:02x}", c as u32)),
                 c if c == quote_char => {
                     result.push('\\');
                     result.push(c);
@@ -607,6 +2065,22 @@ impl Printer {
         result
     }
 
+    /// Escapes the value of a string with lone surrogates, which already spells them as
+    /// `\uXXXX` and its backslashes as `\\` (see [`StringLiteral::lone_surrogates`])
+    fn escape_string_with_surrogates(&self, content: &str, quote_char: char) -> String {
+        let mut result = String::new();
+        let mut rest = content;
+        while let Some(index) = rest.find('\\') {
+            result.push_str(&self.escape_string(&rest[..index], quote_char));
+            let escape_len = if rest[index..].starts_with("\\u") { 6 } else { 2 };
+            let end = (index + escape_len).min(rest.len());
+            result.push_str(&rest[index..end]);
+            rest = &rest[end..];
+        }
+        result.push_str(&self.escape_string(rest, quote_char));
+        result
+    }
+
     fn escape_template_element(&self, content: &str) -> String {
         let mut result = String::new();
         let mut chars = content.chars().peekable();
@@ -615,6 +2089,8 @@ impl Printer {
             match ch {
                 '`' => result.push_str("\\`"),
                 '\\' => result.push_str("\\\\"),
+                // A raw carriage return would be read back as a line feed
+                '\r' => result.push_str("\\r"),
                 '$' => {
                     // Check if this is the start of ${ sequence
                     if chars.peek() == Some(&'{') {
@@ -630,11 +2106,62 @@ impl Printer {
         result
     }
 
+    /// Shortest spelling of `value` among decimal, exponent, integer-mantissa exponent
+    /// (`1234e5`) and hex notation
     fn canonicalize_number(&self, value: f64) -> String {
-        if value == value.trunc() && value.abs() < 1e15 {
-            format!("{}", value as i64)
+        if value < 0.0 {
+            return format!("-{}", self.canonicalize_number(-value));
+        }
+        // A literal is never negative zero; `-0` in source is a unary minus
+        if value == 0.0 {
+            return "0".to_string();
+        }
+
+        let decimal = format!("{}", value);
+        let decimal = match decimal.strip_prefix("0.") {
+            Some(fraction) => format!(".{}", fraction),
+            None => decimal,
+        };
+        let exponent = format!("{:e}", value);
+        // `{:e}` gives the shortest digits that round-trip; moving the point past the
+        // last one (`1.234e8` to `1234e5`) drops it and often shortens the exponent
+        let integer_mantissa = exponent.split_once('e').and_then(|(mantissa, power)| {
+            let digits = mantissa.replace('.', "");
+            let scale = power.parse::<i32>().ok()? - (digits.len() as i32 - 1);
+            Some(if scale == 0 { digits } else { format!("{}e{}", digits, scale) })
+        });
+        let exponent = exponent.strip_suffix("e0").map_or(exponent.clone(), str::to_string);
+        let mut shortest = if exponent.len() < decimal.len() { exponent } else { decimal };
+        if let Some(integer_mantissa) = integer_mantissa
+            && integer_mantissa.len() < shortest.len()
+        {
+            shortest = integer_mantissa;
+        }
+
+        // Integers up to 2^53 are exact, so hex spells the same value
+        if value == value.trunc() && value <= 9_007_199_254_740_992.0 {
+            let hex = format!("0x{:x}", value as u64);
+            if hex.len() < shortest.len() {
+                shortest = hex;
+            }
+        }
+        shortest
+    }
+
+    /// Source spelling of a number literal, if it is still valid for `value` and the target
+    ///
+    /// `None` when a pass changed the value without updating `raw`, or for legacy octal
+    /// (`017`), which strict mode rejects.
+    fn preserved_number(&self, raw: &str, value: f64) -> Option<String> {
+        let digits = raw.replace('_', "");
+        let is_legacy_octal = digits.len() > 1 && digits.starts_with('0') && digits.as_bytes()[1].is_ascii_digit();
+        if is_legacy_octal || parse_number_literal(&digits) != Some(value) {
+            return None;
+        }
+        if self.config.target.supports(Feature::NumericSeparators) {
+            Some(raw.to_string())
         } else {
-            format!("{}", value)
+            Some(digits)
         }
     }
 
@@ -688,7 +2215,7 @@ impl Printer {
         use crate::generator::GeneratorError;
         
         match stmt {
-            Statement::VariableDeclaration { declarations, kind: _ } => {
+            Statement::VariableDeclaration { declarations, kind: _, .. } => {
                 if declarations.is_empty() {
                     return Err(GeneratorError::MissingRequiredField {
                         field: "declarations".to_string(),
@@ -711,15 +2238,15 @@ impl Printer {
                 
                 self.validate_block_statement(body)?;
             }
-            Statement::ExpressionStatement { expression } => {
+            Statement::ExpressionStatement { expression, .. } => {
                 self.validate_expression(expression)?;
             }
-            Statement::BlockStatement { body } => {
+            Statement::BlockStatement { body, .. } => {
                 for stmt in body {
                     self.validate_statement(stmt)?;
                 }
             }
-            Statement::ReturnStatement { argument } => {
+            Statement::ReturnStatement { argument, .. } => {
                 if let Some(expr) = argument {
                     self.validate_expression(expr)?;
                 }
@@ -767,7 +2294,11 @@ impl Printer {
                 self.validate_expression(left)?;
                 self.validate_expression(right)?;
             }
-            Expression::TemplateLiteral { quasis, expressions } => {
+            Expression::TemplateLiteral { quasis, expressions }
+            | Expression::TaggedTemplateExpression { quasis, expressions, .. } => {
+                if let Expression::TaggedTemplateExpression { tag, .. } = expr {
+                    self.validate_expression(tag)?;
+                }
                 if quasis.is_empty() {
                     return Err(GeneratorError::MissingRequiredField {
                         field: "quasis".to_string(),
@@ -806,13 +2337,26 @@ impl Printer {
             });
         }
         
-        // Check for invalid identifier characters (basic validation)
-        if !id.name.chars().next().unwrap_or('_').is_alphabetic() && id.name.chars().next() != Some('_') && id.name.chars().next() != Some('$') {
+        let Some(code_points) = identifier_code_points(&id.name) else {
+            return Err(GeneratorError::IdentifierError {
+                message: "Identifier contains an invalid escape sequence".to_string(),
+                identifier: id.name.clone(),
+            });
+        };
+
+        // ID_Start (plus `$` and `_`) first, then ID_Continue (plus `$`, ZWNJ and ZWJ)
+        if !is_identifier_start(code_points[0]) {
             return Err(GeneratorError::IdentifierError {
                 message: "Identifier must start with letter, underscore, or dollar sign".to_string(),
                 identifier: id.name.clone(),
             });
         }
+        if let Some(c) = code_points[1..].iter().find(|c| !is_identifier_part(**c)) {
+            return Err(GeneratorError::IdentifierError {
+                message: format!("Identifier contains invalid character {:?}", c),
+                identifier: id.name.clone(),
+            });
+        }
         
         Ok(())
     }
@@ -822,15 +2366,6 @@ impl Printer {
         use crate::generator::GeneratorError;
         
         match lit {
-            Literal::String(s) => {
-                // Basic string validation - check for proper content
-                if s.value.contains('\0') {
-                    return Err(GeneratorError::StringProcessingError {
-                        message: "String contains null character".to_string(),
-                        content: s.value.clone(),
-                    });
-                }
-            }
             Literal::Number(n) => {
                 // Validate numeric values
                 if n.value.is_nan() {
@@ -891,12 +2426,22 @@ impl Printer {
     fn check_memory_limits(&self) -> GeneratorResult<()> {
         use crate::generator::GeneratorError;
         
-        const MAX_OUTPUT_SIZE: usize = 10 * 1024 * 1024; // 10MB limit
-        
-        if self.output.len() > MAX_OUTPUT_SIZE {
+        let total_size = self.bytes_flushed + self.output.len();
+        if let Some(limit) = self.config.max_output_size
+            && total_size > limit
+        {
             return Err(GeneratorError::OutputSizeLimitExceeded {
-                current_size: self.output.len(),
-                limit: MAX_OUTPUT_SIZE,
+                current_size: total_size,
+                limit,
+            });
+        }
+
+        if let Some(limit) = self.config.max_memory_usage
+            && self.output.len() > limit
+        {
+            return Err(GeneratorError::MemoryLimitExceeded {
+                current_usage: self.output.len(),
+                limit,
             });
         }
         
@@ -910,8 +2455,9 @@ impl Printer {
         const MAX_CACHED_INDENT: usize = 20;
         self.indent_cache.reserve(MAX_CACHED_INDENT);
         
+        let unit = self.config.indent.unit();
         for i in 0..MAX_CACHED_INDENT {
-            self.indent_cache.push("  ".repeat(i));
+            self.indent_cache.push(unit.repeat(i));
         }
     }
 
@@ -946,10 +2492,123 @@ impl Printer {
         self.indent_level = 0;
         self.chars_written = 0;
         self.string_buffer.clear();
+        self.source_positions.clear();
+        self.after_line_comment = false;
         // Keep the caches for reuse
     }
 }
 
+/// Whether `stmt` ends in an `if` without `else`, which would capture a following `else`
+fn ends_with_open_if(stmt: &Statement) -> bool {
+    match stmt {
+        Statement::IfStatement { alternate: None, .. } => true,
+        Statement::IfStatement { alternate: Some(alternate), .. } => ends_with_open_if(alternate),
+        Statement::WhileStatement { body, .. }
+        | Statement::ForStatement { body, .. }
+        | Statement::ForOfStatement { body, .. } => ends_with_open_if(body),
+        _ => false,
+    }
+}
+
+/// The subexpression printed first, which decides how a statement or arrow body starts
+fn leftmost_expression(expression: &Expression) -> &Expression {
+    match expression {
+        Expression::BinaryExpression { left, .. } | Expression::AssignmentExpression { left, .. } => {
+            leftmost_expression(left)
+        }
+        Expression::ConditionalExpression { test, .. } => leftmost_expression(test),
+        Expression::CallExpression { callee, .. } => leftmost_expression(callee),
+        Expression::MemberExpression { object, .. } => leftmost_expression(object),
+        Expression::ChainExpression { expression } => leftmost_expression(expression),
+        Expression::UpdateExpression { argument, prefix: false, .. } => leftmost_expression(argument),
+        expression => expression,
+    }
+}
+
+/// Whether `new` can be followed by the expression without parentheses: a name, `this`,
+/// `new.target` or a member chain without calls or optional links
+fn is_constructor_reference(expression: &Expression) -> bool {
+    match expression {
+        Expression::Identifier(_) | Expression::ThisExpression | Expression::MetaProperty { .. } => true,
+        Expression::MemberExpression { object, optional: false, .. } => is_constructor_reference(object),
+        _ => false,
+    }
+}
+
+/// Whether a decorator can follow the `@` without parentheses: a dotted name such as
+/// `a.b.#c`, or a single call of one
+fn is_plain_decorator(expression: &Expression) -> bool {
+    fn is_dotted_name(expression: &Expression) -> bool {
+        match expression {
+            Expression::Identifier(_) => true,
+            Expression::MemberExpression { object, computed: false, optional: false, .. } => is_dotted_name(object),
+            _ => false,
+        }
+    }
+    match expression {
+        Expression::CallExpression { callee, optional: false, .. } => is_dotted_name(callee),
+        expression => is_dotted_name(expression),
+    }
+}
+
+/// Whether `name` can be written as an identifier, without escapes
+fn is_identifier_name(name: &str) -> bool {
+    let mut chars = name.chars();
+    chars.next().is_some_and(is_identifier_start) && chars.all(is_identifier_part)
+}
+
+/// Whether a property can be written as just its binding (`{ a }` for `{ a: a }`)
+fn is_shorthand(key: &PropertyKey, binding: &Pattern) -> bool {
+    matches!((key, binding), (PropertyKey::Identifier(key), Pattern::Identifier(binding)) if key.name == binding.name)
+}
+
+/// Value of a number literal without separators, in any radix
+fn parse_number_literal(digits: &str) -> Option<f64> {
+    let radix_digits = |prefix_len: usize, radix: u32| {
+        digits[prefix_len..]
+            .chars()
+            .try_fold(0.0, |value: f64, digit| Some(value * f64::from(radix) + f64::from(digit.to_digit(radix)?)))
+    };
+    match digits.get(..2) {
+        Some("0x" | "0X") => radix_digits(2, 16),
+        Some("0o" | "0O") => radix_digits(2, 8),
+        Some("0b" | "0B") => radix_digits(2, 2),
+        _ => digits.parse().ok(),
+    }
+}
+
+/// Code points of an identifier name with `\uXXXX` and `\u{X…}` escapes decoded
+///
+/// `None` if an escape is malformed or does not denote a Unicode scalar value.
+fn identifier_code_points(name: &str) -> Option<Vec<char>> {
+    let mut code_points = Vec::new();
+    let mut chars = name.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            code_points.push(c);
+            continue;
+        }
+        if chars.next() != Some('u') {
+            return None;
+        }
+
+        let rest = chars.as_str();
+        let (hex, consumed) = match rest.strip_prefix('{') {
+            Some(braced) => {
+                let end = braced.find('}')?;
+                (&braced[..end], end + 2)
+            }
+            None => (rest.get(..4)?, 4),
+        };
+        if hex.is_empty() || !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+            return None;
+        }
+        code_points.push(char::from_u32(u32::from_str_radix(hex, 16).ok()?)?);
+        chars = rest[consumed..].chars();
+    }
+    Some(code_points)
+}
+
 /// Performance metrics for monitoring
 #[derive(Debug, Clone)]
 pub struct PerformanceMetrics {
@@ -992,10 +2651,12 @@ mod tests {
         let program = Program {
             body: vec![Statement::VariableDeclaration {
                 declarations: vec![VariableDeclarator {
-                    id: Pattern::Identifier(Identifier { name: "x".to_string() }),
-                    init: Some(Expression::Literal(Literal::Number(NumberLiteral { value: 5.0 }))),
+                    id: Pattern::Identifier(Identifier { name: "x".to_string(), span: None }),
+                    init: Some(Expression::Literal(Literal::Number(NumberLiteral { value: 5.0, raw: None, span: None }))),
+                    span: None,
                 }],
                 kind: VariableDeclarationKind::Let,
+                span: None,
             }],
             source_type: ProgramSourceType::Script,
         };
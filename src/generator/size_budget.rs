@@ -0,0 +1,274 @@
+//! # Size Budgets
+//!
+//! Byte limits on an output file, raw and after gzip, with the source code to blame when
+//! an output goes over. Output bytes are attributed to the input through the source map:
+//! each mapping covers the generated bytes up to the next one, and the original position
+//! it points at falls in one of the top-level statements of the input. Bytes before the
+//! first mapping (a module wrapper, polyfill imports) are reported as unmapped.
+
+use std::fmt;
+use std::io::Write;
+
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use serde::{Deserialize, Serialize};
+
+use crate::generator::source_maps::{decode_mappings, SourceMap};
+use crate::generator::LineIndex;
+use crate::parser::ast_types::{Pattern, Program, Statement, VariableDeclarationKind};
+
+/// Maximum sizes of one output file; `None` is no limit
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SizeBudget {
+    pub max_bytes:      Option<usize>,
+    pub max_gzip_bytes: Option<usize>,
+}
+
+impl SizeBudget {
+    pub fn with_max_bytes(mut self, max_bytes: Option<usize>) -> Self {
+        self.max_bytes = max_bytes;
+        self
+    }
+
+    pub fn with_max_gzip_bytes(mut self, max_gzip_bytes: Option<usize>) -> Self {
+        self.max_gzip_bytes = max_gzip_bytes;
+        self
+    }
+
+    /// Whether neither size is limited
+    pub fn is_unlimited(&self) -> bool {
+        self.max_bytes.is_none() && self.max_gzip_bytes.is_none()
+    }
+
+    /// The limits `size` goes over
+    pub fn check(&self, size: &OutputSize) -> Vec<BudgetExcess> {
+        let raw = self.max_bytes.filter(|&max| size.bytes > max).map(|max| BudgetExcess {
+            gzip:   false,
+            size:   size.bytes,
+            budget: max,
+        });
+        let gzip = self.max_gzip_bytes.filter(|&max| size.gzip_bytes > max).map(|max| BudgetExcess {
+            gzip:   true,
+            size:   size.gzip_bytes,
+            budget: max,
+        });
+        raw.into_iter().chain(gzip).collect()
+    }
+}
+
+/// Raw and gzip size of an output
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct OutputSize {
+    pub bytes:      usize,
+    /// Size after gzip at the default level, as most servers compress
+    pub gzip_bytes: usize,
+}
+
+impl OutputSize {
+    pub fn measure(code: &str) -> Self {
+        Self { bytes: code.len(), gzip_bytes: gzip_size(code.as_bytes()) }
+    }
+}
+
+/// A size over its limit
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BudgetExcess {
+    /// Whether the gzip size is over, rather than the raw size
+    pub gzip:   bool,
+    pub size:   usize,
+    pub budget: usize,
+}
+
+impl fmt::Display for BudgetExcess {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let kind = if self.gzip { "gzip size" } else { "size" };
+        write!(
+            f,
+            "{} {} bytes exceeds the budget of {} bytes by {}",
+            kind,
+            self.size,
+            self.budget,
+            self.size - self.budget
+        )
+    }
+}
+
+/// A top-level statement of the input, by byte range
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Declaration {
+    /// What the statement declares, such as `function render` or `const a, b`
+    pub name:  String,
+    pub start: usize,
+    pub end:   usize,
+}
+
+/// Output bytes that came from one top-level statement
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Contributor {
+    pub name:  String,
+    /// One-based line of the statement in the input; `None` for unmapped bytes
+    pub line:  Option<usize>,
+    pub bytes: usize,
+}
+
+impl fmt::Display for Contributor {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:>8} bytes  {}", self.bytes, self.name)?;
+        if let Some(line) = self.line {
+            write!(f, " (line {})", line)?;
+        }
+        Ok(())
+    }
+}
+
+/// The top-level statements of `program` that have a source span
+pub fn declarations(program: &Program) -> Vec<Declaration> {
+    program
+        .body
+        .iter()
+        .filter_map(|statement| {
+            let span = statement.span()?;
+            Some(Declaration { name: describe(statement), start: span.start as usize, end: span.end as usize })
+        })
+        .collect()
+}
+
+/// Output bytes of `code` per declaration of `source`, largest first
+///
+/// `source_map` must be the map of `code` back to `source`. Declarations that
+/// contributed nothing are left out; bytes not traced to any declaration come last,
+/// as `(unmapped)`.
+pub fn attribute(code: &str, source_map: &SourceMap, source: &str, declarations: &[Declaration]) -> Vec<Contributor> {
+    let generated_lines = LineIndex::new(code);
+    let original_lines = LineIndex::new(source);
+    let mut points: Vec<(usize, Option<usize>)> = decode_mappings(&source_map.mappings)
+        .unwrap_or_default()
+        .iter()
+        .map(|mapping| {
+            let generated = generated_lines.offset(code, mapping.generated);
+            (generated, mapping.original.map(|original| original_lines.offset(source, original)))
+        })
+        .collect();
+    points.sort_by_key(|(generated, _)| *generated);
+
+    let mut bytes = vec![0; declarations.len()];
+    let mut unmapped = points.first().map_or(code.len(), |(generated, _)| *generated);
+    for (index, (start, original)) in points.iter().enumerate() {
+        let end = points.get(index + 1).map_or(code.len(), |(generated, _)| *generated);
+        let owner = original.and_then(|offset| {
+            declarations.iter().position(|declaration| declaration.start <= offset && offset < declaration.end)
+        });
+        match owner {
+            Some(owner) => bytes[owner] += end - start,
+            None => unmapped += end - start,
+        }
+    }
+
+    let mut contributors: Vec<Contributor> = declarations
+        .iter()
+        .zip(bytes)
+        .filter(|(_, bytes)| *bytes > 0)
+        .map(|(declaration, bytes)| Contributor {
+            name: declaration.name.clone(),
+            line: Some(original_lines.position(source, declaration.start).line as usize + 1),
+            bytes,
+        })
+        .collect();
+    contributors.sort_by(|a, b| b.bytes.cmp(&a.bytes).then_with(|| a.line.cmp(&b.line)));
+    if unmapped > 0 {
+        contributors.push(Contributor { name: "(unmapped)".to_string(), line: None, bytes: unmapped });
+    }
+    contributors
+}
+
+/// Size of `bytes` after gzip at the default level
+fn gzip_size(bytes: &[u8]) -> usize {
+    let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+    // Writing to a `Vec` cannot fail
+    encoder.write_all(bytes).and_then(|_| encoder.finish()).map_or(0, |compressed| compressed.len())
+}
+
+/// Short description of what a top-level statement declares
+fn describe(statement: &Statement) -> String {
+    match statement {
+        Statement::FunctionDeclaration { id: Some(id), .. } => format!("function {}", id.name),
+        Statement::ClassDeclaration { id: Some(id), .. } => format!("class {}", id.name),
+        Statement::FunctionDeclaration { .. } => "function".to_string(),
+        Statement::ClassDeclaration { .. } => "class".to_string(),
+        Statement::VariableDeclaration { declarations, kind, .. } => {
+            let kind = match kind {
+                VariableDeclarationKind::Var => "var",
+                VariableDeclarationKind::Let => "let",
+                VariableDeclarationKind::Const => "const",
+                VariableDeclarationKind::Using => "using",
+                VariableDeclarationKind::AwaitUsing => "await using",
+            };
+            let names: Vec<&str> = declarations
+                .iter()
+                .map(|declarator| match &declarator.id {
+                    Pattern::Identifier(id) => id.name.as_str(),
+                    _ => "{...}",
+                })
+                .collect();
+            format!("{} {}", kind, names.join(", "))
+        }
+        Statement::ExportNamedDeclaration { declaration: Some(declaration), .. } => {
+            format!("export {}", describe(declaration))
+        }
+        Statement::ExportNamedDeclaration { .. } => "export {...}".to_string(),
+        Statement::ExportDefaultDeclaration { declaration, .. } => match declaration.as_ref() {
+            Statement::ExpressionStatement { .. } => "export default".to_string(),
+            declaration => format!("export default {}", describe(declaration)),
+        },
+        Statement::ExportAllDeclaration { source, .. } => format!("export * from '{}'", source.value),
+        Statement::ImportDeclaration { source, .. } => format!("import '{}'", source.value),
+        _ => "statement".to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::generator::{Generator, GeneratorConfig, SourceMapMode};
+    use crate::parser::{parse_js, ParserConfig};
+
+    #[test]
+    fn test_budget_reports_sizes_over_the_limit() {
+        let size = OutputSize::measure(&"a".repeat(1000));
+        assert_eq!(size.bytes, 1000);
+        assert!(size.gzip_bytes < 100, "{:?}", size);
+
+        let budget = SizeBudget::default().with_max_bytes(Some(500)).with_max_gzip_bytes(Some(100));
+        let excesses = budget.check(&size);
+        assert_eq!(excesses, vec![BudgetExcess { gzip: false, size: 1000, budget: 500 }]);
+        assert_eq!(excesses[0].to_string(), "size 1000 bytes exceeds the budget of 500 bytes by 500");
+        assert!(SizeBudget::default().check(&size).is_empty());
+        assert!(SizeBudget::default().is_unlimited());
+    }
+
+    #[test]
+    fn test_attributes_output_bytes_to_top_level_declarations() {
+        let source = "import x from 'x';\n\
+                      function small() { return 1; }\n\
+                      function large(first, second) {\n  return [first, second, first * second, 'a long string literal'];\n}\n\
+                      const a = 1, b = 2;\n";
+        let ast = parse_js(source, "test.js", &ParserConfig::default()).ast.unwrap();
+        let config = GeneratorConfig { source_map: SourceMapMode::File, ..GeneratorConfig::default() };
+        let result = Generator::new(config).generate(&ast, Some(source)).unwrap();
+
+        let contributors = attribute(&result.code, result.source_map.as_ref().unwrap(), source, &declarations(&ast));
+        let names: Vec<(&str, Option<usize>)> =
+            contributors.iter().map(|contributor| (contributor.name.as_str(), contributor.line)).collect();
+        assert_eq!(
+            names,
+            vec![
+                ("function large", Some(3)),
+                ("function small", Some(2)),
+                ("import 'x'", Some(1)),
+                ("const a, b", Some(6)),
+            ]
+        );
+        assert_eq!(contributors.iter().map(|contributor| contributor.bytes).sum::<usize>(), result.code.len());
+        assert_eq!(contributors[1].to_string(), format!("{:>8} bytes  function small (line 2)", contributors[1].bytes));
+    }
+}
@@ -28,6 +28,12 @@ pub struct SourceMap {
     /// Optional sections for indexed source maps
     #[serde(skip_serializing_if = "Option::is_none")]
     pub sections: Option<Vec<SourceMapSection>>,
+    /// Indices into `sources` that debuggers should skip (see [`SourceMap::ignore_sources`])
+    #[serde(rename = "ignoreList", default, skip_serializing_if = "Vec::is_empty")]
+    pub ignore_list: Vec<u32>,
+    /// The same indices under the name Chrome read before `ignoreList` was standardised
+    #[serde(rename = "x_google_ignoreList", default, skip_serializing_if = "Vec::is_empty")]
+    pub x_google_ignore_list: Vec<u32>,
 }
 
 /// Source map section for indexed maps
@@ -115,6 +121,8 @@ impl SourceMap {
             names: Vec::new(),
             mappings: String::new(),
             sections: None,
+            ignore_list: Vec::new(),
+            x_google_ignore_list: Vec::new(),
         }
     }
 
@@ -131,6 +139,8 @@ impl SourceMap {
             names: builder.names,
             mappings,
             sections: None,
+            ignore_list: Vec::new(),
+            x_google_ignore_list: Vec::new(),
         }
     }
 
@@ -150,6 +160,67 @@ impl SourceMap {
     pub fn add_source_mapping_url_comment(&self, url: &str) -> String {
         format!("//# sourceMappingURL={}", url)
     }
+
+    /// Marks the sources matching any of `patterns` (see [`matches_glob`]) as
+    /// ignore-listed, so debuggers step over them and hide them from stack traces
+    ///
+    /// Replaces any previous ignore list. Returns how many sources were marked.
+    pub fn ignore_sources(&mut self, patterns: &[String]) -> usize {
+        self.ignore_list = (0..)
+            .zip(&self.sources)
+            .filter(|(_, source)| patterns.iter().any(|pattern| matches_glob(pattern, source)))
+            .map(|(index, _)| index)
+            .collect();
+        self.x_google_ignore_list = self.ignore_list.clone();
+        self.ignore_list.len()
+    }
+}
+
+/// Whether the source path `path` matches the glob `pattern`
+///
+/// `*` matches within a path segment, `**` across segments (`**/` also matches no
+/// segment at all) and `?` a single character other than `/`. A pattern without `/`
+/// is matched against the file name only. Backslashes and a leading `./` are ignored
+/// on both sides.
+///
+/// ```rust
+/// use rjs_compiler::generator::source_maps::matches_glob;
+///
+/// assert!(matches_glob("**/node_modules/**", "node_modules/lodash/map.js"));
+/// assert!(matches_glob("vendor/*.js", "./vendor/jquery.js"));
+/// assert!(matches_glob("*.generated.js", "src/api.generated.js"));
+/// assert!(!matches_glob("vendor/*.js", "vendor/jquery/core.js"));
+/// ```
+pub fn matches_glob(pattern: &str, path: &str) -> bool {
+    let normalize = |value: &str| {
+        let value = value.replace('\\', "/");
+        value.strip_prefix("./").unwrap_or(&value).to_string()
+    };
+    let (pattern, path) = (normalize(pattern), normalize(path));
+    let path = if pattern.contains('/') {
+        path.as_str()
+    } else {
+        path.rsplit('/').next().unwrap_or_default()
+    };
+    let pattern: Vec<char> = pattern.chars().collect();
+    let path: Vec<char> = path.chars().collect();
+    glob_match(&pattern, &path)
+}
+
+fn glob_match(pattern: &[char], path: &[char]) -> bool {
+    match pattern {
+        [] => path.is_empty(),
+        ['*', '*', rest @ ..] => {
+            (rest.first() == Some(&'/') && glob_match(&rest[1..], path))
+                || (0..=path.len()).any(|start| glob_match(rest, &path[start..]))
+        }
+        ['*', rest @ ..] => {
+            let segment = path.iter().position(|&c| c == '/').unwrap_or(path.len());
+            (0..=segment).any(|start| glob_match(rest, &path[start..]))
+        }
+        ['?', rest @ ..] => path.first().is_some_and(|&c| c != '/') && glob_match(rest, &path[1..]),
+        [c, rest @ ..] => path.first() == Some(c) && glob_match(rest, &path[1..]),
+    }
 }
 
 impl Default for SourceMap {
@@ -241,61 +312,50 @@ impl Default for SourceMapBuilder {
 }
 
 /// Encode mappings to Base64 VLQ format
+///
+/// Lines are separated by `;`, with an empty entry for each generated line without
+/// segments, and the segments of a line by `,` in column order. Each field is the
+/// difference from the same field of the previous segment: the generated column
+/// restarts from 0 on every line, while the source index, original position and name
+/// index carry over from line to line. Segments without an original position have only
+/// the generated column.
 fn encode_mappings(mappings: &HashMap<u32, Vec<MappingSegment>>) -> String {
     let mut result = String::new();
-    let mut prev_generated_column = 0;
-    let mut prev_source_index = 0;
-    let mut prev_original_line = 0;
-    let mut prev_original_column = 0;
-    let mut prev_name_index = 0;
-
-    // Process mappings in line order
-    let mut sorted_lines: Vec<_> = mappings.keys().collect();
-    sorted_lines.sort();
-
-    for (line_idx, &line) in sorted_lines.iter().enumerate() {
-        if line_idx > 0 {
+    let mut previous_source_index = 0;
+    let mut previous_original_line = 0;
+    let mut previous_original_column = 0;
+    let mut previous_name_index = 0;
+
+    let line_count = mappings.keys().max().map_or(0, |&line| line + 1);
+    for line in 0..line_count {
+        if line > 0 {
             result.push(';');
         }
+        let Some(segments) = mappings.get(&line) else {
+            continue;
+        };
 
-        // Reset column for new line
-        prev_generated_column = 0;
-
-        if let Some(segments) = mappings.get(line) {
-            let mut sorted_segments = segments.clone();
-            sorted_segments.sort_by_key(|s| s.generated_column);
-
-            for (seg_idx, segment) in sorted_segments.iter().enumerate() {
-                if seg_idx > 0 {
-                    result.push(',');
-                }
-
-                // Generated column (always present)
-                result.push_str(&encode_vlq(segment.generated_column as i32 - prev_generated_column as i32));
-                prev_generated_column = segment.generated_column;
-
-                // Source information (optional)
-                if let (Some(source_idx), Some(orig_line), Some(orig_col)) = 
-                    (segment.source_index, segment.original_line, segment.original_column) {
-                    
-                    // Source index
-                    result.push_str(&encode_vlq(source_idx as i32 - prev_source_index as i32));
-                    prev_source_index = source_idx;
-
-                    // Original line
-                    result.push_str(&encode_vlq(orig_line as i32 - prev_original_line as i32));
-                    prev_original_line = orig_line;
-
-                    // Original column
-                    result.push_str(&encode_vlq(orig_col as i32 - prev_original_column as i32));
-                    prev_original_column = orig_col;
-
-                    // Name index (optional)
-                    if let Some(name_idx) = segment.name_index {
-                        result.push_str(&encode_vlq(name_idx as i32 - prev_name_index as i32));
-                        prev_name_index = name_idx;
-                    }
-                }
+        // Stable, so segments added at the same column keep their order
+        let mut sorted_segments: Vec<&MappingSegment> = segments.iter().collect();
+        sorted_segments.sort_by_key(|segment| segment.generated_column);
+
+        let mut previous_generated_column = 0;
+        for (index, segment) in sorted_segments.into_iter().enumerate() {
+            if index > 0 {
+                result.push(',');
+            }
+            encode_vlq_into(&mut result, delta(segment.generated_column, &mut previous_generated_column));
+
+            let (Some(source_index), Some(original_line), Some(original_column)) =
+                (segment.source_index, segment.original_line, segment.original_column)
+            else {
+                continue;
+            };
+            encode_vlq_into(&mut result, delta(source_index, &mut previous_source_index));
+            encode_vlq_into(&mut result, delta(original_line, &mut previous_original_line));
+            encode_vlq_into(&mut result, delta(original_column, &mut previous_original_column));
+            if let Some(name_index) = segment.name_index {
+                encode_vlq_into(&mut result, delta(name_index, &mut previous_name_index));
             }
         }
     }
@@ -303,31 +363,134 @@ fn encode_mappings(mappings: &HashMap<u32, Vec<MappingSegment>>) -> String {
     result
 }
 
+/// Difference of `value` from `*previous`, which becomes `value`
+fn delta(value: u32, previous: &mut u32) -> i64 {
+    let difference = i64::from(value) - i64::from(*previous);
+    *previous = value;
+    difference
+}
+
+/// Decode Base64 VLQ mappings into one mapping per segment, in generated order
+///
+/// # Errors
+///
+/// Returns an error if the mappings contain a character outside Base64, a value cut
+/// short, a segment with the wrong number of fields or a negative position.
+pub fn decode_mappings(mappings: &str) -> Result<Vec<Mapping>, String> {
+    let mut result = Vec::new();
+    let mut source_index = 0;
+    let mut original_line = 0;
+    let mut original_column = 0;
+    let mut name_index = 0;
+
+    for (line, segments) in mappings.split(';').enumerate() {
+        let mut generated_column = 0;
+        for segment in segments.split(',').filter(|segment| !segment.is_empty()) {
+            let fields = decode_vlq(segment)?;
+            if !matches!(fields.len(), 1 | 4 | 5) {
+                return Err(format!("mappings segment '{}' has {} fields", segment, fields.len()));
+            }
+            generated_column += fields[0];
+            let original = if fields.len() >= 4 {
+                source_index += fields[1];
+                original_line += fields[2];
+                original_column += fields[3];
+                Some(Position { line: to_index(original_line)?, column: to_index(original_column)? })
+            } else {
+                None
+            };
+            if fields.len() == 5 {
+                name_index += fields[4];
+            }
+
+            result.push(Mapping {
+                generated: Position { line: line as u32, column: to_index(generated_column)? },
+                original,
+                source_index: if original.is_some() { Some(to_index(source_index)?) } else { None },
+                name_index: if fields.len() == 5 { Some(to_index(name_index)?) } else { None },
+            });
+        }
+    }
+
+    Ok(result)
+}
+
+/// Decode the Base64 VLQ values of `segment`, the inverse of [`encode_vlq`] applied to
+/// each value in turn
+///
+/// # Errors
+///
+/// Returns an error on a character outside Base64, a value cut short or a value that
+/// needs more than 35 bits.
+pub fn decode_vlq(segment: &str) -> Result<Vec<i64>, String> {
+    let mut values = Vec::new();
+    let mut value = 0_i64;
+    let mut shift = 0;
+
+    for c in segment.chars() {
+        let digit = decode_base64_digit(c).ok_or_else(|| format!("invalid character '{}' in mappings", c))?;
+        if shift > 30 {
+            return Err(format!("mappings segment '{}' has a value out of range", segment));
+        }
+        value |= i64::from(digit & 0x1f) << shift;
+        if digit & 0x20 != 0 {
+            shift += 5;
+            continue;
+        }
+
+        // The sign is in the least significant bit
+        let magnitude = value >> 1;
+        values.push(if value & 1 == 1 { -magnitude } else { magnitude });
+        value = 0;
+        shift = 0;
+    }
+
+    if shift != 0 {
+        return Err(format!("mappings segment '{}' ends in the middle of a value", segment));
+    }
+    Ok(values)
+}
+
+/// A decoded running total as a line, column or index
+fn to_index(value: i64) -> Result<u32, String> {
+    u32::try_from(value).map_err(|_| format!("mappings decode to an invalid position {}", value))
+}
+
+/// Decode a Base64 character into its 6-bit value
+fn decode_base64_digit(c: char) -> Option<u8> {
+    match c {
+        'A'..='Z' => Some(c as u8 - b'A'),
+        'a'..='z' => Some(c as u8 - b'a' + 26),
+        '0'..='9' => Some(c as u8 - b'0' + 52),
+        '+' => Some(62),
+        '/' => Some(63),
+        _ => None,
+    }
+}
+
 /// Encode a signed integer as Base64 VLQ
-fn encode_vlq(mut value: i32) -> String {
+///
+/// The sign goes in the least significant bit, then the magnitude follows in groups of
+/// five bits, least significant first, with the sixth bit set on every digit but the last.
+pub fn encode_vlq(value: i64) -> String {
     let mut result = String::new();
-    let sign = if value < 0 { 1 } else { 0 };
-    value = value.abs();
-
-    // Encode sign in LSB
-    let mut vlq = (value << 1) | sign;
+    encode_vlq_into(&mut result, value);
+    result
+}
 
+fn encode_vlq_into(result: &mut String, value: i64) {
+    let mut vlq = (value.unsigned_abs() << 1) | u64::from(value < 0);
     loop {
-        let mut digit = vlq & 0x1f;
+        let mut digit = (vlq & 0x1f) as u8;
         vlq >>= 5;
-
         if vlq != 0 {
             digit |= 0x20; // Continuation bit
         }
-
-        result.push(encode_base64_digit(digit as u8));
-
+        result.push(encode_base64_digit(digit));
         if vlq == 0 {
             break;
         }
     }
-
-    result
 }
 
 /// Encode a 6-bit value as Base64 character
@@ -403,6 +566,29 @@ mod tests {
         assert!(map.names.is_empty());
     }
 
+    #[test]
+    fn test_ignore_sources_marks_matching_sources() {
+        let mut map = SourceMap::new();
+        map.sources = vec![
+            "src/app.js".to_string(),
+            "node_modules/lodash/map.js".to_string(),
+            "src\\vendor\\jquery.js".to_string(),
+            "src/api.generated.js".to_string(),
+        ];
+        let patterns = ["**/node_modules/**", "**/vendor/*.js", "*.generated.js"].map(String::from);
+
+        assert_eq!(map.ignore_sources(&patterns), 3);
+        assert_eq!(map.ignore_list, [1, 2, 3]);
+        let json = map.to_json().unwrap();
+        assert!(json.contains("\"ignoreList\":[1,2,3]"), "{}", json);
+        assert!(json.contains("\"x_google_ignoreList\":[1,2,3]"), "{}", json);
+
+        assert_eq!(map.ignore_sources(&[]), 0);
+        assert!(!map.to_json().unwrap().contains("ignoreList"));
+        assert!(!matches_glob("src/*.js", "src/lib/a.js"));
+        assert!(matches_glob("src/**/?.js", "src/a.js"));
+    }
+
     #[test]
     fn test_source_map_builder() {
         let mut builder = SourceMapBuilder::new();
@@ -438,6 +624,94 @@ mod tests {
         assert_eq!(encode_vlq(-1), "D");
     }
 
+    #[test]
+    fn test_decode_mappings_reverses_encoding() {
+        let mut builder = SourceMapBuilder::new();
+        let source_index = builder.add_source("test.js");
+        let name_index = builder.add_name("answer");
+        let positions = [((0, 0), (0, 0), None), ((0, 5), (1, 2), Some(name_index)), ((2, 3), (0, 9), None)];
+        for ((line, column), (original_line, original_column), name_index) in positions {
+            builder.add_mapping(Mapping {
+                generated: Position { line, column },
+                original: Some(Position { line: original_line, column: original_column }),
+                source_index: Some(source_index),
+                name_index,
+            });
+        }
+        let map = builder.build();
+        // Line 1 has no segments but still ends with a `;`
+        assert_eq!(map.mappings.matches(';').count(), 2);
+
+        let decoded = decode_mappings(&map.mappings).unwrap();
+        let decoded: Vec<_> = decoded
+            .iter()
+            .map(|mapping| {
+                let original = mapping.original.unwrap();
+                ((mapping.generated.line, mapping.generated.column), (original.line, original.column), mapping.name_index)
+            })
+            .collect();
+        assert_eq!(decoded, positions);
+        assert!(decode_mappings("AA!A").is_err());
+        assert!(decode_mappings("g").is_err());
+    }
+
+    #[test]
+    fn test_vlq_round_trip() {
+        let values = [0, 1, -1, 15, -16, 16, 31, 32, -33, 1000, -123_456, i64::from(u32::MAX), -i64::from(u32::MAX)];
+        for value in values {
+            assert_eq!(decode_vlq(&encode_vlq(value)).unwrap(), vec![value], "{}", encode_vlq(value));
+        }
+        assert_eq!(encode_vlq(16), "gB");
+        assert_eq!(encode_vlq(-1000), "x+B");
+
+        let segment: String = values.iter().map(|&value| encode_vlq(value)).collect();
+        assert_eq!(decode_vlq(&segment).unwrap(), values);
+    }
+
+    #[test]
+    fn test_encoding_sorts_segments_and_carries_deltas_across_lines() {
+        let mut builder = SourceMapBuilder::new();
+        let first = builder.add_source("a.js");
+        let second = builder.add_source("b.js");
+        let name = builder.add_name("count");
+        let mapping = |line, column, original: Option<(u32, u32, u32)>, name_index| Mapping {
+            generated: Position { line, column },
+            original: original.map(|(_, line, column)| Position { line, column }),
+            source_index: original.map(|(source, _, _)| source),
+            name_index,
+        };
+        // Added out of order, on lines 0, 1 and 3
+        builder.add_mapping(mapping(0, 12, Some((first, 4, 2)), Some(name)));
+        builder.add_mapping(mapping(3, 7, Some((second, 0, 0)), None));
+        builder.add_mapping(mapping(0, 0, Some((first, 3, 0)), None));
+        builder.add_mapping(mapping(1, 4, None, None));
+        builder.add_mapping(mapping(3, 2, Some((first, 10, 8)), Some(name)));
+        let map = builder.build();
+
+        // Generated columns restart on each line; source, original position and name
+        // continue from the previous segment, wherever it was
+        assert_eq!(map.mappings, "AAGA,YACEA;I;;EAMMA,KCVR");
+
+        let decoded: Vec<_> = decode_mappings(&map.mappings)
+            .unwrap()
+            .into_iter()
+            .map(|mapping| {
+                let original = mapping.original.map(|position| (mapping.source_index.unwrap(), position.line, position.column));
+                ((mapping.generated.line, mapping.generated.column), original, mapping.name_index)
+            })
+            .collect();
+        assert_eq!(
+            decoded,
+            vec![
+                ((0, 0), Some((first, 3, 0)), None),
+                ((0, 12), Some((first, 4, 2)), Some(name)),
+                ((1, 4), None, None),
+                ((3, 2), Some((first, 10, 8)), Some(name)),
+                ((3, 7), Some((second, 0, 0)), None),
+            ]
+        );
+    }
+
     #[test]
     fn test_base64_digit_encoding() {
         assert_eq!(encode_base64_digit(0), 'A');
@@ -4,6 +4,7 @@
 //! Tests cover all AST node types, precedence handling, ASI hazards, string processing,
 //! and source map generation.
 
+use crate::generator::module_format::ModuleFormat;
 use crate::generator::{Generator, GeneratorConfig, OutputFormat, SemicolonStrategy, QuoteStrategy};
 use crate::parser::ast_types::*;
 
@@ -13,22 +14,22 @@ pub struct AstTestBuilder;
 impl AstTestBuilder {
     /// Create a simple identifier
     pub fn identifier(name: &str) -> Identifier {
-        Identifier { name: name.to_string() }
+        Identifier { name: name.to_string(), span: None }
     }
 
     /// Create a number literal
     pub fn number(value: f64) -> Expression {
-        Expression::Literal(Literal::Number(NumberLiteral { value }))
+        Expression::Literal(Literal::Number(NumberLiteral { value, raw: None, span: None }))
     }
 
     /// Create a string literal
     pub fn string(value: &str) -> Expression {
-        Expression::Literal(Literal::String(StringLiteral { value: value.to_string() }))
+        Expression::Literal(Literal::String(StringLiteral::new(value.to_string(), None)))
     }
 
     /// Create a boolean literal
     pub fn boolean(value: bool) -> Expression {
-        Expression::Literal(Literal::Boolean(BooleanLiteral { value }))
+        Expression::Literal(Literal::Boolean(BooleanLiteral { value, span: None }))
     }
 
     /// Create a null literal
@@ -42,8 +43,10 @@ impl AstTestBuilder {
             declarations: vec![VariableDeclarator {
                 id: Pattern::Identifier(Self::identifier(name)),
                 init,
+                span: None,
             }],
             kind,
+            span: None,
         }
     }
 
@@ -193,12 +196,14 @@ mod performance_tests {
         
         // Create deeply nested block statements
         let mut nested_stmt = Statement::ExpressionStatement {
-            expression: AstTestBuilder::number(1.0)
+            expression: AstTestBuilder::number(1.0),
+            span: None,
         };
         
         for _ in 0..10 {
             nested_stmt = Statement::BlockStatement {
-                body: vec![nested_stmt]
+                body: vec![nested_stmt],
+                span: None,
             };
         }
         
@@ -221,9 +226,9 @@ mod performance_tests {
         // Create template literal with multiple expressions
         let template_expr = Expression::TemplateLiteral {
             quasis: vec![
-                TemplateElement { value: "Hello ".to_string(), tail: false },
-                TemplateElement { value: ", you are ".to_string(), tail: false },
-                TemplateElement { value: " years old!".to_string(), tail: true },
+                TemplateElement { value: "Hello ".to_string(), raw: None, tail: false, span: None },
+                TemplateElement { value: ", you are ".to_string(), raw: None, tail: false, span: None },
+                TemplateElement { value: " years old!".to_string(), raw: None, tail: true, span: None },
             ],
             expressions: vec![
                 AstTestBuilder::id_expr("name"),
@@ -232,7 +237,7 @@ mod performance_tests {
         };
         
         let program = AstTestBuilder::program(vec![
-            Statement::ExpressionStatement { expression: template_expr }
+            Statement::ExpressionStatement { expression: template_expr, span: None }
         ]);
         
         let result = printer.print_program(&program).unwrap();
@@ -254,7 +259,8 @@ mod performance_tests {
         let strings: Vec<Statement> = (0..100)
             .map(|i| {
                 Statement::ExpressionStatement {
-                    expression: AstTestBuilder::string(&format!("String number {} with special chars \\n\\t", i))
+                    expression: AstTestBuilder::string(&format!("String number {} with special chars \\n\\t", i)),
+                    span: None,
                 }
             })
             .collect();
@@ -285,11 +291,12 @@ mod performance_tests {
         
         // Create nested block structure to test indent caching
         let mut nested_body = vec![Statement::ExpressionStatement {
-            expression: AstTestBuilder::number(1.0)
+            expression: AstTestBuilder::number(1.0),
+            span: None,
         }];
         
         for _ in 0..5 {
-            nested_body = vec![Statement::BlockStatement { body: nested_body }];
+            nested_body = vec![Statement::BlockStatement { body: nested_body, span: None }];
         }
         
         let program = AstTestBuilder::program(nested_body);
@@ -455,21 +462,21 @@ mod tests {
         
         // Integer
         let program = AstTestBuilder::program(vec![
-            Statement::ExpressionStatement { expression: AstTestBuilder::number(42.0) }
+            Statement::ExpressionStatement { expression: AstTestBuilder::number(42.0), span: None }
         ]);
         let result = generator.generate(&program, None).unwrap();
         assert_eq!(result.code, "42;");
 
         // Float
         let program = AstTestBuilder::program(vec![
-            Statement::ExpressionStatement { expression: AstTestBuilder::number(3.14) }
+            Statement::ExpressionStatement { expression: AstTestBuilder::number(3.14), span: None }
         ]);
         let result = generator.generate(&program, None).unwrap();
         assert_eq!(result.code, "3.14;");
 
         // Zero
         let program = AstTestBuilder::program(vec![
-            Statement::ExpressionStatement { expression: AstTestBuilder::number(0.0) }
+            Statement::ExpressionStatement { expression: AstTestBuilder::number(0.0), span: None }
         ]);
         let result = generator.generate(&program, None).unwrap();
         assert_eq!(result.code, "0;");
@@ -482,21 +489,21 @@ mod tests {
         
         // Simple string
         let program = AstTestBuilder::program(vec![
-            Statement::ExpressionStatement { expression: AstTestBuilder::string("hello") }
+            Statement::ExpressionStatement { expression: AstTestBuilder::string("hello"), span: None }
         ]);
         let result = generator.generate(&program, None).unwrap();
         assert_eq!(result.code, "'hello';");
 
         // String with single quotes (should use double quotes)
         let program = AstTestBuilder::program(vec![
-            Statement::ExpressionStatement { expression: AstTestBuilder::string("it's") }
+            Statement::ExpressionStatement { expression: AstTestBuilder::string("it's"), span: None }
         ]);
         let result = generator.generate(&program, None).unwrap();
         assert_eq!(result.code, "\"it's\";");
 
         // Empty string
         let program = AstTestBuilder::program(vec![
-            Statement::ExpressionStatement { expression: AstTestBuilder::string("") }
+            Statement::ExpressionStatement { expression: AstTestBuilder::string(""), span: None }
         ]);
         let result = generator.generate(&program, None).unwrap();
         assert_eq!(result.code, "'';");
@@ -509,14 +516,14 @@ mod tests {
         
         // True
         let program = AstTestBuilder::program(vec![
-            Statement::ExpressionStatement { expression: AstTestBuilder::boolean(true) }
+            Statement::ExpressionStatement { expression: AstTestBuilder::boolean(true), span: None }
         ]);
         let result = generator.generate(&program, None).unwrap();
         assert_eq!(result.code, "true;");
 
         // False
         let program = AstTestBuilder::program(vec![
-            Statement::ExpressionStatement { expression: AstTestBuilder::boolean(false) }
+            Statement::ExpressionStatement { expression: AstTestBuilder::boolean(false), span: None }
         ]);
         let result = generator.generate(&program, None).unwrap();
         assert_eq!(result.code, "false;");
@@ -528,7 +535,7 @@ mod tests {
         let generator = Generator::new(config);
         
         let program = AstTestBuilder::program(vec![
-            Statement::ExpressionStatement { expression: AstTestBuilder::null() }
+            Statement::ExpressionStatement { expression: AstTestBuilder::null(), span: None }
         ]);
         let result = generator.generate(&program, None).unwrap();
         assert_eq!(result.code, "null;");
@@ -547,7 +554,8 @@ mod tests {
                     AstTestBuilder::number(1.0),
                     BinaryOperator::Add,
                     AstTestBuilder::number(2.0)
-                )
+                ),
+                span: None,
             }
         ]);
         let result = generator.generate(&program, None).unwrap();
@@ -560,7 +568,8 @@ mod tests {
                     AstTestBuilder::number(5.0),
                     BinaryOperator::Subtract,
                     AstTestBuilder::number(3.0)
-                )
+                ),
+                span: None,
             }
         ]);
         let result = generator.generate(&program, None).unwrap();
@@ -573,7 +582,8 @@ mod tests {
                     AstTestBuilder::number(4.0),
                     BinaryOperator::Multiply,
                     AstTestBuilder::number(6.0)
-                )
+                ),
+                span: None,
             }
         ]);
         let result = generator.generate(&program, None).unwrap();
@@ -586,7 +596,8 @@ mod tests {
                     AstTestBuilder::number(10.0),
                     BinaryOperator::Divide,
                     AstTestBuilder::number(2.0)
-                )
+                ),
+                span: None,
             }
         ]);
         let result = generator.generate(&program, None).unwrap();
@@ -599,7 +610,8 @@ mod tests {
                     AstTestBuilder::number(7.0),
                     BinaryOperator::Remainder,
                     AstTestBuilder::number(3.0)
-                )
+                ),
+                span: None,
             }
         ]);
         let result = generator.generate(&program, None).unwrap();
@@ -618,7 +630,8 @@ mod tests {
                     AstTestBuilder::number(4.0),
                     BinaryOperator::Multiply,
                     AstTestBuilder::number(6.0)
-                )
+                ),
+                span: None,
             }
         ]);
         let result = generator.generate(&program, None).unwrap();
@@ -631,7 +644,8 @@ mod tests {
                     AstTestBuilder::number(8.0),
                     BinaryOperator::Divide,
                     AstTestBuilder::number(2.0)
-                )
+                ),
+                span: None,
             }
         ]);
         let result = generator.generate(&program, None).unwrap();
@@ -650,7 +664,8 @@ mod tests {
                     AstTestBuilder::id_expr("a"),
                     BinaryOperator::Equal,
                     AstTestBuilder::id_expr("b")
-                )
+                ),
+                span: None,
             }
         ]);
         let result = generator.generate(&program, None).unwrap();
@@ -663,7 +678,8 @@ mod tests {
                     AstTestBuilder::id_expr("x"),
                     BinaryOperator::StrictEqual,
                     AstTestBuilder::number(5.0)
-                )
+                ),
+                span: None,
             }
         ]);
         let result = generator.generate(&program, None).unwrap();
@@ -676,7 +692,8 @@ mod tests {
                     AstTestBuilder::id_expr("y"),
                     BinaryOperator::LessThan,
                     AstTestBuilder::number(10.0)
-                )
+                ),
+                span: None,
             }
         ]);
         let result = generator.generate(&program, None).unwrap();
@@ -695,7 +712,8 @@ mod tests {
                     AstTestBuilder::id_expr("a"),
                     BinaryOperator::LogicalAnd,
                     AstTestBuilder::id_expr("b")
-                )
+                ),
+                span: None,
             }
         ]);
         let result = generator.generate(&program, None).unwrap();
@@ -708,7 +726,8 @@ mod tests {
                     AstTestBuilder::id_expr("x"),
                     BinaryOperator::LogicalOr,
                     AstTestBuilder::id_expr("y")
-                )
+                ),
+                span: None,
             }
         ]);
         let result = generator.generate(&program, None).unwrap();
@@ -732,7 +751,8 @@ mod tests {
                         BinaryOperator::Multiply,
                         AstTestBuilder::id_expr("c")
                     )
-                )
+                ),
+                span: None,
             }
         ]);
         let result = generator.generate(&program, None).unwrap();
@@ -749,7 +769,8 @@ mod tests {
                     ),
                     BinaryOperator::Multiply,
                     AstTestBuilder::id_expr("c")
-                )
+                ),
+                span: None,
             }
         ]);
         let result = generator.generate(&program, None).unwrap();
@@ -770,7 +791,8 @@ mod tests {
                     AstTestBuilder::id_expr("x"),
                     BinaryOperator::Add,
                     AstTestBuilder::id_expr("y")
-                )
+                ),
+                span: None,
             }
         ]);
 
@@ -815,7 +837,7 @@ mod tests {
         let generator = Generator::new(config);
         
         let program = AstTestBuilder::program(vec![
-            Statement::ExpressionStatement { expression: AstTestBuilder::string("hello") }
+            Statement::ExpressionStatement { expression: AstTestBuilder::string("hello"), span: None }
         ]);
 
         let result = generator.generate(&program, None).unwrap();
@@ -829,7 +851,7 @@ mod tests {
         let generator = Generator::new(config);
         
         let program = AstTestBuilder::program(vec![
-            Statement::ExpressionStatement { expression: AstTestBuilder::string("hello") }
+            Statement::ExpressionStatement { expression: AstTestBuilder::string("hello"), span: None }
         ]);
 
         let result = generator.generate(&program, None).unwrap();
@@ -844,7 +866,7 @@ mod tests {
         let generator = Generator::new(config);
         
         let program = AstTestBuilder::program(vec![
-            Statement::ExpressionStatement { expression: AstTestBuilder::number(42.0) }
+            Statement::ExpressionStatement { expression: AstTestBuilder::number(42.0), span: None }
         ]);
 
         let result = generator.generate(&program, None).unwrap();
@@ -858,7 +880,7 @@ mod tests {
         let generator = Generator::new(config);
         
         let program = AstTestBuilder::program(vec![
-            Statement::ExpressionStatement { expression: AstTestBuilder::number(42.0) }
+            Statement::ExpressionStatement { expression: AstTestBuilder::number(42.0), span: None }
         ]);
 
         let result = generator.generate(&program, None).unwrap();
@@ -891,7 +913,7 @@ mod tests {
         let generator = Generator::new(config);
         
         let program = AstTestBuilder::program(vec![
-            Statement::ExpressionStatement { expression: Expression::ThisExpression }
+            Statement::ExpressionStatement { expression: Expression::ThisExpression, span: None }
         ]);
 
         let result = generator.generate(&program, None).unwrap();
@@ -910,11 +932,366 @@ mod tests {
             "file"
         );
         
-        assert!(matches!(config.ecma, crate::generator::EcmaScriptVersion::ES5));
+        assert!(!config.target.supports(crate::target::Feature::BlockScoping));
         assert!(matches!(config.format, OutputFormat::Pretty));
         assert!(matches!(config.semicolon, SemicolonStrategy::Always));
         assert!(matches!(config.quote, QuoteStrategy::Single));
     }
+
+    /// Test pretty output honours the indent style and wraps at `max_line_len`
+    #[test]
+    fn test_indent_style_and_line_wrapping() {
+        use crate::generator::IndentStyle;
+        use crate::parser::{parse_js_in, Allocator, ParserConfig};
+
+        let source = "function f(alpha, beta) { return call(alpha + beta + alpha, beta); }";
+        let allocator = Allocator::default();
+        let parsed = parse_js_in(source, "test.js", &allocator, &ParserConfig::default());
+        let program = Program::from_oxc(&parsed.program);
+
+        let print = |indent, max_line_len| {
+            let config = GeneratorConfig {
+                format: OutputFormat::Pretty,
+                indent,
+                max_line_len,
+                ..GeneratorConfig::default()
+            };
+            Generator::new(config).generate(&program, None).unwrap().code
+        };
+
+        assert_eq!(print(IndentStyle::Spaces(4), None), "function f(alpha, beta) {\n    return call(alpha + beta + alpha, beta);\n}\n");
+        assert_eq!(
+            print(IndentStyle::Tabs, Some(24)),
+            "function f(alpha, beta) {\n\treturn call(alpha +\n\t\tbeta + alpha, beta);\n}\n"
+        );
+        assert_eq!("tab".parse(), Ok(IndentStyle::Tabs));
+        assert_eq!("3".parse(), Ok(IndentStyle::Spaces(3)));
+        assert!("wide".parse::<IndentStyle>().is_err());
+    }
+
+    /// Test JSDoc blocks are re-emitted before exported declarations
+    #[test]
+    fn test_jsdoc_preserved_on_exports() {
+        use crate::generator::CommentPreservation;
+        use crate::parser::{parse_js_in, Allocator, ParserConfig};
+
+        let source = "/**\n   * Adds.\n   */\nexport function add(a, b) { return a + b; }\n\
+                      /** Internal. */\nfunction helper() {}\n\
+                      // Not JSDoc\nexport class Point {}\n";
+        let allocator = Allocator::default();
+        let parsed = parse_js_in(source, "lib.mjs", &allocator, &ParserConfig::default());
+        let doc_comments = parsed.doc_comments();
+        assert_eq!(doc_comments.len(), 1);
+        let program = Program::from_oxc(&parsed.program);
+
+        let config = GeneratorConfig {
+            format: OutputFormat::Readable,
+            preserve_comments: CommentPreservation::JsDoc,
+            ..GeneratorConfig::default()
+        };
+        let generator = Generator::new(config).with_doc_comments(doc_comments.clone());
+        let result = generator.generate(&program, None).unwrap();
+        assert!(result.code.starts_with("/**\n * Adds.\n */\nexport function add(a, b){"), "{}", result.code);
+        assert!(result.code.contains("\nexport class Point{}"));
+        assert!(!result.code.contains("Internal"));
+
+        // Without JsDoc preservation the blocks are dropped
+        let generator = Generator::new(GeneratorConfig::default()).with_doc_comments(doc_comments);
+        assert!(!generator.generate(&program, None).unwrap().code.contains("/**"));
+    }
+
+    /// Test attached comments are re-emitted around their statements
+    #[test]
+    fn test_all_comments_preserved_near_statements() {
+        use crate::generator::CommentPreservation;
+        use crate::parser::{parse_js_in, Allocator, ParserConfig};
+
+        let source = "// Counter\nlet count = 0; // starts at zero\nfunction bump() {\n  /* add one */\n  count += 1;\n}\n";
+        let allocator = Allocator::default();
+        let parsed = parse_js_in(source, "test.js", &allocator, &ParserConfig::default());
+        let comments = parsed.attached_comments();
+        let program = Program::from_oxc(&parsed.program);
+
+        let generate = |format| {
+            let config = GeneratorConfig { format, preserve_comments: CommentPreservation::All, ..GeneratorConfig::default() };
+            Generator::new(config).with_comments(comments.clone()).generate(&program, None).unwrap().code
+        };
+        assert_eq!(
+            generate(OutputFormat::Compact),
+            "// Counter\nlet count=0;// starts at zero\nfunction bump(){/* add one */count+=1;}"
+        );
+        assert_eq!(
+            generate(OutputFormat::Pretty),
+            "// Counter\nlet count = 0; // starts at zero\nfunction bump() {\n  /* add one */\n  count += 1;\n}\n"
+        );
+
+        // Other preservation levels ignore attached comments
+        let generator = Generator::new(GeneratorConfig::default()).with_comments(comments);
+        assert_eq!(generator.generate(&program, None).unwrap().code, "let count=0;function bump(){count+=1;}");
+    }
+
+    /// Test import declarations and star re-exports
+    #[test]
+    fn test_import_and_export_all_declarations() {
+        use crate::parser::{parse_js, ParserConfig};
+
+        let source = "import def, * as ns from './a';\nimport { x as y, z } from './b';\nimport './side';\n\
+                      export * from './c';\nexport * as all from './d';\n";
+        let program = parse_js(source, "lib.mjs", &ParserConfig::default()).ast.unwrap();
+        let result = Generator::new(GeneratorConfig::default()).generate(&program, None).unwrap();
+        assert_eq!(
+            result.code,
+            "import def,*as ns from'./a';import{x as y,z}from'./b';import'./side';export*from'./c';export*as all from'./d';"
+        );
+    }
+
+    /// Test default exports of declarations, named or not, and of expressions
+    #[test]
+    fn test_export_default_declarations() {
+        use crate::parser::{parse_js, ParserConfig};
+
+        for (source, expected) in [
+            ("export default function () { return 1; }", "export default function(){return 1;}"),
+            ("export default async function load() {}", "export default async function load(){}"),
+            ("export default class extends Base {}", "export default class extends Base{}"),
+            ("const helper = 1;\nexport default helper;", "const helper=1;export default helper;"),
+            ("export default { a: 1 };", "export default{a:1};"),
+            ("export default (function () {})();", "export default(function(){}());"),
+            ("export default x = 2;", "export default x=2;"),
+        ] {
+            let program = parse_js(source, "lib.mjs", &ParserConfig::default()).ast.unwrap();
+            let result = Generator::new(GeneratorConfig::default()).generate(&program, None).unwrap();
+            assert_eq!(result.code, expected, "{}", source);
+        }
+    }
+
+    /// Test that decorators, import attributes and `using` declarations are printed as written
+    #[test]
+    fn test_stage3_proposals_pass_through() {
+        use crate::parser::{parse_js, ParserConfig};
+
+        let source = "import data from './a.json' with { type: 'json' };\n\
+                      export * from './b.css' with { 'content-type': 'css' };\n\
+                      @sealed @(cond ? a : b) @ns.register('w') class W { @observable count = 0; @bound static click() {} }\n\
+                      async function f() { using r = open(); await using c = connect(); for (using x of xs) x.run(); }\n";
+        let config = ParserConfig::default()
+            .with_decorators(true)
+            .with_import_attributes(true)
+            .with_explicit_resource_management(true);
+        let program = parse_js(source, "lib.mjs", &config).ast.unwrap();
+        let result = Generator::new(GeneratorConfig::default()).generate(&program, None).unwrap();
+        assert_eq!(
+            result.code,
+            "import data from'./a.json'with{type:'json'};export*from'./b.css'with{'content-type':'css'};\
+             @sealed @(cond?a:b) @ns.register('w') class W{@observable count=0;@bound static click(){}}\
+             async function f(){using r=open();await using c=connect();for(using x of xs)x.run();}"
+        );
+    }
+
+    /// Flow annotations, type-only imports and exports and `declare` declarations are
+    /// removed, and type assertions keep only their value
+    #[test]
+    fn test_flow_annotations_are_stripped() {
+        use crate::parser::{parse_js, ParserConfig};
+
+        let source = "import type { Point } from './types';\n\
+                      import { type Size, scale } from './geo';\n\
+                      import { type Unit } from './units';\n\
+                      type Box = { p: Point, s: ?Size };\n\
+                      declare var VERSION: string;\n\
+                      function area<T>(b: Box, k?: number): ?number { return scale((b.s as any).w, k!); }\n\
+                      class Shape { declare kind: string; size: number = 0; }\n\
+                      export type { Box };\n";
+        assert!(!parse_js(source, "lib.js", &ParserConfig::default()).errors.is_empty());
+
+        let program = parse_js(source, "lib.js", &ParserConfig::default().with_flow(true)).ast.unwrap();
+        let result = Generator::new(GeneratorConfig::default()).generate(&program, None).unwrap();
+        assert_eq!(
+            result.code,
+            "import{scale}from'./geo';function area(b, k){return scale(b.s.w,k);}class Shape{size=0;}"
+        );
+    }
+
+    /// Decorators of an exported class are printed before `export`, and only for
+    /// targets that run them
+    #[test]
+    fn test_decorators_precede_export_and_follow_the_target() {
+        use crate::parser::{parse_js, ParserConfig};
+        use crate::generator::GeneratorError;
+        use crate::target::Targets;
+
+        let source = "export @sealed class A { @bound run() {} }\n@frozen export class B {}\n";
+        let program = parse_js(source, "lib.mjs", &ParserConfig::default().with_decorators(true)).ast.unwrap();
+        let result = Generator::new(GeneratorConfig::default()).generate(&program, None).unwrap();
+        assert_eq!(result.code, "@sealed export class A{@bound run(){}}@frozen export class B{}");
+
+        let config = GeneratorConfig { target: Targets::parse("es2022").unwrap(), ..Default::default() };
+        assert!(matches!(
+            Generator::new(config).generate(&program, None),
+            Err(GeneratorError::UnsupportedNode { node_type, .. }) if node_type == "decorators"
+        ));
+    }
+
+    /// Dynamic imports are kept, with their import attributes
+    #[test]
+    fn test_dynamic_imports_round_trip() {
+        use crate::parser::{parse_js, ParserConfig};
+
+        let source = "const m = await import('./m.js');\nload(() => import(name), import('./d.json', { with: { type: 'json' } }));\n";
+        let program = parse_js(source, "app.mjs", &ParserConfig::default()).ast.unwrap();
+        let result = Generator::new(GeneratorConfig::default()).generate(&program, None).unwrap();
+        assert_eq!(result.code, "const m=await import('./m.js');load(()=>import(name),import('./d.json',{with:{type:'json'}}));");
+    }
+
+    /// `new` keeps its argument list, and a callee with a call in it is wrapped
+    #[test]
+    fn test_new_expressions_and_meta_properties() {
+        use crate::parser::{parse_js, ParserConfig};
+
+        let source = "const a = new Map, b = new a.b.C(1, 2), c = new (f())(), d = new (g().h)(), e = new new X()();\n\
+                      function F() { if (!new.target) return new F(); }\nlog(import.meta.url, new Date().getTime());\n";
+        let program = parse_js(source, "app.mjs", &ParserConfig::default()).ast.unwrap();
+        let result = Generator::new(GeneratorConfig::default()).generate(&program, None).unwrap();
+        assert_eq!(
+            result.code,
+            "const a=new Map(), b=new a.b.C(1,2), c=new(f())(), d=new(g().h)(), e=new(new X())();function F(){if(!new.target)return new F();}log(import.meta.url,new Date().getTime());"
+        );
+    }
+
+    /// Test generators, yield and for-of loops
+    #[test]
+    fn test_generators_and_for_of() {
+        use crate::parser::{parse_js, ParserConfig};
+
+        let source = "function* ids() { let x = yield; yield* other(); f(yield x, yield); }\n\
+                      async function* lines(stream) { for await (const chunk of stream) { yield chunk; } }\n\
+                      for (item of ids()) { use(item); }\n";
+        let program = parse_js(source, "gen.js", &ParserConfig::default()).ast.unwrap();
+        let result = Generator::new(GeneratorConfig::default()).generate(&program, None).unwrap();
+        assert_eq!(
+            result.code,
+            "function* ids(){let x=yield;yield*other();f(yield x,yield);}async function* lines(stream){for await(const chunk of stream){yield chunk;}}for(item of ids()){use(item);}"
+        );
+    }
+
+    /// Test `for` and `while` loops, including declarations in the loop head
+    #[test]
+    fn test_for_and_while_loops() {
+        use crate::parser::{parse_js, ParserConfig};
+
+        let source = "for (let i = 0, n = 3; i < n; i++) f(i);\nfor (;;) { break_out(); }\nwhile (x) x = g(x);\n";
+        let program = parse_js(source, "loops.js", &ParserConfig::default()).ast.unwrap();
+        let result = Generator::new(GeneratorConfig::default()).generate(&program, None).unwrap();
+        assert_eq!(result.code, "for(let i=0,n=3;i<n;i++)f(i);for(;;){break_out();}while(x)x=g(x);");
+    }
+
+    /// Test prefix and postfix updates, which must not fuse with neighbouring operators
+    #[test]
+    fn test_update_expressions() {
+        use crate::parser::{parse_js, ParserConfig};
+
+        let source = "i++; --o.n; a[i]--; x = a + ++b; y = a++ + b; z = -(--a); w = (a++).toString();\n";
+        let program = parse_js(source, "update.js", &ParserConfig::default()).ast.unwrap();
+        let result = Generator::new(GeneratorConfig::default()).generate(&program, None).unwrap();
+        assert_eq!(result.code, "i++;--o.n;a[i]--;x=a+ ++b;y=a+++b;z=- --a;w=(a++).toString();");
+    }
+
+    /// Test await expressions, which only modules allow at top level
+    #[test]
+    fn test_top_level_await() {
+        use crate::parser::{parse_js, ParserConfig};
+        use crate::generator::GeneratorError;
+        use crate::target::Targets;
+
+        let source = "const data = await load();\nawait (a + b);\nasync function f() { return -await g(); }\n";
+        let mut program = parse_js(source, "main.mjs", &ParserConfig::default()).ast.unwrap();
+        let result = Generator::new(GeneratorConfig::default()).generate(&program, None).unwrap();
+        assert_eq!(result.code, "const data=await load();await (a+b);async function f(){return -await g();}");
+
+        let config = GeneratorConfig { target: Targets::parse("chrome90,firefox88").unwrap(), ..Default::default() };
+        assert!(matches!(
+            Generator::new(config).generate(&program, None),
+            Err(GeneratorError::UnsupportedNode { .. })
+        ));
+
+        // A module converted to CommonJS becomes a script, but the module was fine
+        let config = GeneratorConfig { module_format: ModuleFormat::Cjs, ..Default::default() };
+        let error = Generator::new(config).generate(&program, None).unwrap_err();
+        assert!(matches!(error, GeneratorError::TopLevelAwaitInFormat { format: ModuleFormat::Cjs }));
+        assert!(error.to_string().starts_with("Top-level await cannot be emitted as CommonJS output"), "{}", error);
+
+        program.source_type = ProgramSourceType::Script;
+        assert!(matches!(
+            Generator::new(GeneratorConfig::default()).generate(&program, None),
+            Err(GeneratorError::TopLevelAwaitInScript)
+        ));
+    }
+
+    /// Test spread elements, rest parameters and destructuring patterns
+    #[test]
+    fn test_spread_rest_and_destructuring() {
+        use crate::parser::{parse_js, ParserConfig};
+        use crate::generator::GeneratorError;
+        use crate::target::Targets;
+
+        let source = "function f(a, { b, c: d = 1, ...e }, [g, , ...h], ...i) { return g(...i, [...h, , ], { ...e, b, m() {} }); }\n\
+                      const k = (p, ...q) => ({ p, q });\n\
+                      (function () {})();\n";
+        let program = parse_js(source, "spread.js", &ParserConfig::default()).ast.unwrap();
+        let result = Generator::new(GeneratorConfig::default()).generate(&program, None).unwrap();
+        assert_eq!(
+            result.code,
+            "function f(a, {b,c:d=1,...e}, [g,,...h], ...i){return g(...i,[...h,,],{...e,b,m(){}});}const k=(p, ...q)=>({p,q});(function(){}());"
+        );
+
+        // Object rest and spread are ES2018, array spread and rest parameters ES2015
+        let program = parse_js("const { a, ...b } = o;\n", "spread.js", &ParserConfig::default()).ast.unwrap();
+        let config = GeneratorConfig { target: Targets::parse("es2017").unwrap(), ..Default::default() };
+        assert!(matches!(Generator::new(config.clone()).generate(&program, None), Err(GeneratorError::UnsupportedNode { .. })));
+        let program = parse_js("f(...[1, 2]);\n", "spread.js", &ParserConfig::default()).ast.unwrap();
+        assert_eq!(Generator::new(config).generate(&program, None).unwrap().code, "f(...[1,2]);");
+    }
+
+    /// Test that template quasis are printed as written, which tags can observe
+    #[test]
+    fn test_tagged_templates_print_raw_text() {
+        use crate::parser::{parse_js, ParserConfig};
+
+        let source = "String.raw`x\\ny\\unicode${1}`;\nconst s = `a\\tb`;\n(a?.b)`c`;\n";
+        let program = parse_js(source, "tagged.js", &ParserConfig::default()).ast.unwrap();
+        let result = Generator::new(GeneratorConfig::default()).generate(&program, None).unwrap();
+        assert_eq!(result.code, "String.raw`x\\ny\\unicode${1}`;const s=`a\\tb`;(a?.b)`c`;");
+
+        // Elements built by a pass have no raw text and are escaped from their value
+        let program = AstTestBuilder::program(vec![Statement::ExpressionStatement {
+            expression: Expression::TemplateLiteral {
+                quasis: vec![TemplateElement { value: "a\r`".to_string(), raw: None, tail: true, span: None }],
+                expressions: vec![],
+            },
+            span: None,
+        }]);
+        let result = Generator::new(GeneratorConfig::default()).generate(&program, None).unwrap();
+        assert_eq!(result.code, "`a\\r\\``;");
+    }
+
+    /// Test optional chains, nullish coalescing and the precedence around them
+    #[test]
+    fn test_optional_chaining_and_nullish_coalescing() {
+        use crate::parser::{parse_js, ParserConfig};
+        use crate::generator::GeneratorError;
+        use crate::target::Targets;
+
+        let source = "a?.b[c]?.(d);\n(a?.b).c;\nx = a ?? (b || c);\n(a ?? b) || c;\nx ??= y ? 1 : 2;\n";
+        let program = parse_js(source, "chain.js", &ParserConfig::default()).ast.unwrap();
+        let result = Generator::new(GeneratorConfig::default()).generate(&program, None).unwrap();
+        assert_eq!(result.code, "a?.b[c]?.(d);(a?.b).c;x=a??(b||c);(a??b)||c;x??=y?1:2;");
+
+        let config = GeneratorConfig { target: Targets::parse("es2019").unwrap(), ..Default::default() };
+        assert!(matches!(
+            Generator::new(config).generate(&program, None),
+            Err(GeneratorError::UnsupportedNode { .. })
+        ));
+    }
 }
 
 /// Comprehensive ASI (Automatic Semicolon Insertion) hazard tests
@@ -934,7 +1311,8 @@ mod asi_hazard_tests {
         // Return with argument - should have space to prevent ASI
         let program = AstTestBuilder::program(vec![
             Statement::ReturnStatement {
-                argument: Some(AstTestBuilder::id_expr("x"))
+                argument: Some(AstTestBuilder::id_expr("x")),
+                span: None,
             }
         ]);
         
@@ -943,7 +1321,7 @@ mod asi_hazard_tests {
         
         // Return without argument - should be safe
         let program = AstTestBuilder::program(vec![
-            Statement::ReturnStatement { argument: None }
+            Statement::ReturnStatement { argument: None, span: None }
         ]);
         
         let result = generator.generate(&program, None).unwrap();
@@ -959,12 +1337,14 @@ mod asi_hazard_tests {
         
         // Multiple statements that could be misinterpreted
         let program = AstTestBuilder::program(vec![
-            Statement::ExpressionStatement { expression: AstTestBuilder::id_expr("a") },
+            Statement::ExpressionStatement { expression: AstTestBuilder::id_expr("a"), span: None },
             Statement::ExpressionStatement { 
                 expression: Expression::CallExpression {
                     callee: Box::new(AstTestBuilder::id_expr("b")),
-                    arguments: vec![]
-                }
+                    arguments: vec![],
+                    optional: false,
+                },
+                span: None,
             }
         ]);
         
@@ -982,7 +1362,7 @@ mod asi_hazard_tests {
         let generator = Generator::new(config);
         
         let program = AstTestBuilder::program(vec![
-            Statement::ExpressionStatement { expression: AstTestBuilder::number(42.0) }
+            Statement::ExpressionStatement { expression: AstTestBuilder::number(42.0), span: None }
         ]);
         
         let result = generator.generate(&program, None).unwrap();
@@ -1007,7 +1387,7 @@ mod asi_hazard_tests {
         
         let program = AstTestBuilder::program(vec![
             AstTestBuilder::var_declaration("x", Some(AstTestBuilder::number(1.0)), VariableDeclarationKind::Let),
-            Statement::ExpressionStatement { expression: AstTestBuilder::id_expr("x") }
+            Statement::ExpressionStatement { expression: AstTestBuilder::id_expr("x"), span: None }
         ]);
         
         let result = generator.generate(&program, None).unwrap();
@@ -1033,7 +1413,7 @@ mod asi_hazard_tests {
         
         // Test with this expression
         let program = AstTestBuilder::program(vec![
-            Statement::ExpressionStatement { expression: Expression::ThisExpression }
+            Statement::ExpressionStatement { expression: Expression::ThisExpression, span: None }
         ]);
         
         let result = generator.generate(&program, None).unwrap();
@@ -1041,8 +1421,8 @@ mod asi_hazard_tests {
         
         // Test with boolean literals
         let program = AstTestBuilder::program(vec![
-            Statement::ExpressionStatement { expression: AstTestBuilder::boolean(true) },
-            Statement::ExpressionStatement { expression: AstTestBuilder::boolean(false) }
+            Statement::ExpressionStatement { expression: AstTestBuilder::boolean(true), span: None },
+            Statement::ExpressionStatement { expression: AstTestBuilder::boolean(false), span: None }
         ]);
         
         let result = generator.generate(&program, None).unwrap();
@@ -1075,7 +1455,8 @@ mod precedence_tests {
                         BinaryOperator::Multiply,
                         AstTestBuilder::id_expr("c")
                     )
-                )
+                ),
+                span: None,
             }
         ]);
         
@@ -1093,7 +1474,8 @@ mod precedence_tests {
                     ),
                     BinaryOperator::Multiply,
                     AstTestBuilder::id_expr("c")
-                )
+                ),
+                span: None,
             }
         ]);
         
@@ -1123,7 +1505,8 @@ mod precedence_tests {
                         BinaryOperator::Add,
                         AstTestBuilder::id_expr("d")
                     )
-                )
+                ),
+                span: None,
             }
         ]);
         
@@ -1153,7 +1536,8 @@ mod precedence_tests {
                         BinaryOperator::LogicalAnd,
                         AstTestBuilder::id_expr("d")
                     )
-                )
+                ),
+                span: None,
             }
         ]);
         
@@ -1182,7 +1566,8 @@ mod precedence_tests {
                     ),
                     BinaryOperator::StrictEqual,
                     AstTestBuilder::id_expr("d")
-                )
+                ),
+                span: None,
             }
         ]);
         
@@ -1208,7 +1593,8 @@ mod precedence_tests {
                     ),
                     BinaryOperator::LogicalAnd,
                     AstTestBuilder::id_expr("c")
-                )
+                ),
+                span: None,
             }
         ]);
         
@@ -1237,13 +1623,81 @@ mod precedence_tests {
                     ),
                     BinaryOperator::Subtract,
                     AstTestBuilder::id_expr("d")
-                )
+                ),
+                span: None,
             }
         ]);
         
         let result = generator.generate(&program, None).unwrap();
         assert_eq!(result.code, "(a+b)*c-d;");
     }
+
+    /// Test that associativity keeps parentheses on the non-associating side
+    #[test]
+    fn test_associativity_parentheses() {
+        let config = GeneratorConfig::default();
+        let generator = Generator::new(config);
+
+        // a - (b - c) must keep its parentheses, (a - b) - c must not
+        let program = AstTestBuilder::program(vec![
+            Statement::ExpressionStatement {
+                expression: AstTestBuilder::binary_expr(
+                    AstTestBuilder::id_expr("a"),
+                    BinaryOperator::Subtract,
+                    AstTestBuilder::binary_expr(
+                        AstTestBuilder::id_expr("b"),
+                        BinaryOperator::Subtract,
+                        AstTestBuilder::id_expr("c")
+                    )
+                ),
+                span: None,
+            },
+            Statement::ExpressionStatement {
+                expression: AstTestBuilder::binary_expr(
+                    AstTestBuilder::binary_expr(
+                        AstTestBuilder::id_expr("a"),
+                        BinaryOperator::Subtract,
+                        AstTestBuilder::id_expr("b")
+                    ),
+                    BinaryOperator::Subtract,
+                    AstTestBuilder::id_expr("c")
+                ),
+                span: None,
+            }
+        ]);
+        let result = generator.generate(&program, None).unwrap();
+        assert_eq!(result.code, "a-(b-c);a-b-c;");
+
+        // Exponentiation is right-associative: (a ** b) ** c needs parentheses
+        let program = AstTestBuilder::program(vec![
+            Statement::ExpressionStatement {
+                expression: AstTestBuilder::binary_expr(
+                    AstTestBuilder::binary_expr(
+                        AstTestBuilder::id_expr("a"),
+                        BinaryOperator::Exponentiation,
+                        AstTestBuilder::id_expr("b")
+                    ),
+                    BinaryOperator::Exponentiation,
+                    AstTestBuilder::id_expr("c")
+                ),
+                span: None,
+            },
+            Statement::ExpressionStatement {
+                expression: AstTestBuilder::binary_expr(
+                    AstTestBuilder::id_expr("a"),
+                    BinaryOperator::Exponentiation,
+                    AstTestBuilder::binary_expr(
+                        AstTestBuilder::id_expr("b"),
+                        BinaryOperator::Exponentiation,
+                        AstTestBuilder::id_expr("c")
+                    )
+                ),
+                span: None,
+            }
+        ]);
+        let result = generator.generate(&program, None).unwrap();
+        assert_eq!(result.code, "(a**b)**c;a**b**c;");
+    }
 }
 
 /// Comprehensive string and template literal tests
@@ -1262,10 +1716,57 @@ mod string_tests {
     fn template_element(value: &str, tail: bool) -> TemplateElement {
         TemplateElement {
             value: value.to_string(),
+            raw: None,
             tail,
+            span: None,
         }
     }
 
+    /// Test number literals print in their shortest form, or as written when preserved
+    #[test]
+    fn test_number_spellings() {
+        use crate::generator::NumberStyle;
+        use crate::parser::{parse_js, ParserConfig};
+        use crate::target::Targets;
+
+        let print = |source: &str, numbers, target: &str| {
+            let program = parse_js(source, "test.js", &ParserConfig::default()).ast.unwrap();
+            let config = GeneratorConfig {
+                numbers,
+                target: Targets::parse(target).unwrap(),
+                ..GeneratorConfig::default()
+            };
+            Generator::new(config).generate(&program, None).unwrap().code
+        };
+
+        let source = "f(1000000, 0.5, 0x1F, 0b1010, 0o17, 1_000_000, 0.000001, 1099511627775, 1e21, 3.14);";
+        assert_eq!(
+            print(source, NumberStyle::Shortest, "latest"),
+            "f(1e6,.5,31,10,15,1e6,1e-6,0xffffffffff,1e21,3.14);"
+        );
+        assert_eq!(
+            print(source, NumberStyle::Preserve, "latest"),
+            "f(1000000,0.5,0x1F,0b1010,0o17,1_000_000,0.000001,1099511627775,1e21,3.14);"
+        );
+        // Separators are dropped for targets without them
+        assert_eq!(print("f(1_000);", NumberStyle::Preserve, "es2015"), "f(1000);");
+
+        // Exponents with an integer mantissa are often shorter than either form
+        let source = "f(123400000, 123456789012345680000, 0.00000015, 0.00012, 1.5e300, 12e-20, 120);";
+        assert_eq!(
+            print(source, NumberStyle::Shortest, "latest"),
+            "f(1234e5,12345678901234568e4,15e-8,12e-5,15e299,12e-20,120);"
+        );
+
+        // A raw spelling that no longer matches the value is ignored
+        let program = AstTestBuilder::program(vec![Statement::ExpressionStatement {
+            expression: Expression::Literal(Literal::Number(NumberLiteral { value: 2.0, raw: Some("0x1".to_string()), span: None })),
+            span: None,
+        }]);
+        let config = GeneratorConfig { numbers: NumberStyle::Preserve, ..GeneratorConfig::default() };
+        assert_eq!(Generator::new(config).generate(&program, None).unwrap().code, "2;");
+    }
+
     /// Test quote selection for string literals
     /// Should choose the quote type that minimizes escaping
     #[test]
@@ -1276,7 +1777,8 @@ mod string_tests {
         // String with single quotes - should use double quotes
         let program = AstTestBuilder::program(vec![
             Statement::ExpressionStatement { 
-                expression: AstTestBuilder::string("don't")
+                expression: AstTestBuilder::string("don't"),
+                span: None,
             }
         ]);
         let result = generator.generate(&program, None).unwrap();
@@ -1285,7 +1787,8 @@ mod string_tests {
         // String with double quotes - should use single quotes
         let program = AstTestBuilder::program(vec![
             Statement::ExpressionStatement { 
-                expression: AstTestBuilder::string("He said \"hello\"")
+                expression: AstTestBuilder::string("He said \"hello\""),
+                span: None,
             }
         ]);
         let result = generator.generate(&program, None).unwrap();
@@ -1294,7 +1797,8 @@ mod string_tests {
         // String with both quotes - should choose better option
         let program = AstTestBuilder::program(vec![
             Statement::ExpressionStatement { 
-                expression: AstTestBuilder::string("It's a \"test\"")
+                expression: AstTestBuilder::string("It's a \"test\""),
+                span: None,
             }
         ]);
         let result = generator.generate(&program, None).unwrap();
@@ -1302,6 +1806,64 @@ mod string_tests {
         assert_eq!(result.code, "'It\\'s a \"test\"';");
     }
 
+    /// Test a matrix of characters that need escaping, checking each printed literal
+    /// parses back to the original value
+    #[test]
+    fn test_tricky_character_escapes() {
+        use crate::parser::{parse_js, ParserConfig};
+
+        let cases = [
+            ("a\0b", "'a\\0b'"),
+            ("\u{0}1", "'\\x001'"),
+            ("\u{0}", "'\\0'"),
+            ("line\u{2028}para\u{2029}", "'line\\u2028para\\u2029'"),
+            ("\u{8}\u{b}\u{c}", "'\\b\\v\\f'"),
+            ("\u{1}\u{1b}\u{7f}", "'\\x01\\x1b\\x7f'"),
+            ("tab\there\r\n", "'tab\\there\\r\\n'"),
+            ("back\\slash", "'back\\\\slash'"),
+            ("it's", "\"it's\""),
+            ("caf\u{e9} \u{1f600}", "'caf\u{e9} \u{1f600}'"),
+        ];
+
+        let generator = Generator::new(GeneratorConfig::default());
+        for (value, expected) in cases {
+            let program = AstTestBuilder::program(vec![
+                Statement::ExpressionStatement { expression: AstTestBuilder::string(value), span: None }
+            ]);
+            let code = generator.generate(&program, None).unwrap().code;
+            assert_eq!(code, format!("{};", expected), "escaping {:?}", value);
+
+            // A bare string statement would parse as a directive, so pass it to a call
+            let call = format!("f({});", expected);
+            let reparsed = parse_js(&call, "test.js", &ParserConfig::default()).ast.unwrap();
+            match &reparsed.body[..] {
+                [Statement::ExpressionStatement { expression: Expression::CallExpression { arguments, .. }, .. }] => {
+                    let [Expression::Literal(Literal::String(literal))] = &arguments[..] else {
+                        panic!("unexpected arguments for {}: {:?}", call, arguments);
+                    };
+                    assert_eq!(literal.value, value, "round trip of {}", code);
+                }
+                other => panic!("unexpected AST for {}: {:?}", call, other),
+            }
+        }
+
+        // Lone surrogates can't be held by a Rust string, so they come from parsed source
+        let cases = [
+            ("f('c\\ud800d');", "f('c\\ud800d');"),
+            ("f('\\uDC00\\\\ud800');", "f('\\udc00\\\\ud800');"),
+            ("f('\\u{d83d}x', '\\ud800\\u0041');", "f('\\ud83dx','\\ud800A');"),
+            ("f('\\ud83d\\ude00');", "f('\u{1f600}');"),
+            ("f('a\\\\ud800');", "f('a\\\\ud800');"),
+        ];
+        for (source, expected) in cases {
+            let program = parse_js(source, "test.js", &ParserConfig::default()).ast.unwrap();
+            let code = generator.generate(&program, None).unwrap().code;
+            assert_eq!(code, expected, "escaping {}", source);
+            let reparsed = parse_js(&code, "test.js", &ParserConfig::default()).ast.unwrap();
+            assert_eq!(generator.generate(&reparsed, None).unwrap().code, code, "round trip of {}", source);
+        }
+    }
+
     /// Test escape sequence handling
     #[test]
     fn test_escape_sequences() {
@@ -1311,7 +1873,8 @@ mod string_tests {
         // Newline characters
         let program = AstTestBuilder::program(vec![
             Statement::ExpressionStatement { 
-                expression: AstTestBuilder::string("line1\nline2")
+                expression: AstTestBuilder::string("line1\nline2"),
+                span: None,
             }
         ]);
         let result = generator.generate(&program, None).unwrap();
@@ -1320,7 +1883,8 @@ mod string_tests {
         // Tab characters
         let program = AstTestBuilder::program(vec![
             Statement::ExpressionStatement { 
-                expression: AstTestBuilder::string("col1\tcol2")
+                expression: AstTestBuilder::string("col1\tcol2"),
+                span: None,
             }
         ]);
         let result = generator.generate(&program, None).unwrap();
@@ -1329,7 +1893,8 @@ mod string_tests {
         // Backslash
         let program = AstTestBuilder::program(vec![
             Statement::ExpressionStatement { 
-                expression: AstTestBuilder::string("path\\to\\file")
+                expression: AstTestBuilder::string("path\\to\\file"),
+                span: None,
             }
         ]);
         let result = generator.generate(&program, None).unwrap();
@@ -1345,7 +1910,8 @@ mod string_tests {
         // Unicode characters (should be preserved as-is in most cases)
         let program = AstTestBuilder::program(vec![
             Statement::ExpressionStatement { 
-                expression: AstTestBuilder::string("Hello 世界")
+                expression: AstTestBuilder::string("Hello 世界"),
+                span: None,
             }
         ]);
         let result = generator.generate(&program, None).unwrap();
@@ -1354,7 +1920,8 @@ mod string_tests {
         // Emoji characters
         let program = AstTestBuilder::program(vec![
             Statement::ExpressionStatement { 
-                expression: AstTestBuilder::string("Test 🚀 emoji")
+                expression: AstTestBuilder::string("Test 🚀 emoji"),
+                span: None,
             }
         ]);
         let result = generator.generate(&program, None).unwrap();
@@ -1370,7 +1937,8 @@ mod string_tests {
         // Empty string
         let program = AstTestBuilder::program(vec![
             Statement::ExpressionStatement { 
-                expression: AstTestBuilder::string("")
+                expression: AstTestBuilder::string(""),
+                span: None,
             }
         ]);
         let result = generator.generate(&program, None).unwrap();
@@ -1379,7 +1947,8 @@ mod string_tests {
         // Whitespace-only string
         let program = AstTestBuilder::program(vec![
             Statement::ExpressionStatement { 
-                expression: AstTestBuilder::string("   ")
+                expression: AstTestBuilder::string("   "),
+                span: None,
             }
         ]);
         let result = generator.generate(&program, None).unwrap();
@@ -1388,7 +1957,8 @@ mod string_tests {
         // String with various whitespace
         let program = AstTestBuilder::program(vec![
             Statement::ExpressionStatement { 
-                expression: AstTestBuilder::string(" \t\n ")
+                expression: AstTestBuilder::string(" \t\n "),
+                span: None,
             }
         ]);
         let result = generator.generate(&program, None).unwrap();
@@ -1407,7 +1977,8 @@ mod string_tests {
                 expression: template_literal(
                     vec![template_element("Hello world", true)],
                     vec![]
-                )
+                ),
+                span: None,
             }
         ]);
         let result = generator.generate(&program, None).unwrap();
@@ -1429,7 +2000,8 @@ mod string_tests {
                         template_element("!", true)
                     ],
                     vec![AstTestBuilder::id_expr("name")]
-                )
+                ),
+                span: None,
             }
         ]);
         let result = generator.generate(&program, None).unwrap();
@@ -1448,7 +2020,8 @@ mod string_tests {
                         AstTestBuilder::id_expr("value"),
                         AstTestBuilder::id_expr("count")
                     ]
-                )
+                ),
+                span: None,
             }
         ]);
         let result = generator.generate(&program, None).unwrap();
@@ -1467,7 +2040,8 @@ mod string_tests {
                 expression: template_literal(
                     vec![template_element("Code: `example`", true)],
                     vec![]
-                )
+                ),
+                span: None,
             }
         ]);
         let result = generator.generate(&program, None).unwrap();
@@ -1479,7 +2053,8 @@ mod string_tests {
                 expression: template_literal(
                     vec![template_element("Literal ${", true)],
                     vec![]
-                )
+                ),
+                span: None,
             }
         ]);
         let result = generator.generate(&program, None).unwrap();
@@ -1499,7 +2074,8 @@ mod string_tests {
                 expression: template_literal(
                     vec![template_element("simple", true)],
                     vec![]
-                )
+                ),
+                span: None,
             }
         ]);
         let result = generator.generate(&program, None).unwrap();
@@ -1528,7 +2104,8 @@ mod string_tests {
                             AstTestBuilder::id_expr("b")
                         )
                     ]
-                )
+                ),
+                span: None,
             }
         ]);
         let result = generator.generate(&program, None).unwrap();
@@ -1547,7 +2124,8 @@ mod string_tests {
         
         let program = AstTestBuilder::program(vec![
             Statement::ExpressionStatement { 
-                expression: AstTestBuilder::string("test")
+                expression: AstTestBuilder::string("test"),
+                span: None,
             }
         ]);
         let result = generator.generate(&program, None).unwrap();
@@ -1587,7 +2165,8 @@ mod string_tests {
                     AstTestBuilder::string("hello "),
                     BinaryOperator::Add,
                     AstTestBuilder::string("world")
-                )
+                ),
+                span: None,
             }
         ]);
         let result = generator.generate(&program, None).unwrap();
@@ -1712,6 +2291,60 @@ mod sourcemap_tests {
         assert_eq!(source_map.sources[0], "multi.js");
     }
 
+    /// Test that parsed spans become mappings, skipping nodes built without one
+    #[test]
+    fn test_source_map_maps_parsed_spans() {
+        let source = "let a = 1;\n  f(a, 'x');\n";
+        let program = crate::parser::parse_js(source, "spans.js", &crate::parser::ParserConfig::default()).ast.unwrap();
+        let config = GeneratorConfig {
+            source_map: SourceMapMode::File,
+            ..GeneratorConfig::default()
+        };
+
+        let result = Generator::new(config).generate(&program, Some(source)).unwrap();
+        assert_eq!(result.code, "let a=1;f(a,'x');");
+        // let@1:1, a@1:5, 1@1:9, f(...)@2:3, a@2:5, 'x'@2:8
+        assert_eq!(result.source_map.unwrap().mappings, "AAAA,IAAI,EAAI,EACN,EAAE,EAAG");
+
+        let statement_config = GeneratorConfig {
+            source_map: SourceMapMode::File,
+            mapping_granularity: crate::generator::MappingGranularity::Statement,
+            ..GeneratorConfig::default()
+        };
+        let result = Generator::new(statement_config).generate(&program, Some(source)).unwrap();
+        assert_eq!(result.source_map.unwrap().mappings, "AAAA,QACE");
+    }
+
+    /// Test that identifiers printed under a new name map back to the original name
+    #[test]
+    fn test_source_map_names_renamed_identifiers() {
+        let source = "let count = 1;\ncount += f(count);\n";
+        let program = crate::parser::parse_js(source, "names.js", &crate::parser::ParserConfig::default()).ast.unwrap();
+        let config = GeneratorConfig {
+            source_map: SourceMapMode::File,
+            ..GeneratorConfig::default()
+        };
+        let result = Generator::new(config.clone()).generate(&program, Some(source)).unwrap();
+        assert!(result.source_map.unwrap().names.is_empty());
+
+        // Rename `count` the way a renaming pass would, keeping the spans
+        let json = serde_json::to_string(&program).unwrap().replace("\"name\":\"count\"", "\"name\":\"c\"");
+        let renamed: crate::parser::ast_types::Program = serde_json::from_str(&json).unwrap();
+        let result = Generator::new(config).generate(&renamed, Some(source)).unwrap();
+        assert_eq!(result.code, "let c=1;c+=f(c);");
+
+        let source_map = result.source_map.unwrap();
+        assert_eq!(source_map.names, vec!["count"]);
+        let named: Vec<(u32, u32)> = decode_mappings(&source_map.mappings)
+            .unwrap()
+            .iter()
+            .filter(|mapping| mapping.name_index == Some(0))
+            .map(|mapping| (mapping.generated.column, mapping.original.unwrap().column))
+            .collect();
+        // The declaration, then the assignment target, which starts its statement
+        assert_eq!(named, vec![(4, 4), (8, 0), (13, 11)]);
+    }
+
     /// Test multi-file source map support
     #[test]
     fn test_multi_file_source_map() {
@@ -1897,7 +2530,8 @@ mod sourcemap_tests {
                     AstTestBuilder::id_expr("result"),
                     BinaryOperator::Multiply,
                     AstTestBuilder::number(3.0)
-                )
+                ),
+                span: None,
             }
         ]);
         
@@ -1959,6 +2593,7 @@ mod error_handling_tests {
             body: vec![Statement::VariableDeclaration {
                 declarations: vec![], // Empty declarations should cause error
                 kind: VariableDeclarationKind::Let,
+                span: None,
             }],
             source_type: ProgramSourceType::Script,
         };
@@ -1984,10 +2619,12 @@ mod error_handling_tests {
         let program = AstTestBuilder::program(vec![
             Statement::VariableDeclaration {
                 declarations: vec![VariableDeclarator {
-                    id: Pattern::Identifier(Identifier { name: String::new() }), // Empty name
+                    id: Pattern::Identifier(Identifier { name: String::new(), span: None }), // Empty name
                     init: Some(AstTestBuilder::number(1.0)),
+                    span: None,
                 }],
                 kind: VariableDeclarationKind::Let,
+                span: None,
             }
         ]);
         
@@ -2012,10 +2649,12 @@ mod error_handling_tests {
         let program = AstTestBuilder::program(vec![
             Statement::VariableDeclaration {
                 declarations: vec![VariableDeclarator {
-                    id: Pattern::Identifier(Identifier { name: "123invalid".to_string() }),
+                    id: Pattern::Identifier(Identifier { name: "123invalid".to_string(), span: None }),
                     init: Some(AstTestBuilder::number(1.0)),
+                    span: None,
                 }],
                 kind: VariableDeclarationKind::Let,
+                span: None,
             }
         ]);
         
@@ -2030,29 +2669,41 @@ mod error_handling_tests {
         }
     }
 
-    /// Test string with null character error
+    /// Test Unicode identifiers and escapes against ID_Start/ID_Continue
     #[test]
-    fn test_string_null_character_error() {
-        let config = GeneratorConfig::default();
-        let generator = Generator::new(config);
-        
-        // Create string with null character
-        let program = AstTestBuilder::program(vec![
-            Statement::ExpressionStatement {
-                expression: Expression::Literal(Literal::String(StringLiteral {
-                    value: "test\0null".to_string(),
-                }))
+    fn test_unicode_identifier_validation() {
+        let generator = Generator::new(GeneratorConfig::default());
+        let declare = |name: &str| {
+            let program = AstTestBuilder::program(vec![AstTestBuilder::var_declaration(
+                name,
+                Some(AstTestBuilder::number(1.0)),
+                VariableDeclarationKind::Let,
+            )]);
+            generator.generate(&program, None)
+        };
+
+        for name in ["café", "π", "℮x", r"$_\u200d", r"\u0061b", r"\u{1d4b6}", r"a\u{30}"] {
+            assert!(declare(name).is_ok(), "{} should be accepted", name);
+        }
+
+        let cases = [
+            ("1x", "must start with letter"),
+            (r"\u0031x", "must start with letter"),
+            ("a-b", "invalid character '-'"),
+            (r"a\u{2F}", "invalid character '/'"),
+            ("😀", "must start with letter"),
+            (r"a\x41", "invalid escape"),
+            (r"a\u00G1", "invalid escape"),
+            (r"a\u{D800}", "invalid escape"),
+        ];
+        for (name, expected) in cases {
+            match declare(name) {
+                Err(GeneratorError::IdentifierError { message, identifier }) => {
+                    assert!(message.contains(expected), "{}: {}", name, message);
+                    assert_eq!(identifier, name);
+                }
+                other => panic!("{} should be rejected, got {:?}", name, other),
             }
-        ]);
-        
-        let result = generator.generate(&program, None);
-        assert!(result.is_err());
-        
-        if let Err(GeneratorError::StringProcessingError { message, content }) = result {
-            assert!(message.contains("null character"));
-            assert_eq!(content, "test\0null");
-        } else {
-            panic!("Expected StringProcessingError for null character");
         }
     }
 
@@ -2067,7 +2718,10 @@ mod error_handling_tests {
             Statement::ExpressionStatement {
                 expression: Expression::Literal(Literal::Number(NumberLiteral {
                     value: f64::NAN,
-                }))
+                    raw: None,
+                    span: None,
+                })),
+                span: None,
             }
         ]);
         
@@ -2093,7 +2747,10 @@ mod error_handling_tests {
             Statement::ExpressionStatement {
                 expression: Expression::Literal(Literal::Number(NumberLiteral {
                     value: f64::INFINITY,
-                }))
+                    raw: None,
+                    span: None,
+                })),
+                span: None,
             }
         ]);
         
@@ -2120,7 +2777,9 @@ mod error_handling_tests {
                 expression: Expression::Literal(Literal::RegExp(RegExpLiteral {
                     pattern: String::new(), // Empty pattern
                     flags: "g".to_string(),
-                }))
+                    span: None,
+                })),
+                span: None,
             }
         ]);
         
@@ -2146,14 +2805,15 @@ mod error_handling_tests {
             Statement::ExpressionStatement {
                 expression: Expression::TemplateLiteral {
                     quasis: vec![
-                        TemplateElement { value: "Hello ".to_string(), tail: false },
-                        TemplateElement { value: " world".to_string(), tail: true },
+                        TemplateElement { value: "Hello ".to_string(), raw: None, tail: false, span: None },
+                        TemplateElement { value: " world".to_string(), raw: None, tail: true, span: None },
                     ],
                     expressions: vec![
                         AstTestBuilder::id_expr("name"),
                         AstTestBuilder::id_expr("extra"), // Extra expression
                     ],
-                }
+                },
+                span: None,
             }
         ]);
         
@@ -2181,7 +2841,8 @@ mod error_handling_tests {
                 expression: Expression::TemplateLiteral {
                     quasis: vec![], // Empty quasis
                     expressions: vec![],
-                }
+                },
+                span: None,
             }
         ]);
         
@@ -2241,6 +2902,93 @@ mod error_handling_tests {
         assert!(result.is_ok());
     }
 
+    /// Helper to build a program with `count` numbered declarations
+    fn numbered_declarations(count: usize) -> Program {
+        let statements: Vec<Statement> = (0..count)
+            .map(|i| {
+                AstTestBuilder::var_declaration(
+                    &format!("var{}", i),
+                    Some(AstTestBuilder::number(i as f64)),
+                    VariableDeclarationKind::Let
+                )
+            })
+            .collect();
+        AstTestBuilder::program(statements)
+    }
+
+    /// Test configurable output size limits
+    #[test]
+    fn test_configurable_output_size_limit() {
+        let program = numbered_declarations(100);
+
+        let config = GeneratorConfig {
+            max_output_size: Some(64),
+            ..GeneratorConfig::default()
+        };
+        let result = Generator::new(config).generate(&program, None);
+        assert!(matches!(
+            result,
+            Err(GeneratorError::OutputSizeLimitExceeded { limit: 64, .. })
+        ));
+
+        let config = GeneratorConfig {
+            max_output_size: None,
+            ..GeneratorConfig::default()
+        };
+        assert!(Generator::new(config).generate(&program, None).is_ok());
+    }
+
+    /// Test that streaming output matches buffered output
+    #[test]
+    fn test_streaming_matches_buffered_output() {
+        let program = numbered_declarations(100);
+        let config = GeneratorConfig {
+            stream_buffer_size: 16,
+            ..GeneratorConfig::default()
+        };
+        let generator = Generator::new(config);
+
+        let buffered = generator.generate(&program, None).unwrap();
+        let mut streamed = Vec::new();
+        let diagnostics = generator.generate_to_writer(&program, None, &mut streamed).unwrap();
+
+        assert_eq!(String::from_utf8(streamed).unwrap(), buffered.code);
+        assert_eq!(diagnostics.generated_size, buffered.code.len());
+    }
+
+    /// Test that streaming keeps the in-memory buffer within the memory limit
+    #[test]
+    fn test_streaming_respects_memory_limit() {
+        let program = numbered_declarations(100);
+        let config = GeneratorConfig {
+            max_memory_usage: Some(64),
+            stream_buffer_size: 32,
+            ..GeneratorConfig::default()
+        };
+        let generator = Generator::new(config);
+
+        // Buffering everything exceeds the limit, streaming flushes before reaching it
+        assert!(matches!(
+            generator.generate(&program, None),
+            Err(GeneratorError::MemoryLimitExceeded { limit: 64, .. })
+        ));
+        let mut streamed = Vec::new();
+        assert!(generator.generate_to_writer(&program, None, &mut streamed).is_ok());
+    }
+
+    /// Test that streaming rejects source map generation
+    #[test]
+    fn test_streaming_rejects_source_maps() {
+        let config = GeneratorConfig {
+            source_map: crate::generator::SourceMapMode::File,
+            ..GeneratorConfig::default()
+        };
+        let mut streamed = Vec::new();
+        let result = Generator::new(config).generate_to_writer(&numbered_declarations(1), None, &mut streamed);
+
+        assert!(matches!(result, Err(GeneratorError::InvalidConfiguration { .. })));
+    }
+
     /// Test error message formatting
     #[test]
     fn test_error_message_formatting() {
@@ -2267,12 +3015,15 @@ mod error_handling_tests {
                 body: vec![
                     Statement::VariableDeclaration {
                         declarations: vec![VariableDeclarator {
-                            id: Pattern::Identifier(Identifier { name: String::new() }), // Error here
+                            id: Pattern::Identifier(Identifier { name: String::new(), span: None }), // Error here
                             init: Some(AstTestBuilder::number(1.0)),
+                            span: None,
                         }],
                         kind: VariableDeclarationKind::Let,
+                        span: None,
                     }
                 ],
+                span: None,
             }
         ]);
         
@@ -0,0 +1,139 @@
+//! # Name Interning
+//!
+//! Maps identifier names to small, copyable [`Name`] ids so that maps keyed by
+//! names hash a `u32` instead of a string and store each distinct name once.
+//!
+//! One interner follows a program through the pipeline: the parser interns every
+//! binding name into [`ParseResult::names`](crate::parser::ParseResult::names), the
+//! analyzer continues it as [`SymbolTable::names`](crate::analyzer::SymbolTable::names)
+//! (passed on through `AnalyzerConfig::names`), where each
+//! [`Symbol::name`](crate::analyzer::Symbol::name) is a [`Name`], and the transformer
+//! passes group and look up symbols by those ids instead of by string.
+
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::Arc;
+
+use serde::de::{Deserializer, SeqAccess, Visitor};
+use serde::ser::{SerializeSeq, Serializer};
+use serde::{Deserialize, Serialize};
+
+/// Interned identifier name
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct Name(u32);
+
+impl Name {
+    /// Index of the name in its interner
+    pub fn index(self) -> u32 {
+        self.0
+    }
+}
+
+/// Table of interned names
+///
+/// Serializes as the list of names in id order.
+#[derive(Debug, Clone, Default)]
+pub struct Interner {
+    ids: HashMap<Arc<str>, Name>,
+    names: Vec<Arc<str>>,
+}
+
+impl Interner {
+    /// Creates an empty interner
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the id of `name`, adding it on first use
+    pub fn intern(&mut self, name: &str) -> Name {
+        if let Some(&id) = self.ids.get(name) {
+            return id;
+        }
+        let id = Name(self.names.len() as u32);
+        let name: Arc<str> = Arc::from(name);
+        self.names.push(Arc::clone(&name));
+        self.ids.insert(name, id);
+        id
+    }
+
+    /// Returns the id of `name` if it has been interned
+    pub fn get(&self, name: &str) -> Option<Name> {
+        self.ids.get(name).copied()
+    }
+
+    /// Returns the string for `name`
+    ///
+    /// # Panics
+    ///
+    /// Panics if `name` was produced by a different interner.
+    pub fn resolve(&self, name: Name) -> &str {
+        &self.names[name.0 as usize]
+    }
+
+    /// Number of distinct names
+    pub fn len(&self) -> usize {
+        self.names.len()
+    }
+
+    /// Returns whether no name has been interned
+    pub fn is_empty(&self) -> bool {
+        self.names.is_empty()
+    }
+}
+
+impl Serialize for Interner {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut seq = serializer.serialize_seq(Some(self.names.len()))?;
+        for name in &self.names {
+            seq.serialize_element(&**name)?;
+        }
+        seq.end()
+    }
+}
+
+impl<'de> Deserialize<'de> for Interner {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct InternerVisitor;
+
+        impl<'de> Visitor<'de> for InternerVisitor {
+            type Value = Interner;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                formatter.write_str("a list of names")
+            }
+
+            fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Interner, A::Error> {
+                let mut interner = Interner::new();
+                while let Some(name) = seq.next_element::<String>()? {
+                    interner.intern(&name);
+                }
+                Ok(interner)
+            }
+        }
+
+        deserializer.deserialize_seq(InternerVisitor)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_intern_and_round_trip() {
+        let mut interner = Interner::new();
+        let a = interner.intern("alpha");
+        let b = interner.intern("beta");
+        assert_eq!(interner.intern("alpha"), a);
+        assert_ne!(a, b);
+        assert_eq!(interner.resolve(b), "beta");
+        assert_eq!(interner.get("gamma"), None);
+        assert_eq!(interner.len(), 2);
+
+        let json = serde_json::to_string(&interner).unwrap();
+        assert_eq!(json, r#"["alpha","beta"]"#);
+        let restored: Interner = serde_json::from_str(&json).unwrap();
+        assert_eq!(restored.get("beta"), Some(b));
+    }
+}
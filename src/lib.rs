@@ -0,0 +1,323 @@
+//! # RJS Compiler Library
+//!
+//! Library entry point for the RJS Compiler. It exposes the four pipeline phases
+//! (parser, analyzer, transformer, generator) together with a convenience function
+//! that runs the complete pipeline on a source string.
+//!
+//! The `rjs-compiler` binary and the integration tests under `tests/` are both
+//! built on top of this crate.
+//!
+//! ## Usage
+//!
+//! ```rust,no_run
+//! let minified = rjs_compiler::compile_source("let answer = 42;", "input.js")?;
+//! println!("{}", minified);
+//! # Ok::<(), rjs_compiler::CompileError>(())
+//! ```
+//!
+//! ## WebAssembly
+//!
+//! With the `wasm` feature the crate builds for `wasm32-unknown-unknown` and exports
+//! [`minify`] to JavaScript through `wasm-bindgen` (see the `wasm` module). The compilation
+//! pipeline never touches the filesystem, the stdio `server` and `lsp` modules and the
+//! `logging` subscriber are left out, and transformer worker threads fall back to
+//! sequential execution on wasm32.
+
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+pub mod analyzer;
+pub mod cancel;
+pub mod encoding;
+pub mod generator;
+pub mod interner;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod logging;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod lsp;
+pub mod parser;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod server;
+pub mod target;
+pub mod transformer;
+
+#[cfg(feature = "wasm")]
+pub mod wasm;
+
+/// Monotonic clock used for phase timings (`std::time::Instant` panics on wasm32)
+#[cfg(not(target_arch = "wasm32"))]
+pub(crate) use std::time::Instant;
+#[cfg(target_arch = "wasm32")]
+pub(crate) use web_time::Instant;
+
+/// Errors that can occur while running the complete compilation pipeline
+#[derive(Error, Debug)]
+pub enum CompileError {
+    #[error("Parse error: {0}")]
+    Parse(String),
+
+    #[error("Analysis failed: {0}")]
+    Analysis(#[from] analyzer::AnalysisError),
+
+    #[error("Transformation failed: {0}")]
+    Transform(#[from] transformer::TransformError),
+
+    #[error("Code generation failed: {0}")]
+    Generation(#[from] generator::GeneratorError),
+
+    #[error("Compilation stopped: {0}")]
+    Cancelled(#[from] cancel::Cancelled),
+
+    #[error("Failed to start the compiler thread: {0}")]
+    Thread(#[from] std::io::Error),
+}
+
+impl CompileError {
+    /// Why the compilation was stopped, when it failed because its
+    /// [`CancellationToken`](cancel::CancellationToken) was cancelled
+    pub fn cancelled(&self) -> Option<cancel::Cancelled> {
+        match self {
+            Self::Cancelled(cancelled)
+            | Self::Analysis(analyzer::AnalysisError::Cancelled(cancelled))
+            | Self::Transform(transformer::TransformError::Cancelled(cancelled)) => Some(*cancelled),
+            Self::Generation(generator::GeneratorError::Cancelled) => Some(cancel::Cancelled::Requested),
+            Self::Generation(generator::GeneratorError::GenerationTimeout { timeout_ms }) => {
+                Some(cancel::Cancelled::DeadlineExceeded { timeout_ms: *timeout_ms })
+            }
+            _ => None,
+        }
+    }
+}
+
+/// Result type for pipeline operations
+pub type CompileResult<T> = Result<T, CompileError>;
+
+/// Options for [`minify`]
+///
+/// Field names are camelCase when deserialized, matching the JavaScript API.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct MinifyOptions {
+    /// Name of the input, used in diagnostics and as the source map source
+    pub filename: Option<String>,
+    /// Whether to produce a source map
+    pub source_map: bool,
+    /// Globs of sources to mark as ignore-listed in the source map (`ignoreList`), such as
+    /// vendored or generated code that debuggers should step over
+    pub source_map_ignore_list: Vec<String>,
+}
+
+/// Output of [`minify`]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MinifyOutput {
+    /// Minified JavaScript code
+    pub code: String,
+    /// Source map JSON, when requested
+    pub map: Option<String>,
+    /// Warnings reported by the transformer and generator
+    pub warnings: Vec<String>,
+}
+
+/// Runs the full parse → analyze → transform → generate pipeline on a source string
+///
+/// Uses the same phase configuration as the command-line tool without verbose output,
+/// so the result matches what `rjs-compiler` writes for the same input.
+///
+/// # Arguments
+///
+/// * `source` - JavaScript source code to minify
+/// * `filename` - Name used in diagnostics
+///
+/// # Returns
+///
+/// Returns the minified JavaScript code
+///
+/// # Errors
+///
+/// Returns `CompileError::Parse` if the source contains syntax errors, or the error of
+/// whichever later phase fails
+pub fn compile_source(source: &str, filename: &str) -> CompileResult<String> {
+    let options = MinifyOptions {
+        filename: Some(filename.to_string()),
+        ..MinifyOptions::default()
+    };
+    minify(source, &options).map(|output| output.code)
+}
+
+/// Minifies a source string, returning the code together with its source map and warnings
+///
+/// This is the entry point exported to JavaScript by the `wasm` feature.
+///
+/// # Errors
+///
+/// Returns `CompileError::Parse` if the source contains syntax errors, or the error of
+/// whichever later phase fails
+pub fn minify(source: &str, options: &MinifyOptions) -> CompileResult<MinifyOutput> {
+    minify_with_cancellation(source, options, &cancel::CancellationToken::new())
+}
+
+/// Minifies a source string like [`minify`], stopping early once `token` is cancelled
+///
+/// # Errors
+///
+/// Returns an error for which [`CompileError::cancelled`] is `Some` when `token` was
+/// cancelled or timed out, or the errors of [`minify`]
+pub fn minify_with_cancellation(
+    source: &str,
+    options: &MinifyOptions,
+    token: &cancel::CancellationToken,
+) -> CompileResult<MinifyOutput> {
+    with_pipeline_stack(|| minify_on_current_thread(source, options, token))
+}
+
+/// Runs [`minify_with_cancellation`] on the current thread
+fn minify_on_current_thread(source: &str, options: &MinifyOptions, token: &cancel::CancellationToken) -> CompileResult<MinifyOutput> {
+    let filename = options.filename.as_deref().unwrap_or(DEFAULT_FILENAME);
+    token.check()?;
+    let (ast, analyzer_config) = parse_source(source, filename)?;
+    token.check()?;
+    let analyzer_config = analyzer::AnalyzerConfig {
+        cancellation: token.clone(),
+        ..analyzer_config
+    };
+    let analysis_result = analyzer::analyze_ast(&ast, &analyzer_config)?;
+
+    let transformer_config = pipeline_transformer_config().with_cancellation(token.clone());
+    let transformation_result = transformer::transform_ast_with_config(ast, analysis_result, transformer_config)?;
+
+    let generator_config = generator::GeneratorConfig {
+        format: generator::OutputFormat::Compact,
+        semicolon: generator::SemicolonStrategy::Auto,
+        quote: generator::QuoteStrategy::Auto,
+        preserve_comments: generator::CommentPreservation::None,
+        source_map: if options.source_map {
+            generator::SourceMapMode::File
+        } else {
+            generator::SourceMapMode::None
+        },
+        cancellation: token.clone(),
+        ..generator::GeneratorConfig::default()
+    };
+    let generation_result = generator::Generator::new(generator_config)
+        .generate(&transformation_result.transformed_ast, Some(source))?;
+
+    let map = generation_result
+        .source_map
+        .map(|mut map| {
+            map.sources = vec![filename.to_string()];
+            map.ignore_sources(&options.source_map_ignore_list);
+            map.to_json()
+        })
+        .transpose()
+        .map_err(generator::GeneratorError::from)?;
+
+    // Like the command-line tool, only report warnings, not the notes about what the passes did
+    let mut warnings: Vec<String> = transformation_result
+        .warnings
+        .iter()
+        .filter(|diagnostic| diagnostic.severity == transformer::diagnostics::Severity::Warning)
+        .map(ToString::to_string)
+        .collect();
+    warnings.extend(generation_result.diagnostics.warnings);
+
+    Ok(MinifyOutput {
+        code: generation_result.code,
+        map,
+        warnings,
+    })
+}
+
+/// Parses and analyzes a source string without transforming it
+///
+/// # Errors
+///
+/// Returns `CompileError::Parse` if the source contains syntax errors, or
+/// `CompileError::Analysis` if semantic analysis fails
+pub fn analyze_source(source: &str, filename: &str) -> CompileResult<analyzer::SemanticAnalysis> {
+    with_pipeline_stack(|| {
+        let (ast, analyzer_config) = parse_source(source, filename)?;
+        Ok(analyzer::analyze_ast(&ast, &analyzer_config)?)
+    })
+}
+
+/// Parses minified code and prints it with the Pretty format, restoring the original
+/// names that `source_map` and `name_map` record
+///
+/// # Errors
+///
+/// Returns `CompileError::Parse` if the code contains syntax errors, or
+/// `CompileError::Generation` if it cannot be printed
+pub fn beautify_source(
+    source: &str,
+    filename: &str,
+    source_map: Option<&generator::source_maps::SourceMap>,
+    name_map: Option<&transformer::NameMap>,
+) -> CompileResult<String> {
+    with_pipeline_stack(|| {
+        let (mut ast, _) = parse_source(source, filename)?;
+        generator::beautify::restore_names(&mut ast, source, source_map, name_map)?;
+        let config = generator::GeneratorConfig {
+            format: generator::OutputFormat::Pretty,
+            ..generator::GeneratorConfig::default()
+        };
+        Ok(generator::Generator::new(config).generate(&ast, None)?.code)
+    })
+}
+
+/// Name used for inputs that do not specify one
+const DEFAULT_FILENAME: &str = "input.js";
+
+/// Runs a pipeline entry point on a thread whose stack fits the deepest nesting the
+/// default parser configuration accepts, whatever thread the caller is on
+fn with_pipeline_stack<T: Send>(f: impl FnOnce() -> CompileResult<T> + Send) -> CompileResult<T> {
+    parser::depth::with_stack_for(parser::depth::DEFAULT_MAX_DEPTH, f)?
+}
+
+/// Parses a source string, joining all syntax errors into a single `CompileError::Parse`
+///
+/// Returns the AST together with the analyzer configuration for it, which carries the
+/// names of `@__KEEP__` declarations and the spans of code the AST lacks. Runs on the
+/// current thread, which [`with_pipeline_stack`] has sized already.
+fn parse_source(source: &str, filename: &str) -> CompileResult<(parser::ast_types::Program, analyzer::AnalyzerConfig)> {
+    let config = parser::ParserConfig::default();
+    let allocator = parser::Allocator::default();
+    let parse_result = parser::parse_js_in(source, filename, &allocator, &config).into_parse_result(&config);
+
+    if !parse_result.errors.is_empty() {
+        let messages: Vec<String> = parse_result.errors.iter().map(|e| e.to_string()).collect();
+        return Err(CompileError::Parse(messages.join("; ")));
+    }
+
+    if let Some(error) = parse_result.unsupported_syntax(source) {
+        return Err(CompileError::Parse(error.to_string()));
+    }
+
+    let ast = parse_result.ast.ok_or_else(|| {
+        CompileError::Parse("No AST generated despite no errors".to_string())
+    })?;
+    Ok((ast, pipeline_analyzer_config(parse_result.kept_names, parse_result.dropped_spans, parse_result.names)))
+}
+
+/// Analyzer configuration shared by the library entry points
+fn pipeline_analyzer_config(
+    kept_names: Vec<String>,
+    dropped_spans: Vec<parser::SourceSpan>,
+    names: interner::Interner,
+) -> analyzer::AnalyzerConfig {
+    analyzer::AnalyzerConfig {
+        preserve_exports: true,
+        aggressive_optimization: false,
+        strict_mode: false,
+        kept_names,
+        dropped_spans,
+        names,
+        cancellation: cancel::CancellationToken::new(),
+    }
+}
+
+/// Transformer configuration shared by the library entry points
+///
+/// Matches the command-line defaults, where property minification is opt-in.
+fn pipeline_transformer_config() -> transformer::TransformerConfig {
+    transformer::TransformerConfig::default().with_property_minification(false)
+}
@@ -0,0 +1,190 @@
+//! # Logging
+//!
+//! The pipeline reports its progress as [`tracing`] events; this module installs the
+//! subscriber the `rjs-compiler` binary prints them with.
+//!
+//! ## Levels
+//!
+//! | Flag   | Level   | Shows                                                  |
+//! |--------|---------|--------------------------------------------------------|
+//! | `-q`   | `warn`  | Warnings and errors only                               |
+//! | (none) | `info`  | Where the output was written                           |
+//! | `-v`   | `debug` | Phase results and what each pass changed               |
+//! | `-vv`  | `trace` | Every symbol declared and reference resolved           |
+//!
+//! `--log-filter` takes [`EnvFilter`] directives that are applied on top of the level,
+//! such as `rjs_compiler::analyzer=warn,rjs_compiler::transformer=trace`; without it the
+//! `RJS_LOG` environment variable is read.
+//!
+//! ## Formats
+//!
+//! The text format prints each message on its own line, warnings and errors on stderr
+//! and everything else on stdout, the same as the output before logging went through
+//! `tracing`. The JSON format prints one object per event on stderr, with the
+//! timestamp, level and target, and leaves stdout to the data the command prints.
+
+use std::fmt;
+use std::str::FromStr;
+
+use tracing::{Event, Level, Subscriber};
+use tracing_subscriber::fmt::format::Writer;
+use tracing_subscriber::fmt::writer::MakeWriterExt;
+use tracing_subscriber::fmt::{FmtContext, FormatEvent, FormatFields};
+use tracing_subscriber::registry::LookupSpan;
+use tracing_subscriber::EnvFilter;
+
+/// Environment variable read for filter directives when `--log-filter` is not given
+pub const LOG_FILTER_ENV: &str = "RJS_LOG";
+
+/// How much is logged, from `-q` and the number of `-v` flags
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Default)]
+pub enum Verbosity {
+    /// Warnings and errors only
+    Quiet,
+    /// Where the output was written
+    #[default]
+    Normal,
+    /// Phase results and pass changes
+    Verbose,
+    /// Everything, down to individual symbols
+    Trace,
+}
+
+impl Verbosity {
+    /// Verbosity for `-q` and a count of `-v` flags
+    pub fn from_flags(quiet: bool, verbose: u8) -> Self {
+        match (quiet, verbose) {
+            (true, _) => Self::Quiet,
+            (false, 0) => Self::Normal,
+            (false, 1) => Self::Verbose,
+            (false, _) => Self::Trace,
+        }
+    }
+
+    fn level(self) -> Level {
+        match self {
+            Self::Quiet => Level::WARN,
+            Self::Normal => Level::INFO,
+            Self::Verbose => Level::DEBUG,
+            Self::Trace => Level::TRACE,
+        }
+    }
+}
+
+/// Output format of log events
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum LogFormat {
+    /// Plain messages, one per line
+    #[default]
+    Text,
+    /// One JSON object per event, on stderr
+    Json,
+}
+
+impl FromStr for LogFormat {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "text" => Ok(Self::Text),
+            "json" => Ok(Self::Json),
+            _ => Err(format!("unknown log format '{}' (expected text or json)", value)),
+        }
+    }
+}
+
+/// Configuration of the installed subscriber
+#[derive(Debug, Clone, Default)]
+pub struct LogConfig {
+    pub verbosity: Verbosity,
+    pub format:    LogFormat,
+    /// Extra [`EnvFilter`] directives, applied after the verbosity level
+    pub filter:    Option<String>,
+}
+
+impl LogConfig {
+    pub fn with_verbosity(mut self, verbosity: Verbosity) -> Self {
+        self.verbosity = verbosity;
+        self
+    }
+
+    pub fn with_format(mut self, format: LogFormat) -> Self {
+        self.format = format;
+        self
+    }
+
+    pub fn with_filter(mut self, filter: Option<String>) -> Self {
+        self.filter = filter;
+        self
+    }
+
+    /// Filter directives: the verbosity level followed by the extra directives
+    pub fn directives(&self) -> String {
+        let level = self.verbosity.level().to_string().to_lowercase();
+        match self.filter.as_deref().map(str::trim) {
+            Some(filter) if !filter.is_empty() => format!("{},{}", level, filter),
+            _ => level,
+        }
+    }
+}
+
+/// Installs the global subscriber for `config`
+///
+/// # Errors
+///
+/// Returns an error if the filter directives are invalid or a subscriber is already
+/// installed.
+pub fn init(config: &LogConfig) -> Result<(), String> {
+    let filter = EnvFilter::try_new(config.directives())
+        .map_err(|e| format!("invalid log filter '{}': {}", config.directives(), e))?;
+    // A closed stdout (`| head`) drops the remaining messages quietly
+    let builder = tracing_subscriber::fmt().with_env_filter(filter).log_internal_errors(false);
+    let result = match config.format {
+        LogFormat::Text => builder
+            .event_format(MessageFormat)
+            .with_writer(std::io::stderr.with_max_level(Level::WARN).or_else(std::io::stdout))
+            .try_init(),
+        LogFormat::Json => builder.json().with_writer(std::io::stderr).try_init(),
+    };
+    result.map_err(|e| e.to_string())
+}
+
+/// Prints the fields of an event, without level, target or timestamp
+struct MessageFormat;
+
+impl<S, N> FormatEvent<S, N> for MessageFormat
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+    N: for<'a> FormatFields<'a> + 'static,
+{
+    fn format_event(&self, ctx: &FmtContext<'_, S, N>, mut writer: Writer<'_>, event: &Event<'_>) -> fmt::Result {
+        ctx.field_format().format_fields(writer.by_ref(), event)?;
+        writeln!(writer)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_verbosity_from_flags() {
+        assert_eq!(Verbosity::from_flags(false, 0), Verbosity::Normal);
+        assert_eq!(Verbosity::from_flags(false, 1), Verbosity::Verbose);
+        assert_eq!(Verbosity::from_flags(false, 3), Verbosity::Trace);
+        assert_eq!(Verbosity::from_flags(true, 0), Verbosity::Quiet);
+    }
+
+    #[test]
+    fn test_directives_combine_level_and_filter() {
+        let config = LogConfig::default();
+        assert_eq!(config.directives(), "info");
+
+        let config = config
+            .with_verbosity(Verbosity::Quiet)
+            .with_filter(Some("rjs_compiler::transformer=trace".to_string()));
+        assert_eq!(config.directives(), "warn,rjs_compiler::transformer=trace");
+        assert!(EnvFilter::try_new(config.directives()).is_ok());
+        assert!("xml".parse::<LogFormat>().is_err());
+    }
+}
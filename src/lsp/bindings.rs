@@ -0,0 +1,360 @@
+//! # Binding Resolution on the OXC AST
+//!
+//! A lightweight resolver that walks the arena AST returned by
+//! [`parse_js_in`](crate::parser::parse_js_in) and links every identifier reference to
+//! the binding it resolves to, keeping the source spans that the owned AST does not
+//! carry. The language server uses it to place analyzer findings in the document and
+//! to detect temporal dead zone violations, which depend on source order.
+//!
+//! ## Scope Frames
+//!
+//! A frame is pushed for every scope OXC enters, plus one per class body. Function,
+//! static block and class body frames are *deferred*: code inside them runs later, so references from there into
+//! outer scopes are never reported as TDZ violations.
+
+use std::cell::Cell;
+use std::collections::HashMap;
+
+use oxc_ast::ast::{
+    BindingIdentifier, CallExpression, CatchParameter, Class, ClassBody, Expression, FormalParameter,
+    Function, FunctionType, IdentifierReference, ImportDeclaration, Program, VariableDeclarationKind,
+    VariableDeclarator,
+};
+use oxc_ast::visit::walk;
+use oxc_ast::Visit;
+use oxc_span::Span;
+use oxc_syntax::scope::{ScopeFlags, ScopeId};
+
+/// How a name was bound
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BindingKind {
+    /// `var` declaration (function-scoped)
+    Var,
+    /// `let`, `const` or `using` declaration
+    Lexical,
+    /// Function declaration
+    Function,
+    /// Class declaration
+    Class,
+    /// Function or arrow parameter
+    Parameter,
+    /// Catch clause parameter
+    CatchParameter,
+    /// Import specifier
+    Import,
+    /// Any other binding (function and class expression names)
+    Other,
+}
+
+/// A binding with its location and resolved usage
+#[derive(Debug, Clone)]
+pub struct Binding {
+    /// Bound name
+    pub name: String,
+    /// Span of the binding identifier
+    pub span: Span,
+    /// How the name was bound
+    pub kind: BindingKind,
+    /// Offset where the temporal dead zone ends, for `let`/`const`/`class` bindings
+    pub tdz_end: Option<u32>,
+    /// Whether any reference resolved to this binding
+    pub used: bool,
+}
+
+/// A reference that reads a lexical binding before its declaration completes
+#[derive(Debug, Clone)]
+pub struct TdzViolation {
+    /// Referenced name
+    pub name: String,
+    /// Span of the offending reference
+    pub span: Span,
+}
+
+/// Bindings, TDZ violations and `eval` calls found in a program
+#[derive(Debug, Default)]
+pub struct BindingIndex {
+    /// All bindings, in source order
+    pub bindings: Vec<Binding>,
+    /// References made while their binding is in the temporal dead zone
+    pub tdz_violations: Vec<TdzViolation>,
+    /// Spans of direct `eval(...)` calls
+    pub eval_calls: Vec<Span>,
+}
+
+impl BindingIndex {
+    /// Builds the index for a parsed program
+    pub fn build(program: &Program<'_>) -> Self {
+        let mut collector = Collector::default();
+        collector.visit_program(program);
+        collector.resolve()
+    }
+}
+
+/// A scope entered during the walk
+#[derive(Debug)]
+struct Frame {
+    parent: Option<usize>,
+    /// Function or program scope, the target of `var` declarations
+    is_var_scope: bool,
+    /// Code in this frame runs later than the surrounding code
+    is_deferred: bool,
+}
+
+/// An identifier reference awaiting resolution
+#[derive(Debug)]
+struct Reference {
+    name: String,
+    span: Span,
+    frame: usize,
+}
+
+/// Visitor collecting frames, bindings and references
+#[derive(Debug, Default)]
+struct Collector {
+    frames: Vec<Frame>,
+    stack: Vec<usize>,
+    bindings: Vec<(Binding, usize)>,
+    references: Vec<Reference>,
+    /// Kind (and TDZ end) applied to binding identifiers visited next
+    binding_context: Option<(BindingKind, Option<u32>)>,
+    /// Declaration name already recorded by `visit_function`/`visit_class`
+    declared_name: Option<Span>,
+    eval_calls: Vec<Span>,
+}
+
+impl Collector {
+    fn current_frame(&self) -> usize {
+        *self.stack.last().expect("program scope is always entered first")
+    }
+
+    fn nearest_var_frame(&self) -> usize {
+        self.stack
+            .iter()
+            .rev()
+            .copied()
+            .find(|&frame| self.frames[frame].is_var_scope)
+            .unwrap_or(0)
+    }
+
+    fn record_binding(&mut self, id: &BindingIdentifier<'_>, kind: BindingKind, tdz_end: Option<u32>, frame: usize) {
+        let binding = Binding {
+            name: id.name.to_string(),
+            span: id.span,
+            kind,
+            tdz_end,
+            used: false,
+        };
+        self.bindings.push((binding, frame));
+    }
+
+    /// Visits `visit` with a binding context, restoring the previous one afterwards
+    fn with_binding_context(&mut self, context: (BindingKind, Option<u32>), visit: impl FnOnce(&mut Self)) {
+        let previous = self.binding_context.replace(context);
+        visit(self);
+        self.binding_context = previous;
+    }
+
+    /// Resolves every reference against the collected bindings
+    fn resolve(self) -> BindingIndex {
+        let Collector { frames, bindings, references, eval_calls, .. } = self;
+
+        // Later declarations of the same name in a frame are redeclarations; keep the first
+        let mut lookup: HashMap<(usize, &str), usize> = HashMap::new();
+        for (index, (binding, frame)) in bindings.iter().enumerate() {
+            lookup.entry((*frame, binding.name.as_str())).or_insert(index);
+        }
+
+        let mut used = vec![false; bindings.len()];
+        let mut tdz_violations = Vec::new();
+
+        for reference in &references {
+            let mut frame = Some(reference.frame);
+            let mut deferred = false;
+
+            while let Some(current) = frame {
+                if let Some(&index) = lookup.get(&(current, reference.name.as_str())) {
+                    used[index] = true;
+                    let binding = &bindings[index].0;
+                    if !deferred && binding.tdz_end.is_some_and(|end| reference.span.start < end) {
+                        tdz_violations.push(TdzViolation {
+                            name: reference.name.clone(),
+                            span: reference.span,
+                        });
+                    }
+                    break;
+                }
+                deferred |= frames[current].is_deferred;
+                frame = frames[current].parent;
+            }
+        }
+
+        let bindings = bindings
+            .into_iter()
+            .zip(used)
+            .map(|((binding, _), used)| Binding { used, ..binding })
+            .collect();
+
+        BindingIndex { bindings, tdz_violations, eval_calls }
+    }
+}
+
+impl<'a> Visit<'a> for Collector {
+    fn enter_scope(&mut self, flags: ScopeFlags, _scope_id: &Cell<Option<ScopeId>>) {
+        let index = self.frames.len();
+        self.frames.push(Frame {
+            parent: self.stack.last().copied(),
+            is_var_scope: flags.intersects(ScopeFlags::Top | ScopeFlags::Function),
+            is_deferred: flags.intersects(ScopeFlags::Function | ScopeFlags::ClassStaticBlock),
+        });
+        self.stack.push(index);
+    }
+
+    fn leave_scope(&mut self) {
+        self.stack.pop();
+    }
+
+    fn visit_binding_identifier(&mut self, it: &BindingIdentifier<'a>) {
+        if self.declared_name == Some(it.span) {
+            self.declared_name = None;
+            return;
+        }
+
+        let (kind, tdz_end) = self.binding_context.unwrap_or((BindingKind::Other, None));
+        let frame = if kind == BindingKind::Var {
+            self.nearest_var_frame()
+        } else {
+            self.current_frame()
+        };
+        self.record_binding(it, kind, tdz_end, frame);
+    }
+
+    fn visit_identifier_reference(&mut self, it: &IdentifierReference<'a>) {
+        self.references.push(Reference {
+            name: it.name.to_string(),
+            span: it.span,
+            frame: self.current_frame(),
+        });
+    }
+
+    fn visit_variable_declarator(&mut self, it: &VariableDeclarator<'a>) {
+        let context = match it.kind {
+            VariableDeclarationKind::Var => (BindingKind::Var, None),
+            // The initializer is still inside the temporal dead zone: `let x = x;`
+            _ => (BindingKind::Lexical, Some(it.span.end)),
+        };
+        self.with_binding_context(context, |this| this.visit_binding_pattern(&it.id));
+
+        if let Some(init) = &it.init {
+            self.visit_expression(init);
+        }
+    }
+
+    fn visit_function(&mut self, it: &Function<'a>, flags: ScopeFlags) {
+        // A declaration binds its name in the enclosing scope, before the function scope opens
+        if it.r#type == FunctionType::FunctionDeclaration
+            && let Some(id) = &it.id
+        {
+            self.record_binding(id, BindingKind::Function, None, self.current_frame());
+            self.declared_name = Some(id.span);
+        }
+        self.with_binding_context((BindingKind::Other, None), |this| walk::walk_function(this, it, flags));
+    }
+
+    fn visit_class(&mut self, it: &Class<'a>) {
+        if it.is_declaration()
+            && let Some(id) = &it.id
+        {
+            self.record_binding(id, BindingKind::Class, Some(id.span.end), self.current_frame());
+            self.declared_name = Some(id.span);
+        }
+        self.with_binding_context((BindingKind::Other, None), |this| walk::walk_class(this, it));
+    }
+
+    fn visit_class_body(&mut self, it: &ClassBody<'a>) {
+        // Field initializers and methods run when the class is instantiated or called
+        let index = self.frames.len();
+        self.frames.push(Frame {
+            parent: self.stack.last().copied(),
+            is_var_scope: false,
+            is_deferred: true,
+        });
+        self.stack.push(index);
+        walk::walk_class_body(self, it);
+        self.stack.pop();
+    }
+
+    fn visit_formal_parameter(&mut self, it: &FormalParameter<'a>) {
+        self.with_binding_context((BindingKind::Parameter, None), |this| walk::walk_formal_parameter(this, it));
+    }
+
+    fn visit_catch_parameter(&mut self, it: &CatchParameter<'a>) {
+        self.with_binding_context((BindingKind::CatchParameter, None), |this| {
+            walk::walk_catch_parameter(this, it)
+        });
+    }
+
+    fn visit_import_declaration(&mut self, it: &ImportDeclaration<'a>) {
+        self.with_binding_context((BindingKind::Import, None), |this| walk::walk_import_declaration(this, it));
+    }
+
+    fn visit_call_expression(&mut self, it: &CallExpression<'a>) {
+        if let Expression::Identifier(callee) = &it.callee
+            && callee.name == "eval"
+        {
+            self.eval_calls.push(it.span);
+        }
+        walk::walk_call_expression(self, it);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::{parse_js_in, Allocator, ParserConfig};
+
+    fn index(source: &str) -> BindingIndex {
+        let allocator = Allocator::default();
+        let result = parse_js_in(source, "test.js", &allocator, &ParserConfig::default());
+        assert!(result.errors.is_empty(), "{:?}", result.errors);
+        BindingIndex::build(&result.program)
+    }
+
+    fn unused(index: &BindingIndex) -> Vec<&str> {
+        index.bindings.iter().filter(|b| !b.used).map(|b| b.name.as_str()).collect()
+    }
+
+    #[test]
+    fn test_resolves_references_through_scopes() {
+        let index = index("let a = 1; var b; function f(p) { return a + p; } { let a; } b = 2;");
+
+        assert_eq!(unused(&index), vec!["f", "a"]);
+        assert!(index.tdz_violations.is_empty());
+    }
+
+    #[test]
+    fn test_detects_tdz_violations() {
+        let index = index("console.log(x); let x = x; { y; const y = 1; } class C {} new C();");
+        let names: Vec<&str> = index.tdz_violations.iter().map(|v| v.name.as_str()).collect();
+
+        assert_eq!(names, vec!["x", "x", "y"]);
+        assert_eq!(index.tdz_violations[0].span, Span::new(12, 13));
+    }
+
+    #[test]
+    fn test_deferred_references_are_not_tdz_violations() {
+        let index = index("function f() { return later; } const g = () => later; class K { m() { return later; } } let later = 1;");
+        assert!(index.tdz_violations.is_empty());
+    }
+
+    #[test]
+    fn test_var_declarations_bind_in_function_scope() {
+        let index = index("function f() { { var inner = 1; } return inner; } f();");
+        assert!(unused(&index).is_empty());
+    }
+
+    #[test]
+    fn test_records_eval_calls() {
+        let index = index("function f() { eval('1'); } f(); obj.eval('2');");
+        assert_eq!(index.eval_calls, vec![Span::new(15, 24)]);
+    }
+}
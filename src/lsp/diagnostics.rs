@@ -0,0 +1,346 @@
+//! # Document Diagnostics
+//!
+//! Turns parse errors and analyzer findings into LSP diagnostics for a single document.
+//!
+//! ## Reported Problems
+//!
+//! - **Syntax errors** (error) - reported alone, since no analysis runs on a broken tree
+//! - **Unused bindings** (warning, `Unnecessary` tag) - declarations the analyzer found
+//!   no references to, skipping exports, parameters and scopes made unsafe by `eval`
+//! - **Redeclarations** (error) - names the analyzer rejects as declared twice in a
+//!   scope, reported alone
+//! - **TDZ violations** (error) - `let`/`const`/`class` bindings read before their
+//!   declaration completes
+//! - **Unsafe scopes** (warning) - direct `eval` calls, which disable renaming and
+//!   dead-code removal for every enclosing scope
+
+use std::collections::HashMap;
+
+use oxc_span::Span;
+use serde::{Deserialize, Serialize};
+
+use crate::analyzer::{self, SemanticAnalysis, SymbolType, UnsafeReason};
+use crate::lsp::bindings::{BindingIndex, BindingKind};
+use crate::parser::{self, Allocator, ParseError, ParserConfig};
+
+/// Value of the `source` field on every diagnostic
+pub const DIAGNOSTIC_SOURCE: &str = "rjs";
+
+/// Diagnostic severity (LSP `DiagnosticSeverity`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Severity {
+    Error = 1,
+    Warning = 2,
+}
+
+impl Serialize for Severity {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_u8(*self as u8)
+    }
+}
+
+/// Zero-based position with the character offset in UTF-16 code units
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Position {
+    pub line: u32,
+    pub character: u32,
+}
+
+/// Half-open range between two positions
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Range {
+    pub start: Position,
+    pub end: Position,
+}
+
+/// A single LSP diagnostic
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Diagnostic {
+    pub range: Range,
+    pub severity: Severity,
+    /// Stable identifier of the problem kind (e.g. `unused-binding`)
+    pub code: &'static str,
+    pub source: &'static str,
+    pub message: String,
+    /// LSP `DiagnosticTag` values (`1` = unnecessary code)
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<u8>,
+}
+
+/// `DiagnosticTag.Unnecessary`, rendered faded by most editors
+const TAG_UNNECESSARY: u8 = 1;
+
+impl Diagnostic {
+    fn new(range: Range, severity: Severity, code: &'static str, message: String) -> Self {
+        Self {
+            range,
+            severity,
+            code,
+            source: DIAGNOSTIC_SOURCE,
+            message,
+            tags: Vec::new(),
+        }
+    }
+}
+
+/// Maps byte offsets in a document to LSP positions
+#[derive(Debug)]
+pub struct LineIndex<'a> {
+    source: &'a str,
+    /// Byte offset of the first character of each line
+    line_starts: Vec<u32>,
+}
+
+impl<'a> LineIndex<'a> {
+    pub fn new(source: &'a str) -> Self {
+        let line_starts = std::iter::once(0)
+            .chain(source.match_indices('\n').map(|(offset, _)| offset as u32 + 1))
+            .collect();
+        Self { source, line_starts }
+    }
+
+    /// Converts a byte offset to a position, clamping offsets past the end of the document
+    pub fn position(&self, offset: u32) -> Position {
+        let offset = offset.min(self.source.len() as u32);
+        let line = self.line_starts.partition_point(|&start| start <= offset) - 1;
+        let line_start = self.line_starts[line] as usize;
+
+        let mut end = offset as usize;
+        while !self.source.is_char_boundary(end) {
+            end -= 1;
+        }
+        let character = self.source[line_start..end].chars().map(char::len_utf16).sum::<usize>();
+
+        Position {
+            line: line as u32,
+            character: character as u32,
+        }
+    }
+
+    pub fn range(&self, span: Span) -> Range {
+        Range {
+            start: self.position(span.start),
+            end: self.position(span.end),
+        }
+    }
+}
+
+/// Computes all diagnostics for a document
+///
+/// # Arguments
+///
+/// * `source` - Full text of the document
+///
+/// # Returns
+///
+/// Returns diagnostics in source order within each category, or a single error
+/// diagnostic if no thread could be started to compute them
+pub fn diagnose(source: &str) -> Vec<Diagnostic> {
+    let max_depth = ParserConfig::default().max_depth;
+    parser::depth::with_stack_for(max_depth, || diagnose_on_current_thread(source)).unwrap_or_else(|error| {
+        let message = format!("Failed to start the diagnostics thread: {}", error);
+        vec![Diagnostic::new(Range::default(), Severity::Error, "internal-error", message)]
+    })
+}
+
+/// Runs [`diagnose`] on the current thread
+fn diagnose_on_current_thread(source: &str) -> Vec<Diagnostic> {
+    let index = LineIndex::new(source);
+    let allocator = Allocator::default();
+    let parsed = parser::parse_js_in(source, "document.js", &allocator, &ParserConfig::default());
+
+    if !parsed.errors.is_empty() {
+        return parsed.errors.iter().map(|error| syntax_diagnostic(error, &index)).collect();
+    }
+
+    let bindings = BindingIndex::build(&parsed.program);
+    let config = analyzer::AnalyzerConfig { names: parsed.binding_names(), ..analyzer::AnalyzerConfig::default() };
+    let analysis = match parsed.to_owned_ast().map(|ast| analyzer::analyze_ast(&ast, &config)) {
+        Some(Ok(analysis)) => analysis,
+        Some(Err(error @ analyzer::AnalysisError::Redeclaration { .. })) => return vec![redeclaration_diagnostic(&error, &index)],
+        _ => return Vec::new(),
+    };
+
+    let mut diagnostics = unused_binding_diagnostics(&analysis, &bindings, &index);
+
+    diagnostics.extend(bindings.tdz_violations.iter().map(|violation| {
+        Diagnostic::new(
+            index.range(violation.span),
+            Severity::Error,
+            "tdz-violation",
+            format!("'{}' is used before its declaration (temporal dead zone)", violation.name),
+        )
+    }));
+
+    let has_eval_scope = analysis
+        .semantic_flags
+        .unsafe_scopes
+        .values()
+        .any(|reason| matches!(reason, UnsafeReason::EvalUsage));
+    if has_eval_scope {
+        diagnostics.extend(bindings.eval_calls.iter().map(|&span| {
+            Diagnostic::new(
+                index.range(span),
+                Severity::Warning,
+                "unsafe-eval",
+                "Direct eval prevents renaming and dead-code removal in all enclosing scopes".to_string(),
+            )
+        }));
+    }
+
+    diagnostics
+}
+
+/// Converts a redeclaration the analyzer rejected, which like a syntax error stops the
+/// analysis
+fn redeclaration_diagnostic(error: &analyzer::AnalysisError, index: &LineIndex) -> Diagnostic {
+    let (range, message) = match error {
+        analyzer::AnalysisError::Redeclaration { identifier, reason, span } => (
+            span.as_ref().map(|span| index.range(Span::new(span.start, span.end))).unwrap_or_default(),
+            format!("'{}' has already been declared: {}", identifier, reason),
+        ),
+        other => (Range::default(), other.to_string()),
+    };
+    Diagnostic::new(range, Severity::Error, "redeclaration", message)
+}
+
+/// Converts a parse error, falling back to its line/column when it has no span
+fn syntax_diagnostic(error: &ParseError, index: &LineIndex) -> Diagnostic {
+    let (range, message) = match error {
+        ParseError::SyntaxError { message, span: Some(span), .. } => {
+            (index.range(Span::new(span.start, span.end)), message.clone())
+        }
+        ParseError::SyntaxError { message, line, column, .. } => {
+            let position = Position {
+                line: line.saturating_sub(1),
+                character: column.saturating_sub(1),
+            };
+            (Range { start: position, end: position }, message.clone())
+        }
+        other => (Range::default(), other.to_string()),
+    };
+
+    Diagnostic::new(range, Severity::Error, "syntax-error", message)
+}
+
+/// Reports declarations that both the analyzer and the binding resolver consider unused
+///
+/// The analyzer decides *whether* a symbol is unused (exports and unsafe scopes
+/// included); the resolver supplies the source spans, matched per name in source order.
+fn unused_binding_diagnostics(analysis: &SemanticAnalysis, bindings: &BindingIndex, index: &LineIndex) -> Vec<Diagnostic> {
+    let mut unused_per_name: HashMap<&str, usize> = HashMap::new();
+    for symbol in analysis.symbol_table.symbols.values() {
+        let reportable = matches!(
+            symbol.symbol_type,
+            SymbolType::Variable { .. } | SymbolType::Function | SymbolType::Class | SymbolType::Import
+        );
+        if reportable
+            && symbol.references.is_empty()
+            && !symbol.is_exported
+            && !analysis.semantic_flags.unsafe_scopes.contains_key(&symbol.scope_id)
+        {
+            *unused_per_name.entry(analysis.symbol_table.name(symbol)).or_default() += 1;
+        }
+    }
+
+    bindings
+        .bindings
+        .iter()
+        .filter(|binding| {
+            !binding.used
+                && !matches!(
+                    binding.kind,
+                    BindingKind::Parameter | BindingKind::CatchParameter | BindingKind::Other
+                )
+        })
+        .filter(|binding| match unused_per_name.get_mut(binding.name.as_str()) {
+            Some(remaining) if *remaining > 0 => {
+                *remaining -= 1;
+                true
+            }
+            _ => false,
+        })
+        .map(|binding| {
+            let mut diagnostic = Diagnostic::new(
+                index.range(binding.span),
+                Severity::Warning,
+                "unused-binding",
+                format!("'{}' is declared but never used", binding.name),
+            );
+            diagnostic.tags.push(TAG_UNNECESSARY);
+            diagnostic
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn codes(diagnostics: &[Diagnostic]) -> Vec<&str> {
+        diagnostics.iter().map(|d| d.code).collect()
+    }
+
+    #[test]
+    fn test_line_index_uses_utf16_columns() {
+        let source = "let a;\nconst s = \"é😀\"; x";
+        let index = LineIndex::new(source);
+
+        assert_eq!(index.position(4), Position { line: 0, character: 4 });
+        let x = source.rfind('x').unwrap() as u32;
+        assert_eq!(index.position(x), Position { line: 1, character: 17 });
+        assert_eq!(index.position(u32::MAX).line, 1);
+    }
+
+    #[test]
+    fn test_syntax_errors_are_reported_alone() {
+        let diagnostics = diagnose("let unused = 1;\nlet = ;");
+
+        assert_eq!(codes(&diagnostics), vec!["syntax-error"]);
+        assert_eq!(diagnostics[0].severity, Severity::Error);
+        assert_eq!(diagnostics[0].range.start.line, 1);
+    }
+
+    #[test]
+    fn test_reports_unused_bindings() {
+        let diagnostics = diagnose("let used = 1;\nlet unused = 2;\nconsole.log(used);");
+
+        assert_eq!(codes(&diagnostics), vec!["unused-binding"]);
+        assert_eq!(diagnostics[0].message, "'unused' is declared but never used");
+        assert_eq!(diagnostics[0].tags, vec![TAG_UNNECESSARY]);
+        assert_eq!(diagnostics[0].range.start, Position { line: 1, character: 4 });
+    }
+
+    #[test]
+    fn test_reports_redeclarations_alone() {
+        let diagnostics = diagnose("var total = 0;\nlet unused;\nlet total = 1;");
+
+        assert_eq!(codes(&diagnostics), vec!["redeclaration"]);
+        assert_eq!(diagnostics[0].range.start, Position { line: 2, character: 4 });
+        assert!(diagnostics[0].message.contains("`let` declaration conflicts with the earlier `var`"), "{}", diagnostics[0].message);
+    }
+
+    #[test]
+    fn test_reports_tdz_violations() {
+        let diagnostics = diagnose("console.log(value);\nlet value = 1;");
+
+        assert_eq!(codes(&diagnostics), vec!["tdz-violation"]);
+        assert_eq!(diagnostics[0].range.start, Position { line: 0, character: 12 });
+    }
+
+    #[test]
+    fn test_reports_eval_and_suppresses_unused_in_unsafe_scopes() {
+        let diagnostics = diagnose("function run(code) { let hidden = 1; return eval(code); }\nrun('hidden');");
+        assert_eq!(codes(&diagnostics), vec!["unsafe-eval"]);
+    }
+
+    #[test]
+    fn test_diagnostic_serializes_to_lsp_shape() {
+        let diagnostics = diagnose("let unused = 1;");
+        let json = serde_json::to_value(&diagnostics[0]).unwrap();
+
+        assert_eq!(json["severity"], 2);
+        assert_eq!(json["source"], "rjs");
+        assert_eq!(json["range"]["end"], serde_json::json!({ "line": 0, "character": 10 }));
+    }
+}
@@ -0,0 +1,410 @@
+//! # Language Server
+//!
+//! Language Server Protocol mode (`rjs-compiler --lsp`) that publishes analyzer
+//! diagnostics for open documents. Messages use the LSP base protocol over stdio:
+//! each JSON-RPC message is preceded by a `Content-Length` header.
+//!
+//! ## Capabilities
+//!
+//! - Full-text document synchronisation (`didOpen`, `didChange`, `didClose`)
+//! - `textDocument/publishDiagnostics` after every open and change, covering syntax
+//!   errors, unused bindings, TDZ violations and `eval` usage (see [`diagnostics`])
+//!
+//! Closing a document clears its diagnostics. Requests other than `initialize` and
+//! `shutdown` are answered with `MethodNotFound`; unknown notifications are ignored.
+
+pub mod bindings;
+pub mod diagnostics;
+
+use std::collections::HashMap;
+use std::io::{self, BufRead, Write};
+
+use serde::Deserialize;
+use serde_json::{json, Value};
+
+use diagnostics::diagnose;
+
+/// JSON-RPC and LSP error codes
+const PARSE_ERROR: i64 = -32700;
+const INVALID_REQUEST: i64 = -32600;
+const METHOD_NOT_FOUND: i64 = -32601;
+const INVALID_PARAMS: i64 = -32602;
+const SERVER_NOT_INITIALIZED: i64 = -32002;
+
+/// `TextDocumentSyncKind.Full`: clients send the whole document on every change
+const TEXT_DOCUMENT_SYNC_FULL: u8 = 1;
+
+/// Incoming JSON-RPC request or notification
+#[derive(Debug, Deserialize)]
+struct Message {
+    #[serde(default)]
+    id: Option<Value>,
+    method: String,
+    #[serde(default)]
+    params: Value,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct TextDocumentItem {
+    uri: String,
+    text: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct TextDocumentIdentifier {
+    uri: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct ContentChange {
+    text: String,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct DidOpenParams {
+    text_document: TextDocumentItem,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct DidChangeParams {
+    text_document: TextDocumentIdentifier,
+    content_changes: Vec<ContentChange>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct DidCloseParams {
+    text_document: TextDocumentIdentifier,
+}
+
+/// Stdio language server holding the text of open documents
+#[derive(Debug, Default)]
+pub struct LanguageServer {
+    /// Open documents by URI
+    documents: HashMap<String, String>,
+    /// Set once `initialize` has been answered
+    initialized: bool,
+    /// Set once a `shutdown` request has been handled
+    shutdown_requested: bool,
+    /// Set when the `exit` notification arrives
+    exit_requested: bool,
+}
+
+impl LanguageServer {
+    /// Creates a server with no open documents
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Serves LSP messages from `input` until end of input or an `exit` notification
+    ///
+    /// # Returns
+    ///
+    /// Returns `true` if the client requested `shutdown` before exiting, which the
+    /// protocol maps to exit code 0
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if reading from `input` or writing to `output` fails, or if a
+    /// message header is malformed
+    pub fn serve<R: BufRead, W: Write>(&mut self, mut input: R, mut output: W) -> io::Result<bool> {
+        while let Some(message) = read_message(&mut input)? {
+            for outgoing in self.handle_message(&message) {
+                write_message(&mut output, &outgoing)?;
+            }
+
+            if self.exit_requested {
+                break;
+            }
+        }
+        Ok(self.shutdown_requested)
+    }
+
+    /// Handles a single JSON-RPC message
+    ///
+    /// # Returns
+    ///
+    /// Returns the messages to send back: a response for requests, plus any
+    /// `publishDiagnostics` notifications
+    pub fn handle_message(&mut self, message: &str) -> Vec<String> {
+        let message: Message = match serde_json::from_str::<Value>(message) {
+            Ok(value) => match serde_json::from_value(value) {
+                Ok(message) => message,
+                Err(e) => return vec![error_response(Value::Null, INVALID_REQUEST, &e.to_string())],
+            },
+            Err(e) => return vec![error_response(Value::Null, PARSE_ERROR, &e.to_string())],
+        };
+
+        match message.id {
+            Some(id) => vec![match self.handle_request(&message.method, message.params) {
+                Ok(result) => json!({ "jsonrpc": "2.0", "id": id, "result": result }).to_string(),
+                Err((code, error)) => error_response(id, code, &error),
+            }],
+            None => self.handle_notification(&message.method, message.params),
+        }
+    }
+
+    /// Answers a request
+    fn handle_request(&mut self, method: &str, _params: Value) -> Result<Value, (i64, String)> {
+        if !self.initialized && method != "initialize" {
+            return Err((SERVER_NOT_INITIALIZED, "Server not initialized".to_string()));
+        }
+
+        match method {
+            "initialize" => {
+                self.initialized = true;
+                Ok(json!({
+                    "capabilities": { "textDocumentSync": TEXT_DOCUMENT_SYNC_FULL },
+                    "serverInfo": { "name": "rjs-compiler", "version": env!("CARGO_PKG_VERSION") },
+                }))
+            }
+            "shutdown" => {
+                self.shutdown_requested = true;
+                Ok(Value::Null)
+            }
+            _ => Err((METHOD_NOT_FOUND, format!("Unknown method: {}", method))),
+        }
+    }
+
+    /// Applies a notification, returning the diagnostics to publish
+    fn handle_notification(&mut self, method: &str, params: Value) -> Vec<String> {
+        match method {
+            "exit" => {
+                self.exit_requested = true;
+                Vec::new()
+            }
+            "textDocument/didOpen" => match serde_json::from_value::<DidOpenParams>(params) {
+                Ok(params) => {
+                    let document = params.text_document;
+                    let notification = publish_diagnostics(&document.uri, &document.text);
+                    self.documents.insert(document.uri, document.text);
+                    vec![notification]
+                }
+                Err(e) => vec![log_message(INVALID_PARAMS, &e.to_string())],
+            },
+            "textDocument/didChange" => match serde_json::from_value::<DidChangeParams>(params) {
+                Ok(params) => {
+                    // With full synchronisation the last change holds the complete text
+                    let Some(change) = params.content_changes.into_iter().last() else {
+                        return Vec::new();
+                    };
+                    let uri = params.text_document.uri;
+                    let notification = publish_diagnostics(&uri, &change.text);
+                    self.documents.insert(uri, change.text);
+                    vec![notification]
+                }
+                Err(e) => vec![log_message(INVALID_PARAMS, &e.to_string())],
+            },
+            "textDocument/didClose" => match serde_json::from_value::<DidCloseParams>(params) {
+                Ok(params) => {
+                    self.documents.remove(&params.text_document.uri);
+                    vec![diagnostics_notification(&params.text_document.uri, Value::Array(Vec::new()))]
+                }
+                Err(e) => vec![log_message(INVALID_PARAMS, &e.to_string())],
+            },
+            // `initialized`, `didSave`, `$/cancelRequest` and friends need no action
+            _ => Vec::new(),
+        }
+    }
+
+    /// Returns the current text of an open document
+    pub fn document(&self, uri: &str) -> Option<&str> {
+        self.documents.get(uri).map(String::as_str)
+    }
+}
+
+/// Reads one base-protocol message, returning `None` at end of input
+fn read_message<R: BufRead>(input: &mut R) -> io::Result<Option<String>> {
+    let mut content_length = None;
+
+    loop {
+        let mut header = String::new();
+        if input.read_line(&mut header)? == 0 {
+            return Ok(None);
+        }
+
+        let header = header.trim_end();
+        if header.is_empty() {
+            if content_length.is_some() {
+                break;
+            }
+            continue;
+        }
+
+        if let Some((name, value)) = header.split_once(':')
+            && name.eq_ignore_ascii_case("Content-Length")
+        {
+            let length = value.trim().parse::<usize>().map_err(|e| {
+                io::Error::new(io::ErrorKind::InvalidData, format!("Invalid Content-Length: {}", e))
+            })?;
+            content_length = Some(length);
+        }
+    }
+
+    let mut body = vec![0; content_length.unwrap_or_default()];
+    input.read_exact(&mut body)?;
+    String::from_utf8(body)
+        .map(Some)
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+}
+
+/// Writes one base-protocol message and flushes it
+fn write_message<W: Write>(output: &mut W, message: &str) -> io::Result<()> {
+    write!(output, "Content-Length: {}\r\n\r\n{}", message.len(), message)?;
+    output.flush()
+}
+
+fn publish_diagnostics(uri: &str, text: &str) -> String {
+    let diagnostics = serde_json::to_value(diagnose(text)).unwrap_or_else(|_| Value::Array(Vec::new()));
+    diagnostics_notification(uri, diagnostics)
+}
+
+fn diagnostics_notification(uri: &str, diagnostics: Value) -> String {
+    json!({
+        "jsonrpc": "2.0",
+        "method": "textDocument/publishDiagnostics",
+        "params": { "uri": uri, "diagnostics": diagnostics },
+    })
+    .to_string()
+}
+
+/// Reports a malformed notification through `window/logMessage` (notifications have no response)
+fn log_message(code: i64, message: &str) -> String {
+    json!({
+        "jsonrpc": "2.0",
+        "method": "window/logMessage",
+        "params": { "type": 1, "message": format!("Invalid params ({}): {}", code, message) },
+    })
+    .to_string()
+}
+
+fn error_response(id: Value, code: i64, message: &str) -> String {
+    json!({
+        "jsonrpc": "2.0",
+        "id": id,
+        "error": { "code": code, "message": message },
+    })
+    .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn frame(message: &Value) -> String {
+        let body = message.to_string();
+        format!("Content-Length: {}\r\n\r\n{}", body.len(), body)
+    }
+
+    fn parse(messages: &[String]) -> Vec<Value> {
+        messages.iter().map(|m| serde_json::from_str(m).unwrap()).collect()
+    }
+
+    fn initialized_server() -> LanguageServer {
+        let mut server = LanguageServer::new();
+        server.handle_message(r#"{"jsonrpc":"2.0","id":1,"method":"initialize","params":{}}"#);
+        server
+    }
+
+    #[test]
+    fn test_initialize_advertises_full_sync() {
+        let mut server = LanguageServer::new();
+        let responses = parse(&server.handle_message(r#"{"jsonrpc":"2.0","id":1,"method":"initialize","params":{}}"#));
+
+        assert_eq!(responses[0]["result"]["capabilities"]["textDocumentSync"], 1);
+        assert_eq!(responses[0]["result"]["serverInfo"]["name"], "rjs-compiler");
+    }
+
+    #[test]
+    fn test_requests_before_initialize_are_rejected() {
+        let mut server = LanguageServer::new();
+        let responses = parse(&server.handle_message(r#"{"jsonrpc":"2.0","id":7,"method":"shutdown"}"#));
+
+        assert_eq!(responses[0]["error"]["code"], SERVER_NOT_INITIALIZED);
+        assert_eq!(responses[0]["id"], 7);
+    }
+
+    #[test]
+    fn test_open_change_close_publish_diagnostics() {
+        let mut server = initialized_server();
+        let uri = "file:///app.js";
+
+        let open = json!({
+            "jsonrpc": "2.0",
+            "method": "textDocument/didOpen",
+            "params": { "textDocument": { "uri": uri, "languageId": "javascript", "version": 1, "text": "let unused = 1;" } },
+        });
+        let published = parse(&server.handle_message(&open.to_string()));
+        assert_eq!(published[0]["method"], "textDocument/publishDiagnostics");
+        assert_eq!(published[0]["params"]["diagnostics"][0]["code"], "unused-binding");
+
+        let change = json!({
+            "jsonrpc": "2.0",
+            "method": "textDocument/didChange",
+            "params": {
+                "textDocument": { "uri": uri, "version": 2 },
+                "contentChanges": [{ "text": "let used = 1; console.log(used);" }],
+            },
+        });
+        let published = parse(&server.handle_message(&change.to_string()));
+        assert_eq!(published[0]["params"]["diagnostics"], json!([]));
+        assert_eq!(server.document(uri), Some("let used = 1; console.log(used);"));
+
+        let close = json!({ "jsonrpc": "2.0", "method": "textDocument/didClose", "params": { "textDocument": { "uri": uri } } });
+        let published = parse(&server.handle_message(&close.to_string()));
+        assert_eq!(published[0]["params"]["diagnostics"], json!([]));
+        assert_eq!(server.document(uri), None);
+    }
+
+    #[test]
+    fn test_unknown_messages() {
+        let mut server = initialized_server();
+
+        let responses = parse(&server.handle_message(r#"{"jsonrpc":"2.0","id":2,"method":"textDocument/hover"}"#));
+        assert_eq!(responses[0]["error"]["code"], METHOD_NOT_FOUND);
+
+        assert!(server.handle_message(r#"{"jsonrpc":"2.0","method":"$/setTrace"}"#).is_empty());
+
+        let responses = parse(&server.handle_message("{not json"));
+        assert_eq!(responses[0]["error"]["code"], PARSE_ERROR);
+    }
+
+    #[test]
+    fn test_serve_uses_content_length_framing() {
+        let input = [
+            frame(&json!({ "jsonrpc": "2.0", "id": 1, "method": "initialize", "params": {} })),
+            frame(&json!({ "jsonrpc": "2.0", "method": "initialized", "params": {} })),
+            frame(&json!({
+                "jsonrpc": "2.0",
+                "method": "textDocument/didOpen",
+                "params": { "textDocument": { "uri": "file:///é.js", "text": "let = ;" } },
+            })),
+            frame(&json!({ "jsonrpc": "2.0", "id": 2, "method": "shutdown" })),
+            frame(&json!({ "jsonrpc": "2.0", "method": "exit" })),
+            frame(&json!({ "jsonrpc": "2.0", "id": 3, "method": "initialize" })),
+        ]
+        .concat();
+
+        let mut output = Vec::new();
+        let clean_exit = LanguageServer::new().serve(input.as_bytes(), &mut output).unwrap();
+        assert!(clean_exit);
+
+        let output = String::from_utf8(output).unwrap();
+        let mut rest = output.as_str();
+        let mut messages = Vec::new();
+        while let Some(body) = rest.strip_prefix("Content-Length: ") {
+            let (length, body) = body.split_once("\r\n\r\n").unwrap();
+            let length: usize = length.parse().unwrap();
+            messages.push(serde_json::from_str::<Value>(&body[..length]).unwrap());
+            rest = &body[length..];
+        }
+
+        assert_eq!(messages.len(), 3);
+        assert_eq!(messages[1]["params"]["uri"], "file:///é.js");
+        assert_eq!(messages[1]["params"]["diagnostics"][0]["code"], "syntax-error");
+        assert_eq!(messages[2]["id"], 2);
+    }
+}
@@ -15,19 +15,21 @@
 //!
 //! ```bash
 //! rjs-compiler [OPTIONS] <FILE>
+//! rjs-compiler analyze <FILE> [--json]
+//! rjs-compiler ast <FILE> [--format json|pretty|dot]
 //! ```
 //!
 //! For more information, run `rjs-compiler --help`.
 
-use std::path::PathBuf;
+use std::collections::HashMap;
+use std::io;
+use std::path::{Path, PathBuf};
 use std::process;
+use std::time::Instant;
 
 use clap::{Arg, Command};
 
-mod parser;
-mod analyzer;
-mod transformer;
-mod generator;
+use rjs_compiler::{analyzer, encoding, generator, logging, lsp, parser, server, target, transformer};
 
 /// Application version constant
 const VERSION: &str = "0.1.0";
@@ -35,15 +37,218 @@ const VERSION: &str = "0.1.0";
 /// Application name constant
 const APP_NAME: &str = "rjs-compiler";
 
+/// Extension of output files named after their input (`--out-ext`)
+const DEFAULT_OUT_EXT: &str = ".min.js";
+
 /// Configuration structure for the compiler
 #[derive(Debug, Clone)]
 struct CompilerConfig {
-    /// Input file path to compile
-    input_file: Option<PathBuf>,
+    /// Input file paths to compile; with more than one, each is written to
+    /// `<name>.min.js` next to it
+    input_files: Vec<PathBuf>,
     /// Output file path for minified code
     output_file: Option<PathBuf>,
-    /// Enable verbose output
+    /// Print the phase results; set once logging is initialized, when `debug` events of
+    /// the binary are enabled (`-v` or a `--log-filter` that enables them)
     verbose: bool,
+    /// Verbosity, format and filters of the log output
+    log: logging::LogConfig,
+    /// Print a per-phase timing breakdown
+    timing: bool,
+    /// Maximum generated output size in bytes (`None` for unlimited)
+    max_output_size: Option<usize>,
+    /// Maximum in-memory output buffer size in bytes (`None` for unlimited)
+    max_memory_usage: Option<usize>,
+    /// Stream generated code to the output file instead of buffering it
+    stream: bool,
+    /// Run as a JSON-RPC server over stdio instead of compiling a file
+    serve: bool,
+    /// Run as a Language Server Protocol diagnostics provider over stdio
+    lsp: bool,
+    /// Subcommand to run instead of compiling `input_files`
+    command: Option<CliCommand>,
+    /// Shared-library transform plugins to load, in registration order
+    plugins: Vec<PathBuf>,
+    /// Transformer passes selected with `--no-mangle`, `--no-dce`, `--drop`, `--drop-console`,
+    /// `--no-collapse-vars`, `--no-inline-captures`, `--no-if-return`, `--drop-debugger`,
+    /// `--hoist-funs`, `--hoist-vars`, `--no-inline`, `--mangle-props`,
+    /// `--mangle-props-debug`, `--no-inline-enums`, `--dedupe-strings`, `--seed`,
+    /// `--threads` and `--aggressive`
+    transformer: transformer::TransformerConfig,
+    /// File to write the `--explain` report to
+    explain: Option<PathBuf>,
+    /// JSON file mapping original exported names to the `--module-exports mangle` names
+    export_map: Option<PathBuf>,
+    /// JSON file listing the exports of the input and output and how they differ
+    api_report: Option<PathBuf>,
+    /// Format of the `--explain` report
+    explain_format: transformer::explain::ExplainFormat,
+    /// Format to print transformer warnings in on stderr, with their source locations
+    diagnostics_format: Option<transformer::diagnostics::DiagnosticFormat>,
+    /// Add a content hash to the output file name and record it in `manifest.json`
+    hash_filenames: bool,
+    /// Target environments from `--target` (`None` to look for a `.browserslistrc`)
+    target: Option<target::Targets>,
+    /// JSON file listing the polyfills each output file needs
+    polyfill_report: Option<PathBuf>,
+    /// Module to import the needed polyfills from, e.g. `core-js/es`
+    polyfill_module: Option<String>,
+    /// Print readable output that keeps the JSDoc blocks of exported functions and classes
+    keep_jsdoc: bool,
+    /// Keep all comments next to the statements they document
+    keep_comments: bool,
+    /// Skip analysis and transformation and pretty-print the parsed code
+    format_only: bool,
+    /// Quote character for string literals
+    quote: generator::QuoteStrategy,
+    /// Where semicolons are printed
+    semicolons: generator::SemicolonStrategy,
+    /// Indentation unit of `--format-only` output
+    indent: generator::IndentStyle,
+    /// Column limit for wrapping readable and formatted output
+    max_line_len: Option<usize>,
+    /// Module system of the output, from `--output-format`
+    module_format: generator::module_format::ModuleFormat,
+    /// Global variable to expose the exports of IIFE and UMD output on
+    global_name: Option<String>,
+    /// Convert between `import()` and `require()` to match the module system of the output
+    module_interop: bool,
+    /// Compile the worker entries the inputs start as separate outputs, from `--workers`
+    workers: bool,
+    /// Copy the declaration files of the inputs next to their outputs, from `--declarations`
+    declarations: bool,
+    /// Append a comment with the tool version and the config and input hashes
+    build_info: bool,
+    /// Fail with exit code 6 when there are more warnings than this (`Some(0)` for
+    /// `--warnings-as-errors`)
+    max_warnings: Option<usize>,
+    /// Show a progress bar while compiling several files (off with `--no-progress`)
+    progress: bool,
+    /// Run the whole pipeline but write no files, only report what would be written
+    dry_run: bool,
+    /// Raw and gzip size limits of each output file, from `--max-size` and `--max-gzip-size`
+    size_budget: generator::size_budget::SizeBudget,
+    /// File to write the report of which declarations the output bytes came from
+    bundle_report: Option<PathBuf>,
+    /// Format of the `--bundle-report`
+    bundle_report_format: generator::bundle_report::BundleReportFormat,
+    /// Write every identifier, property and export rename to `<output>.names.json`
+    emit_name_map: bool,
+    /// Run the correctness checks on the inputs instead of compiling them
+    check: bool,
+    /// Deepest nesting of brackets, statements or expressions accepted in an input
+    max_depth: usize,
+    /// Convert UTF-16 inputs to UTF-8 instead of rejecting them
+    transcode_utf16: bool,
+    /// Stage-3 proposals accepted in the inputs, from `--proposal`
+    proposals: Vec<parser::proposals::Proposal>,
+    /// Accept and remove Flow type annotations, and drop type-only comments
+    strip_types: bool,
+    /// Overwrite each input with its output
+    in_place: bool,
+    /// Keep the previous contents of an overwritten output in `<output>.bak`
+    backup: bool,
+    /// Directory to write outputs to, mirroring the input directories under it
+    out_dir: Option<PathBuf>,
+    /// Extension replacing the input's in output file names, such as `.min.js`
+    out_ext: String,
+    /// Let outputs overwrite input files
+    force: bool,
+    /// Give each output the permissions and owner of its input
+    preserve_mode: bool,
+    /// Give each output the access and modification times of its input
+    preserve_times: bool,
+}
+
+/// Outcome of compiling one file, for the multi-file summary table
+#[derive(Debug, Clone)]
+struct FileSummary {
+    /// Where the output was written
+    output_path: PathBuf,
+    /// Size of the source
+    input_size:  usize,
+    /// Size of the generated code
+    output_size: usize,
+}
+
+/// Subcommands that run part of the pipeline for tooling and debugging
+#[derive(Debug, Clone)]
+enum CliCommand {
+    /// Run the parser and analyzer only and print the semantic model
+    Analyze {
+        /// File to analyze
+        file: PathBuf,
+        /// Print the full `SemanticAnalysis` as JSON instead of a summary
+        json: bool,
+    },
+    /// Parse only and print the AST
+    Ast {
+        /// File to parse
+        file: PathBuf,
+        /// Output format
+        format: parser::ast_dump::AstFormat,
+    },
+    /// Pretty-print minified code with its original names restored
+    Beautify {
+        /// Minified file to beautify
+        file: PathBuf,
+        /// Source map of the minified file, for the names it records
+        source_map: Option<PathBuf>,
+        /// Name map written by `--emit-name-map`
+        name_map: Option<PathBuf>,
+        /// File to write to instead of stdout
+        output: Option<PathBuf>,
+    },
+    /// Print the structural differences between the ASTs of two files
+    Diff {
+        /// File to compare from
+        before: PathBuf,
+        /// File to compare to
+        after: PathBuf,
+    },
+}
+
+/// Time spent in one phase of the compilation pipeline
+#[derive(Debug, Clone)]
+struct PhaseTiming {
+    /// Phase name shown in the timing report
+    name: String,
+    /// Elapsed time (in milliseconds)
+    time_ms: f64,
+    /// Nesting depth within the report (0 for top-level phases)
+    depth: usize,
+}
+
+/// Collects phase timings for the `--timing` report
+#[derive(Debug, Default)]
+struct TimingReport {
+    /// Recorded phases, in execution order
+    phases: Vec<PhaseTiming>,
+}
+
+impl TimingReport {
+    /// Records a top-level phase that started at `start`
+    fn record(&mut self, name: &str, start: Instant) {
+        self.phases.push(PhaseTiming {
+            name: name.to_string(),
+            time_ms: start.elapsed().as_secs_f64() * 1000.0,
+            depth: 0,
+        });
+    }
+
+    /// Records a nested entry with an already measured duration
+    fn record_nested(&mut self, name: &str, time_ms: f64) {
+        self.phases.push(PhaseTiming {
+            name: name.to_string(),
+            time_ms,
+            depth: 1,
+        });
+    }
+
+    /// Sum of all top-level phases
+    fn total_ms(&self) -> f64 {
+        self.phases.iter().filter(|p| p.depth == 0).map(|p| p.time_ms).sum()
+    }
 }
 
 /// Custom error types for the compiler
@@ -53,8 +258,64 @@ enum CompilerError {
     MissingInputFile,
     #[error("File not found: {0}")]
     FileNotFound(PathBuf),
+    #[error("Permission denied: {0}")]
+    PermissionDenied(PathBuf),
+    #[error("Cannot read {path}: {reason}")]
+    InvalidEncoding { path: PathBuf, reason: String },
     #[error("Parse error: {0}")]
     ParseError(String),
+    #[error("Invalid arguments: {0}")]
+    InvalidArguments(String),
+    #[error("{0}")]
+    TransformError(String),
+    #[error("I/O error: {0}")]
+    IoError(String),
+    #[error("Too many warnings: {count} (limit {max})")]
+    TooManyWarnings { count: usize, max: usize },
+    #[error("{0}")]
+    BudgetExceeded(String),
+    #[error("The programs differ in {0} statements")]
+    ProgramsDiffer(usize),
+    #[error("Checks found {0} errors")]
+    ChecksFailed(usize),
+    #[error("Internal compiler error: {0}")]
+    Internal(String),
+}
+
+impl CompilerError {
+    /// Process exit code, distinct for each kind of failure
+    ///
+    /// | Code | Failure |
+    /// |------|---------|
+    /// | 1 | `diff` found structural differences |
+    /// | 2 | Invalid command-line arguments |
+    /// | 3 | Syntax errors in the input, or code the compiler cannot represent yet |
+    /// | 4 | Analysis, transformation or generation failed, or the compiler panicked |
+    /// | 5 | A file could not be read or written |
+    /// | 6 | More warnings than `--max-warnings` allows |
+    /// | 7 | An output is over its `--max-size` or `--max-gzip-size` budget |
+    /// | 8 | `--check` found errors |
+    fn exit_code(&self) -> i32 {
+        match self {
+            Self::MissingInputFile | Self::InvalidArguments(_) => 2,
+            Self::ParseError(_) => 3,
+            Self::TransformError(_) | Self::Internal(_) => 4,
+            Self::FileNotFound(_) | Self::PermissionDenied(_) | Self::InvalidEncoding { .. } | Self::IoError(_) => 5,
+            Self::TooManyWarnings { .. } => 6,
+            Self::BudgetExceeded(_) => 7,
+            Self::ProgramsDiffer(_) => 1,
+            Self::ChecksFailed(_) => 8,
+        }
+    }
+}
+
+impl From<rjs_compiler::CompileError> for CompilerError {
+    fn from(error: rjs_compiler::CompileError) -> Self {
+        match error {
+            rjs_compiler::CompileError::Parse(message) => Self::ParseError(message),
+            error => Self::TransformError(error.to_string()),
+        }
+    }
 }
 
 /// Result type alias for compiler operations
@@ -65,12 +326,33 @@ type CompilerResult<T> = Result<T, CompilerError>;
 /// This function sets up command-line argument parsing, initializes the compiler
 /// configuration, and orchestrates the compilation process.
 fn main() {
-    if let Err(error) = run_compiler() {
+    let result = parse_command_line_arguments().and_then(|config| {
+        // Every phase recurses on the AST, so compile on a thread whose stack fits the
+        // deepest nesting `--max-depth` lets through rather than on the fixed main stack
+        let stack_size = parser::depth::stack_size_for(config.max_depth);
+        std::thread::Builder::new()
+            .name("compiler".to_string())
+            .stack_size(stack_size)
+            .spawn(move || run_compiler(config))
+            .map_err(|e| CompilerError::IoError(format!("Failed to start the compiler thread: {}", e)))?
+            .join()
+            .unwrap_or_else(|panic| Err(CompilerError::Internal(panic_message(panic.as_ref()))))
+    });
+    if let Err(error) = result {
         eprintln!("Error: {}", error);
-        process::exit(1);
+        process::exit(error.exit_code());
     }
 }
 
+/// The message a panic was raised with, for reporting it as an internal error
+fn panic_message(panic: &(dyn std::any::Any + Send)) -> String {
+    panic
+        .downcast_ref::<&str>()
+        .map(|message| message.to_string())
+        .or_else(|| panic.downcast_ref::<String>().cloned())
+        .unwrap_or_else(|| "the compiler panicked".to_string())
+}
+
 /// Main application logic separated from main() for better error handling.
 ///
 /// # Returns
@@ -81,13 +363,27 @@ fn main() {
 ///
 /// ```rust,no_run
 /// // This is called internally by main()
-/// match run_compiler() {
+/// match run_compiler(parse_command_line_arguments()?) {
 ///     Ok(()) => println!("Compilation successful"),
 ///     Err(e) => eprintln!("Compilation failed: {}", e),
 /// }
 /// ```
-fn run_compiler() -> CompilerResult<()> {
-    let config = parse_command_line_arguments()?;
+fn run_compiler(mut config: CompilerConfig) -> CompilerResult<()> {
+    // stdout carries the protocol in server modes, so nothing else may be printed
+    if config.serve {
+        return run_server();
+    }
+    if config.lsp {
+        return run_language_server();
+    }
+    logging::init(&config.log).map_err(CompilerError::InvalidArguments)?;
+    config.verbose = tracing::enabled!(tracing::Level::DEBUG);
+    if let Some(ref command) = config.command {
+        return run_subcommand(command, config.transcode_utf16);
+    }
+    if config.check {
+        return check_files(&config);
+    }
     
     display_welcome_message();
     
@@ -95,12 +391,588 @@ fn run_compiler() -> CompilerResult<()> {
         display_verbose_info(&config);
     }
     
-    match config.input_file {
-        Some(ref file_path) => compile_file(file_path, &config),
+    let workers = if config.workers { find_worker_entries(&config.input_files, &config)? } else { Vec::new() };
+    if workers.iter().any(|(_, entries)| !entries.is_empty()) {
+        config.input_files = workers.iter().map(|(file_path, _)| file_path.clone()).collect();
+        compile_files(&config.input_files, &workers, &config)?;
+    } else {
+        match config.input_files.as_slice() {
+            [] => {
+                display_usage_information();
+                return Err(CompilerError::MissingInputFile);
+            }
+            [file_path] => {
+                compile_file(file_path, &config)?;
+                tracing::info!("✅ Compilation completed successfully!");
+            }
+            file_paths => compile_files(file_paths, &[], &config)?,
+        }
+    }
+
+    if config.declarations && !config.dry_run {
+        write_declarations(&config)?;
+    }
+    Ok(())
+}
+
+/// Copies the declaration file next to each input (`util.d.ts` for `util.js`) next to
+/// its output, named for the output (`util.min.d.ts`), with `--declarations`
+///
+/// Relative specifiers naming an input are pointed at its output. Declaration files
+/// without a module of their own that those specifiers name (`./types.d.ts`) are
+/// copied as well, to the same place under `--out-dir`.
+///
+/// # Errors
+///
+/// Returns `CompilerError::IoError` if a declaration file cannot be read or written.
+fn write_declarations(config: &CompilerConfig) -> CompilerResult<()> {
+    let base = common_directory(&config.input_files);
+    let outputs: Vec<(PathBuf, PathBuf)> = config
+        .input_files
+        .iter()
+        .map(|input| (normalize_path(input), mapped_output_path(input, &base, config)))
+        .collect();
+    let mut declarations: Vec<(PathBuf, PathBuf)> = outputs
+        .iter()
+        .map(|(input, output)| (generator::declarations::declaration_path(input), generator::declarations::declaration_path(output)))
+        .filter(|(declaration, _)| declaration.is_file())
+        .collect();
+
+    let mut index = 0;
+    while index < declarations.len() {
+        let (declaration, output) = declarations[index].clone();
+        let source = std::fs::read_to_string(&declaration).map_err(|e| {
+            CompilerError::IoError(format!("Failed to read declarations '{}': {}", declaration.display(), e))
+        })?;
+        let directory = declaration.parent().unwrap_or(Path::new(""));
+        let output_directory = output.parent().unwrap_or(Path::new(""));
+        let rewritten = generator::declarations::rewrite_specifiers(&source, |specifier| {
+            if !specifier.starts_with("./") && !specifier.starts_with("../") {
+                return None;
+            }
+            let target = normalize_path(&directory.join(specifier));
+
+            // A module among the inputs: `./util.js` keeps its extension, while `./util`
+            // and `./util/index.js` reached as `./util` drop the output's
+            let module = outputs.iter().find(|(input, _)| *input == target).map(|(_, output)| (output, false)).or_else(|| {
+                [with_suffix(&target, ".js"), target.join("index.js")]
+                    .into_iter()
+                    .find_map(|candidate| outputs.iter().find(|(input, _)| *input == candidate))
+                    .map(|(_, output)| (output, true))
+            });
+            if let Some((module_output, extensionless)) = module {
+                let url = relative_url(output_directory, module_output);
+                let extension = module_output.extension().map(|extension| format!(".{}", extension.to_string_lossy()));
+                return Some(match extension {
+                    Some(extension) if extensionless => url.trim_end_matches(extension.as_str()).to_string(),
+                    _ => url,
+                });
+            }
+
+            // A declaration file of its own: `./types.d.ts`, `./types.js`, `./types` or
+            // `./types/index.d.ts` reached as `./types`
+            let suffix = [".d.ts", ".js"].into_iter().find(|suffix| specifier.ends_with(suffix)).unwrap_or("");
+            let target = normalize_path(&directory.join(&specifier[..specifier.len() - suffix.len()]));
+            let index = with_suffix(&target.join("index"), ".d.ts");
+            let found = [with_suffix(&target, ".d.ts"), index.clone()].into_iter().find(|candidate| candidate.is_file())?;
+            let found_output = match declarations.iter().find(|(known, _)| *known == found) {
+                Some((_, known_output)) => known_output.clone(),
+                None => {
+                    let relative = found.parent().and_then(|parent| parent.strip_prefix(&base).ok()).unwrap_or(Path::new(""));
+                    let found_output = match config.out_dir {
+                        Some(ref out_dir) => out_dir.join(relative).join(found.file_name().unwrap_or_default()),
+                        None => found.clone(),
+                    };
+                    declarations.push((found.clone(), found_output.clone()));
+                    found_output
+                }
+            };
+            let url = relative_url(output_directory, &found_output);
+            let url = url.trim_end_matches(".d.ts");
+            let url = if found == index { url.trim_end_matches("/index") } else { url };
+            Some(format!("{}{}", url, suffix))
+        });
+
+        if let Some(parent) = output.parent().filter(|parent| !parent.as_os_str().is_empty()) {
+            std::fs::create_dir_all(parent).map_err(|e| {
+                CompilerError::IoError(format!("Failed to create output directory '{}': {}", parent.display(), e))
+            })?;
+        }
+        write_atomically(&output, rewritten, false)
+            .map_err(|e| CompilerError::IoError(format!("Failed to write declarations '{}': {}", output.display(), e)))?;
+        tracing::info!("📄 Declarations saved to: {}", output.display());
+        index += 1;
+    }
+    Ok(())
+}
+
+/// `path` with `suffix` appended to its file name (`util` -> `util.js`)
+fn with_suffix(path: &Path, suffix: &str) -> PathBuf {
+    let mut file_name = path.file_name().unwrap_or_default().to_os_string();
+    file_name.push(suffix);
+    path.with_file_name(file_name)
+}
+
+/// Each of `file_paths` and the worker entries it starts (see `--workers`), followed by
+/// the worker entries that aren't inputs and the ones they start in turn
+///
+/// Only specifiers relative to the module (`./w.js`, `../w.js`) naming an existing file
+/// are followed; others are left as written with a warning. A file that fails to parse
+/// is skipped here, and reported when it is compiled.
+///
+/// # Errors
+///
+/// Returns `CompilerError::FileNotFound` if a file cannot be read, or
+/// `CompilerError::TransformError` if its analysis fails.
+fn find_worker_entries(file_paths: &[PathBuf], config: &CompilerConfig) -> CompilerResult<Vec<(PathBuf, Vec<(String, PathBuf)>)>> {
+    let mut files: Vec<(PathBuf, Vec<(String, PathBuf)>)> =
+        file_paths.iter().map(|file_path| (file_path.clone(), Vec::new())).collect();
+    let mut index = 0;
+    while index < files.len() {
+        let file_path = files[index].0.clone();
+        let source_code = read_source(&file_path, config.transcode_utf16)?;
+        let parse_result = parser::parse_js(&source_code, &file_path.to_string_lossy(), &input_parser_config(config));
+        let Some(ast) = parse_result.ast else {
+            index += 1;
+            continue;
+        };
+        let analyzer_config = analyzer::AnalyzerConfig { names: parse_result.names, ..analyzer::AnalyzerConfig::default() };
+        let analysis = analyzer::analyze_ast(&ast, &analyzer_config)
+            .map_err(|e| CompilerError::TransformError(format!("Analysis failed: {}", e)))?;
+
+        for specifier in analysis.module_record.workers {
+            if !specifier.starts_with("./") && !specifier.starts_with("../") {
+                tracing::warn!("⚠️  Worker '{}' in {} is not a relative path; left as written", specifier, file_path.display());
+                continue;
+            }
+            let worker = normalize_path(&file_path.parent().unwrap_or(Path::new("")).join(&specifier));
+            if !worker.is_file() {
+                tracing::warn!("⚠️  Worker '{}' in {} not found; left as written", specifier, file_path.display());
+                continue;
+            }
+            let known = files.iter().find(|(known, _)| same_file(known, &worker)).map(|(known, _)| known.clone());
+            let worker = match known {
+                Some(known) => known,
+                None => {
+                    files.push((worker.clone(), Vec::new()));
+                    worker
+                }
+            };
+            files[index].1.push((specifier, worker));
+        }
+        index += 1;
+    }
+    Ok(files)
+}
+
+/// Compiles each of `file_paths` to `<name>.min.js` next to it (or as `--out-dir` and
+/// `--out-ext` map it), or over itself with `--in-place`, then prints a summary table.
+/// `--output` names the output of the first file, when the worker entries found by
+/// [`find_worker_entries`] follow a single input; `workers` points each file's entries
+/// at their outputs.
+///
+/// Shows a progress bar on stderr unless `--no-progress` or `-q` is given, logs are JSON,
+/// or stdout is not a terminal. A file that fails does not stop the others.
+///
+/// # Errors
+///
+/// Returns the error of the first file that failed.
+fn compile_files(
+    file_paths: &[PathBuf],
+    workers: &[(PathBuf, Vec<(String, PathBuf)>)],
+    config: &CompilerConfig,
+) -> CompilerResult<()> {
+    use std::io::IsTerminal;
+
+    let show_progress = config.progress
+        && config.log.verbosity > logging::Verbosity::Quiet
+        && config.log.format == logging::LogFormat::Text
+        && io::stdout().is_terminal();
+    let progress = if show_progress {
+        indicatif::ProgressBar::new(file_paths.len() as u64)
+    } else {
+        indicatif::ProgressBar::hidden()
+    };
+    progress.set_style(
+        indicatif::ProgressStyle::with_template("{bar:30} {pos}/{len} {wide_msg}")
+            .unwrap_or_else(|_| indicatif::ProgressStyle::default_bar()),
+    );
+
+    let base = common_directory(file_paths);
+    let output_files: Vec<PathBuf> = file_paths
+        .iter()
+        .enumerate()
+        .map(|(index, file_path)| match &config.output_file {
+            Some(output_file) if index == 0 => output_file.clone(),
+            _ if config.in_place => file_path.clone(),
+            _ => mapped_output_path(file_path, &base, config),
+        })
+        .collect();
+    let output_of = |file_path: &PathBuf| {
+        file_paths.iter().position(|known| known == file_path).map(|index| &output_files[index])
+    };
+    let mut rows = Vec::with_capacity(file_paths.len());
+    let mut first_error = None;
+    for (file_path, output_file) in file_paths.iter().zip(&output_files) {
+        progress.set_message(file_path.display().to_string());
+        let entries = workers.iter().find(|(known, _)| known == file_path).map_or(&[][..], |(_, entries)| entries);
+        let worker_urls = entries
+            .iter()
+            .filter_map(|(specifier, worker)| {
+                let directory = output_file.parent().unwrap_or(Path::new(""));
+                Some((specifier.clone(), relative_url(directory, output_of(worker)?)))
+            })
+            .collect();
+        let file_config = CompilerConfig {
+            output_file: Some(output_file.clone()),
+            transformer: transformer::TransformerConfig { worker_urls, ..config.transformer.clone() },
+            ..config.clone()
+        };
+        let start = Instant::now();
+        let result = progress.suspend(|| compile_file(file_path, &file_config));
+        let time_ms = start.elapsed().as_secs_f64() * 1000.0;
+        match result {
+            Ok(summary) => rows.push((file_path, Some(summary), time_ms)),
+            Err(error) => {
+                progress.suspend(|| tracing::error!("❌ {}: {}", file_path.display(), error));
+                rows.push((file_path, None, time_ms));
+                first_error.get_or_insert(error);
+            }
+        }
+        progress.inc(1);
+    }
+    progress.finish_and_clear();
+
+    let failed = rows.iter().filter(|(_, summary, _)| summary.is_none()).count();
+    tracing::info!("📊 Build summary:");
+    tracing::info!(
+        "   {:<32} {:<32} {:<8} {:>10} {:>10} {:>10}",
+        "File", "Output", "Status", "Bytes in", "Bytes out", "Time (ms)"
+    );
+    for (file_path, summary, time_ms) in &rows {
+        let (output, status, input_size, output_size) = match summary {
+            Some(summary) => (
+                summary.output_path.display().to_string(),
+                "ok",
+                summary.input_size.to_string(),
+                summary.output_size.to_string(),
+            ),
+            None => ("-".to_string(), "failed", "-".to_string(), "-".to_string()),
+        };
+        tracing::info!(
+            "   {:<32} {:<32} {:<8} {:>10} {:>10} {:>10.1}",
+            file_path.display(),
+            output,
+            status,
+            input_size,
+            output_size,
+            time_ms
+        );
+    }
+    let total = |size: fn(&FileSummary) -> usize| -> usize {
+        rows.iter().filter_map(|(_, summary, _)| summary.as_ref()).map(size).sum()
+    };
+    tracing::info!(
+        "   {:<32} {:<32} {:<8} {:>10} {:>10} {:>10.1}",
+        format!("total ({} files)", rows.len()),
+        "",
+        if failed == 0 { "ok".to_string() } else { format!("{} failed", failed) },
+        total(|summary| summary.input_size),
+        total(|summary| summary.output_size),
+        rows.iter().map(|(_, _, time_ms)| time_ms).sum::<f64>()
+    );
+
+    match first_error {
+        Some(error) => Err(error),
+        None => {
+            tracing::info!("✅ Compilation completed successfully!");
+            Ok(())
+        }
+    }
+}
+
+/// Serves JSON-RPC requests from stdin until end of input or a `shutdown` request.
+///
+/// # Errors
+///
+/// Returns `CompilerError::IoError` if reading stdin or writing stdout fails.
+fn run_server() -> CompilerResult<()> {
+    let stdin = io::stdin();
+    let stdout = io::stdout();
+
+    server::Server::new()
+        .serve(stdin.lock(), stdout.lock())
+        .map_err(|e| CompilerError::IoError(format!("Server I/O failed: {}", e)))
+}
+
+/// Serves Language Server Protocol messages from stdin until the client sends `exit`.
+///
+/// # Errors
+///
+/// Returns `CompilerError::IoError` if stdio fails, or if the client exits
+/// without a prior `shutdown` request (exit code 1 per the protocol).
+fn run_language_server() -> CompilerResult<()> {
+    let stdin = io::stdin();
+    let stdout = io::stdout();
+
+    let clean_exit = lsp::LanguageServer::new()
+        .serve(stdin.lock(), stdout.lock())
+        .map_err(|e| CompilerError::IoError(format!("Language server I/O failed: {}", e)))?;
+
+    if clean_exit {
+        Ok(())
+    } else {
+        Err(CompilerError::IoError("Language server exited without shutdown".to_string()))
+    }
+}
+
+/// Runs a subcommand, printing only its own output.
+///
+/// # Errors
+///
+/// Returns `CompilerError::FileNotFound` if the input cannot be read, or
+/// `CompilerError::ParseError` or `CompilerError::TransformError` if parsing or analysis fails.
+fn run_subcommand(command: &CliCommand, transcode_utf16: bool) -> CompilerResult<()> {
+    match command {
+        CliCommand::Analyze { file, json } => analyze_file(file, *json, transcode_utf16),
+        CliCommand::Ast { file, format } => print_ast(file, *format, transcode_utf16),
+        CliCommand::Beautify { file, source_map, name_map, output } => {
+            beautify_file(file, source_map.as_deref(), name_map.as_deref(), output.as_deref(), transcode_utf16)
+        }
+        CliCommand::Diff { before, after } => diff_files(before, after, transcode_utf16),
+    }
+}
+
+/// Parses and analyzes a file, printing the scope tree, symbols, captures and unsafe flags.
+///
+/// With `json`, the complete `SemanticAnalysis` is printed as pretty JSON with object
+/// keys sorted, so the output is stable across runs.
+///
+/// # Errors
+///
+/// Returns `CompilerError::FileNotFound` if the file cannot be read, or
+/// `CompilerError::ParseError` or `CompilerError::TransformError` if parsing or analysis fails.
+fn analyze_file(file_path: &PathBuf, json: bool, transcode_utf16: bool) -> CompilerResult<()> {
+    let source_code = read_source(file_path, transcode_utf16)?;
+    let analysis = rjs_compiler::analyze_source(&source_code, &file_path.to_string_lossy())
+        .map_err(CompilerError::from)?;
+
+    if json {
+        let mut value = serde_json::to_value(&analysis)
+            .map_err(|e| CompilerError::IoError(format!("Failed to serialize analysis: {}", e)))?;
+        // Symbols hold interned name ids; print the names themselves
+        if let Some(symbols) = value["symbol_table"]["symbols"].as_array_mut() {
+            for (symbol_value, symbol) in symbols.iter_mut().zip(analysis.symbol_table.symbols.values()) {
+                symbol_value["name"] = analysis.symbol_table.name(symbol).into();
+            }
+        }
+        let output = serde_json::to_string_pretty(&value)
+            .map_err(|e| CompilerError::IoError(format!("Failed to serialize analysis: {}", e)))?;
+        println!("{}", output);
+        return Ok(());
+    }
+
+    let metadata = &analysis.metadata;
+    println!("🔍 Semantic analysis of {}", file_path.display());
+    println!("   🏗️  Scopes: {}", metadata.scope_count);
+    println!("   🏷️  Symbols: {}", metadata.symbol_count);
+    println!("   🔗 Closure captures: {}", metadata.capture_count);
+    println!("   📤 Exports: {}", metadata.export_count);
+    println!("🌳 Scope tree:");
+    display_scope(&analysis, analysis.scope_tree.root_scope_id, 1);
+
+    if !analysis.semantic_flags.global_references.is_empty() {
+        let mut globals: Vec<&str> = analysis
+            .semantic_flags
+            .global_references
+            .iter()
+            .filter_map(|id| analysis.symbol_table.symbols.get(*id))
+            .map(|symbol| analysis.symbol_table.name(symbol))
+            .collect();
+        globals.sort_unstable();
+        globals.dedup();
+        println!("🌐 Global references: {}", globals.join(", "));
+    }
+
+    if !analysis.semantic_flags.global_exports.is_empty() {
+        let exports: Vec<&str> = analysis.semantic_flags.global_exports.iter().map(String::as_str).collect();
+        println!("📌 Global exports: {}", exports.join(", "));
+    }
+
+    Ok(())
+}
+
+/// Parses a file and prints its AST in the requested format.
+///
+/// # Errors
+///
+/// Returns `CompilerError::FileNotFound` if the file cannot be read, or
+/// `CompilerError::ParseError` if the file has syntax errors.
+fn print_ast(file_path: &PathBuf, format: parser::ast_dump::AstFormat, transcode_utf16: bool) -> CompilerResult<()> {
+    let source_code = read_source(file_path, transcode_utf16)?;
+    let parser_config = parser::ParserConfig {
+        preserve_trivia: false,
+        ..parser::ParserConfig::default()
+    };
+    let parse_result = parser::parse_js(&source_code, &file_path.to_string_lossy(), &parser_config);
+
+    if !parse_result.errors.is_empty() {
+        tracing::error!("❌ Parsing errors found:");
+        for error in &parse_result.errors {
+            tracing::error!("   {}", error);
+        }
+        return Err(CompilerError::ParseError(format!(
+            "Found {} parsing errors",
+            parse_result.errors.len()
+        )));
+    }
+
+    let ast = parse_result.ast.ok_or_else(|| {
+        CompilerError::ParseError("No AST generated despite no errors".to_string())
+    })?;
+    let output = parser::ast_dump::dump_ast(&ast, format)
+        .map_err(|e| CompilerError::IoError(format!("Failed to serialize AST: {}", e)))?;
+    print!("{}", output);
+    if format == parser::ast_dump::AstFormat::Json {
+        println!();
+    }
+    Ok(())
+}
+
+/// Pretty-prints a minified file, restoring the names its source map and name map record.
+///
+/// # Errors
+///
+/// Returns `CompilerError::FileNotFound` if a file cannot be read,
+/// `CompilerError::InvalidArguments` if the source map or name map is not valid JSON,
+/// `CompilerError::ParseError` if the file has syntax errors, or `CompilerError::IoError`
+/// if the output cannot be written.
+fn beautify_file(
+    file_path: &Path,
+    source_map_path: Option<&Path>,
+    name_map_path: Option<&Path>,
+    output_path: Option<&Path>,
+    transcode_utf16: bool,
+) -> CompilerResult<()> {
+    let source_code = read_source(file_path, transcode_utf16)?;
+    let source_map: Option<generator::source_maps::SourceMap> = source_map_path.map(read_json_file).transpose()?;
+    let name_map: Option<transformer::NameMap> = name_map_path.map(read_json_file).transpose()?;
+
+    let code = rjs_compiler::beautify_source(
+        &source_code,
+        &file_path.to_string_lossy(),
+        source_map.as_ref(),
+        name_map.as_ref(),
+    )?;
+    match output_path {
+        Some(output_path) => write_atomically(output_path, &code, false).map_err(|e| {
+            CompilerError::IoError(format!("Failed to write {}: {}", output_path.display(), e))
+        }),
         None => {
-            display_usage_information();
-            Err(CompilerError::MissingInputFile)
+            print!("{}", code);
+            Ok(())
+        }
+    }
+}
+
+/// Prints the structural differences between the ASTs of two files.
+///
+/// # Errors
+///
+/// Returns `CompilerError::ProgramsDiffer` if any top-level statement differs,
+/// `CompilerError::FileNotFound` if a file cannot be read, or
+/// `CompilerError::ParseError` if a file has syntax errors.
+fn diff_files(before_path: &Path, after_path: &Path, transcode_utf16: bool) -> CompilerResult<()> {
+    let parse = |path: &Path| -> CompilerResult<(String, parser::ast_types::Program)> {
+        let source_code = read_source(path, transcode_utf16)?;
+        let parse_result = parser::parse_js(&source_code, &path.to_string_lossy(), &parser::ParserConfig::default());
+        if !parse_result.errors.is_empty() {
+            let messages: Vec<String> = parse_result.errors.iter().map(|e| e.to_string()).collect();
+            return Err(CompilerError::ParseError(format!("{}: {}", path.display(), messages.join("; "))));
+        }
+        let ast = parse_result.ast.ok_or_else(|| {
+            CompilerError::ParseError("No AST generated despite no errors".to_string())
+        })?;
+        Ok((source_code, ast))
+    };
+    let (before_source, before) = parse(before_path)?;
+    let (after_source, after) = parse(after_path)?;
+
+    let diff = parser::ast_diff::diff_programs(&before, &before_source, &after, &after_source)
+        .map_err(|e| CompilerError::IoError(format!("Failed to serialize AST: {}", e)))?;
+    print!("{}", diff);
+    if diff.is_empty() {
+        Ok(())
+    } else {
+        Err(CompilerError::ProgramsDiffer(diff.changes.len()))
+    }
+}
+
+/// Reads a JSON file given on the command line.
+fn read_json_file<T: serde::de::DeserializeOwned>(path: &Path) -> CompilerResult<T> {
+    let contents = read_source(path, false)?;
+    serde_json::from_str(&contents)
+        .map_err(|e| CompilerError::InvalidArguments(format!("{} is not valid: {}", path.display(), e)))
+}
+
+/// Parser configuration for the inputs: the `--max-depth` limit and the `--proposal`s
+fn input_parser_config(config: &CompilerConfig) -> parser::ParserConfig {
+    let parser_config = parser::ParserConfig::default().with_max_depth(config.max_depth).with_flow(config.strip_types);
+    config.proposals.iter().fold(parser_config, |parser_config, &proposal| parser_config.with_proposal(proposal))
+}
+
+/// Reads an input file as UTF-8 source text, without its byte order mark.
+///
+/// UTF-16 input is converted with `transcode_utf16` and rejected without it.
+///
+/// # Errors
+///
+/// Returns `CompilerError::FileNotFound` or `CompilerError::PermissionDenied` if the
+/// file cannot be opened, `CompilerError::InvalidEncoding` if it is not UTF-8, or
+/// `CompilerError::IoError` for any other read failure.
+fn read_source(path: &Path, transcode_utf16: bool) -> CompilerResult<String> {
+    let bytes = std::fs::read(path).map_err(|e| match e.kind() {
+        io::ErrorKind::NotFound => CompilerError::FileNotFound(path.to_path_buf()),
+        io::ErrorKind::PermissionDenied => CompilerError::PermissionDenied(path.to_path_buf()),
+        _ => CompilerError::IoError(format!("Failed to read {}: {}", path.display(), e)),
+    })?;
+    encoding::decode_source(bytes, transcode_utf16).map_err(|e| {
+        let hint = if matches!(e, encoding::EncodingError::Utf16(_)) { "; pass --transcode-utf16 to convert it" } else { "" };
+        CompilerError::InvalidEncoding { path: path.to_path_buf(), reason: format!("{}{}", e, hint) }
+    })
+}
+
+/// Prints a scope, its bindings and its child scopes, indented by `depth`.
+fn display_scope(analysis: &analyzer::SemanticAnalysis, scope_id: analyzer::ScopeId, depth: usize) {
+    let Some(scope) = analysis.scope_tree.get_scope(scope_id) else {
+        return;
+    };
+    let indent = "   ".repeat(depth);
+
+    match analysis.semantic_flags.unsafe_scopes.get(&scope_id) {
+        Some(reason) => println!("{}📦 Scope {} ({:?}) ⚠️  unsafe: {:?}", indent, scope.id, scope.scope_type, reason),
+        None => println!("{}📦 Scope {} ({:?})", indent, scope.id, scope.scope_type),
+    }
+
+    for symbol in scope.bindings.iter().filter_map(|id| analysis.symbol_table.symbols.get(*id)) {
+        let mut notes = vec![format!("{} refs", symbol.references.len())];
+        if symbol.is_captured {
+            notes.push("captured".to_string());
+        }
+        if symbol.is_exported {
+            notes.push("exported".to_string());
+        }
+        if symbol.is_kept {
+            notes.push("kept".to_string());
         }
+        if !symbol.is_renamable {
+            notes.push("not renamable".to_string());
+        }
+        println!("{}   🏷️  {} {:?} ({})", indent, analysis.symbol_table.name(symbol), symbol.symbol_type, notes.join(", "));
+    }
+
+    for &child in &scope.children {
+        display_scope(analysis, child, depth + 1);
     }
 }
 
@@ -129,49 +1001,1414 @@ fn parse_command_line_arguments() -> CompilerResult<CompilerConfig> {
             Arg::new("verbose")
                 .short('v')
                 .long("verbose")
-                .action(clap::ArgAction::SetTrue)
-                .help("Enable verbose output for debugging")
+                .action(clap::ArgAction::Count)
+                .help("Enable verbose output for debugging (-vv for trace output)")
                 .long_help(
                     "Enable verbose output mode which provides detailed information \n\
                      about the compilation process, including intermediate steps \n\
-                     and performance metrics."
+                     and performance metrics. Repeat (-vv) to also trace every \n\
+                     symbol declaration and reference."
                 ),
         )
         .arg(
-            Arg::new("output")
-                .short('o')
-                .long("output")
-                .help("Output file for minified JavaScript")
+            Arg::new("quiet")
+                .short('q')
+                .long("quiet")
+                .action(clap::ArgAction::SetTrue)
+                .conflicts_with("verbose")
+                .help("Only print warnings and errors"),
+        )
+        .arg(
+            Arg::new("log-format")
+                .long("log-format")
+                .value_name("FORMAT")
+                .value_parser(["text", "json"])
+                .help("Print log messages as plain text or as JSON lines on stderr"),
+        )
+        .arg(
+            Arg::new("log-filter")
+                .long("log-filter")
+                .value_name("DIRECTIVES")
+                .help("Per-module log levels, e.g. rjs_compiler::transformer=trace (default: $RJS_LOG)"),
+        )
+        .arg(
+            Arg::new("timing")
+                .long("timing")
+                .action(clap::ArgAction::SetTrue)
+                .help("Print a per-phase timing breakdown")
                 .long_help(
-                    "Path to the output file where the minified JavaScript \n\
-                     will be saved. If not specified, output will be printed \n\
-                     to stdout. File will be created if it doesn't exist."
+                    "Print a table with the time spent in each compilation phase \n\
+                     (read, parse, analyze, transform, generate, write), including \n\
+                     the individual transformer passes, without full verbose output."
+                ),
+        )
+        .arg(
+            Arg::new("max-output-size")
+                .long("max-output-size")
+                .help("Fail if the generated output exceeds this size")
+                .long_help(
+                    "Maximum size of the generated output. Accepts a byte count with \n\
+                     an optional K, M or G suffix (e.g. 512K, 10M). Use 0 for no limit. \n\
+                     Defaults to 10M."
                 )
-                .value_name("OUTPUT_FILE")
-                .value_parser(clap::value_parser!(PathBuf)),
+                .value_name("SIZE")
+                .value_parser(parse_byte_size),
         )
         .arg(
-            Arg::new("input")
-                .help("Input JavaScript file to compile")
+            Arg::new("max-memory")
+                .long("max-memory")
+                .help("Fail if the in-memory output buffer exceeds this size")
                 .long_help(
-                    "Path to the JavaScript source file that will be compiled. \n\
-                     The file must exist and be readable."
+                    "Maximum size of the output buffer held in memory during code \n\
+                     generation, with the same units as --max-output-size. Combine \n\
+                     with --stream to bound memory use for very large bundles."
                 )
+                .value_name("SIZE")
+                .value_parser(parse_byte_size),
+        )
+        .arg(
+            Arg::new("max-size")
+                .long("max-size")
+                .help("Fail if an output file is larger than this size budget")
+                .long_help(
+                    "Size budget of each output file, with the same units as \n\
+                     --max-output-size. An output over budget is not written; the \n\
+                     error lists the top-level declarations that contributed the most \n\
+                     bytes. Exits with code 7."
+                )
+                .value_name("SIZE")
+                .value_parser(parse_byte_size)
+                .conflicts_with("stream"),
+        )
+        .arg(
+            Arg::new("max-gzip-size")
+                .long("max-gzip-size")
+                .help("Fail if an output file is larger than this after gzip")
+                .long_help(
+                    "Like --max-size, for the size of each output file after gzip \n\
+                     at the default compression level."
+                )
+                .value_name("SIZE")
+                .value_parser(parse_byte_size)
+                .conflicts_with("stream"),
+        )
+        .arg(
+            Arg::new("bundle-report")
+                .long("bundle-report")
                 .value_name("FILE")
                 .value_parser(clap::value_parser!(PathBuf))
-                .index(1),
+                .conflicts_with("stream")
+                .help("Write a treemap report of which declarations the output bytes come from")
+                .long_help(
+                    "Attribute the bytes of the output to the top-level declarations \n\
+                     of the input through its source map, and write the tree as \n\
+                     { name, value, children } JSON for treemap tools, or as an HTML \n\
+                     page with --bundle-report-format html."
+                ),
         )
-        .get_matches();
+        .arg(
+            Arg::new("bundle-report-format")
+                .long("bundle-report-format")
+                .value_name("FORMAT")
+                .value_parser(["json", "html"])
+                .default_value("json")
+                .requires("bundle-report")
+                .help("Format of the --bundle-report"),
+        )
+        .arg(
+            Arg::new("stream")
+                .long("stream")
+                .action(clap::ArgAction::SetTrue)
+                .help("Stream generated code to the output file incrementally")
+                .long_help(
+                    "Flush generated code to the output file as it is produced instead \n\
+                     of building the complete output in memory first."
+                ),
+        )
+        .arg(
+            Arg::new("serve")
+                .long("serve")
+                .action(clap::ArgAction::SetTrue)
+                .conflicts_with("input")
+                .help("Run as a JSON-RPC server over stdio")
+                .long_help(
+                    "Keep the process running and answer JSON-RPC 2.0 requests \n\
+                     (compile, minify, analyze, shutdown) read from stdin, one \n\
+                     message per line. Responses are written to stdout."
+                ),
+        )
+        .arg(
+            Arg::new("lsp")
+                .long("lsp")
+                .action(clap::ArgAction::SetTrue)
+                .conflicts_with_all(["input", "serve"])
+                .help("Run as a Language Server Protocol diagnostics provider over stdio")
+                .long_help(
+                    "Speak the Language Server Protocol on stdin/stdout and publish \n\
+                     diagnostics (syntax errors, unused bindings, TDZ violations, \n\
+                     eval usage) for documents opened by the editor."
+                ),
+        )
+        .arg(
+            Arg::new("plugin")
+                .long("plugin")
+                .action(clap::ArgAction::Append)
+                .value_name("PATH")
+                .value_parser(clap::value_parser!(PathBuf))
+                .help("Load a transform plugin from a shared library (repeatable)")
+                .long_help(
+                    "Load a transform plugin built with `rjs_compiler::export_plugin!` \n\
+                     from a shared library (.so, .dylib or .dll). The plugin chooses \n\
+                     where it runs relative to the built-in passes; plugins at the \n\
+                     same position run in command-line order."
+                ),
+        )
+        .arg(
+            Arg::new("no-mangle")
+                .long("no-mangle")
+                .action(clap::ArgAction::SetTrue)
+                .help("Keep variable and function names (disable identifier renaming)"),
+        )
+        .arg(
+            Arg::new("no-dce")
+                .long("no-dce")
+                .action(clap::ArgAction::SetTrue)
+                .help("Disable dead code elimination"),
+        )
+        .arg(
+            Arg::new("drop-console")
+                .long("drop-console")
+                .action(clap::ArgAction::SetTrue)
+                .help("Remove console.* calls whose result is unused (same as --drop 'console.*')"),
+        )
+        .arg(
+            Arg::new("drop")
+                .long("drop")
+                .action(clap::ArgAction::Append)
+                .value_delimiter(',')
+                .value_name("CALLEES")
+                .help("Remove unused calls to these functions, e.g. console.log,assert,logger.*")
+                .long_help(
+                    "Remove calls whose result is unused when the callee matches one of the \n\
+                     comma-separated names. Names are dotted paths (`console.log`, `assert`) \n\
+                     and `*` matches any single name (`console.*`). Arguments are dropped \n\
+                     with the call unless --keep-dropped-args is given."
+                ),
+        )
+        .arg(
+            Arg::new("keep-dropped-args")
+                .long("keep-dropped-args")
+                .action(clap::ArgAction::SetTrue)
+                .help("Keep the side effects of the arguments of calls removed by --drop"),
+        )
+        .arg(
+            Arg::new("no-collapse-vars")
+                .long("no-collapse-vars")
+                .action(clap::ArgAction::SetTrue)
+                .help("Keep single-use variables instead of inlining them into their use"),
+        )
+        .arg(
+            Arg::new("no-inline-captures")
+                .long("no-inline-captures")
+                .action(clap::ArgAction::SetTrue)
+                .help("Keep constants captured by closures instead of copying them into the closures"),
+        )
+        .arg(
+            Arg::new("no-if-return")
+                .long("no-if-return")
+                .action(clap::ArgAction::SetTrue)
+                .help("Keep `else` branches after `return` and braces around single statements"),
+        )
+        .arg(
+            Arg::new("drop-debugger")
+                .long("drop-debugger")
+                .action(clap::ArgAction::SetTrue)
+                .help("Remove debugger statements"),
+        )
+        .arg(
+            Arg::new("hoist-funs")
+                .long("hoist-funs")
+                .action(clap::ArgAction::SetTrue)
+                .help("Move function declarations to the top of their scope"),
+        )
+        .arg(
+            Arg::new("hoist-vars")
+                .long("hoist-vars")
+                .action(clap::ArgAction::SetTrue)
+                .help("Merge the var statements of each function into one declaration (may grow output)"),
+        )
+        .arg(
+            Arg::new("no-inline")
+                .long("no-inline")
+                .action(clap::ArgAction::SetTrue)
+                .help("Disable function inlining and minification"),
+        )
+        .arg(
+            Arg::new("no-inline-enums")
+                .long("no-inline-enums")
+                .action(clap::ArgAction::SetTrue)
+                .help("Keep enum-like objects instead of inlining their values"),
+        )
+        .arg(
+            Arg::new("mangle-props")
+                .long("mangle-props")
+                .action(clap::ArgAction::SetTrue)
+                .help("Rename object properties (off by default)")
+                .long_help(
+                    "Enable property minification. Renaming properties breaks code that \n\
+                     accesses them by computed name or from outside the bundle, so it \n\
+                     is opt-in."
+                ),
+        )
+        .arg(
+            Arg::new("mangle-props-debug")
+                .long("mangle-props-debug")
+                .action(clap::ArgAction::SetTrue)
+                .help("Rename object properties to readable _$name$_ forms (implies --mangle-props)")
+                .long_help(
+                    "Property minification with readable names: `foo` becomes `_$foo$_` \n\
+                     instead of a short name. Use it to find code that property \n\
+                     renaming breaks before shipping the fully mangled build."
+                ),
+        )
+        .arg(
+            Arg::new("emit-name-map")
+                .long("emit-name-map")
+                .action(clap::ArgAction::SetTrue)
+                .help("Write the original and new name of everything renamed to <output>.names.json")
+                .long_help(
+                    "Write the complete rename mapping (identifiers, object properties \n\
+                     by their object, and mangled exports) next to the output as \n\
+                     <output>.names.json, for de-obfuscation tools and crash report \n\
+                     symbolication."
+                ),
+        )
+        .arg(
+            Arg::new("module-exports")
+                .long("module-exports")
+                .value_name("MODE")
+                .value_parser(["keep", "mangle"])
+                .default_value("keep")
+                .help("Keep exported names as written, or shorten them (see --export-map)")
+                .long_help(
+                    "With mangle, the names a module exports are renamed to short \n\
+                     names (a, b, ...) and exported symbols may be renamed like any \n\
+                     other binding. Importers must be rewritten to match, so use it \n\
+                     with --export-map when another tool post-processes the output. \n\
+                     `default` exports are never renamed."
+                ),
+        )
+        .arg(
+            Arg::new("export-map")
+                .long("export-map")
+                .value_name("MAP_FILE")
+                .value_parser(clap::value_parser!(PathBuf))
+                .help("Write the original-to-mangled exported names to a JSON file"),
+        )
+        .arg(
+            Arg::new("api-report")
+                .long("api-report")
+                .value_name("REPORT_FILE")
+                .value_parser(clap::value_parser!(PathBuf))
+                .conflicts_with("format-only")
+                .help("Write the exported names, kinds and arities before and after minification to a JSON file"),
+        )
+        .arg(
+            Arg::new("dedupe-strings")
+                .long("dedupe-strings")
+                .action(clap::ArgAction::SetTrue)
+                .help("Hoist long repeated strings into a top-level const (off by default)"),
+        )
+        .arg(
+            Arg::new("dedupe-strings-threshold")
+                .long("dedupe-strings-threshold")
+                .value_name("BYTES")
+                .value_parser(clap::value_parser!(usize))
+                .help("Minimum estimated saving for hoisting a string; implies --dedupe-strings")
+                .long_help(
+                    "Hoist a repeated string only if it saves at least BYTES bytes of \n\
+                     minified output (default 32). Gzip already compresses repeats, so \n\
+                     small savings mostly disappear after compression."
+                ),
+        )
+        .arg(
+            Arg::new("seed")
+                .long("seed")
+                .value_name("SEED")
+                .value_parser(clap::value_parser!(u64))
+                .help("Shuffle generated names with SEED; the same seed always gives the same output"),
+        )
+        .arg(
+            Arg::new("threads")
+                .long("threads")
+                .value_name("COUNT")
+                .value_parser(clap::value_parser!(usize))
+                .help("Worker threads for the passes that run per top-level statement (default 0: one per core)")
+                .long_help(
+                    "Worker threads for the passes that transform top-level statements \n\
+                     independently (default 0: one per available core; 1 runs \n\
+                     everything on the calling thread). Programs with fewer than 64 \n\
+                     statements per worker use fewer workers. The output does not \n\
+                     depend on the count."
+                ),
+        )
+        .arg(
+            Arg::new("aggressive")
+                .long("aggressive")
+                .action(clap::ArgAction::SetTrue)
+                .help("Enable optimizations that may be less safe"),
+        )
+        .arg(
+            Arg::new("explain")
+                .long("explain")
+                .value_name("REPORT_FILE")
+                .value_parser(clap::value_parser!(PathBuf))
+                .help("Write a report of every change made by each pass")
+                .long_help(
+                    "Write a report listing, for each change made by a transformer pass \n\
+                     or plugin, the source location and the affected top-level \n\
+                     statements before and after the change."
+                ),
+        )
+        .arg(
+            Arg::new("explain-format")
+                .long("explain-format")
+                .value_name("FORMAT")
+                .value_parser(["text", "json"])
+                .default_value("text")
+                .requires("explain")
+                .help("Format of the --explain report"),
+        )
+        .arg(
+            Arg::new("diagnostics-format")
+                .long("diagnostics-format")
+                .value_name("FORMAT")
+                .value_parser(["text", "json"])
+                .help("Print transformer warnings to stderr with their source locations")
+                .long_help(
+                    "Print every transformer warning to stderr, as `file:line:column: \n\
+                     warning[pass]: message` lines or as a JSON array. Locations point \n\
+                     at the original source: the offending directive comment, or the \n\
+                     first statement a rolled-back pass changed."
+                ),
+        )
+        .arg(
+            Arg::new("target")
+                .long("target")
+                .value_name("TARGETS")
+                .value_parser(|value: &str| value.parse::<target::Targets>().map_err(|e| e.to_string()))
+                .help("Environments the output must run on, e.g. chrome90,firefox88,node16")
+                .long_help(
+                    "Comma separated engines with minimum versions (chrome, edge, \n\
+                     firefox, safari, ios, node), or an edition preset (es5, es2015 \n\
+                     ... es2022, latest). Syntax a target lacks is reported instead \n\
+                     of emitted. Without this flag the nearest .browserslistrc above \n\
+                     the input file is used, and all syntax passes through if none exists."
+                ),
+        )
+        .arg(
+            Arg::new("polyfill-report")
+                .long("polyfill-report")
+                .value_name("REPORT_FILE")
+                .value_parser(clap::value_parser!(PathBuf))
+                .help("Write the polyfills each output file needs for --target to a JSON file")
+                .long_help(
+                    "Record the runtime built-ins (Promise, Object.assign, ...) that the \n\
+                     output uses but a target lacks, keyed by output file. Entries \n\
+                     already in the file are kept."
+                ),
+        )
+        .arg(
+            Arg::new("polyfill-module")
+                .long("polyfill-module")
+                .value_name("MODULE")
+                .help("Import needed polyfills from MODULE, e.g. core-js/es")
+                .long_help(
+                    "Prepend an import of <MODULE>/<path> for every needed polyfill, \n\
+                     using the core-js file layout (core-js/es/object/assign). Scripts \n\
+                     get require() calls instead of import statements."
+                ),
+        )
+        .arg(
+            Arg::new("hash-filenames")
+                .long("hash-filenames")
+                .action(clap::ArgAction::SetTrue)
+                .help("Add a content hash to the output file name and record it in manifest.json")
+                .long_help(
+                    "Rename the output to include a hash of its contents (e.g. \n\
+                     build.js -> build.3f9ab2c1.js) and add the mapping from the \n\
+                     logical name to the hashed name to manifest.json next to it, \n\
+                     for cache-busting deployments."
+                ),
+        )
+        .arg(
+            Arg::new("indent")
+                .long("indent")
+                .value_name("WIDTH")
+                .value_parser(|value: &str| value.parse::<generator::IndentStyle>())
+                .help("Indentation of --format-only output: a number of spaces (default 2) or tab"),
+        )
+        .arg(
+            Arg::new("max-line-len")
+                .long("max-line-len")
+                .value_name("COLUMNS")
+                .value_parser(clap::value_parser!(usize))
+                .help("Wrap long argument lists and expressions in formatted or readable output"),
+        )
+        .arg(
+            Arg::new("keep-jsdoc")
+                .long("keep-jsdoc")
+                .action(clap::ArgAction::SetTrue)
+                .help("Print readable output that keeps the JSDoc of exported functions and classes")
+                .long_help(
+                    "Print one statement per line and keep the /** ... */ block \n\
+                     directly before each exported function and class, so library \n\
+                     builds still carry their API documentation. Other comments \n\
+                     are removed."
+                ),
+        )
+        .arg(
+            Arg::new("keep-comments")
+                .long("keep-comments")
+                .action(clap::ArgAction::SetTrue)
+                .conflicts_with("keep-jsdoc")
+                .help("Keep all comments next to the statements they document")
+                .long_help(
+                    "Re-emit every source comment before or after the statement \n\
+                     it documents. Comments inside expressions move to the \n\
+                     nearest statement, and comments of removed code are dropped."
+                ),
+        )
+        .arg(
+            Arg::new("format-only")
+                .long("format-only")
+                .action(clap::ArgAction::SetTrue)
+                .conflicts_with_all(["explain", "plugin"])
+                .help("Pretty-print the input without analyzing or minifying it")
+                .long_help(
+                    "Skip semantic analysis and every transformation and print the \n\
+                     parsed code with indentation and one statement per line, as a \n\
+                     lightweight formatter. Combine with --quote and --semicolons."
+                ),
+        )
+        .arg(
+            Arg::new("quote")
+                .long("quote")
+                .value_name("QUOTE")
+                .value_parser(["auto", "single", "double"])
+                .default_value("auto")
+                .help("Quote character for strings; auto picks the one needing fewer escapes"),
+        )
+        .arg(
+            Arg::new("output-format")
+                .long("output-format")
+                .value_name("FORMAT")
+                .value_parser(["esm", "cjs", "iife", "umd"])
+                .default_value("esm")
+                .help("Module system of the output: esm, cjs, iife or umd")
+                .long_help(
+                    "Keep import and export as written (esm), turn them into require() \n\
+                     and exports (cjs), or wrap the module in a function for browser \n\
+                     scripts (iife) or for CommonJS, AMD and browsers alike (umd). \n\
+                     IIFE and UMD output cannot import other modules."
+                ),
+        )
+        .arg(
+            Arg::new("module-interop")
+                .long("module-interop")
+                .action(clap::ArgAction::SetTrue)
+                .help("Turn import() into require() for cjs output and top-level require() into import for esm")
+                .long_help(
+                    "With --output-format cjs, rewrite dynamic import(m) as \n\
+                     Promise.resolve(require(m)). With esm, turn a top-level require('m'); \n\
+                     or const binding of require('m') into an import. Calls that can't be \n\
+                     converted are kept and reported as warnings."
+                ),
+        )
+        .arg(
+            Arg::new("workers")
+                .long("workers")
+                .action(clap::ArgAction::SetTrue)
+                .conflicts_with_all(["hash-filenames", "format-only"])
+                .help("Compile the workers started with new Worker(new URL(..., import.meta.url)) too")
+                .long_help(
+                    "Find the worker entries the inputs start with \n\
+                     new Worker(new URL('./w.js', import.meta.url)) or SharedWorker, \n\
+                     compile each as a separate output (named as --out-dir and --out-ext \n\
+                     map it), and point the URL at that output. Workers started by \n\
+                     workers are followed as well."
+                ),
+        )
+        .arg(
+            Arg::new("build-info")
+                .long("build-info")
+                .action(clap::ArgAction::SetTrue)
+                .help("Append a comment with the compiler version and hashes of the options and input"),
+        )
+        .arg(
+            Arg::new("max-warnings")
+                .long("max-warnings")
+                .value_name("N")
+                .value_parser(clap::value_parser!(usize))
+                .help("Fail with exit code 6 when there are more than N warnings"),
+        )
+        .arg(
+            Arg::new("warnings-as-errors")
+                .long("warnings-as-errors")
+                .action(clap::ArgAction::SetTrue)
+                .conflicts_with("max-warnings")
+                .help("Fail with exit code 6 on any warning (same as --max-warnings 0)"),
+        )
+        .arg(
+            Arg::new("max-depth")
+                .long("max-depth")
+                .value_name("LEVELS")
+                .value_parser(clap::value_parser!(usize))
+                .default_value("500")
+                .help("Fail on input nested deeper than LEVELS brackets, statements or expressions (at most 10000)"),
+        )
+        .arg(
+            Arg::new("transcode-utf16")
+                .long("transcode-utf16")
+                .global(true)
+                .action(clap::ArgAction::SetTrue)
+                .help("Convert UTF-16 input files to UTF-8 instead of rejecting them")
+                .long_help(
+                    "Convert input files saved as UTF-16 (recognized by their byte order \n\
+                     mark, or by the zero bytes of ASCII text) to UTF-8 before compiling. \n\
+                     Without it, such files fail with an error naming the encoding. A \n\
+                     UTF-8 byte order mark is always dropped."
+                ),
+        )
+        .arg(
+            Arg::new("proposal")
+                .long("proposal")
+                .value_name("PROPOSAL")
+                .value_parser(["decorators", "import-attributes", "using"])
+                .value_delimiter(',')
+                .action(clap::ArgAction::Append)
+                .help("Accept a stage-3 proposal in the inputs: decorators, import-attributes or using")
+                .long_help(
+                    "Accept syntax from a stage-3 proposal: `decorators` (`@dec class A {}`), \n\
+                     `import-attributes` (`import data from \"./a.json\" with { type: \"json\" }`) \n\
+                     or `using` (`using` and `await using` declarations). The syntax is \n\
+                     kept as written. Repeat the flag or separate names with commas; \n\
+                     without it, inputs using a proposal fail to parse."
+                ),
+        )
+        .arg(
+            Arg::new("strip-types")
+                .long("strip-types")
+                .action(clap::ArgAction::SetTrue)
+                .help("Remove Flow type annotations and type-only comments")
+                .long_help(
+                    "Accept Flow type annotations (`x: ?number`, `type A = B`, \n\
+                     `import type`) and remove them, so annotated code needs no \n\
+                     separate Babel pass. Comments that only carry types, such as \n\
+                     `/*: number */`, `// @flow` and `/** @type {T} */`, are dropped \n\
+                     from --keep-comments and --keep-jsdoc output."
+                ),
+        )
+        .arg(
+            Arg::new("global-name")
+                .long("global-name")
+                .value_name("NAME")
+                .help("Global variable to expose the exports of iife or umd output on"),
+        )
+        .arg(
+            Arg::new("semicolons")
+                .long("semicolons")
+                .value_name("MODE")
+                .value_parser(["auto", "always", "remove"])
+                .default_value("auto")
+                .help("Print semicolons where needed (auto), after every statement (always) or never (remove)"),
+        )
+        .arg(
+            Arg::new("output")
+                .short('o')
+                .long("output")
+                .help("Output file for minified JavaScript")
+                .long_help(
+                    "Path to the output file where the minified JavaScript \n\
+                     will be saved. If not specified, it is written next to the \n\
+                     input as <name>.min.js (see --out-dir and --out-ext). File \n\
+                     will be created if it doesn't exist."
+                )
+                .value_name("OUTPUT_FILE")
+                .value_parser(clap::value_parser!(PathBuf)),
+        )
+        .arg(
+            Arg::new("in-place")
+                .long("in-place")
+                .action(clap::ArgAction::SetTrue)
+                .conflicts_with_all(["output", "hash-filenames"])
+                .help("Overwrite each input file with its compiled output")
+                .long_help(
+                    "Write the output over the input file instead of to a separate file. \n\
+                     The input is only replaced once it has compiled successfully, and \n\
+                     the replacement is atomic. Combine with --backup to keep the original."
+                ),
+        )
+        .arg(
+            Arg::new("out-dir")
+                .long("out-dir")
+                .value_name("DIR")
+                .value_parser(clap::value_parser!(PathBuf))
+                .conflicts_with_all(["output", "in-place"])
+                .help("Write outputs to DIR, mirroring the input directories")
+                .long_help(
+                    "Write each output to DIR instead of next to its input, named after \n\
+                     the input with --out-ext. Inputs in subdirectories of their common \n\
+                     directory keep that layout (src/lib/a.js -> dist/lib/a.min.js), and \n\
+                     missing directories are created."
+                ),
+        )
+        .arg(
+            Arg::new("out-ext")
+                .long("out-ext")
+                .value_name("EXT")
+                .default_value(DEFAULT_OUT_EXT)
+                .conflicts_with_all(["output", "in-place"])
+                .help("Extension that replaces the input's in output file names"),
+        )
+        .arg(
+            Arg::new("declarations")
+                .long("declarations")
+                .action(clap::ArgAction::SetTrue)
+                .requires("out-dir")
+                .conflicts_with("hash-filenames")
+                .help("Copy the .d.ts file of each input next to its output, with paths rewritten")
+                .long_help(
+                    "Copy the TypeScript declarations next to each input (util.d.ts for \n\
+                     util.js) to --out-dir, named for the output (util.min.d.ts), and \n\
+                     point their relative imports at the outputs, so TypeScript users can \n\
+                     consume the compiled package. Declaration files the copied ones \n\
+                     import are copied too."
+                ),
+        )
+        .arg(
+            Arg::new("force")
+                .long("force")
+                .action(clap::ArgAction::SetTrue)
+                .help("Allow outputs to overwrite input files"),
+        )
+        .arg(
+            Arg::new("preserve-mode")
+                .long("preserve-mode")
+                .action(clap::ArgAction::SetTrue)
+                .help("Give each output the permissions and owner of its input")
+                .long_help(
+                    "Copy the permissions of each input to its output, such as the \n\
+                     executable bit of a script with a #! line, and its owner and group \n\
+                     where allowed. Without it, new outputs get default permissions and \n\
+                     replaced files keep theirs."
+                ),
+        )
+        .arg(
+            Arg::new("preserve-times")
+                .long("preserve-times")
+                .action(clap::ArgAction::SetTrue)
+                .help("Give each output the access and modification times of its input"),
+        )
+        .arg(
+            Arg::new("backup")
+                .long("backup")
+                .action(clap::ArgAction::SetTrue)
+                .help("Keep the previous contents of an overwritten output in <output>.bak"),
+        )
+        .args_conflicts_with_subcommands(true)
+        .subcommand(
+            Command::new("analyze")
+                .about("Run the parser and analyzer only and print the semantic model")
+                .long_about(
+                    "Parse and analyze a file without transforming it, then print the \n\
+                     scope tree, symbol table, closure captures and unsafe-scope flags."
+                )
+                .arg(
+                    Arg::new("file")
+                        .help("JavaScript file to analyze")
+                        .value_name("FILE")
+                        .value_parser(clap::value_parser!(PathBuf))
+                        .required(true),
+                )
+                .arg(
+                    Arg::new("json")
+                        .long("json")
+                        .action(clap::ArgAction::SetTrue)
+                        .help("Print the complete semantic analysis as JSON"),
+                ),
+        )
+        .subcommand(
+            Command::new("ast")
+                .about("Parse a file and print its AST")
+                .long_about(
+                    "Parse a file without analyzing or transforming it and print the AST \n\
+                     as JSON, as an indented tree, or as a Graphviz DOT graph \n\
+                     (e.g. `rjs-compiler ast app.js --format dot | dot -Tsvg > ast.svg`)."
+                )
+                .arg(
+                    Arg::new("file")
+                        .help("JavaScript file to parse")
+                        .value_name("FILE")
+                        .value_parser(clap::value_parser!(PathBuf))
+                        .required(true),
+                )
+                .arg(
+                    Arg::new("format")
+                        .long("format")
+                        .value_name("FORMAT")
+                        .value_parser(["json", "pretty", "dot"])
+                        .default_value("pretty")
+                        .help("Output format"),
+                ),
+        )
+        .subcommand(
+            Command::new("beautify")
+                .about("Pretty-print minified code with its original names restored")
+                .long_about(
+                    "Parse minified code and print it readably for debugging. Names are \n\
+                     restored from the `names` of its source map and from the name map \n\
+                     written by --emit-name-map, where either is given."
+                )
+                .arg(
+                    Arg::new("file")
+                        .help("Minified JavaScript file")
+                        .value_name("FILE")
+                        .value_parser(clap::value_parser!(PathBuf))
+                        .required(true),
+                )
+                .arg(
+                    Arg::new("source-map")
+                        .long("source-map")
+                        .value_name("MAP")
+                        .value_parser(clap::value_parser!(PathBuf))
+                        .help("Source map of the minified file"),
+                )
+                .arg(
+                    Arg::new("name-map")
+                        .long("name-map")
+                        .value_name("NAMES")
+                        .value_parser(clap::value_parser!(PathBuf))
+                        .help("Name map written by --emit-name-map"),
+                )
+                .arg(
+                    Arg::new("output")
+                        .short('o')
+                        .long("output")
+                        .value_name("OUTPUT_FILE")
+                        .value_parser(clap::value_parser!(PathBuf))
+                        .help("Write to a file instead of stdout"),
+                ),
+        )
+        .subcommand(
+            Command::new("diff")
+                .about("Print the structural differences between the ASTs of two files")
+                .long_about(
+                    "Parse two files and list the top-level statements that were added, \n\
+                     removed or changed, ignoring formatting, comments and the spelling of \n\
+                     literals. Exits with status 1 when the programs differ."
+                )
+                .arg(
+                    Arg::new("before")
+                        .help("File to compare from")
+                        .value_name("A")
+                        .value_parser(clap::value_parser!(PathBuf))
+                        .required(true),
+                )
+                .arg(
+                    Arg::new("after")
+                        .help("File to compare to")
+                        .value_name("B")
+                        .value_parser(clap::value_parser!(PathBuf))
+                        .required(true),
+                ),
+        )
+        .arg(
+            Arg::new("input")
+                .help("Input JavaScript files to compile")
+                .long_help(
+                    "Paths to the JavaScript source files that will be compiled. \n\
+                     The files must exist and be readable. With several files, each \n\
+                     is written to <name>.min.js next to it and a summary table is \n\
+                     printed at the end."
+                )
+                .value_name("FILE")
+                .value_parser(clap::value_parser!(PathBuf))
+                .num_args(1..)
+                .index(1),
+        )
+        .arg(
+            Arg::new("check")
+                .long("check")
+                .action(clap::ArgAction::SetTrue)
+                .help("Run correctness checks on the inputs instead of compiling them")
+                .long_help(
+                    "Report assignments to constants, duplicate declarations, undeclared \n\
+                     names in modules, unreachable code and `with` statements as \n\
+                     diagnostics in the --diagnostics-format (text by default), and \n\
+                     write nothing. Fails with exit code 8 when a check finds an error, \n\
+                     and with exit code 6 when warnings exceed --max-warnings."
+                ),
+        )
+        .arg(
+            Arg::new("dry-run")
+                .long("dry-run")
+                .action(clap::ArgAction::SetTrue)
+                .help("Compile without writing any files and print the would-be output path and size")
+                .long_help(
+                    "Run the whole pipeline but write nothing: no output file, source \n\
+                     map, manifest or report. Prints the path the output would be \n\
+                     written to, its size and compression ratio, and the warnings. \n\
+                     Combine with --max-output-size or --max-warnings to fail a CI \n\
+                     build that goes over budget."
+                ),
+        )
+        .arg(
+            Arg::new("no-progress")
+                .long("no-progress")
+                .action(clap::ArgAction::SetTrue)
+                .help("Hide the progress bar when compiling several files"),
+        )
+        .get_matches();
+
+    let input_files: Vec<PathBuf> = matches.get_many::<PathBuf>("input").map(|files| files.cloned().collect()).unwrap_or_default();
+    if input_files.len() > 1 && matches.contains_id("output") {
+        return Err(CompilerError::InvalidArguments(
+            "--output takes a single input file; with several, use --out-dir or let each be written to <name>.min.js"
+                .to_string(),
+        ));
+    }
+    let max_depth = matches.get_one::<usize>("max-depth").copied().unwrap_or(parser::depth::DEFAULT_MAX_DEPTH);
+    if max_depth > parser::depth::MAX_DEPTH_LIMIT {
+        return Err(CompilerError::InvalidArguments(format!(
+            "--max-depth {} is over the largest supported limit, {}",
+            max_depth,
+            parser::depth::MAX_DEPTH_LIMIT
+        )));
+    }
+    let in_place = matches.get_flag("in-place");
+    let output_file = match input_files.as_slice() {
+        [input] if in_place => Some(input.clone()),
+        _ => matches.get_one::<PathBuf>("output").cloned(),
+    };
+    let verbosity = logging::Verbosity::from_flags(matches.get_flag("quiet"), matches.get_count("verbose"));
+    let verbose = verbosity >= logging::Verbosity::Verbose;
+    let log_format = matches
+        .get_one::<String>("log-format")
+        .map_or(Ok(logging::LogFormat::default()), |format| format.parse())
+        .map_err(CompilerError::InvalidArguments)?;
+    let log = logging::LogConfig::default()
+        .with_verbosity(verbosity)
+        .with_format(log_format)
+        .with_filter(matches.get_one::<String>("log-filter").cloned().or_else(|| std::env::var(logging::LOG_FILTER_ENV).ok()));
+    let timing = matches.get_flag("timing");
+    let max_output_size = match matches.get_one::<usize>("max-output-size") {
+        Some(0) => None,
+        Some(&size) => Some(size),
+        None => Some(generator::DEFAULT_MAX_OUTPUT_SIZE),
+    };
+    let max_memory_usage = matches.get_one::<usize>("max-memory").copied().filter(|&size| size > 0);
+    let stream = matches.get_flag("stream");
+    let serve = matches.get_flag("serve");
+    let lsp = matches.get_flag("lsp");
+    let plugins = matches
+        .get_many::<PathBuf>("plugin")
+        .map(|paths| paths.cloned().collect())
+        .unwrap_or_default();
+    let module_exports = matches
+        .get_one::<String>("module-exports")
+        .map_or(Ok(transformer::export_mangling::ModuleExports::Keep), |mode| mode.parse())
+        .map_err(CompilerError::InvalidArguments)?;
+    let export_map = matches.get_one::<PathBuf>("export-map").cloned();
+    if export_map.is_some() && module_exports != transformer::export_mangling::ModuleExports::Mangle {
+        return Err(CompilerError::InvalidArguments("--export-map requires --module-exports mangle".to_string()));
+    }
+    let mut drop_calls: Vec<String> = matches
+        .get_many::<String>("drop")
+        .map(|names| names.map(|name| name.trim().to_string()).collect())
+        .unwrap_or_default();
+    if matches.get_flag("drop-console") {
+        drop_calls.push("console.*".to_string());
+    }
+    if let Some(invalid) = drop_calls.iter().find(|name| name.split('.').any(str::is_empty)) {
+        return Err(CompilerError::InvalidArguments(format!("Invalid --drop name '{}'", invalid)));
+    }
+    let dedupe_strings = matches.get_one::<usize>("dedupe-strings-threshold").copied().or(
+        matches
+            .get_flag("dedupe-strings")
+            .then_some(transformer::string_dedupe::DEFAULT_DEDUPE_STRINGS_THRESHOLD),
+    );
+    let transformer = transformer::TransformerConfig::default()
+        .with_identifier_renaming(!matches.get_flag("no-mangle"))
+        .with_dead_code_elimination(!matches.get_flag("no-dce"))
+        .with_drop_calls(drop_calls)
+        .with_keep_dropped_call_arguments(matches.get_flag("keep-dropped-args"))
+        .with_collapse_vars(!matches.get_flag("no-collapse-vars"))
+        .with_inline_captures(!matches.get_flag("no-inline-captures"))
+        .with_if_return(!matches.get_flag("no-if-return"))
+        .with_drop_debugger(matches.get_flag("drop-debugger"))
+        .with_hoist_funs(matches.get_flag("hoist-funs"))
+        .with_hoist_vars(matches.get_flag("hoist-vars"))
+        .with_property_minification(matches.get_flag("mangle-props") || matches.get_flag("mangle-props-debug"))
+        .with_debug_property_names(matches.get_flag("mangle-props-debug"))
+        .with_inline_enums(!matches.get_flag("no-inline-enums"))
+        .with_function_minification(!matches.get_flag("no-inline"))
+        .with_aggressive_optimization(matches.get_flag("aggressive"))
+        .with_explain(matches.contains_id("explain"))
+        .with_module_exports(module_exports)
+        .with_dedupe_strings(dedupe_strings)
+        .with_name_seed(matches.get_one::<u64>("seed").copied())
+        .with_worker_threads(matches.get_one::<usize>("threads").copied().unwrap_or(0));
+    let explain = matches.get_one::<PathBuf>("explain").cloned();
+    let explain_format = matches
+        .get_one::<String>("explain-format")
+        .map_or(Ok(transformer::explain::ExplainFormat::default()), |format| format.parse())
+        .map_err(CompilerError::InvalidArguments)?;
+    let diagnostics_format = matches
+        .get_one::<String>("diagnostics-format")
+        .map(|format| format.parse())
+        .transpose()
+        .map_err(CompilerError::InvalidArguments)?;
+    let quote = matches
+        .get_one::<String>("quote")
+        .map_or(Ok(generator::QuoteStrategy::Auto), |quote| quote.parse())
+        .map_err(CompilerError::InvalidArguments)?;
+    let semicolons = matches
+        .get_one::<String>("semicolons")
+        .map_or(Ok(generator::SemicolonStrategy::Auto), |semicolons| semicolons.parse())
+        .map_err(CompilerError::InvalidArguments)?;
+    let module_format = matches
+        .get_one::<String>("output-format")
+        .map_or(Ok(generator::module_format::ModuleFormat::default()), |format| format.parse())
+        .map_err(CompilerError::InvalidArguments)?;
+    let bundle_report_format = matches
+        .get_one::<String>("bundle-report-format")
+        .map_or(Ok(generator::bundle_report::BundleReportFormat::default()), |format| format.parse())
+        .map_err(CompilerError::InvalidArguments)?;
+    let proposals = matches
+        .get_many::<String>("proposal")
+        .unwrap_or_default()
+        .map(|proposal| proposal.parse())
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(CompilerError::InvalidArguments)?;
+    let command = match matches.subcommand() {
+        Some(("analyze", sub_matches)) => Some(CliCommand::Analyze {
+            file: sub_matches.get_one::<PathBuf>("file").cloned().ok_or(CompilerError::MissingInputFile)?,
+            json: sub_matches.get_flag("json"),
+        }),
+        Some(("ast", sub_matches)) => Some(CliCommand::Ast {
+            file: sub_matches.get_one::<PathBuf>("file").cloned().ok_or(CompilerError::MissingInputFile)?,
+            format: sub_matches
+                .get_one::<String>("format")
+                .map_or(Ok(parser::ast_dump::AstFormat::default()), |format| format.parse())
+                .map_err(CompilerError::InvalidArguments)?,
+        }),
+        Some(("beautify", sub_matches)) => Some(CliCommand::Beautify {
+            file:       sub_matches.get_one::<PathBuf>("file").cloned().ok_or(CompilerError::MissingInputFile)?,
+            source_map: sub_matches.get_one::<PathBuf>("source-map").cloned(),
+            name_map:   sub_matches.get_one::<PathBuf>("name-map").cloned(),
+            output:     sub_matches.get_one::<PathBuf>("output").cloned(),
+        }),
+        Some(("diff", sub_matches)) => Some(CliCommand::Diff {
+            before: sub_matches.get_one::<PathBuf>("before").cloned().ok_or(CompilerError::MissingInputFile)?,
+            after:  sub_matches.get_one::<PathBuf>("after").cloned().ok_or(CompilerError::MissingInputFile)?,
+        }),
+        _ => None,
+    };
+
+    Ok(CompilerConfig {
+        input_files,
+        output_file,
+        verbose,
+        log,
+        timing,
+        max_output_size,
+        max_memory_usage,
+        stream,
+        serve,
+        lsp,
+        command,
+        plugins,
+        transformer,
+        explain,
+        explain_format,
+        diagnostics_format,
+        export_map,
+        api_report: matches.get_one::<PathBuf>("api-report").cloned(),
+        hash_filenames: matches.get_flag("hash-filenames"),
+        target: matches.get_one::<target::Targets>("target").cloned(),
+        polyfill_report: matches.get_one::<PathBuf>("polyfill-report").cloned(),
+        polyfill_module: matches.get_one::<String>("polyfill-module").cloned(),
+        keep_jsdoc: matches.get_flag("keep-jsdoc"),
+        keep_comments: matches.get_flag("keep-comments"),
+        format_only: matches.get_flag("format-only"),
+        quote,
+        semicolons,
+        indent: matches.get_one::<generator::IndentStyle>("indent").copied().unwrap_or_default(),
+        max_line_len: matches.get_one::<usize>("max-line-len").copied(),
+        module_format,
+        global_name: matches.get_one::<String>("global-name").cloned(),
+        module_interop: matches.get_flag("module-interop"),
+        workers: matches.get_flag("workers"),
+        declarations: matches.get_flag("declarations"),
+        build_info: matches.get_flag("build-info"),
+        max_warnings: if matches.get_flag("warnings-as-errors") {
+            Some(0)
+        } else {
+            matches.get_one::<usize>("max-warnings").copied()
+        },
+        progress: !matches.get_flag("no-progress"),
+        dry_run: matches.get_flag("dry-run"),
+        size_budget: generator::size_budget::SizeBudget::default()
+            .with_max_bytes(matches.get_one::<usize>("max-size").copied())
+            .with_max_gzip_bytes(matches.get_one::<usize>("max-gzip-size").copied()),
+        bundle_report: matches.get_one::<PathBuf>("bundle-report").cloned(),
+        bundle_report_format,
+        emit_name_map: matches.get_flag("emit-name-map"),
+        check: matches.get_flag("check"),
+        max_depth,
+        transcode_utf16: matches.get_flag("transcode-utf16"),
+        proposals,
+        strip_types: matches.get_flag("strip-types"),
+        in_place,
+        backup: matches.get_flag("backup"),
+        out_dir: matches.get_one::<PathBuf>("out-dir").cloned(),
+        out_ext: matches.get_one::<String>("out-ext").cloned().unwrap_or_else(|| DEFAULT_OUT_EXT.to_string()),
+        force: matches.get_flag("force"),
+        preserve_mode: matches.get_flag("preserve-mode"),
+        preserve_times: matches.get_flag("preserve-times"),
+    })
+}
+
+/// Output bytes of `code` per top-level declaration of the input, largest first
+///
+/// `code` is the complete output, including the polyfill imports before the code of
+/// `generation_result`; they are counted as one entry without a line.
+fn output_contributors(
+    code: &str,
+    generation_result: &generator::GenerationResult,
+    source_code: &str,
+    declarations: &[generator::size_budget::Declaration],
+) -> Vec<generator::size_budget::Contributor> {
+    let mut contributors = generation_result.source_map.as_ref().map_or_else(Vec::new, |source_map| {
+        generator::size_budget::attribute(&generation_result.code, source_map, source_code, declarations)
+    });
+    let prelude_size = code.len() - generation_result.code.len();
+    if prelude_size > 0 {
+        contributors.push(generator::size_budget::Contributor {
+            name:  "(polyfill imports)".to_string(),
+            line:  None,
+            bytes: prelude_size,
+        });
+        contributors.sort_by(|a, b| b.bytes.cmp(&a.bytes));
+    }
+    contributors
+}
+
+/// Fails when an output of `size` is over the size budget, listing the declarations
+/// its bytes came from
+///
+/// # Errors
+///
+/// Returns `CompilerError::BudgetExceeded` if the raw or gzip size is over budget.
+fn check_size_budget(
+    size: &generator::size_budget::OutputSize,
+    contributors: &[generator::size_budget::Contributor],
+    output_path: &Path,
+    config: &CompilerConfig,
+) -> CompilerResult<()> {
+    const LISTED_CONTRIBUTORS: usize = 10;
+
+    let excesses = config.size_budget.check(size);
+    if excesses.is_empty() {
+        if config.verbose {
+            tracing::debug!("📦 Within size budget: {} bytes, {} bytes gzip", size.bytes, size.gzip_bytes);
+        }
+        return Ok(());
+    }
+
+    let mut message = format!("Size budget exceeded for {}:", output_path.display());
+    for excess in &excesses {
+        message.push_str(&format!("\n   {}", excess));
+    }
+    message.push_str("\nLargest contributors:");
+    for contributor in contributors.iter().take(LISTED_CONTRIBUTORS) {
+        message.push_str(&format!("\n{}", contributor));
+    }
+    if contributors.len() > LISTED_CONTRIBUTORS {
+        message.push_str(&format!("\n   ... and {} more", contributors.len() - LISTED_CONTRIBUTORS));
+    }
+    Err(CompilerError::BudgetExceeded(message))
+}
+
+/// Writes the report of where the bytes of the output at `output_path` came from
+///
+/// # Errors
+///
+/// Returns `CompilerError::IoError` if the report cannot be written.
+fn write_bundle_report(
+    report_path: &Path,
+    output_path: &Path,
+    file_path: &Path,
+    size: &generator::size_budget::OutputSize,
+    contributors: Vec<generator::size_budget::Contributor>,
+    format: generator::bundle_report::BundleReportFormat,
+) -> CompilerResult<()> {
+    let output_name = output_path.file_name().unwrap_or_default().to_string_lossy();
+    let report = generator::bundle_report::bundle_report(
+        &output_name,
+        size,
+        vec![(file_path.display().to_string(), contributors)],
+    );
+    write_atomically(report_path, generator::bundle_report::render_report(&report, format), false)
+        .map_err(|e| CompilerError::IoError(format!("Failed to write bundle report '{}': {}", report_path.display(), e)))
+}
+
+/// Fails when the transformer and generator warnings outnumber `max`
+///
+/// Notes are not counted. Transformer warnings are printed before failing unless
+/// `--diagnostics-format` already printed them; generator warnings always are.
+///
+/// # Errors
+///
+/// Returns `CompilerError::TooManyWarnings` if there are more than `max` warnings.
+fn check_warning_limit(
+    max: usize,
+    transform_warnings: &[transformer::diagnostics::Diagnostic],
+    generator_warnings: &[String],
+    config: &CompilerConfig,
+    source_code: &str,
+    file_path: &Path,
+) -> CompilerResult<()> {
+    let transform_warnings: Vec<_> = transform_warnings
+        .iter()
+        .filter(|diagnostic| diagnostic.severity == transformer::diagnostics::Severity::Warning)
+        .cloned()
+        .collect();
+    let count = transform_warnings.len() + generator_warnings.len();
+    if count <= max {
+        return Ok(());
+    }
+    // A dry run has already printed them
+    if config.diagnostics_format.is_none() && !config.dry_run {
+        eprint!(
+            "{}",
+            transformer::diagnostics::render_diagnostics(
+                &transform_warnings,
+                source_code,
+                &file_path.display().to_string(),
+                transformer::diagnostics::DiagnosticFormat::Text,
+            )
+        );
+    }
+    Err(CompilerError::TooManyWarnings { count, max })
+}
+
+/// Runs the correctness checks on every input and prints what they find.
+///
+/// Diagnostics go to stdout in the `--diagnostics-format`, as one JSON array for all
+/// files in the JSON format. Syntax errors are reported as errors of the `syntax` check.
+///
+/// # Errors
+///
+/// Returns `CompilerError::ChecksFailed` if any check reports an error,
+/// `CompilerError::TooManyWarnings` if the warnings exceed `--max-warnings`,
+/// `CompilerError::MissingInputFile` without inputs, or `CompilerError::FileNotFound` if
+/// an input cannot be read.
+fn check_files(config: &CompilerConfig) -> CompilerResult<()> {
+    if config.input_files.is_empty() {
+        return Err(CompilerError::MissingInputFile);
+    }
+    let format = config.diagnostics_format.unwrap_or_default();
+    let (mut errors, mut warnings) = (0, 0);
+    let mut json = Vec::new();
+    let parser_config = input_parser_config(config);
+    for file_path in &config.input_files {
+        let source_code = read_source(file_path, config.transcode_utf16)?;
+        let allocator = parser::Allocator::default();
+        let parse_result =
+            parser::parse_js_in(&source_code, &file_path.to_string_lossy(), &allocator, &parser_config);
+        let diagnostics = if parse_result.errors.is_empty() {
+            let mut diagnostics = analyzer::checks::check_program(&parse_result.program);
+            // Compiling would fail on these, so they are errors here too
+            diagnostics.extend(parse_result.dropped_spans().into_iter().map(|span| {
+                transformer::diagnostics::Diagnostic::error("The compiler cannot represent this code yet")
+                    .with_pass("unsupported")
+                    .with_span(Some(span))
+            }));
+            diagnostics
+        } else {
+            parse_result
+                .errors
+                .iter()
+                .map(|error| transformer::diagnostics::Diagnostic::error(error.to_string()).with_pass("syntax"))
+                .collect()
+        };
+
+        for diagnostic in &diagnostics {
+            match diagnostic.severity {
+                transformer::diagnostics::Severity::Error => errors += 1,
+                transformer::diagnostics::Severity::Warning => warnings += 1,
+                transformer::diagnostics::Severity::Note => {}
+            }
+        }
+        let rendered = transformer::diagnostics::render_diagnostics(
+            &diagnostics,
+            &source_code,
+            &file_path.display().to_string(),
+            format,
+        );
+        match format {
+            transformer::diagnostics::DiagnosticFormat::Text => print!("{}", rendered),
+            transformer::diagnostics::DiagnosticFormat::Json => {
+                json.extend(serde_json::from_str::<Vec<serde_json::Value>>(&rendered).unwrap_or_default());
+            }
+        }
+    }
+
+    if format == transformer::diagnostics::DiagnosticFormat::Json {
+        println!("{}", serde_json::to_string_pretty(&json).unwrap_or_default());
+    } else {
+        tracing::info!("🔎 Checked {} files: {} errors, {} warnings", config.input_files.len(), errors, warnings);
+    }
+    if errors > 0 {
+        return Err(CompilerError::ChecksFailed(errors));
+    }
+    match config.max_warnings {
+        Some(max) if warnings > max => Err(CompilerError::TooManyWarnings { count: warnings, max }),
+        _ => Ok(()),
+    }
+}
+
+/// Prints what a `--dry-run` would have written: the output path, its size and
+/// compression ratio, and the transformer warnings.
+fn report_dry_run(
+    output_path: &Path,
+    output_size: usize,
+    diagnostics: &generator::GeneratorDiagnostics,
+    transform_warnings: &[transformer::diagnostics::Diagnostic],
+    source_code: &str,
+    file_path: &Path,
+) {
+    let reduction = if source_code.is_empty() {
+        0.0
+    } else {
+        100.0 * (1.0 - output_size as f64 / source_code.len() as f64)
+    };
+    tracing::info!(
+        "🔎 Dry run: would write {} bytes to {} ({} bytes in, {:.1}% smaller)",
+        output_size,
+        output_path.display(),
+        source_code.len(),
+        reduction
+    );
+
+    let transform_warnings: Vec<_> = transform_warnings
+        .iter()
+        .filter(|diagnostic| diagnostic.severity == transformer::diagnostics::Severity::Warning)
+        .cloned()
+        .collect();
+    for line in transformer::diagnostics::render_diagnostics(
+        &transform_warnings,
+        source_code,
+        &file_path.display().to_string(),
+        transformer::diagnostics::DiagnosticFormat::Text,
+    )
+    .lines()
+    {
+        tracing::warn!("⚠️  {}", line);
+    }
+    tracing::info!("   ⚠️  Warnings: {}", transform_warnings.len() + diagnostics.warnings.len());
+}
+
+/// Writes the exports of the input and of the transformed program to `report_path`
+///
+/// The output is analyzed again to find its exports. Exports that were removed or
+/// changed kind or arity are also reported on stderr.
+///
+/// # Errors
+///
+/// Returns `CompilerError::TransformError` if the output cannot be analyzed, or `CompilerError::IoError` if the report
+/// cannot be written.
+fn write_api_report(
+    report_path: &PathBuf,
+    input_api: &analyzer::api_surface::ApiSurface,
+    output: &parser::ast_types::Program,
+    analyzer_config: &analyzer::AnalyzerConfig,
+) -> CompilerResult<()> {
+    let output_analysis = analyzer::analyze_ast(output, analyzer_config)
+        .map_err(|e| CompilerError::TransformError(format!("Analysis of the output failed: {}", e)))?;
+    let output_api = analyzer::api_surface::api_surface(output, &output_analysis);
+    let changes = analyzer::api_surface::compare(input_api, &output_api);
+    for change in &changes {
+        if !matches!(change, analyzer::api_surface::ApiChange::Added { .. }) {
+            tracing::warn!("⚠️  {}", change);
+        }
+    }
+
+    let report = serde_json::json!({
+        "input": input_api,
+        "output": output_api,
+        "changes": changes,
+    });
+    write_atomically(report_path, serde_json::to_string_pretty(&report).unwrap_or_default() + "\n", false)
+        .map_err(|e| CompilerError::IoError(format!("Failed to write API report '{}': {}", report_path.display(), e)))
+}
+
+/// Resolves the target environments for compiling `file_path`.
+///
+/// Uses `--target` when given, otherwise the nearest `.browserslistrc` in the input
+/// file's directory or one of its ancestors, and finally no restriction at all.
+///
+/// # Errors
+///
+/// Returns `CompilerError::IoError` if the `.browserslistrc` cannot be read, or `CompilerError::InvalidArguments` if it
+/// contains an unsupported query.
+fn resolve_target(file_path: &Path, config: &CompilerConfig) -> CompilerResult<target::Targets> {
+    if let Some(ref target) = config.target {
+        return Ok(target.clone());
+    }
 
-    let input_file = matches.get_one::<PathBuf>("input").cloned();
-    let output_file = matches.get_one::<PathBuf>("output").cloned();
-    let verbose = matches.get_flag("verbose");
+    let directory = std::fs::canonicalize(file_path)
+        .ok()
+        .and_then(|path| path.parent().map(Path::to_path_buf));
+    let Some(rc_path) = directory.as_deref().and_then(target::Targets::find_browserslistrc) else {
+        return Ok(target::Targets::latest());
+    };
 
-    Ok(CompilerConfig {
-        input_file,
-        output_file,
-        verbose,
-    })
+    let contents = std::fs::read_to_string(&rc_path)
+        .map_err(|e| CompilerError::IoError(format!("Failed to read '{}': {}", rc_path.display(), e)))?;
+    let targets = contents
+        .parse()
+        .map_err(|e| CompilerError::InvalidArguments(format!("{}: {}", rc_path.display(), e)))?;
+    if config.verbose {
+        tracing::debug!("🎯 Using targets from {}", rc_path.display());
+    }
+    Ok(targets)
+}
+
+/// Parses a byte size such as `1024`, `512K`, `10M` or `1G` (binary units).
+///
+/// # Arguments
+///
+/// * `value` - The size string from the command line
+///
+/// # Returns
+///
+/// Returns the size in bytes, or a message describing why the value is invalid.
+fn parse_byte_size(value: &str) -> Result<usize, String> {
+    let trimmed = value.trim();
+    let (digits, multiplier) = match trimmed.char_indices().last() {
+        Some((index, 'k' | 'K')) => (&trimmed[..index], 1024),
+        Some((index, 'm' | 'M')) => (&trimmed[..index], 1024 * 1024),
+        Some((index, 'g' | 'G')) => (&trimmed[..index], 1024 * 1024 * 1024),
+        _ => (trimmed, 1),
+    };
+    
+    digits.parse::<usize>()
+        .ok()
+        .and_then(|count| count.checked_mul(multiplier))
+        .ok_or_else(|| format!("invalid size '{}' (expected e.g. 1024, 512K, 10M)", value))
 }
 
 /// Displays the welcome message for the application.
@@ -179,8 +2416,8 @@ fn parse_command_line_arguments() -> CompilerResult<CompilerConfig> {
 /// This function prints the application greeting and version information
 /// in a user-friendly format.
 fn display_welcome_message() {
-    println!("🦀 Hello Rust!");
-    println!("Welcome to RJS Compiler v{}", VERSION);
+    tracing::info!("🦀 Hello Rust!");
+    tracing::info!("Welcome to RJS Compiler v{}", VERSION);
 }
 
 /// Displays verbose information about the current configuration.
@@ -193,27 +2430,131 @@ fn display_welcome_message() {
 ///
 /// ```rust,no_run
 /// let config = CompilerConfig {
-///     input_file: Some(PathBuf::from("test.js")),
+///     input_files: vec![PathBuf::from("test.js")],
 ///     output_file: Some(PathBuf::from("build.js")),
 ///     verbose: true,
+///     log: logging::LogConfig::default(),
+///     timing: false,
+///     max_output_size: None,
+///     max_memory_usage: None,
+///     stream: false,
+///     serve: false,
+///     lsp: false,
+///     command: None,
+///     plugins: Vec::new(),
+///     transformer: transformer::TransformerConfig::default(),
+///     explain: None,
+///     explain_format: transformer::explain::ExplainFormat::Text,
+///     diagnostics_format: None,
+///     export_map: None,
+///     api_report: None,
+///     hash_filenames: false,
+///     target: None,
+///     polyfill_report: None,
+///     polyfill_module: None,
+///     keep_jsdoc: false,
+///     keep_comments: false,
+///     format_only: false,
+///     quote: generator::QuoteStrategy::Auto,
+///     semicolons: generator::SemicolonStrategy::Auto,
+///     indent: generator::IndentStyle::Spaces(2),
+///     max_line_len: None,
+///     module_format: generator::module_format::ModuleFormat::Esm,
+///     global_name: None,
+///     module_interop: false,
+///     workers: false,
+///     declarations: false,
+///     build_info: false,
+///     max_warnings: None,
+///     progress: true,
+///     dry_run: false,
+///     size_budget: Default::default(),
+///     bundle_report: None,
+///     bundle_report_format: Default::default(),
+///     emit_name_map: false,
+///     check: false,
+///     max_depth: parser::depth::DEFAULT_MAX_DEPTH,
+///     transcode_utf16: false,
+///     proposals: Vec::new(),
+///     strip_types: false,
+///     in_place: false,
+///     backup: false,
+///     out_dir: None,
+///     out_ext: DEFAULT_OUT_EXT.to_string(),
+///     force: false,
+///     preserve_mode: false,
+///     preserve_times: false,
 /// };
 /// display_verbose_info(&config);
 /// ```
 fn display_verbose_info(config: &CompilerConfig) {
-    println!("🔍 Verbose mode enabled");
-    println!("📋 Configuration:");
+    tracing::debug!("🔍 Verbose mode enabled");
+    tracing::debug!("📋 Configuration:");
     
-    if let Some(ref input_path) = config.input_file {
-        println!("   📁 Input file: {}", input_path.display());
+    for input_path in &config.input_files {
+        tracing::debug!("   📁 Input file: {}", input_path.display());
     }
     
     if let Some(ref output_path) = config.output_file {
-        println!("   📄 Output file: {}", output_path.display());
+        tracing::debug!("   📄 Output file: {}", output_path.display());
     } else {
-        println!("   📄 Output file: stdout (console)");
+        match config.out_dir {
+            Some(ref out_dir) => tracing::debug!("   📄 Output files: <name>{} in {}", config.out_ext, out_dir.display()),
+            None => tracing::debug!("   📄 Output files: <name>{} next to each input", config.out_ext),
+        }
     }
     
-    println!("   🔧 Verbose output: {}", config.verbose);
+    tracing::debug!("   🔧 Verbose output: {}", config.verbose);
+    tracing::debug!("   ⏱️  Phase timing: {}", config.timing);
+    match config.max_output_size {
+        Some(limit) => tracing::debug!("   📏 Max output size: {} bytes", limit),
+        None => tracing::debug!("   📏 Max output size: unlimited"),
+    }
+    if let Some(limit) = config.max_memory_usage {
+        tracing::debug!("   🧠 Max memory buffer: {} bytes", limit);
+    }
+    if let Some(budget) = config.size_budget.max_bytes {
+        tracing::debug!("   📦 Size budget: {} bytes", budget);
+    }
+    if let Some(budget) = config.size_budget.max_gzip_bytes {
+        tracing::debug!("   📦 Gzip size budget: {} bytes", budget);
+    }
+    tracing::debug!("   🌊 Streaming output: {}", config.stream);
+    tracing::debug!("   #️⃣  Hashed file names: {}", config.hash_filenames);
+    tracing::debug!("   📚 Keep JSDoc: {}", config.keep_jsdoc);
+    tracing::debug!("   💬 Keep comments: {}", config.keep_comments);
+    tracing::debug!("   🎨 Format only: {}", config.format_only);
+    if let Some(columns) = config.max_line_len {
+        tracing::debug!("   📐 Max line length: {} columns", columns);
+    }
+    if let Some(ref target) = config.target {
+        tracing::debug!("   🎯 Target: {}", target);
+    }
+    for plugin in &config.plugins {
+        tracing::debug!("   🔌 Plugin: {}", plugin.display());
+    }
+    let passes: Vec<&str> = transformer::BuiltinPass::ALL
+        .iter()
+        .filter(|pass| pass.is_enabled(&config.transformer))
+        .map(|pass| pass.name())
+        .collect();
+    tracing::debug!("   ⚙️  Transformer passes: {}", if passes.is_empty() { "none".to_string() } else { passes.join(", ") });
+    tracing::debug!("   🔥 Aggressive optimization: {}", config.transformer.aggressive_optimization);
+}
+
+/// Displays the per-phase timing breakdown collected during compilation.
+///
+/// # Arguments
+///
+/// * `report` - The collected phase timings
+fn display_timing_report(report: &TimingReport) {
+    println!("⏱️  Phase timings:");
+    println!("   {:<32} {:>10}", "Phase", "Time (ms)");
+    for phase in &report.phases {
+        let label = format!("{}{}", "  ".repeat(phase.depth), phase.name);
+        println!("   {:<32} {:>10.3}", label, phase.time_ms);
+    }
+    println!("   {:<32} {:>10.3}", "total", report.total_ms());
 }
 
 /// Displays usage information when no input file is provided.
@@ -225,6 +2566,8 @@ fn display_usage_information() {
     println!("   Use --help for more information");
     println!("   Example: {} --verbose my_script.js", APP_NAME);
     println!("   Example: {} -o build.js my_script.js", APP_NAME);
+    println!("   Example: {} analyze --json my_script.js", APP_NAME);
+    println!("   Example: {} ast --format dot my_script.js", APP_NAME);
 }
 
 /// Compiles the specified JavaScript file and saves the minified output.
@@ -241,12 +2584,15 @@ fn display_usage_information() {
 /// # Errors
 ///
 /// Returns `CompilerError::FileNotFound` if the input file doesn't exist.
-/// Returns `CompilerError::ParseError` if file writing fails.
+/// Returns `CompilerError::ParseError` for syntax errors, `CompilerError::TransformError` if
+/// analysis, transformation or generation fails, `CompilerError::IoError` if writing fails,
+/// and `CompilerError::TooManyWarnings` if the warnings exceed `--max-warnings`.
 ///
 /// # Output Behavior
 ///
 /// If an output file is specified in config, the minified code is saved there.
-/// If no output file is specified, defaults to 'build.js' in the input file's directory.
+/// Otherwise the input's name with `--out-ext` is used, in `--out-dir` or next to the input.
+/// An output that would overwrite an input is refused unless `--force` or `--in-place` is given.
 ///
 /// # Examples
 ///
@@ -255,19 +2601,70 @@ fn display_usage_information() {
 /// 
 /// let file_path = PathBuf::from("example.js");
 /// let config = CompilerConfig {
-///     input_file: Some(file_path.clone()),
+///     input_files: vec![file_path.clone()],
 ///     output_file: Some(PathBuf::from("build.js")),
 ///     verbose: false,
+///     log: logging::LogConfig::default(),
+///     timing: false,
+///     max_output_size: None,
+///     max_memory_usage: None,
+///     stream: false,
+///     serve: false,
+///     lsp: false,
+///     command: None,
+///     plugins: Vec::new(),
+///     transformer: transformer::TransformerConfig::default(),
+///     explain: None,
+///     explain_format: transformer::explain::ExplainFormat::Text,
+///     diagnostics_format: None,
+///     export_map: None,
+///     api_report: None,
+///     hash_filenames: false,
+///     target: None,
+///     polyfill_report: None,
+///     polyfill_module: None,
+///     keep_jsdoc: false,
+///     keep_comments: false,
+///     format_only: false,
+///     quote: generator::QuoteStrategy::Auto,
+///     semicolons: generator::SemicolonStrategy::Auto,
+///     indent: generator::IndentStyle::Spaces(2),
+///     max_line_len: None,
+///     module_format: generator::module_format::ModuleFormat::Esm,
+///     global_name: None,
+///     module_interop: false,
+///     workers: false,
+///     declarations: false,
+///     build_info: false,
+///     max_warnings: None,
+///     progress: true,
+///     dry_run: false,
+///     size_budget: Default::default(),
+///     bundle_report: None,
+///     bundle_report_format: Default::default(),
+///     emit_name_map: false,
+///     check: false,
+///     max_depth: parser::depth::DEFAULT_MAX_DEPTH,
+///     transcode_utf16: false,
+///     proposals: Vec::new(),
+///     strip_types: false,
+///     in_place: false,
+///     backup: false,
+///     out_dir: None,
+///     out_ext: DEFAULT_OUT_EXT.to_string(),
+///     force: false,
+///     preserve_mode: false,
+///     preserve_times: false,
 /// };
 /// 
 /// match compile_file(&file_path, &config) {
-///     Ok(()) => println!("Compilation successful"),
+///     Ok(summary) => println!("Compiled to {}", summary.output_path.display()),
 ///     Err(e) => eprintln!("Compilation failed: {}", e),
 /// }
 /// ```
-fn compile_file(file_path: &PathBuf, config: &CompilerConfig) -> CompilerResult<()> {
+fn compile_file(file_path: &PathBuf, config: &CompilerConfig) -> CompilerResult<FileSummary> {
     if config.verbose {
-        println!("📂 Processing input file: {}", file_path.display());
+        tracing::debug!("📂 Processing input file: {}", file_path.display());
     }
     
     // Validate that the file exists
@@ -275,35 +2672,103 @@ fn compile_file(file_path: &PathBuf, config: &CompilerConfig) -> CompilerResult<
         return Err(CompilerError::FileNotFound(file_path.clone()));
     }
     
+    // Determine output destination
+    let output_path = config.output_file.clone().unwrap_or_else(|| {
+        mapped_output_path(file_path, file_path.parent().unwrap_or(Path::new("")), config)
+    });
+    if !config.in_place && !config.force && is_input_file(&output_path, config) {
+        return Err(CompilerError::InvalidArguments(format!(
+            "{} is an input file; pass --force to overwrite it",
+            output_path.display()
+        )));
+    }
+    if !config.dry_run
+        && let Some(directory) = output_path.parent().filter(|directory| !directory.as_os_str().is_empty())
+    {
+        std::fs::create_dir_all(directory).map_err(|e| {
+            CompilerError::IoError(format!("Failed to create output directory '{}': {}", directory.display(), e))
+        })?;
+    }
+    
     if config.verbose {
-        println!("✅ Input file validation passed");
-        println!("🚀 Starting compilation process...");
+        tracing::debug!("✅ Input file validation passed");
+        tracing::debug!("🚀 Starting compilation process...");
     }
     
+    let mut timing_report = TimingReport::default();
+
+    let target = resolve_target(file_path, config)?;
+    if config.verbose {
+        tracing::debug!("🎯 Target: {}", target);
+        let unsupported = target.unsupported();
+        if unsupported.is_empty() {
+            tracing::debug!("   ✅ All syntax passes through");
+        } else {
+            let names: Vec<&str> = unsupported.iter().map(|feature| feature.name()).collect();
+            tracing::debug!("   ⬇️  Needs down-leveling: {}", names.join(", "));
+        }
+    }
+
+    // Load plugins up front so a broken plugin fails before any work is done
+    let plugins = config
+        .plugins
+        .iter()
+        .map(|path| transformer::dynamic_plugin::DynamicPlugin::load(path))
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| CompilerError::TransformError(e.to_string()))?;
+    if config.verbose {
+        for plugin in &plugins {
+            tracing::debug!("🔌 Loaded plugin '{}' ({:?})", transformer::plugin::TransformPlugin::name(plugin), plugin.position());
+        }
+    }
+
     // Read the file content
-    let source_code = std::fs::read_to_string(file_path)
-        .map_err(|_| CompilerError::FileNotFound(file_path.clone()))?;
+    let phase_start = Instant::now();
+    let source_code = read_source(file_path, config.transcode_utf16)?;
+    timing_report.record("read", phase_start);
     
     if config.verbose {
-        println!("📄 Read {} bytes from file", source_code.len());
+        tracing::debug!("📄 Read {} bytes from file", source_code.len());
     }
     
     // Parse the JavaScript file
     let parser_config = parser::ParserConfig {
-        preserve_trivia: config.verbose, // Enable trivia preservation in verbose mode
-        ..parser::ParserConfig::default()
+        preserve_trivia: true, // Comments carry rjs-disable directives
+        ..input_parser_config(config)
     };
-    let parse_result = parser::parse_js(&source_code, &file_path.to_string_lossy(), &parser_config);
+    let phase_start = Instant::now();
+    let allocator = parser::Allocator::default();
+    let arena_result = parser::parse_js_in(&source_code, &file_path.to_string_lossy(), &allocator, &parser_config);
+    let statement_spans = arena_result.statement_spans();
+    let polyfills = target::polyfills::required_polyfills(&arena_result.program, &target);
+    let input_api = config.api_report.as_ref().map(|_| analyzer::api_surface::source_api_surface(&arena_result.program));
+    // A `#!` line has to stay first for the output to run as a script
+    let mut prelude = hashbang_line(&source_code);
+    if let Some(ref module) = config.polyfill_module {
+        prelude += &target::polyfills::import_prelude(&polyfills, module, arena_result.program.source_type.is_module());
+    }
+    let mut doc_comments = if config.keep_jsdoc { arena_result.doc_comments() } else { HashMap::new() };
+    // A formatter must not lose comments, so --format-only keeps them all
+    let keep_comments = config.keep_comments || config.format_only;
+    let mut comments = if keep_comments { arena_result.attached_comments() } else { Default::default() };
+    if config.strip_types {
+        doc_comments.retain(|_, text| !parser::flow::is_type_comment(text));
+        comments.retain(|comment| !parser::flow::is_type_comment(&comment.text));
+    }
+    let parse_result = arena_result.into_parse_result(&parser_config);
+    // Later phases only use the owned AST, so the arena doesn't have to outlive parsing
+    drop(allocator);
+    timing_report.record("parse", phase_start);
     
     if config.verbose {
-        println!("🔍 Phase 1: Parsing completed");
+        tracing::debug!("🔍 Phase 1: Parsing completed");
     }
     
     // Check for parsing errors
     if !parse_result.errors.is_empty() {
-        eprintln!("❌ Parsing errors found:");
+        tracing::error!("❌ Parsing errors found:");
         for error in &parse_result.errors {
-            eprintln!("   {}", error);
+            tracing::error!("   {}", error);
         }
         return Err(CompilerError::ParseError(format!(
             "Found {} parsing errors", 
@@ -311,223 +2776,754 @@ fn compile_file(file_path: &PathBuf, config: &CompilerConfig) -> CompilerResult<
         )));
     }
     
+    // Code the owned AST can't represent would be missing from the output, which
+    // --in-place would then write over the input
+    if let Some(error) = parse_result.unsupported_syntax(&source_code) {
+        tracing::error!("❌ {}", error);
+        return Err(CompilerError::ParseError(error.to_string()));
+    }
+
+    let directives = parse_result
+        .trivia
+        .as_ref()
+        .map(|trivia| transformer::directives::PassDirectives::from_trivia(&source_code, trivia, &statement_spans))
+        .unwrap_or_default();
+
     // Extract the AST
     let ast = parse_result.ast.ok_or_else(|| {
         CompilerError::ParseError("No AST generated despite no errors".to_string())
     })?;
+    // Size budgets and bundle reports blame the statements of the input, before transformation
+    let attribute_output = !config.size_budget.is_unlimited() || config.bundle_report.is_some();
+    let declarations = if !attribute_output {
+        Vec::new()
+    } else {
+        generator::size_budget::declarations(&ast)
+    };
     
     if config.verbose {
-        println!("📊 AST Statistics:");
-        println!("   📋 Statements: {}", ast.body.len());
-        println!("   📘 Source type: {:?}", ast.source_type);
+        tracing::debug!("📊 AST Statistics:");
+        tracing::debug!("   📋 Statements: {}", ast.body.len());
+        tracing::debug!("   📘 Source type: {:?}", ast.source_type);
         
         // Display trivia information if available
         if let Some(ref trivia) = parse_result.trivia {
-            println!("📝 Trivia Preserved:");
-            println!("   💬 Line comments: {}", trivia.line_comments.len());
-            println!("   💬 Block comments: {}", trivia.block_comments.len());
-            println!("   ⬜ Leading whitespace: {}", trivia.leading_whitespace.len());
-            println!("   ⬜ Trailing whitespace: {}", trivia.trailing_whitespace.len());
+            tracing::debug!("📝 Trivia Preserved:");
+            tracing::debug!("   💬 Line comments: {}", trivia.line_comments.len());
+            tracing::debug!("   💬 Block comments: {}", trivia.block_comments.len());
+            tracing::debug!("   ⬜ Leading whitespace: {}", trivia.leading_whitespace.len());
+            tracing::debug!("   ⬜ Trailing whitespace: {}", trivia.trailing_whitespace.len());
             
             // Show first few comments for debugging
             if !trivia.line_comments.is_empty() {
-                println!("   🗺 Sample line comments:");
+                tracing::debug!("   🗺 Sample line comments:");
                 for (i, comment) in trivia.line_comments.iter().take(3).enumerate() {
-                    println!("     {}. '{}' (pos: {}-{})", 
+                    tracing::debug!("     {}. '{}' (pos: {}-{})", 
                         i + 1, comment.text, comment.span.start, comment.span.end);
                 }
             }
             
             if !trivia.block_comments.is_empty() {
-                println!("   🗺 Sample block comments:");
+                tracing::debug!("   🗺 Sample block comments:");
                 for (i, comment) in trivia.block_comments.iter().take(3).enumerate() {
-                    println!("     {}. '{}' (pos: {}-{})", 
+                    tracing::debug!("     {}. '{}' (pos: {}-{})", 
                         i + 1, comment.text, comment.span.start, comment.span.end);
                 }
             }
         }
         
-        // Pretty print AST in JSON format for debugging
-        if let Ok(ast_json) = serde_json::to_string_pretty(&ast) {
-            println!("🌳 AST Structure (JSON):");
-            // Limit output to first 1000 characters for readability
-            let truncated = if ast_json.len() > 1000 {
-                format!("{}...\n(truncated)", &ast_json[..1000])
-            } else {
-                ast_json
-            };
-            println!("{}", truncated);
-        }
-    }
-    
-    // Phase 3: Semantic Analysis
-    if config.verbose {
-        println!("🔍 Phase 3: Starting semantic analysis...");
+        tracing::debug!("🌳 Run `{} ast {}` to print the full AST", APP_NAME, file_path.display());
     }
     
-    let analyzer_config = analyzer::AnalyzerConfig {
-        verbose: config.verbose,
-        preserve_exports: true,
-        aggressive_optimization: false,
-        strict_mode: true,
-    };
-    
-    let analysis_result = analyzer::analyze_ast(&ast, &analyzer_config)
-        .map_err(|e| CompilerError::ParseError(format!("Analysis failed: {}", e)))?;
-    
-    if config.verbose {
-        println!("📊 Analysis Results:");
-        println!("   🏗️  Scopes analyzed: {}", analysis_result.metadata.scope_count);
-        println!("   🏷️  Symbols found: {}", analysis_result.metadata.symbol_count);
-        println!("   🔗 Closure captures: {}", analysis_result.metadata.capture_count);
-        println!("   📤 Export symbols: {}", analysis_result.metadata.export_count);
-        println!("   ⏱️  Analysis time: {}ms", analysis_result.metadata.analysis_time_ms);
-        
-        // Display unsafe scopes
-        if !analysis_result.semantic_flags.unsafe_scopes.is_empty() {
-            println!("   ⚠️  Unsafe scopes detected: {}", analysis_result.semantic_flags.unsafe_scopes.len());
-            for (scope_id, reason) in &analysis_result.semantic_flags.unsafe_scopes {
-                println!("     Scope {}: {:?}", scope_id, reason);
+    // Phases 3 and 4 only run when optimizing; --format-only re-prints the parsed AST
+    let (program, transform_warnings, name_map) = if config.format_only {
+        if config.verbose {
+            tracing::debug!("🎨 Format-only: skipping analysis and transformation");
+        }
+        (ast, Vec::new(), transformer::NameMap::default())
+    } else {
+        // Phase 3: Semantic Analysis
+        if config.verbose {
+            tracing::debug!("🔍 Phase 3: Starting semantic analysis...");
+        }
+
+        let analyzer_config = analyzer::AnalyzerConfig {
+            preserve_exports: config.transformer.module_exports == transformer::export_mangling::ModuleExports::Keep,
+            aggressive_optimization: false,
+            strict_mode: false,
+            kept_names: parse_result.kept_names,
+            dropped_spans: parse_result.dropped_spans,
+            names: parse_result.names,
+            ..analyzer::AnalyzerConfig::default()
+        };
+
+        let phase_start = Instant::now();
+        let analysis_result = analyzer::analyze_ast(&ast, &analyzer_config)
+            .map_err(|e| CompilerError::TransformError(format!("Analysis failed: {}", e)))?;
+        timing_report.record("analyze", phase_start);
+
+        if config.verbose {
+            tracing::debug!("📊 Analysis Results:");
+            tracing::debug!("   🏗️  Scopes analyzed: {}", analysis_result.metadata.scope_count);
+            tracing::debug!("   🏷️  Symbols found: {}", analysis_result.metadata.symbol_count);
+            tracing::debug!("   🔗 Closure captures: {}", analysis_result.metadata.capture_count);
+            tracing::debug!("   📤 Export symbols: {}", analysis_result.metadata.export_count);
+            tracing::debug!("   ⏱️  Analysis time: {}ms", analysis_result.metadata.analysis_time_ms);
+
+            // Display unsafe scopes
+            if !analysis_result.semantic_flags.unsafe_scopes.is_empty() {
+                tracing::debug!("   ⚠️  Unsafe scopes detected: {}", analysis_result.semantic_flags.unsafe_scopes.len());
+                for (scope_id, reason) in &analysis_result.semantic_flags.unsafe_scopes {
+                    tracing::debug!("     Scope {}: {:?}", scope_id, reason);
+                }
             }
+
+            // Display symbol statistics
+            let renamable_symbols = analysis_result.symbol_table.symbols.values()
+                .filter(|s| s.is_renamable)
+                .count();
+            let captured_symbols = analysis_result.symbol_table.symbols.values()
+                .filter(|s| s.is_captured)
+                .count();
+
+            tracing::debug!("   ✅ Renamable symbols: {}", renamable_symbols);
+            tracing::debug!("   📎 Captured symbols: {}", captured_symbols);
         }
-        
-        // Display symbol statistics
-        let renamable_symbols = analysis_result.symbol_table.symbols.values()
-            .filter(|s| s.is_renamable)
-            .count();
-        let captured_symbols = analysis_result.symbol_table.symbols.values()
-            .filter(|s| s.is_captured)
-            .count();
-        
-        println!("   ✅ Renamable symbols: {}", renamable_symbols);
-        println!("   📎 Captured symbols: {}", captured_symbols);
-    }
-    
-    // Phase 4: Transformation
-    if config.verbose {
-        println!("🔄 Phase 4: Starting transformation...");
-    }
-    
-    let _transformer_config = transformer::TransformerConfig {
-        enable_identifier_renaming: true,
-        enable_dead_code_elimination: true,
-        enable_expression_simplification: true,
-        enable_property_minification: true,
-        enable_function_minification: true,
-        enable_rollback: true,
-        verbose: config.verbose,
-        aggressive_optimization: false,
-    };
-    
-    let transformation_result = transformer::transform_ast(ast, analysis_result)
-        .map_err(|e| CompilerError::ParseError(format!("Transformation failed: {}", e)))?;
-    
-    if config.verbose {
-        println!("📊 Transformation Results:");
-        println!("   🏷️  Identifiers renamed: {}", transformation_result.stats.identifiers_renamed);
-        println!("   🗑️  Dead statements removed: {}", transformation_result.stats.dead_statements_removed);
-        println!("   🔧 Expressions simplified: {}", transformation_result.stats.expressions_simplified);
-        println!("   🏠 Properties renamed: {}", transformation_result.stats.properties_renamed);
-        println!("   📎 Functions inlined: {}", transformation_result.stats.functions_inlined);
-        println!("   ⏱️  Transformation time: {}ms", transformation_result.stats.transformation_time_ms);
-        
-        if !transformation_result.warnings.is_empty() {
-            println!("   ⚠️  Warnings:");
-            for warning in &transformation_result.warnings {
-                println!("     {}", warning);
+
+        // Phase 4: Transformation
+        if config.verbose {
+            tracing::debug!("🔄 Phase 4: Starting transformation...");
+        }
+
+        let phase_start = Instant::now();
+        let mut ast_transformer = transformer::Transformer::new(config.transformer.clone().with_target(target.clone()), analysis_result);
+        ast_transformer.set_statement_spans(statement_spans);
+        if config.verbose && !directives.is_empty() {
+            tracing::debug!("🚫 rjs-disable directives protect {} statement(s)", directives.protected_statements());
+        }
+        ast_transformer.set_directives(directives);
+        for plugin in plugins {
+            let position = plugin.position();
+            ast_transformer.register_plugin(Box::new(plugin), position);
+        }
+        let transformation_result = ast_transformer
+            .transform(ast)
+            .map_err(|e| CompilerError::TransformError(format!("Transformation failed: {}", e)))?;
+        timing_report.record("transform", phase_start);
+        for pass in &transformation_result.stats.pass_timings {
+            timing_report.record_nested(&pass.pass_name, pass.time_ms);
+        }
+
+        if config.verbose {
+            tracing::debug!("📊 Transformation Results:");
+            tracing::debug!("   🏷️  Identifiers renamed: {}", transformation_result.stats.identifiers_renamed);
+            tracing::debug!("   🗑️  Dead statements removed: {}", transformation_result.stats.dead_statements_removed);
+            tracing::debug!("   🔧 Expressions simplified: {}", transformation_result.stats.expressions_simplified);
+            tracing::debug!("   🧲 Variables collapsed: {}", transformation_result.stats.variables_collapsed);
+            tracing::debug!("   📌 Captures inlined: {}", transformation_result.stats.captures_inlined);
+            tracing::debug!("   🔀 If/return simplified: {}", transformation_result.stats.if_returns_simplified);
+            tracing::debug!("   🧹 Statements cleaned up: {}", transformation_result.stats.statements_cleaned);
+            tracing::debug!(
+                "   ⬆️  Declarations hoisted: {}",
+                transformation_result.stats.functions_hoisted + transformation_result.stats.variables_hoisted
+            );
+            tracing::debug!("   🏠 Properties renamed: {}", transformation_result.stats.properties_renamed);
+            tracing::debug!("   📎 Functions inlined: {}", transformation_result.stats.functions_inlined);
+            tracing::debug!("   🔁 Loop invariants hoisted: {}", transformation_result.stats.invariants_hoisted);
+            tracing::debug!("   ⏱️  Transformation time: {}ms", transformation_result.stats.transformation_time_ms);
+
+            if !transformation_result.warnings.is_empty() {
+                tracing::debug!("   ⚠️  Warnings:");
+                for warning in &transformation_result.warnings {
+                    tracing::debug!("     {}", warning);
+                }
+            }
+
+            if !transformation_result.identifier_mapping.is_empty() {
+                tracing::debug!("   🔄 Identifier mappings:");
+                for (original, renamed) in transformation_result.identifier_mapping.iter().take(5) {
+                    tracing::debug!("     {} -> {}", original, renamed);
+                }
+                if transformation_result.identifier_mapping.len() > 5 {
+                    tracing::debug!("     ... and {} more", transformation_result.identifier_mapping.len() - 5);
+                }
             }
+
+            tracing::debug!("   🎯 Statements processed: {}", transformation_result.transformed_ast.body.len());
         }
-        
-        if !transformation_result.identifier_mapping.is_empty() {
-            println!("   🔄 Identifier mappings:");
-            for (original, renamed) in transformation_result.identifier_mapping.iter().take(5) {
-                println!("     {} -> {}", original, renamed);
+
+        if let Some(format) = config.diagnostics_format {
+            eprint!(
+                "{}",
+                transformer::diagnostics::render_diagnostics(
+                    &transformation_result.warnings,
+                    &source_code,
+                    &file_path.display().to_string(),
+                    format,
+                )
+            );
+        }
+
+        if let Some(ref report_path) = config.explain
+            && !config.dry_run
+        {
+            let report = transformer::explain::render_report(
+                &transformation_result.explanations,
+                &source_code,
+                config.explain_format,
+            );
+            write_atomically(report_path, report, false)
+                .map_err(|e| CompilerError::IoError(format!("Failed to write explain report '{}': {}", report_path.display(), e)))?;
+            if config.verbose {
+                tracing::debug!("📝 Wrote {} change(s) to explain report {}", transformation_result.explanations.len(), report_path.display());
             }
-            if transformation_result.identifier_mapping.len() > 5 {
-                println!("     ... and {} more", transformation_result.identifier_mapping.len() - 5);
+        }
+
+        if let Some(ref map_path) = config.export_map
+            && !config.dry_run
+        {
+            let export_map = serde_json::to_string_pretty(&transformation_result.export_map).unwrap_or_default();
+            write_atomically(map_path, export_map + "\n", false)
+                .map_err(|e| CompilerError::IoError(format!("Failed to write export map '{}': {}", map_path.display(), e)))?;
+            if config.verbose {
+                tracing::debug!("🗺️  Wrote {} exported name(s) to {}", transformation_result.export_map.len(), map_path.display());
             }
         }
-        
-        println!("   🎯 Statements processed: {}", transformation_result.transformed_ast.body.len());
-    }
+
+        if let (Some(report_path), Some(input_api)) = (&config.api_report, input_api)
+            && !config.dry_run
+        {
+            write_api_report(report_path, &input_api, &transformation_result.transformed_ast, &analyzer_config)?;
+            if config.verbose {
+                tracing::debug!("📝 Wrote API report {}", report_path.display());
+            }
+        }
+
+        let name_map = transformation_result.name_map();
+        (transformation_result.transformed_ast, transformation_result.warnings, name_map)
+    };
     
     // Phase 5: Code Generation
     if config.verbose {
-        println!("🏗️ Phase 5: Starting code generation...");
+        tracing::debug!("🏗️ Phase 5: Starting code generation...");
     }
     
-    let generator_config = generator::GeneratorConfig {
-        format: generator::OutputFormat::Compact,
-        semicolon: generator::SemicolonStrategy::Auto,
-        quote: generator::QuoteStrategy::Auto,
-        preserve_comments: generator::CommentPreservation::None,
-        source_map: generator::SourceMapMode::None,
+    let format = if config.format_only {
+        generator::OutputFormat::Pretty
+    } else if config.keep_jsdoc {
+        generator::OutputFormat::Readable
+    } else {
+        generator::OutputFormat::Compact
+    };
+    let preserve_comments = if keep_comments {
+        generator::CommentPreservation::All
+    } else if config.keep_jsdoc {
+        generator::CommentPreservation::JsDoc
+    } else {
+        generator::CommentPreservation::None
+    };
+    let mut generator_config = generator::GeneratorConfig {
+        format,
+        semicolon: config.semicolons,
+        quote: config.quote,
+        indent: config.indent,
+        max_line_len: config.max_line_len,
+        module_format: config.module_format,
+        global_name: config.global_name.clone(),
+        module_interop: config.module_interop,
+        numbers: if config.format_only {
+            generator::NumberStyle::Preserve
+        } else {
+            generator::NumberStyle::Shortest
+        },
+        preserve_comments,
+        // The map is only kept in memory, to attribute output bytes to the input
+        source_map: if attribute_output {
+            generator::SourceMapMode::File
+        } else {
+            generator::SourceMapMode::None
+        },
+        max_output_size: config.max_output_size,
+        max_memory_usage: config.max_memory_usage,
+        target,
         ..generator::GeneratorConfig::default()
     };
+    if config.build_info {
+        let transformer_config = config.transformer.clone().with_target(generator_config.target.clone());
+        generator_config.build_info =
+            Some(generator::build_info::BuildInfo::new(&transformer_config, &generator_config));
+    }
     
-    let generator = generator::Generator::new(generator_config);
-    let generation_result = generator.generate(&transformation_result.transformed_ast, Some(&source_code))
-        .map_err(|e| CompilerError::ParseError(format!("Code generation failed: {}", e)))?;
-    
-    if config.verbose {
-        println!("📊 Generation Results:");
-        println!("   📏 Original size: {} bytes", generation_result.diagnostics.original_size);
-        println!("   📏 Generated size: {} bytes", generation_result.diagnostics.generated_size);
-        println!("   📉 Compression ratio: {:.1}%", generation_result.diagnostics.compression_ratio * 100.0);
-        println!("   ⏱️  Generation time: {:.2}ms", generation_result.diagnostics.generation_time_ms);
+    let generator = generator::Generator::new(generator_config)
+        .with_doc_comments(doc_comments)
+        .with_comments(comments);
+    // A dry run keeps the code in memory, to measure it and name a hashed output
+    let (generated_code, diagnostics, attribution) = if config.stream && !config.dry_run {
+        // Generate straight into the output file without holding the whole result in memory
+        let phase_start = Instant::now();
+        let diagnostics = stream_to_file(
+            &generator,
+            &program,
+            &source_code,
+            &prelude,
+            &output_path,
+            file_path,
+            config,
+        )?;
+        timing_report.record("generate + write", phase_start);
+        (None, diagnostics, None)
+    } else {
+        let phase_start = Instant::now();
+        let generation_result = generator.generate(&program, Some(&source_code))
+            .map_err(|e| CompilerError::TransformError(format!("Code generation failed: {}", e)))?;
+        timing_report.record("generate", phase_start);
         
-        if generation_result.diagnostics.warning_count > 0 {
-            println!("   ⚠️  Generation warnings: {}", generation_result.diagnostics.warning_count);
-            for warning in &generation_result.diagnostics.warnings {
-                println!("     {}", warning);
+        let code = prelude + &generation_result.code;
+        let attribution = attribute_output.then(|| {
+            let size = generator::size_budget::OutputSize::measure(&code);
+            (size, output_contributors(&code, &generation_result, &source_code, &declarations))
+        });
+        if let Some((ref size, ref contributors)) = attribution
+            && !config.size_budget.is_unlimited()
+        {
+            check_size_budget(size, contributors, &output_path, config)?;
+        }
+        if !config.dry_run {
+            // Write the minified code to file
+            let phase_start = Instant::now();
+            create_output(&output_path, file_path, config)
+                .and_then(|mut file| {
+                    io::Write::write_all(&mut file, code.as_bytes())?;
+                    file.commit()
+                })
+                .map_err(|e| CompilerError::IoError(format!("Failed to write output file '{}': {}", output_path.display(), e)))?;
+            timing_report.record("write", phase_start);
+        }
+        (Some(code), generation_result.diagnostics, attribution)
+    };
+    let output_path = if config.dry_run {
+        match (config.hash_filenames, &generated_code) {
+            (true, Some(code)) => {
+                let logical_name = output_path.file_name().unwrap_or_default().to_string_lossy().into_owned();
+                output_path.with_file_name(hashed_file_name(&logical_name, code.as_bytes()))
             }
+            _ => output_path,
+        }
+    } else if config.hash_filenames {
+        let phase_start = Instant::now();
+        let hashed_path = hash_output_file(&output_path)?;
+        timing_report.record("hash", phase_start);
+        hashed_path
+    } else {
+        output_path
+    };
+
+    if !polyfills.is_empty() {
+        let names: Vec<&str> = polyfills.iter().map(|polyfill| polyfill.name).collect();
+        match config.polyfill_module {
+            Some(ref module) => tracing::info!("🧩 Imported polyfills from {}: {}", module, names.join(", ")),
+            None => tracing::info!("🧩 Polyfills needed for {}: {}", output_path.display(), names.join(", ")),
+        }
+    }
+    if config.emit_name_map && !config.dry_run {
+        let map_path = name_map_path(&output_path);
+        let contents = serde_json::to_string_pretty(&name_map).unwrap_or_default();
+        write_atomically(&map_path, contents + "\n", false)
+            .map_err(|e| CompilerError::IoError(format!("Failed to write name map '{}': {}", map_path.display(), e)))?;
+        if config.verbose {
+            tracing::debug!("🗺️  Wrote name map {}", map_path.display());
+        }
+    }
+    if let (Some(report_path), Some((size, contributors))) = (&config.bundle_report, attribution)
+        && !config.dry_run
+    {
+        write_bundle_report(report_path, &output_path, file_path, &size, contributors, config.bundle_report_format)?;
+        if config.verbose {
+            tracing::debug!("📝 Wrote bundle report {}", report_path.display());
+        }
+    }
+    if let Some(ref report_path) = config.polyfill_report
+        && !config.dry_run
+    {
+        write_polyfill_report(report_path, &output_path, &polyfills)?;
+        if config.verbose {
+            tracing::debug!("📝 Wrote polyfill report {}", report_path.display());
         }
     }
-    
-    // Determine output destination
-    let output_path = config.output_file.as_ref()
-        .cloned()
-        .unwrap_or_else(|| {
-            // Default to build.js in the same directory as input file
-            if let Some(ref input_path) = config.input_file {
-                let mut output_path = input_path.clone();
-                output_path.set_file_name("build.js");
-                output_path
-            } else {
-                PathBuf::from("build.js")
-            }
-        });
-    
-    // Write the minified code to file
-    std::fs::write(&output_path, &generation_result.code)
-        .map_err(|e| CompilerError::ParseError(format!("Failed to write output file '{}': {}", output_path.display(), e)))?;
     
     if config.verbose {
-        println!("💾 Output written to: {}", output_path.display());
-        println!("🎯 Generated Code Preview:");
+        tracing::debug!("📊 Generation Results:");
+        tracing::debug!("   📏 Original size: {} bytes", diagnostics.original_size);
+        tracing::debug!("   📏 Generated size: {} bytes", diagnostics.generated_size);
+        tracing::debug!("   📉 Compression ratio: {:.1}%", diagnostics.compression_ratio * 100.0);
+        tracing::debug!("   ⏱️  Generation time: {:.2}ms", diagnostics.generation_time_ms);
+        
+        if diagnostics.warning_count > 0 {
+            tracing::debug!("   ⚠️  Generation warnings: {}", diagnostics.warning_count);
+        }
+    }
+    for warning in &diagnostics.warnings {
+        tracing::warn!("⚠️  {}", warning);
+    }
+    
+    let output_size = generated_code.as_ref().map_or(diagnostics.generated_size, String::len);
+    if config.dry_run {
+        report_dry_run(&output_path, output_size, &diagnostics, &transform_warnings, &source_code, file_path);
+    } else {
+        let kind = if config.format_only { "Formatted" } else { "Minified" };
+        tracing::info!("📄 {} JavaScript saved to: {}", kind, output_path.display());
+    }
+    if config.verbose && let Some(ref code) = generated_code {
+        tracing::debug!("🎯 Generated Code Preview:");
         // Show a preview of the generated code (first 200 characters)
-        let preview = if generation_result.code.len() > 200 {
-            format!("{}...", &generation_result.code[..200])
-        } else {
-            generation_result.code.clone()
+        let preview = match code.char_indices().nth(200) {
+            Some((end, _)) => format!("{}...", &code[..end]),
+            None => code.clone(),
         };
-        println!("{}", preview);
-    } else {
-        // In non-verbose mode, just show the output file location
-        println!("📄 Minified JavaScript saved to: {}", output_path.display());
+        tracing::debug!("{}", preview);
     }
     
-    println!("✅ Compilation completed successfully!");
+    if let Some(max) = config.max_warnings {
+        check_warning_limit(max, &transform_warnings, &diagnostics.warnings, config, &source_code, file_path)?;
+    }
+
+    if config.timing {
+        display_timing_report(&timing_report);
+    }
     
     if config.verbose {
-        println!("📊 Compilation statistics:");
-        println!("   ⏱️  Total file size reduction: {:.1}%", generation_result.diagnostics.compression_ratio * 100.0);
-        println!("   📁 Input: {} -> 📄 Output: {}", 
-            config.input_file.as_ref().map(|p| p.display().to_string()).unwrap_or_else(|| "<unknown>".to_string()),
-            output_path.display());
+        tracing::debug!("📊 Compilation statistics:");
+        tracing::debug!("   ⏱️  Total file size reduction: {:.1}%", diagnostics.compression_ratio * 100.0);
+        tracing::debug!("   📁 Input: {} -> 📄 Output: {}", file_path.display(), output_path.display());
     }
     
-    Ok(())
+    Ok(FileSummary {
+        output_path,
+        input_size: source_code.len(),
+        output_size,
+    })
+}
+
+/// Generates code directly into the output file through a buffered writer.
+///
+/// # Arguments
+///
+/// * `generator` - Configured code generator
+/// * `ast` - The transformed program to print
+/// * `source_code` - Original source, used for size diagnostics
+/// * `prelude` - Code written before the generated code (polyfill imports)
+/// * `output_path` - Destination file
+///
+/// # Returns
+///
+/// Returns the generation diagnostics, or a `CompilerError` on failure.
+///
+/// # Errors
+///
+/// Returns `CompilerError::IoError` if the file cannot be created, or
+/// `CompilerError::TransformError` if generation fails.
+/// The output is written to a temporary file that only replaces `output_path` once
+/// generation succeeds, so a failure leaves any previous output untouched.
+fn stream_to_file(
+    generator: &generator::Generator,
+    ast: &parser::ast_types::Program,
+    source_code: &str,
+    prelude: &str,
+    output_path: &PathBuf,
+    input_path: &Path,
+    config: &CompilerConfig,
+) -> CompilerResult<generator::GeneratorDiagnostics> {
+    let write_error =
+        |e: std::io::Error| CompilerError::IoError(format!("Failed to write output file '{}': {}", output_path.display(), e));
+    let mut file = create_output(output_path, input_path, config).map_err(write_error)?;
+    io::Write::write_all(&mut file, prelude.as_bytes()).map_err(write_error)?;
+    
+    let diagnostics = generator.generate_to_writer(ast, Some(source_code), &mut file)
+        .map_err(|e| CompilerError::TransformError(format!("Code generation failed: {}", e)))?;
+    file.commit().map_err(write_error)?;
+    Ok(diagnostics)
+}
+
+/// Creates the output of `input_path` with what `--backup`, `--preserve-mode` and
+/// `--preserve-times` ask for
+fn create_output(output_path: &Path, input_path: &Path, config: &CompilerConfig) -> io::Result<AtomicFile> {
+    let mut file = AtomicFile::create(output_path)?;
+    file.backup = config.backup;
+    file.mode_from = config.preserve_mode.then(|| input_path.to_path_buf());
+    file.times_from = config.preserve_times.then(|| input_path.to_path_buf());
+    Ok(file)
+}
+
+/// A file written under a temporary name next to its destination and renamed over it
+/// once complete.
+///
+/// The rename is atomic, so an interrupted or failed write never leaves a truncated
+/// file at the destination: it still holds its previous contents, or does not exist.
+/// The temporary file is removed if the `AtomicFile` is dropped without a commit.
+///
+/// A replaced file keeps its permissions and, where allowed, its owner, as it would
+/// when written over in place.
+struct AtomicFile {
+    path:           PathBuf,
+    temp_path:      PathBuf,
+    writer:         Option<io::BufWriter<std::fs::File>>,
+    /// Copy the file being replaced to `<path>.bak` first
+    backup:         bool,
+    /// File whose permissions and owner the new file takes instead of the replaced one's
+    mode_from:      Option<PathBuf>,
+    /// File whose access and modification times the new file takes
+    times_from:     Option<PathBuf>,
+}
+
+impl AtomicFile {
+    /// Creates the temporary file for `path` in the same directory, so the final
+    /// rename does not cross file systems
+    fn create(path: &Path) -> io::Result<Self> {
+        let mut temp_name = std::ffi::OsString::from(".");
+        temp_name.push(path.file_name().unwrap_or_default());
+        temp_name.push(format!(".{}.tmp", process::id()));
+        let temp_path = path.with_file_name(temp_name);
+        let file = std::fs::File::create(&temp_path)?;
+        Ok(Self {
+            path: path.to_path_buf(),
+            temp_path,
+            writer: Some(io::BufWriter::new(file)),
+            backup: false,
+            mode_from: None,
+            times_from: None,
+        })
+    }
+
+    /// Flushes the contents to disk and moves them to the destination, first copying
+    /// an existing file there to `<path>.bak` with `backup`
+    fn commit(mut self) -> io::Result<()> {
+        if let Some(writer) = self.writer.take() {
+            let file = writer.into_inner().map_err(io::IntoInnerError::into_error)?;
+            if let Some(ref source) = self.times_from {
+                let metadata = std::fs::metadata(source)?;
+                file.set_times(std::fs::FileTimes::new().set_accessed(metadata.accessed()?).set_modified(metadata.modified()?))?;
+            }
+            file.sync_all()?;
+        }
+        let mode_source = self.mode_from.as_deref().or_else(|| self.path.exists().then_some(self.path.as_path()));
+        if let Some(source) = mode_source {
+            let metadata = std::fs::metadata(source)?;
+            std::fs::set_permissions(&self.temp_path, metadata.permissions())?;
+            copy_owner(&metadata, &self.temp_path);
+        }
+        if self.backup && self.path.exists() {
+            std::fs::copy(&self.path, backup_path(&self.path))?;
+        }
+        std::fs::rename(&self.temp_path, &self.path)
+    }
+}
+
+/// Gives `path` the owner and group of `metadata`, where the process is allowed to;
+/// only root can hand a file to another user, so failing is expected and ignored
+#[cfg(unix)]
+fn copy_owner(metadata: &std::fs::Metadata, path: &Path) {
+    use std::os::unix::fs::MetadataExt;
+    drop(std::os::unix::fs::chown(path, Some(metadata.uid()), Some(metadata.gid())));
+}
+
+#[cfg(not(unix))]
+fn copy_owner(_metadata: &std::fs::Metadata, _path: &Path) {}
+
+impl io::Write for AtomicFile {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.writer.as_mut().map_or(Ok(0), |writer| writer.write(buf))
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.writer.as_mut().map_or(Ok(()), io::Write::flush)
+    }
+}
+
+impl Drop for AtomicFile {
+    fn drop(&mut self) {
+        if self.temp_path.exists() {
+            drop(std::fs::remove_file(&self.temp_path));
+        }
+    }
+}
+
+/// Writes `contents` to `path` through an [`AtomicFile`], keeping the previous contents
+/// in `<path>.bak` with `backup`.
+fn write_atomically(path: &Path, contents: impl AsRef<[u8]>, backup: bool) -> io::Result<()> {
+    let mut file = AtomicFile::create(path)?;
+    file.backup = backup;
+    io::Write::write_all(&mut file, contents.as_ref())?;
+    file.commit()
+}
+
+/// The `#!` line starting `source`, with its line break, or nothing
+fn hashbang_line(source: &str) -> String {
+    if !source.starts_with("#!") {
+        return String::new();
+    }
+    let line = source.lines().next().unwrap_or_default();
+    format!("{}\n", line)
+}
+
+/// Path of the `--backup` copy of `path`: `app.js` -> `app.js.bak`
+fn backup_path(path: &Path) -> PathBuf {
+    let mut file_name = path.file_name().unwrap_or_default().to_os_string();
+    file_name.push(".bak");
+    path.with_file_name(file_name)
+}
+
+/// Renames the output file to include a hash of its contents and records the new name
+/// in `manifest.json` in the same directory.
+///
+/// Entries already in the manifest are kept, so several outputs can share one manifest.
+///
+/// # Returns
+///
+/// Returns the hashed path, or a `CompilerError` if the file cannot be renamed or the
+/// manifest cannot be written. An existing manifest that cannot be read or is not a
+/// JSON object is an error rather than being replaced, and the file is left unrenamed.
+fn hash_output_file(output_path: &Path) -> CompilerResult<PathBuf> {
+    let io_error = |path: &Path, e: std::io::Error| {
+        CompilerError::IoError(format!("Failed to write output file '{}': {}", path.display(), e))
+    };
+    let manifest_path = output_path.with_file_name("manifest.json");
+    let manifest_error = |reason: String| {
+        CompilerError::IoError(format!("Failed to read manifest '{}': {}", manifest_path.display(), reason))
+    };
+    let mut manifest = match std::fs::read_to_string(&manifest_path) {
+        Ok(existing) => serde_json::from_str::<serde_json::Map<String, serde_json::Value>>(&existing)
+            .map_err(|e| manifest_error(e.to_string()))?,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => serde_json::Map::new(),
+        Err(e) => return Err(manifest_error(e.to_string())),
+    };
+
+    let contents = std::fs::read(output_path).map_err(|e| io_error(output_path, e))?;
+    let logical_name = output_path.file_name().unwrap_or_default().to_string_lossy().into_owned();
+    let hashed_name = hashed_file_name(&logical_name, &contents);
+    let hashed_path = output_path.with_file_name(&hashed_name);
+    std::fs::rename(output_path, &hashed_path).map_err(|e| io_error(&hashed_path, e))?;
+
+    manifest.insert(logical_name, serde_json::Value::String(hashed_name));
+    let manifest = serde_json::to_string_pretty(&manifest).unwrap_or_default();
+    write_atomically(&manifest_path, manifest + "\n", false).map_err(|e| io_error(&manifest_path, e))?;
+
+    Ok(hashed_path)
+}
+
+/// Output path of `input` without `--output`: its file stem with `--out-ext`, in
+/// `--out-dir` at the input's path relative to `base`, or next to the input.
+///
+/// `src/lib/foo.js` with base `src` and `--out-dir dist` maps to `dist/lib/foo.min.js`.
+fn mapped_output_path(input: &Path, base: &Path, config: &CompilerConfig) -> PathBuf {
+    let mut file_name = input.file_stem().unwrap_or_default().to_os_string();
+    file_name.push(&config.out_ext);
+    match config.out_dir {
+        Some(ref out_dir) => {
+            let relative = input.parent().and_then(|parent| parent.strip_prefix(base).ok()).unwrap_or(Path::new(""));
+            out_dir.join(relative).join(file_name)
+        }
+        None => input.with_file_name(file_name),
+    }
+}
+
+/// `path` with `.` components dropped and `..` components applied where possible
+fn normalize_path(path: &Path) -> PathBuf {
+    let mut normalized = PathBuf::new();
+    for component in path.components() {
+        match component {
+            std::path::Component::CurDir => {}
+            std::path::Component::ParentDir
+                if matches!(normalized.components().next_back(), Some(std::path::Component::Normal(_))) =>
+            {
+                normalized.pop();
+            }
+            component => normalized.push(component),
+        }
+    }
+    normalized
+}
+
+/// Whether `a` and `b` name the same existing file
+fn same_file(a: &Path, b: &Path) -> bool {
+    matches!((a.canonicalize(), b.canonicalize()), (Ok(a), Ok(b)) if a == b)
+}
+
+/// URL of the file `to` relative to the directory `from`, as a module specifier
+/// (`./w.min.js`, `../workers/w.min.js`)
+fn relative_url(from: &Path, to: &Path) -> String {
+    let absolute = |path: &Path| {
+        let path = if path.is_absolute() { path.to_path_buf() } else { std::env::current_dir().unwrap_or_default().join(path) };
+        normalize_path(&path)
+    };
+    let (from, to) = (absolute(from), absolute(to));
+    let common = from.components().zip(to.components()).take_while(|(a, b)| a == b).count();
+    let parents = std::iter::repeat_n("..".to_string(), from.components().count() - common);
+    let rest = to.components().skip(common).map(|component| component.as_os_str().to_string_lossy().into_owned());
+    let segments: Vec<String> = parents.chain(rest).collect();
+    let url = segments.join("/");
+    if url.starts_with("../") { url } else { format!("./{}", url) }
+}
+
+/// Deepest directory containing all of `files`, against which `--out-dir` mirrors them
+fn common_directory(files: &[PathBuf]) -> PathBuf {
+    let mut directories = files.iter().map(|file| file.parent().unwrap_or(Path::new("")));
+    let first = directories.next().unwrap_or(Path::new("")).to_path_buf();
+    directories.fold(first, |common, directory| {
+        common.components().zip(directory.components()).take_while(|(a, b)| a == b).map(|(a, _)| a).collect()
+    })
+}
+
+/// Whether `path` names one of the input files, which outputs may only replace with `--force`
+fn is_input_file(path: &Path, config: &CompilerConfig) -> bool {
+    let Ok(path) = path.canonicalize() else {
+        return false;
+    };
+    config.input_files.iter().any(|input| input.canonicalize().is_ok_and(|input| input == path))
+}
+
+/// Path of the `--emit-name-map` file of `output_path`: `app.min.js` -> `app.min.js.names.json`
+fn name_map_path(output_path: &Path) -> PathBuf {
+    let mut file_name = output_path.file_name().unwrap_or_default().to_os_string();
+    file_name.push(".names.json");
+    output_path.with_file_name(file_name)
+}
+
+/// Records the polyfills `output_path` needs in the JSON report at `report_path`.
+///
+/// The report maps each output file to the names of its polyfills; entries for other
+/// output files already in the report are kept.
+///
+/// # Errors
+///
+/// Returns `CompilerError::IoError` if the report cannot be written.
+fn write_polyfill_report(
+    report_path: &Path,
+    output_path: &Path,
+    polyfills: &[&target::polyfills::Polyfill],
+) -> CompilerResult<()> {
+    let mut report = std::fs::read_to_string(report_path)
+        .ok()
+        .and_then(|existing| serde_json::from_str::<serde_json::Map<String, serde_json::Value>>(&existing).ok())
+        .unwrap_or_default();
+    let names = polyfills.iter().map(|polyfill| serde_json::Value::from(polyfill.name)).collect();
+    report.insert(output_path.display().to_string(), serde_json::Value::Array(names));
+    let report = serde_json::to_string_pretty(&report).unwrap_or_default();
+    write_atomically(report_path, report + "\n", false)
+        .map_err(|e| CompilerError::IoError(format!("Failed to write polyfill report '{}': {}", report_path.display(), e)))
+}
+
+/// Inserts an 8-digit content hash before the extension: `app.js` -> `app.3f9ab2c1.js`.
+///
+/// The hash is 64-bit FNV-1a, so names are stable across platforms and compiler versions.
+fn hashed_file_name(file_name: &str, contents: &[u8]) -> String {
+    let hash = contents.iter().fold(0xcbf2_9ce4_8422_2325_u64, |hash, &byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(0x0000_0100_0000_01b3)
+    });
+    let digest = format!("{:016x}", hash);
+    match file_name.rsplit_once('.') {
+        Some((stem, extension)) if !stem.is_empty() => format!("{}.{}.{}", stem, &digest[..8], extension),
+        _ => format!("{}.{}", file_name, &digest[..8]),
+    }
 }
 
 /// Simulates the remaining compilation process for demonstration purposes.
@@ -548,7 +3544,7 @@ fn compile_file(file_path: &PathBuf, config: &CompilerConfig) -> CompilerResult<
 /// This is a placeholder function for Phase 5 (code generation).
 fn simulate_compilation_process(config: &CompilerConfig) -> CompilerResult<()> {
     if config.verbose {
-        println!("🔄 Phase 5: Code generation (TODO)");
+        tracing::debug!("🔄 Phase 5: Code generation (TODO)");
     }
     
     // Simulate successful compilation
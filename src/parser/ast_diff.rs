@@ -0,0 +1,303 @@
+//! # AST Diff Module
+//!
+//! Compares the top-level statements of two parsed programs, as used by
+//! `rjs-compiler diff`, to check that a change meant to be behavior-neutral left the
+//! program structure alone.
+//!
+//! Statements are compared on their JSON form with source spans and literal `raw` text
+//! left out, so formatting, comments and number spelling (`1e3` for `1000`) are not
+//! differences. Statements are aligned with a longest common subsequence; a removed and
+//! an added statement at the same place that declare the same thing are reported as one
+//! changed statement, together with the first field where they differ.
+
+use std::fmt;
+
+use serde_json::{Map, Value};
+
+use crate::parser::ast_types::Program;
+
+/// Longest JSON excerpt shown for a changed value
+const MAX_VALUE_LENGTH: usize = 60;
+
+/// A top-level statement of one of the compared programs
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StatementRef {
+    /// What the statement is, such as `FunctionDeclaration f`
+    pub summary: String,
+    /// One-based line of the statement, when it has a span
+    pub line:    Option<usize>,
+}
+
+/// A difference between the statements of two programs
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum StatementChange {
+    /// A statement only the first program has
+    Removed(StatementRef),
+    /// A statement only the second program has
+    Added(StatementRef),
+    /// A statement both programs have, with different contents
+    Changed {
+        before: StatementRef,
+        after:  StatementRef,
+        /// Field path of the first difference, such as `body.body[0].argument`
+        path:   String,
+        /// The field in each program, as compact JSON; `None` where it is missing
+        old:    Option<String>,
+        new:    Option<String>,
+    },
+}
+
+/// The structural differences between two programs, in statement order
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct AstDiff {
+    pub changes: Vec<StatementChange>,
+}
+
+impl AstDiff {
+    /// Whether the programs have the same structure
+    pub fn is_empty(&self) -> bool {
+        self.changes.is_empty()
+    }
+}
+
+impl fmt::Display for AstDiff {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.changes.is_empty() {
+            return writeln!(f, "No structural differences");
+        }
+        let (mut removed, mut added, mut changed) = (0, 0, 0);
+        for change in &self.changes {
+            match change {
+                StatementChange::Removed(statement) => {
+                    removed += 1;
+                    writeln!(f, "- {}{}", statement.summary, at_line(statement.line))?;
+                }
+                StatementChange::Added(statement) => {
+                    added += 1;
+                    writeln!(f, "+ {}{}", statement.summary, at_line(statement.line))?;
+                }
+                StatementChange::Changed { before, after, path, old, new } => {
+                    changed += 1;
+                    let lines = match (before.line, after.line) {
+                        (Some(old_line), Some(new_line)) => format!(" (line {} -> {})", old_line, new_line),
+                        _ => String::new(),
+                    };
+                    writeln!(f, "~ {}{}", before.summary, lines)?;
+                    let missing = "(none)".to_string();
+                    writeln!(
+                        f,
+                        "    {}: {} -> {}",
+                        path,
+                        old.as_ref().unwrap_or(&missing),
+                        new.as_ref().unwrap_or(&missing)
+                    )?;
+                }
+            }
+        }
+        writeln!(f, "{} changed, {} removed, {} added", changed, removed, added)
+    }
+}
+
+fn at_line(line: Option<usize>) -> String {
+    line.map(|line| format!(" (line {})", line)).unwrap_or_default()
+}
+
+/// Compares the top-level statements of `before` and `after`
+///
+/// The sources are only used to turn spans into line numbers.
+///
+/// # Errors
+///
+/// Returns an error if either AST cannot be serialized
+pub fn diff_programs(
+    before: &Program,
+    before_source: &str,
+    after: &Program,
+    after_source: &str,
+) -> serde_json::Result<AstDiff> {
+    let old = statements(before, before_source)?;
+    let new = statements(after, after_source)?;
+
+    // Longest common subsequence of equal statements, filled from the end
+    let mut common = vec![vec![0usize; new.len() + 1]; old.len() + 1];
+    for i in (0..old.len()).rev() {
+        for j in (0..new.len()).rev() {
+            common[i][j] = if old[i].1 == new[j].1 {
+                common[i + 1][j + 1] + 1
+            } else {
+                common[i + 1][j].max(common[i][j + 1])
+            };
+        }
+    }
+
+    let mut changes = Vec::new();
+    let (mut removed, mut added) = (Vec::new(), Vec::new());
+    let (mut i, mut j) = (0, 0);
+    while i < old.len() || j < new.len() {
+        if i < old.len() && j < new.len() && old[i].1 == new[j].1 {
+            flush(&mut changes, &mut removed, &mut added);
+            i += 1;
+            j += 1;
+        } else if j == new.len() || (i < old.len() && common[i + 1][j] >= common[i][j + 1]) {
+            removed.push(&old[i]);
+            i += 1;
+        } else {
+            added.push(&new[j]);
+            j += 1;
+        }
+    }
+    flush(&mut changes, &mut removed, &mut added);
+    Ok(AstDiff { changes })
+}
+
+/// A statement reference with its normalized JSON form
+type Normalized = (StatementRef, Value);
+
+/// Turns a run of removed and added statements into changes, pairing the ones in the
+/// same place that declare the same thing
+fn flush(changes: &mut Vec<StatementChange>, removed: &mut Vec<&Normalized>, added: &mut Vec<&Normalized>) {
+    let mut added_iter = std::mem::take(added).into_iter().peekable();
+    for (before, old) in std::mem::take(removed) {
+        match added_iter.next_if(|(after, _)| after.summary == before.summary) {
+            Some((after, new)) => {
+                let (path, old, new) = first_difference(old, new, String::new()).unwrap_or_default();
+                changes.push(StatementChange::Changed {
+                    before: before.clone(),
+                    after: after.clone(),
+                    path,
+                    old: old.map(excerpt),
+                    new: new.map(excerpt),
+                });
+            }
+            None => changes.push(StatementChange::Removed(before.clone())),
+        }
+    }
+    changes.extend(added_iter.map(|(after, _)| StatementChange::Added(after.clone())));
+}
+
+/// The top-level statements of `program`, described and normalized
+fn statements(program: &Program, source: &str) -> serde_json::Result<Vec<Normalized>> {
+    program
+        .body
+        .iter()
+        .map(|statement| {
+            let value = normalize(serde_json::to_value(statement)?);
+            let line = statement
+                .span()
+                .map(|span| source[..(span.start as usize).min(source.len())].matches('\n').count() + 1);
+            Ok((StatementRef { summary: summarize(&value), line }, value))
+        })
+        .collect()
+}
+
+/// `node` without spans and raw literal text
+pub(crate) fn normalize(node: Value) -> Value {
+    match node {
+        Value::Object(fields) => Value::Object(
+            fields
+                .into_iter()
+                .filter(|(key, _)| key != "span" && key != "raw")
+                .map(|(key, value)| (key, normalize(value)))
+                .collect(),
+        ),
+        Value::Array(items) => Value::Array(items.into_iter().map(normalize).collect()),
+        value => value,
+    }
+}
+
+/// The statement type with the names it declares, such as `VariableDeclaration a, b`
+fn summarize(statement: &Value) -> String {
+    let node_type = statement.get("type").and_then(Value::as_str).unwrap_or("Statement");
+    let declaration = statement.get("declaration").filter(|declaration| !declaration.is_null()).unwrap_or(statement);
+    let mut names: Vec<&str> = declaration.get("id").and_then(|id| id.get("name")).and_then(Value::as_str).into_iter().collect();
+    if let Some(Value::Array(declarators)) = declaration.get("declarations") {
+        names.extend(declarators.iter().filter_map(|declarator| declarator["id"].get("name").and_then(Value::as_str)));
+    }
+    if names.is_empty() { node_type.to_string() } else { format!("{} {}", node_type, names.join(", ")) }
+}
+
+/// Path and values of the first field where `old` and `new` differ
+fn first_difference<'a>(
+    old: &'a Value,
+    new: &'a Value,
+    path: String,
+) -> Option<(String, Option<&'a Value>, Option<&'a Value>)> {
+    if old == new {
+        return None;
+    }
+    match (old, new) {
+        (Value::Object(old_fields), Value::Object(new_fields)) if same_type(old_fields, new_fields) => {
+            let mut keys = old_fields.keys().chain(new_fields.keys().filter(|key| !old_fields.contains_key(*key)));
+            keys.find_map(|key| {
+                let child_path = if path.is_empty() { key.clone() } else { format!("{}.{}", path, key) };
+                match (old_fields.get(key), new_fields.get(key)) {
+                    (Some(old), Some(new)) => first_difference(old, new, child_path),
+                    (old, new) => Some((child_path, old, new)),
+                }
+            })
+        }
+        (Value::Array(old_items), Value::Array(new_items)) => {
+            (0..old_items.len().max(new_items.len())).find_map(|index| {
+                let child_path = format!("{}[{}]", path, index);
+                match (old_items.get(index), new_items.get(index)) {
+                    (Some(old), Some(new)) => first_difference(old, new, child_path),
+                    (old, new) => Some((child_path, old, new)),
+                }
+            })
+        }
+        _ => Some((path, Some(old), Some(new))),
+    }
+}
+
+/// Whether two nodes are of the same kind, so their fields can be compared one by one
+fn same_type(old: &Map<String, Value>, new: &Map<String, Value>) -> bool {
+    old.get("type") == new.get("type")
+}
+
+/// Compact JSON of `value`, shortened to [`MAX_VALUE_LENGTH`] characters
+fn excerpt(value: &Value) -> String {
+    let text = value.to_string();
+    match text.char_indices().nth(MAX_VALUE_LENGTH) {
+        Some((end, _)) => format!("{}...", &text[..end]),
+        None => text,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::{parse_js, ParserConfig};
+
+    fn diff(before: &str, after: &str) -> AstDiff {
+        let parse = |source| parse_js(source, "test.js", &ParserConfig::default()).ast.unwrap();
+        diff_programs(&parse(before), before, &parse(after), after).unwrap()
+    }
+
+    #[test]
+    fn test_formatting_is_not_a_difference() {
+        let result = diff("const a = 1000;\nfunction f(x) { return x; }\n", "// comment\nconst a=1e3;function f(x){return x}");
+        assert!(result.is_empty(), "{}", result);
+        assert_eq!(result.to_string(), "No structural differences\n");
+    }
+
+    #[test]
+    fn test_reports_added_removed_and_changed_statements() {
+        let result = diff(
+            "const a = 1;\nfunction f(x) { return x + 1; }\nlet unused;\n",
+            "const a = 1;\nfunction f(x) { return x + 2; }\nf(a);\n",
+        );
+        assert_eq!(result.changes.len(), 3, "{}", result);
+        assert!(matches!(
+            &result.changes[0],
+            StatementChange::Changed { before, path, .. }
+                if before.summary == "FunctionDeclaration f" && path == "body.body[0].argument.right.value"
+        ));
+        assert_eq!(
+            result.to_string(),
+            "~ FunctionDeclaration f (line 2 -> 2)\n    body.body[0].argument.right.value: 1.0 -> 2.0\n\
+             - VariableDeclaration unused (line 3)\n\
+             + ExpressionStatement (line 3)\n\
+             1 changed, 1 removed, 1 added\n"
+        );
+    }
+}
@@ -0,0 +1,208 @@
+//! # AST Dump Module
+//!
+//! Renders a parsed [`Program`] for inspection, as used by `rjs-compiler ast`.
+//!
+//! ## Formats
+//!
+//! - **JSON**: The serde representation of the AST, pretty-printed
+//! - **Pretty**: An indented tree with one node per line and scalar fields inline
+//! - **DOT**: A Graphviz digraph, rendered with e.g. `dot -Tsvg`
+//!
+//! The pretty and DOT renderers walk the JSON form of the tree, so every AST node type
+//! is covered without a separate visitor. Nodes are labelled with their `type` tag;
+//! untagged nodes (such as variable declarators) are labelled with the field holding them.
+//! Source spans are shown inline as `@start..end` byte offsets.
+
+use std::fmt::Write;
+use std::str::FromStr;
+
+use serde_json::{Map, Value};
+
+use crate::parser::ast_types::Program;
+
+/// Output format for [`dump_ast`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum AstFormat {
+    /// Serde JSON representation
+    Json,
+    /// Indented tree
+    #[default]
+    Pretty,
+    /// Graphviz DOT digraph
+    Dot,
+}
+
+impl FromStr for AstFormat {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "json" => Ok(Self::Json),
+            "pretty" => Ok(Self::Pretty),
+            "dot" => Ok(Self::Dot),
+            _ => Err(format!("unknown AST format '{}' (expected json, pretty or dot)", value)),
+        }
+    }
+}
+
+/// Renders a program in the given format
+///
+/// # Errors
+///
+/// Returns an error if the AST cannot be serialized
+pub fn dump_ast(program: &Program, format: AstFormat) -> serde_json::Result<String> {
+    match format {
+        AstFormat::Json => serde_json::to_string_pretty(program),
+        AstFormat::Pretty => Ok(to_pretty(&serde_json::to_value(program)?)),
+        AstFormat::Dot => Ok(to_dot(&serde_json::to_value(program)?)),
+    }
+}
+
+/// A node's label and its child nodes, each with the field path it was found under
+struct NodeParts<'a> {
+    label: String,
+    children: Vec<(String, &'a Map<String, Value>)>,
+}
+
+/// Splits an AST object into a label (type plus scalar fields) and child nodes
+fn node_parts<'a>(node: &'a Map<String, Value>, fallback_name: &str) -> NodeParts<'a> {
+    let mut label = node
+        .get("type")
+        .and_then(Value::as_str)
+        .unwrap_or(fallback_name)
+        .to_string();
+    let mut children = Vec::new();
+
+    for (key, value) in node {
+        match value {
+            Value::Object(_) if key == "span" => {}
+            Value::Object(child) => children.push((key.clone(), child)),
+            Value::Array(items) => {
+                let mut scalars = Vec::new();
+                for (index, item) in items.iter().enumerate() {
+                    match item {
+                        Value::Object(child) => children.push((format!("{}[{}]", key, index), child)),
+                        Value::Null => {}
+                        scalar => scalars.push(scalar.to_string()),
+                    }
+                }
+                if !scalars.is_empty() {
+                    let _ = write!(label, " {}=[{}]", key, scalars.join(", "));
+                }
+            }
+            Value::Null => {}
+            _ if key == "type" => {}
+            scalar => {
+                let _ = write!(label, " {}={}", key, scalar);
+            }
+        }
+    }
+    if let Some(Value::Object(span)) = node.get("span") {
+        let _ = write!(label, " @{}..{}", span["start"], span["end"]);
+    }
+
+    NodeParts { label, children }
+}
+
+/// Renders the tree with box-drawing guides, one node per line
+fn to_pretty(root: &Value) -> String {
+    fn render(output: &mut String, node: &Map<String, Value>, name: &str, prefix: &str) {
+        let parts = node_parts(node, name);
+        let count = parts.children.len();
+
+        for (index, (field, child)) in parts.children.into_iter().enumerate() {
+            let last = index + 1 == count;
+            let child_parts = node_parts(child, &field);
+            let label = if child_parts.label.starts_with(field.as_str()) {
+                child_parts.label
+            } else {
+                format!("{}: {}", field, child_parts.label)
+            };
+
+            let _ = writeln!(output, "{}{}{}", prefix, if last { "└── " } else { "├── " }, label);
+            let child_prefix = format!("{}{}", prefix, if last { "    " } else { "│   " });
+            render(output, child, &field, &child_prefix);
+        }
+    }
+
+    let mut output = String::new();
+    if let Value::Object(node) = root {
+        let _ = writeln!(output, "{}", node_parts(node, "Program").label);
+        render(&mut output, node, "Program", "");
+    }
+    output
+}
+
+/// Renders the tree as a Graphviz digraph with field names on the edges
+fn to_dot(root: &Value) -> String {
+    fn render(output: &mut String, node: &Map<String, Value>, name: &str, next_id: &mut usize) -> usize {
+        let id = *next_id;
+        *next_id += 1;
+
+        let parts = node_parts(node, name);
+        let _ = writeln!(output, "  n{} [label=\"{}\"];", id, escape_dot(&parts.label));
+
+        for (field, child) in parts.children {
+            let child_id = render(output, child, &field, next_id);
+            let _ = writeln!(output, "  n{} -> n{} [label=\"{}\"];", id, child_id, escape_dot(&field));
+        }
+        id
+    }
+
+    let mut output = String::from("digraph AST {\n  node [shape=box, fontname=\"monospace\"];\n");
+    if let Value::Object(node) = root {
+        render(&mut output, node, "Program", &mut 0);
+    }
+    output.push_str("}\n");
+    output
+}
+
+/// Escapes a string for use inside a quoted DOT label
+fn escape_dot(text: &str) -> String {
+    text.replace('\\', "\\\\").replace('"', "\\\"").replace('\n', "\\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::{parse_js, ParserConfig};
+
+    fn program(source: &str) -> Program {
+        parse_js(source, "test.js", &ParserConfig::default()).ast.unwrap()
+    }
+
+    #[test]
+    fn test_pretty_tree() {
+        let output = dump_ast(&program("let a = b + 1;"), AstFormat::Pretty).unwrap();
+        let expected = "\
+Program source_type=\"Module\"
+└── body[0]: VariableDeclaration kind=\"Let\" @0..14
+    └── declarations[0] @4..13
+        ├── id: Identifier name=\"a\" @4..5
+        └── init: BinaryExpression operator=\"Add\"
+            ├── left: Identifier name=\"b\" @8..9
+            └── right: Literal kind=\"Number\" raw=\"1\" value=1.0 @12..13
+";
+        assert_eq!(output, expected);
+    }
+
+    #[test]
+    fn test_dot_graph() {
+        let output = dump_ast(&program("f(\"x\");"), AstFormat::Dot).unwrap();
+
+        assert!(output.starts_with("digraph AST {\n"));
+        assert!(output.ends_with("}\n"));
+        assert!(output.contains("n0 [label=\"Program source_type=\\\"Module\\\"\"];"));
+        assert!(output.contains("n0 -> n1 [label=\"body[0]\"];"));
+        assert!(output.contains("[label=\"Literal kind=\\\"String\\\" value=\\\"x\\\" @2..5\"];"));
+    }
+
+    #[test]
+    fn test_json_and_format_names() {
+        let output = dump_ast(&program("x;"), AstFormat::Json).unwrap();
+        assert!(output.contains("\"ExpressionStatement\""));
+
+        assert_eq!("dot".parse::<AstFormat>(), Ok(AstFormat::Dot));
+        assert!("yaml".parse::<AstFormat>().is_err());
+    }
+}
@@ -3,10 +3,21 @@
 //! This module defines the Abstract Syntax Tree (AST) node types used by the parser.
 //! It provides a simplified, serializable representation of the OXC AST that can be
 //! easily processed by the minification engine.
+//!
+//! Statements and the nodes that name or spell out source text (identifiers, literals,
+//! template elements, functions, declarators, blocks and class bodies) carry the
+//! [`SourceSpan`] of the code they were parsed from, for source maps and diagnostics.
+//! Operator expressions are located through their operands. Nodes built by a pass have
+//! no span and are left out of the JSON form.
+
+use std::cell::RefCell;
 
 use oxc_ast::ast as oxc;
+use oxc_span::GetSpan;
 use serde::{Deserialize, Serialize};
 
+use crate::parser::SourceSpan;
+
 /// Root program node containing all statements
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Program {
@@ -31,6 +42,8 @@ pub enum Statement {
     VariableDeclaration {
         declarations: Vec<VariableDeclarator>,
         kind: VariableDeclarationKind,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        span: Option<SourceSpan>,
     },
     /// Function declaration
     FunctionDeclaration {
@@ -39,35 +52,52 @@ pub enum Statement {
         body: BlockStatement,
         is_async: bool,
         is_generator: bool,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        span: Option<SourceSpan>,
     },
     /// Class declaration
     ClassDeclaration {
         id: Option<Identifier>,
         super_class: Option<Box<Expression>>,
         body: ClassBody,
+        /// Decorator expressions, without the `@`, in source order
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        decorators: Vec<Expression>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        span: Option<SourceSpan>,
     },
     /// Expression statement
     ExpressionStatement {
         expression: Expression,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        span: Option<SourceSpan>,
     },
     /// Block statement
     BlockStatement {
         body: Vec<Statement>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        span: Option<SourceSpan>,
     },
     /// Return statement
     ReturnStatement {
         argument: Option<Expression>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        span: Option<SourceSpan>,
     },
     /// If statement
     IfStatement {
         test: Expression,
         consequent: Box<Statement>,
         alternate: Option<Box<Statement>>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        span: Option<SourceSpan>,
     },
     /// While loop
     WhileStatement {
         test: Expression,
         body: Box<Statement>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        span: Option<SourceSpan>,
     },
     /// For loop
     ForStatement {
@@ -75,18 +105,58 @@ pub enum Statement {
         test: Option<Expression>,
         update: Option<Expression>,
         body: Box<Statement>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        span: Option<SourceSpan>,
+    },
+    /// `for (left of right)` loop, or `for await (left of right)` when `is_await`
+    ForOfStatement {
+        /// Declaration of the loop binding (without initializer) or assignment target
+        left: ForInit,
+        right: Expression,
+        body: Box<Statement>,
+        is_await: bool,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        span: Option<SourceSpan>,
     },
     /// Import declaration (ES6 modules)
     ImportDeclaration {
         specifiers: Vec<ImportSpecifier>,
         source: StringLiteral,
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        attributes: Vec<ImportAttribute>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        span: Option<SourceSpan>,
     },
     /// Export declaration (ES6 modules)
     ExportNamedDeclaration {
         declaration: Option<Box<Statement>>,
         specifiers: Vec<ExportSpecifier>,
         source: Option<StringLiteral>,
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        attributes: Vec<ImportAttribute>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        span: Option<SourceSpan>,
+    },
+    /// `export default` of a function or class declaration, which may be anonymous, or of
+    /// an expression, held as an expression statement
+    ExportDefaultDeclaration {
+        declaration: Box<Statement>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        span: Option<SourceSpan>,
     },
+    /// `export * from "source"`, or `export * as exported from "source"`
+    ExportAllDeclaration {
+        exported: Option<Identifier>,
+        source: StringLiteral,
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        attributes: Vec<ImportAttribute>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        span: Option<SourceSpan>,
+    },
+    /// Empty statement (`;`)
+    EmptyStatement,
+    /// `debugger` statement
+    DebuggerStatement,
 }
 
 /// Variable declaration kind
@@ -95,6 +165,10 @@ pub enum VariableDeclarationKind {
     Var,
     Let,
     Const,
+    /// `using`, which disposes of its value when the block is left
+    Using,
+    /// `await using`, which awaits the disposal
+    AwaitUsing,
 }
 
 /// Variable declarator
@@ -102,18 +176,24 @@ pub enum VariableDeclarationKind {
 pub struct VariableDeclarator {
     pub id: Pattern,
     pub init: Option<Expression>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub span: Option<SourceSpan>,
 }
 
 /// Block statement
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct BlockStatement {
     pub body: Vec<Statement>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub span: Option<SourceSpan>,
 }
 
 /// Class body
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ClassBody {
     pub body: Vec<ClassElement>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub span: Option<SourceSpan>,
 }
 
 /// Class element (method, property, etc.)
@@ -126,6 +206,8 @@ pub enum ClassElement {
         value: Option<Expression>,
         is_static: bool,
         is_private: bool,
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        decorators: Vec<Expression>,
     },
     /// Method definition
     MethodDefinition {
@@ -134,6 +216,8 @@ pub enum ClassElement {
         kind: MethodKind,
         is_static: bool,
         is_private: bool,
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        decorators: Vec<Expression>,
     },
 }
 
@@ -147,8 +231,10 @@ pub enum MethodKind {
 }
 
 /// For loop initialization
+///
+/// Tagged with `init_type` so the wrapped expression keeps its own `type` tag.
 #[derive(Debug, Clone, Serialize, Deserialize)]
-#[serde(tag = "type")]
+#[serde(tag = "init_type")]
 pub enum ForInit {
     VariableDeclaration {
         declarations: Vec<VariableDeclarator>,
@@ -173,6 +259,14 @@ pub enum ImportSpecifier {
     },
 }
 
+/// Entry of the `with { … }` clause of an import or re-export, e.g. `type: "json"`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ImportAttribute {
+    /// The key, quoted or not in the source
+    pub key: StringLiteral,
+    pub value: StringLiteral,
+}
+
 /// Export specifier
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(tag = "type")]
@@ -215,16 +309,20 @@ pub enum Expression {
         argument: Box<Expression>,
         prefix: bool,
     },
-    /// Function call
+    /// Function call; `optional` for `f?.()` inside a [`Expression::ChainExpression`]
     CallExpression {
         callee: Box<Expression>,
         arguments: Vec<Expression>,
+        #[serde(default)]
+        optional: bool,
     },
-    /// Member access (a.b, a[b])
+    /// Member access (a.b, a[b]); `optional` for `a?.b` inside a [`Expression::ChainExpression`]
     MemberExpression {
         object: Box<Expression>,
         property: Box<Expression>,
         computed: bool,
+        #[serde(default)]
+        optional: bool,
     },
     /// Function expression
     FunctionExpression(FunctionExpression),
@@ -247,6 +345,17 @@ pub enum Expression {
         quasis: Vec<TemplateElement>,
         expressions: Vec<Expression>,
     },
+    /// Tagged template (``tag`a${b}c` ``)
+    ///
+    /// The tag receives the raw text of each quasi as well as the cooked one, and the two
+    /// differ whenever the text contains escapes, so the quasis must be printed exactly
+    /// as written. They are kept here rather than in a nested [`Expression::TemplateLiteral`]
+    /// so that passes rewriting template literals never see them.
+    TaggedTemplateExpression {
+        tag: Box<Expression>,
+        quasis: Vec<TemplateElement>,
+        expressions: Vec<Expression>,
+    },
     /// Conditional expression (a ? b : c)
     ConditionalExpression {
         test: Box<Expression>,
@@ -255,6 +364,41 @@ pub enum Expression {
     },
     /// This expression
     ThisExpression,
+    /// Await expression (`await a`), inside async functions or at module top level
+    AwaitExpression {
+        argument: Box<Expression>,
+    },
+    /// Optional chain (`a?.b.c`, `f?.()`): the member accesses and calls an optional
+    /// link short-circuits to `undefined` when its object is null or undefined
+    ChainExpression {
+        expression: Box<Expression>,
+    },
+    /// Yield expression (`yield a`, or `yield* a` when `delegate`) inside generators
+    YieldExpression {
+        argument: Option<Box<Expression>>,
+        delegate: bool,
+    },
+    /// Spread element (`...a`); only valid as a call argument or array literal element
+    SpreadElement {
+        argument: Box<Expression>,
+    },
+    /// Dynamic import (`import(source)`, or `import(source, options)` with import
+    /// attributes), which loads a module and resolves to its namespace
+    ImportExpression {
+        source: Box<Expression>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        options: Option<Box<Expression>>,
+    },
+    /// Constructor call (`new Callee(arguments)`)
+    NewExpression {
+        callee: Box<Expression>,
+        arguments: Vec<Expression>,
+    },
+    /// `import.meta` or `new.target`
+    MetaProperty {
+        meta: String,
+        property: String,
+    },
 }
 
 /// Function expression
@@ -265,11 +409,15 @@ pub struct FunctionExpression {
     pub body: BlockStatement,
     pub is_async: bool,
     pub is_generator: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub span: Option<SourceSpan>,
 }
 
 /// Arrow function body
+///
+/// Tagged with `body_type` so the wrapped expression keeps its own `type` tag.
 #[derive(Debug, Clone, Serialize, Deserialize)]
-#[serde(tag = "type")]
+#[serde(tag = "body_type")]
 pub enum ArrowFunctionBody {
     BlockStatement(BlockStatement),
     Expression(Box<Expression>),
@@ -313,13 +461,35 @@ pub enum PropertyKey {
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PrivateName {
     pub name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub span: Option<SourceSpan>,
 }
 
 /// Template element
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TemplateElement {
+    /// Cooked text, with escapes decoded; empty for a tagged template quasi with an
+    /// invalid escape (such as `\unicode`), which has no cooked value
     pub value: String,
+    /// Source text between the delimiters, printed verbatim; `None` for elements built
+    /// by a pass, which are printed from `value`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub raw: Option<String>,
     pub tail: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub span: Option<SourceSpan>,
+}
+
+impl TemplateElement {
+    /// Convert from an OXC template element
+    pub fn from_oxc(oxc_elem: &oxc::TemplateElement<'_>) -> Self {
+        Self {
+            value: oxc_elem.value.cooked.as_ref().map(|cooked| cooked.to_string()).unwrap_or_default(),
+            raw: Some(oxc_elem.value.raw.to_string()),
+            tail: oxc_elem.tail,
+            span: Some(oxc_elem.span.into()),
+        }
+    }
 }
 
 /// Patterns (for destructuring, parameters, etc.)
@@ -361,11 +531,16 @@ pub enum ObjectPatternProperty {
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Identifier {
     pub name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub span: Option<SourceSpan>,
 }
 
 /// Literal values
+///
+/// Tagged with `kind` so that `Expression::Literal` serializes as
+/// `{"type": "Literal", "kind": "Number", ...}` and round-trips through JSON.
 #[derive(Debug, Clone, Serialize, Deserialize)]
-#[serde(tag = "type")]
+#[serde(tag = "kind")]
 pub enum Literal {
     /// String literal
     String(StringLiteral),
@@ -383,18 +558,133 @@ pub enum Literal {
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct StringLiteral {
     pub value: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub span: Option<SourceSpan>,
+    /// Whether the string has lone surrogates, which a Rust string can't hold; `value`
+    /// then spells each one as a `\uXXXX` escape and each backslash as `\\`
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub lone_surrogates: bool,
+}
+
+impl StringLiteral {
+    /// A string literal for `value`, which has no lone surrogates
+    pub fn new(value: String, span: Option<SourceSpan>) -> Self {
+        Self { value, span, lone_surrogates: false }
+    }
+
+    /// Convert from an OXC string literal
+    ///
+    /// OXC writes a lone surrogate as the text `\uXXXX`, the same as a backslash
+    /// followed by `uXXXX`, so such values are decoded again from the source when the
+    /// running conversion has it.
+    fn from_oxc(lit: &oxc::StringLiteral<'_>) -> Self {
+        let span = Some(lit.span.into());
+        if lit.value.contains("\\u")
+            && let Some(units) = with_source(|source| {
+                source.get(lit.span.start as usize + 1..(lit.span.end as usize).saturating_sub(1)).map(decode_string_escapes)
+            })
+            .flatten()
+        {
+            return Self::from_utf16(&units, span);
+        }
+        Self::new(lit.value.to_string(), span)
+    }
+
+    /// A string literal for the UTF-16 code units `units`
+    fn from_utf16(units: &[u16], span: Option<SourceSpan>) -> Self {
+        if let Ok(value) = String::from_utf16(units) {
+            return Self::new(value, span);
+        }
+        let mut value = String::new();
+        for unit in char::decode_utf16(units.iter().copied()) {
+            match unit {
+                Ok('\\') => value.push_str("\\\\"),
+                Ok(c) => value.push(c),
+                Err(lone) => value.push_str(&format!("\\u{:04x}", lone.unpaired_surrogate())),
+            }
+        }
+        Self { value, span, lone_surrogates: true }
+    }
+}
+
+/// The UTF-16 code units of the string literal body `raw` (without its quotes)
+fn decode_string_escapes(raw: &str) -> Vec<u16> {
+    let mut units = Vec::with_capacity(raw.len());
+    let mut chars = raw.chars().peekable();
+    let push = |units: &mut Vec<u16>, code_point: u32| match char::from_u32(code_point) {
+        Some(c) => units.extend(c.encode_utf16(&mut [0; 2]).iter()),
+        // A surrogate code point written as `\u{D800}`
+        None => units.push(code_point as u16),
+    };
+    let hex = |chars: &mut std::iter::Peekable<std::str::Chars<'_>>, digits: usize| {
+        (0..digits).try_fold(0, |value, _| Some(value * 16 + chars.next()?.to_digit(16)?))
+    };
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            push(&mut units, c as u32);
+            continue;
+        }
+        let Some(escape) = chars.next() else { break };
+        match escape {
+            'n' => units.push(0x0a),
+            'r' => units.push(0x0d),
+            't' => units.push(0x09),
+            'b' => units.push(0x08),
+            'f' => units.push(0x0c),
+            'v' => units.push(0x0b),
+            'x' => push(&mut units, hex(&mut chars, 2).unwrap_or_default()),
+            'u' if chars.peek() == Some(&'{') => {
+                chars.next();
+                let mut code_point = 0;
+                while let Some(digit) = chars.next().and_then(|c| c.to_digit(16)) {
+                    code_point = code_point * 16 + digit;
+                }
+                push(&mut units, code_point);
+            }
+            'u' => units.push(hex(&mut chars, 4).unwrap_or_default() as u16),
+            // Line continuations
+            '\r' => {
+                chars.next_if_eq(&'\n');
+            }
+            '\n' | '\u{2028}' | '\u{2029}' => {}
+            // Legacy octal escapes, up to `\377`
+            '0'..='7' => {
+                let mut value = escape.to_digit(8).unwrap_or_default();
+                let max_digits = if value < 4 { 2 } else { 1 };
+                for _ in 0..max_digits {
+                    match chars.peek().and_then(|c| c.to_digit(8)) {
+                        Some(digit) => {
+                            value = value * 8 + digit;
+                            chars.next();
+                        }
+                        None => break,
+                    }
+                }
+                units.push(value as u16);
+            }
+            other => push(&mut units, other as u32),
+        }
+    }
+    units
 }
 
 /// Number literal
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct NumberLiteral {
     pub value: f64,
+    /// Source text (`0x1F`, `1_000_000`); `None` for numbers computed by a pass
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub raw: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub span: Option<SourceSpan>,
 }
 
 /// Boolean literal
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct BooleanLiteral {
     pub value: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub span: Option<SourceSpan>,
 }
 
 /// Regular expression literal
@@ -402,6 +692,8 @@ pub struct BooleanLiteral {
 pub struct RegExpLiteral {
     pub pattern: String,
     pub flags: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub span: Option<SourceSpan>,
 }
 
 /// Unary operators
@@ -448,6 +740,7 @@ pub enum BinaryOperator {
     BitwiseXor,
     LogicalAnd,
     LogicalOr,
+    NullishCoalescing,
     In,
     Instanceof,
 }
@@ -480,13 +773,64 @@ pub enum UpdateOperator {
     Decrement,
 }
 
+/// State of a running [`Program::from_oxc_in`]
+#[derive(Default)]
+struct Conversion {
+    /// Source the program was parsed from, kept only if a string in it may need decoding
+    source:  Option<String>,
+    /// Spans of the code dropped so far
+    dropped: Vec<SourceSpan>,
+}
+
+thread_local! {
+    static CONVERSION: RefCell<Option<Conversion>> = const { RefCell::new(None) };
+}
+
+/// Records that the construct at `span` has no owned representation and was dropped
+fn dropped(span: oxc_span::Span) {
+    CONVERSION.with_borrow_mut(|conversion| {
+        if let Some(conversion) = conversion {
+            conversion.dropped.push(span.into());
+        }
+    });
+}
+
+/// Calls `f` with the source of the running conversion, if it has one
+fn with_source<T>(f: impl FnOnce(&str) -> T) -> Option<T> {
+    CONVERSION.with_borrow(|conversion| conversion.as_ref()?.source.as_deref().map(f))
+}
+
 impl Program {
     /// Convert from OXC Program to our Program type
+    ///
+    /// Without the source, lone surrogates in strings are read as the text OXC gives
+    /// them; [`from_oxc_in`](Self::from_oxc_in) keeps them.
     pub fn from_oxc(oxc_program: &oxc::Program<'_>) -> Self {
-        let body = oxc_program
-            .body
-            .iter()
-            .filter_map(|stmt| Statement::from_oxc(stmt))
+        Self::convert_with(oxc_program, Conversion::default()).0
+    }
+
+    /// Convert from the OXC Program parsed from `source`, also returning the spans of the
+    /// statements, expressions, class members and keys that have no owned representation
+    ///
+    /// The converted program lacks that code, so passes that need to see every use of a
+    /// binding must not trust it around these spans. Type annotations and other
+    /// TypeScript-only syntax are removed on purpose and not reported.
+    pub fn from_oxc_in(oxc_program: &oxc::Program<'_>, source: &str) -> (Self, Vec<SourceSpan>) {
+        // Only `\u` escapes can produce a lone surrogate
+        let source = source.contains("\\u").then(|| source.to_string());
+        Self::convert_with(oxc_program, Conversion { source, dropped: Vec::new() })
+    }
+
+    fn convert_with(oxc_program: &oxc::Program<'_>, conversion: Conversion) -> (Self, Vec<SourceSpan>) {
+        let outer = CONVERSION.replace(Some(conversion));
+        let program = Self::convert(oxc_program);
+        let conversion = CONVERSION.replace(outer).unwrap_or_default();
+        (program, conversion.dropped)
+    }
+
+    fn convert(oxc_program: &oxc::Program<'_>) -> Self {
+        let body = directives_from_oxc(&oxc_program.directives)
+            .chain(oxc_program.body.iter().filter_map(|stmt| Statement::from_oxc(stmt)))
             .collect();
 
         let source_type = if oxc_program.source_type.is_module() {
@@ -500,84 +844,138 @@ impl Program {
 }
 
 impl Statement {
+    /// Source span of the statement, if it was parsed rather than built by a pass
+    pub fn span(&self) -> Option<&SourceSpan> {
+        match self {
+            Statement::VariableDeclaration { span, .. }
+            | Statement::FunctionDeclaration { span, .. }
+            | Statement::ClassDeclaration { span, .. }
+            | Statement::ExpressionStatement { span, .. }
+            | Statement::BlockStatement { span, .. }
+            | Statement::ReturnStatement { span, .. }
+            | Statement::IfStatement { span, .. }
+            | Statement::WhileStatement { span, .. }
+            | Statement::ForStatement { span, .. }
+            | Statement::ForOfStatement { span, .. }
+            | Statement::ImportDeclaration { span, .. }
+            | Statement::ExportNamedDeclaration { span, .. }
+            | Statement::ExportDefaultDeclaration { span, .. }
+            | Statement::ExportAllDeclaration { span, .. } => span.as_ref(),
+            Statement::EmptyStatement | Statement::DebuggerStatement => None,
+        }
+    }
+
+    /// The value of a string literal statement, which is a directive (`"use strict"`)
+    /// when it starts a program or function body
+    pub fn directive(&self) -> Option<&str> {
+        match self {
+            Statement::ExpressionStatement { expression: Expression::Literal(Literal::String(string)), .. } => {
+                Some(&string.value)
+            }
+            _ => None,
+        }
+    }
+
+    /// Whether the program or function body `body` starts with a `"use strict"` directive
+    pub fn is_strict_body(body: &[Statement]) -> bool {
+        body.iter().map_while(Statement::directive).any(|directive| directive == "use strict")
+    }
+
     /// Convert from OXC Statement to our Statement type
     pub fn from_oxc(oxc_stmt: &oxc::Statement<'_>) -> Option<Self> {
+        let span = Some(SourceSpan::from(oxc_stmt.span()));
         match oxc_stmt {
-            oxc::Statement::VariableDeclaration(decl) => {
-                let kind = match decl.kind {
-                    oxc::VariableDeclarationKind::Var => VariableDeclarationKind::Var,
-                    oxc::VariableDeclarationKind::Let => VariableDeclarationKind::Let,
-                    oxc::VariableDeclarationKind::Const => VariableDeclarationKind::Const,
-                    _ => return None,
+            oxc::Statement::VariableDeclaration(_)
+            | oxc::Statement::FunctionDeclaration(_)
+            | oxc::Statement::ClassDeclaration(_) => Statement::from_oxc_declaration(oxc_stmt.as_declaration()?),
+            // Type-only imports and exports (`import type`, `export type { A }`) have no
+            // runtime effect, so they go with the types
+            oxc::Statement::ExportNamedDeclaration(export) if export.export_kind.is_type() => None,
+            oxc::Statement::ExportAllDeclaration(export) if export.export_kind.is_type() => None,
+            oxc::Statement::ImportDeclaration(import) if import.import_kind.is_type() => None,
+            oxc::Statement::ExportNamedDeclaration(export) => {
+                let declaration = match &export.declaration {
+                    Some(declaration) => Some(Box::new(Statement::from_oxc_declaration(declaration)?)),
+                    None => None,
                 };
-
-                let declarations = decl
-                    .declarations
-                    .iter()
-                    .filter_map(|decl| VariableDeclarator::from_oxc(decl))
+                let specifiers = export.specifiers.iter()
+                    .filter(|specifier| !specifier.export_kind.is_type())
+                    .map(|specifier| ExportSpecifier::ExportSpecifier {
+                        local: Identifier { name: specifier.local.name().to_string(), span: Some(specifier.local.span().into()) },
+                        exported: Identifier { name: specifier.exported.name().to_string(), span: Some(specifier.exported.span().into()) },
+                    })
                     .collect();
+                let source = export.source.as_ref().map(|source| StringLiteral::new(source.value.to_string(), Some(source.span().into())));
+                let attributes = ImportAttribute::from_oxc(export.with_clause.as_deref());
 
-                Some(Statement::VariableDeclaration { declarations, kind })
+                Some(Statement::ExportNamedDeclaration { declaration, specifiers, source, attributes, span })
             }
-            oxc::Statement::FunctionDeclaration(func) => {
-                let id = func.id.as_ref().map(|id| Identifier {
-                    name: id.name.to_string(),
-                });
-                
-                let params = func.params.items.iter()
-                    .filter_map(|param| Pattern::from_oxc(&param.pattern))
-                    .collect();
-                
-                let body = BlockStatement {
-                    body: func.body.as_ref()?
-                        .statements.iter()
-                        .filter_map(|stmt| Statement::from_oxc(stmt))
-                        .collect(),
+            oxc::Statement::ExportDefaultDeclaration(export) => {
+                let declaration = match &export.declaration {
+                    oxc::ExportDefaultDeclarationKind::FunctionDeclaration(func) => Statement::from_oxc_function(func)?,
+                    oxc::ExportDefaultDeclarationKind::ClassDeclaration(class) => Statement::from_oxc_class(class),
+                    oxc::ExportDefaultDeclarationKind::TSInterfaceDeclaration(_) => return None,
+                    kind => Statement::ExpressionStatement {
+                        expression: Expression::from_oxc(kind.as_expression()?)?,
+                        span: Some(kind.span().into()),
+                    },
                 };
-                
-                Some(Statement::FunctionDeclaration {
-                    id,
-                    params,
-                    body,
-                    is_async: func.r#async,
-                    is_generator: func.generator,
-                })
+                Some(Statement::ExportDefaultDeclaration { declaration: Box::new(declaration), span })
             }
-            oxc::Statement::ClassDeclaration(class) => {
-                let id = class.id.as_ref().map(|id| Identifier {
-                    name: id.name.to_string(),
-                });
-                
-                let super_class = class.super_class.as_ref()
-                    .and_then(|expr| Expression::from_oxc(expr))
-                    .map(Box::new);
-                
-                let body = ClassBody {
-                    body: class.body.body.iter()
-                        .filter_map(|elem| ClassElement::from_oxc(elem))
-                        .collect(),
-                };
-                
-                Some(Statement::ClassDeclaration {
-                    id,
-                    super_class,
-                    body,
+            oxc::Statement::ExportAllDeclaration(export) => Some(Statement::ExportAllDeclaration {
+                exported: export.exported.as_ref().map(|exported| Identifier { name: exported.name().to_string(), span: Some(exported.span().into()) }),
+                source: StringLiteral::new(export.source.value.to_string(), Some(export.source.span().into())),
+                attributes: ImportAttribute::from_oxc(export.with_clause.as_deref()),
+                span,
+            }),
+            oxc::Statement::ImportDeclaration(import) => {
+                let specifiers: Vec<_> = import.specifiers.iter().flatten()
+                    .filter(|specifier| {
+                        !matches!(specifier, oxc::ImportDeclarationSpecifier::ImportSpecifier(specifier) if specifier.import_kind.is_type())
+                    })
+                    .map(|specifier| match specifier {
+                        oxc::ImportDeclarationSpecifier::ImportSpecifier(specifier) => ImportSpecifier::ImportSpecifier {
+                            imported: Identifier { name: specifier.imported.name().to_string(), span: Some(specifier.imported.span().into()) },
+                            local: Identifier { name: specifier.local.name.to_string(), span: Some(specifier.local.span().into()) },
+                        },
+                        oxc::ImportDeclarationSpecifier::ImportDefaultSpecifier(specifier) => {
+                            ImportSpecifier::ImportDefaultSpecifier {
+                                local: Identifier { name: specifier.local.name.to_string(), span: Some(specifier.local.span().into()) },
+                            }
+                        }
+                        oxc::ImportDeclarationSpecifier::ImportNamespaceSpecifier(specifier) => {
+                            ImportSpecifier::ImportNamespaceSpecifier {
+                                local: Identifier { name: specifier.local.name.to_string(), span: Some(specifier.local.span().into()) },
+                            }
+                        }
+                    })
+                    .collect();
+                // `import { type A } from 'a'` imports nothing at runtime
+                if specifiers.is_empty() && import.specifiers.as_ref().is_some_and(|all| !all.is_empty()) {
+                    return None;
+                }
+
+                Some(Statement::ImportDeclaration {
+                    specifiers,
+                    source: StringLiteral::new(import.source.value.to_string(), Some(import.source.span().into())),
+                    attributes: ImportAttribute::from_oxc(import.with_clause.as_deref()),
+                    span,
                 })
             }
             oxc::Statement::ExpressionStatement(stmt) => {
                 Expression::from_oxc(&stmt.expression).map(|expression| {
-                    Statement::ExpressionStatement { expression }
+                    Statement::ExpressionStatement { expression, span }
                 })
             }
             oxc::Statement::BlockStatement(block) => {
                 let body = block.body.iter()
                     .filter_map(|stmt| Statement::from_oxc(stmt))
                     .collect();
-                Some(Statement::BlockStatement { body })
+                Some(Statement::BlockStatement { body, span })
             }
             oxc::Statement::ReturnStatement(stmt) => {
                 let argument = stmt.argument.as_ref().and_then(|expr| Expression::from_oxc(expr));
-                Some(Statement::ReturnStatement { argument })
+                Some(Statement::ReturnStatement { argument, span })
             }
             oxc::Statement::IfStatement(if_stmt) => {
                 let test = Expression::from_oxc(&if_stmt.test)?;
@@ -590,23 +988,26 @@ impl Statement {
                     test,
                     consequent,
                     alternate,
+                    span,
                 })
             }
             oxc::Statement::WhileStatement(while_stmt) => {
                 let test = Expression::from_oxc(&while_stmt.test)?;
                 let body = Box::new(Statement::from_oxc(&while_stmt.body)?);
                 
-                Some(Statement::WhileStatement { test, body })
+                Some(Statement::WhileStatement { test, body, span })
             }
             oxc::Statement::ForStatement(for_stmt) => {
-                let init = for_stmt.init.as_ref().and_then(|init| {
-                    if let Some(expr) = init.as_expression() {
-                        Expression::from_oxc(expr).map(ForInit::Expression)
-                    } else {
-                        // For now, skip variable declarations in for loops
-                        None
-                    }
-                });
+                let init = match &for_stmt.init {
+                    Some(oxc::ForStatementInit::VariableDeclaration(decl)) => Some(ForInit::VariableDeclaration {
+                        declarations: decl.declarations.iter()
+                            .filter_map(|decl| VariableDeclarator::from_oxc(decl))
+                            .collect(),
+                        kind: VariableDeclarationKind::from_oxc(decl.kind),
+                    }),
+                    Some(init) => Some(ForInit::Expression(Expression::from_oxc(init.as_expression()?)?)),
+                    None => None,
+                };
                 
                 let test = for_stmt.test.as_ref().and_then(|expr| Expression::from_oxc(expr));
                 let update = for_stmt.update.as_ref().and_then(|expr| Expression::from_oxc(expr));
@@ -617,12 +1018,129 @@ impl Statement {
                     test,
                     update,
                     body,
+                    span,
                 })
             }
+            oxc::Statement::ForOfStatement(for_of) => {
+                let left = match &for_of.left {
+                    oxc::ForStatementLeft::VariableDeclaration(decl) => ForInit::VariableDeclaration {
+                        declarations: decl.declarations.iter()
+                            .filter_map(|decl| VariableDeclarator::from_oxc(decl))
+                            .collect(),
+                        kind: VariableDeclarationKind::from_oxc(decl.kind),
+                    },
+                    oxc::ForStatementLeft::AssignmentTargetIdentifier(id) => {
+                        ForInit::Expression(Expression::Identifier(Identifier { name: id.name.to_string(), span: Some(id.span().into()) }))
+                    }
+                    // TODO: Add destructuring and member expression targets
+                    _ => {
+                        dropped(for_of.span);
+                        return None;
+                    }
+                };
+                let right = Expression::from_oxc(&for_of.right)?;
+                let body = Box::new(Statement::from_oxc(&for_of.body)?);
+
+                Some(Statement::ForOfStatement { left, right, body, is_await: for_of.r#await, span })
+            }
+            oxc::Statement::EmptyStatement(_) => Some(Statement::EmptyStatement),
+            oxc::Statement::DebuggerStatement(_) => Some(Statement::DebuggerStatement),
             // TODO: Add more statement types as needed
+            _ => {
+                if !oxc_stmt.is_typescript_syntax() {
+                    dropped(oxc_stmt.span());
+                }
+                None
+            }
+        }
+    }
+
+    /// Convert from an OXC declaration (the statement of `export <declaration>`)
+    pub fn from_oxc_declaration(oxc_decl: &oxc::Declaration<'_>) -> Option<Self> {
+        let span = Some(SourceSpan::from(oxc_decl.span()));
+        match oxc_decl {
+            // `declare` only describes a binding defined elsewhere
+            oxc::Declaration::VariableDeclaration(decl) if decl.declare => None,
+            oxc::Declaration::FunctionDeclaration(func) if func.declare => None,
+            oxc::Declaration::ClassDeclaration(class) if class.declare => None,
+            oxc::Declaration::VariableDeclaration(decl) => {
+                let kind = VariableDeclarationKind::from_oxc(decl.kind);
+
+                let declarations = decl
+                    .declarations
+                    .iter()
+                    .filter_map(|decl| VariableDeclarator::from_oxc(decl))
+                    .collect();
+
+                Some(Statement::VariableDeclaration { declarations, kind, span })
+            }
+            oxc::Declaration::FunctionDeclaration(func) => Statement::from_oxc_function(func),
+            oxc::Declaration::ClassDeclaration(class) => Some(Statement::from_oxc_class(class)),
             _ => None,
         }
     }
+
+    /// Convert a function declaration, named or not (`export default function () {}`)
+    fn from_oxc_function(func: &oxc::Function<'_>) -> Option<Self> {
+        let id = func.id.as_ref().map(|id| Identifier { name: id.name.to_string(), span: Some(id.span().into()) });
+        let params = Pattern::from_oxc_params(&func.params);
+        let body = BlockStatement::from_oxc_function_body(func.body.as_ref()?);
+
+        Some(Statement::FunctionDeclaration {
+            id,
+            params,
+            body,
+            is_async: func.r#async,
+            is_generator: func.generator,
+            span: Some(func.span.into()),
+        })
+    }
+
+    /// Convert a class declaration, named or not (`export default class {}`)
+    fn from_oxc_class(class: &oxc::Class<'_>) -> Self {
+        let id = class.id.as_ref().map(|id| Identifier { name: id.name.to_string(), span: Some(id.span().into()) });
+        let super_class = class.super_class.as_ref()
+            .and_then(|expr| Expression::from_oxc(expr))
+            .map(Box::new);
+        let body = ClassBody {
+            body: class.body.body.iter()
+                .filter_map(|elem| ClassElement::from_oxc(elem))
+                .collect(),
+            span: Some(class.body.span.into()),
+        };
+
+        Statement::ClassDeclaration {
+            id,
+            super_class,
+            body,
+            decorators: decorators_from_oxc(&class.decorators),
+            span: Some(class.span.into()),
+        }
+    }
+}
+
+impl BlockStatement {
+    /// Convert the body of a function, keeping its directive prologue
+    fn from_oxc_function_body(function_body: &oxc::FunctionBody<'_>) -> Self {
+        Self {
+            body: directives_from_oxc(&function_body.directives)
+                .chain(function_body.statements.iter().filter_map(|stmt| Statement::from_oxc(stmt)))
+                .collect(),
+            span: Some(function_body.span.into()),
+        }
+    }
+}
+
+/// Converts a directive prologue (`"use strict"`) to the string expression statements
+/// it is written as
+fn directives_from_oxc<'a>(directives: &'a [oxc::Directive<'_>]) -> impl Iterator<Item = Statement> + 'a {
+    directives.iter().map(|directive| Statement::ExpressionStatement {
+        expression: Expression::Literal(Literal::String(StringLiteral::new(
+            directive.expression.value.to_string(),
+            Some(directive.expression.span.into()),
+        ))),
+        span: Some(directive.span.into()),
+    })
 }
 
 impl VariableDeclarator {
@@ -631,14 +1149,25 @@ impl VariableDeclarator {
         let id = Pattern::from_oxc(&oxc_decl.id)?;
         let init = oxc_decl.init.as_ref().and_then(|expr| Expression::from_oxc(expr));
 
-        Some(Self { id, init })
+        Some(Self { id, init, span: Some(oxc_decl.span.into()) })
     }
 }
 
 impl ClassElement {
+    /// Decorator expressions of the member, in source order
+    pub fn decorators(&self) -> &[Expression] {
+        match self {
+            ClassElement::PropertyDefinition { decorators, .. } | ClassElement::MethodDefinition { decorators, .. } => {
+                decorators
+            }
+        }
+    }
+
     /// Convert from OXC ClassElement to our ClassElement type
     pub fn from_oxc(oxc_elem: &oxc::ClassElement<'_>) -> Option<Self> {
         match oxc_elem {
+            // A `declare` field only gives an inherited property a type
+            oxc::ClassElement::PropertyDefinition(prop) if prop.declare => None,
             oxc::ClassElement::PropertyDefinition(prop) => {
                 let key = PropertyKey::from_oxc(&prop.key)?;
                 let value = prop.value.as_ref().and_then(|expr| Expression::from_oxc(expr));
@@ -650,6 +1179,7 @@ impl ClassElement {
                     value,
                     is_static,
                     is_private,
+                    decorators: decorators_from_oxc(&prop.decorators),
                 })
             }
             oxc::ClassElement::MethodDefinition(method) => {
@@ -670,36 +1200,109 @@ impl ClassElement {
                     kind,
                     is_static,
                     is_private,
+                    decorators: decorators_from_oxc(&method.decorators),
                 })
             }
-            _ => None,
+            oxc::ClassElement::TSIndexSignature(_) => None,
+            _ => {
+                dropped(oxc_elem.span());
+                None
+            }
         }
     }
 }
 
+/// Converts the decorators of a class or class member, dropping those it cannot represent
+fn decorators_from_oxc(decorators: &[oxc::Decorator<'_>]) -> Vec<Expression> {
+    decorators.iter().filter_map(|decorator| Expression::from_oxc(&decorator.expression)).collect()
+}
+
+impl ImportAttribute {
+    /// Converts the entries of a `with` (or legacy `assert`) clause
+    pub fn from_oxc(with_clause: Option<&oxc::WithClause<'_>>) -> Vec<Self> {
+        let Some(with_clause) = with_clause else {
+            return Vec::new();
+        };
+        with_clause
+            .with_entries
+            .iter()
+            .map(|attribute| {
+                let key = match &attribute.key {
+                    oxc::ImportAttributeKey::Identifier(key) => key.name.to_string(),
+                    oxc::ImportAttributeKey::StringLiteral(key) => key.value.to_string(),
+                };
+                ImportAttribute {
+                    key: StringLiteral::new(key, Some(attribute.key.span().into())),
+                    value: StringLiteral::new(attribute.value.value.to_string(), Some(attribute.value.span.into())),
+                }
+            })
+            .collect()
+    }
+}
+
 impl Expression {
+    /// Returns whether evaluating the expression may suspend the enclosing function at a
+    /// `yield` or `await`
+    ///
+    /// Nested functions are not entered, since their bodies don't run here. Code must not
+    /// be moved across a suspending expression (for example when inlining a variable into
+    /// its use), because other code can run, and observe or change state, while the
+    /// function is suspended.
+    pub fn may_suspend(&self) -> bool {
+        match self {
+            Expression::AwaitExpression { .. } | Expression::YieldExpression { .. } => true,
+            Expression::Identifier(_)
+            | Expression::Literal(_)
+            | Expression::ThisExpression
+            | Expression::MetaProperty { .. }
+            | Expression::FunctionExpression(_)
+            | Expression::ArrowFunctionExpression { .. } => false,
+            Expression::BinaryExpression { left, right, .. }
+            | Expression::AssignmentExpression { left, right, .. } => left.may_suspend() || right.may_suspend(),
+            Expression::UnaryExpression { argument, .. }
+            | Expression::UpdateExpression { argument, .. }
+            | Expression::SpreadElement { argument } => argument.may_suspend(),
+            Expression::ChainExpression { expression } => expression.may_suspend(),
+            Expression::CallExpression { callee, arguments, .. } | Expression::NewExpression { callee, arguments } => {
+                callee.may_suspend() || arguments.iter().any(Expression::may_suspend)
+            }
+            Expression::ImportExpression { source, options } => {
+                source.may_suspend() || options.as_ref().is_some_and(|options| options.may_suspend())
+            }
+            Expression::MemberExpression { object, property, .. } => object.may_suspend() || property.may_suspend(),
+            Expression::ObjectExpression { properties } => properties.iter().any(|property| match property {
+                ObjectProperty::Property { value, .. } => value.may_suspend(),
+                ObjectProperty::SpreadElement { argument } => argument.may_suspend(),
+            }),
+            Expression::ArrayExpression { elements } => elements.iter().flatten().any(Expression::may_suspend),
+            Expression::TemplateLiteral { expressions, .. } => expressions.iter().any(Expression::may_suspend),
+            Expression::TaggedTemplateExpression { tag, expressions, .. } => {
+                tag.may_suspend() || expressions.iter().any(Expression::may_suspend)
+            }
+            Expression::ConditionalExpression { test, consequent, alternate } => {
+                test.may_suspend() || consequent.may_suspend() || alternate.may_suspend()
+            }
+        }
+    }
+
     /// Convert from OXC Expression to our Expression type
     pub fn from_oxc(oxc_expr: &oxc::Expression<'_>) -> Option<Self> {
         match oxc_expr {
             oxc::Expression::Identifier(id) => {
-                Some(Expression::Identifier(Identifier {
-                    name: id.name.to_string(),
-                }))
+                Some(Expression::Identifier(Identifier { name: id.name.to_string(), span: Some(id.span().into()) }))
             }
             oxc::Expression::NumericLiteral(lit) => {
                 Some(Expression::Literal(Literal::Number(NumberLiteral {
                     value: lit.value,
+                    raw: Some(lit.raw.to_string()),
+                    span: Some(lit.span.into()),
                 })))
             }
             oxc::Expression::StringLiteral(lit) => {
-                Some(Expression::Literal(Literal::String(StringLiteral {
-                    value: lit.value.to_string(),
-                })))
+                Some(Expression::Literal(Literal::String(StringLiteral::from_oxc(lit))))
             }
             oxc::Expression::BooleanLiteral(lit) => {
-                Some(Expression::Literal(Literal::Boolean(BooleanLiteral {
-                    value: lit.value,
-                })))
+                Some(Expression::Literal(Literal::Boolean(BooleanLiteral { value: lit.value, span: Some(lit.span().into()) })))
             }
             oxc::Expression::NullLiteral(_) => {
                 Some(Expression::Literal(Literal::Null))
@@ -715,13 +1318,23 @@ impl Expression {
                     right,
                 })
             }
+            oxc::Expression::LogicalExpression(expr) => {
+                let left = Box::new(Expression::from_oxc(&expr.left)?);
+                let right = Box::new(Expression::from_oxc(&expr.right)?);
+                let operator = match expr.operator {
+                    oxc::LogicalOperator::And => BinaryOperator::LogicalAnd,
+                    oxc::LogicalOperator::Or => BinaryOperator::LogicalOr,
+                    oxc::LogicalOperator::Coalesce => BinaryOperator::NullishCoalescing,
+                };
+
+                Some(Expression::BinaryExpression {
+                    left,
+                    operator,
+                    right,
+                })
+            }
             oxc::Expression::TemplateLiteral(tmpl) => {
-                let quasis = tmpl.quasis.iter()
-                    .map(|quasi| TemplateElement {
-                        value: quasi.value.raw.to_string(),
-                        tail: quasi.tail,
-                    })
-                    .collect();
+                let quasis = tmpl.quasis.iter().map(TemplateElement::from_oxc).collect();
                 
                 let expressions = tmpl.expressions.iter()
                     .filter_map(|expr| Expression::from_oxc(expr))
@@ -732,32 +1345,305 @@ impl Expression {
             oxc::Expression::FunctionExpression(func) => {
                 Some(Expression::FunctionExpression(FunctionExpression::from_oxc(func)?))
             }
-            oxc::Expression::CallExpression(call) => {
-                let callee = Box::new(Expression::from_oxc(&call.callee)?);
-                let arguments = call.arguments.iter()
-                    .filter_map(|arg| {
-                        if let Some(expr) = arg.as_expression() {
-                            Expression::from_oxc(expr)
-                        } else {
-                            None // Skip spread arguments for now
-                        }
-                    })
-                    .collect();
-                
-                Some(Expression::CallExpression { callee, arguments })
-            }
+            oxc::Expression::CallExpression(call) => Expression::from_oxc_call(call),
+            oxc::Expression::NewExpression(new) => Expression::from_oxc_new(new),
+            oxc::Expression::MetaProperty(meta) => Some(Expression::MetaProperty {
+                meta: meta.meta.name.to_string(),
+                property: meta.property.name.to_string(),
+            }),
             oxc::Expression::RegExpLiteral(regex) => {
                 Some(Expression::Literal(Literal::RegExp(RegExpLiteral {
                     pattern: regex.regex.pattern.to_string(),
                     flags: regex.regex.flags.to_string(),
+                    span: Some(regex.span.into()),
                 })))
             }
+            oxc::Expression::StaticMemberExpression(_) | oxc::Expression::ComputedMemberExpression(_) => {
+                Expression::from_oxc_member(oxc_expr.as_member_expression()?)
+            }
+            oxc::Expression::ChainExpression(chain) => Expression::from_oxc_chain(chain),
+            oxc::Expression::AssignmentExpression(expr) => Expression::from_oxc_assignment(expr),
+            oxc::Expression::UpdateExpression(expr) => Expression::from_oxc_update(expr),
+            oxc::Expression::ConditionalExpression(expr) => Some(Expression::ConditionalExpression {
+                test: Box::new(Expression::from_oxc(&expr.test)?),
+                consequent: Box::new(Expression::from_oxc(&expr.consequent)?),
+                alternate: Box::new(Expression::from_oxc(&expr.alternate)?),
+            }),
+            oxc::Expression::UnaryExpression(expr) => {
+                let argument = Box::new(Expression::from_oxc(&expr.argument)?);
+                let operator = UnaryOperator::from_oxc(expr.operator);
+
+                Some(Expression::UnaryExpression {
+                    operator,
+                    argument,
+                    prefix: true,
+                })
+            }
+            oxc::Expression::ParenthesizedExpression(paren) => {
+                // Parentheses are reintroduced by the printer based on precedence
+                Expression::from_oxc(&paren.expression)
+            }
+            // Type assertions and instantiations only wrap the value for the type checker
+            oxc::Expression::TSAsExpression(expr) => Expression::from_oxc(&expr.expression),
+            oxc::Expression::TSSatisfiesExpression(expr) => Expression::from_oxc(&expr.expression),
+            oxc::Expression::TSTypeAssertion(expr) => Expression::from_oxc(&expr.expression),
+            oxc::Expression::TSNonNullExpression(expr) => Expression::from_oxc(&expr.expression),
+            oxc::Expression::TSInstantiationExpression(expr) => Expression::from_oxc(&expr.expression),
+            oxc::Expression::ThisExpression(_) => Some(Expression::ThisExpression),
+            oxc::Expression::ImportExpression(expr) => Expression::from_oxc_import(expr),
+            oxc::Expression::AwaitExpression(expr) => {
+                let argument = Box::new(Expression::from_oxc(&expr.argument)?);
+                Some(Expression::AwaitExpression { argument })
+            }
+            oxc::Expression::YieldExpression(expr) => {
+                let argument = match &expr.argument {
+                    Some(argument) => Some(Box::new(Expression::from_oxc(argument)?)),
+                    None => None,
+                };
+                Some(Expression::YieldExpression { argument, delegate: expr.delegate })
+            }
+            oxc::Expression::TaggedTemplateExpression(tagged) => Expression::from_oxc_tagged_template(tagged),
+            oxc::Expression::ArrayExpression(array) => Expression::from_oxc_array(array),
+            oxc::Expression::ObjectExpression(object) => {
+                let properties = object.properties.iter()
+                    .map(ObjectProperty::from_oxc)
+                    .collect::<Option<_>>()?;
+                Some(Expression::ObjectExpression { properties })
+            }
+            oxc::Expression::ArrowFunctionExpression(arrow) => Expression::from_oxc_arrow(arrow),
             // TODO: Add more expression types as needed
-            _ => None,
+            _ => {
+                dropped(oxc_expr.span());
+                None
+            }
         }
     }
 }
 
+impl ObjectProperty {
+    /// Convert from an OXC object literal member; computed keys are not supported
+    pub fn from_oxc(oxc_prop: &oxc::ObjectPropertyKind<'_>) -> Option<Self> {
+        match oxc_prop {
+            oxc::ObjectPropertyKind::ObjectProperty(prop) if !prop.computed => Some(ObjectProperty::Property {
+                key: PropertyKey::from_oxc(&prop.key)?,
+                value: Expression::from_oxc(&prop.value)?,
+                kind: match prop.kind {
+                    oxc::PropertyKind::Init => PropertyKind::Init,
+                    oxc::PropertyKind::Get => PropertyKind::Get,
+                    oxc::PropertyKind::Set => PropertyKind::Set,
+                },
+                method: prop.method,
+                shorthand: prop.shorthand,
+                computed: false,
+            }),
+            oxc::ObjectPropertyKind::ObjectProperty(prop) => {
+                dropped(prop.span);
+                None
+            }
+            oxc::ObjectPropertyKind::SpreadProperty(spread) => Some(ObjectProperty::SpreadElement {
+                argument: Expression::from_oxc(&spread.argument)?,
+            }),
+        }
+    }
+}
+
+impl Expression {
+    /// Convert from an OXC call, inside or outside of an optional chain
+    fn from_oxc_call(call: &oxc::CallExpression<'_>) -> Option<Self> {
+        let callee = Box::new(Expression::from_oxc(&call.callee)?);
+        let arguments = call.arguments.iter()
+            .filter_map(|arg| match arg {
+                oxc::Argument::SpreadElement(spread) => Expression::from_oxc_spread(spread),
+                arg => Expression::from_oxc(arg.as_expression()?),
+            })
+            .collect();
+
+        Some(Expression::CallExpression { callee, arguments, optional: call.optional })
+    }
+
+    /// Convert from an OXC `new` expression
+    fn from_oxc_new(new: &oxc::NewExpression<'_>) -> Option<Self> {
+        let callee = Box::new(Expression::from_oxc(&new.callee)?);
+        let arguments = new.arguments.iter()
+            .filter_map(|arg| match arg {
+                oxc::Argument::SpreadElement(spread) => Expression::from_oxc_spread(spread),
+                arg => Expression::from_oxc(arg.as_expression()?),
+            })
+            .collect();
+
+        Some(Expression::NewExpression { callee, arguments })
+    }
+
+    // The larger conversions live in their own functions to keep the frame of the
+    // recursive `from_oxc` small for deeply nested expressions
+
+    /// Convert from an OXC dynamic `import()`
+    #[inline(never)]
+    fn from_oxc_import(expr: &oxc::ImportExpression<'_>) -> Option<Self> {
+        let options = match expr.arguments.first() {
+            Some(options) => Some(Box::new(Expression::from_oxc(options)?)),
+            None => None,
+        };
+        Some(Expression::ImportExpression { source: Box::new(Expression::from_oxc(&expr.source)?), options })
+    }
+
+    /// Convert from an OXC optional chain
+    fn from_oxc_chain(chain: &oxc::ChainExpression<'_>) -> Option<Self> {
+        let expression = match &chain.expression {
+            oxc::ChainElement::CallExpression(call) => Expression::from_oxc_call(call)?,
+            element => Expression::from_oxc_member(element.as_member_expression()?)?,
+        };
+        Some(Expression::ChainExpression { expression: Box::new(expression) })
+    }
+
+    /// Convert from an OXC assignment to an identifier or member; destructuring
+    /// assignments are not supported
+    fn from_oxc_assignment(expr: &oxc::AssignmentExpression<'_>) -> Option<Self> {
+        let left = match expr.left.as_simple_assignment_target()? {
+            oxc::SimpleAssignmentTarget::AssignmentTargetIdentifier(id) => {
+                Expression::Identifier(Identifier { name: id.name.to_string(), span: Some(id.span().into()) })
+            }
+            target => Expression::from_oxc_member(target.as_member_expression()?)?,
+        };
+
+        Some(Expression::AssignmentExpression {
+            left: Box::new(left),
+            operator: AssignmentOperator::from_oxc(expr.operator),
+            right: Box::new(Expression::from_oxc(&expr.right)?),
+        })
+    }
+
+    /// Convert from an OXC `++` or `--` on an identifier or member
+    fn from_oxc_update(expr: &oxc::UpdateExpression<'_>) -> Option<Self> {
+        let argument = match &expr.argument {
+            oxc::SimpleAssignmentTarget::AssignmentTargetIdentifier(id) => {
+                Expression::Identifier(Identifier { name: id.name.to_string(), span: Some(id.span().into()) })
+            }
+            target => Expression::from_oxc_member(target.as_member_expression()?)?,
+        };
+
+        Some(Expression::UpdateExpression {
+            operator: match expr.operator {
+                oxc::UpdateOperator::Increment => UpdateOperator::Increment,
+                oxc::UpdateOperator::Decrement => UpdateOperator::Decrement,
+            },
+            argument: Box::new(argument),
+            prefix: expr.prefix,
+        })
+    }
+
+    /// Convert from an OXC tagged template
+    fn from_oxc_tagged_template(tagged: &oxc::TaggedTemplateExpression<'_>) -> Option<Self> {
+        Some(Expression::TaggedTemplateExpression {
+            tag: Box::new(Expression::from_oxc(&tagged.tag)?),
+            quasis: tagged.quasi.quasis.iter().map(TemplateElement::from_oxc).collect(),
+            expressions: tagged.quasi.expressions.iter()
+                .map(Expression::from_oxc)
+                .collect::<Option<_>>()?,
+        })
+    }
+
+    /// Convert from an OXC array literal, keeping holes as `None`
+    fn from_oxc_array(array: &oxc::ArrayExpression<'_>) -> Option<Self> {
+        let elements = array.elements.iter()
+            .map(|element| match element {
+                oxc::ArrayExpressionElement::Elision(_) => Some(None),
+                oxc::ArrayExpressionElement::SpreadElement(spread) => Expression::from_oxc_spread(spread).map(Some),
+                element => Expression::from_oxc(element.as_expression()?).map(Some),
+            })
+            .collect::<Option<_>>()?;
+        Some(Expression::ArrayExpression { elements })
+    }
+
+    /// Convert from an OXC arrow function
+    fn from_oxc_arrow(arrow: &oxc::ArrowFunctionExpression<'_>) -> Option<Self> {
+        let body = match (arrow.expression, arrow.body.statements.first()) {
+            (true, Some(oxc::Statement::ExpressionStatement(stmt))) => {
+                ArrowFunctionBody::Expression(Box::new(Expression::from_oxc(&stmt.expression)?))
+            }
+            _ => ArrowFunctionBody::BlockStatement(BlockStatement::from_oxc_function_body(&arrow.body)),
+        };
+
+        Some(Expression::ArrowFunctionExpression {
+            params: Pattern::from_oxc_params(&arrow.params),
+            body,
+            is_async: arrow.r#async,
+        })
+    }
+
+    /// Convert from an OXC spread argument or array element
+    fn from_oxc_spread(spread: &oxc::SpreadElement<'_>) -> Option<Self> {
+        let argument = Box::new(Expression::from_oxc(&spread.argument)?);
+        Some(Expression::SpreadElement { argument })
+    }
+
+    /// Convert from an OXC member access, inside or outside of an optional chain
+    fn from_oxc_member(member: &oxc::MemberExpression<'_>) -> Option<Self> {
+        match member {
+            oxc::MemberExpression::StaticMemberExpression(member) => Some(Expression::MemberExpression {
+                object: Box::new(Expression::from_oxc(&member.object)?),
+                property: Box::new(Expression::Identifier(Identifier { name: member.property.name.to_string(), span: Some(member.property.span().into()) })),
+                computed: false,
+                optional: member.optional,
+            }),
+            oxc::MemberExpression::ComputedMemberExpression(member) => Some(Expression::MemberExpression {
+                object: Box::new(Expression::from_oxc(&member.object)?),
+                property: Box::new(Expression::from_oxc(&member.expression)?),
+                computed: true,
+                optional: member.optional,
+            }),
+            // TODO: Add private field access (`this.#x`)
+            oxc::MemberExpression::PrivateFieldExpression(field) => {
+                dropped(field.span);
+                None
+            }
+        }
+    }
+}
+
+impl AssignmentOperator {
+    /// Convert from OXC AssignmentOperator to our AssignmentOperator type
+    pub fn from_oxc(oxc_op: oxc::AssignmentOperator) -> Self {
+        match oxc_op {
+            oxc::AssignmentOperator::Assign => AssignmentOperator::Assign,
+            oxc::AssignmentOperator::Addition => AssignmentOperator::AddAssign,
+            oxc::AssignmentOperator::Subtraction => AssignmentOperator::SubtractAssign,
+            oxc::AssignmentOperator::Multiplication => AssignmentOperator::MultiplyAssign,
+            oxc::AssignmentOperator::Division => AssignmentOperator::DivideAssign,
+            oxc::AssignmentOperator::Remainder => AssignmentOperator::RemainderAssign,
+            oxc::AssignmentOperator::Exponential => AssignmentOperator::ExponentiationAssign,
+            oxc::AssignmentOperator::ShiftLeft => AssignmentOperator::LeftShiftAssign,
+            oxc::AssignmentOperator::ShiftRight => AssignmentOperator::RightShiftAssign,
+            oxc::AssignmentOperator::ShiftRightZeroFill => AssignmentOperator::UnsignedRightShiftAssign,
+            oxc::AssignmentOperator::BitwiseAnd => AssignmentOperator::BitwiseAndAssign,
+            oxc::AssignmentOperator::BitwiseOR => AssignmentOperator::BitwiseOrAssign,
+            oxc::AssignmentOperator::BitwiseXOR => AssignmentOperator::BitwiseXorAssign,
+            oxc::AssignmentOperator::LogicalAnd => AssignmentOperator::LogicalAndAssign,
+            oxc::AssignmentOperator::LogicalOr => AssignmentOperator::LogicalOrAssign,
+            oxc::AssignmentOperator::LogicalNullish => AssignmentOperator::NullishCoalescingAssign,
+        }
+    }
+}
+
+impl VariableDeclarationKind {
+    /// Convert from an OXC declaration kind
+    pub fn from_oxc(oxc_kind: oxc::VariableDeclarationKind) -> Self {
+        match oxc_kind {
+            oxc::VariableDeclarationKind::Var => VariableDeclarationKind::Var,
+            oxc::VariableDeclarationKind::Let => VariableDeclarationKind::Let,
+            oxc::VariableDeclarationKind::Const => VariableDeclarationKind::Const,
+            oxc::VariableDeclarationKind::Using => VariableDeclarationKind::Using,
+            oxc::VariableDeclarationKind::AwaitUsing => VariableDeclarationKind::AwaitUsing,
+        }
+    }
+
+    /// Whether the declaration disposes of its values, so it has an effect even when
+    /// its bindings are never read
+    pub fn is_using(&self) -> bool {
+        matches!(self, VariableDeclarationKind::Using | VariableDeclarationKind::AwaitUsing)
+    }
+}
+
 impl Pattern {
     /// Convert from OXC BindingPattern to our Pattern type
     pub fn from_oxc(oxc_pattern: &oxc::BindingPattern<'_>) -> Option<Self> {
@@ -765,9 +1651,56 @@ impl Pattern {
             oxc::BindingPatternKind::BindingIdentifier(id) => {
                 Some(Pattern::Identifier(Identifier::from_oxc(id)))
             }
-            // TODO: Add more pattern types as needed
-            _ => None,
+            oxc::BindingPatternKind::ArrayPattern(array) => {
+                let mut elements = array.elements.iter()
+                    .map(|element| match element {
+                        Some(element) => Pattern::from_oxc(element).map(Some),
+                        None => Some(None),
+                    })
+                    .collect::<Option<Vec<_>>>()?;
+                if let Some(rest) = &array.rest {
+                    elements.push(Some(Pattern::RestElement { argument: Box::new(Pattern::from_oxc(&rest.argument)?) }));
+                }
+                Some(Pattern::ArrayPattern { elements })
+            }
+            oxc::BindingPatternKind::ObjectPattern(object) => {
+                let mut properties = object.properties.iter()
+                    .map(|prop| {
+                        if prop.computed {
+                            dropped(prop.span);
+                            return None;
+                        }
+                        Some(ObjectPatternProperty::Property {
+                            key: PropertyKey::from_oxc(&prop.key)?,
+                            value: Pattern::from_oxc(&prop.value)?,
+                            computed: false,
+                            shorthand: prop.shorthand,
+                        })
+                    })
+                    .collect::<Option<Vec<_>>>()?;
+                if let Some(rest) = &object.rest {
+                    properties.push(ObjectPatternProperty::RestElement { argument: Pattern::from_oxc(&rest.argument)? });
+                }
+                Some(Pattern::ObjectPattern { properties })
+            }
+            oxc::BindingPatternKind::AssignmentPattern(assignment) => Some(Pattern::AssignmentPattern {
+                left: Box::new(Pattern::from_oxc(&assignment.left)?),
+                right: Expression::from_oxc(&assignment.right)?,
+            }),
+        }
+    }
+
+    /// Convert a formal parameter list, with a rest parameter as a trailing [`Pattern::RestElement`]
+    pub fn from_oxc_params(oxc_params: &oxc::FormalParameters<'_>) -> Vec<Self> {
+        let mut params: Vec<Self> = oxc_params.items.iter()
+            .filter_map(|param| Pattern::from_oxc(&param.pattern))
+            .collect();
+        if let Some(rest) = &oxc_params.rest
+            && let Some(argument) = Pattern::from_oxc(&rest.argument)
+        {
+            params.push(Pattern::RestElement { argument: Box::new(argument) });
         }
+        params
     }
 }
 
@@ -776,6 +1709,7 @@ impl Identifier {
     pub fn from_oxc(oxc_id: &oxc::BindingIdentifier<'_>) -> Self {
         Self {
             name: oxc_id.name.to_string(),
+            span: Some(oxc_id.span.into()),
         }
     }
 }
@@ -810,24 +1744,53 @@ impl BinaryOperator {
     }
 }
 
+impl UnaryOperator {
+    /// Convert from OXC UnaryOperator to our UnaryOperator type
+    pub fn from_oxc(oxc_op: oxc::UnaryOperator) -> Self {
+        match oxc_op {
+            oxc::UnaryOperator::UnaryNegation => UnaryOperator::Minus,
+            oxc::UnaryOperator::UnaryPlus => UnaryOperator::Plus,
+            oxc::UnaryOperator::LogicalNot => UnaryOperator::LogicalNot,
+            oxc::UnaryOperator::BitwiseNot => UnaryOperator::BitwiseNot,
+            oxc::UnaryOperator::Typeof => UnaryOperator::Typeof,
+            oxc::UnaryOperator::Void => UnaryOperator::Void,
+            oxc::UnaryOperator::Delete => UnaryOperator::Delete,
+        }
+    }
+}
+
 impl PropertyKey {
     /// Convert from OXC PropertyKey to our PropertyKey type
     pub fn from_oxc(oxc_key: &oxc::PropertyKey<'_>) -> Option<Self> {
         match oxc_key {
+            oxc::PropertyKey::StaticIdentifier(id) => {
+                Some(PropertyKey::Identifier(Identifier { name: id.name.to_string(), span: Some(id.span().into()) }))
+            }
+            oxc::PropertyKey::StringLiteral(lit) => {
+                Some(PropertyKey::Literal(Literal::String(StringLiteral::from_oxc(lit))))
+            }
+            oxc::PropertyKey::NumericLiteral(lit) => {
+                Some(PropertyKey::Literal(Literal::Number(NumberLiteral {
+                    value: lit.value,
+                    raw: Some(lit.raw.to_string()),
+                    span: Some(lit.span.into()),
+                })))
+            }
             oxc::PropertyKey::Identifier(id) => {
-                Some(PropertyKey::Identifier(Identifier {
-                    name: id.name.to_string(),
-                }))
+                Some(PropertyKey::Identifier(Identifier { name: id.name.to_string(), span: Some(id.span().into()) }))
             }
             oxc::PropertyKey::PrivateIdentifier(private) => {
                 Some(PropertyKey::PrivateName(PrivateName {
                     name: private.name.to_string(),
+                    span: Some(private.span.into()),
                 }))
             }
             _ => {
                 // For now, handle computed property keys as identifier
+                dropped(oxc_key.span());
                 Some(PropertyKey::Identifier(Identifier {
                     name: "computed".to_string(),
+                    span: Some(oxc_key.span().into()),
                 }))
             }
         }
@@ -837,20 +1800,11 @@ impl PropertyKey {
 impl FunctionExpression {
     /// Convert from OXC Function to our FunctionExpression type
     pub fn from_oxc(oxc_func: &oxc::Function<'_>) -> Option<Self> {
-        let id = oxc_func.id.as_ref().map(|id| Identifier {
-            name: id.name.to_string(),
-        });
+        let id = oxc_func.id.as_ref().map(|id| Identifier { name: id.name.to_string(), span: Some(id.span().into()) });
         
-        let params = oxc_func.params.items.iter()
-            .filter_map(|param| Pattern::from_oxc(&param.pattern))
-            .collect();
+        let params = Pattern::from_oxc_params(&oxc_func.params);
         
-        let body = BlockStatement {
-            body: oxc_func.body.as_ref()?
-                .statements.iter()
-                .filter_map(|stmt| Statement::from_oxc(stmt))
-                .collect(),
-        };
+        let body = BlockStatement::from_oxc_function_body(oxc_func.body.as_ref()?);
         
         Some(FunctionExpression {
             id,
@@ -858,6 +1812,7 @@ impl FunctionExpression {
             body,
             is_async: oxc_func.r#async,
             is_generator: oxc_func.generator,
+            span: Some(oxc_func.span.into()),
         })
     }
 }
\ No newline at end of file
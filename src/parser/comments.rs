@@ -0,0 +1,186 @@
+//! # Comment Attachment Module
+//!
+//! Attaches source comments to the statements they document, so the generator can
+//! re-emit them next to those statements with
+//! [`CommentPreservation::All`](crate::generator::CommentPreservation::All).
+//!
+//! A comment leads the statement starting right after it, or trails the statement
+//! ending right before it on the same line, following the classification of [`Trivia`].
+//! Other comments, such as those inside an expression or after the last statement of a
+//! block, go to the nearest statement within the innermost statement containing them:
+//! the next one, else the previous one, else the containing statement itself.
+//!
+//! Statements are identified by their source span, which passes keep when they move or
+//! rewrite a statement. Comments of removed statements are dropped with them.
+
+use std::collections::HashMap;
+
+use crate::parser::{Comment, CommentKind, CommentPosition, SourceSpan, Trivia};
+
+/// A comment attached to a statement
+#[derive(Debug, Clone)]
+pub struct AttachedComment {
+    /// Comment text exactly as written, including the `//` or `/* */` delimiters
+    pub text: String,
+    /// Whether it's a line comment, which must be followed by a line break
+    pub kind: CommentKind,
+}
+
+/// Comments of a program, by the source span of the statement they belong to
+#[derive(Debug, Clone, Default)]
+pub struct AttachedComments {
+    leading:  HashMap<SourceSpan, Vec<AttachedComment>>,
+    trailing: HashMap<SourceSpan, Vec<AttachedComment>>,
+    /// Comments of a program without statements
+    dangling: Vec<AttachedComment>,
+}
+
+impl AttachedComments {
+    /// Attaches the comments in `trivia` to the statements spanning `statement_spans`
+    ///
+    /// `statement_spans` lists the statements at any depth, each before the statements
+    /// nested in it.
+    pub fn attach(source: &str, trivia: &Trivia, statement_spans: &[SourceSpan]) -> Self {
+        let mut comments: Vec<&Comment> = trivia.line_comments.iter().chain(&trivia.block_comments).collect();
+        comments.sort_by_key(|comment| comment.span.start);
+
+        let mut attached = Self::default();
+        for comment in comments {
+            let text = source.get(comment.span.start as usize..comment.span.end as usize).unwrap_or_default();
+            let entry = AttachedComment { text: text.to_string(), kind: comment.kind.clone() };
+            match attachment(comment, statement_spans) {
+                Some((CommentPosition::Leading, span)) => attached.leading.entry(span).or_default().push(entry),
+                Some((CommentPosition::Trailing, span)) => attached.trailing.entry(span).or_default().push(entry),
+                None => attached.dangling.push(entry),
+            }
+        }
+        attached
+    }
+
+    /// Comments printed before the statement spanning `span`, in source order
+    pub fn leading(&self, span: &SourceSpan) -> &[AttachedComment] {
+        self.leading.get(span).map_or(&[], Vec::as_slice)
+    }
+
+    /// Comments printed after the statement spanning `span`, in source order
+    pub fn trailing(&self, span: &SourceSpan) -> &[AttachedComment] {
+        self.trailing.get(span).map_or(&[], Vec::as_slice)
+    }
+
+    /// Comments of a program without statements
+    pub fn dangling(&self) -> &[AttachedComment] {
+        &self.dangling
+    }
+
+    /// Keeps only the comments for which `keep` returns true
+    pub fn retain(&mut self, keep: impl Fn(&AttachedComment) -> bool) {
+        for comments in self.leading.values_mut().chain(self.trailing.values_mut()) {
+            comments.retain(&keep);
+        }
+        self.leading.retain(|_, comments| !comments.is_empty());
+        self.trailing.retain(|_, comments| !comments.is_empty());
+        self.dangling.retain(&keep);
+    }
+
+    /// Whether there are no comments at all
+    pub fn is_empty(&self) -> bool {
+        self.leading.is_empty() && self.trailing.is_empty() && self.dangling.is_empty()
+    }
+}
+
+/// The statement a comment belongs to and the side it goes on, or `None` without statements
+fn attachment(comment: &Comment, spans: &[SourceSpan]) -> Option<(CommentPosition, SourceSpan)> {
+    // Statements sharing a boundary are listed outermost first, and the comment goes
+    // outside all of them
+    let adjacent = match comment.position {
+        CommentPosition::Leading => spans.iter().find(|span| span.start == comment.attached_to),
+        CommentPosition::Trailing => spans.iter().find(|span| span.end == comment.attached_to),
+    };
+    if let Some(span) = adjacent {
+        return Some((comment.position, span.clone()));
+    }
+
+    let contains = |outer: &SourceSpan, inner: &SourceSpan| outer.start <= inner.start && inner.end <= outer.end;
+    let container = spans
+        .iter()
+        .filter(|span| contains(span, &comment.span))
+        .min_by_key(|span| span.end - span.start);
+    let inside: Vec<&SourceSpan> = spans
+        .iter()
+        .filter(|span| container.is_none_or(|container| *span != container && contains(container, span)))
+        .collect();
+
+    let next = inside
+        .iter()
+        .filter(|span| span.start >= comment.span.end)
+        .min_by_key(|span| span.start);
+    // `max_by_key` keeps the last maximum, so search from the end for the outermost
+    let previous = inside
+        .iter()
+        .rev()
+        .filter(|span| span.end <= comment.span.start)
+        .max_by_key(|span| span.end);
+
+    next.map(|span| (CommentPosition::Leading, (*span).clone()))
+        .or_else(|| previous.map(|span| (CommentPosition::Trailing, (*span).clone())))
+        .or_else(|| container.map(|span| (CommentPosition::Leading, span.clone())))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::parser::{parse_js_in, Allocator, ParserConfig};
+
+    /// Leading and trailing comment texts of each top-level statement, as `(statement, leading, trailing)`
+    fn attached(source: &str) -> Vec<(String, Vec<String>, Vec<String>)> {
+        let allocator = Allocator::default();
+        let parsed = parse_js_in(source, "test.js", &allocator, &ParserConfig::default());
+        let comments = parsed.attached_comments();
+        let texts = |comments: &[super::AttachedComment]| comments.iter().map(|c| c.text.clone()).collect::<Vec<_>>();
+        parsed
+            .statement_spans()
+            .iter()
+            .map(|span| {
+                let statement = source[span.start as usize..span.end as usize].to_string();
+                (statement, texts(comments.leading(span)), texts(comments.trailing(span)))
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_attaches_leading_and_trailing_comments() {
+        let statements = attached("// setup\n/* a */ let a = 1; // one\nf(a); /* two */\n// end\n");
+
+        assert_eq!(statements[0].1, ["// setup", "/* a */"]);
+        assert_eq!(statements[0].2, ["// one"]);
+        assert_eq!(statements[1].1, Vec::<String>::new());
+        // The comment after the last statement has nothing to lead
+        assert_eq!(statements[1].2, ["/* two */", "// end"]);
+    }
+
+    #[test]
+    fn test_attaches_nested_comments_within_their_container() {
+        let source = "function f() { // body\n  return 1;\n  // after\n}\ng(/* arg */ x);\n";
+        let allocator = Allocator::default();
+        let parsed = parse_js_in(source, "test.js", &allocator, &ParserConfig::default());
+        let comments = parsed.attached_comments();
+        let span_of = |text: &str| {
+            let start = source.find(text).unwrap() as u32;
+            crate::parser::SourceSpan { start, end: start + text.len() as u32 }
+        };
+
+        let leading = |text| comments.leading(&span_of(text)).iter().map(|c| c.text.as_str()).collect::<Vec<_>>();
+        let trailing = |text| comments.trailing(&span_of(text)).iter().map(|c| c.text.as_str()).collect::<Vec<_>>();
+        assert_eq!(leading("return 1;"), ["// body"]);
+        assert_eq!(trailing("return 1;"), ["// after"]);
+        assert_eq!(leading("g(/* arg */ x);"), ["/* arg */"]);
+        assert!(comments.dangling().is_empty());
+    }
+
+    #[test]
+    fn test_keeps_comments_of_empty_programs() {
+        let allocator = Allocator::default();
+        let parsed = parse_js_in("/* only */\n", "test.js", &allocator, &ParserConfig::default());
+
+        assert_eq!(parsed.attached_comments().dangling()[0].text, "/* only */");
+    }
+}
@@ -0,0 +1,354 @@
+//! # Nesting Depth Guard
+//!
+//! Every phase after parsing walks the AST recursively, and so does the OXC parser
+//! itself, so a pathologically nested input (ten thousand parentheses, or an `if`
+//! chain as long) would overflow the native stack and abort the process. The guard
+//! rejects such input with [`ParseError::NestingTooDeep`](crate::parser::ParseError)
+//! before anything recursive runs deeper than [`ParserConfig::max_depth`](crate::parser::ParserConfig).
+//!
+//! It runs in two steps. Before OXC parses, a lexical scan measures how deeply the
+//! brackets (`(`, `[`, `{` and template substitutions) nest, without recursion; that is
+//! the nesting the parser recurses on. Nesting without brackets, such as a long `a+b+…`
+//! or `if (x) if (y) …` chain, is measured on the OXC AST by [`DepthGuard`], which does
+//! not descend below the limit. The links of `a + b + …`, `a.b.c…` or `else if` chains
+//! take less stack than a level of nesting, so they count for a fraction of one.
+//! OXC parses operator chains in a loop, but it does recurse on unbracketed
+//! statements, so those are bounded by the caller's stack.
+//!
+//! Input at the limit still needs far more stack than the 2 MiB threads that Rust
+//! spawns by default, so [`with_stack_for`] runs a closure on a thread sized for the
+//! limit. `parse_js`, the `minify`, `analyze_source` and `beautify_source` entry points,
+//! the language server and the CLI all run there; callers of `parse_js_in`, which
+//! borrows the caller's arena, provide the stack themselves.
+
+use oxc_ast::ast::{
+    BinaryExpression, CallExpression, ComputedMemberExpression, Expression, IfStatement, LogicalExpression,
+    PrivateFieldExpression, Statement, StaticMemberExpression,
+};
+use oxc_ast::visit::walk;
+use oxc_ast::Visit;
+use oxc_span::{GetSpan, Span};
+
+/// Default for [`ParserConfig::max_depth`](crate::parser::ParserConfig)
+pub const DEFAULT_MAX_DEPTH: usize = 500;
+
+/// Stack reserved per level of nesting; a level takes up to about 25 KiB in unoptimized
+/// builds, across the parser, analyzer, transformer and printer
+pub const STACK_PER_NESTING_LEVEL: usize = 64 * 1024;
+
+/// Largest `--max-depth` the CLI accepts, whose stack takes 625 MiB
+pub const MAX_DEPTH_LIMIT: usize = 10_000;
+
+/// Smallest stack the compiler runs on, the usual size of a main thread
+pub const MIN_STACK_SIZE: usize = 8 * 1024 * 1024;
+
+/// Stack size that fits input nested `max_depth` levels deep through every phase
+pub fn stack_size_for(max_depth: usize) -> usize {
+    max_depth.saturating_mul(STACK_PER_NESTING_LEVEL).max(MIN_STACK_SIZE)
+}
+
+/// Runs `f` on a thread whose stack fits [`stack_size_for`] `max_depth`, and returns
+/// its result; a panic in `f` is resumed on the calling thread
+///
+/// # Errors
+///
+/// Returns the error of [`std::thread::Builder::spawn_scoped`] if the thread cannot be
+/// spawned, as when the system has no room for its stack.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn with_stack_for<T: Send>(max_depth: usize, f: impl FnOnce() -> T + Send) -> std::io::Result<T> {
+    std::thread::scope(|scope| {
+        let thread = std::thread::Builder::new()
+            .name("rjs-pipeline".to_string())
+            .stack_size(stack_size_for(max_depth))
+            .spawn_scoped(scope, f)?;
+        Ok(thread.join().unwrap_or_else(|panic| std::panic::resume_unwind(panic)))
+    })
+}
+
+/// Runs `f` on the current thread: wasm32 has no threads, and its stack is set when the
+/// module is linked
+#[cfg(target_arch = "wasm32")]
+pub fn with_stack_for<T: Send>(_max_depth: usize, f: impl FnOnce() -> T + Send) -> std::io::Result<T> {
+    Ok(f())
+}
+
+/// Words after which a `/` starts a regular expression rather than a division
+const REGEX_KEYWORDS: &[&str] = &[
+    "return", "typeof", "instanceof", "in", "of", "new", "delete", "void", "throw", "case", "do", "else",
+    "yield", "await",
+];
+
+/// Offset of the first bracket in `source` that opens deeper than `limit`, if any
+///
+/// Strings, comments, regular expressions and template text are skipped. Regular
+/// expressions are told from divisions by the token before them, which is enough to
+/// keep the count honest on real code; a miscounted bracket only shifts the depth by one.
+pub fn find_deep_bracket(source: &str, limit: usize) -> Option<usize> {
+    let bytes = source.as_bytes();
+    // Open brackets, with `$` for a template substitution
+    let mut open: Vec<u8> = Vec::new();
+    // Whether a `/` here would start a regular expression
+    let mut regex_allowed = true;
+    let mut i = 0;
+
+    while i < bytes.len() {
+        let byte = bytes[i];
+        match byte {
+            b'(' | b'[' | b'{' => {
+                open.push(byte);
+                if open.len() > limit {
+                    return Some(i);
+                }
+                regex_allowed = true;
+                i += 1;
+            }
+            b'}' if open.last() == Some(&b'$') => {
+                open.pop();
+                i = skip_template(bytes, i + 1, &mut open);
+                if open.len() > limit {
+                    return Some(i - 2);
+                }
+                regex_allowed = false;
+            }
+            b')' | b']' | b'}' => {
+                open.pop();
+                // `}` ends a block more often than an object literal
+                regex_allowed = byte == b'}';
+                i += 1;
+            }
+            b'\'' | b'"' => {
+                i = skip_string(bytes, i + 1, byte);
+                regex_allowed = false;
+            }
+            b'`' => {
+                i = skip_template(bytes, i + 1, &mut open);
+                if open.len() > limit {
+                    return Some(i - 2);
+                }
+                regex_allowed = false;
+            }
+            b'/' if bytes.get(i + 1) == Some(&b'/') => {
+                i = bytes[i..].iter().position(|&b| b == b'\n').map_or(bytes.len(), |end| i + end);
+            }
+            b'/' if bytes.get(i + 1) == Some(&b'*') => {
+                i = source[i + 2..].find("*/").map_or(bytes.len(), |end| i + 2 + end + 2);
+            }
+            b'/' if regex_allowed => {
+                i = skip_regex(bytes, i + 1);
+                regex_allowed = false;
+            }
+            _ if byte.is_ascii_alphanumeric() || byte == b'_' || byte == b'$' || byte >= 0x80 => {
+                let start = i;
+                while i < bytes.len() && (bytes[i].is_ascii_alphanumeric() || matches!(bytes[i], b'_' | b'$') || bytes[i] >= 0x80) {
+                    i += 1;
+                }
+                regex_allowed = REGEX_KEYWORDS.contains(&&source[start..i]);
+            }
+            _ if byte.is_ascii_whitespace() => i += 1,
+            _ => {
+                // Punctuation other than a closing bracket leaves room for an operand
+                regex_allowed = true;
+                i += 1;
+            }
+        }
+    }
+    None
+}
+
+/// Offset after the string literal whose body starts at `i`
+fn skip_string(bytes: &[u8], mut i: usize, quote: u8) -> usize {
+    while i < bytes.len() {
+        match bytes[i] {
+            b'\\' => i += 2,
+            b'\n' => return i,
+            byte if byte == quote => return i + 1,
+            _ => i += 1,
+        }
+    }
+    bytes.len()
+}
+
+/// Offset after the template text starting at `i`: past the closing backtick, or past
+/// a `${`, which is pushed onto `open`
+fn skip_template(bytes: &[u8], mut i: usize, open: &mut Vec<u8>) -> usize {
+    while i < bytes.len() {
+        match bytes[i] {
+            b'\\' => i += 2,
+            b'`' => return i + 1,
+            b'$' if bytes.get(i + 1) == Some(&b'{') => {
+                open.push(b'$');
+                return i + 2;
+            }
+            _ => i += 1,
+        }
+    }
+    bytes.len()
+}
+
+/// Offset after the regular expression whose body starts at `i`
+fn skip_regex(bytes: &[u8], mut i: usize) -> usize {
+    let mut in_class = false;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'\\' => i += 2,
+            b'\n' => return i,
+            b'[' => {
+                in_class = true;
+                i += 1;
+            }
+            b']' => {
+                in_class = false;
+                i += 1;
+            }
+            b'/' if !in_class => return i + 1,
+            _ => i += 1,
+        }
+    }
+    bytes.len()
+}
+
+/// Finds the first statement or expression nested deeper than the limit in an OXC AST
+///
+/// Only the nesting the lexical scan cannot see is left by the time this runs, and the
+/// visitor never recurses below the limit, so it is safe on any tree OXC produced.
+///
+/// Chains count for part of a level each link, by the stack the phases were measured
+/// to use on them: an `else if` for half a level, and the left operand of a binary or
+/// logical expression, the object of a member expression or the callee of a call for a
+/// quarter. So a 500 level limit lets through an `else if` chain of about 1000
+/// branches, or an `a + b + …` of about 2000 terms.
+pub struct DepthGuard {
+    /// The limit, in quarter levels
+    limit:        usize,
+    /// The depth so far, in quarter levels
+    depth:        usize,
+    /// The first node found deeper than the limit
+    pub too_deep: Option<Span>,
+}
+
+impl DepthGuard {
+    /// Cost of a level of nesting
+    const LEVEL: usize = 4;
+    /// Cost of an `else if`
+    const ELSE_IF: usize = 2;
+    /// Cost of the next link of an operator, member or call chain
+    const LINK: usize = 1;
+
+    pub fn new(limit: usize) -> Self {
+        Self { limit: limit.saturating_mul(Self::LEVEL), depth: 0, too_deep: None }
+    }
+
+    fn descend(&mut self, span: Span, cost: usize, walk: impl FnOnce(&mut Self)) {
+        if self.too_deep.is_some() {
+            return;
+        }
+        if self.depth + cost > self.limit {
+            self.too_deep = Some(span);
+            return;
+        }
+        self.depth += cost;
+        walk(self);
+        self.depth -= cost;
+    }
+
+    /// Visits `it` as the next link of a chain
+    fn visit_link(&mut self, it: &Expression<'_>) {
+        self.descend(it.span(), Self::LINK, |guard| walk::walk_expression(guard, it));
+    }
+}
+
+impl<'a> Visit<'a> for DepthGuard {
+    fn visit_statement(&mut self, it: &Statement<'a>) {
+        self.descend(it.span(), Self::LEVEL, |guard| walk::walk_statement(guard, it));
+    }
+
+    fn visit_expression(&mut self, it: &Expression<'a>) {
+        self.descend(it.span(), Self::LEVEL, |guard| walk::walk_expression(guard, it));
+    }
+
+    fn visit_binary_expression(&mut self, it: &BinaryExpression<'a>) {
+        self.visit_link(&it.left);
+        self.visit_expression(&it.right);
+    }
+
+    fn visit_logical_expression(&mut self, it: &LogicalExpression<'a>) {
+        self.visit_link(&it.left);
+        self.visit_expression(&it.right);
+    }
+
+    fn visit_static_member_expression(&mut self, it: &StaticMemberExpression<'a>) {
+        self.visit_link(&it.object);
+    }
+
+    fn visit_computed_member_expression(&mut self, it: &ComputedMemberExpression<'a>) {
+        self.visit_link(&it.object);
+        self.visit_expression(&it.expression);
+    }
+
+    fn visit_private_field_expression(&mut self, it: &PrivateFieldExpression<'a>) {
+        self.visit_link(&it.object);
+    }
+
+    fn visit_call_expression(&mut self, it: &CallExpression<'a>) {
+        self.visit_link(&it.callee);
+        self.visit_arguments(&it.arguments);
+    }
+
+    fn visit_if_statement(&mut self, it: &IfStatement<'a>) {
+        self.visit_expression(&it.test);
+        self.visit_statement(&it.consequent);
+        match &it.alternate {
+            Some(alternate @ Statement::IfStatement(_)) => {
+                self.descend(alternate.span(), Self::ELSE_IF, |guard| walk::walk_statement(guard, alternate));
+            }
+            Some(alternate) => self.visit_statement(alternate),
+            None => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bracket_depth_skips_strings_comments_and_regexes() {
+        let source = "f(\"(((\", '[[', /* {{{ */ /[(]/, `(${ g([1]) }(`); // (((";
+        assert_eq!(find_deep_bracket(source, 4), None);
+        assert_eq!(find_deep_bracket(source, 3), Some(source.find("[1]").unwrap()));
+        assert_eq!(find_deep_bracket("return /(/.test(x)", 1), None);
+        assert_eq!(find_deep_bracket("a = b / (c) / (d)", 1), None);
+        assert_eq!(find_deep_bracket(&"(".repeat(10_000), 500), Some(500));
+    }
+
+    #[test]
+    fn test_nesting_at_the_default_limit_fits_on_a_default_thread() {
+        // Just inside the limit, counting the statement and call around the nesting
+        let depth = DEFAULT_MAX_DEPTH - 5;
+        let brackets = format!("let a = {}1{};", "(".repeat(depth), ")".repeat(depth));
+        let statements = format!("{}f();", "if (x) ".repeat(depth));
+        // Test threads have the default 2 MiB stack
+        for source in [brackets, statements] {
+            let output = crate::minify(&source, &crate::MinifyOptions::default());
+            assert!(output.is_ok(), "{:?}", output.err());
+        }
+    }
+
+    #[test]
+    fn test_chains_count_for_part_of_a_level() {
+        let terms = format!("let a = {};", vec!["\"a\""; 1800].join(" + "));
+        let members = format!("let a = o{};", ".b".repeat(1800));
+        let branches = format!("if (a) f();{}", " else if (a) f();".repeat(900));
+        for source in [terms, members, branches] {
+            let output = crate::minify(&source, &crate::MinifyOptions::default());
+            assert!(output.is_ok(), "{:?}", output.err());
+        }
+
+        let terms = format!("let a = {};", vec!["\"a\""; 2100].join(" + "));
+        let branches = format!("if (a) f();{}", " else if (a) f();".repeat(1100));
+        for source in [terms, branches] {
+            let output = crate::minify(&source, &crate::MinifyOptions::default());
+            assert!(output.is_err_and(|error| error.to_string().contains("Nesting deeper than 500 levels")));
+        }
+    }
+}
@@ -0,0 +1,124 @@
+//! # Flow Type Stripping
+//!
+//! Legacy codebases annotated for Flow or the Closure Compiler can be minified without
+//! a separate Babel pass. With [`ParserConfig::flow`](crate::parser::ParserConfig)
+//! the source is parsed with OXC's TypeScript grammar, which accepts the Flow
+//! annotations in common use (`x: ?number`, `function f<T>(a: T): Array<T>`,
+//! `type A = …`, `import type`, `declare function`). The owned AST has no place for
+//! types, so they disappear on conversion, together with type-only imports, exports
+//! and `declare` declarations.
+//!
+//! Flow typecasts (`(x: T)`), exact object types (`{| a: number |}`) and `opaque type`
+//! have no TypeScript counterpart and are not supported: they fail to parse with a
+//! syntax error, never compile to something else. `x as T` and inexact object types
+//! (`{ a: number }`) are accepted.
+//!
+//! [`is_type_comment`] recognizes the comments that only carry types: Flow's comment
+//! syntax (`/*: number */`, `/*:: type A = B; */`), the `@flow` pragma and Closure
+//! `/** @type {…} */` annotations. The compiler drops them from the comments it keeps.
+
+/// Returns whether a comment, delimiters included, only carries type information
+///
+/// ```rust
+/// use rjs_compiler::parser::flow::is_type_comment;
+///
+/// assert!(is_type_comment("/*: ?number */"));
+/// assert!(is_type_comment("/** @type {Array<string>} */"));
+/// assert!(!is_type_comment("/** @type {string} The user's name */"));
+/// ```
+pub fn is_type_comment(text: &str) -> bool {
+    if let Some(line) = text.strip_prefix("//") {
+        return is_flow_pragma(line);
+    }
+    let Some(body) = text.strip_prefix("/*").and_then(|text| text.strip_suffix("*/")) else {
+        return false;
+    };
+    if body.starts_with(':') {
+        return true;
+    }
+    if let Some(doc) = body.strip_prefix('*') {
+        return is_type_annotation(doc) || is_flow_pragma(doc);
+    }
+    is_flow_pragma(body)
+}
+
+/// Whether a comment body is just the `@flow` or `@noflow` pragma (`@flow strict` too)
+fn is_flow_pragma(body: &str) -> bool {
+    let mut words = body.split_whitespace().filter(|word| *word != "*");
+    matches!(words.next(), Some("@flow" | "@noflow")) && words.all(|word| matches!(word, "strict" | "strict-local"))
+}
+
+/// Whether a JSDoc body holds nothing but one `@type {…}` tag
+fn is_type_annotation(doc: &str) -> bool {
+    let text: String = doc
+        .lines()
+        .map(|line| line.trim().trim_start_matches('*').trim())
+        .collect::<Vec<_>>()
+        .join(" ");
+    let Some(rest) = text.trim().strip_prefix("@type") else {
+        return false;
+    };
+    let Some(rest) = rest.trim_start().strip_prefix('{') else {
+        return false;
+    };
+
+    // The type may nest braces (`{{a: number}}`); nothing may follow it
+    let mut depth = 1;
+    for (index, c) in rest.char_indices() {
+        match c {
+            '{' => depth += 1,
+            '}' => {
+                depth -= 1;
+                if depth == 0 {
+                    return rest[index + 1..].trim().is_empty();
+                }
+            }
+            _ => {}
+        }
+    }
+    false
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::{parse_js, ParseError, ParserConfig};
+
+    #[test]
+    fn test_recognizes_type_only_comments() {
+        for comment in [
+            "/*: number */",
+            "/*:: type Point = { x: number }; */",
+            "// @flow",
+            "/* @flow strict */",
+            "/**\n * @noflow\n */",
+            "/** @type {number} */",
+            "/**\n * @type {{ x: number, y: number }}\n */",
+        ] {
+            assert!(is_type_comment(comment), "{}", comment);
+        }
+        for comment in [
+            "// flow control",
+            "/* @flow is great */",
+            "/** Returns the width. @type {number} */",
+            "/** @type {number} The width */",
+            "/** @typedef {Object} Point */",
+            "/*! @license MIT */",
+        ] {
+            assert!(!is_type_comment(comment), "{}", comment);
+        }
+    }
+
+    #[test]
+    fn test_unsupported_flow_syntax_is_rejected() {
+        let config = ParserConfig::default().with_flow(true);
+        assert!(parse_js("type P = { a: number };\nconst n = (x as number);", "flow.js", &config).errors.is_empty());
+
+        // Exact object types and typecasts have no TypeScript counterpart
+        for source in ["type P = {| a: number |};", "const n = (x: number);"] {
+            let result = parse_js(source, "flow.js", &config);
+            assert!(result.ast.is_none(), "{}", source);
+            assert!(matches!(result.errors.as_slice(), [ParseError::SyntaxError { .. }]), "{}: {:?}", source, result.errors);
+        }
+    }
+}
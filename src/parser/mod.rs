@@ -14,22 +14,41 @@
 //! ## Usage
 //!
 //! ```rust
-//! use crate::parser::{parse_js, ParserConfig};
+//! use rjs_compiler::parser::{parse_js, ParserConfig};
 //!
 //! let source = "let x = 5;";
 //! let config = ParserConfig::default();
 //! let result = parse_js(source, "example.js", &config);
 //! ```
 
-use oxc_allocator::Allocator;
-use oxc_ast::ast::Program;
-use oxc_parser::{Parser, ParserReturn};
-use oxc_span::SourceType;
+use std::collections::{HashMap, HashSet};
+
+pub use oxc_allocator::Allocator;
+use oxc_ast::ast::{
+    ArrowFunctionExpression, BindingIdentifier, CatchClause, Class, Declaration, Function, Program, RegExpLiteral, Statement, StringLiteral, SwitchStatement,
+    TemplateElement, VariableDeclaration, VariableDeclarator,
+};
+use oxc_ast::syntax_directed_operations::BoundNames;
+use oxc_ast::visit::walk;
+use oxc_ast::{Trivias, Visit};
+use oxc_parser::Parser;
+use oxc_span::{GetSpan, SourceType};
+use oxc_syntax::scope::ScopeFlags;
 use serde::{Deserialize, Serialize};
 use thiserror::Error;
 
+use crate::interner::Interner;
+use crate::parser::comments::AttachedComments;
+
+pub mod ast_diff;
+pub mod ast_dump;
 pub mod ast_types;
+pub mod comments;
+pub mod depth;
 pub mod error_recovery;
+pub mod flow;
+pub mod proposals;
+pub mod visit;
 
 #[cfg(test)]
 mod tests;
@@ -43,6 +62,26 @@ pub struct ParserConfig {
     pub error_recovery: bool,
     /// Source type (Script, Module, TypeScript, etc.)
     pub source_type: SourceTypeConfig,
+    /// Deepest nesting of brackets, statements or expressions accepted; deeper input is
+    /// rejected with [`ParseError::NestingTooDeep`] instead of overflowing the stack
+    #[serde(default = "default_max_depth")]
+    pub max_depth: usize,
+    /// Whether to accept decorators on classes and class members
+    #[serde(default)]
+    pub decorators: bool,
+    /// Whether to accept `with { … }` attributes on imports and re-exports
+    #[serde(default)]
+    pub import_attributes: bool,
+    /// Whether to accept `using` and `await using` declarations
+    #[serde(default)]
+    pub explicit_resource_management: bool,
+    /// Whether to accept Flow type annotations, which are removed (see [`flow`])
+    #[serde(default)]
+    pub flow: bool,
+}
+
+fn default_max_depth() -> usize {
+    depth::DEFAULT_MAX_DEPTH
 }
 
 /// Source type configuration for parsing
@@ -78,15 +117,27 @@ pub enum ParseError {
     InvalidRegex { message: String },
     #[error("Internal parser error: {message}")]
     InternalError { message: String },
+    #[error("Nesting deeper than {limit} levels at line {line}, column {column}")]
+    NestingTooDeep { limit: usize, line: u32, column: u32 },
+    #[error("Stage-3 proposal not enabled at line {line}, column {column}: {proposal}")]
+    ProposalNotEnabled { proposal: proposals::Proposal, line: u32, column: u32 },
+    #[error("Unsupported syntax at line {line}, column {column}: the compiler cannot represent this code yet, so its output would lose it")]
+    UnsupportedSyntax { line: u32, column: u32 },
 }
 
 /// Source position information
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub struct SourceSpan {
     pub start: u32,
     pub end: u32,
 }
 
+impl From<oxc_span::Span> for SourceSpan {
+    fn from(span: oxc_span::Span) -> Self {
+        Self { start: span.start, end: span.end }
+    }
+}
+
 /// Parse result containing the AST or multiple errors
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ParseResult {
@@ -96,6 +147,29 @@ pub struct ParseResult {
     pub errors: Vec<ParseError>,
     /// Source trivia (comments, whitespace) if preserved
     pub trivia: Option<Trivia>,
+    /// Names of declarations annotated with `/* @__KEEP__ */`
+    pub kept_names: Vec<String>,
+    /// Every binding name of the program, interned in source order; pass it on as
+    /// [`AnalyzerConfig::names`](crate::analyzer::AnalyzerConfig::names) so the symbol
+    /// table shares it
+    #[serde(default)]
+    pub names: Interner,
+    /// Spans of the code the owned AST lacks because it has no representation for it
+    #[serde(default)]
+    pub dropped_spans: Vec<SourceSpan>,
+}
+
+impl ParseResult {
+    /// Error for the first code the owned AST dropped, if it dropped any
+    ///
+    /// Output printed from such an AST silently lacks that code, so everything that
+    /// writes output rejects the input with this error instead.
+    pub fn unsupported_syntax(&self, source: &str) -> Option<ParseError> {
+        self.dropped_spans.iter().min_by_key(|span| span.start).map(|span| {
+            let (line, column) = get_line_column(source, span.start);
+            ParseError::UnsupportedSyntax { line, column }
+        })
+    }
 }
 
 /// Trivia information (comments and whitespace)
@@ -111,6 +185,29 @@ pub struct Trivia {
     pub trailing_whitespace: Vec<Whitespace>,
 }
 
+impl Trivia {
+    /// Comments leading the node that starts at `start`, in source order
+    pub fn leading_comments(&self, start: u32) -> Vec<&Comment> {
+        self.attached_comments(CommentPosition::Leading, start)
+    }
+
+    /// Comments trailing the node that ends at `end`, in source order
+    pub fn trailing_comments(&self, end: u32) -> Vec<&Comment> {
+        self.attached_comments(CommentPosition::Trailing, end)
+    }
+
+    fn attached_comments(&self, position: CommentPosition, offset: u32) -> Vec<&Comment> {
+        let mut comments: Vec<&Comment> = self
+            .line_comments
+            .iter()
+            .chain(&self.block_comments)
+            .filter(|comment| comment.position == position && comment.attached_to == offset)
+            .collect();
+        comments.sort_by_key(|comment| comment.span.start);
+        comments
+    }
+}
+
 /// Comment information
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Comment {
@@ -120,6 +217,13 @@ pub struct Comment {
     pub span: SourceSpan,
     /// Whether it's a line comment or block comment
     pub kind: CommentKind,
+    /// Whether the comment documents the code after it or the code before it
+    #[serde(default)]
+    pub position: CommentPosition,
+    /// Offset the comment is attached to: the start of the next token for leading
+    /// comments, the end of the previous token for trailing ones
+    #[serde(default)]
+    pub attached_to: u32,
 }
 
 /// Type of comment
@@ -129,6 +233,17 @@ pub enum CommentKind {
     Block,
 }
 
+/// Side of the code a comment belongs to
+///
+/// Comments on their own line lead the next token; comments after code on the same
+/// line trail the previous token.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum CommentPosition {
+    #[default]
+    Leading,
+    Trailing,
+}
+
 /// Whitespace information
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Whitespace {
@@ -144,6 +259,52 @@ impl Default for ParserConfig {
             preserve_trivia: true,
             error_recovery: true,
             source_type: SourceTypeConfig::Module,
+            max_depth: depth::DEFAULT_MAX_DEPTH,
+            decorators: false,
+            import_attributes: false,
+            explicit_resource_management: false,
+            flow: false,
+        }
+    }
+}
+
+impl ParserConfig {
+    /// Sets the deepest nesting accepted before parsing fails
+    pub fn with_max_depth(mut self, max_depth: usize) -> Self {
+        self.max_depth = max_depth;
+        self
+    }
+
+    /// Sets whether decorators are accepted
+    pub fn with_decorators(mut self, decorators: bool) -> Self {
+        self.decorators = decorators;
+        self
+    }
+
+    /// Sets whether import attributes are accepted
+    pub fn with_import_attributes(mut self, import_attributes: bool) -> Self {
+        self.import_attributes = import_attributes;
+        self
+    }
+
+    /// Sets whether `using` declarations are accepted
+    pub fn with_explicit_resource_management(mut self, explicit_resource_management: bool) -> Self {
+        self.explicit_resource_management = explicit_resource_management;
+        self
+    }
+
+    /// Sets whether Flow type annotations are accepted and removed
+    pub fn with_flow(mut self, flow: bool) -> Self {
+        self.flow = flow;
+        self
+    }
+
+    /// Accepts `proposal` in addition to those already enabled
+    pub fn with_proposal(self, proposal: proposals::Proposal) -> Self {
+        match proposal {
+            proposals::Proposal::Decorators => self.with_decorators(true),
+            proposals::Proposal::ImportAttributes => self.with_import_attributes(true),
+            proposals::Proposal::ExplicitResourceManagement => self.with_explicit_resource_management(true),
         }
     }
 }
@@ -151,7 +312,7 @@ impl Default for ParserConfig {
 impl From<SourceTypeConfig> for SourceType {
     fn from(config: SourceTypeConfig) -> Self {
         match config {
-            SourceTypeConfig::Script => SourceType::default(),
+            SourceTypeConfig::Script => SourceType::default().with_script(true),
             SourceTypeConfig::Module => SourceType::default().with_module(true),
             SourceTypeConfig::TypeScript => SourceType::default().with_typescript(true),
         }
@@ -173,7 +334,7 @@ impl From<SourceTypeConfig> for SourceType {
 /// # Examples
 ///
 /// ```rust
-/// use crate::parser::{parse_js, ParserConfig};
+/// use rjs_compiler::parser::{parse_js, ParserConfig};
 ///
 /// let source = "let x = 5;";
 /// let config = ParserConfig::default();
@@ -184,56 +345,433 @@ impl From<SourceTypeConfig> for SourceType {
 /// }
 /// ```
 pub fn parse_js(source: &str, filename: &str, config: &ParserConfig) -> ParseResult {
-    let allocator = Allocator::default();
-    let source_type = SourceType::from(config.source_type.clone());
-    
-    let ret = Parser::new(&allocator, source, source_type).parse();
-    
-    convert_parser_result(ret, source, filename, config)
+    // OXC and the owned conversion recurse on the nesting `max_depth` lets through
+    depth::with_stack_for(config.max_depth, || {
+        let allocator = Allocator::default();
+        parse_js_in(source, filename, &allocator, config).into_parse_result(config)
+    })
+    .unwrap_or_else(|error| ParseResult {
+        ast: None,
+        errors: vec![ParseError::InternalError { message: format!("Failed to start the parser thread: {}", error) }],
+        trivia: None,
+        kept_names: Vec::new(),
+        names: Interner::new(),
+        dropped_spans: Vec::new(),
+    })
 }
 
-/// Converts OXC parser result to our internal ParseResult format
-fn convert_parser_result(
-    ret: ParserReturn<'_>,
-    source: &str,
+/// Parses JavaScript into a caller-owned arena without copying the AST.
+///
+/// The returned [`ArenaParseResult`] borrows the OXC program allocated in
+/// `allocator`, so tools that only inspect the tree (linters, metrics, the
+/// language server) can walk it directly and skip the owned conversion
+/// entirely. Callers processing many files can `reset()` and reuse a single
+/// allocator between them.
+///
+/// # Examples
+///
+/// ```rust
+/// use oxc_allocator::Allocator;
+/// use rjs_compiler::parser::{parse_js_in, ParserConfig};
+///
+/// let allocator = Allocator::default();
+/// let config = ParserConfig::default();
+/// let result = parse_js_in("let x = 5;", "example.js", &allocator, &config);
+///
+/// assert_eq!(result.program.body.len(), 1);
+/// let owned = result.to_owned_ast(); // only when later phases need it
+/// ```
+pub fn parse_js_in<'a>(
+    source: &'a str,
     _filename: &str,
+    allocator: &'a Allocator,
     config: &ParserConfig,
-) -> ParseResult {
-    let mut errors = Vec::new();
-    
-    // Convert OXC errors to our error format
-    for error in ret.errors {
-        let (line, column) = (1, 1); // TODO: Extract from diagnostic
-        
-        let parse_error = ParseError::SyntaxError {
-            message: format!("{:?}", error),
-            line,
-            column,
-            span: None,
+) -> ArenaParseResult<'a> {
+    // Flow annotations are parsed with the TypeScript grammar, see `flow`
+    let source_type = SourceType::from(config.source_type.clone()).with_typescript(config.flow);
+
+    // OXC recurses on brackets, so too deep a nesting has to be caught before it runs
+    if let Some(offset) = depth::find_deep_bracket(source, config.max_depth) {
+        return nesting_too_deep(source, offset as u32, allocator, source_type, config.max_depth);
+    }
+    let ret = Parser::new(allocator, source, source_type).parse();
+
+    // Convert OXC errors to our error format, positioned at their primary label
+    let mut errors: Vec<ParseError> = ret
+        .errors
+        .into_iter()
+        .map(|error| {
+            let span = error.labels.as_ref().and_then(|labels| labels.first()).map(|label| SourceSpan {
+                start: label.offset() as u32,
+                end: (label.offset() + label.len()) as u32,
+            });
+            let (line, column) = span
+                .as_ref()
+                .map_or((1, 1), |span| get_line_column(source, span.start));
+
+            ParseError::SyntaxError {
+                message: error.message.to_string(),
+                line,
+                column,
+                span,
+            }
+        })
+        .collect();
+    let mut guard = depth::DepthGuard::new(config.max_depth);
+    guard.visit_program(&ret.program);
+    if let Some(span) = guard.too_deep {
+        return nesting_too_deep(source, span.start, allocator, source_type, config.max_depth);
+    }
+
+    // OXC parses every stage-3 proposal, so those that are off are rejected here
+    let mut proposal_guard = proposals::ProposalGuard::new(config);
+    if errors.is_empty() && !proposal_guard.allows_all() {
+        proposal_guard.visit_program(&ret.program);
+        if let Some((proposal, span)) = proposal_guard.disabled {
+            let (line, column) = get_line_column(source, span.start);
+            errors.push(ParseError::ProposalNotEnabled { proposal, line, column });
+        }
+    }
+
+    // The owned AST has no `switch` or `try`, so the scope analysis never sees a case
+    // block or a catch parameter
+    if errors.is_empty() {
+        let mut redeclaration_guard = RedeclarationGuard::default();
+        redeclaration_guard.visit_program(&ret.program);
+        if let Some((name, span)) = redeclaration_guard.redeclared {
+            let (line, column) = get_line_column(source, span.start);
+            errors.push(ParseError::SyntaxError {
+                message: format!("Identifier '{}' has already been declared", name),
+                line,
+                column,
+                span: Some(span),
+            });
+        }
+    }
+
+    ArenaParseResult {
+        program: ret.program,
+        errors,
+        source,
+        trivias: ret.trivias,
+    }
+}
+
+/// Parse result borrowing the OXC AST from a caller-owned arena
+pub struct ArenaParseResult<'a> {
+    /// The OXC program, allocated in the arena passed to [`parse_js_in`]
+    pub program: Program<'a>,
+    /// Any errors encountered during parsing
+    pub errors: Vec<ParseError>,
+    /// Source the program was parsed from
+    source: &'a str,
+    /// Comment table recorded by the OXC lexer
+    trivias: Trivias,
+}
+
+impl<'a> ArenaParseResult<'a> {
+    /// Converts the arena AST into the owned representation used by later phases
+    ///
+    /// Returns `None` when parsing produced errors.
+    pub fn to_owned_ast(&self) -> Option<ast_types::Program> {
+        if self.errors.is_empty() {
+            Some(ast_types::Program::from_oxc_in(&self.program, self.source).0)
+        } else {
+            None
+        }
+    }
+
+    /// Spans of the code [`to_owned_ast`](Self::to_owned_ast) drops because the owned AST
+    /// has no representation for it
+    pub fn dropped_spans(&self) -> Vec<SourceSpan> {
+        if self.errors.is_empty() {
+            ast_types::Program::from_oxc_in(&self.program, self.source).1
+        } else {
+            Vec::new()
+        }
+    }
+
+    /// Source spans of the top-level statements kept by [`to_owned_ast`](Self::to_owned_ast)
+    ///
+    /// The spans line up with the owned program's `body`.
+    pub fn statement_spans(&self) -> Vec<SourceSpan> {
+        self.program
+            .body
+            .iter()
+            .filter(|stmt| ast_types::Statement::from_oxc(stmt).is_some())
+            .map(|stmt| SourceSpan::from(stmt.span()))
+            .collect()
+    }
+
+    /// Extracts comments and whitespace from the source
+    pub fn trivia(&self) -> Trivia {
+        extract_trivia(self.source, &self.program, &self.trivias)
+    }
+
+    /// Comments attached to the statements they document, for
+    /// [`Generator::with_comments`](crate::generator::Generator::with_comments)
+    pub fn attached_comments(&self) -> AttachedComments {
+        let mut collector = StatementSpanCollector::default();
+        collector.visit_program(&self.program);
+        AttachedComments::attach(self.source, &self.trivia(), &collector.spans)
+    }
+
+    /// Converts into an owned [`ParseResult`], releasing the borrow on the arena
+    pub fn into_parse_result(self, config: &ParserConfig) -> ParseResult {
+        let (ast, dropped_spans) = if self.errors.is_empty() {
+            let (ast, dropped_spans) = ast_types::Program::from_oxc_in(&self.program, self.source);
+            (Some(ast), dropped_spans)
+        } else {
+            (None, Vec::new())
         };
-        errors.push(parse_error);
+        let trivia = config.preserve_trivia.then(|| self.trivia());
+        let kept_names = self.kept_names();
+        let names = self.binding_names();
+
+        ParseResult {
+            ast,
+            errors: self.errors,
+            trivia,
+            kept_names,
+            names,
+            dropped_spans,
+        }
+    }
+
+    /// Interns the name of every binding the program declares, in source order
+    pub fn binding_names(&self) -> Interner {
+        let mut collector = BindingNameCollector::default();
+        collector.visit_program(&self.program);
+        collector.names
+    }
+
+    /// Names of the declarations annotated with `/* @__KEEP__ */` (or `#__KEEP__`)
+    ///
+    /// The annotation marks the function, class, variable declaration or variable
+    /// declarator directly after it, at any depth, as used even when nothing references
+    /// it, e.g. for code looked up by name at runtime.
+    pub fn kept_names(&self) -> Vec<String> {
+        if !self.source.contains("__KEEP__") {
+            return Vec::new();
+        }
+
+        // The annotation applies to the next token even when OXC classifies the comment
+        // as trailing the code before it on the same line
+        let targets = self
+            .trivias
+            .comments()
+            .filter(|comment| KEEP_ANNOTATIONS.contains(&comment.span.source_text(self.source).trim()))
+            .map(|comment| {
+                let end = comment.real_span_end();
+                let rest = &self.source[end as usize..];
+                end + (rest.len() - rest.trim_start().len()) as u32
+            })
+            .collect();
+
+        let mut collector = KeptNameCollector { targets, names: Vec::new() };
+        collector.visit_program(&self.program);
+        collector.names
+    }
+
+    /// JSDoc blocks (`/** … */`) documenting exported functions and classes, by name
+    ///
+    /// Only the last JSDoc block directly before `export` counts. The text includes
+    /// the delimiters, exactly as written in the source.
+    pub fn doc_comments(&self) -> HashMap<String, String> {
+        let mut doc_comments = HashMap::new();
+        for statement in &self.program.body {
+            let Statement::ExportNamedDeclaration(export) = statement else {
+                continue;
+            };
+            let id = match &export.declaration {
+                Some(Declaration::FunctionDeclaration(function)) => function.id.as_ref(),
+                Some(Declaration::ClassDeclaration(class)) => class.id.as_ref(),
+                _ => None,
+            };
+            let doc = self
+                .trivias
+                .comments()
+                .filter(|comment| comment.is_leading() && comment.attached_to == export.span.start)
+                .filter(|comment| comment.is_jsdoc(self.source))
+                .last();
+            if let (Some(id), Some(doc)) = (id, doc) {
+                doc_comments.insert(id.name.to_string(), doc.real_span().source_text(self.source).to_string());
+            }
+        }
+        doc_comments
     }
-    
-    // Convert AST if parsing was successful
-    let ast = if errors.is_empty() {
-        Some(ast_types::Program::from_oxc(&ret.program))
-    } else {
-        None
-    };
-    
-    // Extract trivia if configured
-    let trivia = if config.preserve_trivia {
-        Some(extract_trivia(source, &ret.program))
-    } else {
-        None
-    };
-    
-    ParseResult { ast, errors, trivia }
+}
+
+/// Comments that mark the next declaration as kept
+const KEEP_ANNOTATIONS: [&str; 2] = ["@__KEEP__", "#__KEEP__"];
+
+/// Collects the names bound by declarations starting at an annotated offset
+struct KeptNameCollector {
+    /// Offsets of the first token after each keep annotation
+    targets: HashSet<u32>,
+    names: Vec<String>,
+}
+
+/// Interns the names of all binding identifiers
+#[derive(Default)]
+struct BindingNameCollector {
+    names: Interner,
+}
+
+impl<'a> Visit<'a> for BindingNameCollector {
+    fn visit_binding_identifier(&mut self, it: &BindingIdentifier<'a>) {
+        self.names.intern(&it.name);
+    }
+}
+
+impl<'a> Visit<'a> for KeptNameCollector {
+    fn visit_variable_declaration(&mut self, it: &VariableDeclaration<'a>) {
+        if self.targets.contains(&it.span.start) {
+            it.bound_names(&mut |id| self.names.push(id.name.to_string()));
+        }
+        walk::walk_variable_declaration(self, it);
+    }
+
+    fn visit_variable_declarator(&mut self, it: &VariableDeclarator<'a>) {
+        if self.targets.contains(&it.span.start) {
+            it.id.bound_names(&mut |id| self.names.push(id.name.to_string()));
+        }
+        walk::walk_variable_declarator(self, it);
+    }
+
+    fn visit_function(&mut self, it: &Function<'a>, flags: ScopeFlags) {
+        if let Some(id) = &it.id
+            && self.targets.contains(&it.span.start)
+        {
+            self.names.push(id.name.to_string());
+        }
+        walk::walk_function(self, it, flags);
+    }
+
+    fn visit_class(&mut self, it: &Class<'a>) {
+        if let Some(id) = &it.id
+            && self.targets.contains(&it.span.start)
+        {
+            self.names.push(id.name.to_string());
+        }
+        walk::walk_class(self, it);
+    }
+}
+
+/// Collects the spans of statements at any depth, each before those nested in it
+#[derive(Default)]
+struct StatementSpanCollector {
+    spans: Vec<SourceSpan>,
+}
+
+impl StatementSpanCollector {
+    fn push(&mut self, span: SourceSpan) {
+        // A declaration statement is visited both as a statement and as a declaration
+        if self.spans.last() != Some(&span) {
+            self.spans.push(span);
+        }
+    }
+}
+
+impl<'a> Visit<'a> for StatementSpanCollector {
+    fn visit_statement(&mut self, it: &Statement<'a>) {
+        self.push(it.span().into());
+        walk::walk_statement(self, it);
+    }
+
+    // Exported declarations are statements of their own in the owned AST
+    fn visit_declaration(&mut self, it: &Declaration<'a>) {
+        self.push(it.span().into());
+        walk::walk_declaration(self, it);
+    }
+}
+
+/// Finds the first name declared twice in the scopes the owned AST drops
+///
+/// - the cases of a `switch`, where one of the declarations is a `let`, `const` or
+///   class; all cases share one block scope
+/// - a `catch` parameter and a `let`, `const`, class or function declared directly in
+///   its block, or a `var` anywhere in the block when the parameter is destructured
+#[derive(Default)]
+struct RedeclarationGuard {
+    redeclared: Option<(String, SourceSpan)>,
+}
+
+impl<'a> Visit<'a> for RedeclarationGuard {
+    fn visit_switch_statement(&mut self, it: &SwitchStatement<'a>) {
+        let mut lexical = HashSet::new();
+        let mut hoisted = HashSet::new();
+        let declarations = it.cases.iter().flat_map(|case| &case.consequent).filter_map(Statement::as_declaration);
+        for declaration in declarations {
+            let is_lexical = match declaration {
+                Declaration::VariableDeclaration(variables) => variables.kind.is_lexical(),
+                Declaration::ClassDeclaration(_) => true,
+                _ => false,
+            };
+            declaration.bound_names(&mut |id| {
+                let name = id.name.as_str();
+                let conflicts = lexical.contains(name) || (is_lexical && hoisted.contains(name));
+                if conflicts && self.redeclared.is_none() {
+                    self.redeclared = Some((name.to_string(), id.span.into()));
+                }
+                if is_lexical { &mut lexical } else { &mut hoisted }.insert(name.to_string());
+            });
+        }
+        if self.redeclared.is_none() {
+            walk::walk_switch_statement(self, it);
+        }
+    }
+
+    fn visit_catch_clause(&mut self, it: &CatchClause<'a>) {
+        if let Some(param) = &it.param {
+            let mut params = HashSet::new();
+            param.pattern.bound_names(&mut |id| {
+                params.insert(id.name.as_str());
+            });
+            let declarations = it.body.body.iter().filter_map(Statement::as_declaration);
+            let lexical = declarations
+                .filter(|declaration| !matches!(declaration, Declaration::VariableDeclaration(variables) if variables.kind.is_var()));
+            for declaration in lexical {
+                declaration.bound_names(&mut |id| {
+                    if params.contains(id.name.as_str()) && self.redeclared.is_none() {
+                        self.redeclared = Some((id.name.to_string(), id.span.into()));
+                    }
+                });
+            }
+            // Annex B allows `var e` in `catch (e)`, but not in a destructured catch
+            if !param.pattern.kind.is_binding_identifier() && self.redeclared.is_none() {
+                let mut vars = VarNames::default();
+                vars.visit_block_statement(&it.body);
+                self.redeclared = vars.names.into_iter().find(|(name, _)| params.contains(name.as_str()));
+            }
+        }
+        if self.redeclared.is_none() {
+            walk::walk_catch_clause(self, it);
+        }
+    }
+}
+
+/// Names a `var` declares in a block, without entering nested functions
+#[derive(Default)]
+struct VarNames {
+    names: Vec<(String, SourceSpan)>,
+}
+
+impl<'a> Visit<'a> for VarNames {
+    fn visit_variable_declaration(&mut self, it: &VariableDeclaration<'a>) {
+        if it.kind.is_var() {
+            it.bound_names(&mut |id| self.names.push((id.name.to_string(), id.span.into())));
+        }
+        walk::walk_variable_declaration(self, it);
+    }
+
+    fn visit_function(&mut self, _it: &Function<'a>, _flags: ScopeFlags) {}
+
+    fn visit_arrow_function_expression(&mut self, _it: &ArrowFunctionExpression<'a>) {}
 }
 
 /// Calculates line and column numbers from a byte offset
-#[allow(dead_code)]
-fn get_line_column(source: &str, offset: u32) -> (u32, u32) {
+pub(crate) fn get_line_column(source: &str, offset: u32) -> (u32, u32) {
     let mut line = 1;
     let mut column = 1;
     
@@ -253,132 +791,44 @@ fn get_line_column(source: &str, offset: u32) -> (u32, u32) {
     (line, column)
 }
 
-/// Extracts trivia information from the source code
-fn extract_trivia(source: &str, _program: &Program) -> Trivia {
+/// Builds [`Trivia`] from OXC's comment table
+///
+/// Comments come straight from the lexer, so comment-like text inside strings,
+/// templates and regular expressions is never mistaken for a comment. Whitespace is
+/// collected in one pass over the source bytes, skipping comments and those literals.
+fn extract_trivia(source: &str, program: &Program, trivias: &Trivias) -> Trivia {
+    let comments: Vec<&oxc_ast::Comment> = trivias.comments().collect();
     let mut line_comments = Vec::new();
     let mut block_comments = Vec::new();
-    let mut leading_whitespace = Vec::new();
-    let mut trailing_whitespace = Vec::new();
-    
-    let mut pos = 0;
-    let chars: Vec<char> = source.chars().collect();
-    let mut in_string = false;
-    let mut string_delimiter = '\0';
-    let mut escaped = false;
-    
-    while pos < chars.len() {
-        let ch = chars[pos];
-        
-        // Handle string context
-        if in_string {
-            if escaped {
-                escaped = false;
-            } else if ch == '\\' {
-                escaped = true;
-            } else if ch == string_delimiter {
-                in_string = false;
-                string_delimiter = '\0';
-            }
-            pos += 1;
-            continue;
-        }
-        
-        match ch {
-            // Start of string
-            '"' | '\'' | '`' => {
-                in_string = true;
-                string_delimiter = ch;
-                pos += 1;
-            }
-            // Handle line comments (only when not in string)
-            '/' if pos + 1 < chars.len() && chars[pos + 1] == '/' => {
-                let start = pos;
-                pos += 2; // Skip '//'
-                
-                // Extract comment text until end of line
-                let mut comment_text = String::new();
-                while pos < chars.len() && chars[pos] != '\n' {
-                    comment_text.push(chars[pos]);
-                    pos += 1;
-                }
-                
-                line_comments.push(Comment {
-                    text: comment_text.trim().to_string(),
-                    span: SourceSpan {
-                        start: start as u32,
-                        end: pos as u32,
-                    },
-                    kind: CommentKind::Line,
-                });
-                
-                if pos < chars.len() {
-                    pos += 1; // Skip newline
-                }
-            }
-            // Handle block comments (only when not in string)
-            '/' if pos + 1 < chars.len() && chars[pos + 1] == '*' => {
-                let start = pos;
-                pos += 2; // Skip '/*'
-                
-                let mut comment_text = String::new();
-                let mut found_end = false;
-                
-                while pos + 1 < chars.len() {
-                    if chars[pos] == '*' && chars[pos + 1] == '/' {
-                        pos += 2; // Skip '*/'
-                        found_end = true;
-                        break;
-                    }
-                    comment_text.push(chars[pos]);
-                    pos += 1;
-                }
-                
-                if found_end {
-                    block_comments.push(Comment {
-                        text: comment_text.trim().to_string(),
-                        span: SourceSpan {
-                            start: start as u32,
-                            end: pos as u32,
-                        },
-                        kind: CommentKind::Block,
-                    });
-                }
-            }
-            // Handle whitespace
-            ' ' | '\t' | '\r' | '\n' => {
-                let start = pos;
-                let mut whitespace_text = String::new();
-                
-                // Collect consecutive whitespace
-                while pos < chars.len() && matches!(chars[pos], ' ' | '\t' | '\r' | '\n') {
-                    whitespace_text.push(chars[pos]);
-                    pos += 1;
-                }
-                
-                // Determine if it's leading or trailing based on context
-                // For simplicity, consider whitespace at start of line as leading
-                let is_leading = whitespace_text.contains('\n') || start == 0;
-                
-                let whitespace = Whitespace {
-                    text: whitespace_text,
-                    span: SourceSpan {
-                        start: start as u32,
-                        end: pos as u32,
-                    },
-                };
-                
-                if is_leading {
-                    leading_whitespace.push(whitespace);
-                } else {
-                    trailing_whitespace.push(whitespace);
-                }
-            }
-            _ => {
-                pos += 1;
-            }
+
+    for (index, comment) in comments.iter().enumerate() {
+        let (position, attached_to) = if comment.is_trailing() {
+            (CommentPosition::Trailing, preceding_token_end(source, &comments[..index], comment.real_span_start()))
+        } else {
+            (CommentPosition::Leading, comment.attached_to)
+        };
+        let converted = Comment {
+            text: comment.span.source_text(source).trim().to_string(),
+            span: SourceSpan { start: comment.real_span_start(), end: comment.real_span_end() },
+            kind: if comment.is_line() { CommentKind::Line } else { CommentKind::Block },
+            position,
+            attached_to,
+        };
+        match converted.kind {
+            CommentKind::Line => line_comments.push(converted),
+            CommentKind::Block => block_comments.push(converted),
         }
     }
-    
+
+    // Spans whose whitespace belongs to a token rather than to the trivia, in source order
+    let mut opaque = LiteralSpanCollector::default();
+    opaque.visit_program(program);
+    let mut opaque_spans = opaque.spans;
+    opaque_spans.extend(comments.iter().map(|comment| (comment.real_span_start(), comment.real_span_end())));
+    opaque_spans.sort_unstable();
+
+    let (leading_whitespace, trailing_whitespace) = extract_whitespace(source, &opaque_spans);
+
     Trivia {
         line_comments,
         block_comments,
@@ -387,6 +837,104 @@ fn extract_trivia(source: &str, _program: &Program) -> Trivia {
     }
 }
 
+/// Finds where the token before a trailing comment ends, skipping earlier comments
+fn preceding_token_end(source: &str, earlier: &[&oxc_ast::Comment], mut end: u32) -> u32 {
+    loop {
+        end = source[..end as usize].trim_end().len() as u32;
+        match earlier.iter().rev().find(|comment| comment.real_span_end() == end) {
+            Some(comment) => end = comment.real_span_start(),
+            None => return end,
+        }
+    }
+}
+
+/// Collects whitespace runs outside `opaque_spans`
+///
+/// Runs containing a newline (or starting the file) are leading whitespace, the
+/// others trailing.
+fn extract_whitespace(source: &str, opaque_spans: &[(u32, u32)]) -> (Vec<Whitespace>, Vec<Whitespace>) {
+    let bytes = source.as_bytes();
+    let mut leading = Vec::new();
+    let mut trailing = Vec::new();
+    let mut spans = opaque_spans.iter().peekable();
+    let mut pos = 0;
+
+    while pos < bytes.len() {
+        // Jump over comments and literals
+        while spans.peek().is_some_and(|&&(_, end)| end as usize <= pos) {
+            spans.next();
+        }
+        if let Some(&&(start, end)) = spans.peek()
+            && start as usize <= pos
+        {
+            pos = end as usize;
+            continue;
+        }
+
+        if !matches!(bytes[pos], b' ' | b'\t' | b'\r' | b'\n') {
+            pos += 1;
+            continue;
+        }
+
+        let start = pos;
+        while pos < bytes.len() && matches!(bytes[pos], b' ' | b'\t' | b'\r' | b'\n') {
+            pos += 1;
+        }
+        let text = &source[start..pos];
+        let whitespace = Whitespace {
+            text: text.to_string(),
+            span: SourceSpan { start: start as u32, end: pos as u32 },
+        };
+        if text.contains('\n') || start == 0 {
+            leading.push(whitespace);
+        } else {
+            trailing.push(whitespace);
+        }
+    }
+
+    (leading, trailing)
+}
+
+/// Collects the spans of string, template and regular expression literals
+#[derive(Default)]
+struct LiteralSpanCollector {
+    spans: Vec<(u32, u32)>,
+}
+
+impl<'a> Visit<'a> for LiteralSpanCollector {
+    fn visit_string_literal(&mut self, it: &StringLiteral<'a>) {
+        self.spans.push((it.span.start, it.span.end));
+    }
+
+    fn visit_template_element(&mut self, it: &TemplateElement<'a>) {
+        self.spans.push((it.span.start, it.span.end));
+    }
+
+    fn visit_reg_exp_literal(&mut self, it: &RegExpLiteral<'a>) {
+        self.spans.push((it.span.start, it.span.end));
+    }
+}
+
+/// The result for a source nesting deeper than `limit` at `offset`
+///
+/// The program is left empty so that nothing walks the deep tree afterwards.
+fn nesting_too_deep<'a>(
+    source: &'a str,
+    offset: u32,
+    allocator: &'a Allocator,
+    source_type: SourceType,
+    limit: usize,
+) -> ArenaParseResult<'a> {
+    let (line, column) = get_line_column(source, offset);
+    let ret = Parser::new(allocator, "", source_type).parse();
+    ArenaParseResult {
+        program: ret.program,
+        errors: vec![ParseError::NestingTooDeep { limit, line, column }],
+        source,
+        trivias: ret.trivias,
+    }
+}
+
 /// Helper function to create a simple syntax error
 #[allow(dead_code)]
 pub fn create_syntax_error(message: &str, line: u32, column: u32) -> ParseError {
@@ -0,0 +1,139 @@
+//! # Stage-3 Proposals
+//!
+//! OXC always parses decorators, import attributes (`with { type: "json" }`) and
+//! explicit resource management (`using` declarations); it has no option to turn them
+//! off. Not every engine runs them yet, so the parser only accepts them when the
+//! matching [`ParserConfig`](crate::parser::ParserConfig) switch is on.
+//! [`ProposalGuard`] walks the OXC AST after parsing and reports the first use of a
+//! proposal that is switched off, as
+//! [`ParseError::ProposalNotEnabled`](crate::parser::ParseError).
+//!
+//! Enabled proposals are carried through the owned AST and printed back as written.
+
+use std::fmt;
+use std::str::FromStr;
+
+use oxc_ast::ast::{Decorator, VariableDeclaration, VariableDeclarationKind, WithClause};
+use oxc_ast::visit::walk;
+use oxc_ast::Visit;
+use oxc_span::Span;
+use serde::{Deserialize, Serialize};
+
+use crate::parser::ParserConfig;
+
+/// A stage-3 proposal the parser can accept
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Proposal {
+    /// `@decorator` on classes and class members
+    Decorators,
+    /// `with { … }` after the module specifier of an import or re-export
+    ImportAttributes,
+    /// `using` and `await using` declarations
+    ExplicitResourceManagement,
+}
+
+impl fmt::Display for Proposal {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Decorators => "decorators",
+            Self::ImportAttributes => "import attributes",
+            Self::ExplicitResourceManagement => "`using` declarations",
+        })
+    }
+}
+
+impl FromStr for Proposal {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "decorators" => Ok(Self::Decorators),
+            "import-attributes" => Ok(Self::ImportAttributes),
+            "using" | "explicit-resource-management" => Ok(Self::ExplicitResourceManagement),
+            _ => Err(format!("unknown proposal '{}' (expected decorators, import-attributes or using)", value)),
+        }
+    }
+}
+
+/// Finds the first use of a proposal the configuration does not enable
+pub struct ProposalGuard {
+    decorators:                   bool,
+    import_attributes:            bool,
+    explicit_resource_management: bool,
+    /// The first disabled proposal found, and where
+    pub disabled:                 Option<(Proposal, Span)>,
+}
+
+impl ProposalGuard {
+    pub fn new(config: &ParserConfig) -> Self {
+        Self {
+            decorators: config.decorators,
+            import_attributes: config.import_attributes,
+            explicit_resource_management: config.explicit_resource_management,
+            disabled: None,
+        }
+    }
+
+    /// Whether every proposal is enabled, so there is nothing to look for
+    pub fn allows_all(&self) -> bool {
+        self.decorators && self.import_attributes && self.explicit_resource_management
+    }
+
+    fn report(&mut self, proposal: Proposal, span: Span) {
+        // Nodes are visited in source order, so the first report is the earliest use
+        if self.disabled.is_none() {
+            self.disabled = Some((proposal, span));
+        }
+    }
+}
+
+impl<'a> Visit<'a> for ProposalGuard {
+    fn visit_decorator(&mut self, it: &Decorator<'a>) {
+        if !self.decorators {
+            self.report(Proposal::Decorators, it.span);
+        }
+        walk::walk_decorator(self, it);
+    }
+
+    fn visit_with_clause(&mut self, it: &WithClause<'a>) {
+        if !self.import_attributes {
+            self.report(Proposal::ImportAttributes, it.span);
+        }
+        walk::walk_with_clause(self, it);
+    }
+
+    fn visit_variable_declaration(&mut self, it: &VariableDeclaration<'a>) {
+        let is_using = matches!(it.kind, VariableDeclarationKind::Using | VariableDeclarationKind::AwaitUsing);
+        if !self.explicit_resource_management && is_using {
+            self.report(Proposal::ExplicitResourceManagement, it.span);
+        }
+        walk::walk_variable_declaration(self, it);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use oxc_allocator::Allocator;
+    use oxc_parser::Parser;
+    use oxc_span::SourceType;
+
+    fn disabled(source: &str, config: &ParserConfig) -> Option<Proposal> {
+        let allocator = Allocator::default();
+        let ret = Parser::new(&allocator, source, SourceType::default().with_module(true)).parse();
+        let mut guard = ProposalGuard::new(config);
+        guard.visit_program(&ret.program);
+        guard.disabled.map(|(proposal, _)| proposal)
+    }
+
+    #[test]
+    fn test_reports_the_first_disabled_proposal() {
+        let source = "import data from './a.json' with { type: 'json' };\n@sealed class A {}\n{ using r = open(); }";
+        let config = ParserConfig::default();
+        assert_eq!(disabled(source, &config), Some(Proposal::ImportAttributes));
+        assert_eq!(disabled(source, &config.clone().with_import_attributes(true)), Some(Proposal::Decorators));
+        let config = config.with_import_attributes(true).with_decorators(true);
+        assert_eq!(disabled(source, &config), Some(Proposal::ExplicitResourceManagement));
+        assert_eq!(disabled(source, &config.with_explicit_resource_management(true)), None);
+    }
+}
@@ -7,7 +7,7 @@
 mod tests {
     use crate::parser::{parse_js, ParserConfig};
     use crate::parser::ast_types::*;
-    use crate::parser::{CommentKind};
+    use crate::parser::{CommentKind, CommentPosition};
 
     /// Helper function to create default parser config
     fn default_config() -> ParserConfig {
@@ -50,7 +50,7 @@ mod tests {
             assert_eq!(ast.body.len(), 1);
             
             match &ast.body[0] {
-                Statement::VariableDeclaration { declarations, kind } => {
+                Statement::VariableDeclaration { declarations, kind, .. } => {
                     assert!(matches!(kind, VariableDeclarationKind::Let));
                     assert_eq!(declarations.len(), 1);
                     
@@ -101,7 +101,7 @@ mod tests {
                     // Check function body
                     assert_eq!(body.body.len(), 1);
                     match &body.body[0] {
-                        Statement::ReturnStatement { argument } => {
+                        Statement::ReturnStatement { argument, .. } => {
                             assert!(argument.is_some());
                             match argument.as_ref().unwrap() {
                                 Expression::BinaryExpression { left, operator, right } => {
@@ -174,7 +174,7 @@ mod tests {
             assert_eq!(ast.body.len(), 1);
             
             match &ast.body[0] {
-                Statement::VariableDeclaration { declarations, kind } => {
+                Statement::VariableDeclaration { declarations, kind, .. } => {
                     assert!(matches!(kind, VariableDeclarationKind::Const));
                     assert_eq!(declarations.len(), 1);
                     
@@ -387,7 +387,7 @@ mod tests {
                     
                     // First should be return statement with no argument
                     match &body.body[0] {
-                        Statement::ReturnStatement { argument } => {
+                        Statement::ReturnStatement { argument, .. } => {
                             assert!(argument.is_none());
                         }
                         _ => panic!("Expected return statement"),
@@ -395,7 +395,7 @@ mod tests {
                     
                     // Second should be expression statement with literal 5
                     match &body.body[1] {
-                        Statement::ExpressionStatement { expression } => {
+                        Statement::ExpressionStatement { expression, .. } => {
                             match expression {
                                 Expression::Literal(Literal::Number(num)) => {
                                     assert_eq!(num.value, 5.0);
@@ -459,6 +459,90 @@ mod tests {
                 _ => panic!("Expected variable declaration"),
             }
         }
+
+        #[test]
+        fn test_generators_and_for_await() {
+            let source = "async function* f(xs) { for await (const x of xs) { yield* g(yield x); } }";
+            let ast = assert_parse_success(source, "test.js");
+
+            let Statement::FunctionDeclaration { body, is_async: true, is_generator: true, .. } = &ast.body[0] else {
+                panic!("Expected async generator declaration");
+            };
+            let Statement::ForOfStatement { left, right, body, is_await: true, .. } = &body.body[0] else {
+                panic!("Expected for await loop");
+            };
+            assert!(matches!(left, ForInit::VariableDeclaration { kind: VariableDeclarationKind::Const, .. }));
+            assert!(!right.may_suspend());
+            let Statement::BlockStatement { body, .. } = body.as_ref() else {
+                panic!("Expected loop block");
+            };
+            let Statement::ExpressionStatement { expression, .. } = &body[0] else {
+                panic!("Expected expression statement");
+            };
+            assert!(matches!(expression, Expression::YieldExpression { delegate: true, argument: Some(_) }));
+            assert!(expression.may_suspend());
+        }
+
+        #[test]
+        fn test_spread_and_rest() {
+            let source = "const [a, , ...b] = f(...xs, 1), { c, ...d } = o;\nfunction g(x = 1, ...rest) {}\nh([...a, , ], { ...d, e });\n";
+            let ast = assert_parse_success(source, "test.js");
+
+            let Statement::VariableDeclaration { declarations, .. } = &ast.body[0] else {
+                panic!("Expected variable declaration");
+            };
+            let Pattern::ArrayPattern { elements } = &declarations[0].id else {
+                panic!("Expected array pattern");
+            };
+            assert!(matches!(elements.as_slice(), [Some(Pattern::Identifier(_)), None, Some(Pattern::RestElement { .. })]));
+            let Some(Expression::CallExpression { arguments, .. }) = &declarations[0].init else {
+                panic!("Expected call");
+            };
+            assert!(matches!(arguments.as_slice(), [Expression::SpreadElement { .. }, Expression::Literal(_)]));
+            assert!(matches!(&declarations[1].id, Pattern::ObjectPattern { properties }
+                if matches!(properties.as_slice(), [ObjectPatternProperty::Property { shorthand: true, .. }, ObjectPatternProperty::RestElement { .. }])));
+
+            let Statement::FunctionDeclaration { params, .. } = &ast.body[1] else {
+                panic!("Expected function declaration");
+            };
+            assert!(matches!(params.as_slice(), [Pattern::AssignmentPattern { .. }, Pattern::RestElement { .. }]));
+
+            let Statement::ExpressionStatement { expression: Expression::CallExpression { arguments, .. }, .. } = &ast.body[2] else {
+                panic!("Expected call statement");
+            };
+            assert!(matches!(&arguments[0], Expression::ArrayExpression { elements } if elements.len() == 2));
+            assert!(matches!(&arguments[1], Expression::ObjectExpression { properties }
+                if matches!(properties.as_slice(), [ObjectProperty::SpreadElement { .. }, ObjectProperty::Property { shorthand: true, .. }])));
+        }
+
+        #[test]
+        fn test_tagged_template_keeps_raw_and_cooked_text() {
+            let source = "tag`a\\n${x}\\unicode`;";
+            let ast = assert_parse_success(source, "test.js");
+
+            let Statement::ExpressionStatement {
+                expression: Expression::TaggedTemplateExpression { tag, quasis, expressions },
+                ..
+            } = &ast.body[0]
+            else {
+                panic!("Expected tagged template");
+            };
+            assert!(matches!(tag.as_ref(), Expression::Identifier(id) if id.name == "tag"));
+            assert_eq!(expressions.len(), 1);
+            assert_eq!((quasis[0].value.as_str(), quasis[0].raw.as_deref()), ("a\n", Some("a\\n")));
+            // An invalid escape has no cooked value, only raw text
+            assert_eq!((quasis[1].value.as_str(), quasis[1].raw.as_deref()), ("", Some("\\unicode")));
+        }
+
+        #[test]
+        fn test_json_roundtrip() {
+            let source = "for (i = 0; i < 3; i++) { f(x => 'a', { 1: null, b: /re/g }); }";
+            let ast = assert_parse_success(source, "test.js");
+
+            let json = serde_json::to_string(&ast).unwrap();
+            let parsed: Program = serde_json::from_str(&json).expect("AST JSON should deserialize");
+            assert_eq!(serde_json::to_string(&parsed).unwrap(), json);
+        }
     }
 
     mod invalid_inputs {
@@ -490,6 +574,23 @@ mod tests {
             }
         }
 
+        #[test]
+        fn test_disabled_proposal_is_reported() {
+            use crate::parser::proposals::Proposal;
+            use crate::parser::ParseError;
+
+            let source = "let a = 1;\n@sealed class A {}";
+            let result = parse_js(source, "test.js", &default_config());
+            assert!(result.ast.is_none());
+            assert!(matches!(
+                result.errors.as_slice(),
+                [ParseError::ProposalNotEnabled { proposal: Proposal::Decorators, line: 2, column: 1 }]
+            ));
+
+            let config = default_config().with_proposal(Proposal::Decorators);
+            assert!(parse_js(source, "test.js", &config).errors.is_empty());
+        }
+
         #[test]
         fn test_invalid_identifier() {
             let source = "let 123abc = 5;";
@@ -570,6 +671,26 @@ mod tests {
             // Should handle deep nesting without stack overflow
             assert!(duration.as_secs() < 5, "Deep nesting parsing took too long: {:?}", duration);
         }
+
+        #[test]
+        fn test_nesting_deeper_than_the_limit_is_an_error() {
+            let config = ParserConfig::default();
+            let brackets = format!("let r = {}1{};", "(".repeat(10_000), ")".repeat(10_000));
+            let statements = format!("{}f();", "if (x) ".repeat(2_000));
+            let operators = format!("let s = {};", vec!["x"; 10_000].join(" + "));
+
+            for source in [&brackets, &statements, &operators] {
+                let result = parse_js(source, "deep.js", &config);
+                assert!(result.ast.is_none());
+                assert!(
+                    matches!(result.errors[..], [crate::parser::ParseError::NestingTooDeep { limit: 500, line: 1, .. }]),
+                    "{:?}",
+                    result.errors
+                );
+            }
+            assert!(parse_js("let r = ((1));", "test.js", &config.clone().with_max_depth(4)).errors.is_empty());
+            assert!(!parse_js("let r = ((1));", "test.js", &config.with_max_depth(3)).errors.is_empty());
+        }
     }
 
     mod trivia_tests {
@@ -742,5 +863,98 @@ mod tests {
             // Should not find any comments since it's inside a string
             assert_eq!(trivia.line_comments.len(), 0);
         }
+
+        #[test]
+        fn test_unicode_byte_spans_and_attachment() {
+            let source = "const s = \"héllo /* not a comment */\"; // é trailing\n/* 日本 */\nlet ü = 1;";
+            let config = ParserConfig {
+                preserve_trivia: true,
+                ..ParserConfig::default()
+            };
+            let result = parse_js(source, "test.js", &config);
+
+            assert!(result.errors.is_empty());
+            let trivia = result.trivia.unwrap();
+            assert!(trivia.block_comments.iter().all(|c| c.text != "not a comment"));
+
+            // Spans are byte offsets into the source, including the delimiters
+            let line = &trivia.line_comments[0];
+            assert_eq!(&source[line.span.start as usize..line.span.end as usize], "// é trailing");
+            assert_eq!(line.position, CommentPosition::Trailing);
+            assert_eq!(line.attached_to as usize, source.find(';').unwrap() + 1);
+
+            let block = &trivia.block_comments[0];
+            assert_eq!(&source[block.span.start as usize..block.span.end as usize], "/* 日本 */");
+            assert_eq!(block.text, "日本");
+            let let_start = source.find("let").unwrap() as u32;
+            assert_eq!(trivia.leading_comments(let_start).len(), 1);
+            assert_eq!(trivia.trailing_comments(line.attached_to)[0].text, "é trailing");
+
+            for whitespace in trivia.leading_whitespace.iter().chain(&trivia.trailing_whitespace) {
+                assert_eq!(&source[whitespace.span.start as usize..whitespace.span.end as usize], whitespace.text);
+            }
+        }
+
+        #[test]
+        fn test_keep_annotations() {
+            let source = "/* @__KEEP__ */ function hook() {}\n\
+                function unused() { /* #__KEEP__ */ const [a, b] = [1, 2]; }\n\
+                let c = 1, /* @__KEEP__ */ d = 2;\n\
+                /* @__KEEP__ */\nclass Plugin {}\n\
+                /* @__PURE__ */ foo();";
+            let result = parse_js(source, "test.js", &default_config());
+
+            assert!(result.errors.is_empty());
+            assert_eq!(result.kept_names, vec!["hook", "a", "b", "d", "Plugin"]);
+        }
+    }
+    /// Tests for parsing into a caller-owned arena
+    mod arena_tests {
+        use super::*;
+        use crate::parser::{parse_js_in, Allocator};
+
+        #[test]
+        fn test_arena_parse_matches_owned_parse() {
+            let source = "let x = 5; function add(a, b) { return a + b; }";
+            let config = default_config();
+            let allocator = Allocator::default();
+            let result = parse_js_in(source, "test.js", &allocator, &config);
+
+            assert!(result.errors.is_empty());
+            assert_eq!(result.program.body.len(), 2);
+
+            let owned = result.to_owned_ast().unwrap();
+            let expected = assert_parse_success(source, "test.js");
+            assert_eq!(
+                serde_json::to_value(&owned).unwrap(),
+                serde_json::to_value(&expected).unwrap()
+            );
+        }
+
+        #[test]
+        fn test_arena_reused_across_files() {
+            let config = default_config();
+            let mut allocator = Allocator::default();
+
+            for source in ["let a = 1;", "// note\nlet b = 2; let c = 3;"] {
+                let ast = {
+                    let result = parse_js_in(source, "test.js", &allocator, &config);
+                    assert!(result.errors.is_empty());
+                    result.to_owned_ast()
+                };
+                assert!(ast.is_some());
+                allocator.reset();
+            }
+        }
+
+        #[test]
+        fn test_arena_parse_errors_skip_conversion() {
+            let config = default_config();
+            let allocator = Allocator::default();
+            let result = parse_js_in("let = ;", "test.js", &allocator, &config);
+
+            assert!(!result.errors.is_empty());
+            assert!(result.to_owned_ast().is_none());
+        }
     }
 }
\ No newline at end of file
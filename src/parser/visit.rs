@@ -0,0 +1,295 @@
+//! # Owned AST Traversal
+//!
+//! Depth-first, source-order traversal of the owned AST for passes that rewrite it in
+//! place. A visitor implements [`VisitMut`], receiving enter/exit callbacks for every
+//! statement, expression and function; every callback has a no-op default and may stop
+//! the traversal with an error.
+//!
+//! [`TransformPlugin`](crate::transformer::plugin::TransformPlugin)s run on this
+//! traversal, and so do the rewrites of later phases that have no semantic analysis at
+//! hand, such as the module format conversion of the generator.
+//!
+//! `enter_*` runs before a node's children and `exit_*` after them; replacing the node
+//! in `enter_*` means the replacement's children are visited. Function and class bodies,
+//! default values in patterns and class field initializers are all visited.
+
+use crate::parser::ast_types::{
+    ArrowFunctionBody, ClassBody, ClassElement, Expression, ForInit, FunctionExpression, Identifier,
+    ObjectPatternProperty, ObjectProperty, Pattern, Program, Statement, VariableDeclarator,
+};
+
+/// A rewrite of the owned AST, see the [module documentation](self)
+pub trait VisitMut {
+    /// Error that stops the traversal
+    type Error;
+
+    /// Called before a statement's children are visited
+    fn enter_statement(&mut self, _stmt: &mut Statement) -> Result<(), Self::Error> {
+        Ok(())
+    }
+
+    /// Called after a statement's children are visited
+    fn exit_statement(&mut self, _stmt: &mut Statement) -> Result<(), Self::Error> {
+        Ok(())
+    }
+
+    /// Called before an expression's children are visited
+    fn enter_expression(&mut self, _expr: &mut Expression) -> Result<(), Self::Error> {
+        Ok(())
+    }
+
+    /// Called after an expression's children are visited
+    fn exit_expression(&mut self, _expr: &mut Expression) -> Result<(), Self::Error> {
+        Ok(())
+    }
+
+    /// Called before the parameters and body of a function, method or arrow function
+    /// are visited, with its own name and its body's statements (none for an arrow
+    /// function with an expression body)
+    fn enter_function(&mut self, _id: Option<&Identifier>, _params: &[Pattern], _body: &[Statement]) -> Result<(), Self::Error> {
+        Ok(())
+    }
+
+    /// Called after the parameters and body of a function are visited
+    fn exit_function(&mut self) -> Result<(), Self::Error> {
+        Ok(())
+    }
+}
+
+/// Visits every statement of `program`
+///
+/// # Errors
+///
+/// Returns the first error of the visitor.
+pub fn walk_program<V: VisitMut + ?Sized>(visitor: &mut V, program: &mut Program) -> Result<(), V::Error> {
+    walk_statements(visitor, &mut program.body)
+}
+
+/// Visits `stmt` and everything in it
+///
+/// # Errors
+///
+/// Returns the first error of the visitor.
+pub fn walk_statement<V: VisitMut + ?Sized>(visitor: &mut V, stmt: &mut Statement) -> Result<(), V::Error> {
+    visitor.enter_statement(stmt)?;
+
+    match stmt {
+        Statement::VariableDeclaration { declarations, .. } => walk_declarators(visitor, declarations)?,
+        Statement::FunctionDeclaration { id, params, body, .. } => {
+            visitor.enter_function(id.as_ref(), params, &body.body)?;
+            walk_patterns(visitor, params)?;
+            walk_statements(visitor, &mut body.body)?;
+            visitor.exit_function()?;
+        }
+        Statement::ClassDeclaration { super_class, body, decorators, .. } => {
+            for decorator in decorators {
+                walk_expression(visitor, decorator)?;
+            }
+            if let Some(super_class) = super_class {
+                walk_expression(visitor, super_class)?;
+            }
+            walk_class_body(visitor, body)?;
+        }
+        Statement::ExpressionStatement { expression, .. } => walk_expression(visitor, expression)?,
+        Statement::BlockStatement { body, .. } => walk_statements(visitor, body)?,
+        Statement::ReturnStatement { argument, .. } => {
+            if let Some(argument) = argument {
+                walk_expression(visitor, argument)?;
+            }
+        }
+        Statement::IfStatement { test, consequent, alternate, .. } => {
+            walk_expression(visitor, test)?;
+            walk_statement(visitor, consequent)?;
+            if let Some(alternate) = alternate {
+                walk_statement(visitor, alternate)?;
+            }
+        }
+        Statement::WhileStatement { test, body, .. } => {
+            walk_expression(visitor, test)?;
+            walk_statement(visitor, body)?;
+        }
+        Statement::ForStatement { init, test, update, body, .. } => {
+            match init {
+                Some(ForInit::VariableDeclaration { declarations, .. }) => walk_declarators(visitor, declarations)?,
+                Some(ForInit::Expression(expr)) => walk_expression(visitor, expr)?,
+                None => {}
+            }
+            for expr in [test, update].into_iter().flatten() {
+                walk_expression(visitor, expr)?;
+            }
+            walk_statement(visitor, body)?;
+        }
+        Statement::ForOfStatement { left, right, body, .. } => {
+            match left {
+                ForInit::VariableDeclaration { declarations, .. } => walk_declarators(visitor, declarations)?,
+                ForInit::Expression(expr) => walk_expression(visitor, expr)?,
+            }
+            walk_expression(visitor, right)?;
+            walk_statement(visitor, body)?;
+        }
+        Statement::ImportDeclaration { .. }
+        | Statement::ExportAllDeclaration { .. }
+        | Statement::EmptyStatement
+        | Statement::DebuggerStatement => {}
+        Statement::ExportNamedDeclaration { declaration, .. } => {
+            if let Some(declaration) = declaration {
+                walk_statement(visitor, declaration)?;
+            }
+        }
+        Statement::ExportDefaultDeclaration { declaration, .. } => walk_statement(visitor, declaration)?,
+    }
+
+    visitor.exit_statement(stmt)
+}
+
+/// Visits `expr` and everything in it
+///
+/// # Errors
+///
+/// Returns the first error of the visitor.
+pub fn walk_expression<V: VisitMut + ?Sized>(visitor: &mut V, expr: &mut Expression) -> Result<(), V::Error> {
+    visitor.enter_expression(expr)?;
+
+    match expr {
+        Expression::Identifier(_)
+        | Expression::Literal(_)
+        | Expression::ThisExpression
+        | Expression::MetaProperty { .. } => {}
+        Expression::BinaryExpression { left, right, .. } | Expression::AssignmentExpression { left, right, .. } => {
+            walk_expression(visitor, left)?;
+            walk_expression(visitor, right)?;
+        }
+        Expression::UnaryExpression { argument, .. }
+        | Expression::UpdateExpression { argument, .. }
+        | Expression::AwaitExpression { argument }
+        | Expression::ChainExpression { expression: argument }
+        | Expression::SpreadElement { argument } => {
+            walk_expression(visitor, argument)?;
+        }
+        Expression::YieldExpression { argument, .. } => {
+            if let Some(argument) = argument {
+                walk_expression(visitor, argument)?;
+            }
+        }
+        Expression::CallExpression { callee, arguments, .. } | Expression::NewExpression { callee, arguments } => {
+            walk_expression(visitor, callee)?;
+            walk_expressions(visitor, arguments)?;
+        }
+        Expression::ImportExpression { source, options } => {
+            walk_expression(visitor, source)?;
+            if let Some(options) = options {
+                walk_expression(visitor, options)?;
+            }
+        }
+        Expression::MemberExpression { object, property, .. } => {
+            walk_expression(visitor, object)?;
+            walk_expression(visitor, property)?;
+        }
+        Expression::FunctionExpression(function) => walk_function(visitor, function)?,
+        Expression::ArrowFunctionExpression { params, body, .. } => {
+            match body {
+                ArrowFunctionBody::BlockStatement(block) => visitor.enter_function(None, params, &block.body)?,
+                ArrowFunctionBody::Expression(_) => visitor.enter_function(None, params, &[])?,
+            }
+            walk_patterns(visitor, params)?;
+            match body {
+                ArrowFunctionBody::BlockStatement(block) => walk_statements(visitor, &mut block.body)?,
+                ArrowFunctionBody::Expression(expr) => walk_expression(visitor, expr)?,
+            }
+            visitor.exit_function()?;
+        }
+        Expression::ObjectExpression { properties } => {
+            for property in properties {
+                match property {
+                    ObjectProperty::Property { value, .. } => walk_expression(visitor, value)?,
+                    ObjectProperty::SpreadElement { argument } => walk_expression(visitor, argument)?,
+                }
+            }
+        }
+        Expression::ArrayExpression { elements } => {
+            for element in elements.iter_mut().flatten() {
+                walk_expression(visitor, element)?;
+            }
+        }
+        Expression::TemplateLiteral { expressions, .. } => walk_expressions(visitor, expressions)?,
+        Expression::TaggedTemplateExpression { tag, expressions, .. } => {
+            walk_expression(visitor, tag)?;
+            walk_expressions(visitor, expressions)?;
+        }
+        Expression::ConditionalExpression { test, consequent, alternate } => {
+            walk_expression(visitor, test)?;
+            walk_expression(visitor, consequent)?;
+            walk_expression(visitor, alternate)?;
+        }
+    }
+
+    visitor.exit_expression(expr)
+}
+
+fn walk_statements<V: VisitMut + ?Sized>(visitor: &mut V, stmts: &mut [Statement]) -> Result<(), V::Error> {
+    stmts.iter_mut().try_for_each(|stmt| walk_statement(visitor, stmt))
+}
+
+fn walk_expressions<V: VisitMut + ?Sized>(visitor: &mut V, exprs: &mut [Expression]) -> Result<(), V::Error> {
+    exprs.iter_mut().try_for_each(|expr| walk_expression(visitor, expr))
+}
+
+fn walk_declarators<V: VisitMut + ?Sized>(visitor: &mut V, declarations: &mut [VariableDeclarator]) -> Result<(), V::Error> {
+    for declarator in declarations {
+        walk_pattern(visitor, &mut declarator.id)?;
+        if let Some(init) = &mut declarator.init {
+            walk_expression(visitor, init)?;
+        }
+    }
+    Ok(())
+}
+
+fn walk_function<V: VisitMut + ?Sized>(visitor: &mut V, function: &mut FunctionExpression) -> Result<(), V::Error> {
+    visitor.enter_function(function.id.as_ref(), &function.params, &function.body.body)?;
+    walk_patterns(visitor, &mut function.params)?;
+    walk_statements(visitor, &mut function.body.body)?;
+    visitor.exit_function()
+}
+
+fn walk_class_body<V: VisitMut + ?Sized>(visitor: &mut V, body: &mut ClassBody) -> Result<(), V::Error> {
+    for element in &mut body.body {
+        match element {
+            ClassElement::PropertyDefinition { value, decorators, .. } => {
+                for decorator in decorators {
+                    walk_expression(visitor, decorator)?;
+                }
+                if let Some(value) = value {
+                    walk_expression(visitor, value)?;
+                }
+            }
+            ClassElement::MethodDefinition { value, decorators, .. } => {
+                for decorator in decorators {
+                    walk_expression(visitor, decorator)?;
+                }
+                walk_function(visitor, value)?;
+            }
+        }
+    }
+    Ok(())
+}
+
+fn walk_patterns<V: VisitMut + ?Sized>(visitor: &mut V, patterns: &mut [Pattern]) -> Result<(), V::Error> {
+    patterns.iter_mut().try_for_each(|pattern| walk_pattern(visitor, pattern))
+}
+
+/// Visits the default-value expressions inside a binding pattern
+fn walk_pattern<V: VisitMut + ?Sized>(visitor: &mut V, pattern: &mut Pattern) -> Result<(), V::Error> {
+    match pattern {
+        Pattern::Identifier(_) => Ok(()),
+        Pattern::ArrayPattern { elements } => elements.iter_mut().flatten().try_for_each(|element| walk_pattern(visitor, element)),
+        Pattern::ObjectPattern { properties } => properties.iter_mut().try_for_each(|property| match property {
+            ObjectPatternProperty::Property { value, .. } => walk_pattern(visitor, value),
+            ObjectPatternProperty::RestElement { argument } => walk_pattern(visitor, argument),
+        }),
+        Pattern::AssignmentPattern { left, right } => {
+            walk_pattern(visitor, left)?;
+            walk_expression(visitor, right)
+        }
+        Pattern::RestElement { argument } => walk_pattern(visitor, argument),
+    }
+}
@@ -0,0 +1,495 @@
+//! # JSON-RPC Server
+//!
+//! Long-running server mode (`rjs-compiler --serve`) that accepts JSON-RPC 2.0
+//! requests over stdio, one JSON message per line, so editors and build tools can
+//! keep a warm process instead of paying startup cost for every file.
+//!
+//! ## Methods
+//!
+//! | Method     | Params                                   | Result                               |
+//! |------------|------------------------------------------|--------------------------------------|
+//! | `minify`   | `{code, filename?, sourceMap?, sourceMapIgnoreList?, timeoutMs?}` | `{code, map, warnings, cached}` |
+//! | `compile`  | `{input, output?, sourceMap?, sourceMapIgnoreList?, timeoutMs?}` | `{code, map, warnings, cached, output}` |
+//! | `analyze`  | `{code, filename?}`                      | `{metadata, symbols, globals}`       |
+//! | `shutdown` | none                                     | `null`, then the server exits        |
+//!
+//! `compile` reads `input` from disk and, when `output` is given, writes the minified
+//! code there (and the source map to `<output>.map` when requested).
+//!
+//! `sourceMapIgnoreList` takes globs of sources to mark as ignore-listed in the source map
+//! (see [`matches_glob`](crate::generator::source_maps::matches_glob)).
+//!
+//! Results of `minify` and `compile` are cached in memory by source text and options,
+//! so recompiling an unchanged file is answered without running the pipeline.
+//!
+//! With `timeoutMs`, a compilation still running after that many milliseconds is
+//! stopped between pipeline steps and answered with a [`TIMEOUT_ERROR`].
+//!
+//! ## Example
+//!
+//! ```text
+//! → {"jsonrpc":"2.0","id":1,"method":"minify","params":{"code":"let a = 1;"}}
+//! ← {"jsonrpc":"2.0","id":1,"result":{"code":"let a=1;","map":null,"warnings":[...],"cached":false}}
+//! ```
+
+use std::collections::{HashMap, VecDeque};
+use std::fs;
+use std::io::{self, BufRead, Write};
+use std::path::PathBuf;
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+use crate::analyzer::SemanticAnalysis;
+use crate::cancel::CancellationToken;
+use crate::{CompileError, MinifyOptions, MinifyOutput};
+
+/// Maximum number of compilation results kept in the in-memory cache
+pub const CACHE_CAPACITY: usize = 256;
+
+/// JSON-RPC error codes defined by the specification
+const PARSE_ERROR: i64 = -32700;
+const INVALID_REQUEST: i64 = -32600;
+const METHOD_NOT_FOUND: i64 = -32601;
+const INVALID_PARAMS: i64 = -32602;
+
+/// Server-defined error code for failed compilations
+pub const COMPILE_ERROR: i64 = -32000;
+
+/// Server-defined error code for filesystem failures in `compile`
+pub const IO_ERROR: i64 = -32001;
+
+/// Server-defined error code for compilations stopped after their `timeoutMs`
+pub const TIMEOUT_ERROR: i64 = -32002;
+
+/// Incoming JSON-RPC request or notification
+#[derive(Debug, Deserialize)]
+struct Request {
+    /// Protocol version, must be "2.0"
+    jsonrpc: String,
+    /// Request id; absent for notifications
+    #[serde(default)]
+    id: Option<Value>,
+    /// Method name
+    method: String,
+    /// Method parameters
+    #[serde(default)]
+    params: Value,
+}
+
+/// JSON-RPC error object
+#[derive(Debug, Serialize)]
+struct RpcError {
+    code: i64,
+    message: String,
+}
+
+impl RpcError {
+    fn new(code: i64, message: impl Into<String>) -> Self {
+        Self { code, message: message.into() }
+    }
+}
+
+/// Parameters of the `minify` method
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct MinifyParams {
+    code: String,
+    #[serde(default)]
+    timeout_ms: Option<u64>,
+    #[serde(flatten)]
+    options: MinifyOptions,
+}
+
+/// Parameters of the `compile` method
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct CompileParams {
+    input: PathBuf,
+    #[serde(default)]
+    output: Option<PathBuf>,
+    #[serde(default)]
+    source_map: bool,
+    #[serde(default)]
+    source_map_ignore_list: Vec<String>,
+    #[serde(default)]
+    timeout_ms: Option<u64>,
+}
+
+/// Parameters of the `analyze` method
+#[derive(Debug, Deserialize)]
+struct AnalyzeParams {
+    code: String,
+    #[serde(default)]
+    filename: Option<String>,
+}
+
+/// Result of `minify` and `compile`
+#[derive(Debug, Serialize)]
+struct CompileResponse {
+    #[serde(flatten)]
+    output: MinifyOutput,
+    /// Whether the result was served from the cache
+    cached: bool,
+    /// File the code was written to, for `compile` with an output path
+    #[serde(rename = "output", skip_serializing_if = "Option::is_none")]
+    output_path: Option<PathBuf>,
+}
+
+/// Bounded cache of compilation results, evicting the oldest entry first
+#[derive(Debug, Default)]
+struct CompileCache {
+    entries: HashMap<(String, MinifyOptions), MinifyOutput>,
+    order: VecDeque<(String, MinifyOptions)>,
+}
+
+impl CompileCache {
+    fn get(&self, key: &(String, MinifyOptions)) -> Option<&MinifyOutput> {
+        self.entries.get(key)
+    }
+
+    fn insert(&mut self, key: (String, MinifyOptions), output: MinifyOutput) {
+        if self.entries.len() >= CACHE_CAPACITY
+            && let Some(oldest) = self.order.pop_front()
+        {
+            self.entries.remove(&oldest);
+        }
+        self.order.push_back(key.clone());
+        self.entries.insert(key, output);
+    }
+}
+
+/// Stdio JSON-RPC server holding the warm compilation cache
+#[derive(Debug, Default)]
+pub struct Server {
+    /// Cached compilation results
+    cache: CompileCache,
+    /// Set once a `shutdown` request has been handled
+    shutdown_requested: bool,
+}
+
+impl Server {
+    /// Creates a server with an empty cache
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Serves requests from `input` until end of input or a `shutdown` request
+    ///
+    /// Each line of `input` is one JSON-RPC message; each response is written to
+    /// `output` as a single line and flushed immediately.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if reading from `input` or writing to `output` fails
+    pub fn serve<R: BufRead, W: Write>(&mut self, input: R, mut output: W) -> io::Result<()> {
+        for line in input.lines() {
+            let line = line?;
+            if line.trim().is_empty() {
+                continue;
+            }
+
+            if let Some(response) = self.handle_message(&line) {
+                writeln!(output, "{}", response)?;
+                output.flush()?;
+            }
+
+            if self.shutdown_requested {
+                break;
+            }
+        }
+        Ok(())
+    }
+
+    /// Handles a single JSON-RPC message
+    ///
+    /// # Returns
+    ///
+    /// Returns the serialized response, or `None` for notifications
+    pub fn handle_message(&mut self, message: &str) -> Option<String> {
+        let value: Value = match serde_json::from_str(message) {
+            Ok(value) => value,
+            Err(e) => {
+                return Some(error_response(Value::Null, RpcError::new(PARSE_ERROR, e.to_string())));
+            }
+        };
+
+        let request: Request = match serde_json::from_value(value) {
+            Ok(request) => request,
+            Err(e) => {
+                return Some(error_response(Value::Null, RpcError::new(INVALID_REQUEST, e.to_string())));
+            }
+        };
+
+        let id = request.id.clone();
+        let result = if request.jsonrpc == "2.0" {
+            self.dispatch(&request.method, request.params)
+        } else {
+            Err(RpcError::new(INVALID_REQUEST, "jsonrpc must be \"2.0\""))
+        };
+
+        // Notifications never receive a response, even on error
+        let id = id?;
+        Some(match result {
+            Ok(result) => json!({ "jsonrpc": "2.0", "id": id, "result": result }).to_string(),
+            Err(error) => error_response(id, error),
+        })
+    }
+
+    /// Routes a request to its method handler
+    fn dispatch(&mut self, method: &str, params: Value) -> Result<Value, RpcError> {
+        match method {
+            "minify" => {
+                let params: MinifyParams = parse_params(params)?;
+                let response = self.minify_cached(params.code, params.options, params.timeout_ms)?;
+                to_value(response)
+            }
+            "compile" => {
+                let params: CompileParams = parse_params(params)?;
+                self.compile_file(params)
+            }
+            "analyze" => {
+                let params: AnalyzeParams = parse_params(params)?;
+                let filename = params.filename.as_deref().unwrap_or("input.js");
+                let analysis = crate::analyze_source(&params.code, filename).map_err(compile_error)?;
+                Ok(analysis_summary(&analysis))
+            }
+            "shutdown" => {
+                self.shutdown_requested = true;
+                Ok(Value::Null)
+            }
+            _ => Err(RpcError::new(METHOD_NOT_FOUND, format!("Unknown method: {}", method))),
+        }
+    }
+
+    /// Minifies `code`, consulting the cache first
+    fn minify_cached(
+        &mut self,
+        code: String,
+        options: MinifyOptions,
+        timeout_ms: Option<u64>,
+    ) -> Result<CompileResponse, RpcError> {
+        let key = (code, options);
+        if let Some(output) = self.cache.get(&key) {
+            return Ok(CompileResponse { output: output.clone(), cached: true, output_path: None });
+        }
+
+        let mut token = CancellationToken::new();
+        if let Some(timeout_ms) = timeout_ms {
+            token = token.with_timeout(Duration::from_millis(timeout_ms));
+        }
+        let output = crate::minify_with_cancellation(&key.0, &key.1, &token).map_err(compile_error)?;
+        self.cache.insert(key, output.clone());
+        Ok(CompileResponse { output, cached: false, output_path: None })
+    }
+
+    /// Handles `compile`: reads the input file, minifies it and optionally writes the result
+    fn compile_file(&mut self, params: CompileParams) -> Result<Value, RpcError> {
+        let code = fs::read_to_string(&params.input).map_err(|e| {
+            RpcError::new(IO_ERROR, format!("Failed to read '{}': {}", params.input.display(), e))
+        })?;
+
+        let options = MinifyOptions {
+            filename: Some(params.input.to_string_lossy().into_owned()),
+            source_map: params.source_map,
+            source_map_ignore_list: params.source_map_ignore_list,
+        };
+        let mut response = self.minify_cached(code, options, params.timeout_ms)?;
+
+        if let Some(output_path) = params.output {
+            let write_error = |path: &PathBuf, e: io::Error| {
+                RpcError::new(IO_ERROR, format!("Failed to write '{}': {}", path.display(), e))
+            };
+            fs::write(&output_path, &response.output.code).map_err(|e| write_error(&output_path, e))?;
+
+            if let Some(map) = &response.output.map {
+                let map_path = PathBuf::from(format!("{}.map", output_path.display()));
+                fs::write(&map_path, map).map_err(|e| write_error(&map_path, e))?;
+            }
+            response.output_path = Some(output_path);
+        }
+
+        to_value(response)
+    }
+}
+
+/// Summarizes an analysis for the `analyze` method
+fn analysis_summary(analysis: &SemanticAnalysis) -> Value {
+    let mut symbols: Vec<_> = analysis.symbol_table.symbols.values().collect();
+    symbols.sort_by_key(|symbol| symbol.id);
+
+    let symbols: Vec<Value> = symbols
+        .into_iter()
+        .map(|symbol| {
+            json!({
+                "name": analysis.symbol_table.name(symbol),
+                "scopeId": symbol.scope_id,
+                "references": symbol.references.len(),
+                "captured": symbol.is_captured,
+                "exported": symbol.is_exported,
+                "renamable": symbol.is_renamable,
+                "kept": symbol.is_kept,
+            })
+        })
+        .collect();
+
+    let globals: Vec<&str> = analysis
+        .semantic_flags
+        .global_references
+        .iter()
+        .filter_map(|id| analysis.symbol_table.symbols.get(*id))
+        .map(|symbol| analysis.symbol_table.name(symbol))
+        .collect();
+
+    json!({
+        "metadata": analysis.metadata,
+        "symbols": symbols,
+        "globals": globals,
+        "globalExports": analysis.semantic_flags.global_exports,
+    })
+}
+
+/// Deserializes method parameters, reporting failures as `Invalid params`
+fn parse_params<T: serde::de::DeserializeOwned>(params: Value) -> Result<T, RpcError> {
+    serde_json::from_value(params).map_err(|e| RpcError::new(INVALID_PARAMS, e.to_string()))
+}
+
+/// Serializes a method result
+fn to_value<T: Serialize>(result: T) -> Result<Value, RpcError> {
+    serde_json::to_value(result).map_err(|e| RpcError::new(COMPILE_ERROR, e.to_string()))
+}
+
+/// Converts a pipeline failure into a JSON-RPC error
+fn compile_error(error: CompileError) -> RpcError {
+    let code = if error.cancelled().is_some() { TIMEOUT_ERROR } else { COMPILE_ERROR };
+    RpcError::new(code, error.to_string())
+}
+
+/// Builds a serialized error response
+fn error_response(id: Value, error: RpcError) -> String {
+    json!({ "jsonrpc": "2.0", "id": id, "error": error }).to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn request(server: &mut Server, message: Value) -> Value {
+        let response = server.handle_message(&message.to_string()).expect("request should get a response");
+        serde_json::from_str(&response).unwrap()
+    }
+
+    #[test]
+    fn test_minify_is_cached() {
+        let mut server = Server::new();
+        let message = json!({
+            "jsonrpc": "2.0", "id": 1, "method": "minify",
+            "params": { "code": "let answer = Math.max(40, 42);" }
+        });
+
+        let first = request(&mut server, message.clone());
+        assert_eq!(first["id"], 1);
+        assert_eq!(first["result"]["code"], "let answer=42;");
+        assert_eq!(first["result"]["cached"], false);
+
+        let second = request(&mut server, message);
+        assert_eq!(second["result"]["code"], "let answer=42;");
+        assert_eq!(second["result"]["cached"], true);
+    }
+
+    #[test]
+    fn test_clean_input_has_no_warnings() {
+        let mut server = Server::new();
+        let response = request(&mut server, json!({
+            "jsonrpc": "2.0", "id": 1, "method": "minify",
+            "params": { "code": "let answer = Math.max(40, 42);\nconsole.log(answer);" }
+        }));
+        assert_eq!(response["result"]["warnings"], json!([]), "{}", response);
+    }
+
+    #[test]
+    fn test_analyze_reports_symbols() {
+        let mut server = Server::new();
+        let response = request(&mut server, json!({
+            "jsonrpc": "2.0", "id": "a", "method": "analyze",
+            "params": { "code": "function add(a, b) { return a + b; }" }
+        }));
+
+        let names: Vec<&str> = response["result"]["symbols"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|symbol| symbol["name"].as_str().unwrap())
+            .collect();
+        assert!(names.contains(&"add"));
+        assert!(response["result"]["metadata"]["symbol_count"].as_u64().unwrap() >= 1);
+    }
+
+    #[test]
+    fn test_errors() {
+        let mut server = Server::new();
+
+        let response: Value = serde_json::from_str(&server.handle_message("{not json").unwrap()).unwrap();
+        assert_eq!(response["error"]["code"], PARSE_ERROR);
+
+        let response = request(&mut server, json!({ "jsonrpc": "2.0", "id": 2, "method": "bundle" }));
+        assert_eq!(response["error"]["code"], METHOD_NOT_FOUND);
+
+        let response = request(&mut server, json!({ "jsonrpc": "2.0", "id": 3, "method": "minify", "params": {} }));
+        assert_eq!(response["error"]["code"], INVALID_PARAMS);
+
+        let response = request(&mut server, json!({
+            "jsonrpc": "2.0", "id": 4, "method": "minify", "params": { "code": "let = ;" }
+        }));
+        assert_eq!(response["error"]["code"], COMPILE_ERROR);
+
+        let response = request(&mut server, json!({
+            "jsonrpc": "2.0", "id": 5, "method": "minify", "params": { "code": "let a = 1;", "timeoutMs": 0 }
+        }));
+        assert_eq!(response["error"]["code"], TIMEOUT_ERROR);
+        assert_eq!(response["error"]["message"], "Compilation stopped: timed out after 0ms");
+    }
+
+    #[test]
+    fn test_notifications_get_no_response() {
+        let mut server = Server::new();
+        let notification = json!({ "jsonrpc": "2.0", "method": "minify", "params": { "code": "1" } });
+        assert!(server.handle_message(&notification.to_string()).is_none());
+    }
+
+    #[test]
+    fn test_serve_stops_at_shutdown() {
+        let input = [
+            json!({ "jsonrpc": "2.0", "id": 1, "method": "minify", "params": { "code": "let a = 1;" } }),
+            json!({ "jsonrpc": "2.0", "id": 2, "method": "shutdown" }),
+            json!({ "jsonrpc": "2.0", "id": 3, "method": "minify", "params": { "code": "let b = 2;" } }),
+        ]
+        .map(|message| message.to_string())
+        .join("\n");
+
+        let mut output = Vec::new();
+        Server::new().serve(input.as_bytes(), &mut output).unwrap();
+
+        let responses: Vec<Value> = String::from_utf8(output)
+            .unwrap()
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        assert_eq!(responses.len(), 2);
+        assert_eq!(responses[1]["result"], Value::Null);
+    }
+
+    #[test]
+    fn test_cache_evicts_oldest_entry() {
+        let mut cache = CompileCache::default();
+        let output = MinifyOutput { code: String::new(), map: None, warnings: Vec::new() };
+
+        for i in 0..=CACHE_CAPACITY {
+            cache.insert((format!("let v = {};", i), MinifyOptions::default()), output.clone());
+        }
+
+        assert_eq!(cache.entries.len(), CACHE_CAPACITY);
+        assert!(cache.get(&("let v = 0;".to_string(), MinifyOptions::default())).is_none());
+    }
+}
@@ -0,0 +1,512 @@
+//! # Target Environments
+//!
+//! Describes which JavaScript engines the output has to run on and which syntax
+//! features those engines support. The same [`Targets`] value is shared by the
+//! transformer and the generator, so every phase gates syntax per feature instead
+//! of on a single ECMAScript edition.
+//!
+//! Targets are written as a comma separated list of engines with minimum versions
+//! (`chrome90,firefox88,node16`), in the `<engine> <version>` form used by
+//! `.browserslistrc` files (`chrome >= 90`), or as an edition preset (`es5`,
+//! `es2015` … `es2022`, `latest`).
+//!
+//! ## Example
+//!
+//! ```rust
+//! use rjs_compiler::target::{Feature, Targets};
+//!
+//! let targets = Targets::parse("chrome90,firefox88,node16").unwrap();
+//! assert!(targets.supports(Feature::OptionalChaining));
+//! assert!(!targets.supports(Feature::ClassStaticBlocks));
+//! ```
+
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+pub mod polyfills;
+
+/// Name of the browserslist configuration file looked up next to the input
+pub const BROWSERSLIST_FILE: &str = ".browserslistrc";
+
+/// Errors produced while parsing a target list
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum TargetError {
+    #[error("Unknown target engine '{0}' (expected chrome, edge, firefox, safari, ios, node or an es20xx preset)")]
+    UnknownEngine(String),
+
+    #[error("Invalid version '{version}' for target engine '{engine}'")]
+    InvalidVersion { engine: String, version: String },
+
+    #[error("Unsupported browserslist query '{0}' (only '<engine> <version>' and '<engine> >= <version>' are supported)")]
+    UnsupportedQuery(String),
+
+    #[error("Empty target list")]
+    Empty,
+}
+
+/// JavaScript engine a target refers to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Engine {
+    Chrome,
+    Edge,
+    Firefox,
+    Safari,
+    Ios,
+    Node,
+}
+
+impl Engine {
+    /// All engines, in display order
+    pub const ALL: [Engine; 6] = [Engine::Chrome, Engine::Edge, Engine::Firefox, Engine::Safari, Engine::Ios, Engine::Node];
+
+    /// Name used in target lists
+    pub fn name(self) -> &'static str {
+        match self {
+            Engine::Chrome => "chrome",
+            Engine::Edge => "edge",
+            Engine::Firefox => "firefox",
+            Engine::Safari => "safari",
+            Engine::Ios => "ios",
+            Engine::Node => "node",
+        }
+    }
+
+    /// Looks up an engine by name, accepting common browserslist aliases
+    pub fn from_name(name: &str) -> Option<Engine> {
+        match name.to_ascii_lowercase().as_str() {
+            "chrome" | "and_chr" => Some(Engine::Chrome),
+            "edge" => Some(Engine::Edge),
+            "firefox" | "ff" | "and_ff" => Some(Engine::Firefox),
+            "safari" => Some(Engine::Safari),
+            "ios" | "ios_saf" => Some(Engine::Ios),
+            "node" => Some(Engine::Node),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for Engine {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// Engine version, compared by major then minor number
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct Version {
+    pub major: u32,
+    pub minor: u32,
+}
+
+impl Version {
+    /// Creates a version
+    pub const fn new(major: u32, minor: u32) -> Self {
+        Self { major, minor }
+    }
+}
+
+impl FromStr for Version {
+    type Err = ();
+
+    /// Parses `16`, `13.1` or `16.0.0` (the patch number is ignored)
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let mut parts = value.split('.');
+        let major = parts.next().ok_or(())?.parse().map_err(|_| ())?;
+        let minor = match parts.next() {
+            Some(minor) => minor.parse().map_err(|_| ())?,
+            None => 0,
+        };
+        if parts.any(|patch| patch.parse::<u32>().is_err()) {
+            return Err(());
+        }
+        Ok(Self { major, minor })
+    }
+}
+
+impl fmt::Display for Version {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.minor == 0 {
+            write!(f, "{}", self.major)
+        } else {
+            write!(f, "{}.{}", self.major, self.minor)
+        }
+    }
+}
+
+/// Minimum version of one engine
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct EngineTarget {
+    pub engine: Engine,
+    pub version: Version,
+}
+
+impl fmt::Display for EngineTarget {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {}", self.engine, self.version)
+    }
+}
+
+/// Syntax feature that may have to be down-leveled for older targets
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum Feature {
+    ArrowFunctions,
+    BlockScoping,
+    Classes,
+    Destructuring,
+    Generators,
+    Parameters,
+    Spread,
+    TemplateLiterals,
+    Exponentiation,
+    AsyncFunctions,
+    ObjectRestSpread,
+    AsyncGenerators,
+    OptionalCatchBinding,
+    OptionalChaining,
+    NullishCoalescing,
+    BigInt,
+    LogicalAssignment,
+    NumericSeparators,
+    ClassFields,
+    ClassStaticBlocks,
+    TopLevelAwait,
+    Decorators,
+}
+
+impl Feature {
+    /// All features, ordered by the edition that introduced them
+    pub const ALL: [Feature; 22] = [
+        Feature::ArrowFunctions,
+        Feature::BlockScoping,
+        Feature::Classes,
+        Feature::Destructuring,
+        Feature::Generators,
+        Feature::Parameters,
+        Feature::Spread,
+        Feature::TemplateLiterals,
+        Feature::Exponentiation,
+        Feature::AsyncFunctions,
+        Feature::ObjectRestSpread,
+        Feature::AsyncGenerators,
+        Feature::OptionalCatchBinding,
+        Feature::OptionalChaining,
+        Feature::NullishCoalescing,
+        Feature::BigInt,
+        Feature::LogicalAssignment,
+        Feature::NumericSeparators,
+        Feature::ClassFields,
+        Feature::ClassStaticBlocks,
+        Feature::TopLevelAwait,
+        Feature::Decorators,
+    ];
+
+    /// Human-readable name, e.g. `optional chaining (?.)`
+    pub fn name(self) -> &'static str {
+        match self {
+            Feature::ArrowFunctions => "arrow functions",
+            Feature::BlockScoping => "let/const declarations",
+            Feature::Classes => "classes",
+            Feature::Destructuring => "destructuring",
+            Feature::Generators => "generators",
+            Feature::Parameters => "default and rest parameters",
+            Feature::Spread => "spread arguments",
+            Feature::TemplateLiterals => "template literals",
+            Feature::Exponentiation => "exponentiation operator (**)",
+            Feature::AsyncFunctions => "async functions",
+            Feature::ObjectRestSpread => "object rest/spread",
+            Feature::AsyncGenerators => "async generators",
+            Feature::OptionalCatchBinding => "optional catch binding",
+            Feature::OptionalChaining => "optional chaining (?.)",
+            Feature::NullishCoalescing => "nullish coalescing (??)",
+            Feature::BigInt => "BigInt literals",
+            Feature::LogicalAssignment => "logical assignment (&&=, ||=, ??=)",
+            Feature::NumericSeparators => "numeric separators",
+            Feature::ClassFields => "class fields",
+            Feature::ClassStaticBlocks => "class static blocks",
+            Feature::TopLevelAwait => "top-level await",
+            Feature::Decorators => "decorators",
+        }
+    }
+
+    /// ECMAScript edition that introduced the feature
+    ///
+    /// Stage-3 proposals are not part of an edition yet and report `u16::MAX`, so
+    /// only `latest` allows them.
+    pub fn edition(self) -> u16 {
+        match self {
+            Feature::ArrowFunctions
+            | Feature::BlockScoping
+            | Feature::Classes
+            | Feature::Destructuring
+            | Feature::Generators
+            | Feature::Parameters
+            | Feature::Spread
+            | Feature::TemplateLiterals => 2015,
+            Feature::Exponentiation => 2016,
+            Feature::AsyncFunctions => 2017,
+            Feature::ObjectRestSpread | Feature::AsyncGenerators => 2018,
+            Feature::OptionalCatchBinding => 2019,
+            Feature::OptionalChaining | Feature::NullishCoalescing | Feature::BigInt => 2020,
+            Feature::LogicalAssignment | Feature::NumericSeparators => 2021,
+            Feature::ClassFields | Feature::ClassStaticBlocks | Feature::TopLevelAwait => 2022,
+            Feature::Decorators => u16::MAX,
+        }
+    }
+
+    /// First version of `engine` with working support for the feature
+    ///
+    /// Versions follow the compat data used by Babel's preset-env, which is
+    /// conservative where early implementations were buggy.
+    pub fn min_version(self, engine: Engine) -> Version {
+        // chrome, edge, firefox, safari, ios, node
+        let table: [(u32, u32); 6] = match self {
+            Feature::ArrowFunctions => [(47, 0), (13, 0), (43, 0), (10, 0), (10, 0), (6, 0)],
+            Feature::BlockScoping => [(49, 0), (14, 0), (51, 0), (11, 0), (11, 0), (6, 0)],
+            Feature::Classes => [(46, 0), (13, 0), (45, 0), (10, 0), (10, 0), (5, 0)],
+            Feature::Destructuring => [(51, 0), (15, 0), (53, 0), (10, 0), (10, 0), (6, 5)],
+            Feature::Generators => [(50, 0), (13, 0), (53, 0), (10, 0), (10, 0), (6, 0)],
+            Feature::Parameters => [(49, 0), (14, 0), (52, 0), (10, 0), (10, 0), (6, 0)],
+            Feature::Spread => [(46, 0), (13, 0), (45, 0), (10, 0), (10, 0), (5, 0)],
+            Feature::TemplateLiterals => [(41, 0), (13, 0), (34, 0), (13, 0), (13, 0), (4, 0)],
+            Feature::Exponentiation => [(52, 0), (14, 0), (52, 0), (10, 1), (10, 3), (7, 0)],
+            Feature::AsyncFunctions => [(55, 0), (15, 0), (52, 0), (11, 0), (11, 0), (7, 6)],
+            Feature::ObjectRestSpread => [(60, 0), (79, 0), (55, 0), (11, 1), (11, 3), (8, 3)],
+            Feature::AsyncGenerators => [(63, 0), (79, 0), (57, 0), (12, 0), (12, 0), (10, 0)],
+            Feature::OptionalCatchBinding => [(66, 0), (79, 0), (58, 0), (11, 1), (11, 3), (10, 0)],
+            Feature::OptionalChaining => [(80, 0), (80, 0), (74, 0), (13, 1), (13, 4), (14, 0)],
+            Feature::NullishCoalescing => [(80, 0), (80, 0), (72, 0), (13, 1), (13, 4), (14, 0)],
+            Feature::BigInt => [(67, 0), (79, 0), (68, 0), (14, 0), (14, 0), (10, 4)],
+            Feature::LogicalAssignment => [(85, 0), (85, 0), (79, 0), (14, 0), (14, 0), (15, 0)],
+            Feature::NumericSeparators => [(75, 0), (79, 0), (70, 0), (13, 0), (13, 0), (12, 5)],
+            Feature::ClassFields => [(74, 0), (79, 0), (90, 0), (14, 1), (14, 5), (12, 0)],
+            Feature::ClassStaticBlocks => [(94, 0), (94, 0), (93, 0), (16, 4), (16, 4), (16, 11)],
+            Feature::TopLevelAwait => [(89, 0), (89, 0), (89, 0), (15, 0), (15, 0), (14, 8)],
+            // No engine ships decorators yet
+            Feature::Decorators => [(u32::MAX, 0); 6],
+        };
+        engine_version(&table, engine)
+    }
+}
+
+/// Looks up `engine` in a table ordered like [`Engine::ALL`]
+fn engine_version(table: &[(u32, u32); 6], engine: Engine) -> Version {
+    let index = Engine::ALL.iter().position(|&candidate| candidate == engine).unwrap_or(0);
+    let (major, minor) = table[index];
+    Version::new(major, minor)
+}
+
+impl fmt::Display for Feature {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// Environments the generated code has to run on
+///
+/// The default places no restriction on syntax (`latest`).
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Targets {
+    /// Minimum supported version of each listed engine
+    pub engines: Vec<EngineTarget>,
+    /// Newest ECMAScript edition the output may use (`None` for no limit)
+    pub edition: Option<u16>,
+}
+
+impl Targets {
+    /// Targets that allow every supported syntax feature
+    pub fn latest() -> Self {
+        Self::default()
+    }
+
+    /// Targets limited to ECMAScript `edition` (5 or a year such as 2015)
+    pub fn edition(edition: u16) -> Self {
+        Self { engines: Vec::new(), edition: Some(edition) }
+    }
+
+    /// Parses a target list such as `chrome90,firefox88,node16` or `es2015`
+    ///
+    /// Entries are separated by commas or newlines; `#` starts a comment. Each entry
+    /// is an engine with a minimum version (`chrome90`, `chrome 90`, `chrome >= 90`)
+    /// or an edition preset (`es5`, `es2015`, `latest`). When an engine is listed
+    /// several times the oldest version wins.
+    pub fn parse(list: &str) -> Result<Self, TargetError> {
+        let mut targets = Self::default();
+        let mut any = false;
+
+        for entry in list
+            .lines()
+            .map(|line| line.split('#').next().unwrap_or_default())
+            .flat_map(|line| line.split(','))
+            .map(str::trim)
+            .filter(|entry| !entry.is_empty())
+        {
+            any = true;
+            if let Some(edition) = parse_preset(entry) {
+                targets.edition = match (targets.edition, edition) {
+                    (Some(current), Some(edition)) => Some(current.min(edition)),
+                    (current, None) => current,
+                    (None, edition) => edition,
+                };
+                continue;
+            }
+
+            let target = parse_engine_target(entry)?;
+            match targets.engines.iter_mut().find(|existing| existing.engine == target.engine) {
+                Some(existing) => existing.version = existing.version.min(target.version),
+                None => targets.engines.push(target),
+            }
+        }
+
+        if !any {
+            return Err(TargetError::Empty);
+        }
+        targets.engines.sort_by_key(|target| target.engine);
+        Ok(targets)
+    }
+
+    /// Finds the nearest `.browserslistrc` in `dir` or one of its ancestors
+    pub fn find_browserslistrc(dir: &Path) -> Option<PathBuf> {
+        dir.ancestors()
+            .map(|ancestor| ancestor.join(BROWSERSLIST_FILE))
+            .find(|candidate| candidate.is_file())
+    }
+
+    /// Returns whether every target supports `feature` natively
+    pub fn supports(&self, feature: Feature) -> bool {
+        self.meets(feature.edition(), |engine| feature.min_version(engine))
+    }
+
+    /// Returns whether every target is at least `edition` and `min_version` of its engine
+    pub(crate) fn meets(&self, edition: u16, min_version: impl Fn(Engine) -> Version) -> bool {
+        self.edition.is_none_or(|limit| limit >= edition)
+            && self.engines.iter().all(|target| target.version >= min_version(target.engine))
+    }
+
+    /// Features that at least one target lacks and that must be down-leveled
+    pub fn unsupported(&self) -> Vec<Feature> {
+        Feature::ALL.into_iter().filter(|&feature| !self.supports(feature)).collect()
+    }
+}
+
+impl FromStr for Targets {
+    type Err = TargetError;
+
+    fn from_str(list: &str) -> Result<Self, Self::Err> {
+        Self::parse(list)
+    }
+}
+
+impl fmt::Display for Targets {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut parts: Vec<String> = self.engines.iter().map(ToString::to_string).collect();
+        match self.edition {
+            Some(5) => parts.push("es5".to_string()),
+            Some(edition) => parts.push(format!("es{}", edition)),
+            None if parts.is_empty() => parts.push("latest".to_string()),
+            None => {}
+        }
+        f.write_str(&parts.join(", "))
+    }
+}
+
+/// Parses an edition preset: `Some(None)` for `latest`, `Some(Some(year))` for `es20xx`
+fn parse_preset(entry: &str) -> Option<Option<u16>> {
+    let lower = entry.to_ascii_lowercase();
+    match lower.as_str() {
+        "latest" | "esnext" => Some(None),
+        "es5" => Some(Some(5)),
+        "es6" => Some(Some(2015)),
+        _ => lower
+            .strip_prefix("es")
+            .and_then(|year| year.parse::<u16>().ok())
+            .filter(|year| (2015..=2100).contains(year))
+            .map(Some),
+    }
+}
+
+/// Parses `chrome90`, `chrome 90`, `chrome >= 90` or `node 16.4.0`
+fn parse_engine_target(entry: &str) -> Result<EngineTarget, TargetError> {
+    let name_len = entry
+        .find(|c: char| !(c.is_ascii_alphabetic() || c == '_'))
+        .unwrap_or(entry.len());
+    let (name, rest) = entry.split_at(name_len);
+    if name.is_empty() {
+        return Err(TargetError::UnsupportedQuery(entry.to_string()));
+    }
+
+    let rest = rest.trim_start();
+    let version = match rest.strip_prefix(">=") {
+        Some(version) => version.trim(),
+        None if rest.starts_with(['<', '>', '=']) || rest.contains(' ') => {
+            return Err(TargetError::UnsupportedQuery(entry.to_string()));
+        }
+        None => rest,
+    };
+
+    let Some(engine) = Engine::from_name(name) else {
+        return Err(if version.is_empty() || version.parse::<Version>().is_err() {
+            TargetError::UnsupportedQuery(entry.to_string())
+        } else {
+            TargetError::UnknownEngine(name.to_string())
+        });
+    };
+    let version = version.parse().map_err(|_| TargetError::InvalidVersion {
+        engine: name.to_string(),
+        version: version.to_string(),
+    })?;
+    Ok(EngineTarget { engine, version })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_engine_list() {
+        let targets = Targets::parse("chrome90,firefox88, node16").unwrap();
+        assert_eq!(targets.to_string(), "chrome 90, firefox 88, node 16");
+        assert!(targets.supports(Feature::OptionalChaining));
+        assert!(targets.supports(Feature::LogicalAssignment));
+        // Firefox 88 predates class fields (90) and top-level await (89), node 16 class
+        // static blocks (16.11)
+        assert_eq!(
+            targets.unsupported(),
+            vec![Feature::ClassFields, Feature::ClassStaticBlocks, Feature::TopLevelAwait, Feature::Decorators]
+        );
+
+        let old = Targets::parse("chrome 49\nsafari >= 10.1\nchrome 60").unwrap();
+        assert_eq!(old.engines[0].version, Version::new(49, 0));
+        // Safari's template literals are only counted as working from 13
+        assert!(!old.supports(Feature::TemplateLiterals));
+        assert!(old.supports(Feature::Classes));
+        assert!(!old.supports(Feature::Destructuring));
+    }
+
+    #[test]
+    fn test_presets_and_browserslistrc_syntax() {
+        assert!(Targets::parse("es5").unwrap().unsupported().contains(&Feature::BlockScoping));
+        let es2015 = Targets::parse("es2015").unwrap();
+        assert!(es2015.supports(Feature::ArrowFunctions));
+        assert!(!es2015.supports(Feature::Exponentiation));
+        assert!(Targets::parse("latest").unwrap().unsupported().is_empty());
+
+        let rc = "# production\nchrome >= 80\n\nios_saf 13.4 # phones\n";
+        let targets = Targets::parse(rc).unwrap();
+        assert_eq!(targets.to_string(), "chrome 80, ios 13.4");
+        assert!(targets.supports(Feature::NullishCoalescing));
+
+        assert_eq!(
+            Targets::parse("last 2 versions"),
+            Err(TargetError::UnsupportedQuery("last 2 versions".to_string()))
+        );
+        assert_eq!(Targets::parse("opera70"), Err(TargetError::UnknownEngine("opera".to_string())));
+        assert!(matches!(Targets::parse("node x"), Err(TargetError::InvalidVersion { .. })));
+        assert_eq!(Targets::parse(" # nothing\n"), Err(TargetError::Empty));
+    }
+}
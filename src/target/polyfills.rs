@@ -0,0 +1,164 @@
+//! # Polyfill Detection
+//!
+//! Finds uses of runtime built-ins (`Promise`, `Object.assign`, …) that a set of
+//! [`Targets`] lacks. Unlike syntax these cannot be down-leveled by the compiler, so
+//! they are reported, and can be imported from a polyfill package such as `core-js`.
+//!
+//! Detection is syntactic: a global built-in (`Map`) or a static method of one
+//! (`Object.entries`) counts as used unless the file declares a binding with the same
+//! name anywhere. Prototype methods (`[].includes`) are not detected, because the
+//! receiver's type is unknown.
+
+use std::collections::{BTreeSet, HashSet};
+
+use oxc_ast::ast::{BindingIdentifier, Expression, IdentifierReference, MemberExpression, Program};
+use oxc_ast::visit::walk;
+use oxc_ast::Visit;
+
+use super::{engine_version, Engine, Targets};
+
+/// A runtime built-in that may need a polyfill
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Polyfill {
+    /// Name as written in code, e.g. `Object.assign`
+    pub name: &'static str,
+    /// Path of the polyfill below the polyfill module, e.g. `object/assign` (core-js layout)
+    pub module: &'static str,
+    /// ECMAScript edition that introduced the built-in
+    pub edition: u16,
+    /// First supporting version per engine, ordered like [`Engine::ALL`]
+    versions: [(u32, u32); 6],
+}
+
+impl Polyfill {
+    /// Returns whether every target provides the built-in natively
+    pub fn is_supported_by(&self, targets: &Targets) -> bool {
+        targets.meets(self.edition, |engine: Engine| engine_version(&self.versions, engine))
+    }
+}
+
+/// Known built-ins; versions follow the compat data used by core-js
+pub const POLYFILLS: &[Polyfill] = &[
+    Polyfill { name: "Promise", module: "promise", edition: 2015, versions: [(33, 0), (12, 0), (29, 0), (8, 0), (8, 0), (0, 12)] },
+    Polyfill { name: "Map", module: "map", edition: 2015, versions: [(38, 0), (12, 0), (13, 0), (8, 0), (8, 0), (0, 12)] },
+    Polyfill { name: "Set", module: "set", edition: 2015, versions: [(38, 0), (12, 0), (13, 0), (8, 0), (8, 0), (0, 12)] },
+    Polyfill { name: "WeakMap", module: "weak-map", edition: 2015, versions: [(36, 0), (12, 0), (6, 0), (8, 0), (8, 0), (0, 12)] },
+    Polyfill { name: "WeakSet", module: "weak-set", edition: 2015, versions: [(36, 0), (12, 0), (34, 0), (9, 0), (9, 0), (0, 12)] },
+    Polyfill { name: "Symbol", module: "symbol", edition: 2015, versions: [(38, 0), (12, 0), (36, 0), (9, 0), (9, 0), (0, 12)] },
+    Polyfill { name: "Reflect", module: "reflect", edition: 2015, versions: [(49, 0), (12, 0), (42, 0), (10, 0), (10, 0), (6, 0)] },
+    Polyfill { name: "Object.assign", module: "object/assign", edition: 2015, versions: [(45, 0), (12, 0), (34, 0), (9, 0), (9, 0), (4, 0)] },
+    Polyfill { name: "Array.from", module: "array/from", edition: 2015, versions: [(45, 0), (12, 0), (32, 0), (9, 0), (9, 0), (4, 0)] },
+    Polyfill { name: "Array.of", module: "array/of", edition: 2015, versions: [(45, 0), (12, 0), (25, 0), (9, 0), (9, 0), (4, 0)] },
+    Polyfill { name: "Number.isInteger", module: "number/is-integer", edition: 2015, versions: [(34, 0), (12, 0), (16, 0), (9, 0), (9, 0), (0, 12)] },
+    Polyfill { name: "Number.isNaN", module: "number/is-nan", edition: 2015, versions: [(25, 0), (12, 0), (15, 0), (9, 0), (9, 0), (0, 10)] },
+    Polyfill { name: "String.raw", module: "string/raw", edition: 2015, versions: [(41, 0), (12, 0), (34, 0), (9, 0), (9, 0), (4, 0)] },
+    Polyfill { name: "Object.entries", module: "object/entries", edition: 2017, versions: [(54, 0), (14, 0), (47, 0), (10, 1), (10, 3), (7, 0)] },
+    Polyfill { name: "Object.values", module: "object/values", edition: 2017, versions: [(54, 0), (14, 0), (47, 0), (10, 1), (10, 3), (7, 0)] },
+    Polyfill { name: "Object.fromEntries", module: "object/from-entries", edition: 2019, versions: [(73, 0), (79, 0), (63, 0), (12, 1), (12, 2), (12, 0)] },
+    Polyfill { name: "Promise.allSettled", module: "promise/all-settled", edition: 2020, versions: [(76, 0), (79, 0), (71, 0), (13, 0), (13, 0), (12, 9)] },
+    Polyfill { name: "globalThis", module: "global-this", edition: 2020, versions: [(71, 0), (79, 0), (65, 0), (12, 1), (12, 2), (12, 0)] },
+    Polyfill { name: "Promise.any", module: "promise/any", edition: 2021, versions: [(85, 0), (85, 0), (79, 0), (14, 0), (14, 0), (15, 0)] },
+];
+
+/// Built-ins used by `program` that at least one target lacks, in [`POLYFILLS`] order
+pub fn required_polyfills(program: &Program, targets: &Targets) -> Vec<&'static Polyfill> {
+    let mut collector = BuiltinCollector::default();
+    collector.visit_program(program);
+
+    POLYFILLS
+        .iter()
+        .enumerate()
+        .filter(|(index, polyfill)| {
+            collector.used.iter().any(|(used, root)| used == index && !collector.bound.contains(*root))
+                && !polyfill.is_supported_by(targets)
+        })
+        .map(|(_, polyfill)| polyfill)
+        .collect()
+}
+
+/// Statements importing each polyfill from `module`, one per line
+///
+/// Modules use `import "<module>/<path>";`, scripts `require("<module>/<path>");`.
+pub fn import_prelude(polyfills: &[&Polyfill], module: &str, is_module: bool) -> String {
+    let module = module.trim_end_matches('/');
+    polyfills
+        .iter()
+        .map(|polyfill| {
+            if is_module {
+                format!("import \"{}/{}\";\n", module, polyfill.module)
+            } else {
+                format!("require(\"{}/{}\");\n", module, polyfill.module)
+            }
+        })
+        .collect()
+}
+
+/// Records references to known built-ins and every name the file binds
+#[derive(Default)]
+struct BuiltinCollector {
+    /// Index into [`POLYFILLS`] with the global name the use goes through
+    used: BTreeSet<(usize, &'static str)>,
+    bound: HashSet<String>,
+}
+
+impl<'a> Visit<'a> for BuiltinCollector {
+    fn visit_binding_identifier(&mut self, it: &BindingIdentifier<'a>) {
+        self.bound.insert(it.name.to_string());
+    }
+
+    fn visit_identifier_reference(&mut self, it: &IdentifierReference<'a>) {
+        if let Some(index) = POLYFILLS.iter().position(|polyfill| polyfill.name == it.name.as_str()) {
+            self.used.insert((index, POLYFILLS[index].name));
+        }
+    }
+
+    fn visit_member_expression(&mut self, it: &MemberExpression<'a>) {
+        if let MemberExpression::StaticMemberExpression(member) = it
+            && let Expression::Identifier(object) = &member.object
+            && let Some(root) = ["Object", "Array", "Number", "String", "Promise"]
+                .into_iter()
+                .find(|root| *root == object.name.as_str())
+        {
+            let name = format!("{}.{}", root, member.property.name);
+            if let Some(index) = POLYFILLS.iter().position(|polyfill| polyfill.name == name) {
+                self.used.insert((index, root));
+            }
+        }
+        walk::walk_member_expression(self, it);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::{parse_js_in, Allocator, ParserConfig};
+
+    fn required(source: &str, targets: &str) -> Vec<&'static str> {
+        let allocator = Allocator::default();
+        let result = parse_js_in(source, "test.js", &allocator, &ParserConfig::default());
+        let targets = Targets::parse(targets).unwrap();
+        required_polyfills(&result.program, &targets).iter().map(|polyfill| polyfill.name).collect()
+    }
+
+    #[test]
+    fn test_required_polyfills_depend_on_targets() {
+        let source = "const o = Object.assign({}, Object.entries(x));\nPromise.allSettled([]);\nnew Map();\n";
+        assert_eq!(required(source, "es5"), ["Promise", "Map", "Object.assign", "Object.entries", "Promise.allSettled"]);
+        assert_eq!(required(source, "chrome60,safari10"), ["Object.entries", "Promise.allSettled"]);
+        assert!(required(source, "chrome90,node16").is_empty());
+        assert!(required(source, "latest").is_empty());
+    }
+
+    #[test]
+    fn test_local_bindings_and_prelude() {
+        // A local `Map` or `Object` shadows the built-in
+        assert!(required("function Map() {}\nnew Map();\nlet Object = {};\nObject.assign(a);\n", "es5").is_empty());
+
+        let polyfills = [&POLYFILLS[0], &POLYFILLS[7]];
+        assert_eq!(
+            import_prelude(&polyfills, "core-js/es/", true),
+            "import \"core-js/es/promise\";\nimport \"core-js/es/object/assign\";\n"
+        );
+        assert_eq!(import_prelude(&polyfills[..1], "core-js/es", false), "require(\"core-js/es/promise\");\n");
+    }
+}
@@ -0,0 +1,211 @@
+//! # Statement Cleanup
+//!
+//! Removes statements that do nothing and the structure left behind by other passes:
+//!
+//! - empty statements (`;`) in statement lists
+//! - expression statements that only evaluate a literal, such as `void 0;` or `"x";`,
+//!   except a `"use strict"` directive at the start of a statement list
+//! - blocks nested in a statement list, whose statements move into the list, unless they
+//!   declare `let`, `const`, class or function bindings scoped to the block
+//! - `debugger` statements, when [`TransformerConfig::drop_debugger`] is set
+//!
+//! Empty statements that are the body of an `if` or a loop are kept, since the
+//! statement is required there; a dropped `debugger` in that position becomes one.
+
+use crate::analyzer::SemanticAnalysis;
+use crate::parser::ast_types::{
+    ArrowFunctionBody, ClassElement, Expression, Program, Statement, UnaryOperator,
+};
+use crate::transformer::if_return::declares_lexically;
+use crate::transformer::plugin::{self, PluginContext, TransformPlugin};
+use crate::transformer::{TransformResult, TransformerConfig};
+
+/// Result of statement cleanup
+#[derive(Debug, Clone)]
+pub struct CleanupResult {
+    /// Number of statements removed and blocks flattened
+    pub removed_count: u32,
+    /// Any warnings generated during cleanup
+    pub warnings: Vec<String>,
+}
+
+/// Removes empty and no-op statements and flattens nested blocks
+///
+/// # Arguments
+///
+/// * `ast` - The AST to transform (modified in place)
+/// * `analysis` - Semantic analysis of the program
+/// * `config` - Transformer configuration
+///
+/// # Returns
+///
+/// Returns `CleanupResult` with the number of removed statements
+pub fn cleanup_statements(
+    ast: &mut Program,
+    analysis: &SemanticAnalysis,
+    config: &TransformerConfig,
+) -> TransformResult<CleanupResult> {
+    let mut cleaner = StatementCleaner {
+        drop_debugger: config.drop_debugger,
+        removed:       0,
+    };
+    let mut ctx = PluginContext::new(analysis, config);
+    plugin::run_plugin(&mut cleaner, ast, &mut ctx)?;
+
+    if cleaner.removed > 0 {
+        tracing::debug!("🧹 Cleaned up {} statements", cleaner.removed);
+    }
+
+    Ok(CleanupResult {
+        removed_count: cleaner.removed,
+        warnings: Vec::new(),
+    })
+}
+
+struct StatementCleaner {
+    drop_debugger: bool,
+    removed:       u32,
+}
+
+impl TransformPlugin for StatementCleaner {
+    fn name(&self) -> &str {
+        "cleanup"
+    }
+
+    fn enter_program(&mut self, program: &mut Program, _ctx: &mut PluginContext) -> TransformResult<()> {
+        self.clean_list(&mut program.body);
+        Ok(())
+    }
+
+    fn enter_statement(&mut self, stmt: &mut Statement, _ctx: &mut PluginContext) -> TransformResult<()> {
+        match stmt {
+            Statement::BlockStatement { body, .. } => self.clean_list(body),
+            Statement::FunctionDeclaration { body, .. } => self.clean_list(&mut body.body),
+            Statement::ClassDeclaration { body, .. } => {
+                for element in &mut body.body {
+                    if let ClassElement::MethodDefinition { value, .. } = element {
+                        self.clean_list(&mut value.body.body);
+                    }
+                }
+            }
+            Statement::IfStatement { consequent, alternate, .. } => {
+                self.clean_branch(consequent);
+                if let Some(alternate) = alternate {
+                    self.clean_branch(alternate);
+                }
+            }
+            Statement::WhileStatement { body, .. }
+            | Statement::ForStatement { body, .. }
+            | Statement::ForOfStatement { body, .. } => self.clean_branch(body),
+            _ => {}
+        }
+        Ok(())
+    }
+
+    fn enter_expression(&mut self, expr: &mut Expression, _ctx: &mut PluginContext) -> TransformResult<()> {
+        match expr {
+            Expression::FunctionExpression(function) => self.clean_list(&mut function.body.body),
+            Expression::ArrowFunctionExpression { body: ArrowFunctionBody::BlockStatement(block), .. } => {
+                self.clean_list(&mut block.body)
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+}
+
+impl StatementCleaner {
+    /// Removes no-op statements from `statements` and splices in nested blocks
+    fn clean_list(&mut self, statements: &mut Vec<Statement>) {
+        let mut cleaned = Vec::with_capacity(statements.len());
+        let prologue = statements.iter().take_while(|statement| statement.directive().is_some()).count();
+        for (index, statement) in std::mem::take(statements).into_iter().enumerate() {
+            if index < prologue && statement.directive() == Some("use strict") {
+                cleaned.push(statement);
+                continue;
+            }
+            match statement {
+                Statement::BlockStatement { mut body, .. } if !declares_lexically(&body) => {
+                    self.clean_list(&mut body);
+                    cleaned.extend(body);
+                    self.removed += 1;
+                }
+                statement if self.is_removable(&statement) => self.removed += 1,
+                statement => cleaned.push(statement),
+            }
+        }
+        *statements = cleaned;
+    }
+
+    /// Replaces a dropped `debugger` that is the body of an `if` or loop
+    fn clean_branch(&mut self, branch: &mut Statement) {
+        if self.drop_debugger && matches!(branch, Statement::DebuggerStatement) {
+            *branch = Statement::EmptyStatement;
+            self.removed += 1;
+        }
+    }
+
+    /// Whether `statement` can be removed from a statement list
+    fn is_removable(&self, statement: &Statement) -> bool {
+        match statement {
+            Statement::EmptyStatement => true,
+            Statement::DebuggerStatement => self.drop_debugger,
+            Statement::ExpressionStatement { expression, .. } => is_literal_noop(expression),
+            _ => false,
+        }
+    }
+}
+
+/// Whether evaluating `expression` only produces a literal value
+fn is_literal_noop(expression: &Expression) -> bool {
+    match expression {
+        Expression::Literal(_) => true,
+        Expression::UnaryExpression { operator: UnaryOperator::Void, argument, .. } => {
+            matches!(argument.as_ref(), Expression::Literal(_))
+        }
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::transformer::tests::run_pass;
+
+    /// Cleans up, returning the output and the removal count
+    fn cleanup_source(source: &str, config: &TransformerConfig) -> (String, u32) {
+        let (code, result) = run_pass(source, config, cleanup_statements);
+        (code, result.removed_count)
+    }
+
+    #[test]
+    fn test_removes_empty_statements_and_flattens_blocks() {
+        let (code, removed) = cleanup_source(
+            "function f(x) { ; if (x) ; { var a = 1; { g(a); } } void 0; 1; { let b = 2; g(b); } debugger; }",
+            &TransformerConfig::default(),
+        );
+
+        assert_eq!(removed, 5);
+        assert_eq!(code, "function f(x){if(x);var a=1;g(a);{let b=2;g(b);}debugger;}");
+    }
+
+    #[test]
+    fn test_keeps_use_strict_directives() {
+        let (code, removed) = cleanup_source(
+            "'use strict'; 'use asm'; f(); function g() { 'use strict'; 'use strict'; g(); 'use strict'; }",
+            &TransformerConfig::default(),
+        );
+
+        assert_eq!(removed, 2);
+        assert_eq!(code, "'use strict';f();function g(){'use strict';'use strict';g();}");
+    }
+
+    #[test]
+    fn test_drop_debugger() {
+        let config = TransformerConfig::default().with_drop_debugger(true);
+        let (code, removed) = cleanup_source("debugger; function f(x) { if (x) debugger; debugger; return x; }", &config);
+
+        assert_eq!(removed, 3);
+        assert_eq!(code, "function f(x){if(x);return x;}");
+    }
+}
@@ -0,0 +1,448 @@
+//! # Collapse Variables
+//!
+//! Inlines the initializer of a variable that is read exactly once into that read, when
+//! the read is in the statement right after the declaration: `const t = f(); g(t)`
+//! becomes `g(f())`.
+//!
+//! The analyzer's references decide which variables qualify: a single symbol with the
+//! name, one read and no other references, not captured by a closure, exported or kept.
+//! The read must be evaluated unconditionally and exactly once by the next statement, so
+//! reads behind `&&`, `?:`, in loop bodies or in nested functions are left alone. The
+//! initializer then moves past everything the statement evaluates before the read,
+//! which is only allowed when the two can be reordered (see
+//! [`Purity`](crate::analyzer::purity::Purity)) and nothing in between can suspend at
+//! a `yield` or `await`. When in doubt the declaration is kept.
+
+use std::collections::{HashMap, HashSet};
+
+use crate::analyzer::purity::Purity;
+use crate::analyzer::{ReferenceType, SemanticAnalysis, Symbol, SymbolType};
+use crate::interner::Name;
+use crate::parser::ast_types::{
+    ArrowFunctionBody, BinaryOperator, ClassElement, Expression, ForInit, ObjectProperty, Pattern, Program, Statement,
+    UnaryOperator, VariableDeclarator,
+};
+use crate::transformer::plugin::{self, PluginContext, TransformPlugin};
+use crate::transformer::{TransformResult, TransformerConfig};
+
+/// Result of collapsing variables
+#[derive(Debug, Clone)]
+pub struct CollapseVarsResult {
+    /// Number of declarations inlined into their use
+    pub collapsed_count: u32,
+    /// Any warnings generated while collapsing
+    pub warnings: Vec<String>,
+}
+
+/// Collapses single-use variables into the statement that uses them
+///
+/// # Arguments
+///
+/// * `ast` - The AST to transform (modified in place)
+/// * `analysis` - Semantic analysis of the program
+/// * `config` - Transformer configuration
+///
+/// # Returns
+///
+/// Returns `CollapseVarsResult` with the number of collapsed declarations
+pub fn collapse_variables(
+    ast: &mut Program,
+    analysis: &SemanticAnalysis,
+    config: &TransformerConfig,
+) -> TransformResult<CollapseVarsResult> {
+    let mut symbols_by_name: HashMap<Name, Vec<&Symbol>> = HashMap::new();
+    for symbol in analysis.symbol_table.symbols.values() {
+        symbols_by_name.entry(symbol.name).or_default().push(symbol);
+    }
+
+    let mut collapser = Collapser {
+        candidates: HashSet::new(),
+        stable:     HashSet::new(),
+        collapsed:  0,
+    };
+    for (&name, symbols) in &symbols_by_name {
+        let name = analysis.symbol_table.names.resolve(name);
+        if symbols.iter().all(|symbol| !references(symbol).iter().any(|kind| matches!(kind, ReferenceType::Write))) {
+            collapser.stable.insert(name.to_string());
+        }
+        if let [symbol] = symbols.as_slice()
+            && matches!(symbol.symbol_type, SymbolType::Variable { .. })
+            && matches!(references(symbol).as_slice(), [ReferenceType::Read])
+            && symbol.is_renamable
+            && !symbol.is_captured
+            && !symbol.is_exported
+            && !symbol.is_kept
+        {
+            collapser.candidates.insert(name.to_string());
+        }
+    }
+
+    if !collapser.candidates.is_empty() {
+        let mut ctx = PluginContext::new(analysis, config);
+        plugin::run_plugin(&mut collapser, ast, &mut ctx)?;
+    }
+
+    if collapser.collapsed > 0 {
+        tracing::debug!("🧲 Collapsed {} single-use variables", collapser.collapsed);
+    }
+
+    Ok(CollapseVarsResult {
+        collapsed_count: collapser.collapsed,
+        warnings: Vec::new(),
+    })
+}
+
+/// References of a symbol, without the property-name identifiers recorded under its name
+fn references(symbol: &Symbol) -> Vec<&ReferenceType> {
+    symbol
+        .references
+        .iter()
+        .filter(|reference| !matches!(reference.reference_type, ReferenceType::PropertyAccess))
+        .map(|reference| &reference.reference_type)
+        .collect()
+}
+
+/// Collapses declarations in every statement list of the program
+struct Collapser {
+    /// Variables with a single read and no other references
+    candidates: HashSet<String>,
+    /// Names that are never assigned after their declaration
+    stable:     HashSet<String>,
+    collapsed:  u32,
+}
+
+impl TransformPlugin for Collapser {
+    fn name(&self) -> &str {
+        "collapse_vars"
+    }
+
+    fn enter_program(&mut self, program: &mut Program, ctx: &mut PluginContext) -> TransformResult<()> {
+        self.collapse_list(&mut program.body, ctx.analysis);
+        Ok(())
+    }
+
+    fn enter_statement(&mut self, stmt: &mut Statement, ctx: &mut PluginContext) -> TransformResult<()> {
+        match stmt {
+            Statement::BlockStatement { body, .. } => self.collapse_list(body, ctx.analysis),
+            Statement::FunctionDeclaration { body, .. } => self.collapse_list(&mut body.body, ctx.analysis),
+            Statement::ClassDeclaration { body, .. } => {
+                for element in &mut body.body {
+                    if let ClassElement::MethodDefinition { value, .. } = element {
+                        self.collapse_list(&mut value.body.body, ctx.analysis);
+                    }
+                }
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    fn enter_expression(&mut self, expr: &mut Expression, ctx: &mut PluginContext) -> TransformResult<()> {
+        match expr {
+            Expression::FunctionExpression(function) => self.collapse_list(&mut function.body.body, ctx.analysis),
+            Expression::ArrowFunctionExpression { body: ArrowFunctionBody::BlockStatement(block), .. } => {
+                self.collapse_list(&mut block.body, ctx.analysis)
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+}
+
+impl Collapser {
+    /// Collapses each qualifying declaration into the statement after it
+    fn collapse_list(&mut self, statements: &mut Vec<Statement>, analysis: &SemanticAnalysis) {
+        let mut index = 0;
+        while index + 1 < statements.len() {
+            let Some((name, init)) = self.candidate(&statements[index]) else {
+                index += 1;
+                continue;
+            };
+
+            let mut prefix = Vec::new();
+            if !find_in_statement(&statements[index + 1], name, &mut prefix)
+                || !self.can_move_past(init, &prefix, analysis)
+            {
+                index += 1;
+                continue;
+            }
+
+            let name = name.to_string();
+            let Statement::VariableDeclaration { mut declarations, .. } = statements.remove(index) else {
+                unreachable!("candidate is a variable declaration");
+            };
+            let mut init = declarations.pop().and_then(|declarator| declarator.init);
+            replace_in_statement(&mut statements[index], &name, &mut init);
+            debug_assert!(init.is_none(), "the checked read is replaced");
+            self.collapsed += 1;
+            // The previous declaration's use may now be in the statement that moved up
+            index = index.saturating_sub(1);
+        }
+    }
+
+    /// Name and initializer of a single-declarator declaration of a candidate
+    ///
+    /// `using` declarations are never candidates, since their value is disposed of when
+    /// the block ends.
+    fn candidate<'a>(&self, statement: &'a Statement) -> Option<(&'a str, &'a Expression)> {
+        match statement {
+            Statement::VariableDeclaration { declarations, kind, .. } if !kind.is_using() => match declarations.as_slice() {
+                [VariableDeclarator { id: Pattern::Identifier(id), init: Some(init), .. }]
+                    if self.candidates.contains(&id.name) =>
+                {
+                    Some((&id.name, init))
+                }
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+
+    /// Whether `init` can be evaluated after `prefix` instead of before it
+    fn can_move_past(&self, init: &Expression, prefix: &[&Expression], analysis: &SemanticAnalysis) -> bool {
+        if prefix.iter().any(|expression| expression.may_suspend()) {
+            return false;
+        }
+        let before = prefix.iter().fold(Purity::Pure, |purity, expression| {
+            purity.join(match expression {
+                // Reading a binding nothing assigns can't observe the initializer's effects
+                Expression::Identifier(id) if self.stable.contains(&id.name) => Purity::Pure,
+                _ => analysis.expression_purity(expression),
+            })
+        });
+        let moved = analysis.expression_purity(init);
+        before.can_reorder() || moved.can_reorder() || (before.is_removable() && moved.is_removable())
+    }
+}
+
+/// Finds the read of `name` among the parts of `statement` evaluated first and exactly
+/// once, collecting the expressions evaluated before it
+fn find_in_statement<'a>(statement: &'a Statement, name: &str, prefix: &mut Vec<&'a Expression>) -> bool {
+    match statement {
+        Statement::ExpressionStatement { expression, .. } => find_in_expression(expression, name, prefix),
+        Statement::ReturnStatement { argument: Some(argument), .. } => find_in_expression(argument, name, prefix),
+        Statement::IfStatement { test, .. } => find_in_expression(test, name, prefix),
+        Statement::ForOfStatement { right, .. } => find_in_expression(right, name, prefix),
+        Statement::VariableDeclaration { declarations, .. } => find_in_declarators(declarations, name, prefix),
+        Statement::ForStatement { init: Some(ForInit::Expression(init)), .. } => find_in_expression(init, name, prefix),
+        Statement::ForStatement { init: Some(ForInit::VariableDeclaration { declarations, .. }), .. } => {
+            find_in_declarators(declarations, name, prefix)
+        }
+        _ => false,
+    }
+}
+
+fn find_in_declarators<'a>(declarations: &'a [VariableDeclarator], name: &str, prefix: &mut Vec<&'a Expression>) -> bool {
+    for declarator in declarations {
+        // Destructuring reads properties and may run defaults before later declarators
+        let Some(init) = &declarator.init else {
+            continue;
+        };
+        if find_in_expression(init, name, prefix) {
+            return true;
+        }
+        if !matches!(declarator.id, Pattern::Identifier(_)) {
+            return false;
+        }
+        prefix.push(init);
+    }
+    false
+}
+
+/// Evaluation-order search through the unconditionally evaluated parts of `expression`
+fn find_in_expression<'a>(expression: &'a Expression, name: &str, prefix: &mut Vec<&'a Expression>) -> bool {
+    let in_order = |parts: &[&'a Expression], prefix: &mut Vec<&'a Expression>| {
+        for part in parts {
+            if find_in_expression(part, name, prefix) {
+                return true;
+            }
+            prefix.push(part);
+        }
+        false
+    };
+    match expression {
+        Expression::Identifier(id) => id.name == name,
+        Expression::CallExpression { callee, arguments, optional: false } => {
+            // A read in callee position would become a method call with a different `this`
+            if matches!(callee.as_ref(), Expression::Identifier(id) if id.name == name) {
+                return false;
+            }
+            let parts: Vec<&Expression> = std::iter::once(callee.as_ref()).chain(arguments).collect();
+            in_order(&parts, prefix)
+        }
+        Expression::NewExpression { callee, arguments } => {
+            let parts: Vec<&Expression> = std::iter::once(callee.as_ref()).chain(arguments).collect();
+            in_order(&parts, prefix)
+        }
+        Expression::MemberExpression { object, property, computed, optional: false } => {
+            if *computed {
+                in_order(&[object, property], prefix)
+            } else {
+                in_order(&[object], prefix)
+            }
+        }
+        Expression::BinaryExpression { left, operator, right } => {
+            if matches!(operator, BinaryOperator::LogicalAnd | BinaryOperator::LogicalOr | BinaryOperator::NullishCoalescing) {
+                in_order(&[left], prefix)
+            } else {
+                in_order(&[left, right], prefix)
+            }
+        }
+        Expression::UnaryExpression { operator, argument, .. } if !matches!(operator, UnaryOperator::Delete) => {
+            in_order(&[argument], prefix)
+        }
+        Expression::AssignmentExpression { left, right, .. } => match left.as_ref() {
+            Expression::Identifier(_) => in_order(&[right], prefix),
+            Expression::MemberExpression { object, property, computed, .. } => {
+                if *computed {
+                    in_order(&[object, property, right], prefix)
+                } else {
+                    in_order(&[object, right], prefix)
+                }
+            }
+            _ => false,
+        },
+        Expression::ConditionalExpression { test, .. } => in_order(&[test], prefix),
+        Expression::ArrayExpression { elements } => {
+            let parts: Vec<&Expression> = elements.iter().flatten().collect();
+            in_order(&parts, prefix)
+        }
+        Expression::ObjectExpression { properties } => {
+            let parts: Vec<&Expression> = properties
+                .iter()
+                .map(|property| match property {
+                    ObjectProperty::Property { value, .. } => value,
+                    ObjectProperty::SpreadElement { argument } => argument,
+                })
+                .collect();
+            in_order(&parts, prefix)
+        }
+        Expression::TemplateLiteral { expressions, .. } => {
+            let parts: Vec<&Expression> = expressions.iter().collect();
+            in_order(&parts, prefix)
+        }
+        Expression::SpreadElement { argument } | Expression::AwaitExpression { argument } => {
+            in_order(&[argument], prefix)
+        }
+        _ => false,
+    }
+}
+
+/// Replaces the read found by [`find_in_statement`] with `init`, taking it
+fn replace_in_statement(statement: &mut Statement, name: &str, init: &mut Option<Expression>) -> bool {
+    match statement {
+        Statement::ExpressionStatement { expression, .. } => replace_in_expression(expression, name, init),
+        Statement::ReturnStatement { argument: Some(argument), .. } => replace_in_expression(argument, name, init),
+        Statement::IfStatement { test, .. } => replace_in_expression(test, name, init),
+        Statement::ForOfStatement { right, .. } => replace_in_expression(right, name, init),
+        Statement::VariableDeclaration { declarations, .. }
+        | Statement::ForStatement { init: Some(ForInit::VariableDeclaration { declarations, .. }), .. } => declarations
+            .iter_mut()
+            .filter_map(|declarator| declarator.init.as_mut())
+            .any(|expression| replace_in_expression(expression, name, init)),
+        Statement::ForStatement { init: Some(ForInit::Expression(expression)), .. } => {
+            replace_in_expression(expression, name, init)
+        }
+        _ => false,
+    }
+}
+
+/// Mirrors [`find_in_expression`]; the variable has a single read, so the first match is it
+fn replace_in_expression(expression: &mut Expression, name: &str, init: &mut Option<Expression>) -> bool {
+    if let Expression::Identifier(id) = expression
+        && id.name == name
+    {
+        *expression = init.take().expect("initializer is only taken once");
+        return true;
+    }
+    let mut in_order = |parts: Vec<&mut Expression>| parts.into_iter().any(|part| replace_in_expression(part, name, init));
+    match expression {
+        Expression::CallExpression { callee, arguments, optional: false } | Expression::NewExpression { callee, arguments } => {
+            in_order(std::iter::once(callee.as_mut()).chain(arguments.iter_mut()).collect())
+        }
+        Expression::MemberExpression { object, property, computed, optional: false } => {
+            if *computed {
+                in_order(vec![object, property])
+            } else {
+                in_order(vec![object])
+            }
+        }
+        Expression::BinaryExpression { left, operator, right } => {
+            if matches!(operator, BinaryOperator::LogicalAnd | BinaryOperator::LogicalOr | BinaryOperator::NullishCoalescing) {
+                in_order(vec![left])
+            } else {
+                in_order(vec![left, right])
+            }
+        }
+        Expression::UnaryExpression { operator, argument, .. } if !matches!(operator, UnaryOperator::Delete) => {
+            in_order(vec![argument])
+        }
+        Expression::AssignmentExpression { left, right, .. } => match left.as_mut() {
+            Expression::Identifier(_) => in_order(vec![right]),
+            Expression::MemberExpression { object, property, computed, .. } => {
+                if *computed {
+                    in_order(vec![object, property, right])
+                } else {
+                    in_order(vec![object, right])
+                }
+            }
+            _ => false,
+        },
+        Expression::ConditionalExpression { test, .. } => in_order(vec![test]),
+        Expression::ArrayExpression { elements } => in_order(elements.iter_mut().flatten().collect()),
+        Expression::ObjectExpression { properties } => in_order(
+            properties
+                .iter_mut()
+                .map(|property| match property {
+                    ObjectProperty::Property { value, .. } => value,
+                    ObjectProperty::SpreadElement { argument } => argument,
+                })
+                .collect(),
+        ),
+        Expression::TemplateLiteral { expressions, .. } => in_order(expressions.iter_mut().collect()),
+        Expression::SpreadElement { argument } | Expression::AwaitExpression { argument } => in_order(vec![argument]),
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::transformer::tests::run_pass;
+
+    /// Collapses variables, returning the output and the collapse count
+    fn collapse_source(source: &str) -> (String, u32) {
+        let (code, result) = run_pass(source, &TransformerConfig::default(), collapse_variables);
+        (code, result.collapsed_count)
+    }
+
+    #[test]
+    fn test_collapses_single_use_into_next_statement() {
+        let (code, collapsed) = collapse_source(
+            "function run(f, g) { const t = f(); g(t); const a = f(); const b = a + 1; return b; }",
+        );
+
+        assert_eq!(collapsed, 3);
+        assert!(code.contains("g(f())"), "{code}");
+        assert!(code.contains("return f()+1"), "{code}");
+        assert!(!code.contains("const"), "{code}");
+    }
+
+    #[test]
+    fn test_keeps_multi_use_and_deferred_reads() {
+        let (code, collapsed) = collapse_source(
+            "function run(f, g, c) { const t = f(); g(t, t); const u = f(); c && g(u); const v = f(); return () => v; }",
+        );
+
+        assert_eq!(collapsed, 0, "{code}");
+    }
+
+    #[test]
+    fn test_keeps_order_of_side_effects() {
+        let (code, collapsed) = collapse_source(
+            "function run(f, g, h) { const t = f(); g(h(), t); const u = f(); const k = 1; g(u); const m = f(); o.x(m); }",
+        );
+
+        assert_eq!(collapsed, 0, "{code}");
+    }
+}
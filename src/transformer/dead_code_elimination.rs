@@ -4,14 +4,27 @@
 //! It removes unreachable code, unused variables, and redundant branches while preserving
 //! program semantics.
 //!
+//! Function declarations are removed when no code outside of them can reach them
+//! through the names it mentions, so groups of functions that only call each other are
+//! removed together. Methods and static members of classes that never escape are
+//! removed the same way when nothing reaches their name.
+//!
 //! ## Test-Driven Development Approach
 //!
 //! Following the TDD methodology, this module starts with comprehensive test cases that
 //! define the expected behavior for various dead code scenarios.
 
-use crate::analyzer::SymbolTable;
-use crate::parser::ast_types::Program;
-use crate::transformer::{TransformError, TransformResult, TransformerConfig};
+use std::collections::{HashMap, HashSet};
+
+use serde_json::Value;
+
+use crate::analyzer::escape::static_key_name;
+use crate::analyzer::{SemanticAnalysis, UnsafeReason};
+use crate::parser::ast_types::{
+    ArrowFunctionBody, ClassElement, Expression, MethodKind, Program, ProgramSourceType, Statement,
+};
+use crate::transformer::plugin::{self, PluginContext, TransformPlugin};
+use crate::transformer::{TransformResult, TransformerConfig};
 
 /// Result of dead code elimination operation
 #[derive(Debug, Clone)]
@@ -24,10 +37,15 @@ pub struct DeadCodeEliminationResult {
 
 /// Eliminates dead code from the given AST
 ///
+/// Calls whose result is unused are removed when the analyzer classifies them as
+/// free of side effects (see [`crate::analyzer::purity`]), or when the callee matches one
+/// of [`TransformerConfig::drop_calls`]. Dropped calls keep their arguments' side effects
+/// as separate statements when [`TransformerConfig::keep_dropped_call_arguments`] is set.
+///
 /// # Arguments
 ///
 /// * `ast` - The AST to transform (modified in place)
-/// * `symbol_table` - Symbol table from semantic analysis
+/// * `analysis` - Semantic analysis of the program
 /// * `config` - Transformer configuration
 ///
 /// # Returns
@@ -38,40 +56,583 @@ pub struct DeadCodeEliminationResult {
 ///
 /// Returns `TransformError::DeadCodeEliminationError` if elimination fails
 pub fn eliminate_dead_code(
-    _ast: &mut Program,
-    symbol_table: &SymbolTable,
+    ast: &mut Program,
+    analysis: &SemanticAnalysis,
     config: &TransformerConfig,
 ) -> TransformResult<DeadCodeEliminationResult> {
-    if config.verbose {
-        println!("🔍 Analyzing statements for dead code");
+    tracing::debug!("🔍 Analyzing statements for dead code");
+
+    let filter = CallFilter {
+        analysis,
+        drop_calls: &config.drop_calls,
+        keep_arguments: config.keep_dropped_call_arguments,
+    };
+    let mut removed_count = remove_unused_pure_calls(&mut ast.body, &filter);
+    if removed_count > 0 {
+        tracing::debug!("🗑️  Removed {} unused pure or dropped calls", removed_count);
+    }
+
+    let (functions_removed, members_removed) = remove_unreachable_declarations(ast, analysis, config)?;
+    if functions_removed > 0 {
+        tracing::debug!("🗑️  Removed {} unreachable functions", functions_removed);
     }
+    if members_removed > 0 {
+        tracing::debug!("🗑️  Removed {} unused class members", members_removed);
+    }
+    removed_count += functions_removed + members_removed;
 
-    // TODO: Implement actual dead code elimination logic
-    // For now, return a placeholder result
+    // TODO: Remove unreachable code, unused variables and redundant branches
     Ok(DeadCodeEliminationResult {
-        removed_count: 0,
+        removed_count,
         warnings: vec!["Dead code elimination not yet fully implemented".to_string()],
     })
 }
 
+/// Decides which unused calls are removed
+struct CallFilter<'a> {
+    analysis:       &'a SemanticAnalysis,
+    /// Callee patterns such as `console.*` or `assert` whose calls are always dropped
+    drop_calls:     &'a [String],
+    /// Keep the side effects of a dropped call's arguments
+    keep_arguments: bool,
+}
+
+impl CallFilter<'_> {
+    /// Statements that replace an unused call, or `None` to keep it
+    fn replacement(&self, expression: &Expression) -> Option<Vec<Statement>> {
+        if !is_call(expression) {
+            return None;
+        }
+        if self.analysis.expression_purity(expression).is_removable() {
+            return Some(Vec::new());
+        }
+        let (callee, arguments) = call_parts(expression)?;
+        let path = callee_path(callee)?;
+        if !self.drop_calls.iter().any(|pattern| matches_pattern(pattern, &path)) {
+            return None;
+        }
+        if !self.keep_arguments {
+            return Some(Vec::new());
+        }
+        let kept = arguments
+            .iter()
+            .filter(|argument| !self.analysis.expression_purity(argument).is_removable())
+            .map(|argument| {
+                let expression = match argument {
+                    // Spreading iterates the argument, which an array literal still does
+                    Expression::SpreadElement { .. } => Expression::ArrayExpression { elements: vec![Some(argument.clone())] },
+                    _ => argument.clone(),
+                };
+                Statement::ExpressionStatement { expression, span: None }
+            })
+            .collect();
+        Some(kept)
+    }
+}
+
+/// Removes expression statements that only make a side-effect free or dropped call, at
+/// any depth
+fn remove_unused_pure_calls(statements: &mut Vec<Statement>, filter: &CallFilter) -> u32 {
+    let mut removed_count = 0;
+    let mut kept = Vec::with_capacity(statements.len());
+    for statement in std::mem::take(statements) {
+        let replacement = match &statement {
+            Statement::ExpressionStatement { expression, .. } => filter.replacement(expression),
+            _ => None,
+        };
+        match replacement {
+            Some(replacement) => {
+                kept.extend(replacement);
+                removed_count += 1;
+            }
+            None => kept.push(statement),
+        }
+    }
+    *statements = kept;
+
+    for statement in statements {
+        removed_count += remove_in_statement(statement, filter);
+    }
+    removed_count
+}
+
+/// Removes unused pure calls from the statement lists nested in `statement`
+fn remove_in_statement(statement: &mut Statement, filter: &CallFilter) -> u32 {
+    match statement {
+        Statement::FunctionDeclaration { body, .. } => remove_unused_pure_calls(&mut body.body, filter),
+        Statement::BlockStatement { body, .. } => remove_unused_pure_calls(body, filter),
+        Statement::IfStatement { consequent, alternate, .. } => {
+            let alternate = alternate.as_mut().map_or(0, |alternate| remove_in_statement(alternate, filter));
+            remove_in_statement(consequent, filter) + alternate
+        }
+        Statement::WhileStatement { body, .. }
+        | Statement::ForStatement { body, .. }
+        | Statement::ForOfStatement { body, .. } => remove_in_statement(body, filter),
+        Statement::ExportNamedDeclaration { declaration: Some(declaration), .. }
+        | Statement::ExportDefaultDeclaration { declaration, .. } => remove_in_statement(declaration, filter),
+        Statement::VariableDeclaration { declarations, .. } => declarations
+            .iter_mut()
+            .filter_map(|declarator| declarator.init.as_mut())
+            .map(|init| match init {
+                Expression::FunctionExpression(function) => remove_unused_pure_calls(&mut function.body.body, filter),
+                Expression::ArrowFunctionExpression { body: ArrowFunctionBody::BlockStatement(block), .. } => {
+                    remove_unused_pure_calls(&mut block.body, filter)
+                }
+                _ => 0,
+            })
+            .sum(),
+        _ => 0,
+    }
+}
+
+/// Names mentioned by the program, split between removable declarations and the rest
+///
+/// Removable declarations are function declarations and the removable members of local
+/// classes (see [`removable_member`]), both keyed by name.
+struct MentionGraph<'a> {
+    analysis:       &'a SemanticAnalysis,
+    /// Classes whose removable members are declarations of their own
+    local_classes:  HashSet<String>,
+    /// Names mentioned outside of any removable declaration
+    roots:          HashSet<String>,
+    /// Names mentioned by the parameters, body or value of the declarations with each name
+    declarations:   HashMap<String, HashSet<String>>,
+    /// Whether a computed member access may reach any member name
+    dynamic_access: bool,
+}
+
+impl<'a> MentionGraph<'a> {
+    fn new(analysis: &'a SemanticAnalysis, local_classes: HashSet<String>) -> Self {
+        Self {
+            analysis,
+            local_classes,
+            roots: HashSet::new(),
+            declarations: HashMap::new(),
+            dynamic_access: false,
+        }
+    }
+
+    /// Records the names mentioned in the JSON form of a node, on behalf of `owner`
+    ///
+    /// String literals count as names too, since `o["name"]` reaches a member.
+    fn collect(&mut self, node: &Value, owner: Option<&str>) {
+        match node {
+            Value::Object(fields) => {
+                let node_type = fields.get("type").and_then(Value::as_str);
+                let name = fields.get("id").and_then(|id| id.get("name")).and_then(Value::as_str);
+                if node_type == Some("FunctionDeclaration") && let Some(name) = name {
+                    self.declarations.entry(name.to_string()).or_default();
+                    for (key, value) in fields {
+                        if key != "id" {
+                            self.collect(value, Some(name));
+                        }
+                    }
+                    return;
+                }
+                if node_type == Some("ClassDeclaration") && name.is_some_and(|name| self.local_classes.contains(name)) {
+                    for (key, value) in fields {
+                        match value.get("body").and_then(Value::as_array) {
+                            Some(elements) if key == "body" => elements.iter().for_each(|element| self.member(element, owner)),
+                            _ => self.collect(value, owner),
+                        }
+                    }
+                    return;
+                }
+                if node_type == Some("MemberExpression")
+                    && fields.get("computed") == Some(&Value::Bool(true))
+                    && fields.get("property").and_then(|property| property.get("kind")).and_then(Value::as_str) != Some("String")
+                {
+                    self.dynamic_access = true;
+                }
+
+                let mentioned = match fields.get("name") {
+                    Some(Value::String(name)) => Some(name),
+                    _ if fields.get("kind").and_then(Value::as_str) == Some("String") => match fields.get("value") {
+                        Some(Value::String(value)) => Some(value),
+                        _ => None,
+                    },
+                    _ => None,
+                };
+                if let Some(name) = mentioned {
+                    let mentions = match owner {
+                        Some(owner) => self.declarations.entry(owner.to_string()).or_default(),
+                        None => &mut self.roots,
+                    };
+                    mentions.insert(name.clone());
+                }
+                fields.values().for_each(|child| self.collect(child, owner));
+            }
+            Value::Array(items) => items.iter().for_each(|item| self.collect(item, owner)),
+            _ => {}
+        }
+    }
+
+    /// Records a member of a local class, as a declaration of its own if it is removable
+    fn member(&mut self, element: &Value, owner: Option<&str>) {
+        let removable = serde_json::from_value::<ClassElement>(element.clone())
+            .ok()
+            .and_then(|element| removable_member(&element, self.analysis).map(str::to_string));
+        let Some(name) = removable else {
+            self.collect(element, owner);
+            return;
+        };
+        self.declarations.entry(name.clone()).or_default();
+        if let Value::Object(fields) = element {
+            for (key, value) in fields {
+                if key != "key" {
+                    self.collect(value, Some(&name));
+                }
+            }
+        }
+    }
+
+    /// Declared names that no root reaches, following what each declaration mentions
+    fn unreachable(&self, entry_points: HashSet<String>) -> HashSet<String> {
+        let mut reachable: HashSet<&str> = self.roots.iter().map(String::as_str).collect();
+        reachable.extend(entry_points.iter().map(String::as_str));
+        let mut pending: Vec<&str> = reachable.iter().copied().collect();
+        while let Some(name) = pending.pop() {
+            for mentioned in self.declarations.get(name).into_iter().flatten() {
+                if reachable.insert(mentioned) {
+                    pending.push(mentioned);
+                }
+            }
+        }
+        self.declarations.keys().filter(|name| !reachable.contains(name.as_str())).cloned().collect()
+    }
+}
+
+/// Member names the language or common host APIs call without naming them in the program
+const IMPLICITLY_CALLED_MEMBERS: &[&str] = &[
+    "constructor",
+    "toString",
+    "valueOf",
+    "toJSON",
+    "toLocaleString",
+    "then",
+    "next",
+    "return",
+    "throw",
+    "handleEvent",
+    // Computed keys are converted to this placeholder name, so their real name is unknown
+    "computed",
+];
+
+/// Name of a class member that can be removed when unused: a public method, accessor or
+/// static member with a static name, whose definition has no side effects
+fn removable_member<'e>(element: &'e ClassElement, analysis: &SemanticAnalysis) -> Option<&'e str> {
+    let (key, removable) = match element {
+        // A decorator can register the member, so it runs even when nothing names it
+        _ if !element.decorators().is_empty() => return None,
+        ClassElement::MethodDefinition { key, kind, is_private: false, .. } => {
+            (key, !matches!(kind, MethodKind::Constructor))
+        }
+        ClassElement::PropertyDefinition { key, value, is_static: true, is_private: false, .. } => {
+            (key, value.as_ref().is_none_or(|value| analysis.expression_purity(value).is_removable()))
+        }
+        _ => return None,
+    };
+    static_key_name(key).filter(|name| removable && !IMPLICITLY_CALLED_MEMBERS.contains(name))
+}
+
+/// Removes function declarations and members of local classes that no code outside of
+/// them reaches, directly or through other declarations
+///
+/// Names are matched without resolving scopes, which only ever keeps more: a name
+/// mentioned anywhere reaches every function and member declared with it. Exported and
+/// `@__KEEP__` functions are entry points, and so are the top-level functions of
+/// scripts, which are globals. Members are only removed from classes that never escape
+/// (see [`crate::analyzer::escape`]) and are not script globals, and not at all when a
+/// computed member access could reach any name. Nothing is removed when the program
+/// uses `eval` or `with`. Removed functions become empty statements, which the cleanup
+/// pass drops.
+///
+/// Returns the number of removed functions and members.
+fn remove_unreachable_declarations(
+    ast: &mut Program,
+    analysis: &SemanticAnalysis,
+    config: &TransformerConfig,
+) -> TransformResult<(u32, u32)> {
+    // Other unsafe scopes, such as ones using `this`, don't reach names dynamically
+    let dynamic_scope = analysis
+        .semantic_flags
+        .unsafe_scopes
+        .values()
+        .any(|reason| matches!(reason, UnsafeReason::EvalUsage | UnsafeReason::WithStatement));
+    if dynamic_scope {
+        return Ok((0, 0));
+    }
+
+    let is_script = matches!(ast.source_type, ProgramSourceType::Script);
+    let global_classes: HashSet<&str> = ast
+        .body
+        .iter()
+        .filter_map(|statement| match statement {
+            Statement::ClassDeclaration { id: Some(id), .. } if is_script => Some(id.name.as_str()),
+            _ => None,
+        })
+        .collect();
+    let local_classes: HashSet<String> = analysis
+        .local_classes
+        .iter()
+        .filter(|class| !global_classes.contains(class.name.as_str()))
+        .map(|class| class.name.clone())
+        .collect();
+
+    let body = serde_json::to_value(&ast.body).unwrap_or(Value::Null);
+    let mut graph = MentionGraph::new(analysis, local_classes.clone());
+    graph.collect(&body, None);
+    if graph.dynamic_access && !local_classes.is_empty() {
+        graph = MentionGraph::new(analysis, HashSet::new());
+        graph.collect(&body, None);
+    }
+    if graph.roots.contains("eval") || graph.declarations.values().any(|mentions| mentions.contains("eval")) {
+        return Ok((0, 0));
+    }
+
+    let mut entry_points: HashSet<String> = analysis
+        .symbol_table
+        .symbols
+        .values()
+        .filter(|symbol| symbol.is_kept)
+        .map(|symbol| analysis.symbol_table.name(symbol).to_string())
+        .collect();
+    for statement in &ast.body {
+        let declaration = match statement {
+            Statement::ExportNamedDeclaration { declaration: Some(declaration), .. }
+            | Statement::ExportDefaultDeclaration { declaration, .. } => declaration.as_ref(),
+            _ if is_script => statement,
+            _ => continue,
+        };
+        if let Statement::FunctionDeclaration { id: Some(id), .. } = declaration {
+            entry_points.insert(id.name.clone());
+        }
+    }
+
+    let unreachable = graph.unreachable(entry_points);
+    if unreachable.is_empty() {
+        return Ok((0, 0));
+    }
+    let mut remover = DeclarationRemover {
+        unreachable,
+        local_classes: graph.local_classes,
+        functions_removed: 0,
+        members_removed: 0,
+    };
+    plugin::run_plugin(&mut remover, ast, &mut PluginContext::new(analysis, config))?;
+    Ok((remover.functions_removed, remover.members_removed))
+}
+
+/// Replaces the declarations of unreachable functions with empty statements, and drops
+/// the unreachable members of local classes
+struct DeclarationRemover {
+    unreachable:       HashSet<String>,
+    local_classes:     HashSet<String>,
+    functions_removed: u32,
+    members_removed:   u32,
+}
+
+impl TransformPlugin for DeclarationRemover {
+    fn name(&self) -> &str {
+        "dead_code_elimination"
+    }
+
+    fn enter_statement(&mut self, stmt: &mut Statement, ctx: &mut PluginContext) -> TransformResult<()> {
+        match stmt {
+            Statement::FunctionDeclaration { id: Some(id), .. } if self.unreachable.contains(&id.name) => {
+                *stmt = Statement::EmptyStatement;
+                self.functions_removed += 1;
+            }
+            Statement::ClassDeclaration { id: Some(id), body, .. } if self.local_classes.contains(&id.name) => {
+                let count = body.body.len();
+                body.body.retain(|element| {
+                    removable_member(element, ctx.analysis).is_none_or(|name| !self.unreachable.contains(name))
+                });
+                self.members_removed += (count - body.body.len()) as u32;
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+}
+
+/// Callee and arguments of a call, possibly inside an optional chain
+fn call_parts(expression: &Expression) -> Option<(&Expression, &[Expression])> {
+    match expression {
+        Expression::CallExpression { callee, arguments, .. } => Some((callee, arguments)),
+        Expression::ChainExpression { expression } => call_parts(expression),
+        _ => None,
+    }
+}
+
+/// Dotted name of a callee made of an identifier and static member accesses (`console.log`)
+fn callee_path(callee: &Expression) -> Option<Vec<&str>> {
+    match callee {
+        Expression::Identifier(id) => Some(vec![id.name.as_str()]),
+        Expression::MemberExpression { object, property, computed: false, .. } => {
+            let Expression::Identifier(property) = property.as_ref() else {
+                return None;
+            };
+            let mut path = callee_path(object)?;
+            path.push(&property.name);
+            Some(path)
+        }
+        _ => None,
+    }
+}
+
+/// Whether a dotted `pattern` names `path`; a `*` segment matches any one name
+fn matches_pattern(pattern: &str, path: &[&str]) -> bool {
+    let segments: Vec<&str> = pattern.split('.').collect();
+    segments.len() == path.len()
+        && segments.iter().zip(path).all(|(segment, name)| *segment == "*" || segment == name)
+}
+
+/// Whether `expression` is a call, possibly inside an optional chain
+fn is_call(expression: &Expression) -> bool {
+    match expression {
+        Expression::CallExpression { .. } => true,
+        Expression::ChainExpression { expression } => is_call(expression),
+        _ => false,
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::analyzer::SymbolTable;
-    use crate::parser::ast_types::{Program, ProgramSourceType};
+    use crate::transformer::tests::run_pass;
+    use crate::analyzer::{analyze_ast, AnalyzerConfig};
+    use crate::parser::{parse_js, ParserConfig};
+
+    /// Eliminates dead code, returning the output and the removal count
+    fn eliminate_source(source: &str) -> (String, u32) {
+        let (code, result) = run_pass(source, &TransformerConfig::default(), eliminate_dead_code);
+        (code, result.removed_count)
+    }
 
     #[test]
-    fn test_placeholder_dead_code_elimination() {
-        let mut ast = Program {
-            body: vec![],
-            source_type: ProgramSourceType::Script,
-        };
+    fn test_removes_unused_pure_calls() {
+        let (code, removed) = eliminate_source(
+            "function add(a, b) { let c = a; c += b; return c; } add(1, 2); Math.max(1, 2); const r = add(3, 4);",
+        );
+
+        assert_eq!(removed, 2);
+        assert!(!code.contains("add(1"));
+        assert!(!code.contains("Math.max"));
+        assert!(code.contains("add(3"));
+    }
+
+    #[test]
+    fn test_keeps_effectful_calls() {
+        let (code, removed) = eliminate_source(
+            "let n = 0; function bump() { n = n + 1; } function outer() { bump(); } outer(); bump(); Math.random(); add(1); pure(undeclared); function pure(x) { return x; }",
+        );
+
+        assert_eq!(removed, 0);
+        assert!(code.contains("outer()"));
+        assert!(code.contains("Math.random()"));
+        assert!(code.contains("pure(undeclared)"));
+    }
+
+    #[test]
+    fn test_removes_pure_calls_in_function_bodies() {
+        let (code, removed) = eliminate_source("export function f(x) { String(typeof x); return x; }");
+
+        assert_eq!(removed, 1);
+        assert!(!code.contains("String"));
+    }
+
+    #[test]
+    fn test_keeps_conversions_that_can_run_user_code() {
+        let source = "const log = []; const o = { toString() { log.push('ts'); return '1'; } }; const n = 2;
+            String(o); Number(o); parseInt(o); Math.abs(o); isNaN([o]);
+            String(1); Math.abs(n * -3); parseInt(`${n}`); Math.max(-n, NaN);";
+        let (code, removed) = eliminate_source(source);
+
+        // Each of these calls `o.toString()`
+        assert_eq!(removed, 4, "{}", code);
+        assert!(code.ends_with("String(o);Number(o);parseInt(o);Math.abs(o);isNaN([o]);"), "{}", code);
+    }
 
-        let symbol_table = SymbolTable::new();
-        let config = TransformerConfig::default();
+    #[test]
+    fn test_drops_configured_calls() {
+        let source = "console.log(f()); console.debug?.(1); logger.info(...items); assert(x); console.log.call(console, 1);";
+        let config = TransformerConfig::default()
+            .with_drop_calls(vec!["console.*".to_string(), "logger.info".to_string()])
+            .with_keep_dropped_call_arguments(true);
+        let (code, result) = run_pass(source, &config, eliminate_dead_code);
+
+        assert_eq!(result.removed_count, 3);
+        assert_eq!(code, "f();[...items];assert(x);console.log.call(console,1);");
+    }
+
+    #[test]
+    fn test_removes_unreachable_mutually_recursive_functions() {
+        let (code, removed) = eliminate_source(
+            "function isEven(n) { return n === 0 || isOdd(n - 1); }\n\
+             function isOdd(n) { return n !== 0 && isEven(n - 1); }\n\
+             function ping() { console.log('ping'); pong(); } function pong() { ping(); }\n\
+             export function main() { return helper(); }\n\
+             function helper() { function inner() { return 1; } return 2; }\n\
+             ping();",
+        );
+
+        assert_eq!(removed, 3);
+        assert!(!code.contains("isEven") && !code.contains("isOdd"), "{}", code);
+        assert!(!code.contains("inner"), "{}", code);
+        assert!(code.contains("function ping()") && code.contains("function pong()"), "{}", code);
+        assert!(code.contains("function helper()"), "{}", code);
+    }
 
-        let result = eliminate_dead_code(&mut ast, &symbol_table, &config).unwrap();
+    #[test]
+    fn test_keeps_functions_of_scripts_and_eval() {
+        let mut ast = parse_js("function a() { return b(); } function b() { return a(); }", "test.js", &ParserConfig::default()).ast.unwrap();
+        ast.source_type = ProgramSourceType::Script;
+        let analysis = analyze_ast(&ast, &AnalyzerConfig::default()).unwrap();
+        let result = eliminate_dead_code(&mut ast, &analysis, &TransformerConfig::default()).unwrap();
         assert_eq!(result.removed_count, 0);
-        assert!(!result.warnings.is_empty());
+
+        let (_, removed) = eliminate_source("function a() { return b(); } function b() { return a(); } function c() { eval('a()'); }");
+        assert_eq!(removed, 0);
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_removes_unused_members_of_local_classes() {
+        let (code, removed) = eliminate_source(
+            "class Point {\n\
+               constructor(x) { this.x = x; }\n\
+               static origin = 0;\n\
+               static unit = 1;\n\
+               static create() { return Point.origin; }\n\
+               norm() { return this.scaled(); }\n\
+               scaled() { return this.x * 2; }\n\
+               unused() { return this.helper(); }\n\
+               helper() { return 1; }\n\
+               toString() { return 'point'; }\n\
+             }\n\
+             const p = Point.create();\n\
+             console.log(p['norm']());",
+        );
+
+        assert_eq!(removed, 3, "{}", code);
+        assert!(!code.contains("unused") && !code.contains("helper") && !code.contains("unit"), "{}", code);
+        assert!(code.contains("constructor") && code.contains("toString"), "{}", code);
+        assert!(code.contains("create()") && code.contains("origin") && code.contains("norm()"), "{}", code);
+        assert!(code.contains("scaled()"), "{}", code);
+    }
+
+    #[test]
+    fn test_keeps_members_of_escaping_classes() {
+        let (_, removed) = eliminate_source("export class Api { unused() { return 1; } }");
+        assert_eq!(removed, 0);
+
+        let (_, removed) = eliminate_source("class Passed { unused() { return 1; } } register(Passed);");
+        assert_eq!(removed, 0);
+
+        let (_, removed) = eliminate_source("class Dynamic { unused() { return 1; } } Dynamic[name];");
+        assert_eq!(removed, 0);
+
+        let (_, removed) = eliminate_source("class Effect { static log = console.log('loaded'); }");
+        assert_eq!(removed, 0);
+    }
+}
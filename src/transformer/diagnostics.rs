@@ -0,0 +1,191 @@
+//! # Diagnostics Module
+//!
+//! Structured warnings reported by the transformer, for `rjs-compiler --diagnostics-format`,
+//! and the findings of `--check` ([`analyzer::checks`](crate::analyzer::checks)).
+//!
+//! Each [`Diagnostic`] names the pass or plugin that reported it and, when known, the
+//! span of the original source it is about: the comment of a malformed `rjs-disable`
+//! directive, or the first top-level statement a rolled-back pass changed. Spans are
+//! resolved to lines and columns only when rendering, against the original source.
+
+use std::fmt;
+use std::fmt::Write;
+use std::str::FromStr;
+
+use serde::Serialize;
+use serde_json::{json, Value};
+
+use crate::parser::{get_line_column, SourceSpan};
+
+/// Output format of diagnostics
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DiagnosticFormat {
+    /// One `file:line:column: warning[pass]: message` line per diagnostic
+    #[default]
+    Text,
+    /// JSON array of diagnostics
+    Json,
+}
+
+impl FromStr for DiagnosticFormat {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "text" => Ok(Self::Text),
+            "json" => Ok(Self::Json),
+            _ => Err(format!("unknown diagnostics format '{}' (expected text or json)", value)),
+        }
+    }
+}
+
+/// How serious a diagnostic is
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    /// The code throws or misbehaves at runtime, as found by `--check`
+    Error,
+    /// The output is still correct, but an optimization was skipped or is incomplete
+    Warning,
+    /// Informational only; never counted against `--max-warnings`
+    Note,
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Severity::Error => write!(f, "error"),
+            Severity::Warning => write!(f, "warning"),
+            Severity::Note => write!(f, "note"),
+        }
+    }
+}
+
+/// A warning reported during transformation
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Diagnostic {
+    /// How serious the diagnostic is
+    pub severity: Severity,
+    /// Name of the pass or plugin that reported it, if any
+    pub pass: Option<String>,
+    /// Human-readable description
+    pub message: String,
+    /// Span of the original source the diagnostic is about, if known
+    pub span: Option<SourceSpan>,
+}
+
+impl Diagnostic {
+    /// Creates a warning without a pass or location
+    pub fn warning(message: impl Into<String>) -> Self {
+        Self {
+            severity: Severity::Warning,
+            pass: None,
+            message: message.into(),
+            span: None,
+        }
+    }
+
+    /// Creates an error without a pass or location
+    pub fn error(message: impl Into<String>) -> Self {
+        Self { severity: Severity::Error, ..Self::warning(message) }
+    }
+
+    /// Creates a note without a pass or location
+    pub fn note(message: impl Into<String>) -> Self {
+        Self { severity: Severity::Note, ..Self::warning(message) }
+    }
+
+    /// Attributes the diagnostic to a pass or plugin
+    pub fn with_pass(mut self, pass: impl Into<String>) -> Self {
+        self.pass = Some(pass.into());
+        self
+    }
+
+    /// Locates the diagnostic in the original source
+    pub fn with_span(mut self, span: Option<SourceSpan>) -> Self {
+        self.span = span;
+        self
+    }
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.message)
+    }
+}
+
+/// Renders diagnostics in `format`, resolving spans to lines and columns in `source`
+pub fn render_diagnostics(diagnostics: &[Diagnostic], source: &str, filename: &str, format: DiagnosticFormat) -> String {
+    match format {
+        DiagnosticFormat::Text => render_text(diagnostics, source, filename),
+        DiagnosticFormat::Json => render_json(diagnostics, source, filename),
+    }
+}
+
+fn render_text(diagnostics: &[Diagnostic], source: &str, filename: &str) -> String {
+    let mut output = String::new();
+    for diagnostic in diagnostics {
+        let location = match &diagnostic.span {
+            Some(span) => {
+                let (line, column) = get_line_column(source, span.start);
+                format!("{}:{}:{}", filename, line, column)
+            }
+            None => filename.to_string(),
+        };
+        let pass = diagnostic.pass.as_ref().map_or(String::new(), |pass| format!("[{}]", pass));
+        let _ = writeln!(output, "{}: {}{}: {}", location, diagnostic.severity, pass, diagnostic.message);
+    }
+    output
+}
+
+fn render_json(diagnostics: &[Diagnostic], source: &str, filename: &str) -> String {
+    let diagnostics: Vec<Value> = diagnostics
+        .iter()
+        .map(|diagnostic| {
+            let (line, column) = diagnostic
+                .span
+                .as_ref()
+                .map(|span| get_line_column(source, span.start))
+                .unzip();
+            json!({
+                "severity": diagnostic.severity,
+                "pass": diagnostic.pass,
+                "message": diagnostic.message,
+                "file": filename,
+                "span": diagnostic.span,
+                "line": line,
+                "column": column,
+            })
+        })
+        .collect();
+    serde_json::to_string_pretty(&diagnostics).unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_formats() {
+        let diagnostics = vec![
+            Diagnostic::warning("Rolled back dce").with_pass("dead_code_elimination").with_span(Some(SourceSpan { start: 8, end: 12 })),
+            Diagnostic::warning("Plugin note"),
+            Diagnostic::note("Pass not yet implemented").with_pass("function_minification"),
+        ];
+        let source = "let a;\n  f();\n";
+
+        assert_eq!(
+            render_diagnostics(&diagnostics, source, "in.js", DiagnosticFormat::Text),
+            "in.js:2:2: warning[dead_code_elimination]: Rolled back dce\nin.js: warning: Plugin note\n\
+             in.js: note[function_minification]: Pass not yet implemented\n"
+        );
+
+        let json: Value = serde_json::from_str(&render_diagnostics(&diagnostics, source, "in.js", DiagnosticFormat::Json)).unwrap();
+        assert_eq!(json[0]["line"], 2);
+        assert_eq!(json[0]["column"], 2);
+        assert_eq!(json[0]["severity"], "warning");
+        assert_eq!(json[0]["span"]["start"], 8);
+        assert!(json[1]["line"].is_null());
+        assert_eq!(json[2]["severity"], "note");
+    }
+}
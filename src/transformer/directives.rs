@@ -0,0 +1,379 @@
+//! # Directives Module
+//!
+//! Comment directives that switch built-in passes off for parts of a file:
+//!
+//! ```javascript
+//! /* rjs-disable mangle */
+//! function keepNames(longParameterName) { ... }
+//! /* rjs-enable mangle */
+//!
+//! // rjs-disable-next-line dce, compress -- kept for debugging
+//! const debugTable = buildTable(1 + 2);
+//! ```
+//!
+//! - `rjs-disable [passes]` disables passes from the comment to the matching
+//!   `rjs-enable [passes]`, or to the end of the file
+//! - `rjs-disable-next-line [passes]` disables passes for the statement starting on the
+//!   line after the comment
+//!
+//! Passes are separated by spaces or commas, and text after `--` is ignored. Without a
+//! pass list every pass is disabled. Passes are named by their CLI flags (`mangle`,
+//! `dce`, `compress`, `collapse-vars`, `inline-captures`, `if-return`, `hoist-funs`,
+//! `hoist-vars`, `mangle-props`, `inline`) or by [`BuiltinPass::name`].
+//!
+//! Directives apply to whole top-level statements: a statement is protected if it overlaps
+//! a disabled region, so a region inside a function body protects the entire function.
+//! After a pass runs, protected statements it changed are restored from a snapshot. When
+//! that is not possible (the pass changed the number of statements around a protected
+//! one, or it renames across the whole program) the pass is undone entirely instead.
+//! Names used in statements protected from identifier renaming are marked as not
+//! renamable before the pass runs, so the rest of the program is still mangled.
+
+use std::collections::HashSet;
+
+use serde_json::Value;
+
+use crate::analyzer::SemanticAnalysis;
+use crate::parser::ast_types::{Program, Statement};
+use crate::parser::{SourceSpan, Trivia};
+use crate::transformer::diagnostics::Diagnostic;
+use crate::transformer::BuiltinPass;
+
+/// Passes disabled by comment directives, per top-level statement
+#[derive(Debug, Clone, Default)]
+pub struct PassDirectives {
+    /// Disabled passes of each current top-level statement
+    disabled: Vec<HashSet<BuiltinPass>>,
+    /// Problems found while reading the directives
+    warnings: Vec<Diagnostic>,
+}
+
+/// Statements before a pass ran, taken by [`PassDirectives::begin`]
+#[derive(Debug)]
+pub(crate) struct DirectiveGuard {
+    snapshot: Vec<(Statement, Value)>,
+}
+
+/// Directive keyword
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum DirectiveKind {
+    Disable,
+    Enable,
+    DisableNextLine,
+}
+
+impl PassDirectives {
+    /// Reads the directives in the comments of `trivia`
+    ///
+    /// `statement_spans` are the source spans of the program's top-level statements (see
+    /// [`ArenaParseResult::statement_spans`](crate::parser::ArenaParseResult::statement_spans)).
+    pub fn from_trivia(source: &str, trivia: &Trivia, statement_spans: &[SourceSpan]) -> Self {
+        let mut comments: Vec<_> = trivia.line_comments.iter().chain(&trivia.block_comments).collect();
+        comments.sort_by_key(|comment| comment.span.start);
+
+        let line_starts: Vec<u32> = std::iter::once(0)
+            .chain(source.match_indices('\n').map(|(offset, _)| offset as u32 + 1))
+            .collect();
+        let line_of = |offset: u32| line_starts.partition_point(|&start| start <= offset);
+
+        let mut directives = Self {
+            disabled: vec![HashSet::new(); statement_spans.len()],
+            warnings: Vec::new(),
+        };
+        // Start offset of each open `rjs-disable` region
+        let mut open: Vec<(BuiltinPass, u32)> = Vec::new();
+        let mut regions: Vec<(BuiltinPass, u32, u32)> = Vec::new();
+        let mut next_lines: Vec<(BuiltinPass, usize)> = Vec::new();
+
+        for comment in comments {
+            let Some((kind, names)) = parse_directive(&comment.text) else {
+                continue;
+            };
+            let passes = if names.is_empty() {
+                BuiltinPass::ALL.to_vec()
+            } else {
+                names
+                    .iter()
+                    .filter_map(|name| {
+                        let pass = pass_from_directive_name(name);
+                        if pass.is_none() {
+                            directives.warnings.push(
+                                Diagnostic::warning(format!(
+                                    "Unknown pass '{}' in rjs directive on line {}",
+                                    name,
+                                    line_of(comment.span.start)
+                                ))
+                                .with_span(Some(comment.span.clone())),
+                            );
+                        }
+                        pass
+                    })
+                    .collect()
+            };
+
+            for pass in passes {
+                match kind {
+                    DirectiveKind::Disable => {
+                        if !open.iter().any(|(open_pass, _)| *open_pass == pass) {
+                            open.push((pass, comment.span.end));
+                        }
+                    }
+                    DirectiveKind::Enable => {
+                        if let Some(index) = open.iter().position(|(open_pass, _)| *open_pass == pass) {
+                            let (_, start) = open.remove(index);
+                            regions.push((pass, start, comment.span.start));
+                        }
+                    }
+                    DirectiveKind::DisableNextLine => next_lines.push((pass, line_of(comment.span.end) + 1)),
+                }
+            }
+        }
+        regions.extend(open.into_iter().map(|(pass, start)| (pass, start, u32::MAX)));
+
+        for (span, disabled) in statement_spans.iter().zip(&mut directives.disabled) {
+            for &(pass, start, end) in &regions {
+                if span.start < end && span.end > start {
+                    disabled.insert(pass);
+                }
+            }
+            let line = line_of(span.start);
+            disabled.extend(next_lines.iter().filter(|(_, target)| *target == line).map(|(pass, _)| *pass));
+        }
+        directives
+    }
+
+    /// Whether no statement has a disabled pass
+    pub fn is_empty(&self) -> bool {
+        self.disabled.iter().all(HashSet::is_empty)
+    }
+
+    /// Whether `pass` is disabled for the top-level statement at `index`
+    pub fn is_disabled(&self, index: usize, pass: BuiltinPass) -> bool {
+        self.disabled.get(index).is_some_and(|disabled| disabled.contains(&pass))
+    }
+
+    /// Number of top-level statements with at least one disabled pass
+    pub fn protected_statements(&self) -> usize {
+        self.disabled.iter().filter(|disabled| !disabled.is_empty()).count()
+    }
+
+    /// Problems found while reading the directives, such as unknown pass names
+    pub fn warnings(&self) -> &[Diagnostic] {
+        &self.warnings
+    }
+
+    /// Removes and returns the warnings
+    pub(crate) fn take_warnings(&mut self) -> Vec<Diagnostic> {
+        std::mem::take(&mut self.warnings)
+    }
+
+    /// Marks every symbol named in a statement protected from identifier renaming as not
+    /// renamable
+    pub(crate) fn protect_names(&self, program: &Program, analysis: &mut SemanticAnalysis) {
+        let mut names = HashSet::new();
+        for (index, statement) in program.body.iter().enumerate() {
+            if self.is_disabled(index, BuiltinPass::IdentifierRenaming) {
+                collect_names(&to_value(statement), &mut names);
+            }
+        }
+        if names.is_empty() {
+            return;
+        }
+
+        let symbol_table = &mut analysis.symbol_table;
+        for symbol in symbol_table.symbols.values_mut() {
+            if names.contains(symbol_table.names.resolve(symbol.name)) {
+                symbol.is_renamable = false;
+            }
+        }
+    }
+
+    /// Snapshots the program before a pass or plugin runs, if any statement is protected
+    pub(crate) fn begin(&self, program: &Program) -> Option<DirectiveGuard> {
+        if self.is_empty() {
+            return None;
+        }
+        let snapshot = program.body.iter().map(|statement| (statement.clone(), to_value(statement))).collect();
+        Some(DirectiveGuard { snapshot })
+    }
+
+    /// Undoes the changes `pass` made to statements that disable it, and keeps track of
+    /// which statements are protected as the program changes
+    ///
+    /// Plugins are passed as `None`; directives do not apply to them.
+    ///
+    /// Returns whether the program still differs from the snapshot, or the reason the
+    /// whole pass was undone.
+    pub(crate) fn end(
+        &mut self,
+        pass: Option<BuiltinPass>,
+        guard: DirectiveGuard,
+        program: &mut Program,
+    ) -> Result<bool, String> {
+        let snapshot = guard.snapshot;
+        let after: Vec<Value> = program.body.iter().map(to_value).collect();
+        let protects = |disabled: &HashSet<BuiltinPass>| pass.is_some_and(|pass| disabled.contains(&pass));
+
+        if snapshot.len() == after.len() {
+            let changed: Vec<usize> = (0..after.len()).filter(|&index| snapshot[index].1 != after[index]).collect();
+            let protected: Vec<usize> =
+                changed.iter().copied().filter(|&index| protects(&self.disabled[index])).collect();
+            if protected.is_empty() {
+                return Ok(!changed.is_empty());
+            }
+            if matches!(pass, Some(BuiltinPass::IdentifierRenaming | BuiltinPass::PropertyMinification)) {
+                program.body = snapshot.into_iter().map(|(statement, _)| statement).collect();
+                return Err("it renames across statements where it is disabled by rjs-disable".to_string());
+            }
+            for &index in &protected {
+                program.body[index] = snapshot[index].0.clone();
+            }
+            return Ok(changed.len() > protected.len());
+        }
+
+        let prefix = snapshot.iter().zip(&after).take_while(|((_, before), after)| before == *after).count();
+        let suffix = snapshot[prefix..]
+            .iter()
+            .rev()
+            .zip(after[prefix..].iter().rev())
+            .take_while(|((_, before), after)| before == *after)
+            .count();
+        let removed = prefix..snapshot.len() - suffix;
+
+        if self.disabled[removed.clone()].iter().any(protects) {
+            program.body = snapshot.into_iter().map(|(statement, _)| statement).collect();
+            return Err("it would change statements where it is disabled by rjs-disable".to_string());
+        }
+
+        // Statements replacing a changed region inherit its directives
+        let inherited: HashSet<BuiltinPass> = self.disabled[removed.clone()].iter().flatten().copied().collect();
+        let inserted = after.len() - suffix - prefix;
+        self.disabled.splice(removed, std::iter::repeat_n(inherited, inserted));
+        Ok(true)
+    }
+}
+
+/// Splits a comment into a directive and its pass names
+fn parse_directive(text: &str) -> Option<(DirectiveKind, Vec<&str>)> {
+    let text = text.split("--").next().unwrap_or_default();
+    let mut words = text.split(|c: char| c.is_whitespace() || c == ',').filter(|word| !word.is_empty());
+    let kind = match words.next()? {
+        "rjs-disable" => DirectiveKind::Disable,
+        "rjs-enable" => DirectiveKind::Enable,
+        "rjs-disable-next-line" => DirectiveKind::DisableNextLine,
+        _ => return None,
+    };
+    Some((kind, words.collect()))
+}
+
+/// Looks up a pass by its CLI flag name or [`BuiltinPass::name`]
+fn pass_from_directive_name(name: &str) -> Option<BuiltinPass> {
+    match name {
+        "mangle" => Some(BuiltinPass::IdentifierRenaming),
+        "dce" => Some(BuiltinPass::DeadCodeElimination),
+        "compress" | "simplify" => Some(BuiltinPass::ExpressionSimplification),
+        "collapse-vars" => Some(BuiltinPass::CollapseVariables),
+        "inline-captures" => Some(BuiltinPass::InlineCaptures),
+        "if-return" => Some(BuiltinPass::IfReturn),
+        "hoist-funs" => Some(BuiltinPass::HoistFunctions),
+        "hoist-vars" => Some(BuiltinPass::HoistVariables),
+        "mangle-props" => Some(BuiltinPass::PropertyMinification),
+        "inline" => Some(BuiltinPass::FunctionMinification),
+        _ => BuiltinPass::from_name(name),
+    }
+}
+
+/// Collects every `name` field in the JSON form of a statement
+fn collect_names(node: &Value, names: &mut HashSet<String>) {
+    match node {
+        Value::Object(fields) => {
+            if let Some(Value::String(name)) = fields.get("name") {
+                names.insert(name.clone());
+            }
+            fields.values().for_each(|child| collect_names(child, names));
+        }
+        Value::Array(items) => items.iter().for_each(|item| collect_names(item, names)),
+        _ => {}
+    }
+}
+
+fn to_value(statement: &Statement) -> Value {
+    serde_json::to_value(statement).unwrap_or(Value::Null)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::{parse_js_in, Allocator, ParserConfig};
+
+    fn parse(source: &str) -> (Program, PassDirectives) {
+        let allocator = Allocator::default();
+        let config = ParserConfig::default();
+        let result = parse_js_in(source, "test.js", &allocator, &config);
+        let directives = PassDirectives::from_trivia(source, &result.trivia(), &result.statement_spans());
+        (result.to_owned_ast().unwrap(), directives)
+    }
+
+    #[test]
+    fn test_directives_select_statements() {
+        let source = "\
+const a = 1;
+/* rjs-disable mangle, dce */
+function b() { /* rjs-enable dce */ }
+const c = 2;
+/* rjs-enable */
+// rjs-disable-next-line compress -- keep the sum
+const d = 1 + 2;
+const e = 3;
+/* rjs-disable bogus */
+";
+        let (_, directives) = parse(source);
+
+        assert!(!directives.is_disabled(0, BuiltinPass::IdentifierRenaming));
+        assert!(directives.is_disabled(1, BuiltinPass::IdentifierRenaming));
+        assert!(directives.is_disabled(1, BuiltinPass::DeadCodeElimination));
+        assert!(directives.is_disabled(2, BuiltinPass::IdentifierRenaming));
+        assert!(!directives.is_disabled(2, BuiltinPass::DeadCodeElimination));
+        assert!(directives.is_disabled(3, BuiltinPass::ExpressionSimplification));
+        assert!(!directives.is_disabled(3, BuiltinPass::IdentifierRenaming));
+        assert!(!directives.is_disabled(4, BuiltinPass::ExpressionSimplification));
+        assert_eq!(directives.protected_statements(), 3);
+        assert_eq!(directives.warnings().len(), 1);
+        assert_eq!(directives.warnings()[0].message, "Unknown pass 'bogus' in rjs directive on line 9");
+        assert_eq!(directives.warnings()[0].span.as_ref().map(|span| span.start), source.find("/* rjs-disable bogus").map(|offset| offset as u32));
+    }
+
+    #[test]
+    fn test_protected_statements_are_restored_or_the_pass_undone() {
+        let source = "const a = 1;\n// rjs-disable-next-line\nconst b = 2;\nconst c = 3;\n";
+        let (mut program, mut directives) = parse(source);
+        let (replacement, _) = parse("const x = 0;");
+
+        // Same number of statements: only the protected one is restored
+        let guard = directives.begin(&program).unwrap();
+        program.body[0] = replacement.body[0].clone();
+        program.body[1] = replacement.body[0].clone();
+        let expected = program.body[0].clone();
+        assert_eq!(directives.end(Some(BuiltinPass::ExpressionSimplification), guard, &mut program), Ok(true));
+        assert_eq!(to_value(&program.body[0]), to_value(&expected));
+        assert_ne!(to_value(&program.body[1]), to_value(&expected));
+
+        // Removing an unprotected statement shifts the protection along
+        let guard = directives.begin(&program).unwrap();
+        program.body.remove(0);
+        assert_eq!(directives.end(Some(BuiltinPass::DeadCodeElimination), guard, &mut program), Ok(true));
+        assert!(directives.is_disabled(0, BuiltinPass::DeadCodeElimination));
+
+        // Removing the protected statement undoes the whole pass
+        let guard = directives.begin(&program).unwrap();
+        program.body.clear();
+        assert!(directives.end(Some(BuiltinPass::DeadCodeElimination), guard, &mut program).is_err());
+        assert_eq!(program.body.len(), 2);
+
+        // Plugins are not restricted
+        let guard = directives.begin(&program).unwrap();
+        program.body.clear();
+        assert_eq!(directives.end(None, guard, &mut program), Ok(true));
+        assert!(directives.is_empty());
+    }
+}
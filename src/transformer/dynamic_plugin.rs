@@ -0,0 +1,525 @@
+//! # Dynamic Plugin Module
+//!
+//! Loads [`TransformPlugin`]s from shared libraries at runtime (`rjs-compiler --plugin
+//! <path>`), so plugins can be distributed without recompiling the CLI.
+//!
+//! ## ABI
+//!
+//! Rust trait objects have no stable layout across compiler versions, so plugins talk
+//! to the host through a small C ABI and exchange the AST as JSON:
+//!
+//! | Symbol                   | Signature                                                   |
+//! |--------------------------|-------------------------------------------------------------|
+//! | `rjs_plugin_abi_version` | `fn() -> u32`, must return [`PLUGIN_ABI_VERSION`]           |
+//! | `rjs_plugin_name`        | `fn() -> *const c_char` (NUL-terminated, static)            |
+//! | `rjs_plugin_position`    | optional, `fn() -> *const c_char`, e.g. `before:dead_code_elimination` |
+//! | `rjs_plugin_transform`   | `fn(*const u8, usize, *mut PluginBuffer) -> i32`            |
+//! | `rjs_plugin_free`        | `fn(PluginBuffer)`, releases buffers returned by `transform` |
+//!
+//! `rjs_plugin_transform` receives a [`PluginRequest`] as JSON and fills the output
+//! buffer with a [`PluginResponse`] on success (return value `0`), or with a UTF-8 error
+//! message otherwise. The ABI version is checked before any other symbol is called.
+//!
+//! ## Writing a Plugin
+//!
+//! Build a `cdylib` crate that depends on `rjs-compiler` and export an ordinary
+//! [`TransformPlugin`] with [`export_plugin!`](crate::export_plugin):
+//!
+//! ```rust,no_run
+//! use rjs_compiler::transformer::plugin::TransformPlugin;
+//!
+//! #[derive(Default)]
+//! struct MyPlugin;
+//!
+//! impl TransformPlugin for MyPlugin {
+//!     fn name(&self) -> &str {
+//!         "my-plugin"
+//!     }
+//! }
+//!
+//! rjs_compiler::export_plugin!(MyPlugin::default, "before:dead_code_elimination");
+//! ```
+//!
+//! A fresh plugin instance is created for every file, so plugins should not rely on
+//! state carried between transformations.
+
+use std::ffi::{c_char, CStr, CString};
+use std::panic::{self, AssertUnwindSafe};
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+use libloading::Library;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::analyzer::SemanticAnalysis;
+use crate::parser::ast_types::Program;
+use crate::transformer::plugin::{self, PluginContext, PluginPosition, TransformPlugin};
+use crate::transformer::{BuiltinPass, TransformError, TransformResult, TransformerConfig};
+
+/// Version of the plugin ABI implemented by this build
+///
+/// Incremented whenever the exported symbols or the JSON request/response format change
+/// incompatibly.
+pub const PLUGIN_ABI_VERSION: u32 = 1;
+
+/// Position used when a plugin does not export `rjs_plugin_position`
+pub const DEFAULT_PLUGIN_POSITION: PluginPosition = PluginPosition::After(BuiltinPass::FunctionMinification);
+
+/// Owned byte buffer passed from a plugin to the host
+///
+/// Always allocated by the plugin and released through the plugin's `rjs_plugin_free`,
+/// since host and plugin may use different allocators.
+#[repr(C)]
+#[derive(Debug)]
+pub struct PluginBuffer {
+    pub ptr: *mut u8,
+    pub len: usize,
+    pub capacity: usize,
+}
+
+impl PluginBuffer {
+    /// An empty buffer that owns no allocation
+    pub const fn empty() -> Self {
+        Self { ptr: std::ptr::null_mut(), len: 0, capacity: 0 }
+    }
+
+    /// Transfers ownership of `bytes` into a buffer
+    pub fn from_vec(bytes: Vec<u8>) -> Self {
+        let mut bytes = std::mem::ManuallyDrop::new(bytes);
+        Self { ptr: bytes.as_mut_ptr(), len: bytes.len(), capacity: bytes.capacity() }
+    }
+
+    /// Reclaims a buffer created by [`from_vec`](Self::from_vec) in the same binary
+    ///
+    /// # Safety
+    ///
+    /// The buffer must come from `from_vec` in this binary and must not be used again.
+    pub unsafe fn into_vec(self) -> Vec<u8> {
+        if self.ptr.is_null() {
+            return Vec::new();
+        }
+        // SAFETY: guaranteed by the caller, the parts come from a leaked Vec<u8>
+        unsafe { Vec::from_raw_parts(self.ptr, self.len, self.capacity) }
+    }
+
+    /// Views the buffer contents
+    ///
+    /// # Safety
+    ///
+    /// `ptr` must be null or valid for reads of `len` bytes.
+    unsafe fn as_bytes(&self) -> &[u8] {
+        if self.ptr.is_null() {
+            return &[];
+        }
+        // SAFETY: guaranteed by the caller
+        unsafe { std::slice::from_raw_parts(self.ptr, self.len) }
+    }
+}
+
+/// Input of `rjs_plugin_transform`
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PluginRequest {
+    pub program: Program,
+    pub analysis: SemanticAnalysis,
+    pub config: TransformerConfig,
+}
+
+/// Borrowed form of [`PluginRequest`], serialized by the host without cloning the AST
+#[derive(Serialize)]
+struct PluginRequestRef<'a> {
+    program: &'a Program,
+    analysis: &'a SemanticAnalysis,
+    config: &'a TransformerConfig,
+}
+
+/// Output of a successful `rjs_plugin_transform`
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PluginResponse {
+    pub program: Program,
+    #[serde(default)]
+    pub warnings: Vec<String>,
+    #[serde(default)]
+    pub changes: u32,
+}
+
+pub type AbiVersionFn = unsafe extern "C" fn() -> u32;
+pub type StringFn = unsafe extern "C" fn() -> *const c_char;
+pub type TransformFn = unsafe extern "C" fn(input: *const u8, input_len: usize, output: *mut PluginBuffer) -> i32;
+pub type FreeFn = unsafe extern "C" fn(buffer: PluginBuffer);
+
+/// Entry points of a plugin
+#[derive(Debug, Clone, Copy)]
+pub struct PluginVTable {
+    pub abi_version: AbiVersionFn,
+    pub name: StringFn,
+    pub position: Option<StringFn>,
+    pub transform: TransformFn,
+    pub free: FreeFn,
+}
+
+/// Errors that can occur while loading a plugin
+#[derive(Debug, Error)]
+pub enum PluginLoadError {
+    #[error("Failed to load plugin {path}: {source}")]
+    Library { path: PathBuf, source: libloading::Error },
+
+    #[error("Plugin {path} does not export `{symbol}`")]
+    MissingSymbol { path: PathBuf, symbol: &'static str },
+
+    #[error("Plugin '{name}' uses ABI version {found}, but this compiler supports version {expected}")]
+    AbiMismatch { name: String, found: u32, expected: u32 },
+
+    #[error("Plugin '{name}' has invalid metadata: {message}")]
+    InvalidMetadata { name: String, message: String },
+}
+
+/// A plugin loaded from a shared library
+#[derive(Debug)]
+pub struct DynamicPlugin {
+    name: String,
+    position: PluginPosition,
+    vtable: PluginVTable,
+    /// Keeps the library mapped for as long as `vtable` may be called
+    _library: Option<Library>,
+}
+
+impl DynamicPlugin {
+    /// Loads a plugin from a shared library and performs the ABI handshake
+    ///
+    /// # Errors
+    ///
+    /// Returns `PluginLoadError` if the library cannot be loaded, lacks a required
+    /// symbol, or was built for a different ABI version
+    pub fn load(path: &Path) -> Result<Self, PluginLoadError> {
+        // SAFETY: loading a library runs its initializers; plugins are trusted code
+        // chosen explicitly on the command line
+        let library = unsafe { Library::new(path) }
+            .map_err(|source| PluginLoadError::Library { path: path.to_path_buf(), source })?;
+
+        let vtable = PluginVTable {
+            abi_version: load_symbol(&library, path, "rjs_plugin_abi_version")?,
+            name: load_symbol(&library, path, "rjs_plugin_name")?,
+            position: load_symbol(&library, path, "rjs_plugin_position").ok(),
+            transform: load_symbol(&library, path, "rjs_plugin_transform")?,
+            free: load_symbol(&library, path, "rjs_plugin_free")?,
+        };
+
+        let mut plugin = Self::from_vtable(vtable)?;
+        plugin._library = Some(library);
+        Ok(plugin)
+    }
+
+    /// Creates a plugin from entry points that are already loaded
+    ///
+    /// The ABI version is checked before the name or position is read.
+    ///
+    /// # Errors
+    ///
+    /// Returns `PluginLoadError::AbiMismatch` or `PluginLoadError::InvalidMetadata`
+    pub fn from_vtable(vtable: PluginVTable) -> Result<Self, PluginLoadError> {
+        // SAFETY: the ABI version function takes no arguments and has a fixed signature
+        let found = unsafe { (vtable.abi_version)() };
+        if found != PLUGIN_ABI_VERSION {
+            return Err(PluginLoadError::AbiMismatch {
+                name: "<unknown>".to_string(),
+                found,
+                expected: PLUGIN_ABI_VERSION,
+            });
+        }
+
+        // SAFETY: ABI version 1 defines both functions as returning static C strings
+        let name = unsafe { read_c_string(vtable.name) }.unwrap_or_else(|| "<unnamed>".to_string());
+        let position = match vtable.position {
+            // SAFETY: as above
+            Some(position) => unsafe { read_c_string(position) }
+                .unwrap_or_default()
+                .parse()
+                .map_err(|message| PluginLoadError::InvalidMetadata { name: name.clone(), message })?,
+            None => DEFAULT_PLUGIN_POSITION,
+        };
+
+        Ok(Self { name, position, vtable, _library: None })
+    }
+
+    /// Where the plugin asked to run in the pipeline
+    pub fn position(&self) -> PluginPosition {
+        self.position
+    }
+
+    fn error(&self, message: impl Into<String>) -> TransformError {
+        TransformError::PluginError { plugin: self.name.clone(), message: message.into() }
+    }
+}
+
+impl TransformPlugin for DynamicPlugin {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Hands the whole program to the plugin in one call
+    fn enter_program(&mut self, program: &mut Program, ctx: &mut PluginContext) -> TransformResult<()> {
+        let request = PluginRequestRef { program, analysis: ctx.analysis, config: ctx.config };
+        let input = serde_json::to_vec(&request).map_err(|e| self.error(e.to_string()))?;
+
+        let mut output = PluginBuffer::empty();
+        // SAFETY: `input` outlives the call and `output` is a valid out-pointer
+        let status = unsafe { (self.vtable.transform)(input.as_ptr(), input.len(), &mut output) };
+        // SAFETY: the plugin filled `output` with a buffer it allocated
+        let bytes = unsafe { output.as_bytes() }.to_vec();
+        // SAFETY: the buffer came from this plugin and is not used again
+        unsafe { (self.vtable.free)(output) };
+
+        if status != 0 {
+            return Err(self.error(String::from_utf8_lossy(&bytes)));
+        }
+
+        let response: PluginResponse = serde_json::from_slice(&bytes)
+            .map_err(|e| self.error(format!("invalid response: {}", e)))?;
+        *program = response.program;
+        ctx.record_changes(response.changes);
+        for warning in response.warnings {
+            ctx.warn(warning);
+        }
+        Ok(())
+    }
+}
+
+fn load_symbol<T: Copy>(library: &Library, path: &Path, symbol: &'static str) -> Result<T, PluginLoadError> {
+    let name = format!("{}\0", symbol);
+    // SAFETY: every symbol is declared with the signature given in the module docs
+    unsafe { library.get::<T>(name.as_bytes()) }
+        .map(|symbol| *symbol)
+        .map_err(|_| PluginLoadError::MissingSymbol { path: path.to_path_buf(), symbol })
+}
+
+/// Reads a static NUL-terminated string returned by a plugin
+///
+/// # Safety
+///
+/// `function` must return null or a pointer to a NUL-terminated string.
+unsafe fn read_c_string(function: StringFn) -> Option<String> {
+    // SAFETY: guaranteed by the caller
+    let pointer = unsafe { function() };
+    if pointer.is_null() {
+        return None;
+    }
+    // SAFETY: guaranteed by the caller
+    Some(unsafe { CStr::from_ptr(pointer) }.to_string_lossy().into_owned())
+}
+
+/// Exports a [`TransformPlugin`] from a `cdylib` using the plugin ABI
+///
+/// Takes a constructor expression (called once per file) and an optional position
+/// string such as `"before:dead_code_elimination"`.
+#[macro_export]
+macro_rules! export_plugin {
+    ($constructor:expr) => {
+        $crate::export_plugin!(@symbols $constructor);
+    };
+    ($constructor:expr, $position:expr) => {
+        $crate::export_plugin!(@symbols $constructor);
+
+        #[unsafe(no_mangle)]
+        pub extern "C" fn rjs_plugin_position() -> *const ::std::ffi::c_char {
+            static POSITION: ::std::sync::OnceLock<::std::ffi::CString> = ::std::sync::OnceLock::new();
+            $crate::transformer::dynamic_plugin::export::static_c_string(&POSITION, || $position.to_string())
+        }
+    };
+    (@symbols $constructor:expr) => {
+        #[unsafe(no_mangle)]
+        pub extern "C" fn rjs_plugin_abi_version() -> u32 {
+            $crate::transformer::dynamic_plugin::PLUGIN_ABI_VERSION
+        }
+
+        #[unsafe(no_mangle)]
+        pub extern "C" fn rjs_plugin_name() -> *const ::std::ffi::c_char {
+            static NAME: ::std::sync::OnceLock<::std::ffi::CString> = ::std::sync::OnceLock::new();
+            $crate::transformer::dynamic_plugin::export::static_c_string(&NAME, || {
+                $crate::transformer::plugin::TransformPlugin::name(&$constructor()).to_string()
+            })
+        }
+
+        /// # Safety
+        ///
+        /// `input` must be valid for reads of `input_len` bytes and `output` must be writable.
+        #[unsafe(no_mangle)]
+        pub unsafe extern "C" fn rjs_plugin_transform(
+            input: *const u8,
+            input_len: usize,
+            output: *mut $crate::transformer::dynamic_plugin::PluginBuffer,
+        ) -> i32 {
+            // SAFETY: forwarded from the caller
+            unsafe { $crate::transformer::dynamic_plugin::export::transform($constructor, input, input_len, output) }
+        }
+
+        /// # Safety
+        ///
+        /// `buffer` must have been returned by `rjs_plugin_transform`.
+        #[unsafe(no_mangle)]
+        pub unsafe extern "C" fn rjs_plugin_free(buffer: $crate::transformer::dynamic_plugin::PluginBuffer) {
+            // SAFETY: forwarded from the caller
+            drop(unsafe { buffer.into_vec() });
+        }
+    };
+}
+
+/// Plugin-side helpers used by [`export_plugin!`](crate::export_plugin)
+#[doc(hidden)]
+pub mod export {
+    use super::*;
+
+    /// Returns a pointer to a lazily created, never freed C string
+    pub fn static_c_string(cell: &'static OnceLock<CString>, value: impl FnOnce() -> String) -> *const c_char {
+        cell.get_or_init(|| CString::new(value()).unwrap_or_default()).as_ptr()
+    }
+
+    /// Implements `rjs_plugin_transform` for a plugin constructor
+    ///
+    /// # Safety
+    ///
+    /// `input` must be valid for reads of `input_len` bytes and `output` must be writable.
+    pub unsafe fn transform<P: TransformPlugin>(
+        constructor: impl FnOnce() -> P,
+        input: *const u8,
+        input_len: usize,
+        output: *mut PluginBuffer,
+    ) -> i32 {
+        // SAFETY: guaranteed by the caller
+        let input = unsafe { std::slice::from_raw_parts(input, input_len) };
+
+        // Unwinding across the C ABI is undefined behaviour, so panics become errors
+        let result = panic::catch_unwind(AssertUnwindSafe(|| run(constructor(), input)))
+            .unwrap_or_else(|_| Err("plugin panicked".to_string()));
+
+        let (status, bytes) = match result {
+            Ok(bytes) => (0, bytes),
+            Err(message) => (1, message.into_bytes()),
+        };
+        // SAFETY: guaranteed by the caller
+        unsafe { output.write(PluginBuffer::from_vec(bytes)) };
+        status
+    }
+
+    fn run<P: TransformPlugin>(mut plugin: P, input: &[u8]) -> Result<Vec<u8>, String> {
+        let PluginRequest { mut program, analysis, config } =
+            serde_json::from_slice(input).map_err(|e| format!("invalid request: {}", e))?;
+
+        let mut ctx = PluginContext::new(&analysis, &config);
+        plugin::run_plugin(&mut plugin, &mut program, &mut ctx).map_err(|e| e.to_string())?;
+
+        let response = PluginResponse { program, changes: ctx.changes(), warnings: ctx.into_warnings() };
+        serde_json::to_vec(&response).map_err(|e| e.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::ast_types::{Expression, Identifier, ProgramSourceType, Statement};
+
+    /// Renames every identifier `a` to `b`
+    #[derive(Default)]
+    struct RenameA;
+
+    impl TransformPlugin for RenameA {
+        fn name(&self) -> &str {
+            "rename-a"
+        }
+
+        fn enter_expression(&mut self, expr: &mut Expression, ctx: &mut PluginContext) -> TransformResult<()> {
+            if let Expression::Identifier(id) = expr
+                && id.name == "a"
+            {
+                id.name = "b".to_string();
+                ctx.record_change();
+            }
+            if let Expression::Identifier(id) = expr
+                && id.name == "panic"
+            {
+                panic!("boom");
+            }
+            Ok(())
+        }
+    }
+
+    mod exported {
+        crate::export_plugin!(super::RenameA::default, "before:dead_code_elimination");
+    }
+
+    fn vtable() -> PluginVTable {
+        PluginVTable {
+            abi_version: exported::rjs_plugin_abi_version,
+            name: exported::rjs_plugin_name,
+            position: Some(exported::rjs_plugin_position),
+            transform: exported::rjs_plugin_transform,
+            free: exported::rjs_plugin_free,
+        }
+    }
+
+    fn program(name: &str) -> Program {
+        Program {
+            body: vec![Statement::ExpressionStatement {
+                expression: Expression::Identifier(Identifier { name: name.to_string(), span: None }),
+                span: None,
+            }],
+            source_type: ProgramSourceType::Script,
+        }
+    }
+
+    fn analysis() -> SemanticAnalysis {
+        crate::analyze_source("", "empty.js").unwrap()
+    }
+
+    #[test]
+    fn test_handshake_reads_name_and_position() {
+        let plugin = DynamicPlugin::from_vtable(vtable()).unwrap();
+
+        assert_eq!(plugin.name(), "rename-a");
+        assert_eq!(plugin.position(), PluginPosition::Before(BuiltinPass::DeadCodeElimination));
+    }
+
+    #[test]
+    fn test_abi_version_mismatch_is_rejected() {
+        unsafe extern "C" fn future_version() -> u32 {
+            PLUGIN_ABI_VERSION + 1
+        }
+
+        let error = DynamicPlugin::from_vtable(PluginVTable { abi_version: future_version, ..vtable() }).unwrap_err();
+        assert!(matches!(error, PluginLoadError::AbiMismatch { found, .. } if found == PLUGIN_ABI_VERSION + 1));
+    }
+
+    #[test]
+    fn test_transform_round_trips_through_the_abi() {
+        let mut plugin = DynamicPlugin::from_vtable(vtable()).unwrap();
+        let analysis = analysis();
+        let config = TransformerConfig::default();
+        let mut ctx = PluginContext::new(&analysis, &config);
+        let mut program = program("a");
+
+        plugin::run_plugin(&mut plugin, &mut program, &mut ctx).unwrap();
+
+        assert!(matches!(
+            &program.body[0],
+            Statement::ExpressionStatement { expression: Expression::Identifier(id), .. } if id.name == "b"
+        ));
+        assert_eq!(ctx.changes(), 1);
+    }
+
+    #[test]
+    fn test_plugin_panics_become_errors() {
+        let mut plugin = DynamicPlugin::from_vtable(vtable()).unwrap();
+        let analysis = analysis();
+        let config = TransformerConfig::default();
+        let mut ctx = PluginContext::new(&analysis, &config);
+
+        let error = plugin::run_plugin(&mut plugin, &mut program("panic"), &mut ctx).unwrap_err();
+        assert_eq!(error.to_string(), "Plugin 'rename-a' failed: plugin panicked");
+    }
+
+    #[test]
+    fn test_load_reports_missing_library() {
+        let error = DynamicPlugin::load(Path::new("/nonexistent/librjs_missing_plugin.so")).unwrap_err();
+        assert!(matches!(error, PluginLoadError::Library { .. }));
+    }
+}
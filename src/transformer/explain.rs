@@ -0,0 +1,278 @@
+//! # Explain Module
+//!
+//! Records what each pass and plugin changed, for `rjs-compiler --explain`.
+//!
+//! Changes are tracked per top-level statement: the body is snapshotted before every
+//! pass and compared with the result afterwards. When the number of statements is
+//! unchanged, every differing statement becomes its own entry; otherwise the changed
+//! region between the common prefix and suffix is reported as one entry. Each entry
+//! keeps the span of the original source statements it replaces, carried through
+//! earlier changes, and compact before/after snippets.
+
+use std::fmt::Write;
+use std::str::FromStr;
+
+use serde::Serialize;
+use serde_json::{json, Value};
+
+use crate::generator::{Generator, GeneratorConfig};
+use crate::parser::ast_types::{Program, ProgramSourceType, Statement};
+use crate::parser::{get_line_column, SourceSpan};
+
+/// Maximum length of a before/after snippet, in characters
+const MAX_SNIPPET_CHARS: usize = 160;
+
+/// Output format of an explain report
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ExplainFormat {
+    /// Human-readable text
+    #[default]
+    Text,
+    /// JSON array of entries
+    Json,
+}
+
+impl FromStr for ExplainFormat {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "text" => Ok(Self::Text),
+            "json" => Ok(Self::Json),
+            _ => Err(format!("unknown explain format '{}' (expected text or json)", value)),
+        }
+    }
+}
+
+/// One change made by a pass
+#[derive(Debug, Clone, Serialize)]
+pub struct ExplainEntry {
+    /// Name of the pass or plugin that made the change
+    pub pass: String,
+    /// Span of the original source statements affected, if known
+    pub span: Option<SourceSpan>,
+    /// Compact code before the change (empty for inserted statements)
+    pub before: String,
+    /// Compact code after the change (empty for removed statements)
+    pub after: String,
+}
+
+/// Collects [`ExplainEntry`]s while the transformer runs
+#[derive(Debug)]
+pub(crate) struct ExplainRecorder {
+    /// Original source span of each current top-level statement
+    spans: Vec<Option<SourceSpan>>,
+    /// Statements before the running pass, with their JSON form for comparison
+    snapshot: Vec<(Statement, Value)>,
+    /// Source type used when printing snippets
+    source_type: ProgramSourceType,
+    /// Recorded changes, in order
+    entries: Vec<ExplainEntry>,
+}
+
+impl ExplainRecorder {
+    /// Creates a recorder for `program`, whose statements start at `spans` in the source
+    ///
+    /// `spans` may be empty when source positions are unknown.
+    pub(crate) fn new(program: &Program, spans: &[SourceSpan]) -> Self {
+        let spans = (0..program.body.len()).map(|index| spans.get(index).cloned()).collect();
+        Self {
+            spans,
+            snapshot: Vec::new(),
+            source_type: program.source_type.clone(),
+            entries: Vec::new(),
+        }
+    }
+
+    /// Snapshots the program before a pass runs
+    pub(crate) fn begin(&mut self, program: &Program) {
+        self.snapshot = program
+            .body
+            .iter()
+            .map(|statement| (statement.clone(), to_value(statement)))
+            .collect();
+    }
+
+    /// Records the differences between the snapshot and `program` under `pass`
+    pub(crate) fn end(&mut self, pass: &str, program: &Program) {
+        let snapshot = std::mem::take(&mut self.snapshot);
+        let after: Vec<Value> = program.body.iter().map(to_value).collect();
+
+        if snapshot.len() == after.len() {
+            for (index, ((statement, before), after_value)) in snapshot.iter().zip(&after).enumerate() {
+                if before != after_value {
+                    self.push(pass, index..index + 1, std::slice::from_ref(statement), &program.body[index..index + 1]);
+                }
+            }
+            return;
+        }
+
+        let prefix = snapshot.iter().zip(&after).take_while(|((_, before), after)| before == *after).count();
+        let suffix = snapshot[prefix..]
+            .iter()
+            .rev()
+            .zip(after[prefix..].iter().rev())
+            .take_while(|((_, before), after)| before == *after)
+            .count();
+
+        let removed: Vec<Statement> = snapshot[prefix..snapshot.len() - suffix]
+            .iter()
+            .map(|(statement, _)| statement.clone())
+            .collect();
+        let inserted = &program.body[prefix..after.len() - suffix];
+        self.push(pass, prefix..snapshot.len() - suffix, &removed, inserted);
+    }
+
+    /// Adds an entry replacing the statements at `range` and updates the span table
+    fn push(&mut self, pass: &str, range: std::ops::Range<usize>, removed: &[Statement], inserted: &[Statement]) {
+        let span = self.spans[range.clone()].iter().flatten().fold(None, |union: Option<SourceSpan>, span| {
+            Some(match union {
+                Some(union) => SourceSpan { start: union.start.min(span.start), end: union.end.max(span.end) },
+                None => span.clone(),
+            })
+        });
+
+        self.entries.push(ExplainEntry {
+            pass: pass.to_string(),
+            span: span.clone(),
+            before: snippet(removed, &self.source_type),
+            after: snippet(inserted, &self.source_type),
+        });
+        self.spans.splice(range, std::iter::repeat_n(span, inserted.len()));
+    }
+
+    /// Returns the recorded entries
+    pub(crate) fn into_entries(self) -> Vec<ExplainEntry> {
+        self.entries
+    }
+}
+
+/// Renders entries in `format`, resolving spans to lines and columns in `source`
+pub fn render_report(entries: &[ExplainEntry], source: &str, format: ExplainFormat) -> String {
+    match format {
+        ExplainFormat::Text => render_text(entries, source),
+        ExplainFormat::Json => render_json(entries, source),
+    }
+}
+
+fn render_text(entries: &[ExplainEntry], source: &str) -> String {
+    let mut output = format!("{} change(s)\n", entries.len());
+    for entry in entries {
+        let location = entry.span.as_ref().map_or("?".to_string(), |span| {
+            let (line, column) = get_line_column(source, span.start);
+            format!("{}:{}", line, column)
+        });
+        let _ = writeln!(output, "\n[{}] {}", entry.pass, location);
+        let _ = writeln!(output, "  - {}", if entry.before.is_empty() { "(nothing)" } else { &entry.before });
+        let _ = writeln!(output, "  + {}", if entry.after.is_empty() { "(removed)" } else { &entry.after });
+    }
+    output
+}
+
+fn render_json(entries: &[ExplainEntry], source: &str) -> String {
+    let entries: Vec<Value> = entries
+        .iter()
+        .map(|entry| {
+            let (line, column) = entry
+                .span
+                .as_ref()
+                .map(|span| get_line_column(source, span.start))
+                .unzip();
+            json!({
+                "pass": entry.pass,
+                "span": entry.span,
+                "line": line,
+                "column": column,
+                "before": entry.before,
+                "after": entry.after,
+            })
+        })
+        .collect();
+    serde_json::to_string_pretty(&entries).unwrap_or_default()
+}
+
+fn to_value(statement: &Statement) -> Value {
+    serde_json::to_value(statement).unwrap_or(Value::Null)
+}
+
+/// Prints statements compactly, shortened to [`MAX_SNIPPET_CHARS`]
+fn snippet(statements: &[Statement], source_type: &ProgramSourceType) -> String {
+    if statements.is_empty() {
+        return String::new();
+    }
+    let program = Program { body: statements.to_vec(), source_type: source_type.clone() };
+    let code = match Generator::new(GeneratorConfig::default()).generate(&program, None) {
+        Ok(result) => result.code.trim().to_string(),
+        Err(e) => return format!("<unprintable: {}>", e),
+    };
+
+    if code.chars().count() > MAX_SNIPPET_CHARS {
+        let mut short: String = code.chars().take(MAX_SNIPPET_CHARS - 1).collect();
+        short.push('…');
+        short
+    } else {
+        code
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::{parse_js_in, Allocator, ParserConfig};
+
+    fn parse(source: &str) -> (Program, Vec<SourceSpan>) {
+        let allocator = Allocator::default();
+        let result = parse_js_in(source, "test.js", &allocator, &ParserConfig::default());
+        (result.to_owned_ast().unwrap(), result.statement_spans())
+    }
+
+    #[test]
+    fn test_changed_statements_are_recorded_with_spans() {
+        let source = "let a = 1;\nlet b = 2;\nlet c = 3;\n";
+        let (mut program, spans) = parse(source);
+        let mut recorder = ExplainRecorder::new(&program, &spans);
+
+        recorder.begin(&program);
+        program.body[1] = parse("let b = 20;").0.body.remove(0);
+        recorder.end("fold", &program);
+
+        recorder.begin(&program);
+        program.body.remove(0);
+        recorder.end("dce", &program);
+
+        // The replaced statement keeps its original span after the earlier removal
+        recorder.begin(&program);
+        program.body[0] = parse("let b = 200;").0.body.remove(0);
+        recorder.end("fold", &program);
+
+        let entries = recorder.into_entries();
+        assert_eq!(entries.len(), 3);
+        assert_eq!((entries[1].pass.as_str(), entries[1].after.as_str()), ("dce", ""));
+
+        let text = render_report(&entries, source, ExplainFormat::Text);
+        assert!(text.starts_with("3 change(s)\n"));
+        assert!(text.contains("[fold] 2:1\n  - let b=2;\n  + let b=20;\n"), "{}", text);
+        assert!(text.contains("[dce] 1:1\n  - let a=1;\n  + (removed)\n"), "{}", text);
+        assert!(text.contains("[fold] 2:1\n  - let b=20;\n  + let b=200;\n"), "{}", text);
+    }
+
+    #[test]
+    fn test_json_report_and_snippet_length() {
+        let long = format!("let s = \"{}\";", "x".repeat(300));
+        let (mut program, spans) = parse(&long);
+        let mut recorder = ExplainRecorder::new(&program, &spans);
+
+        recorder.begin(&program);
+        program.body.clear();
+        recorder.end("dce", &program);
+
+        let entries = recorder.into_entries();
+        assert_eq!(entries[0].before.chars().count(), MAX_SNIPPET_CHARS);
+
+        let report: Value = serde_json::from_str(&render_report(&entries, &long, ExplainFormat::Json)).unwrap();
+        assert_eq!(report[0]["pass"], "dce");
+        assert_eq!(report[0]["line"], 1);
+        assert_eq!(report[0]["span"]["end"], long.len());
+        assert_eq!("json".parse::<ExplainFormat>(), Ok(ExplainFormat::Json));
+    }
+}
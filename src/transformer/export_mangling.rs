@@ -0,0 +1,244 @@
+//! # Export Mangling
+//!
+//! Renames the public names of a module's named exports to short names when
+//! `TransformerConfig::module_exports` is [`ModuleExports::Mangle`], and returns the
+//! mapping so a bundler or post-processing step can rewrite the importers.
+//!
+//! Exported declarations are split into the declaration and an `export { local as a }`
+//! list, so local bindings keep their names (those are identifier renaming's job).
+//! Destructuring declarations (`export const { x } = o`) can't be split that way and
+//! keep their exported names, as does `default`; generated names never collide with them.
+//!
+//! A module with an `export * from` re-exports names that are only known once the other
+//! module is read, and a short name could shadow one of them, so its exports are kept
+//! with a warning (see [`star_reexport`]). `export * as ns from` keeps its name, which
+//! generated names avoid like the destructured ones.
+
+use std::collections::{BTreeMap, HashSet};
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+
+use crate::parser::ast_types::{ExportSpecifier, Identifier, ObjectPatternProperty, Pattern, Program, Statement};
+use crate::parser::SourceSpan;
+use crate::transformer::identifier_renaming::{NameContext, NameGenerator};
+
+/// How the exported names of a module are treated
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ModuleExports {
+    /// Exported names are the module's interface and are printed as written
+    #[default]
+    Keep,
+    /// Exported names are shortened and reported in an export map
+    Mangle,
+}
+
+impl FromStr for ModuleExports {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "keep" => Ok(Self::Keep),
+            "mangle" => Ok(Self::Mangle),
+            _ => Err(format!("unknown module exports mode '{}' (expected keep or mangle)", value)),
+        }
+    }
+}
+
+/// Renames the exported names of `program`
+///
+/// Names are assigned in source order from a [`NameGenerator`] in module context,
+/// shuffled by `seed`, so importers can bind them without `as`. Returns the mapping from
+/// each original exported name to its new name.
+pub fn mangle_exports(program: &mut Program, seed: Option<u64>) -> BTreeMap<String, String> {
+    let body = std::mem::take(&mut program.body);
+    let mut kept = HashSet::from(["default".to_string()]);
+    for statement in &body {
+        match statement {
+            Statement::ExportNamedDeclaration { declaration: Some(declaration), .. } if declared_names(declaration).is_none() => {
+                declaration_pattern_names(declaration, &mut kept);
+            }
+            Statement::ExportAllDeclaration { exported: Some(exported), .. } => {
+                kept.insert(exported.name.clone());
+            }
+            _ => {}
+        }
+    }
+
+    let mut names = NameGenerator::new(NameContext::Module).with_seed(seed).filter(|name| !kept.contains(name));
+    let mut export_map = BTreeMap::new();
+    let mut rename = |exported: Identifier| {
+        if kept.contains(&exported.name) {
+            return exported;
+        }
+        let name = names.next().expect("name generator is unbounded");
+        export_map.insert(exported.name, name.clone());
+        Identifier { name, span: None }
+    };
+
+    for statement in body {
+        match statement {
+            Statement::ExportNamedDeclaration { declaration: Some(declaration), specifiers, source, attributes, .. } => {
+                match declared_names(&declaration) {
+                    Some(locals) => {
+                        let specifiers = locals
+                            .into_iter()
+                            .map(|local| ExportSpecifier::ExportSpecifier { exported: rename(local.clone()), local })
+                            .collect();
+                        program.body.push(*declaration);
+                        program.body.push(Statement::ExportNamedDeclaration {
+                            declaration: None,
+                            specifiers,
+                            source: None,
+                            attributes: Vec::new(),
+                            span: None,
+                        });
+                    }
+                    None => program.body.push(Statement::ExportNamedDeclaration {
+                        declaration: Some(declaration),
+                        specifiers,
+                        source,
+                        attributes,
+                        span: None,
+                    }),
+                }
+            }
+            Statement::ExportNamedDeclaration { declaration: None, specifiers, source, attributes, .. } => {
+                let specifiers = specifiers
+                    .into_iter()
+                    .map(|ExportSpecifier::ExportSpecifier { local, exported }| ExportSpecifier::ExportSpecifier {
+                        local,
+                        exported: rename(exported),
+                    })
+                    .collect();
+                program.body.push(Statement::ExportNamedDeclaration { declaration: None, specifiers, source, attributes, span: None });
+            }
+            statement => program.body.push(statement),
+        }
+    }
+    export_map
+}
+
+/// Source and span of the first `export * from` in `program`, which keeps its exports
+/// from being mangled
+pub fn star_reexport(program: &Program) -> Option<(&str, Option<SourceSpan>)> {
+    program.body.iter().find_map(|statement| match statement {
+        Statement::ExportAllDeclaration { exported: None, source, span, .. } => Some((source.value.as_str(), span.clone())),
+        _ => None,
+    })
+}
+
+/// Bindings of a declaration that can be exported through a specifier list, or `None`
+/// for destructuring declarations
+fn declared_names(declaration: &Statement) -> Option<Vec<Identifier>> {
+    match declaration {
+        Statement::FunctionDeclaration { id: Some(id), .. } | Statement::ClassDeclaration { id: Some(id), .. } => {
+            Some(vec![id.clone()])
+        }
+        Statement::VariableDeclaration { declarations, .. } => declarations
+            .iter()
+            .map(|declarator| match &declarator.id {
+                Pattern::Identifier(id) => Some(id.clone()),
+                _ => None,
+            })
+            .collect(),
+        _ => None,
+    }
+}
+
+/// Adds every name bound by the patterns of a variable declaration to `names`
+fn declaration_pattern_names(declaration: &Statement, names: &mut HashSet<String>) {
+    fn walk(pattern: &Pattern, names: &mut HashSet<String>) {
+        match pattern {
+            Pattern::Identifier(id) => {
+                names.insert(id.name.clone());
+            }
+            Pattern::ArrayPattern { elements } => elements.iter().flatten().for_each(|element| walk(element, names)),
+            Pattern::ObjectPattern { properties } => {
+                for property in properties {
+                    match property {
+                        ObjectPatternProperty::Property { value, .. } => walk(value, names),
+                        ObjectPatternProperty::RestElement { argument } => walk(argument, names),
+                    }
+                }
+            }
+            Pattern::AssignmentPattern { left, .. } => walk(left, names),
+            Pattern::RestElement { argument } => walk(argument, names),
+        }
+    }
+
+    if let Statement::VariableDeclaration { declarations, .. } = declaration {
+        declarations.iter().for_each(|declarator| walk(&declarator.id, names));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::generator::{Generator, GeneratorConfig};
+    use crate::parser::ast_types::{ClassBody, Expression, ProgramSourceType, VariableDeclarationKind, VariableDeclarator};
+    use crate::parser::{parse_js, ParserConfig};
+
+    fn mangle(source: &str) -> (String, BTreeMap<String, String>) {
+        let mut program = parse_js(source, "test.mjs", &ParserConfig::default()).ast.unwrap();
+        let export_map = mangle_exports(&mut program, None);
+        let code = Generator::new(GeneratorConfig::default()).generate(&program, None).unwrap().code;
+        (code, export_map)
+    }
+
+    #[test]
+    fn test_exported_names_are_mangled_and_mapped() {
+        let (code, export_map) = mangle(
+            "export function parseConfig() {}\nexport const first = 1, second = 2;\nconst local = 3;\nexport { local as renamed, local as default };\nexport { helper } from \"./helper\";\n",
+        );
+        assert_eq!(
+            code,
+            "function parseConfig(){}export{parseConfig as a};const first=1, second=2;export{first as b,second as c};const local=3;export{local as d,local as default};export{helper as e}from'./helper';"
+        );
+        let expected = [("parseConfig", "a"), ("first", "b"), ("second", "c"), ("renamed", "d"), ("helper", "e")];
+        assert_eq!(export_map, expected.iter().map(|(from, to)| (from.to_string(), to.to_string())).collect());
+    }
+
+    #[test]
+    fn test_destructured_exports_are_kept() {
+        // The parser does not convert destructuring declarators yet, so build the AST by hand
+        let identifier = |name: &str| Identifier { name: name.to_string(), span: None };
+        let destructuring = Statement::VariableDeclaration {
+            declarations: vec![VariableDeclarator {
+                id: Pattern::ArrayPattern { elements: vec![Some(Pattern::Identifier(identifier("a"))), None] },
+                init: Some(Expression::Identifier(identifier("o"))),
+                span: None,
+            }],
+            kind: VariableDeclarationKind::Const,
+            span: None,
+        };
+        let class = Statement::ClassDeclaration {
+            id: Some(identifier("Widget")),
+            super_class: None,
+            body: ClassBody { body: vec![], span: None },
+            decorators: vec![],
+            span: None,
+        };
+        let export = |declaration| Statement::ExportNamedDeclaration {
+            declaration: Some(Box::new(declaration)),
+            specifiers: vec![],
+            source: None,
+            attributes: vec![],
+            span: None,
+        };
+        let mut program = Program {
+            body: vec![export(destructuring), export(class)],
+            source_type: ProgramSourceType::Module,
+        };
+
+        let export_map = mangle_exports(&mut program, None);
+        assert_eq!(export_map.len(), 1);
+        assert_eq!(export_map["Widget"], "b");
+        assert_eq!(program.body.len(), 3);
+        assert!(matches!(&program.body[0], Statement::ExportNamedDeclaration { declaration: Some(_), .. }));
+
+        assert_eq!("mangle".parse(), Ok(ModuleExports::Mangle));
+        assert!("rename".parse::<ModuleExports>().is_err());
+    }
+}
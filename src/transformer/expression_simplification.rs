@@ -3,9 +3,36 @@
 //! This module implements Pass 3 of the transformation pipeline: Expression Simplification
 //! and Compression. It performs constant folding, algebraic simplifications, and other
 //! optimizations while ensuring semantic correctness.
+//!
+//! ## Pure Global Call Folding
+//!
+//! Calls to a conservative whitelist of well-known pure globals are evaluated at compile
+//! time when every argument is a literal:
+//!
+//! - `Math.max`, `Math.min`, `Math.abs`, `Math.floor`, `Math.ceil`, `Math.round`,
+//!   `Math.trunc`, `Math.sign`, `Math.pow`
+//! - `String(literal)`, `Number("digits")`, `parseInt("digits")`
+//! - `"literal".length`
+//!
+//! A fold is skipped when the global is shadowed by a local binding, or when the result
+//! would be non-finite, `-0`, outside the safe integer range, or would not round-trip
+//! through the printer exactly.
+
+use std::collections::HashSet;
+
+use crate::analyzer::SymbolTable;
+use crate::parser::ast_types::{
+    ArrowFunctionBody, BlockStatement, ClassElement, Expression, ForInit, Literal,
+    NumberLiteral, ObjectProperty, Program, Statement, StringLiteral, UnaryOperator,
+    VariableDeclarator,
+};
+use crate::transformer::{parallel, TransformResult, TransformerConfig};
 
-use crate::parser::ast_types::Program;
-use crate::transformer::{TransformResult, TransformerConfig};
+/// Largest integer that can be represented exactly (2^53 - 1)
+const MAX_SAFE_INTEGER: f64 = 9_007_199_254_740_991.0;
+
+/// Global bindings the folder relies on; a local declaration of any of them disables folding
+const FOLDABLE_GLOBALS: &[&str] = &["Math", "String", "Number", "parseInt"];
 
 /// Result of expression simplification operation
 #[derive(Debug, Clone)]
@@ -18,28 +45,487 @@ pub struct ExpressionSimplificationResult {
     pub warnings: Vec<String>,
 }
 
+/// Traversal state for one chunk of top-level statements
+struct SimplificationContext<'a> {
+    /// Whitelisted globals that are shadowed by a declaration somewhere in the program
+    shadowed_globals: &'a HashSet<String>,
+    /// Number of folds performed so far
+    simplified_count: u32,
+}
+
 /// Simplifies expressions in the given AST
+///
+/// # Arguments
+///
+/// * `ast` - The AST to transform (modified in place)
+/// * `symbol_table` - Symbol table used to detect shadowed globals
+/// * `config` - Transformer configuration
+///
+/// # Returns
+///
+/// Returns `ExpressionSimplificationResult` with statistics about the simplification process
 pub fn simplify_expressions(
-    _ast: &mut Program,
+    ast: &mut Program,
+    symbol_table: &SymbolTable,
     config: &TransformerConfig,
 ) -> TransformResult<ExpressionSimplificationResult> {
-    if config.verbose {
-        println!("🔍 Analyzing expressions for simplification");
-    }
+    tracing::debug!("🔍 Analyzing expressions for simplification");
+
+    let shadowed_globals: HashSet<String> = FOLDABLE_GLOBALS
+        .iter()
+        .filter(|global| symbol_table.is_declared(global))
+        .map(|global| global.to_string())
+        .collect();
 
-    // TODO: Implement actual expression simplification logic
-    // For now, return a placeholder result
+    // Folding decisions depend only on the statement and the shadowed globals, so
+    // top-level statements can be simplified independently on worker threads
+    let simplified_count: u32 = parallel::transform_chunks(&mut ast.body, config.worker_threads, |chunk| {
+        let mut context = SimplificationContext {
+            shadowed_globals: &shadowed_globals,
+            simplified_count: 0,
+        };
+        for statement in chunk {
+            simplify_statement(statement, &mut context);
+        }
+        context.simplified_count
+    })
+    .into_iter()
+    .sum();
+
+    tracing::debug!("🔧 Folded {} pure global calls", simplified_count);
+
+    // TODO: Implement general constant folding and algebraic simplification
     Ok(ExpressionSimplificationResult {
-        simplified_count: 0,
+        simplified_count,
         rollbacks: 0,
         warnings: vec!["Expression simplification not yet fully implemented".to_string()],
     })
 }
 
+/// Simplifies all expressions reachable from a statement
+fn simplify_statement(statement: &mut Statement, context: &mut SimplificationContext) {
+    match statement {
+        Statement::VariableDeclaration { declarations, .. } => {
+            simplify_declarators(declarations, context);
+        }
+        Statement::FunctionDeclaration { body, .. } => simplify_block(body, context),
+        Statement::ClassDeclaration { super_class, body, decorators, .. } => {
+            for decorator in decorators {
+                simplify_expression(decorator, context);
+            }
+            if let Some(super_class) = super_class {
+                simplify_expression(super_class, context);
+            }
+            for element in &mut body.body {
+                match element {
+                    ClassElement::PropertyDefinition { value, decorators, .. } => {
+                        for decorator in decorators {
+                            simplify_expression(decorator, context);
+                        }
+                        if let Some(value) = value {
+                            simplify_expression(value, context);
+                        }
+                    }
+                    ClassElement::MethodDefinition { value, decorators, .. } => {
+                        for decorator in decorators {
+                            simplify_expression(decorator, context);
+                        }
+                        simplify_block(&mut value.body, context);
+                    }
+                }
+            }
+        }
+        Statement::ExpressionStatement { expression, .. } => simplify_expression(expression, context),
+        Statement::BlockStatement { body, .. } => {
+            for stmt in body {
+                simplify_statement(stmt, context);
+            }
+        }
+        Statement::ReturnStatement { argument, .. } => {
+            if let Some(argument) = argument {
+                simplify_expression(argument, context);
+            }
+        }
+        Statement::IfStatement { test, consequent, alternate, .. } => {
+            simplify_expression(test, context);
+            simplify_statement(consequent, context);
+            if let Some(alternate) = alternate {
+                simplify_statement(alternate, context);
+            }
+        }
+        Statement::WhileStatement { test, body, .. } => {
+            simplify_expression(test, context);
+            simplify_statement(body, context);
+        }
+        Statement::ForStatement { init, test, update, body, .. } => {
+            match init {
+                Some(ForInit::VariableDeclaration { declarations, .. }) => {
+                    simplify_declarators(declarations, context);
+                }
+                Some(ForInit::Expression(expr)) => simplify_expression(expr, context),
+                None => {}
+            }
+            if let Some(test) = test {
+                simplify_expression(test, context);
+            }
+            if let Some(update) = update {
+                simplify_expression(update, context);
+            }
+            simplify_statement(body, context);
+        }
+        Statement::ForOfStatement { left, right, body, .. } => {
+            if let ForInit::Expression(expr) = left {
+                simplify_expression(expr, context);
+            }
+            simplify_expression(right, context);
+            simplify_statement(body, context);
+        }
+        Statement::ImportDeclaration { .. }
+        | Statement::ExportAllDeclaration { .. }
+        | Statement::EmptyStatement
+        | Statement::DebuggerStatement => {}
+        Statement::ExportNamedDeclaration { declaration, .. } => {
+            if let Some(declaration) = declaration {
+                simplify_statement(declaration, context);
+            }
+        }
+        Statement::ExportDefaultDeclaration { declaration, .. } => simplify_statement(declaration, context),
+    }
+}
+
+/// Simplifies the initializers of a declarator list
+fn simplify_declarators(declarations: &mut [VariableDeclarator], context: &mut SimplificationContext) {
+    for declarator in declarations {
+        if let Some(init) = &mut declarator.init {
+            simplify_expression(init, context);
+        }
+    }
+}
+
+/// Simplifies every statement in a block
+fn simplify_block(block: &mut BlockStatement, context: &mut SimplificationContext) {
+    for stmt in &mut block.body {
+        simplify_statement(stmt, context);
+    }
+}
+
+/// Simplifies an expression bottom-up so nested calls fold before their parents
+fn simplify_expression(expression: &mut Expression, context: &mut SimplificationContext) {
+    match expression {
+        Expression::BinaryExpression { left, right, .. } => {
+            simplify_expression(left, context);
+            simplify_expression(right, context);
+        }
+        Expression::UnaryExpression { argument, .. }
+        | Expression::UpdateExpression { argument, .. }
+        | Expression::AwaitExpression { argument }
+        | Expression::ChainExpression { expression: argument }
+        | Expression::SpreadElement { argument } => {
+            simplify_expression(argument, context);
+        }
+        Expression::AssignmentExpression { right, .. } => simplify_expression(right, context),
+        Expression::YieldExpression { argument, .. } => {
+            if let Some(argument) = argument {
+                simplify_expression(argument, context);
+            }
+        }
+        Expression::CallExpression { callee, arguments, .. } | Expression::NewExpression { callee, arguments } => {
+            simplify_expression(callee, context);
+            for argument in arguments {
+                simplify_expression(argument, context);
+            }
+        }
+        Expression::ImportExpression { source, options } => {
+            simplify_expression(source, context);
+            if let Some(options) = options {
+                simplify_expression(options, context);
+            }
+        }
+        Expression::MemberExpression { object, property, computed, .. } => {
+            simplify_expression(object, context);
+            if *computed {
+                simplify_expression(property, context);
+            }
+        }
+        Expression::FunctionExpression(function) => simplify_block(&mut function.body, context),
+        Expression::ArrowFunctionExpression { body, .. } => match body {
+            ArrowFunctionBody::BlockStatement(block) => simplify_block(block, context),
+            ArrowFunctionBody::Expression(expr) => simplify_expression(expr, context),
+        },
+        Expression::ObjectExpression { properties } => {
+            for property in properties {
+                match property {
+                    ObjectProperty::Property { value, .. } => simplify_expression(value, context),
+                    ObjectProperty::SpreadElement { argument } => simplify_expression(argument, context),
+                }
+            }
+        }
+        Expression::ArrayExpression { elements } => {
+            for element in elements.iter_mut().flatten() {
+                simplify_expression(element, context);
+            }
+        }
+        Expression::TemplateLiteral { expressions, .. } => {
+            for expr in expressions {
+                simplify_expression(expr, context);
+            }
+        }
+        // Only the tag and substitutions; the quasis are passed to the tag as written
+        Expression::TaggedTemplateExpression { tag, expressions, .. } => {
+            simplify_expression(tag, context);
+            for expr in expressions {
+                simplify_expression(expr, context);
+            }
+        }
+        Expression::ConditionalExpression { test, consequent, alternate } => {
+            simplify_expression(test, context);
+            simplify_expression(consequent, context);
+            simplify_expression(alternate, context);
+        }
+        Expression::Identifier(_)
+        | Expression::Literal(_)
+        | Expression::ThisExpression
+        | Expression::MetaProperty { .. } => {}
+    }
+
+    if let Some(folded) = fold_pure_global_call(expression, context) {
+        *expression = folded;
+        context.simplified_count += 1;
+    }
+}
+
+/// Evaluates a whitelisted pure call or property read, if all its inputs are literals
+fn fold_pure_global_call(expression: &Expression, context: &SimplificationContext) -> Option<Expression> {
+    match expression {
+        Expression::CallExpression { callee, arguments, optional: false } => match callee.as_ref() {
+            Expression::Identifier(id) if !context.shadowed_globals.contains(&id.name) => {
+                match id.name.as_str() {
+                    "String" => fold_string_call(arguments),
+                    "Number" => fold_integer_parse(arguments, false),
+                    "parseInt" => fold_integer_parse(arguments, true),
+                    _ => None,
+                }
+            }
+            Expression::MemberExpression { object, property, computed: false, optional: false } => {
+                match (object.as_ref(), property.as_ref()) {
+                    (Expression::Identifier(object), Expression::Identifier(method))
+                        if object.name == "Math" && !context.shadowed_globals.contains("Math") =>
+                    {
+                        fold_math_call(&method.name, arguments)
+                    }
+                    _ => None,
+                }
+            }
+            _ => None,
+        },
+        Expression::MemberExpression { object, property, computed: false, optional: false } => {
+            match (object.as_ref(), property.as_ref()) {
+                (Expression::Literal(Literal::String(string)), Expression::Identifier(prop))
+                    if prop.name == "length" && !string.lone_surrogates =>
+                {
+                    // JavaScript string length counts UTF-16 code units
+                    number_expression(string.value.encode_utf16().count() as f64)
+                }
+                _ => None,
+            }
+        }
+        _ => None,
+    }
+}
+
+/// Folds `Math.<method>(...)` for methods with exact, platform-independent results
+fn fold_math_call(method: &str, arguments: &[Expression]) -> Option<Expression> {
+    let values = arguments
+        .iter()
+        .map(numeric_literal_value)
+        .collect::<Option<Vec<f64>>>()?;
+
+    let result = match (method, values.as_slice()) {
+        ("max", [first, rest @ ..]) => rest.iter().try_fold(*first, |acc, &v| {
+            // NaN or signed-zero comparisons are left to the runtime
+            (!(v.is_nan() || v == 0.0 && acc == 0.0)).then(|| acc.max(v))
+        })?,
+        ("min", [first, rest @ ..]) => rest.iter().try_fold(*first, |acc, &v| {
+            (!(v.is_nan() || v == 0.0 && acc == 0.0)).then(|| acc.min(v))
+        })?,
+        ("abs", [value]) => value.abs(),
+        ("floor", [value]) => value.floor(),
+        ("ceil", [value]) => value.ceil(),
+        ("round", [value]) => {
+            // Math.round rounds halves towards +Infinity, unlike f64::round
+            let floor = value.floor();
+            let rounded = if value - floor >= 0.5 { floor + 1.0 } else { floor };
+            if rounded == 0.0 && *value < 0.0 {
+                // JavaScript yields -0 here, which has no literal form
+                return None;
+            }
+            rounded
+        }
+        ("trunc", [value]) => value.trunc(),
+        ("sign", [value]) => {
+            if *value == 0.0 {
+                return None;
+            }
+            value.signum()
+        }
+        ("pow", [base, exponent]) => {
+            // Only integral powers are exact; fractional ones depend on libm
+            if base.fract() != 0.0 || exponent.fract() != 0.0 || *exponent < 0.0 {
+                return None;
+            }
+            base.powf(*exponent)
+        }
+        _ => return None,
+    };
+
+    number_expression(result)
+}
+
+/// Folds `String(literal)` into a string literal
+fn fold_string_call(arguments: &[Expression]) -> Option<Expression> {
+    let value = match arguments {
+        [] => String::new(),
+        [Expression::Literal(Literal::String(string))] => {
+            return Some(Expression::Literal(Literal::String(StringLiteral { span: None, ..string.clone() })));
+        }
+        [Expression::Literal(Literal::Boolean(boolean))] => boolean.value.to_string(),
+        [Expression::Literal(Literal::Null)] => "null".to_string(),
+        [argument] => {
+            let value = numeric_literal_value(argument)?;
+            // Only integers share their textual form between Rust and JavaScript
+            if !is_safe_integer(value) || (value == 0.0 && value.is_sign_negative()) {
+                return None;
+            }
+            format!("{}", value as i64)
+        }
+        _ => return None,
+    };
+
+    Some(Expression::Literal(Literal::String(StringLiteral::new(value, None))))
+}
+
+/// Folds `Number("42")` and `parseInt("42")` for plain decimal integer strings
+fn fold_integer_parse(arguments: &[Expression], allow_radix: bool) -> Option<Expression> {
+    let text = match arguments {
+        [Expression::Literal(Literal::String(string))] => &string.value,
+        [Expression::Literal(Literal::String(string)), radix] if allow_radix => {
+            if numeric_literal_value(radix)? != 10.0 {
+                return None;
+            }
+            &string.value
+        }
+        _ => return None,
+    };
+
+    let trimmed = text.trim_matches(is_js_whitespace);
+    let digits = trimmed.strip_prefix(['-', '+']).unwrap_or(trimmed);
+    if digits.is_empty() || !digits.chars().all(|c| c.is_ascii_digit()) {
+        return None;
+    }
+
+    let value: f64 = trimmed.parse().ok()?;
+    if !is_safe_integer(value) || (value == 0.0 && trimmed.starts_with('-')) {
+        return None;
+    }
+
+    number_expression(value)
+}
+
+/// Matches the characters JS's `StringToNumber` trims: WhiteSpace and LineTerminator
+///
+/// Rust's `char::is_whitespace` differs on U+0085 (not JS whitespace) and U+FEFF (JS whitespace).
+fn is_js_whitespace(c: char) -> bool {
+    matches!(
+        c,
+        '\u{0009}'..='\u{000D}'
+            | '\u{0020}'
+            | '\u{00A0}'
+            | '\u{1680}'
+            | '\u{2000}'..='\u{200A}'
+            | '\u{2028}'
+            | '\u{2029}'
+            | '\u{202F}'
+            | '\u{205F}'
+            | '\u{3000}'
+            | '\u{FEFF}'
+    )
+}
+
+/// Extracts a numeric value from a number literal or a negated number literal
+fn numeric_literal_value(expression: &Expression) -> Option<f64> {
+    match expression {
+        Expression::Literal(Literal::Number(number)) => Some(number.value),
+        Expression::UnaryExpression { operator: UnaryOperator::Minus, argument, .. } => {
+            match argument.as_ref() {
+                Expression::Literal(Literal::Number(number)) => Some(-number.value),
+                _ => None,
+            }
+        }
+        _ => None,
+    }
+}
+
+/// Builds a number expression, rejecting results the printer cannot reproduce exactly
+fn number_expression(value: f64) -> Option<Expression> {
+    if !value.is_finite() || (value == 0.0 && value.is_sign_negative()) {
+        return None;
+    }
+    if value.fract() == 0.0 && !is_safe_integer(value) {
+        return None;
+    }
+
+    let literal = Expression::Literal(Literal::Number(NumberLiteral { value: value.abs(), raw: None, span: None }));
+    if value < 0.0 {
+        // Negative values are expressed the way the parser produces them
+        Some(Expression::UnaryExpression {
+            operator: UnaryOperator::Minus,
+            argument: Box::new(literal),
+            prefix: true,
+        })
+    } else {
+        Some(literal)
+    }
+}
+
+/// Checks whether a value is an integer within JavaScript's safe integer range
+fn is_safe_integer(value: f64) -> bool {
+    value.fract() == 0.0 && value.abs() <= MAX_SAFE_INTEGER
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::analyzer::{analyze_ast, AnalyzerConfig};
     use crate::parser::ast_types::{Program, ProgramSourceType};
+    use crate::parser::{parse_js, ParserConfig};
+
+    /// Parses, analyzes and simplifies the source, returning the folded first expression
+    fn simplify_source(source: &str) -> (Program, u32) {
+        let mut ast = parse_js(source, "test.js", &ParserConfig::default()).ast.unwrap();
+        let analysis = analyze_ast(&ast, &AnalyzerConfig::default()).unwrap();
+        let config = TransformerConfig::default();
+
+        let result = simplify_expressions(&mut ast, &analysis.symbol_table, &config).unwrap();
+        (ast, result.simplified_count)
+    }
+
+    fn first_init(ast: &Program) -> &Expression {
+        match &ast.body[0] {
+            Statement::VariableDeclaration { declarations, .. } => declarations[0].init.as_ref().unwrap(),
+            _ => panic!("Expected variable declaration"),
+        }
+    }
+
+    fn assert_folds_to_number(source: &str, expected: f64) {
+        let (ast, count) = simplify_source(source);
+        assert_eq!(count, 1, "expected a fold for {}", source);
+        assert_eq!(numeric_literal_value(first_init(&ast)), Some(expected), "for {}", source);
+    }
+
+    fn assert_not_folded(source: &str) {
+        let (_, count) = simplify_source(source);
+        assert_eq!(count, 0, "expected no fold for {}", source);
+    }
 
     #[test]
     fn test_placeholder_expression_simplification() {
@@ -50,9 +536,80 @@ mod tests {
 
         let config = TransformerConfig::default();
 
-        let result = simplify_expressions(&mut ast, &config).unwrap();
+        let result = simplify_expressions(&mut ast, &SymbolTable::new(), &config).unwrap();
         assert_eq!(result.simplified_count, 0);
         assert_eq!(result.rollbacks, 0);
         assert!(!result.warnings.is_empty());
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_fold_math_calls() {
+        assert_folds_to_number("let x = Math.max(1, 2);", 2.0);
+        assert_folds_to_number("let x = Math.min(3, -4, 5);", -4.0);
+        assert_folds_to_number("let x = Math.abs(-7);", 7.0);
+        assert_folds_to_number("let x = Math.floor(2.5);", 2.0);
+        assert_folds_to_number("let x = Math.round(-2.5);", -2.0);
+        assert_folds_to_number("let x = Math.round(0.49999999999999994);", 0.0);
+        assert_folds_to_number("let x = Math.pow(2, 10);", 1024.0);
+    }
+
+    #[test]
+    fn test_fold_conversions_and_length() {
+        assert_folds_to_number("let x = parseInt(\"42\");", 42.0);
+        assert_folds_to_number("let x = parseInt(\"-42\", 10);", -42.0);
+        assert_folds_to_number("let x = Number(\" 7 \");", 7.0);
+        assert_folds_to_number("let x = Number(\"\\uFEFF\\u2028 7\\u3000\");", 7.0);
+        assert_folds_to_number("let x = \"abc\".length;", 3.0);
+        assert_folds_to_number("let x = \"😀\".length;", 2.0);
+
+        let (ast, count) = simplify_source("let x = String(5);");
+        assert_eq!(count, 1);
+        assert!(matches!(first_init(&ast), Expression::Literal(Literal::String(s)) if s.value == "5"));
+    }
+
+    #[test]
+    fn test_nested_calls_fold_bottom_up() {
+        let (ast, count) = simplify_source("let x = Math.max(Math.abs(-3), 2);");
+        assert_eq!(count, 2);
+        assert_eq!(numeric_literal_value(first_init(&ast)), Some(3.0));
+    }
+
+    #[test]
+    fn test_unsafe_calls_are_not_folded() {
+        assert_not_folded("let x = Math.random();");
+        assert_not_folded("let x = Math.max(a, 2);");
+        assert_not_folded("let x = Math.pow(2, 0.5);");
+        assert_not_folded("let x = Math.pow(2, 64);");
+        assert_not_folded("let x = Math.round(-0.2);");
+        assert_not_folded("let x = parseInt(\"0x1F\");");
+        assert_not_folded("let x = parseInt(\"99999999999999999999\");");
+        assert_not_folded("let x = String(0.1);");
+        assert_not_folded("let x = Number(\"\\u008542\");");
+    }
+
+    #[test]
+    fn test_shadowed_globals_are_not_folded() {
+        assert_not_folded("let Math = { max: function(a, b) { return a; } }; let x = Math.max(1, 2);");
+        assert_not_folded("function parseInt(s) { return 0; } let x = parseInt(\"42\");");
+    }
+
+    #[test]
+    fn test_parallel_simplification_matches_sequential() {
+        let source: String = (0..parallel::MIN_STATEMENTS_PER_WORKER * 4)
+            .map(|i| format!("let v{i} = Math.max({i}, 7) + \"ab\".length;\n"))
+            .collect();
+        let parsed = parse_js(&source, "test.js", &ParserConfig::default()).ast.unwrap();
+        let analysis = analyze_ast(&parsed, &AnalyzerConfig::default()).unwrap();
+
+        let run = |worker_threads| {
+            let mut ast = parsed.clone();
+            let config = TransformerConfig { worker_threads, ..TransformerConfig::default() };
+            let result = simplify_expressions(&mut ast, &analysis.symbol_table, &config).unwrap();
+            (serde_json::to_value(&ast).unwrap(), result.simplified_count)
+        };
+
+        let sequential = run(1);
+        assert_eq!(sequential.1, parallel::MIN_STATEMENTS_PER_WORKER as u32 * 8);
+        assert_eq!(run(4), sequential);
+    }
+}
@@ -0,0 +1,355 @@
+//! # Hoisting
+//!
+//! Two optional passes that move declarations to the top of their function (or the
+//! program), where JavaScript hoists them anyway:
+//!
+//! - **hoist_funs** moves function declarations in a function body ahead of the other
+//!   statements, keeping their relative order. Imports stay first. Functions declared in
+//!   nested blocks are block-scoped and are not moved.
+//! - **hoist_vars** merges every `var` statement of a function into one declaration
+//!   list: the first top-level `var` statement declares all the names, and the others
+//!   become plain assignments (`var a = 1` → `a = 1`) or disappear when they have no
+//!   initializer. This gives the later passes one declaration per function to work
+//!   with, but it can also grow the output when many declarations have initializers,
+//!   which is why it is off by default. `var` statements with destructuring patterns and
+//!   loop-head declarations are left in place.
+
+use crate::analyzer::SemanticAnalysis;
+use crate::parser::ast_types::{
+    ArrowFunctionBody, AssignmentOperator, ClassElement, Expression, Identifier, Pattern, Program, Statement,
+    VariableDeclarationKind, VariableDeclarator,
+};
+use crate::transformer::plugin::{self, PluginContext, TransformPlugin};
+use crate::transformer::{TransformResult, TransformerConfig};
+
+/// Result of a hoisting pass
+#[derive(Debug, Clone)]
+pub struct HoistingResult {
+    /// Number of declarations moved or merged
+    pub hoisted_count: u32,
+    /// Any warnings generated while hoisting
+    pub warnings: Vec<String>,
+}
+
+/// Moves function declarations to the top of every function body and the program
+pub fn hoist_functions(
+    ast: &mut Program,
+    analysis: &SemanticAnalysis,
+    config: &TransformerConfig,
+) -> TransformResult<HoistingResult> {
+    let hoisted_count = for_each_function_body(ast, analysis, config, hoist_functions_in_body)?;
+
+    if hoisted_count > 0 {
+        tracing::debug!("⬆️  Hoisted {} function declarations", hoisted_count);
+    }
+
+    Ok(HoistingResult { hoisted_count, warnings: Vec::new() })
+}
+
+/// Merges the `var` statements of every function body and the program into one
+pub fn hoist_variables(
+    ast: &mut Program,
+    analysis: &SemanticAnalysis,
+    config: &TransformerConfig,
+) -> TransformResult<HoistingResult> {
+    let hoisted_count = for_each_function_body(ast, analysis, config, hoist_variables_in_body)?;
+
+    if hoisted_count > 0 {
+        tracing::debug!("⬆️  Merged {} var statements", hoisted_count);
+    }
+
+    Ok(HoistingResult { hoisted_count, warnings: Vec::new() })
+}
+
+/// Runs `hoist` on the program body and the body of every function, summing its changes
+fn for_each_function_body(
+    ast: &mut Program,
+    analysis: &SemanticAnalysis,
+    config: &TransformerConfig,
+    hoist: fn(&mut Vec<Statement>) -> u32,
+) -> TransformResult<u32> {
+    let mut visitor = FunctionBodies { hoist, hoisted: 0 };
+    let mut ctx = PluginContext::new(analysis, config);
+    plugin::run_plugin(&mut visitor, ast, &mut ctx)?;
+    Ok(visitor.hoisted)
+}
+
+/// Visits every statement list that is the body of a var scope
+struct FunctionBodies {
+    hoist:   fn(&mut Vec<Statement>) -> u32,
+    hoisted: u32,
+}
+
+impl TransformPlugin for FunctionBodies {
+    fn name(&self) -> &str {
+        "hoisting"
+    }
+
+    fn enter_program(&mut self, program: &mut Program, _ctx: &mut PluginContext) -> TransformResult<()> {
+        self.hoisted += (self.hoist)(&mut program.body);
+        Ok(())
+    }
+
+    fn enter_statement(&mut self, stmt: &mut Statement, _ctx: &mut PluginContext) -> TransformResult<()> {
+        match stmt {
+            Statement::FunctionDeclaration { body, .. } => self.hoisted += (self.hoist)(&mut body.body),
+            Statement::ClassDeclaration { body, .. } => {
+                for element in &mut body.body {
+                    if let ClassElement::MethodDefinition { value, .. } = element {
+                        self.hoisted += (self.hoist)(&mut value.body.body);
+                    }
+                }
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    fn enter_expression(&mut self, expr: &mut Expression, _ctx: &mut PluginContext) -> TransformResult<()> {
+        match expr {
+            Expression::FunctionExpression(function) => self.hoisted += (self.hoist)(&mut function.body.body),
+            Expression::ArrowFunctionExpression { body: ArrowFunctionBody::BlockStatement(block), .. } => {
+                self.hoisted += (self.hoist)(&mut block.body)
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+}
+
+/// Moves the function declarations of `body` behind its imports, returning how many moved
+fn hoist_functions_in_body(body: &mut Vec<Statement>) -> u32 {
+    let is_function = |statement: &Statement| matches!(statement, Statement::FunctionDeclaration { .. });
+    let start = body
+        .iter()
+        .take_while(|statement| matches!(statement, Statement::ImportDeclaration { .. }))
+        .count();
+    let leading = body[start..].iter().take_while(|statement| is_function(statement)).count();
+    let moved = body[start + leading..].iter().filter(|statement| is_function(statement)).count();
+    if moved == 0 {
+        return 0;
+    }
+
+    let rest = body.split_off(start);
+    let (functions, others): (Vec<_>, Vec<_>) = rest.into_iter().partition(is_function);
+    body.extend(functions);
+    body.extend(others);
+    moved as u32
+}
+
+/// Declares every `var` of `body` in one statement, returning how many statements merged
+fn hoist_variables_in_body(body: &mut Vec<Statement>) -> u32 {
+    if count_hoistable(body) < 2 {
+        return 0;
+    }
+
+    let host = body.iter().position(is_hoistable_var);
+    let mut names = Vec::new();
+    let mut hoisted = 0;
+    let host = hoist_in_list(body, host, &mut names, &mut hoisted);
+
+    match host.map(|index| &mut body[index]) {
+        Some(Statement::VariableDeclaration { declarations, .. }) => {
+            for name in names {
+                let declared = declarations
+                    .iter()
+                    .any(|declarator| matches!(&declarator.id, Pattern::Identifier(id) if id.name == name));
+                if !declared {
+                    declarations.push(VariableDeclarator { id: Pattern::Identifier(Identifier { name, span: None }), init: None, span: None });
+                }
+            }
+        }
+        _ => {
+            let start = body
+                .iter()
+                .take_while(|statement| matches!(statement, Statement::ImportDeclaration { .. }))
+                .count();
+            let declarations = names
+                .into_iter()
+                .map(|name| VariableDeclarator { id: Pattern::Identifier(Identifier { name, span: None }), init: None, span: None })
+                .collect();
+            body.insert(start, Statement::VariableDeclaration { declarations, kind: VariableDeclarationKind::Var, span: None });
+        }
+    }
+    hoisted
+}
+
+/// A `var` statement whose declarators all bind a plain identifier
+fn is_hoistable_var(statement: &Statement) -> bool {
+    matches!(
+        statement,
+        Statement::VariableDeclaration { kind: VariableDeclarationKind::Var, declarations, .. }
+            if declarations.iter().all(|declarator| matches!(declarator.id, Pattern::Identifier(_)))
+    )
+}
+
+/// Counts the hoistable `var` statements of a var scope, without entering nested functions
+fn count_hoistable(list: &[Statement]) -> usize {
+    list.iter().map(count_hoistable_in).sum()
+}
+
+fn count_hoistable_in(statement: &Statement) -> usize {
+    if is_hoistable_var(statement) {
+        return 1;
+    }
+    match statement {
+        Statement::BlockStatement { body, .. } => count_hoistable(body),
+        Statement::IfStatement { consequent, alternate, .. } => {
+            count_hoistable_in(consequent) + alternate.as_deref().map_or(0, count_hoistable_in)
+        }
+        Statement::WhileStatement { body, .. }
+        | Statement::ForStatement { body, .. }
+        | Statement::ForOfStatement { body, .. } => count_hoistable_in(body),
+        _ => 0,
+    }
+}
+
+/// Replaces the hoistable `var` statements of `list` (except `host`) by assignments,
+/// returning the position of the host afterwards
+fn hoist_in_list(list: &mut Vec<Statement>, host: Option<usize>, names: &mut Vec<String>, hoisted: &mut u32) -> Option<usize> {
+    let mut new_host = None;
+    let mut result = Vec::with_capacity(list.len());
+    for (index, mut statement) in std::mem::take(list).into_iter().enumerate() {
+        if Some(index) == host {
+            new_host = Some(result.len());
+        } else if is_hoistable_var(&statement) {
+            result.extend(declaration_to_assignments(statement, names));
+            *hoisted += 1;
+            continue;
+        } else {
+            hoist_in_nested(&mut statement, names, hoisted);
+        }
+        result.push(statement);
+    }
+    *list = result;
+    new_host
+}
+
+/// Hoists the `var` statements nested in the blocks and loop bodies of `statement`
+fn hoist_in_nested(statement: &mut Statement, names: &mut Vec<String>, hoisted: &mut u32) {
+    match statement {
+        Statement::BlockStatement { body, .. } => {
+            hoist_in_list(body, None, names, hoisted);
+        }
+        Statement::IfStatement { consequent, alternate, .. } => {
+            hoist_in_branch(consequent, names, hoisted);
+            if let Some(alternate) = alternate {
+                hoist_in_branch(alternate, names, hoisted);
+            }
+        }
+        Statement::WhileStatement { body, .. }
+        | Statement::ForStatement { body, .. }
+        | Statement::ForOfStatement { body, .. } => hoist_in_branch(body, names, hoisted),
+        _ => {}
+    }
+}
+
+/// Hoists a `var` statement used directly as an `if` branch or loop body
+fn hoist_in_branch(branch: &mut Box<Statement>, names: &mut Vec<String>, hoisted: &mut u32) {
+    if !is_hoistable_var(branch) {
+        hoist_in_nested(branch, names, hoisted);
+        return;
+    }
+    let statement = std::mem::replace(branch.as_mut(), Statement::BlockStatement { body: Vec::new(), span: None });
+    let mut assignments = declaration_to_assignments(statement, names);
+    **branch = if assignments.len() == 1 {
+        assignments.remove(0)
+    } else {
+        Statement::BlockStatement { body: assignments, span: None }
+    };
+    *hoisted += 1;
+}
+
+/// Records the names a `var` statement declares and turns its initializers into assignments
+fn declaration_to_assignments(statement: Statement, names: &mut Vec<String>) -> Vec<Statement> {
+    let Statement::VariableDeclaration { declarations, .. } = statement else {
+        return vec![statement];
+    };
+    declarations
+        .into_iter()
+        .filter_map(|declarator| {
+            let Pattern::Identifier(id) = declarator.id else {
+                unreachable!("hoistable declarations bind identifiers");
+            };
+            if !names.contains(&id.name) {
+                names.push(id.name.clone());
+            }
+            let span = declarator.span;
+            declarator.init.map(|init| Statement::ExpressionStatement {
+                expression: Expression::AssignmentExpression {
+                    left:     Box::new(Expression::Identifier(id)),
+                    operator: AssignmentOperator::Assign,
+                    right:    Box::new(init),
+                },
+                span,
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::transformer::tests::run_pass;
+    use crate::analyzer::{analyze_ast, AnalyzerConfig};
+    use crate::parser::ast_types::ForInit;
+    use crate::parser::{parse_js, ParserConfig};
+
+    type Pass = fn(&mut Program, &SemanticAnalysis, &TransformerConfig) -> TransformResult<HoistingResult>;
+
+    /// Runs `pass`, returning the output and the hoisted count
+    fn hoist_source(source: &str, pass: Pass) -> (String, u32) {
+        let (code, result) = run_pass(source, &TransformerConfig::default(), pass);
+        (code, result.hoisted_count)
+    }
+
+    #[test]
+    fn test_hoists_function_declarations() {
+        let (code, hoisted) = hoist_source(
+            "function a() {} run(); function b() { x(); function c() {} { function d() {} } } let y = 1;",
+            hoist_functions,
+        );
+
+        assert_eq!(hoisted, 2, "{code}");
+        assert!(code.starts_with("function a(){}function b(){function c(){}x();"), "{code}");
+        assert!(code.contains("{function d(){}}"), "{code}");
+        assert!(code.ends_with("run();let y=1;"), "{code}");
+    }
+
+    #[test]
+    fn test_merges_var_statements() {
+        let (code, hoisted) = hoist_source(
+            "function f() { g(); var a = 1; { var b; var d = a, e; } var a = 2; return a; }",
+            hoist_variables,
+        );
+
+        assert_eq!(hoisted, 3, "{code}");
+        assert!(code.contains("g();var a=1, b, d, e;{d=a;}a=2;return a;"), "{code}");
+    }
+
+    #[test]
+    fn test_hoists_var_branches_and_keeps_loop_heads() {
+        let source = "function f(c) { var a; if (c) var b = 1; for (var x of c) {} }";
+        let mut ast = parse_js(source, "test.js", &ParserConfig::default()).ast.unwrap();
+        let analysis = analyze_ast(&ast, &AnalyzerConfig::default()).unwrap();
+        let result = hoist_variables(&mut ast, &analysis, &TransformerConfig::default()).unwrap();
+
+        assert_eq!(result.hoisted_count, 1);
+        let Statement::FunctionDeclaration { body, .. } = &ast.body[0] else { panic!("expected a function") };
+        assert!(matches!(&body.body[0], Statement::VariableDeclaration { declarations, .. } if declarations.len() == 2));
+        assert!(matches!(
+            &body.body[1],
+            Statement::IfStatement { consequent, .. }
+                if matches!(consequent.as_ref(), Statement::ExpressionStatement { .. })
+        ));
+        assert!(matches!(&body.body[2], Statement::ForOfStatement { left: ForInit::VariableDeclaration { .. }, .. }));
+    }
+
+    #[test]
+    fn test_single_var_statement_is_left_alone() {
+        let (code, hoisted) = hoist_source("function f() { var a = 1; var { b } = o; return a + b; }", hoist_variables);
+
+        assert_eq!(hoisted, 0);
+        assert!(code.contains("var a=1;"), "{code}");
+    }
+}
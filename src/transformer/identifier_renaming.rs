@@ -1,20 +1,27 @@
 //! # Identifier Renaming Module
 //!
-//! This module implements Pass 1 of the transformation pipeline: Variable and Function
-//! name mangling. It follows a strict Test-Driven Development approach where tests
-//! are written first to define expected behavior.
+//! This module is meant to implement Pass 1 of the transformation pipeline: Variable
+//! and Function name mangling. It follows a strict Test-Driven Development approach
+//! where tests are written first to define expected behavior.
 //!
-//! ## Renaming Strategy
+//! ## Status
+//!
+//! [`rename_identifiers`] is not implemented yet: it renames nothing and returns a
+//! warning saying so, see Limitation #4 in `blockers.md`. What exists is the name
+//! generation that the renamer will use, and that export mangling, property renaming,
+//! string deduplication and syntax lowering already use for their generated names:
 //!
 //! - **Alphabet-based sequence**: a, b, c, ..., z, aa, ab, ...
-//! - **Frequency-based prioritization**: Most-used identifiers get shorter names
-//! - **Scope-aware**: Same short name can be reused in different scopes
-//! - **Safety-first**: Only rename symbols marked as safe by the analyzer
+//! - **Reserved-word aware**: [`NameGenerator`] skips names that cannot be bindings in
+//!   the code's context (`do`, `if`, `let` in strict code, `await` in modules, ...)
+//! - **Deterministic**: Names depend only on their position in the sequence and an
+//!   optional seed (`TransformerConfig::name_seed`), so they are the same across runs
+//!   and platforms
 
 use crate::analyzer::SymbolTable;
-use crate::parser::ast_types::Program;
+use crate::parser::ast_types::{Program, ProgramSourceType};
 use crate::transformer::{TransformError, TransformResult, TransformerConfig};
-use std::collections::HashMap;
+use std::collections::BTreeMap;
 
 /// Result of identifier renaming operation
 #[derive(Debug, Clone)]
@@ -22,11 +29,128 @@ pub struct IdentifierRenameResult {
     /// Number of identifiers that were successfully renamed
     pub renamed_count: u32,
     /// Mapping from original identifier names to their new names
-    pub mapping: HashMap<String, String>,
+    pub mapping: BTreeMap<String, String>,
     /// Any warnings generated during the renaming process
     pub warnings: Vec<String>,
 }
 
+/// Keywords and literals that are never valid binding names
+///
+/// `let` is included because it can't name a lexical declaration even in sloppy code.
+const RESERVED_WORDS: &[&str] = &[
+    "break", "case", "catch", "class", "const", "continue", "debugger", "default", "delete", "do",
+    "else", "enum", "export", "extends", "false", "finally", "for", "function", "if", "import", "in",
+    "instanceof", "let", "new", "null", "return", "super", "switch", "this", "throw", "true", "try",
+    "typeof", "var", "void", "while", "with",
+];
+
+/// Additional names strict code can't bind: future reserved words, `yield`, `eval` and `arguments`
+const STRICT_RESERVED_WORDS: &[&str] = &[
+    "arguments", "eval", "implements", "interface", "package", "private", "protected", "public",
+    "static", "yield",
+];
+
+/// Code in which generated names must be valid bindings
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NameContext {
+    /// Non-strict script code
+    Sloppy,
+    /// Strict script code. `await` stays usable, so names for async function bodies
+    /// should come from [`NameContext::Module`]
+    Strict,
+    /// Module code: strict, and `await` is reserved
+    Module,
+}
+
+impl NameContext {
+    /// Context for the top level of `program`
+    ///
+    /// Scripts are treated as strict: names are picked per program, and any function in
+    /// a script may opt into strict mode with a `"use strict"` directive.
+    pub fn for_program(program: &Program) -> Self {
+        match program.source_type {
+            ProgramSourceType::Module => Self::Module,
+            ProgramSourceType::Script => Self::Strict,
+        }
+    }
+
+    /// Returns whether `name` cannot be used as a binding in this context
+    pub fn is_reserved(self, name: &str) -> bool {
+        RESERVED_WORDS.contains(&name)
+            || (self != Self::Sloppy && STRICT_RESERVED_WORDS.contains(&name))
+            || (self == Self::Module && name == "await")
+    }
+}
+
+/// Generates short names in alphabet order (a, b, ..., z, aa, ab, ...), skipping
+/// names that are reserved in its [`NameContext`]
+///
+/// A seed shuffles the alphabet, so the names differ between seeds but are the same for
+/// every run with one seed.
+#[derive(Debug, Clone)]
+pub struct NameGenerator {
+    context: NameContext,
+    alphabet: [u8; 26],
+    next_index: usize,
+}
+
+impl NameGenerator {
+    /// Creates a generator starting at `a`
+    pub fn new(context: NameContext) -> Self {
+        let mut alphabet = [0; 26];
+        for (letter, slot) in (b'a'..=b'z').zip(&mut alphabet) {
+            *slot = letter;
+        }
+        Self { context, alphabet, next_index: 0 }
+    }
+
+    /// Shuffles the alphabet with `seed`, or keeps alphabet order for `None`
+    pub fn with_seed(mut self, seed: Option<u64>) -> Self {
+        let Some(mut state) = seed else {
+            return self;
+        };
+        // Fisher-Yates with splitmix64, which is fully specified so every platform
+        // produces the same order
+        for i in (1..self.alphabet.len()).rev() {
+            state = state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+            let mut z = state;
+            z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+            z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+            z ^= z >> 31;
+            self.alphabet.swap(i, (z % (i as u64 + 1)) as usize);
+        }
+        self
+    }
+
+    /// Name at `index` in the unfiltered sequence (bijective base 26)
+    fn name_at(&self, mut index: usize) -> String {
+        let mut name = Vec::new();
+        loop {
+            name.push(self.alphabet[index % 26]);
+            if index < 26 {
+                break;
+            }
+            index = index / 26 - 1;
+        }
+        name.reverse();
+        String::from_utf8(name).expect("names are ASCII")
+    }
+}
+
+impl Iterator for NameGenerator {
+    type Item = String;
+
+    fn next(&mut self) -> Option<String> {
+        loop {
+            let name = self.name_at(self.next_index);
+            self.next_index += 1;
+            if !self.context.is_reserved(&name) {
+                return Some(name);
+            }
+        }
+    }
+}
+
 /// Renames identifiers in the given AST based on analysis results
 ///
 /// # Arguments
@@ -45,17 +169,15 @@ pub struct IdentifierRenameResult {
 pub fn rename_identifiers(
     _ast: &mut Program,
     symbol_table: &SymbolTable,
-    config: &TransformerConfig,
+    _config: &TransformerConfig,
 ) -> TransformResult<IdentifierRenameResult> {
-    if config.verbose {
-        println!("🔍 Analyzing {} symbols for renaming", symbol_table.symbols.len());
-    }
+    tracing::debug!("🔍 Analyzing {} symbols for renaming", symbol_table.symbols.len());
 
     // TODO: Implement actual identifier renaming logic
     // For now, return a placeholder result
     Ok(IdentifierRenameResult {
         renamed_count: 0,
-        mapping: HashMap::new(),
+        mapping: BTreeMap::new(),
         warnings: vec!["Identifier renaming not yet fully implemented".to_string()],
     })
 }
@@ -81,4 +203,88 @@ mod tests {
         assert!(result.mapping.is_empty());
         assert!(!result.warnings.is_empty());
     }
-}
\ No newline at end of file
+    /// Every name of up to `length` letters, in order, minus `skipped`
+    fn expected_names(length: u32, skipped: &[&str]) -> Vec<String> {
+        let mut names = vec![String::new()];
+        let mut all = Vec::new();
+        for _ in 0..length {
+            names = names
+                .iter()
+                .flat_map(|prefix| (b'a'..=b'z').map(move |c| format!("{}{}", prefix, c as char)))
+                .collect();
+            all.extend(names.iter().cloned());
+        }
+        all.retain(|name| !skipped.contains(&name.as_str()));
+        all
+    }
+
+    fn generated(context: NameContext, count: usize) -> Vec<String> {
+        NameGenerator::new(context).take(count).collect()
+    }
+
+    #[test]
+    fn test_short_name_sequences_skip_reserved_words() {
+        let first: Vec<String> = (b'a'..=b'z').map(|c| (c as char).to_string()).collect();
+        assert_eq!(generated(NameContext::Module, 28)[..26], first[..]);
+        assert_eq!(generated(NameContext::Module, 28)[26..], ["aa", "ab"]);
+
+        // Every name of up to four letters, exhaustively
+        let sloppy = [
+            "do", "if", "in", "for", "let", "new", "try", "var", "case", "else", "enum", "null", "this",
+            "true", "void", "with",
+        ];
+        let strict = [&sloppy[..], &["eval"]].concat();
+        let count = 26 + 26 * 26 + 26 * 26 * 26 + 26 * 26 * 26 * 26;
+        for (context, skipped) in [
+            (NameContext::Sloppy, &sloppy[..]),
+            (NameContext::Strict, &strict[..]),
+            (NameContext::Module, &strict[..]),
+        ] {
+            let expected = expected_names(4, skipped);
+            assert_eq!(expected.len(), count - skipped.len());
+            assert_eq!(generated(context, expected.len()), expected, "{:?}", context);
+        }
+
+        let two_letters = generated(NameContext::Sloppy, 26 + 26 * 26 - 3);
+        let after_dn = two_letters.iter().position(|name| name == "dn").unwrap();
+        assert_eq!(two_letters[after_dn + 1], "dp");
+        assert_eq!(two_letters.last().unwrap(), "zz");
+    }
+
+    #[test]
+    fn test_seeds_shuffle_names_reproducibly() {
+        let seeded = |seed| NameGenerator::new(NameContext::Module).with_seed(seed).take(1000).collect::<Vec<_>>();
+
+        assert_eq!(seeded(None), generated(NameContext::Module, 1000));
+        assert_eq!(seeded(Some(42)), seeded(Some(42)));
+        assert_ne!(seeded(Some(42)), seeded(Some(43)));
+        assert_ne!(seeded(Some(42)), seeded(None));
+
+        // Still every short name once, minus the reserved ones
+        let mut names = NameGenerator::new(NameContext::Sloppy).with_seed(Some(7)).take(26 + 26 * 26 - 3).collect::<Vec<_>>();
+        names.sort_by(|a, b| a.len().cmp(&b.len()).then_with(|| a.cmp(b)));
+        assert_eq!(names, expected_names(2, &["do", "if", "in"]));
+    }
+
+    #[test]
+    fn test_reserved_words_depend_on_context() {
+        for word in ["do", "in", "if", "let", "enum", "typeof", "instanceof"] {
+            assert!(NameContext::Sloppy.is_reserved(word), "{}", word);
+        }
+        for word in ["yield", "static", "public", "implements", "arguments"] {
+            assert!(!NameContext::Sloppy.is_reserved(word), "{}", word);
+            assert!(NameContext::Strict.is_reserved(word), "{}", word);
+        }
+        assert!(!NameContext::Strict.is_reserved("await"));
+        assert!(NameContext::Module.is_reserved("await"));
+
+        // Contextual keywords stay usable as bindings everywhere
+        for word in ["async", "of", "get", "set", "from", "as", "target", "meta"] {
+            assert!(!NameContext::Module.is_reserved(word), "{}", word);
+        }
+
+        let program = |source_type| Program { body: vec![], source_type };
+        assert_eq!(NameContext::for_program(&program(ProgramSourceType::Module)), NameContext::Module);
+        assert_eq!(NameContext::for_program(&program(ProgramSourceType::Script)), NameContext::Strict);
+    }
+}
@@ -0,0 +1,218 @@
+//! # If-Return Elimination
+//!
+//! Shortens the control flow around `return`:
+//!
+//! - An `else` after a branch that always returns is dropped and its statements follow
+//!   the `if`: `if (x) { return a; } else { return b; }` → `if (x) return a; return b;`
+//! - `if` branches that are blocks with a single statement lose their braces; the printer
+//!   adds them back when an `else` would otherwise bind to a nested `if`.
+//! - `return undefined` and `return void 0` become a bare `return`, unless `undefined` is
+//!   declared in the program.
+//!
+//! Blocks holding `let`, `const`, class or function declarations are kept as blocks so the
+//! declarations stay scoped to them.
+
+use crate::analyzer::SemanticAnalysis;
+use crate::parser::ast_types::{
+    ArrowFunctionBody, ClassElement, Expression, Program, Statement, UnaryOperator, VariableDeclarationKind,
+};
+use crate::transformer::plugin::{self, PluginContext, TransformPlugin};
+use crate::transformer::{TransformResult, TransformerConfig};
+
+/// Result of if-return elimination
+#[derive(Debug, Clone)]
+pub struct IfReturnResult {
+    /// Number of `else` branches, braces and return values removed
+    pub simplified_count: u32,
+    /// Any warnings generated while simplifying
+    pub warnings: Vec<String>,
+}
+
+/// Removes redundant `else` branches, braces and `undefined` return values
+///
+/// # Arguments
+///
+/// * `ast` - The AST to transform (modified in place)
+/// * `analysis` - Semantic analysis of the program
+/// * `config` - Transformer configuration
+///
+/// # Returns
+///
+/// Returns `IfReturnResult` with the number of simplifications
+pub fn eliminate_if_returns(
+    ast: &mut Program,
+    analysis: &SemanticAnalysis,
+    config: &TransformerConfig,
+) -> TransformResult<IfReturnResult> {
+    let mut simplifier = IfReturnSimplifier {
+        undefined_declared: analysis.symbol_table.is_declared("undefined"),
+        simplified:         0,
+    };
+    let mut ctx = PluginContext::new(analysis, config);
+    plugin::run_plugin(&mut simplifier, ast, &mut ctx)?;
+
+    if simplifier.simplified > 0 {
+        tracing::debug!("🔀 Simplified {} if/return constructs", simplifier.simplified);
+    }
+
+    Ok(IfReturnResult {
+        simplified_count: simplifier.simplified,
+        warnings: Vec::new(),
+    })
+}
+
+struct IfReturnSimplifier {
+    /// Whether `undefined` may not be the global `undefined`
+    undefined_declared: bool,
+    simplified:         u32,
+}
+
+impl TransformPlugin for IfReturnSimplifier {
+    fn name(&self) -> &str {
+        "if_return"
+    }
+
+    fn enter_statement(&mut self, stmt: &mut Statement, _ctx: &mut PluginContext) -> TransformResult<()> {
+        match stmt {
+            Statement::BlockStatement { body, .. } => self.drop_else_branches(body),
+            Statement::FunctionDeclaration { body, .. } => self.drop_else_branches(&mut body.body),
+            Statement::ClassDeclaration { body, .. } => {
+                for element in &mut body.body {
+                    if let ClassElement::MethodDefinition { value, .. } = element {
+                        self.drop_else_branches(&mut value.body.body);
+                    }
+                }
+            }
+            Statement::IfStatement { consequent, alternate, .. } => {
+                self.unwrap_block(consequent);
+                if let Some(alternate) = alternate {
+                    self.unwrap_block(alternate);
+                }
+            }
+            Statement::ReturnStatement { argument, .. }
+                if argument.as_ref().is_some_and(|argument| self.is_undefined(argument)) =>
+            {
+                *argument = None;
+                self.simplified += 1;
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    fn enter_expression(&mut self, expr: &mut Expression, _ctx: &mut PluginContext) -> TransformResult<()> {
+        match expr {
+            Expression::FunctionExpression(function) => self.drop_else_branches(&mut function.body.body),
+            Expression::ArrowFunctionExpression { body: ArrowFunctionBody::BlockStatement(block), .. } => {
+                self.drop_else_branches(&mut block.body)
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+}
+
+impl IfReturnSimplifier {
+    /// Moves the `else` branch of every `if` whose consequent returns after the `if`
+    fn drop_else_branches(&mut self, statements: &mut Vec<Statement>) {
+        let mut index = 0;
+        while index < statements.len() {
+            if let Statement::IfStatement { consequent, alternate, .. } = &mut statements[index]
+                && alternate.is_some()
+                && always_returns(consequent)
+            {
+                let alternate = *alternate.take().expect("checked above");
+                let following = match alternate {
+                    Statement::BlockStatement { body, .. } if !declares_lexically(&body) => body,
+                    alternate => vec![alternate],
+                };
+                statements.splice(index + 1..index + 1, following);
+                self.simplified += 1;
+            }
+            index += 1;
+        }
+    }
+
+    /// Replaces a block branch holding a single statement with that statement
+    fn unwrap_block(&mut self, branch: &mut Box<Statement>) {
+        if let Statement::BlockStatement { body, .. } = branch.as_mut()
+            && body.len() == 1
+            && !declares_lexically(body)
+        {
+            **branch = body.remove(0);
+            self.simplified += 1;
+        }
+    }
+
+    /// Whether `expression` always evaluates to `undefined` without side effects
+    fn is_undefined(&self, expression: &Expression) -> bool {
+        match expression {
+            Expression::Identifier(id) => id.name == "undefined" && !self.undefined_declared,
+            Expression::UnaryExpression { operator: UnaryOperator::Void, argument, .. } => {
+                matches!(argument.as_ref(), Expression::Literal(_))
+            }
+            _ => false,
+        }
+    }
+}
+
+/// Whether control never continues past `statement` because every path returns
+fn always_returns(statement: &Statement) -> bool {
+    match statement {
+        Statement::ReturnStatement { .. } => true,
+        Statement::BlockStatement { body, .. } => body.iter().any(always_returns),
+        Statement::IfStatement { consequent, alternate: Some(alternate), .. } => {
+            always_returns(consequent) && always_returns(alternate)
+        }
+        _ => false,
+    }
+}
+
+/// Whether a statement list declares block-scoped bindings
+pub(crate) fn declares_lexically(statements: &[Statement]) -> bool {
+    statements.iter().any(|statement| match statement {
+        Statement::VariableDeclaration { kind, .. } => !matches!(kind, VariableDeclarationKind::Var),
+        Statement::FunctionDeclaration { .. } | Statement::ClassDeclaration { .. } => true,
+        _ => false,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::transformer::tests::run_pass;
+
+    /// Simplifies, returning the output and the simplification count
+    fn simplify_source(source: &str) -> (String, u32) {
+        let (code, result) = run_pass(source, &TransformerConfig::default(), eliminate_if_returns);
+        (code, result.simplified_count)
+    }
+
+    #[test]
+    fn test_drops_else_after_return() {
+        let (code, simplified) = simplify_source("function f(x, a, b) { if (x) { return a; } else { g(); return b; } }");
+
+        assert_eq!(simplified, 2);
+        assert_eq!(code, "function f(x, a, b){if(x)return a;g();return b;}");
+    }
+
+    #[test]
+    fn test_bare_returns_and_kept_blocks() {
+        let (code, simplified) = simplify_source(
+            "function f(x) { if (x) { let y = x; return y; } else { return void 0; } return undefined; }",
+        );
+
+        assert_eq!(simplified, 3);
+        assert_eq!(code, "function f(x){if(x){let y=x;return y;}return;return;}");
+
+        let (code, _) = simplify_source("function f() { var undefined = 1; return undefined; }");
+        assert!(code.contains("return undefined;"), "{code}");
+    }
+
+    #[test]
+    fn test_keeps_else_of_nested_if() {
+        let (code, _) = simplify_source("function f(a, b) { if (a) { if (b) g(); } else h(); }");
+
+        assert_eq!(code, "function f(a, b){if(a){if(b)g();}else h();}");
+    }
+}
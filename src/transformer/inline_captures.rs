@@ -0,0 +1,452 @@
+//! # Inline Captures
+//!
+//! Copies constants that closures capture into the closures:
+//! `const step = 2; return () => total += step` becomes `return () => total += 2`. The
+//! closure no longer looks the name up through its scope chain, and when every read
+//! was in a closure the declaration is removed too.
+//!
+//! Candidates come from the analyzer's capture classification: a `let` or `const` that
+//! closures only read ([`CaptureKind::ReadOnly`]), declared once with a literal value,
+//! the only symbol with its name, and neither exported nor kept. Regular expressions
+//! are left alone, since each evaluation creates a new object.
+//!
+//! Reads in the declaring function itself stay, and copies are only made when they do
+//! not grow the output: either the value is no longer than the name it replaces, or the
+//! declaration goes away and saves more than the copies cost.
+//!
+//! A closure called before the declaration runs would have thrown a `ReferenceError`
+//! (the temporal dead zone); with the value copied in, it no longer throws. `var` is
+//! never inlined, since reading it early gives `undefined`.
+
+use std::collections::{HashMap, HashSet};
+
+use crate::analyzer::scope_builder::pattern_names;
+use crate::analyzer::{CaptureKind, SemanticAnalysis, Symbol, SymbolType, VariableKind};
+use crate::interner::Name;
+use crate::parser::ast_types::{
+    Expression, ForInit, Identifier, ObjectProperty, Pattern, Program, Statement, VariableDeclarator,
+};
+use crate::transformer::plugin::{self, PluginContext, TransformPlugin};
+use crate::transformer::property_minification::literal_size;
+use crate::transformer::{TransformResult, TransformerConfig};
+
+/// Result of inlining captured constants
+#[derive(Debug, Clone)]
+pub struct InlineCapturesResult {
+    /// Number of closure reads replaced by a constant
+    pub inlined_count: u32,
+    /// Any warnings generated while inlining
+    pub warnings: Vec<String>,
+}
+
+/// Inlines read-only captured constants into the closures that read them
+///
+/// # Arguments
+///
+/// * `ast` - The AST to transform (modified in place)
+/// * `analysis` - Semantic analysis of the program
+/// * `config` - Transformer configuration
+///
+/// # Returns
+///
+/// Returns `InlineCapturesResult` with the number of reads replaced
+pub fn inline_captures(
+    ast: &mut Program,
+    analysis: &SemanticAnalysis,
+    config: &TransformerConfig,
+) -> TransformResult<InlineCapturesResult> {
+    let mut symbols_by_name: HashMap<Name, Vec<&Symbol>> = HashMap::new();
+    for symbol in analysis.symbol_table.symbols.values() {
+        symbols_by_name.entry(symbol.name).or_default().push(symbol);
+    }
+    let candidates: HashSet<String> = symbols_by_name
+        .into_iter()
+        .filter(|(_, symbols)| {
+            matches!(symbols.as_slice(), [symbol]
+                if matches!(symbol.symbol_type, SymbolType::Variable { kind: VariableKind::Let | VariableKind::Const })
+                    && symbol.capture == CaptureKind::ReadOnly
+                    && symbol.is_renamable
+                    && !symbol.is_exported
+                    && !symbol.is_kept)
+        })
+        .map(|(name, _)| analysis.symbol_table.names.resolve(name).to_string())
+        .collect();
+    if candidates.is_empty() {
+        return Ok(InlineCapturesResult { inlined_count: 0, warnings: Vec::new() });
+    }
+
+    let mut ctx = PluginContext::new(analysis, config);
+    let mut collector = CaptureCollector { candidates, scopes: ScopePath::default(), captures: HashMap::new() };
+    plugin::run_plugin(&mut collector, ast, &mut ctx)?;
+
+    let inlined: HashMap<String, Inlined> = collector
+        .captures
+        .into_iter()
+        .filter_map(|(name, capture)| {
+            let [(Some(value), scope)] = <[_; 1]>::try_from(capture.declarations).ok()? else {
+                return None;
+            };
+            let (mut direct_reads, mut closure_reads) = (0, 0);
+            for (path, is_target) in &capture.reads {
+                match place(path, &scope) {
+                    // Read-only captures are never assigned, so this is a global of the same name
+                    ReadPlace::Elsewhere => {}
+                    _ if *is_target => return None,
+                    ReadPlace::Direct => direct_reads += 1,
+                    ReadPlace::Closure => closure_reads += 1,
+                }
+            }
+            let (size, name_size) = (literal_size(&value)?, name.len());
+            let remove = direct_reads == 0;
+            let worthwhile = if remove {
+                // `name=value,` goes away along with the reads
+                closure_reads * size <= closure_reads * name_size + name_size + size + 2
+            } else {
+                size <= name_size
+            };
+            (closure_reads > 0 && worthwhile).then_some((name, Inlined { value, scope, remove }))
+        })
+        .collect();
+    if inlined.is_empty() {
+        return Ok(InlineCapturesResult { inlined_count: 0, warnings: Vec::new() });
+    }
+
+    let mut inliner = CaptureInliner { inlined, scopes: ScopePath::default(), inlined_count: 0 };
+    plugin::run_plugin(&mut inliner, ast, &mut ctx)?;
+
+    if inliner.inlined_count > 0 {
+        tracing::debug!("📌 Inlined {} reads of captured constants", inliner.inlined_count);
+    }
+
+    Ok(InlineCapturesResult {
+        inlined_count: inliner.inlined_count,
+        warnings: Vec::new(),
+    })
+}
+
+/// A scope path, outermost first: an id per block or function with whether it is a
+/// function or class body
+type Path = Vec<(usize, bool)>;
+
+/// The blocks and functions enclosing the node being visited
+#[derive(Debug, Default)]
+struct ScopePath {
+    path:    Path,
+    next_id: usize,
+    /// Identifiers that do not read a variable, by address: the names of non-computed
+    /// member accesses (`false`) and assignment and update targets (`true`)
+    skipped: HashMap<usize, bool>,
+}
+
+/// Where a use of a name is relative to its declaration
+enum ReadPlace {
+    /// In the declaring function
+    Direct,
+    /// Inside a function or class nested in the declaring one
+    Closure,
+    /// Out of the declaration's scope, so another variable of the same name
+    Elsewhere,
+}
+
+/// Where `path` is relative to a declaration made at `declared`
+fn place(path: &[(usize, bool)], declared: &[(usize, bool)]) -> ReadPlace {
+    match path.strip_prefix(declared) {
+        Some(inner) if inner.iter().any(|&(_, is_function)| is_function) => ReadPlace::Closure,
+        Some(_) => ReadPlace::Direct,
+        None => ReadPlace::Elsewhere,
+    }
+}
+
+impl ScopePath {
+    /// Tracks the scope `stmt` opens, if any
+    fn enter_statement(&mut self, stmt: &Statement) {
+        if let Some(is_function) = opens_scope(stmt) {
+            self.next_id += 1;
+            self.path.push((self.next_id, is_function));
+        }
+    }
+
+    fn leave_statement(&mut self, stmt: &Statement) {
+        if opens_scope(stmt).is_some() {
+            self.path.pop();
+        }
+    }
+
+    /// Tracks the functions and skipped identifiers of `expr`
+    fn enter_expression(&mut self, expr: &Expression) {
+        match expr {
+            Expression::FunctionExpression(_) | Expression::ArrowFunctionExpression { .. } => {
+                self.next_id += 1;
+                self.path.push((self.next_id, true));
+            }
+            Expression::MemberExpression { property, computed: false, .. } => {
+                self.skipped.insert(address(property), false);
+            }
+            Expression::AssignmentExpression { left: target, .. } | Expression::UpdateExpression { argument: target, .. } => {
+                self.skipped.insert(address(target), true);
+            }
+            _ => {}
+        }
+    }
+
+    fn leave_expression(&mut self, expr: &Expression) {
+        if matches!(expr, Expression::FunctionExpression(_) | Expression::ArrowFunctionExpression { .. }) {
+            self.path.pop();
+        }
+    }
+}
+
+/// Whether `stmt` opens a scope, and if so whether it is a function or class body
+fn opens_scope(stmt: &Statement) -> Option<bool> {
+    match stmt {
+        Statement::FunctionDeclaration { .. } | Statement::ClassDeclaration { .. } => Some(true),
+        Statement::BlockStatement { .. } | Statement::ForStatement { .. } | Statement::ForOfStatement { .. } => Some(false),
+        _ => None,
+    }
+}
+
+fn address(expr: &Expression) -> usize {
+    expr as *const Expression as usize
+}
+
+/// Where one candidate is declared and used
+#[derive(Debug, Default)]
+struct Capture {
+    /// Literal value and scope path of each binding; `None` for values that are not
+    /// literals, loop heads and bindings other than variable declarations
+    declarations: Vec<(Option<Expression>, Path)>,
+    /// Scope path of each use, with whether it is an assignment or update target
+    reads:        Vec<(Path, bool)>,
+}
+
+/// A candidate to inline, with the value and the scope path of its declaration
+struct Inlined {
+    value:  Expression,
+    scope:  Path,
+    /// Whether every read is in a closure, so the declaration can go
+    remove: bool,
+}
+
+/// Finds the declarations and uses of the candidates
+struct CaptureCollector {
+    candidates: HashSet<String>,
+    scopes:     ScopePath,
+    captures:   HashMap<String, Capture>,
+}
+
+impl CaptureCollector {
+    /// Records the declarations of candidates, with their values unless `opaque`: loop
+    /// heads bind a new value each iteration, and `using` declarations have to stay for
+    /// their disposal
+    fn declare(&mut self, declarations: &[VariableDeclarator], opaque: bool) {
+        for declarator in declarations {
+            let Pattern::Identifier(id) = &declarator.id else {
+                self.bind(std::slice::from_ref(&declarator.id));
+                continue;
+            };
+            if self.candidates.contains(&id.name) {
+                let value = declarator.init.clone().filter(|init| !opaque && literal_size(init).is_some());
+                let capture = self.captures.entry(id.name.clone()).or_default();
+                capture.declarations.push((value, self.scopes.path.clone()));
+            }
+        }
+    }
+
+    /// Records other bindings of candidate names, such as parameters, which rule them out
+    fn bind(&mut self, patterns: &[Pattern]) {
+        let mut names = Vec::new();
+        patterns.iter().for_each(|pattern| pattern_names(pattern, &mut names));
+        for name in names.into_iter().filter(|name| self.candidates.contains(name)) {
+            self.captures.entry(name).or_default().declarations.push((None, self.scopes.path.clone()));
+        }
+    }
+
+    fn bind_name(&mut self, id: &Option<Identifier>) {
+        if let Some(id) = id {
+            self.bind(&[Pattern::Identifier(id.clone())]);
+        }
+    }
+}
+
+impl TransformPlugin for CaptureCollector {
+    fn name(&self) -> &str {
+        "inline_captures"
+    }
+
+    fn enter_statement(&mut self, stmt: &mut Statement, _ctx: &mut PluginContext) -> TransformResult<()> {
+        self.scopes.enter_statement(stmt);
+        match stmt {
+            Statement::VariableDeclaration { declarations, kind, .. } => self.declare(declarations, kind.is_using()),
+            Statement::FunctionDeclaration { id, params, .. } => {
+                self.bind_name(id);
+                self.bind(params);
+            }
+            Statement::ClassDeclaration { id, .. } => self.bind_name(id),
+            Statement::ForStatement { init: Some(ForInit::VariableDeclaration { declarations, .. }), .. }
+            | Statement::ForOfStatement { left: ForInit::VariableDeclaration { declarations, .. }, .. } => {
+                self.declare(declarations, true)
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    fn exit_statement(&mut self, stmt: &mut Statement, _ctx: &mut PluginContext) -> TransformResult<()> {
+        self.scopes.leave_statement(stmt);
+        Ok(())
+    }
+
+    fn enter_expression(&mut self, expr: &mut Expression, _ctx: &mut PluginContext) -> TransformResult<()> {
+        self.scopes.enter_expression(expr);
+        match expr {
+            Expression::FunctionExpression(function) => {
+                self.bind_name(&function.id);
+                self.bind(&function.params);
+            }
+            Expression::ArrowFunctionExpression { params, .. } => self.bind(params),
+            _ => {}
+        }
+        if let Expression::Identifier(id) = &*expr
+            && self.candidates.contains(&id.name)
+        {
+            let is_target = match self.scopes.skipped.remove(&address(expr)) {
+                Some(false) => return Ok(()),
+                Some(true) => true,
+                None => false,
+            };
+            let capture = self.captures.entry(id.name.clone()).or_default();
+            capture.reads.push((self.scopes.path.clone(), is_target));
+        }
+        Ok(())
+    }
+
+    fn exit_expression(&mut self, expr: &mut Expression, _ctx: &mut PluginContext) -> TransformResult<()> {
+        self.scopes.leave_expression(expr);
+        Ok(())
+    }
+}
+
+/// Replaces closure reads of the chosen candidates and removes the declarations left
+/// without reads
+struct CaptureInliner {
+    inlined:       HashMap<String, Inlined>,
+    scopes:        ScopePath,
+    inlined_count: u32,
+}
+
+impl CaptureInliner {
+    /// The value to copy over a read of `name` at the current position, if any
+    fn replacement(&self, name: &str) -> Option<&Expression> {
+        let inlined = self.inlined.get(name)?;
+        matches!(place(&self.scopes.path, &inlined.scope), ReadPlace::Closure).then_some(&inlined.value)
+    }
+}
+
+impl TransformPlugin for CaptureInliner {
+    fn name(&self) -> &str {
+        "inline_captures"
+    }
+
+    fn enter_statement(&mut self, stmt: &mut Statement, _ctx: &mut PluginContext) -> TransformResult<()> {
+        if let Statement::VariableDeclaration { declarations, .. } = stmt {
+            declarations.retain(|declarator| {
+                !matches!(&declarator.id, Pattern::Identifier(id) if self.inlined.get(&id.name).is_some_and(|inlined| inlined.remove))
+            });
+            if declarations.is_empty() {
+                *stmt = Statement::EmptyStatement;
+            }
+        }
+        self.scopes.enter_statement(stmt);
+        Ok(())
+    }
+
+    fn exit_statement(&mut self, stmt: &mut Statement, _ctx: &mut PluginContext) -> TransformResult<()> {
+        self.scopes.leave_statement(stmt);
+        Ok(())
+    }
+
+    fn enter_expression(&mut self, expr: &mut Expression, _ctx: &mut PluginContext) -> TransformResult<()> {
+        self.scopes.enter_expression(expr);
+        let skipped = self.scopes.skipped.remove(&address(expr)).is_some();
+        match expr {
+            Expression::Identifier(id) => {
+                if skipped {
+                    return Ok(());
+                }
+                if let Some(value) = self.replacement(&id.name) {
+                    *expr = value.clone();
+                    self.inlined_count += 1;
+                }
+            }
+            // `{ step }` becomes `{ step: 2 }`
+            Expression::ObjectExpression { properties } => {
+                for property in properties {
+                    if let ObjectProperty::Property { value: Expression::Identifier(id), shorthand, .. } = property
+                        && *shorthand
+                        && self.replacement(&id.name).is_some()
+                    {
+                        *shorthand = false;
+                    }
+                }
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    fn exit_expression(&mut self, expr: &mut Expression, _ctx: &mut PluginContext) -> TransformResult<()> {
+        self.scopes.leave_expression(expr);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::transformer::tests::run_pass;
+
+    /// Inlines captures, returning the output and the inlined count
+    fn inline_source(source: &str) -> (String, u32) {
+        let (code, result) = run_pass(source, &TransformerConfig::default(), inline_captures);
+        (code, result.inlined_count)
+    }
+
+    #[test]
+    fn test_inlines_constants_only_closures_read() {
+        let (code, inlined) = inline_source(
+            "const LIMIT = 10; export function check(x) { return () => x > LIMIT; }\n\
+             export function label() { const name = 'ok'; return { get: () => ({ name }) }; }",
+        );
+
+        assert_eq!(inlined, 2);
+        assert_eq!(
+            code,
+            ";export function check(x){return ()=>x>10;}export function label(){;return {get:()=>({name:'ok'})};}"
+        );
+    }
+
+    #[test]
+    fn test_keeps_mutated_var_and_directly_read_captures() {
+        let (code, inlined) = inline_source(
+            "let count = 0; export function bump() { count++; return count; }\n\
+             var flag = true; export function read() { return () => flag; }\n\
+             export function greet() { const greeting = 'hello there'; log(greeting); return () => greeting; }",
+        );
+
+        assert_eq!(inlined, 0, "{code}");
+        assert!(code.contains("count++;return count"), "{code}");
+        assert!(code.contains("()=>flag"), "{code}");
+        assert!(code.contains("()=>greeting"), "{code}");
+    }
+
+    #[test]
+    fn test_skips_names_bound_again() {
+        let (code, inlined) = inline_source(
+            "const e = 1; export function f(items) { return items.map((e) => e + 1); }\n\
+             export function g() { return () => e; }",
+        );
+
+        assert_eq!(inlined, 0, "{code}");
+        assert!(code.contains("const e=1"), "{code}");
+    }
+}
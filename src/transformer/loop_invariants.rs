@@ -0,0 +1,509 @@
+//! # Loop-Invariant Hoisting
+//!
+//! Moves constant subexpressions out of loops, so they are computed once instead of on
+//! every iteration: `for (...) { a[i] = x * Math.sqrt(2); }` becomes
+//! `const b = Math.sqrt(2); for (...) { a[i] = x * b; }`. Only runs with
+//! `TransformerConfig::aggressive_optimization`, since it trades bytes for speed.
+//!
+//! An expression is hoisted when the purity analysis classifies it as
+//! [`Purity::Pure`] (so it reads no variables and cannot throw or change anything) and
+//! it is built from literals, operators and calls to pure globals such as
+//! `Math.sqrt`. Object, array, function and regular expression literals create a new
+//! value on every evaluation and are never hoisted, and neither are `in` and
+//! `instanceof`, which throw on primitives. Only expressions with a call are worth
+//! hoisting; expression simplification has already folded the rest.
+//!
+//! ## Size Heuristic
+//!
+//! A hoisted expression of printed length `L`, used `n` times in the loop, gets a name
+//! of length `k`: the declaration costs `k + L + 2` bytes (`k=...,`) and the uses save
+//! `n * (L - k)`. An expression is hoisted when the output grows by at most
+//! [`MAX_GROWTH`] bytes, since each hoist saves a call per iteration.
+//!
+//! The declaration goes right before the loop, so only loops in statement lists
+//! (program, function and block bodies) are handled. Nested functions are left alone,
+//! and so is the top level of scripts, where a declaration would be a global.
+
+use std::collections::{HashMap, HashSet};
+use std::iter::Peekable;
+
+use serde_json::Value;
+
+use crate::analyzer::purity::Purity;
+use crate::analyzer::SemanticAnalysis;
+use crate::generator::{Generator, GeneratorConfig};
+use crate::parser::ast_diff::normalize;
+use crate::parser::ast_types::{
+    ArrowFunctionBody, BinaryOperator, ClassElement, Expression, ForInit, Identifier, Literal, Pattern,
+    Program, ProgramSourceType, Statement, UnaryOperator, VariableDeclarationKind, VariableDeclarator,
+};
+use crate::target::Feature;
+use crate::transformer::identifier_renaming::{NameContext, NameGenerator};
+use crate::transformer::plugin::{self, PluginContext, TransformPlugin};
+use crate::transformer::string_dedupe::collect_names;
+use crate::transformer::{TransformResult, TransformerConfig};
+
+/// Most bytes one hoisted expression may add to the output
+pub const MAX_GROWTH: isize = 16;
+
+/// Result of hoisting loop invariants
+#[derive(Debug, Clone)]
+pub struct LoopInvariantResult {
+    /// Number of expressions moved out of loops
+    pub hoisted_count: u32,
+    /// Any warnings generated while hoisting
+    pub warnings: Vec<String>,
+}
+
+/// Moves constant subexpressions of loop bodies in front of their loops
+///
+/// # Arguments
+///
+/// * `ast` - The AST to transform (modified in place)
+/// * `analysis` - Semantic analysis of the program
+/// * `config` - Transformer configuration
+///
+/// # Returns
+///
+/// Returns `LoopInvariantResult` with the number of expressions hoisted
+pub fn hoist_loop_invariants(
+    ast: &mut Program,
+    analysis: &SemanticAnalysis,
+    config: &TransformerConfig,
+) -> TransformResult<LoopInvariantResult> {
+    if !config.aggressive_optimization {
+        return Ok(LoopInvariantResult { hoisted_count: 0, warnings: Vec::new() });
+    }
+
+    let mut used = HashSet::new();
+    collect_names(&serde_json::to_value(&ast.body).unwrap_or(Value::Null), &mut used);
+    let names = NameGenerator::new(NameContext::for_program(ast))
+        .with_seed(config.name_seed)
+        .filter(move |name| !used.contains(name));
+    let kind = if config.target.supports(Feature::BlockScoping) {
+        VariableDeclarationKind::Const
+    } else {
+        VariableDeclarationKind::Var
+    };
+
+    let mut hoister = LoopHoister {
+        names: (Box::new(names) as Box<dyn Iterator<Item = String> + Send>).peekable(),
+        kind,
+        is_script: matches!(ast.source_type, ProgramSourceType::Script),
+        function_depth: 0,
+        hoisted_count: 0,
+    };
+    let mut ctx = PluginContext::new(analysis, config);
+    plugin::run_plugin(&mut hoister, ast, &mut ctx)?;
+
+    if hoister.hoisted_count > 0 {
+        tracing::debug!("🔁 Hoisted {} loop invariants", hoister.hoisted_count);
+    }
+
+    Ok(LoopInvariantResult {
+        hoisted_count: hoister.hoisted_count,
+        warnings: Vec::new(),
+    })
+}
+
+/// Visits every statement list and hoists the invariants of the loops in it
+struct LoopHoister {
+    names:          Peekable<Box<dyn Iterator<Item = String> + Send>>,
+    kind:           VariableDeclarationKind,
+    is_script:      bool,
+    function_depth: usize,
+    hoisted_count:  u32,
+}
+
+impl LoopHoister {
+    fn hoist_in_list(&mut self, list: &mut Vec<Statement>, ctx: &mut PluginContext) -> TransformResult<()> {
+        let mut index = 0;
+        while index < list.len() {
+            if is_loop(&list[index])
+                && let Some(declaration) = self.hoist_from_loop(&mut list[index], ctx)?
+            {
+                list.insert(index, declaration);
+                index += 1;
+            }
+            index += 1;
+        }
+        Ok(())
+    }
+
+    /// Replaces the invariants of `loop_statement`, returning their declaration
+    fn hoist_from_loop(&mut self, loop_statement: &mut Statement, ctx: &mut PluginContext) -> TransformResult<Option<Statement>> {
+        let mut program = Program {
+            body:        vec![std::mem::replace(loop_statement, Statement::EmptyStatement)],
+            source_type: ProgramSourceType::Module,
+        };
+
+        let mut collector = InvariantCollector { scan: LoopScan::default(), invariants: Vec::new() };
+        plugin::run_plugin(&mut collector, &mut program, ctx)?;
+
+        let mut chosen = HashMap::new();
+        let mut declarations = Vec::new();
+        for invariant in collector.invariants {
+            let Some(length) = printed_length(&invariant.expression) else {
+                continue;
+            };
+            let name_length = self.names.peek().expect("name generator is unbounded").len() as isize;
+            let (length, uses) = (length as isize, invariant.uses as isize);
+            if name_length + length + 2 - uses * (length - name_length) > MAX_GROWTH {
+                continue;
+            }
+            let name = self.names.next().expect("name generator is unbounded");
+            declarations.push(VariableDeclarator {
+                id:   Pattern::Identifier(Identifier { name: name.clone(), span: None }),
+                init: Some(invariant.expression),
+                span: None,
+            });
+            chosen.insert(invariant.key, name);
+        }
+
+        if !chosen.is_empty() {
+            let mut replacer = InvariantReplacer { scan: LoopScan::default(), chosen };
+            plugin::run_plugin(&mut replacer, &mut program, ctx)?;
+            self.hoisted_count += declarations.len() as u32;
+        }
+        *loop_statement = program.body.pop().expect("the loop is put back");
+
+        Ok((!declarations.is_empty()).then(|| Statement::VariableDeclaration {
+            declarations,
+            kind: self.kind.clone(),
+            span: None,
+        }))
+    }
+
+    /// Whether declarations may be added to lists here: not at the top level of a
+    /// script, where a `var` or a top-level `const` would be a global
+    fn can_declare(&self, is_program_body: bool) -> bool {
+        !self.is_script
+            || self.function_depth > 0
+            || (!is_program_body && !matches!(self.kind, VariableDeclarationKind::Var))
+    }
+}
+
+impl TransformPlugin for LoopHoister {
+    fn name(&self) -> &str {
+        "hoist_invariants"
+    }
+
+    fn enter_program(&mut self, program: &mut Program, ctx: &mut PluginContext) -> TransformResult<()> {
+        if self.can_declare(true) {
+            self.hoist_in_list(&mut program.body, ctx)?;
+        }
+        Ok(())
+    }
+
+    fn enter_statement(&mut self, stmt: &mut Statement, ctx: &mut PluginContext) -> TransformResult<()> {
+        match stmt {
+            Statement::BlockStatement { body, .. } if self.can_declare(false) => self.hoist_in_list(body, ctx)?,
+            Statement::FunctionDeclaration { body, .. } => {
+                self.function_depth += 1;
+                self.hoist_in_list(&mut body.body, ctx)?;
+            }
+            Statement::ClassDeclaration { body, .. } => {
+                self.function_depth += 1;
+                for element in &mut body.body {
+                    if let ClassElement::MethodDefinition { value, .. } = element {
+                        self.hoist_in_list(&mut value.body.body, ctx)?;
+                    }
+                }
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    fn exit_statement(&mut self, stmt: &mut Statement, _ctx: &mut PluginContext) -> TransformResult<()> {
+        if matches!(stmt, Statement::FunctionDeclaration { .. } | Statement::ClassDeclaration { .. }) {
+            self.function_depth -= 1;
+        }
+        Ok(())
+    }
+
+    fn enter_expression(&mut self, expr: &mut Expression, ctx: &mut PluginContext) -> TransformResult<()> {
+        match expr {
+            Expression::FunctionExpression(function) => {
+                self.function_depth += 1;
+                self.hoist_in_list(&mut function.body.body, ctx)?;
+            }
+            Expression::ArrowFunctionExpression { body, .. } => {
+                self.function_depth += 1;
+                if let ArrowFunctionBody::BlockStatement(block) = body {
+                    self.hoist_in_list(&mut block.body, ctx)?;
+                }
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    fn exit_expression(&mut self, expr: &mut Expression, _ctx: &mut PluginContext) -> TransformResult<()> {
+        if matches!(expr, Expression::FunctionExpression(_) | Expression::ArrowFunctionExpression { .. }) {
+            self.function_depth -= 1;
+        }
+        Ok(())
+    }
+}
+
+fn is_loop(statement: &Statement) -> bool {
+    matches!(
+        statement,
+        Statement::WhileStatement { .. } | Statement::ForStatement { .. } | Statement::ForOfStatement { .. }
+    )
+}
+
+/// Where the walk over one loop is: expressions in nested functions, in the parts of
+/// the loop head that run once, and inside an invariant already found are skipped
+#[derive(Debug, Default)]
+struct LoopScan {
+    started:        bool,
+    function_depth: usize,
+    /// Addresses of the loop head expressions that are evaluated once
+    once:           HashSet<usize>,
+    /// Expression whose subtree is being skipped
+    skipping:       Option<usize>,
+}
+
+impl LoopScan {
+    fn enter_statement(&mut self, stmt: &Statement) {
+        if !self.started {
+            self.started = true;
+            match stmt {
+                Statement::ForStatement { init: Some(ForInit::Expression(init)), .. } => {
+                    self.once.insert(address(init));
+                }
+                Statement::ForStatement { init: Some(ForInit::VariableDeclaration { declarations, .. }), .. } => {
+                    self.once.extend(declarations.iter().filter_map(|declarator| declarator.init.as_ref()).map(address));
+                }
+                Statement::ForOfStatement { right, .. } => {
+                    self.once.insert(address(right));
+                }
+                _ => {}
+            }
+        }
+        if matches!(stmt, Statement::FunctionDeclaration { .. } | Statement::ClassDeclaration { .. }) {
+            self.function_depth += 1;
+        }
+    }
+
+    fn exit_statement(&mut self, stmt: &Statement) {
+        if matches!(stmt, Statement::FunctionDeclaration { .. } | Statement::ClassDeclaration { .. }) {
+            self.function_depth -= 1;
+        }
+    }
+
+    /// Whether `expr` is evaluated on every iteration and not inside a skipped subtree
+    fn enter_expression(&mut self, expr: &Expression) -> bool {
+        let repeated = self.skipping.is_none() && self.function_depth == 0;
+        if repeated && self.once.contains(&address(expr)) {
+            self.skipping = Some(address(expr));
+            return false;
+        }
+        if matches!(expr, Expression::FunctionExpression(_) | Expression::ArrowFunctionExpression { .. }) {
+            self.function_depth += 1;
+        }
+        repeated
+    }
+
+    fn skip(&mut self, expr: &Expression) {
+        self.skipping = Some(address(expr));
+    }
+
+    fn exit_expression(&mut self, expr: &Expression) {
+        if self.skipping == Some(address(expr)) {
+            self.skipping = None;
+        }
+        if matches!(expr, Expression::FunctionExpression(_) | Expression::ArrowFunctionExpression { .. }) {
+            self.function_depth -= 1;
+        }
+    }
+}
+
+fn address(expr: &Expression) -> usize {
+    expr as *const Expression as usize
+}
+
+/// An invariant found in a loop, with the number of times it appears
+struct Invariant {
+    key:        String,
+    expression: Expression,
+    uses:       usize,
+}
+
+/// Finds the largest invariant expressions of a loop, in source order
+struct InvariantCollector {
+    scan:       LoopScan,
+    invariants: Vec<Invariant>,
+}
+
+impl TransformPlugin for InvariantCollector {
+    fn name(&self) -> &str {
+        "hoist_invariants"
+    }
+
+    fn enter_statement(&mut self, stmt: &mut Statement, _ctx: &mut PluginContext) -> TransformResult<()> {
+        self.scan.enter_statement(stmt);
+        Ok(())
+    }
+
+    fn exit_statement(&mut self, stmt: &mut Statement, _ctx: &mut PluginContext) -> TransformResult<()> {
+        self.scan.exit_statement(stmt);
+        Ok(())
+    }
+
+    fn enter_expression(&mut self, expr: &mut Expression, ctx: &mut PluginContext) -> TransformResult<()> {
+        if !self.scan.enter_expression(expr) || !is_invariant(expr, ctx.analysis) {
+            return Ok(());
+        }
+        self.scan.skip(expr);
+        let Some(key) = expression_key(expr) else {
+            return Ok(());
+        };
+        match self.invariants.iter_mut().find(|invariant| invariant.key == key) {
+            Some(invariant) => invariant.uses += 1,
+            None => self.invariants.push(Invariant { key, expression: expr.clone(), uses: 1 }),
+        }
+        Ok(())
+    }
+
+    fn exit_expression(&mut self, expr: &mut Expression, _ctx: &mut PluginContext) -> TransformResult<()> {
+        self.scan.exit_expression(expr);
+        Ok(())
+    }
+}
+
+/// Replaces the chosen invariants with their names
+struct InvariantReplacer {
+    scan:   LoopScan,
+    /// Name of each hoisted invariant, by its key
+    chosen: HashMap<String, String>,
+}
+
+impl TransformPlugin for InvariantReplacer {
+    fn name(&self) -> &str {
+        "hoist_invariants"
+    }
+
+    fn enter_statement(&mut self, stmt: &mut Statement, _ctx: &mut PluginContext) -> TransformResult<()> {
+        self.scan.enter_statement(stmt);
+        Ok(())
+    }
+
+    fn exit_statement(&mut self, stmt: &mut Statement, _ctx: &mut PluginContext) -> TransformResult<()> {
+        self.scan.exit_statement(stmt);
+        Ok(())
+    }
+
+    fn enter_expression(&mut self, expr: &mut Expression, ctx: &mut PluginContext) -> TransformResult<()> {
+        if !self.scan.enter_expression(expr) || !is_invariant(expr, ctx.analysis) {
+            return Ok(());
+        }
+        if let Some(name) = expression_key(expr).and_then(|key| self.chosen.get(&key)) {
+            *expr = Expression::Identifier(Identifier { name: name.clone(), span: None });
+        } else {
+            self.scan.skip(expr);
+        }
+        Ok(())
+    }
+
+    fn exit_expression(&mut self, expr: &mut Expression, _ctx: &mut PluginContext) -> TransformResult<()> {
+        self.scan.exit_expression(expr);
+        Ok(())
+    }
+}
+
+/// Whether `expr` is constant, safe to evaluate early, and has a call worth saving
+fn is_invariant(expr: &Expression, analysis: &SemanticAnalysis) -> bool {
+    invariant_calls(expr, analysis).is_some_and(|calls| calls > 0)
+        && analysis.expression_purity(expr) == Purity::Pure
+}
+
+/// Number of calls in `expr` if it is built only from primitive literals, operators
+/// that cannot throw on them and calls to functions that are not declared in the program
+fn invariant_calls(expr: &Expression, analysis: &SemanticAnalysis) -> Option<usize> {
+    let all = |exprs: &[&Expression]| {
+        exprs.iter().try_fold(0, |calls, expr| Some(calls + invariant_calls(expr, analysis)?))
+    };
+    match expr {
+        Expression::Literal(Literal::RegExp(_)) => None,
+        Expression::Literal(_) => Some(0),
+        Expression::UnaryExpression { operator, argument, .. } if !matches!(operator, UnaryOperator::Delete) => {
+            invariant_calls(argument, analysis)
+        }
+        Expression::BinaryExpression { operator, left, right }
+            if !matches!(operator, BinaryOperator::In | BinaryOperator::Instanceof) =>
+        {
+            all(&[left, right])
+        }
+        Expression::ConditionalExpression { test, consequent, alternate } => {
+            all(&[test, consequent, alternate])
+        }
+        Expression::TemplateLiteral { expressions, .. } => all(&expressions.iter().collect::<Vec<_>>()),
+        Expression::CallExpression { callee, arguments, optional: false } => {
+            // Functions of the program may be pure but return a new object on each call
+            let declared = matches!(callee.as_ref(), Expression::Identifier(id) if analysis.purity.functions.contains_key(&id.name));
+            let spread = arguments.iter().any(|argument| matches!(argument, Expression::SpreadElement { .. }));
+            if declared || spread {
+                return None;
+            }
+            Some(all(&arguments.iter().collect::<Vec<_>>())? + 1)
+        }
+        _ => None,
+    }
+}
+
+/// Identifies equal expressions, whatever their source positions
+fn expression_key(expr: &Expression) -> Option<String> {
+    serde_json::to_value(expr).ok().map(|value| normalize(value).to_string())
+}
+
+/// Length of `expr` when printed on its own
+fn printed_length(expr: &Expression) -> Option<usize> {
+    let program = Program {
+        body:        vec![Statement::ExpressionStatement { expression: expr.clone(), span: None }],
+        source_type: ProgramSourceType::Module,
+    };
+    let code = Generator::new(GeneratorConfig::default()).generate(&program, None).ok()?.code;
+    Some(code.trim_end_matches(';').len())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::transformer::tests::run_pass;
+
+    /// Hoists loop invariants, returning the output and the hoisted count
+    fn hoist_source(source: &str, aggressive: bool) -> (String, u32) {
+        let config = TransformerConfig::default().with_aggressive_optimization(aggressive);
+        let (code, result) = run_pass(source, &config, hoist_loop_invariants);
+        (code, result.hoisted_count)
+    }
+
+    #[test]
+    fn test_hoists_pure_calls_out_of_loops() {
+        let source = "export function f(xs) { for (const x of xs) { use(x * Math.sqrt(2), x / Math.sqrt(2)); } \
+                      let i = 0; while (i < 3) { use(Math.sin(0.5) + i); i++; } }";
+        let (code, hoisted) = hoist_source(source, true);
+
+        assert_eq!(hoisted, 2);
+        assert_eq!(
+            code,
+            "export function f(xs){const a=Math.sqrt(2);for(const x of xs){use(x*a,x/a);}let i=0;const b=Math.sin(.5);while(i<3){use(b+i);i++;}}"
+        );
+        assert_eq!(hoist_source(source, false).1, 0);
+    }
+
+    #[test]
+    fn test_keeps_reads_fresh_values_and_nested_functions() {
+        let (code, hoisted) = hoist_source(
+            "const k = 2; function make() { return 1; }\n\
+             export function f(xs, n) { for (let i = Math.sqrt(3); i < n; i++) { \
+             use(Math.sqrt(k), Math.sqrt(n), make(), [Math.sqrt(k)].length, () => Math.cos(1), Math.random()); } }",
+            true,
+        );
+
+        assert_eq!(hoisted, 0, "{code}");
+    }
+}
@@ -9,8 +9,26 @@
 //! 1. **Identifier Renaming** - Variable and function name mangling
 //! 2. **Dead Code Elimination** - Remove unused and unreachable code
 //! 3. **Expression Simplification** - Constant folding and algebraic simplifications
-//! 4. **Property Minification** - Safe property renaming
-//! 5. **Function Minification** - Function inlining and optimization
+//! 4. **Collapse Variables** - Inline single-use variables into their use
+//! 5. **Inline Captures** - Copy constants captured by closures into the closures
+//! 6. **If-Return Elimination** - Drop `else` after `return` and redundant braces
+//! 7. **Statement Cleanup** - Remove empty and no-op statements, flatten nested blocks
+//! 8. **Hoist Functions** / **Hoist Variables** - Move declarations to the top of their
+//!    function (off by default)
+//! 9. **Property Minification** - Safe property renaming
+//! 10. **Function Minification** - Function inlining and optimization
+//! 11. **Loop-Invariant Hoisting** - Compute constant expressions once, before their loop
+//!     (only with `aggressive_optimization`)
+//!
+//! The order can be changed with `TransformerConfig::pass_order`, within the
+//! dependencies each pass declares. Passes whose opportunities are invalidated by later
+//! changes (expression simplification and dead code elimination) are re-run until a
+//! fixpoint is reached; see [`pass_manager`].
+//!
+//! Custom passes implementing [`plugin::TransformPlugin`] can be registered before or
+//! after any of these with [`Transformer::register_plugin`].
+//!
+//! `rjs-disable` comments switch passes off for parts of a file; see [`directives`].
 //!
 //! ## Test-Driven Development Approach
 //!
@@ -28,44 +46,128 @@
 //! - Type coercion behavior kept
 //! - This binding maintained
 //! - Scope integrity preserved
+//!
+//! ## Parallelism
+//!
+//! Passes that only look at one top-level statement at a time (currently expression
+//! simplification) run on worker threads via the [`parallel`] module, controlled by
+//! `TransformerConfig::worker_threads`. Results are merged in source order, so output
+//! does not depend on the thread count.
 
 use crate::analyzer::SemanticAnalysis;
-use crate::parser::ast_types::Program;
-use std::collections::HashMap;
+use crate::cancel::{CancellationToken, Cancelled};
+use crate::parser::ast_types::{Program, ProgramSourceType, Statement};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use thiserror::Error;
 
 // Re-export submodules
 pub mod identifier_renaming;
+pub mod collapse_vars;
+pub mod inline_captures;
+pub mod loop_invariants;
+pub mod hoisting;
+pub mod if_return;
+pub mod cleanup;
 pub mod dead_code_elimination;
+pub mod directives;
 pub mod expression_simplification;
+pub mod explain;
+pub mod diagnostics;
+pub mod export_mangling;
 pub mod property_minification;
+pub mod string_dedupe;
 pub mod function_minification;
+pub mod parallel;
+pub mod pass_manager;
+pub mod plugin;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod dynamic_plugin;
 pub mod rollback;
+pub mod syntax_lowering;
+pub mod worker_urls;
 
+use crate::parser::SourceSpan;
+use crate::target::Targets;
+use crate::transformer::directives::PassDirectives;
+use crate::transformer::diagnostics::Diagnostic;
+use crate::transformer::explain::{ExplainEntry, ExplainRecorder};
+use crate::transformer::export_mangling::ModuleExports;
+use crate::transformer::pass_manager::PassManager;
+use crate::transformer::plugin::{PluginContext, PluginPosition, RegisteredPlugin, TransformPlugin};
 use crate::transformer::rollback::{RollbackManager, RollbackConfig};
 
 #[cfg(test)]
 mod tests;
 
 /// Configuration for the transformer component
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
 pub struct TransformerConfig {
     /// Enable identifier renaming (variable mangling)
     pub enable_identifier_renaming: bool,
     /// Enable dead code elimination
     pub enable_dead_code_elimination: bool,
+    /// Callee patterns (`console.*`, `console.log`, `assert`) whose calls dead code
+    /// elimination removes when their result is unused
+    pub drop_calls: Vec<String>,
+    /// Keep the side effects of the arguments of calls removed through `drop_calls`
+    pub keep_dropped_call_arguments: bool,
     /// Enable expression simplification and constant folding
     pub enable_expression_simplification: bool,
+    /// Enable collapsing single-use variables into their use
+    pub enable_collapse_vars: bool,
+    /// Enable inlining constants that closures only read into the closures
+    pub enable_inline_captures: bool,
+    /// Enable removing `else` after `return` and redundant `if` braces
+    pub enable_if_return: bool,
+    /// Enable removing empty and no-op statements and flattening nested blocks
+    pub enable_cleanup: bool,
+    /// Remove `debugger` statements during cleanup
+    pub drop_debugger: bool,
+    /// Enable moving function declarations to the top of their scope
+    pub enable_hoist_funs: bool,
+    /// Enable merging the `var` statements of a function into one
+    pub enable_hoist_vars: bool,
     /// Enable property minification
     pub enable_property_minification: bool,
+    /// Rename properties to readable `_$name$_` forms instead of short names, to find
+    /// code that property renaming breaks (see [`property_minification`])
+    pub debug_property_names: bool,
+    /// Inline the values of enum-like objects; runs the property minification pass even
+    /// when property renaming is disabled (see [`property_minification`])
+    pub inline_enums: bool,
     /// Enable function minification and inlining
     pub enable_function_minification: bool,
     /// Enable rollback for unsafe transformations
     pub enable_rollback: bool,
-    /// Enable verbose output for debugging
-    pub verbose: bool,
     /// Enable aggressive optimization (may be less safe)
     pub aggressive_optimization: bool,
+    /// Worker threads for per-statement passes (0 = one per available core, 1 = sequential)
+    pub worker_threads: usize,
+    /// Order of the built-in passes; passes not listed run afterwards in their default order
+    pub pass_order: Vec<BuiltinPass>,
+    /// Maximum number of rounds over the pipeline while re-running invalidated passes
+    /// (1 = run every pass once)
+    pub max_pass_iterations: u32,
+    /// Record a before/after entry for every change (see [`explain`])
+    pub explain: bool,
+    /// Target environments; passes and plugins must not introduce syntax they lack
+    pub target: Targets,
+    /// Whether the exported names of modules are kept or mangled (see [`export_mangling`])
+    pub module_exports: ModuleExports,
+    /// Hoist repeated strings saving at least this many bytes into a top-level
+    /// declaration (see [`string_dedupe`]); `None` disables it
+    pub dedupe_strings: Option<usize>,
+    /// Seed that shuffles the alphabet of generated names (`None` for alphabet order)
+    pub name_seed: Option<u64>,
+    /// URLs of compiled worker entries, keyed by the specifier the module starts them
+    /// with (see [`worker_urls`])
+    #[serde(default)]
+    pub worker_urls: BTreeMap<String, String>,
+    /// Stops the transformation before the next pass or plugin once cancelled
+    #[serde(skip)]
+    pub cancellation: CancellationToken,
 }
 
 impl Default for TransformerConfig {
@@ -73,12 +175,324 @@ impl Default for TransformerConfig {
         Self {
             enable_identifier_renaming: true,
             enable_dead_code_elimination: true,
+            drop_calls: Vec::new(),
+            keep_dropped_call_arguments: false,
             enable_expression_simplification: true,
+            enable_collapse_vars: true,
+            enable_inline_captures: true,
+            enable_if_return: true,
+            enable_cleanup: true,
+            drop_debugger: false,
+            enable_hoist_funs: false,
+            enable_hoist_vars: false,
             enable_property_minification: true,
+            debug_property_names: false,
+            inline_enums: true,
             enable_function_minification: true,
             enable_rollback: true,
-            verbose: false,
             aggressive_optimization: false,
+            worker_threads: 0,
+            pass_order: BuiltinPass::ALL.to_vec(),
+            max_pass_iterations: 4,
+            explain: false,
+            target: Targets::latest(),
+            module_exports: ModuleExports::Keep,
+            dedupe_strings: None,
+            name_seed: None,
+            worker_urls: BTreeMap::new(),
+            cancellation: CancellationToken::new(),
+        }
+    }
+}
+
+impl TransformerConfig {
+    /// Enables or disables identifier renaming
+    pub fn with_identifier_renaming(mut self, enabled: bool) -> Self {
+        self.enable_identifier_renaming = enabled;
+        self
+    }
+
+    /// Enables or disables dead code elimination
+    pub fn with_dead_code_elimination(mut self, enabled: bool) -> Self {
+        self.enable_dead_code_elimination = enabled;
+        self
+    }
+
+    /// Sets the callee patterns whose unused calls are removed
+    pub fn with_drop_calls(mut self, drop_calls: Vec<String>) -> Self {
+        self.drop_calls = drop_calls;
+        self
+    }
+
+    /// Sets whether removed calls keep the side effects of their arguments
+    pub fn with_keep_dropped_call_arguments(mut self, keep: bool) -> Self {
+        self.keep_dropped_call_arguments = keep;
+        self
+    }
+
+    /// Enables or disables expression simplification
+    pub fn with_expression_simplification(mut self, enabled: bool) -> Self {
+        self.enable_expression_simplification = enabled;
+        self
+    }
+
+    /// Enables or disables collapsing of single-use variables
+    pub fn with_collapse_vars(mut self, enabled: bool) -> Self {
+        self.enable_collapse_vars = enabled;
+        self
+    }
+
+    /// Enables or disables inlining of read-only captured constants
+    pub fn with_inline_captures(mut self, enabled: bool) -> Self {
+        self.enable_inline_captures = enabled;
+        self
+    }
+
+    /// Enables or disables if-return elimination
+    pub fn with_if_return(mut self, enabled: bool) -> Self {
+        self.enable_if_return = enabled;
+        self
+    }
+
+    /// Enables or disables statement cleanup
+    pub fn with_cleanup(mut self, enabled: bool) -> Self {
+        self.enable_cleanup = enabled;
+        self
+    }
+
+    /// Sets whether cleanup removes `debugger` statements
+    pub fn with_drop_debugger(mut self, drop_debugger: bool) -> Self {
+        self.drop_debugger = drop_debugger;
+        self
+    }
+
+    /// Enables or disables hoisting of function declarations
+    pub fn with_hoist_funs(mut self, enabled: bool) -> Self {
+        self.enable_hoist_funs = enabled;
+        self
+    }
+
+    /// Enables or disables merging of `var` statements
+    pub fn with_hoist_vars(mut self, enabled: bool) -> Self {
+        self.enable_hoist_vars = enabled;
+        self
+    }
+
+    /// Enables or disables property minification
+    pub fn with_property_minification(mut self, enabled: bool) -> Self {
+        self.enable_property_minification = enabled;
+        self
+    }
+
+    /// Renames properties to readable `_$name$_` forms instead of short names
+    pub fn with_debug_property_names(mut self, enabled: bool) -> Self {
+        self.debug_property_names = enabled;
+        self
+    }
+
+    /// Enables or disables inlining of enum-like objects
+    pub fn with_inline_enums(mut self, enabled: bool) -> Self {
+        self.inline_enums = enabled;
+        self
+    }
+
+    /// Enables or disables function minification
+    pub fn with_function_minification(mut self, enabled: bool) -> Self {
+        self.enable_function_minification = enabled;
+        self
+    }
+
+    /// Enables or disables rollback checkpoints
+    pub fn with_rollback(mut self, enabled: bool) -> Self {
+        self.enable_rollback = enabled;
+        self
+    }
+
+    /// Enables or disables aggressive optimization
+    pub fn with_aggressive_optimization(mut self, enabled: bool) -> Self {
+        self.aggressive_optimization = enabled;
+        self
+    }
+
+    /// Sets the number of worker threads (0 = one per available core)
+    pub fn with_worker_threads(mut self, worker_threads: usize) -> Self {
+        self.worker_threads = worker_threads;
+        self
+    }
+
+    /// Sets the order of the built-in passes
+    pub fn with_pass_order(mut self, pass_order: Vec<BuiltinPass>) -> Self {
+        self.pass_order = pass_order;
+        self
+    }
+
+    /// Sets the maximum number of rounds over the pipeline
+    pub fn with_max_pass_iterations(mut self, max_pass_iterations: u32) -> Self {
+        self.max_pass_iterations = max_pass_iterations;
+        self
+    }
+
+    /// Enables or disables recording of explain entries
+    pub fn with_explain(mut self, enabled: bool) -> Self {
+        self.explain = enabled;
+        self
+    }
+
+    /// Sets the target environments
+    pub fn with_target(mut self, target: Targets) -> Self {
+        self.target = target;
+        self
+    }
+
+    /// Sets whether the exported names of modules are kept or mangled
+    pub fn with_module_exports(mut self, module_exports: ModuleExports) -> Self {
+        self.module_exports = module_exports;
+        self
+    }
+
+    /// Sets the minimum saving in bytes for hoisting a repeated string, or disables it
+    pub fn with_dedupe_strings(mut self, threshold: Option<usize>) -> Self {
+        self.dedupe_strings = threshold;
+        self
+    }
+
+    /// Sets the seed for generated names
+    pub fn with_name_seed(mut self, seed: Option<u64>) -> Self {
+        self.name_seed = seed;
+        self
+    }
+
+    /// Sets the token that stops the transformation early
+    pub fn with_cancellation(mut self, cancellation: CancellationToken) -> Self {
+        self.cancellation = cancellation;
+        self
+    }
+}
+
+/// Built-in transformation passes, in pipeline order
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BuiltinPass {
+    IdentifierRenaming,
+    DeadCodeElimination,
+    ExpressionSimplification,
+    CollapseVariables,
+    InlineCaptures,
+    IfReturn,
+    Cleanup,
+    HoistFunctions,
+    HoistVariables,
+    PropertyMinification,
+    FunctionMinification,
+    LoopInvariants,
+}
+
+impl BuiltinPass {
+    /// All built-in passes in the order they run
+    pub const ALL: [BuiltinPass; 12] = [
+        BuiltinPass::IdentifierRenaming,
+        BuiltinPass::DeadCodeElimination,
+        BuiltinPass::ExpressionSimplification,
+        BuiltinPass::CollapseVariables,
+        BuiltinPass::InlineCaptures,
+        BuiltinPass::IfReturn,
+        BuiltinPass::Cleanup,
+        BuiltinPass::HoistFunctions,
+        BuiltinPass::HoistVariables,
+        BuiltinPass::PropertyMinification,
+        BuiltinPass::FunctionMinification,
+        BuiltinPass::LoopInvariants,
+    ];
+
+    /// Name used in pass timings and rollback checkpoints
+    pub fn name(self) -> &'static str {
+        match self {
+            BuiltinPass::IdentifierRenaming => "identifier_renaming",
+            BuiltinPass::DeadCodeElimination => "dead_code_elimination",
+            BuiltinPass::ExpressionSimplification => "expression_simplification",
+            BuiltinPass::CollapseVariables => "collapse_vars",
+            BuiltinPass::InlineCaptures => "inline_captures",
+            BuiltinPass::IfReturn => "if_return",
+            BuiltinPass::Cleanup => "cleanup",
+            BuiltinPass::HoistFunctions => "hoist_funs",
+            BuiltinPass::HoistVariables => "hoist_vars",
+            BuiltinPass::PropertyMinification => "property_minification",
+            BuiltinPass::FunctionMinification => "function_minification",
+            BuiltinPass::LoopInvariants => "hoist_invariants",
+        }
+    }
+
+    /// Looks up a pass by its [`name`](Self::name)
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|pass| pass.name() == name)
+    }
+
+    /// Human-readable name used in verbose output
+    pub fn display_name(self) -> &'static str {
+        match self {
+            BuiltinPass::IdentifierRenaming => "Identifier Renaming",
+            BuiltinPass::DeadCodeElimination => "Dead Code Elimination",
+            BuiltinPass::ExpressionSimplification => "Expression Simplification",
+            BuiltinPass::CollapseVariables => "Collapse Variables",
+            BuiltinPass::InlineCaptures => "Inline Captures",
+            BuiltinPass::IfReturn => "If-Return Elimination",
+            BuiltinPass::Cleanup => "Statement Cleanup",
+            BuiltinPass::HoistFunctions => "Hoist Functions",
+            BuiltinPass::HoistVariables => "Hoist Variables",
+            BuiltinPass::PropertyMinification => "Property Minification",
+            BuiltinPass::FunctionMinification => "Function Minification",
+            BuiltinPass::LoopInvariants => "Loop-Invariant Hoisting",
+        }
+    }
+
+    /// Whether the pass is enabled in `config`
+    pub fn is_enabled(self, config: &TransformerConfig) -> bool {
+        match self {
+            BuiltinPass::IdentifierRenaming => config.enable_identifier_renaming,
+            BuiltinPass::DeadCodeElimination => config.enable_dead_code_elimination,
+            BuiltinPass::ExpressionSimplification => config.enable_expression_simplification,
+            BuiltinPass::CollapseVariables => config.enable_collapse_vars,
+            BuiltinPass::InlineCaptures => config.enable_inline_captures,
+            BuiltinPass::IfReturn => config.enable_if_return,
+            BuiltinPass::Cleanup => config.enable_cleanup,
+            BuiltinPass::HoistFunctions => config.enable_hoist_funs,
+            BuiltinPass::HoistVariables => config.enable_hoist_vars,
+            BuiltinPass::PropertyMinification => config.enable_property_minification || config.inline_enums,
+            BuiltinPass::FunctionMinification => config.enable_function_minification,
+            BuiltinPass::LoopInvariants => config.aggressive_optimization,
+        }
+    }
+
+    /// Passes that must run before this one when both are enabled
+    ///
+    /// Functions are only inlined once dead code is gone and their bodies are simplified,
+    /// and loop invariants are only hoisted once the foldable ones are folded.
+    pub fn dependencies(self) -> &'static [BuiltinPass] {
+        match self {
+            BuiltinPass::FunctionMinification => {
+                &[BuiltinPass::DeadCodeElimination, BuiltinPass::ExpressionSimplification]
+            }
+            BuiltinPass::LoopInvariants => &[BuiltinPass::ExpressionSimplification],
+            _ => &[],
+        }
+    }
+
+    /// Passes that may find new work after this one changes the program
+    pub fn invalidates(self) -> &'static [BuiltinPass] {
+        match self {
+            BuiltinPass::DeadCodeElimination => &[BuiltinPass::ExpressionSimplification],
+            BuiltinPass::IfReturn => &[BuiltinPass::DeadCodeElimination],
+            BuiltinPass::Cleanup => &[BuiltinPass::CollapseVariables],
+            BuiltinPass::ExpressionSimplification => &[BuiltinPass::DeadCodeElimination],
+            BuiltinPass::CollapseVariables | BuiltinPass::FunctionMinification => {
+                &[BuiltinPass::DeadCodeElimination, BuiltinPass::ExpressionSimplification]
+            }
+            // Inlined values fold, and removed declarations leave empty statements
+            BuiltinPass::InlineCaptures | BuiltinPass::PropertyMinification => &[BuiltinPass::ExpressionSimplification, BuiltinPass::Cleanup],
+            BuiltinPass::IdentifierRenaming
+            | BuiltinPass::HoistFunctions
+            | BuiltinPass::HoistVariables
+            | BuiltinPass::LoopInvariants => &[],
         }
     }
 }
@@ -106,6 +520,15 @@ pub enum TransformError {
     
     #[error("Invalid transformation state: {0}")]
     InvalidState(String),
+
+    #[error("Invalid pass order: {0}")]
+    InvalidPassOrder(String),
+
+    #[error("Plugin '{plugin}' failed: {message}")]
+    PluginError { plugin: String, message: String },
+
+    #[error("{0}")]
+    Cancelled(#[from] Cancelled),
 }
 
 /// Result type for transformer operations
@@ -120,14 +543,101 @@ pub struct TransformationStats {
     pub dead_statements_removed: u32,
     /// Number of expressions simplified
     pub expressions_simplified: u32,
+    /// Number of single-use variables collapsed into their use
+    pub variables_collapsed: u32,
+    /// Number of closure reads replaced by the constant they captured
+    pub captures_inlined: u32,
+    /// Number of `else` branches, braces and `undefined` return values removed
+    pub if_returns_simplified: u32,
+    /// Number of empty and no-op statements removed and blocks flattened
+    pub statements_cleaned: u32,
+    /// Number of function declarations moved to the top of their scope
+    pub functions_hoisted: u32,
+    /// Number of `var` statements merged into one declaration
+    pub variables_hoisted: u32,
     /// Number of properties renamed
     pub properties_renamed: u32,
     /// Number of functions inlined
     pub functions_inlined: u32,
+    /// Number of constant expressions moved out of loops
+    pub invariants_hoisted: u32,
     /// Number of transformations rolled back due to safety concerns
     pub rollbacks_performed: u32,
+    /// Number of changes reported by plugins
+    pub plugin_changes: u32,
+    /// Number of rounds over the pipeline, including fixpoint re-runs
+    pub pass_iterations: u32,
+    /// Number of expressions down-leveled for the targets
+    pub syntax_lowered: u32,
+    /// Number of string literals replaced by a hoisted declaration
+    pub strings_deduplicated: u32,
     /// Total time spent on transformation (in milliseconds)
     pub transformation_time_ms: u64,
+    /// Time spent in each executed pass, in execution order
+    pub pass_timings: Vec<PassTiming>,
+}
+
+/// Execution time of a single transformation pass
+#[derive(Debug, Clone)]
+pub struct PassTiming {
+    /// Name of the pass (e.g., "identifier_renaming")
+    pub pass_name: String,
+    /// Time spent in the pass (in milliseconds)
+    pub time_ms: f64,
+}
+
+impl TransformationStats {
+    /// Adds the changes made by one run of `pass` to its counter
+    fn record_changes(&mut self, pass: BuiltinPass, changes: u32) {
+        let counter = match pass {
+            BuiltinPass::IdentifierRenaming => &mut self.identifiers_renamed,
+            BuiltinPass::DeadCodeElimination => &mut self.dead_statements_removed,
+            BuiltinPass::ExpressionSimplification => &mut self.expressions_simplified,
+            BuiltinPass::CollapseVariables => &mut self.variables_collapsed,
+            BuiltinPass::InlineCaptures => &mut self.captures_inlined,
+            BuiltinPass::IfReturn => &mut self.if_returns_simplified,
+            BuiltinPass::Cleanup => &mut self.statements_cleaned,
+            BuiltinPass::HoistFunctions => &mut self.functions_hoisted,
+            BuiltinPass::HoistVariables => &mut self.variables_hoisted,
+            BuiltinPass::PropertyMinification => &mut self.properties_renamed,
+            BuiltinPass::FunctionMinification => &mut self.functions_inlined,
+            BuiltinPass::LoopInvariants => &mut self.invariants_hoisted,
+        };
+        *counter += changes;
+    }
+
+    /// Records the elapsed time of a pass that started at `start`
+    fn record_pass(&mut self, pass_name: &str, start: crate::Instant) {
+        self.pass_timings.push(PassTiming {
+            pass_name: pass_name.to_string(),
+            time_ms: start.elapsed().as_secs_f64() * 1000.0,
+        });
+    }
+}
+
+/// Changes made by one run of a built-in pass, applied once the run is kept
+#[derive(Debug, Default)]
+struct PassOutcome {
+    /// Number of changes reported by the pass
+    changes: u32,
+    /// Warnings reported by the pass
+    warnings: Vec<String>,
+    /// Informational messages, such as a placeholder pass noting it does nothing yet
+    notes: Vec<String>,
+    /// Identifiers renamed by the pass
+    identifier_mapping: BTreeMap<String, String>,
+    /// Properties renamed by the pass, by the variable of their object
+    property_mapping: BTreeMap<String, BTreeMap<String, String>>,
+}
+
+impl PassOutcome {
+    fn new(changes: u32, warnings: Vec<String>) -> Self {
+        Self { changes, warnings, ..Self::default() }
+    }
+
+    fn from_notes(changes: u32, notes: Vec<String>) -> Self {
+        Self { changes, notes, ..Self::default() }
+    }
 }
 
 /// Result of the transformation process
@@ -138,9 +648,40 @@ pub struct TransformationResult {
     /// Statistics about the transformations performed
     pub stats: TransformationStats,
     /// Mapping from original identifiers to renamed ones
-    pub identifier_mapping: HashMap<String, String>,
-    /// Any warnings generated during transformation
-    pub warnings: Vec<String>,
+    pub identifier_mapping: BTreeMap<String, String>,
+    /// Mapping from original property names to renamed ones, by the variable of the
+    /// object that owns them
+    pub property_mapping: BTreeMap<String, BTreeMap<String, String>>,
+    /// Any warnings generated during transformation, with their source location when known
+    pub warnings: Vec<Diagnostic>,
+    /// Changes made by each pass, when `TransformerConfig::explain` is set
+    pub explanations: Vec<ExplainEntry>,
+    /// Mapping from original exported names to mangled ones, when
+    /// `TransformerConfig::module_exports` is [`ModuleExports::Mangle`]
+    pub export_map: BTreeMap<String, String>,
+}
+
+impl TransformationResult {
+    /// Every rename the transformation made
+    pub fn name_map(&self) -> NameMap {
+        NameMap {
+            identifiers: self.identifier_mapping.clone(),
+            properties:  self.property_mapping.clone(),
+            exports:     self.export_map.clone(),
+        }
+    }
+}
+
+/// Original and new names of everything a transformation renamed, for de-obfuscation
+/// tools and crash report symbolication
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NameMap {
+    /// Local identifiers, original name to new name
+    pub identifiers: BTreeMap<String, String>,
+    /// Object properties, original name to new name, by the variable of their object
+    pub properties:  BTreeMap<String, BTreeMap<String, String>>,
+    /// Exported names of the module
+    pub exports:     BTreeMap<String, String>,
 }
 
 /// Main transformer that orchestrates all transformation passes
@@ -152,6 +693,16 @@ pub struct Transformer {
     analysis_result: SemanticAnalysis,
     /// Rollback manager for unsafe transformations
     rollback_manager: RollbackManager,
+    /// Custom passes, in registration order
+    plugins: Vec<RegisteredPlugin>,
+    /// Source spans of the top-level statements, for explain entries
+    statement_spans: Vec<SourceSpan>,
+    /// Original source span of each current top-level statement, for diagnostics
+    statement_locations: Vec<Option<SourceSpan>>,
+    /// Explain recorder for the running transformation
+    explain: Option<ExplainRecorder>,
+    /// Passes disabled by `rjs-disable` comments
+    directives: PassDirectives,
 }
 
 impl Transformer {
@@ -170,17 +721,19 @@ impl Transformer {
     ///
     /// ```rust,no_run
     /// use rjs_compiler::transformer::{Transformer, TransformerConfig};
-    /// use rjs_compiler::analyzer::SemanticAnalysis;
+    /// use rjs_compiler::analyzer::{analyze_ast, AnalyzerConfig};
+    /// # use rjs_compiler::parser::{parse_js, ParserConfig};
+    /// # let ast = parse_js("let x = 1;", "example.js", &ParserConfig::default()).ast.unwrap();
     /// 
     /// let config = TransformerConfig::default();
-    /// let analysis_result = SemanticAnalysis::default(); // From analyzer
+    /// let analysis_result = analyze_ast(&ast, &AnalyzerConfig::default())?;
     /// let transformer = Transformer::new(config, analysis_result);
+    /// # Ok::<(), rjs_compiler::analyzer::AnalysisError>(())
     /// ```
     pub fn new(config: TransformerConfig, analysis_result: SemanticAnalysis) -> Self {
         let rollback_config = RollbackConfig {
             auto_rollback: config.enable_rollback,
             max_checkpoints: 10,
-            verbose: config.verbose,
         };
         let rollback_manager = RollbackManager::new(rollback_config);
         
@@ -188,9 +741,40 @@ impl Transformer {
             config,
             analysis_result,
             rollback_manager,
+            plugins: Vec::new(),
+            statement_spans: Vec::new(),
+            statement_locations: Vec::new(),
+            explain: None,
+            directives: PassDirectives::default(),
         }
     }
 
+    /// Sets the source spans of the program's top-level statements
+    ///
+    /// Used to locate explain entries in the source; see
+    /// [`ArenaParseResult::statement_spans`](crate::parser::ArenaParseResult::statement_spans).
+    pub fn set_statement_spans(&mut self, spans: Vec<SourceSpan>) {
+        self.statement_spans = spans;
+    }
+
+    /// Sets the `rjs-disable` directives of the program passed to the next
+    /// [`transform`](Self::transform) call
+    pub fn set_directives(&mut self, directives: PassDirectives) {
+        self.directives = directives;
+    }
+
+    /// Registers a custom pass to run at `position` in the pipeline
+    ///
+    /// Plugins registered at the same position run in registration order.
+    ///
+    /// # Arguments
+    ///
+    /// * `plugin` - The custom pass
+    /// * `position` - Built-in pass to run before or after
+    pub fn register_plugin(&mut self, plugin: Box<dyn TransformPlugin>, position: PluginPosition) {
+        self.plugins.push(RegisteredPlugin { plugin, position });
+    }
+
     /// Transforms the given AST through all enabled transformation passes
     ///
     /// # Arguments
@@ -204,179 +788,425 @@ impl Transformer {
     ///
     /// # Errors
     ///
-    /// Returns `TransformError` if any transformation pass fails or if rollback is required.
+    /// Returns `TransformError` if the configured pass order is invalid, if any
+    /// transformation pass fails or if rollback is required.
     ///
     /// # Examples
     ///
     /// ```rust,no_run
-    /// use rjs_compiler::parser::ast_types::Program;
+    /// use rjs_compiler::parser::{parse_js, ParserConfig};
+    /// # use rjs_compiler::analyzer::{analyze_ast, AnalyzerConfig};
+    /// # use rjs_compiler::transformer::{Transformer, TransformerConfig};
     /// 
-    /// let ast = Program { /* ... */ };
+    /// let ast = parse_js("let x = 1;", "example.js", &ParserConfig::default()).ast.unwrap();
+    /// # let analysis_result = analyze_ast(&ast, &AnalyzerConfig::default())?;
+    /// # let mut transformer = Transformer::new(TransformerConfig::default(), analysis_result);
     /// let result = transformer.transform(ast)?;
     /// println!("Transformed {} identifiers", result.stats.identifiers_renamed);
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
     /// ```
     pub fn transform(&mut self, mut ast: Program) -> TransformResult<TransformationResult> {
-        let start_time = std::time::Instant::now();
+        let start_time = crate::Instant::now();
         let mut stats = TransformationStats::default();
-        let mut identifier_mapping = HashMap::new();
+        let mut identifier_mapping = BTreeMap::new();
+        let mut property_mapping = BTreeMap::new();
         let mut warnings = Vec::new();
 
-        if self.config.verbose {
-            println!("🔄 Starting transformation phase with {} passes enabled", 
-                self.count_enabled_passes());
-        }
+        tracing::debug!("🔄 Starting transformation phase with {} passes enabled", 
+            self.count_enabled_passes());
 
-        // Pass 1: Identifier Renaming (Variable Mangling)
-        if self.config.enable_identifier_renaming {
-            if self.config.verbose {
-                println!("🔄 Pass 1: Identifier Renaming");
-            }
-            
-            // Create checkpoint for rollback if needed
-            if self.config.enable_rollback {
-                self.rollback_manager.create_checkpoint(
-                    &ast, 
-                    "identifier_renaming", 
-                    "Before identifier renaming transformation"
-                );
+        self.explain = self.config.explain.then(|| ExplainRecorder::new(&ast, &self.statement_spans));
+        self.statement_locations = (0..ast.body.len()).map(|index| self.statement_spans.get(index).cloned()).collect();
+        warnings.extend(self.directives.take_warnings());
+        self.directives.protect_names(&ast, &mut self.analysis_result);
+        let mut pass_manager = PassManager::new(&self.config)?;
+        let order = pass_manager.order().to_vec();
+        while let Some(iteration) = pass_manager.begin_iteration() {
+            // Plugins run once, around the first round
+            let first_round = iteration == 1;
+            if !first_round {
+                tracing::debug!("🔁 Round {}: re-running invalidated passes", iteration);
             }
-            
-            let rename_result = identifier_renaming::rename_identifiers(
-                &mut ast, 
-                &self.analysis_result.symbol_table,
-                &self.config
-            )?;
-            
-            stats.identifiers_renamed = rename_result.renamed_count;
-            identifier_mapping.extend(rename_result.mapping);
-            warnings.extend(rename_result.warnings);
-        }
 
-        // Pass 2: Dead Code Elimination
-        if self.config.enable_dead_code_elimination {
-            if self.config.verbose {
-                println!("🔄 Pass 2: Dead Code Elimination");
-            }
-            
-            // Create checkpoint for rollback if needed
-            if self.config.enable_rollback {
-                self.rollback_manager.create_checkpoint(
-                    &ast, 
-                    "dead_code_elimination", 
-                    "Before dead code elimination transformation"
-                );
-            }
-            
-            let dce_result = dead_code_elimination::eliminate_dead_code(
-                &mut ast,
-                &self.analysis_result.symbol_table,
-                &self.config
-            )?;
-            
-            stats.dead_statements_removed = dce_result.removed_count;
-            warnings.extend(dce_result.warnings);
-        }
+            for (index, &pass) in order.iter().enumerate() {
+                self.config.cancellation.check()?;
+                if first_round {
+                    self.run_plugins(PluginPosition::Before(pass), &mut ast, &mut stats, &mut warnings)?;
+                }
 
-        // Pass 3: Expression Simplification & Compression
-        if self.config.enable_expression_simplification {
-            if self.config.verbose {
-                println!("🔄 Pass 3: Expression Simplification");
-            }
-            
-            // Create checkpoint for rollback if needed
-            if self.config.enable_rollback {
-                self.rollback_manager.create_checkpoint(
-                    &ast, 
-                    "expression_simplification", 
-                    "Before expression simplification transformation"
-                );
-            }
-            
-            let simplify_result = expression_simplification::simplify_expressions(
-                &mut ast,
-                &self.config
-            )?;
-            
-            stats.expressions_simplified = simplify_result.simplified_count;
-            stats.rollbacks_performed += simplify_result.rollbacks;
-            warnings.extend(simplify_result.warnings);
-        }
+                if pass_manager.take(pass) {
+                    tracing::debug!("🔄 Pass {}: {}", index + 1, pass.display_name());
+                    let changes = self.run_builtin_pass(
+                        pass,
+                        &mut ast,
+                        &mut stats,
+                        &mut identifier_mapping,
+                        &mut property_mapping,
+                        &mut warnings,
+                    )?;
+                    pass_manager.record_changes(pass, changes, &self.config);
+                }
 
-        // Pass 4: Property Minification
-        if self.config.enable_property_minification {
-            if self.config.verbose {
-                println!("🔄 Pass 4: Property Minification");
+                if first_round {
+                    self.run_plugins(PluginPosition::After(pass), &mut ast, &mut stats, &mut warnings)?;
+                }
             }
-            
-            let prop_result = property_minification::minify_properties(
-                &mut ast,
-                &self.analysis_result,
-                &self.config
-            )?;
-            
-            stats.properties_renamed = prop_result.renamed_count;
-            warnings.extend(prop_result.warnings);
         }
+        self.config.cancellation.check()?;
+        stats.pass_iterations = pass_manager.iterations();
+        stats.syntax_lowered = syntax_lowering::lower_syntax(&mut ast, &self.analysis_result.symbol_table, &self.config);
+        stats.strings_deduplicated = string_dedupe::dedupe_strings(&mut ast, &self.analysis_result, &self.config)?;
 
-        // Pass 5: Function Minification
-        if self.config.enable_function_minification {
-            if self.config.verbose {
-                println!("🔄 Pass 5: Function Minification");
-            }
-            
-            let func_result = function_minification::minify_functions(
-                &mut ast,
-                &self.analysis_result,
-                &self.config
-            )?;
-            
-            stats.functions_inlined = func_result.inlined_count;
-            warnings.extend(func_result.warnings);
+        let export_map = match (self.config.module_exports, &ast.source_type) {
+            (ModuleExports::Mangle, ProgramSourceType::Module) => match export_mangling::star_reexport(&ast) {
+                Some((source, span)) => {
+                    let message = format!(
+                        "Exports are not mangled: `export * from '{}'` re-exports names a short name could clash with",
+                        source
+                    );
+                    warnings.push(Diagnostic::warning(message).with_span(span));
+                    BTreeMap::new()
+                }
+                None => export_mangling::mangle_exports(&mut ast, self.config.name_seed),
+            },
+            _ => BTreeMap::new(),
+        };
+        let workers = worker_urls::rewrite_worker_urls(&mut ast, &self.analysis_result, &self.config)?;
+        if workers > 0 {
+            tracing::debug!("👷 Pointed {} worker entries at their outputs", workers);
         }
 
         stats.transformation_time_ms = start_time.elapsed().as_millis() as u64;
 
-        if self.config.verbose {
-            println!("✅ Transformation completed in {}ms", stats.transformation_time_ms);
-            self.print_transformation_summary(&stats);
+        tracing::debug!("✅ Transformation completed in {}ms", stats.transformation_time_ms);
+        if tracing::enabled!(tracing::Level::DEBUG) {
+            self.log_transformation_summary(&stats);
         }
 
         Ok(TransformationResult {
             transformed_ast: ast,
             stats,
             identifier_mapping,
+            property_mapping,
             warnings,
+            explanations: self.explain.take().map(ExplainRecorder::into_entries).unwrap_or_default(),
+            export_map,
         })
     }
 
+    /// Runs one built-in pass, checkpointing first when it supports rollback
+    ///
+    /// A checkpointed pass that fails, or whose output fails
+    /// [`RollbackManager::check_transformation`], is rolled back to its checkpoint and
+    /// reported as a warning instead of aborting the transformation.
+    ///
+    /// Returns the number of changes the pass made. Statistics accumulate over re-runs,
+    /// and warnings already reported by an earlier run are not repeated.
+    fn run_builtin_pass(
+        &mut self,
+        pass: BuiltinPass,
+        ast: &mut Program,
+        stats: &mut TransformationStats,
+        identifier_mapping: &mut BTreeMap<String, String>,
+        property_mapping: &mut BTreeMap<String, BTreeMap<String, String>>,
+        warnings: &mut Vec<Diagnostic>,
+    ) -> TransformResult<u32> {
+        // Property and function minification do not create rollback checkpoints yet
+        let checkpointed = self.config.enable_rollback
+            && !matches!(pass, BuiltinPass::PropertyMinification | BuiltinPass::FunctionMinification);
+        if checkpointed {
+            self.rollback_manager.create_checkpoint(
+                ast,
+                pass.name(),
+                &format!("Before {} transformation", pass.display_name().to_lowercase()),
+            );
+        }
+
+        if let Some(recorder) = self.explain.as_mut() {
+            recorder.begin(ast);
+        }
+        let guard = self.directives.begin(ast);
+        let pass_start = crate::Instant::now();
+        let outcome = self.apply_builtin_pass(pass, ast, stats);
+        stats.record_pass(pass.name(), pass_start);
+
+        let rollback_reason = match &outcome {
+            Err(e) if checkpointed => Some(e.to_string()),
+            Err(_) => None,
+            Ok(_) if !checkpointed => None,
+            Ok(PassOutcome { changes: 0, .. }) => None,
+            Ok(_) => self.rollback_manager.checkpoint(pass.name()).and_then(|checkpoint| {
+                self.rollback_manager.check_transformation(&checkpoint.original_ast, ast, &self.analysis_result)
+            }),
+        };
+
+        if let Some(reason) = rollback_reason {
+            tracing::debug!("⚠️ Rolling back {}: {}", pass.display_name(), reason);
+            let span = self
+                .rollback_manager
+                .checkpoint(pass.name())
+                .and_then(|checkpoint| self.changed_statement_span(&checkpoint.original_ast, ast));
+            *ast = self.rollback_manager.rollback_to_pass(pass.name())?;
+            stats.rollbacks_performed += 1;
+            warnings.push(
+                Diagnostic::warning(format!("Rolled back {}: {}", pass.name(), reason))
+                    .with_pass(pass.name())
+                    .with_span(span),
+            );
+            return Ok(0);
+        }
+        let mut outcome = outcome?;
+        if let Some(guard) = guard {
+            match self.directives.end(Some(pass), guard, ast) {
+                Ok(true) => {}
+                Ok(false) => outcome.changes = 0,
+                Err(reason) => {
+                    warnings.push(Diagnostic::warning(format!("Skipped {}: {}", pass.name(), reason)).with_pass(pass.name()));
+                    outcome.changes = 0;
+                    outcome.identifier_mapping.clear();
+                    outcome.property_mapping.clear();
+                }
+            }
+        }
+        if let Some(recorder) = self.explain.as_mut() {
+            recorder.end(pass.name(), ast);
+        }
+
+        stats.record_changes(pass, outcome.changes);
+        identifier_mapping.extend(outcome.identifier_mapping);
+        for (object, renames) in outcome.property_mapping {
+            property_mapping.entry(object).or_default().extend(renames);
+        }
+        let notes = outcome.notes.into_iter().map(Diagnostic::note);
+        for warning in outcome.warnings.into_iter().map(Diagnostic::warning).chain(notes) {
+            let warning = warning.with_pass(pass.name());
+            if !warnings.contains(&warning) {
+                warnings.push(warning);
+            }
+        }
+        let before = self.rollback_manager.checkpoint(pass.name()).map(|checkpoint| &checkpoint.original_ast);
+        track_statement_locations(&mut self.statement_locations, before, ast);
+
+        if checkpointed {
+            self.rollback_manager.release_checkpoint(pass.name());
+        }
+        Ok(outcome.changes)
+    }
+
+    /// Runs the implementation of one built-in pass on `ast`
+    fn apply_builtin_pass(
+        &self,
+        pass: BuiltinPass,
+        ast: &mut Program,
+        stats: &mut TransformationStats,
+    ) -> TransformResult<PassOutcome> {
+        let outcome = match pass {
+            BuiltinPass::IdentifierRenaming => {
+                let rename_result = identifier_renaming::rename_identifiers(
+                    ast,
+                    &self.analysis_result.symbol_table,
+                    &self.config
+                )?;
+                PassOutcome {
+                    changes: rename_result.renamed_count,
+                    notes: rename_result.warnings,
+                    identifier_mapping: rename_result.mapping,
+                    ..PassOutcome::default()
+                }
+            }
+            BuiltinPass::DeadCodeElimination => {
+                let dce_result = dead_code_elimination::eliminate_dead_code(
+                    ast,
+                    &self.analysis_result,
+                    &self.config
+                )?;
+                PassOutcome::from_notes(dce_result.removed_count, dce_result.warnings)
+            }
+            BuiltinPass::ExpressionSimplification => {
+                let simplify_result = expression_simplification::simplify_expressions(
+                    ast,
+                    &self.analysis_result.symbol_table,
+                    &self.config
+                )?;
+                stats.rollbacks_performed += simplify_result.rollbacks;
+                PassOutcome::from_notes(simplify_result.simplified_count, simplify_result.warnings)
+            }
+            BuiltinPass::CollapseVariables => {
+                let collapse_result = collapse_vars::collapse_variables(ast, &self.analysis_result, &self.config)?;
+                PassOutcome::new(collapse_result.collapsed_count, collapse_result.warnings)
+            }
+            BuiltinPass::InlineCaptures => {
+                let inline_result = inline_captures::inline_captures(ast, &self.analysis_result, &self.config)?;
+                PassOutcome::new(inline_result.inlined_count, inline_result.warnings)
+            }
+            BuiltinPass::IfReturn => {
+                let if_return_result = if_return::eliminate_if_returns(ast, &self.analysis_result, &self.config)?;
+                PassOutcome::new(if_return_result.simplified_count, if_return_result.warnings)
+            }
+            BuiltinPass::Cleanup => {
+                let cleanup_result = cleanup::cleanup_statements(ast, &self.analysis_result, &self.config)?;
+                PassOutcome::new(cleanup_result.removed_count, cleanup_result.warnings)
+            }
+            BuiltinPass::HoistFunctions => {
+                let hoist_result = hoisting::hoist_functions(ast, &self.analysis_result, &self.config)?;
+                PassOutcome::new(hoist_result.hoisted_count, hoist_result.warnings)
+            }
+            BuiltinPass::HoistVariables => {
+                let hoist_result = hoisting::hoist_variables(ast, &self.analysis_result, &self.config)?;
+                PassOutcome::new(hoist_result.hoisted_count, hoist_result.warnings)
+            }
+            BuiltinPass::PropertyMinification => {
+                let prop_result = property_minification::minify_properties(
+                    ast,
+                    &self.analysis_result,
+                    &self.config
+                )?;
+                PassOutcome {
+                    changes: prop_result.renamed_count + prop_result.removed_count + prop_result.inlined_count,
+                    warnings: prop_result.warnings,
+                    property_mapping: prop_result.renames,
+                    ..PassOutcome::default()
+                }
+            }
+            BuiltinPass::FunctionMinification => {
+                let func_result = function_minification::minify_functions(
+                    ast,
+                    &self.analysis_result,
+                    &self.config
+                )?;
+                PassOutcome::from_notes(func_result.inlined_count, func_result.warnings)
+            }
+            BuiltinPass::LoopInvariants => {
+                let hoist_result = loop_invariants::hoist_loop_invariants(ast, &self.analysis_result, &self.config)?;
+                PassOutcome::new(hoist_result.hoisted_count, hoist_result.warnings)
+            }
+        };
+        Ok(outcome)
+    }
+
+    /// Runs the plugins registered at `position`, in registration order
+    fn run_plugins(
+        &mut self,
+        position: PluginPosition,
+        ast: &mut Program,
+        stats: &mut TransformationStats,
+        warnings: &mut Vec<Diagnostic>,
+    ) -> TransformResult<()> {
+        for registered in self.plugins.iter_mut().filter(|registered| registered.position == position) {
+            let name = registered.plugin.name().to_string();
+            tracing::debug!("🔌 Plugin: {}", name);
+
+            let mut ctx = PluginContext::new(&self.analysis_result, &self.config);
+            if let Some(recorder) = self.explain.as_mut() {
+                recorder.begin(ast);
+            }
+            let guard = self.directives.begin(ast);
+            let pass_start = crate::Instant::now();
+            plugin::run_plugin(registered.plugin.as_mut(), ast, &mut ctx).map_err(|e| match e {
+                TransformError::PluginError { .. } => e,
+                other => TransformError::PluginError { plugin: name.clone(), message: other.to_string() },
+            })?;
+            stats.record_pass(&name, pass_start);
+            if let Some(guard) = guard {
+                let _ = self.directives.end(None, guard, ast);
+            }
+            if let Some(recorder) = self.explain.as_mut() {
+                recorder.end(&name, ast);
+            }
+
+            stats.plugin_changes += ctx.changes();
+            track_statement_locations(&mut self.statement_locations, None, ast);
+            warnings.extend(ctx.into_warnings().into_iter().map(|warning| Diagnostic::warning(warning).with_pass(&name)));
+        }
+        Ok(())
+    }
+
+    /// Original source span of the first top-level statement of `before` that differs in
+    /// `after`, while statement locations are known
+    fn changed_statement_span(&self, before: &Program, after: &Program) -> Option<SourceSpan> {
+        let index = before
+            .body
+            .iter()
+            .zip(&after.body)
+            .position(|(before, after)| !same_statement(before, after))
+            .unwrap_or(before.body.len().min(after.body.len()));
+        self.statement_locations.get(index).cloned().flatten()
+    }
+
     /// Counts the number of enabled transformation passes
     fn count_enabled_passes(&self) -> u32 {
-        let mut count = 0;
-        if self.config.enable_identifier_renaming { count += 1; }
-        if self.config.enable_dead_code_elimination { count += 1; }
-        if self.config.enable_expression_simplification { count += 1; }
-        if self.config.enable_property_minification { count += 1; }
-        if self.config.enable_function_minification { count += 1; }
-        count
-    }
-
-    /// Prints a summary of transformation statistics
-    fn print_transformation_summary(&self, stats: &TransformationStats) {
-        println!("📊 Transformation Summary:");
-        println!("   🏷️  Identifiers renamed: {}", stats.identifiers_renamed);
-        println!("   🗑️  Dead statements removed: {}", stats.dead_statements_removed);
-        println!("   🔧 Expressions simplified: {}", stats.expressions_simplified);
-        println!("   🏠 Properties renamed: {}", stats.properties_renamed);
-        println!("   📎 Functions inlined: {}", stats.functions_inlined);
+        BuiltinPass::ALL.iter().filter(|pass| pass.is_enabled(&self.config)).count() as u32
+    }
+
+    /// Logs a summary of transformation statistics
+    fn log_transformation_summary(&self, stats: &TransformationStats) {
+        tracing::debug!("📊 Transformation Summary:");
+        tracing::debug!("   🏷️  Identifiers renamed: {}", stats.identifiers_renamed);
+        tracing::debug!("   🗑️  Dead statements removed: {}", stats.dead_statements_removed);
+        tracing::debug!("   🔧 Expressions simplified: {}", stats.expressions_simplified);
+        tracing::debug!("   🧲 Variables collapsed: {}", stats.variables_collapsed);
+        tracing::debug!("   📌 Captures inlined: {}", stats.captures_inlined);
+        tracing::debug!("   🔀 If/return simplified: {}", stats.if_returns_simplified);
+        tracing::debug!("   🧹 Statements cleaned up: {}", stats.statements_cleaned);
+        if stats.functions_hoisted + stats.variables_hoisted > 0 {
+            tracing::debug!("   ⬆️  Declarations hoisted: {}", stats.functions_hoisted + stats.variables_hoisted);
+        }
+        tracing::debug!("   🏠 Properties renamed: {}", stats.properties_renamed);
+        tracing::debug!("   📎 Functions inlined: {}", stats.functions_inlined);
+        tracing::debug!("   🔁 Loop invariants hoisted: {}", stats.invariants_hoisted);
         
         if stats.rollbacks_performed > 0 {
-            println!("   ↩️  Rollbacks performed: {}", stats.rollbacks_performed);
+            tracing::debug!("   ↩️  Rollbacks performed: {}", stats.rollbacks_performed);
+        }
+        if stats.plugin_changes > 0 {
+            tracing::debug!("   🔌 Plugin changes: {}", stats.plugin_changes);
+        }
+        if stats.pass_iterations > 1 {
+            tracing::debug!("   🔁 Pipeline rounds: {}", stats.pass_iterations);
+        }
+        if stats.syntax_lowered > 0 {
+            tracing::debug!("   ⬇️  Syntax lowered: {}", stats.syntax_lowered);
+        }
+        if stats.strings_deduplicated > 0 {
+            tracing::debug!("   🔤 Strings deduplicated: {}", stats.strings_deduplicated);
         }
         
-        println!("   ⏱️  Total time: {}ms", stats.transformation_time_ms);
+        tracing::debug!("   ⏱️  Total time: {}ms", stats.transformation_time_ms);
     }
 }
 
+/// Updates `locations` after a pass turned `before` into `after`
+///
+/// Statements in the unchanged prefix and suffix keep their spans and the changed region
+/// between them loses its location. Without `before`, locations survive only when the
+/// number of statements is unchanged.
+fn track_statement_locations(locations: &mut Vec<Option<SourceSpan>>, before: Option<&Program>, after: &Program) {
+    let Some(before) = before else {
+        if locations.len() != after.body.len() {
+            *locations = vec![None; after.body.len()];
+        }
+        return;
+    };
+    if before.body.len() == after.body.len() {
+        return;
+    }
+
+    let prefix = before.body.iter().zip(&after.body).take_while(|(before, after)| same_statement(before, after)).count();
+    let suffix = before.body[prefix..]
+        .iter()
+        .rev()
+        .zip(after.body[prefix..].iter().rev())
+        .take_while(|(before, after)| same_statement(before, after))
+        .count();
+    let inserted = after.body.len() - prefix - suffix;
+    locations.splice(prefix..locations.len() - suffix, std::iter::repeat_n(None, inserted));
+}
+
+/// Whether two statements are structurally identical
+fn same_statement(before: &Statement, after: &Statement) -> bool {
+    matches!((serde_json::to_value(before), serde_json::to_value(after)), (Ok(before), Ok(after)) if before == after)
+}
+
 /// Convenience function to transform an AST with default configuration
 ///
 /// # Arguments
@@ -393,18 +1223,54 @@ impl Transformer {
 ///
 /// ```rust,no_run
 /// use rjs_compiler::transformer::transform_ast;
-/// use rjs_compiler::parser::ast_types::Program;
-/// use rjs_compiler::analyzer::SemanticAnalysis;
+/// use rjs_compiler::parser::{parse_js, ParserConfig};
+/// use rjs_compiler::analyzer::{analyze_ast, AnalyzerConfig};
 /// 
-/// let ast = Program { /* ... */ };
-/// let analysis_result = SemanticAnalysis::default();
+/// let ast = parse_js("let x = 1;", "example.js", &ParserConfig::default()).ast.unwrap();
+/// let analysis_result = analyze_ast(&ast, &AnalyzerConfig::default())?;
 /// let result = transform_ast(ast, analysis_result)?;
+/// # Ok::<(), Box<dyn std::error::Error>>(())
 /// ```
 pub fn transform_ast(
     ast: Program, 
     analysis_result: SemanticAnalysis
 ) -> TransformResult<TransformationResult> {
-    let config = TransformerConfig::default();
+    transform_ast_with_config(ast, analysis_result, TransformerConfig::default())
+}
+
+/// Convenience function to transform an AST with the given configuration
+///
+/// # Arguments
+///
+/// * `ast` - The abstract syntax tree to transform
+/// * `analysis_result` - Results from the semantic analysis phase
+/// * `config` - Configuration settings for transformation passes
+///
+/// # Returns
+///
+/// Returns a `TransformResult<TransformationResult>` containing the transformed AST
+/// and transformation statistics.
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// use rjs_compiler::transformer::{transform_ast_with_config, TransformerConfig};
+/// use rjs_compiler::parser::{parse_js, ParserConfig};
+/// use rjs_compiler::analyzer::{analyze_ast, AnalyzerConfig};
+/// 
+/// let ast = parse_js("let x = 1;", "example.js", &ParserConfig::default()).ast.unwrap();
+/// let analysis_result = analyze_ast(&ast, &AnalyzerConfig::default())?;
+/// let config = TransformerConfig::default()
+///     .with_identifier_renaming(false)
+///     .with_property_minification(false);
+/// let result = transform_ast_with_config(ast, analysis_result, config)?;
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
+pub fn transform_ast_with_config(
+    ast: Program,
+    analysis_result: SemanticAnalysis,
+    config: TransformerConfig,
+) -> TransformResult<TransformationResult> {
     let mut transformer = Transformer::new(config, analysis_result);
     transformer.transform(ast)
 }
\ No newline at end of file
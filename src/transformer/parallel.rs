@@ -0,0 +1,134 @@
+//! # Parallel Transformation Module
+//!
+//! Splits the top-level statements of a program into contiguous chunks and runs a
+//! per-statement transformation on worker threads, then merges the per-chunk results
+//! back in source order.
+//!
+//! ## Partitioning
+//!
+//! Top-level statements (function declarations, classes, module-level code) are
+//! independent units for passes whose decisions depend only on the statement itself
+//! and read-only whole-program data such as the symbol table. Each worker receives a
+//! disjoint `&mut [Statement]`, so no synchronisation is required while transforming
+//! and the output is identical to a sequential run.
+//!
+//! Small programs are transformed on the calling thread, since spawning workers costs
+//! more than it saves below [`MIN_STATEMENTS_PER_WORKER`] statements per worker.
+
+use std::num::NonZeroUsize;
+use std::thread;
+
+use crate::parser::ast_types::Statement;
+
+/// Minimum number of top-level statements a worker must receive to be worth spawning
+pub const MIN_STATEMENTS_PER_WORKER: usize = 64;
+
+/// Resolves the configured worker count, where `0` means one worker per available core
+///
+/// Always `1` on wasm32, which has no threads.
+pub fn resolve_worker_threads(configured: usize) -> usize {
+    if cfg!(target_arch = "wasm32") {
+        return 1;
+    }
+    if configured > 0 {
+        return configured;
+    }
+
+    thread::available_parallelism()
+        .map(NonZeroUsize::get)
+        .unwrap_or(1)
+}
+
+/// Applies `transform` to contiguous chunks of `statements`, in parallel when worthwhile
+///
+/// # Arguments
+///
+/// * `statements` - Top-level statements to transform (modified in place)
+/// * `worker_threads` - Requested worker count (`0` for automatic)
+/// * `transform` - Transformation applied to each chunk, returning its partial result
+///
+/// # Returns
+///
+/// Returns the partial results in source order, one per chunk
+pub fn transform_chunks<T, F>(statements: &mut [Statement], worker_threads: usize, transform: F) -> Vec<T>
+where
+    T: Send,
+    F: Fn(&mut [Statement]) -> T + Sync,
+{
+    let workers = resolve_worker_threads(worker_threads)
+        .min(statements.len() / MIN_STATEMENTS_PER_WORKER)
+        .max(1);
+
+    if workers == 1 {
+        return vec![transform(statements)];
+    }
+
+    let chunk_size = statements.len().div_ceil(workers);
+    let transform = &transform;
+
+    thread::scope(|scope| {
+        let handles: Vec<_> = statements
+            .chunks_mut(chunk_size)
+            .map(|chunk| scope.spawn(move || transform(chunk)))
+            .collect();
+
+        handles
+            .into_iter()
+            .map(|handle| match handle.join() {
+                Ok(result) => result,
+                Err(panic) => std::panic::resume_unwind(panic),
+            })
+            .collect()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::ast_types::{Expression, Identifier};
+
+    fn statements(count: usize) -> Vec<Statement> {
+        (0..count)
+            .map(|i| Statement::ExpressionStatement {
+                expression: Expression::Identifier(Identifier { name: format!("s{}", i), span: None }),
+                span: None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_small_programs_run_on_calling_thread() {
+        let mut body = statements(MIN_STATEMENTS_PER_WORKER);
+        let caller = thread::current().id();
+
+        let results = transform_chunks(&mut body, 8, |chunk| (chunk.len(), thread::current().id()));
+
+        assert_eq!(results, vec![(MIN_STATEMENTS_PER_WORKER, caller)]);
+    }
+
+    #[test]
+    fn test_chunks_cover_all_statements_in_order() {
+        let mut body = statements(MIN_STATEMENTS_PER_WORKER * 4 + 3);
+
+        let results = transform_chunks(&mut body, 4, |chunk| {
+            chunk
+                .iter()
+                .map(|statement| match statement {
+                    Statement::ExpressionStatement { expression: Expression::Identifier(id), .. } => id.name.clone(),
+                    _ => unreachable!(),
+                })
+                .collect::<Vec<_>>()
+        });
+
+        assert_eq!(results.len(), 4);
+        let names: Vec<String> = results.into_iter().flatten().collect();
+        let expected: Vec<String> = (0..body.len()).map(|i| format!("s{}", i)).collect();
+        assert_eq!(names, expected);
+    }
+
+    #[test]
+    fn test_resolve_worker_threads() {
+        assert_eq!(resolve_worker_threads(3), 3);
+        assert!(resolve_worker_threads(0) >= 1);
+    }
+}
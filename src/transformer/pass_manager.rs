@@ -0,0 +1,205 @@
+//! # Pass Manager Module
+//!
+//! Schedules the built-in passes for [`Transformer::transform`](super::Transformer::transform).
+//!
+//! ## Ordering
+//!
+//! Passes run in the order given by `TransformerConfig::pass_order`. Passes missing from
+//! the list are appended in their default order, listing a pass twice is an error, and
+//! every enabled pass must come after the enabled passes it declares as
+//! [`dependencies`](BuiltinPass::dependencies).
+//!
+//! ## Fixpoint Iteration
+//!
+//! A pass that changes the program marks the passes it
+//! [`invalidates`](BuiltinPass::invalidates) as dirty. Dirty passes later in the order
+//! run in the same round; the rest run in another round over the pipeline, until no pass
+//! is dirty or `TransformerConfig::max_pass_iterations` rounds have run. Folding
+//! expressions exposes dead branches and removing code exposes more folds, so expression
+//! simplification and dead code elimination are re-run until neither changes anything.
+
+use std::collections::HashSet;
+
+use crate::transformer::{BuiltinPass, TransformError, TransformResult, TransformerConfig};
+
+/// Execution state of the built-in passes during one transformation
+#[derive(Debug)]
+pub struct PassManager {
+    /// Resolved pass order
+    order: Vec<BuiltinPass>,
+    /// Enabled passes that have to run (again)
+    dirty: HashSet<BuiltinPass>,
+    /// Maximum number of rounds
+    max_iterations: u32,
+    /// Rounds started so far
+    iterations: u32,
+}
+
+impl PassManager {
+    /// Resolves the pass order from `config` and marks every enabled pass as pending
+    ///
+    /// # Errors
+    ///
+    /// Returns `TransformError::InvalidPassOrder` if a pass is listed twice or an enabled
+    /// pass is ordered before one of its enabled dependencies
+    pub fn new(config: &TransformerConfig) -> TransformResult<Self> {
+        let order = resolve_order(&config.pass_order, config)?;
+        let dirty = order.iter().copied().filter(|pass| pass.is_enabled(config)).collect();
+
+        Ok(Self {
+            order,
+            dirty,
+            max_iterations: config.max_pass_iterations.max(1),
+            iterations: 0,
+        })
+    }
+
+    /// All built-in passes in execution order, including disabled ones
+    pub fn order(&self) -> &[BuiltinPass] {
+        &self.order
+    }
+
+    /// Starts the next round, returning its number (starting at 1)
+    ///
+    /// The first round always runs so that plugins positioned around disabled passes still
+    /// execute. Later rounds only start while some pass is dirty and the iteration limit
+    /// has not been reached.
+    pub fn begin_iteration(&mut self) -> Option<u32> {
+        if self.iterations > 0 && (self.dirty.is_empty() || self.iterations >= self.max_iterations) {
+            return None;
+        }
+        self.iterations += 1;
+        Some(self.iterations)
+    }
+
+    /// Returns whether `pass` has to run now, clearing its dirty flag
+    pub fn take(&mut self, pass: BuiltinPass) -> bool {
+        self.dirty.remove(&pass)
+    }
+
+    /// Records that `pass` made `changes` changes, marking the passes it invalidates
+    ///
+    /// Only passes that were enabled when the manager was created are marked.
+    pub fn record_changes(&mut self, pass: BuiltinPass, changes: u32, config: &TransformerConfig) {
+        if changes > 0 {
+            self.dirty.extend(pass.invalidates().iter().filter(|invalidated| invalidated.is_enabled(config)));
+        }
+    }
+
+    /// Number of rounds started so far
+    pub fn iterations(&self) -> u32 {
+        self.iterations
+    }
+}
+
+/// Completes `requested` with the missing passes and checks it against pass dependencies
+fn resolve_order(requested: &[BuiltinPass], config: &TransformerConfig) -> TransformResult<Vec<BuiltinPass>> {
+    let mut order: Vec<BuiltinPass> = Vec::with_capacity(BuiltinPass::ALL.len());
+    for &pass in requested {
+        if order.contains(&pass) {
+            return Err(TransformError::InvalidPassOrder(format!("{} is listed more than once", pass.name())));
+        }
+        order.push(pass);
+    }
+    for pass in BuiltinPass::ALL {
+        if !order.contains(&pass) {
+            order.push(pass);
+        }
+    }
+
+    for (index, pass) in order.iter().enumerate() {
+        if !pass.is_enabled(config) {
+            continue;
+        }
+        let premature = order[index + 1..]
+            .iter()
+            .find(|later| later.is_enabled(config) && pass.dependencies().contains(later));
+        if let Some(dependency) = premature {
+            return Err(TransformError::InvalidPassOrder(format!(
+                "{} must run after {}",
+                pass.name(),
+                dependency.name()
+            )));
+        }
+    }
+
+    Ok(order)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config_with_order(pass_order: Vec<BuiltinPass>) -> TransformerConfig {
+        TransformerConfig { pass_order, ..TransformerConfig::default() }
+    }
+
+    #[test]
+    fn test_missing_passes_are_appended_in_default_order() {
+        let config = config_with_order(vec![BuiltinPass::ExpressionSimplification]);
+        let manager = PassManager::new(&config).unwrap();
+
+        assert_eq!(manager.order(), &[
+            BuiltinPass::ExpressionSimplification,
+            BuiltinPass::IdentifierRenaming,
+            BuiltinPass::DeadCodeElimination,
+            BuiltinPass::CollapseVariables,
+            BuiltinPass::InlineCaptures,
+            BuiltinPass::IfReturn,
+            BuiltinPass::Cleanup,
+            BuiltinPass::HoistFunctions,
+            BuiltinPass::HoistVariables,
+            BuiltinPass::PropertyMinification,
+            BuiltinPass::FunctionMinification,
+            BuiltinPass::LoopInvariants,
+        ]);
+    }
+
+    #[test]
+    fn test_invalid_orders_are_rejected() {
+        let duplicate = config_with_order(vec![BuiltinPass::DeadCodeElimination, BuiltinPass::DeadCodeElimination]);
+        assert!(matches!(PassManager::new(&duplicate), Err(TransformError::InvalidPassOrder(_))));
+
+        let early = config_with_order(vec![BuiltinPass::FunctionMinification]);
+        let error = PassManager::new(&early).unwrap_err();
+        assert!(error.to_string().contains("function_minification must run after dead_code_elimination"));
+
+        // Dependencies on disabled passes do not constrain the order
+        let disabled = TransformerConfig {
+            enable_dead_code_elimination: false,
+            enable_expression_simplification: false,
+            ..early
+        };
+        assert!(PassManager::new(&disabled).is_ok());
+    }
+
+    #[test]
+    fn test_changes_mark_invalidated_passes_until_fixpoint() {
+        let config = TransformerConfig::default();
+        let mut manager = PassManager::new(&config).unwrap();
+
+        assert_eq!(manager.begin_iteration(), Some(1));
+        for pass in BuiltinPass::ALL {
+            assert_eq!(manager.take(pass), pass.is_enabled(&config));
+        }
+        manager.record_changes(BuiltinPass::ExpressionSimplification, 2, &config);
+
+        assert_eq!(manager.begin_iteration(), Some(2));
+        assert!(manager.take(BuiltinPass::DeadCodeElimination));
+        assert!(!manager.take(BuiltinPass::IdentifierRenaming));
+        manager.record_changes(BuiltinPass::DeadCodeElimination, 0, &config);
+
+        assert_eq!(manager.begin_iteration(), None);
+        assert_eq!(manager.iterations(), 2);
+    }
+
+    #[test]
+    fn test_iteration_limit() {
+        let config = TransformerConfig { max_pass_iterations: 1, ..TransformerConfig::default() };
+        let mut manager = PassManager::new(&config).unwrap();
+
+        assert_eq!(manager.begin_iteration(), Some(1));
+        manager.record_changes(BuiltinPass::ExpressionSimplification, 1, &config);
+        assert_eq!(manager.begin_iteration(), None);
+    }
+}
@@ -0,0 +1,219 @@
+//! # Transform Plugin Module
+//!
+//! Public extension point for custom transformation passes. A plugin implements
+//! [`TransformPlugin`], receiving enter/exit callbacks for every statement and
+//! expression of the AST together with the semantic analysis, and is registered on a
+//! [`Transformer`](crate::transformer::Transformer) at a position relative to the
+//! built-in passes.
+//!
+//! ## Example
+//!
+//! ```rust,no_run
+//! use rjs_compiler::parser::ast_types::{Expression, Identifier};
+//! use rjs_compiler::transformer::plugin::{PluginContext, PluginPosition, TransformPlugin};
+//! use rjs_compiler::transformer::{BuiltinPass, TransformResult, Transformer};
+//!
+//! /// Replaces `__DEV__` with `false` so dead code elimination can drop debug branches
+//! struct DefineDev;
+//!
+//! impl TransformPlugin for DefineDev {
+//!     fn name(&self) -> &str {
+//!         "define-dev"
+//!     }
+//!
+//!     fn enter_expression(&mut self, expr: &mut Expression, ctx: &mut PluginContext) -> TransformResult<()> {
+//!         if matches!(expr, Expression::Identifier(Identifier { name, .. }) if name == "__DEV__") {
+//!             *expr = Expression::Identifier(Identifier { name: "false".to_string(), span: None });
+//!             ctx.record_change();
+//!         }
+//!         Ok(())
+//!     }
+//! }
+//!
+//! # fn register(transformer: &mut Transformer) {
+//! transformer.register_plugin(Box::new(DefineDev), PluginPosition::Before(BuiltinPass::DeadCodeElimination));
+//! # }
+//! ```
+//!
+//! ## Traversal Order
+//!
+//! Nodes are visited depth-first in source order. `enter_*` runs before a node's
+//! children and `exit_*` after them; replacing the node in `enter_*` means the
+//! replacement's children are visited. Function and class bodies, default values in
+//! patterns and class field initializers are all visited.
+
+use crate::analyzer::SemanticAnalysis;
+use crate::parser::ast_types::{Expression, Program, Statement};
+use crate::parser::visit::{self, VisitMut};
+use crate::transformer::{BuiltinPass, TransformError, TransformResult, TransformerConfig};
+
+/// A custom transformation pass
+///
+/// Every callback has a no-op default, so a plugin only implements the nodes it
+/// rewrites. Returning an error aborts the transformation with that error.
+pub trait TransformPlugin: Send {
+    /// Name used in pass timings, verbose output and error messages
+    fn name(&self) -> &str;
+
+    /// Called once before the program is traversed
+    fn enter_program(&mut self, _program: &mut Program, _ctx: &mut PluginContext) -> TransformResult<()> {
+        Ok(())
+    }
+
+    /// Called once after the program is traversed
+    fn exit_program(&mut self, _program: &mut Program, _ctx: &mut PluginContext) -> TransformResult<()> {
+        Ok(())
+    }
+
+    /// Called before a statement's children are visited
+    fn enter_statement(&mut self, _stmt: &mut Statement, _ctx: &mut PluginContext) -> TransformResult<()> {
+        Ok(())
+    }
+
+    /// Called after a statement's children are visited
+    fn exit_statement(&mut self, _stmt: &mut Statement, _ctx: &mut PluginContext) -> TransformResult<()> {
+        Ok(())
+    }
+
+    /// Called before an expression's children are visited
+    fn enter_expression(&mut self, _expr: &mut Expression, _ctx: &mut PluginContext) -> TransformResult<()> {
+        Ok(())
+    }
+
+    /// Called after an expression's children are visited
+    fn exit_expression(&mut self, _expr: &mut Expression, _ctx: &mut PluginContext) -> TransformResult<()> {
+        Ok(())
+    }
+}
+
+/// Where a plugin runs relative to the built-in passes
+///
+/// The position applies whether or not the referenced pass is enabled.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum PluginPosition {
+    /// Immediately before the given pass
+    Before(BuiltinPass),
+    /// Immediately after the given pass
+    After(BuiltinPass),
+}
+
+impl std::str::FromStr for PluginPosition {
+    type Err = String;
+
+    /// Parses `before:<pass>` or `after:<pass>`, e.g. `before:dead_code_elimination`
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let (placement, pass_name) = value
+            .split_once(':')
+            .ok_or_else(|| format!("invalid plugin position '{}' (expected before:<pass> or after:<pass>)", value))?;
+        let pass = BuiltinPass::from_name(pass_name)
+            .ok_or_else(|| format!("unknown pass '{}' in plugin position", pass_name))?;
+
+        match placement {
+            "before" => Ok(PluginPosition::Before(pass)),
+            "after" => Ok(PluginPosition::After(pass)),
+            _ => Err(format!("invalid plugin position '{}' (expected before:<pass> or after:<pass>)", value)),
+        }
+    }
+}
+
+/// Read-only pipeline state and result collection available to plugins
+#[derive(Debug)]
+pub struct PluginContext<'a> {
+    /// Semantic analysis of the program being transformed
+    pub analysis: &'a SemanticAnalysis,
+    /// Transformer configuration
+    pub config: &'a TransformerConfig,
+    /// Warnings reported by the plugin
+    warnings: Vec<String>,
+    /// Number of changes the plugin reported
+    changes: u32,
+}
+
+impl<'a> PluginContext<'a> {
+    /// Creates a context for one plugin run
+    pub fn new(analysis: &'a SemanticAnalysis, config: &'a TransformerConfig) -> Self {
+        Self {
+            analysis,
+            config,
+            warnings: Vec::new(),
+            changes: 0,
+        }
+    }
+
+    /// Reports a warning, surfaced in `TransformationResult::warnings`
+    pub fn warn(&mut self, message: impl Into<String>) {
+        self.warnings.push(message.into());
+    }
+
+    /// Records that the plugin modified the AST
+    pub fn record_change(&mut self) {
+        self.changes += 1;
+    }
+
+    /// Records several modifications at once
+    pub fn record_changes(&mut self, count: u32) {
+        self.changes += count;
+    }
+
+    /// Number of changes recorded so far
+    pub fn changes(&self) -> u32 {
+        self.changes
+    }
+
+    /// Consumes the context, returning its warnings
+    pub fn into_warnings(self) -> Vec<String> {
+        self.warnings
+    }
+}
+
+/// A plugin together with its pipeline position
+pub(crate) struct RegisteredPlugin {
+    pub(crate) plugin: Box<dyn TransformPlugin>,
+    pub(crate) position: PluginPosition,
+}
+
+impl std::fmt::Debug for RegisteredPlugin {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("RegisteredPlugin")
+            .field("name", &self.plugin.name())
+            .field("position", &self.position)
+            .finish()
+    }
+}
+
+/// Runs a plugin over the whole program
+///
+/// # Errors
+///
+/// Returns the first error reported by the plugin
+pub fn run_plugin(plugin: &mut dyn TransformPlugin, program: &mut Program, ctx: &mut PluginContext) -> TransformResult<()> {
+    plugin.enter_program(program, ctx)?;
+    visit::walk_program(&mut PluginVisitor { plugin, ctx }, program)?;
+    plugin.exit_program(program, ctx)
+}
+
+/// Runs a plugin's callbacks on the owned AST traversal
+struct PluginVisitor<'p, 'c, 'a> {
+    plugin: &'p mut dyn TransformPlugin,
+    ctx:    &'c mut PluginContext<'a>,
+}
+
+impl VisitMut for PluginVisitor<'_, '_, '_> {
+    type Error = TransformError;
+
+    fn enter_statement(&mut self, stmt: &mut Statement) -> TransformResult<()> {
+        self.plugin.enter_statement(stmt, self.ctx)
+    }
+
+    fn exit_statement(&mut self, stmt: &mut Statement) -> TransformResult<()> {
+        self.plugin.exit_statement(stmt, self.ctx)
+    }
+
+    fn enter_expression(&mut self, expr: &mut Expression) -> TransformResult<()> {
+        self.plugin.enter_expression(expr, self.ctx)
+    }
+
+    fn exit_expression(&mut self, expr: &mut Expression) -> TransformResult<()> {
+        self.plugin.exit_expression(expr, self.ctx)
+    }
+}
@@ -1,25 +1,469 @@
 //! # Property Minification Module
 //!
 //! Pass 4: Property Minification - Safe property renaming
+//!
+//! Only properties of object literals that never escape their variable are touched
+//! (see [`crate::analyzer::escape`]). Their own properties are renamed to short names
+//! that don't collide with the inherited properties the code reads (`o.toString`), and
+//! properties that are never read are dropped from the literal when their value has
+//! no side effects.
+//!
+//! ## Debug Names
+//!
+//! With `TransformerConfig::debug_property_names` each renamed property becomes
+//! `_$name$_` instead of a short name (`width` → `_$width$_`). Code that reaches a
+//! renamed property some way the analysis missed then fails on an `undefined` whose
+//! name shows up in the output, and searching for `_$` lists every rename to check.
+//!
+//! ## Enum-like Objects
+//!
+//! A local `const` object whose properties are all primitive literals and never
+//! assigned, updated or deleted (`const Color = { Red: 0, Green: 1 }`) works like an
+//! enum. Its reads are replaced by the property values, and the declaration is removed
+//! since nothing uses it afterwards. This only happens when the inlined values take
+//! less space than the declaration and the accesses they replace. Enums are inlined
+//! even when property renaming is disabled, since no property name changes.
+
+use std::collections::{BTreeMap, HashMap, HashSet};
 
+use crate::analyzer::escape::{static_key_name, static_property_name};
 use crate::analyzer::SemanticAnalysis;
-use crate::parser::ast_types::Program;
+use crate::parser::ast_types::{
+    Expression, Identifier, Literal, ObjectProperty, Pattern, Program, PropertyKey, Statement, UnaryOperator,
+    VariableDeclarationKind,
+};
+use crate::transformer::identifier_renaming::{NameContext, NameGenerator};
+use crate::transformer::plugin::{self, PluginContext, TransformPlugin};
 use crate::transformer::{TransformResult, TransformerConfig};
 
 #[derive(Debug, Clone)]
 pub struct PropertyMinificationResult {
     pub renamed_count: u32,
+    /// Number of unused properties removed from object literals
+    pub removed_count: u32,
+    /// Number of property reads replaced by the value of an enum-like object
+    pub inlined_count: u32,
+    /// Renamed properties, original name to new name, by the variable of their object
+    pub renames: BTreeMap<String, BTreeMap<String, String>>,
     pub warnings: Vec<String>,
 }
 
 pub fn minify_properties(
-    _ast: &mut Program,
-    _analysis_result: &SemanticAnalysis,
-    _config: &TransformerConfig,
+    ast: &mut Program,
+    analysis_result: &SemanticAnalysis,
+    config: &TransformerConfig,
 ) -> TransformResult<PropertyMinificationResult> {
-    // TODO: Implement property minification
-    Ok(PropertyMinificationResult {
+    let enums = if config.inline_enums { find_enums(ast, analysis_result, config)? } else { HashMap::new() };
+    let mut renamer = PropertyRenamer {
+        objects:       HashMap::new(),
+        enums,
         renamed_count: 0,
-        warnings: vec!["Property minification not yet implemented".to_string()],
+        removed_count: 0,
+        inlined_count: 0,
+    };
+    let local_objects = if config.enable_property_minification { analysis_result.local_objects.as_slice() } else { &[] };
+    for object in local_objects {
+        if renamer.enums.contains_key(&object.name) {
+            continue;
+        }
+        let own = object.own_properties();
+        let inherited: HashSet<&str> = object.read_properties.iter().map(String::as_str).filter(|name| !own.contains(name)).collect();
+        let renames: BTreeMap<String, String> = if config.debug_property_names {
+            debug_names(own, &inherited)
+        } else {
+            let mut names = NameGenerator::new(NameContext::Sloppy).with_seed(config.name_seed).filter(|name| !inherited.contains(name.as_str()));
+            own.into_iter()
+                .map(|property| (property.to_string(), names.next().expect("name generator is unbounded")))
+                .filter(|(property, name)| property != name)
+                .collect()
+        };
+        renamer.renamed_count += renames.len() as u32;
+        let unused = object.unused_properties().map(str::to_string).collect();
+        renamer.objects.insert(object.name.clone(), ObjectRenames { renames, unused });
+    }
+
+    if !renamer.objects.is_empty() || !renamer.enums.is_empty() {
+        let mut ctx = PluginContext::new(analysis_result, config);
+        plugin::run_plugin(&mut renamer, ast, &mut ctx)?;
+    }
+
+    if renamer.renamed_count + renamer.removed_count > 0 {
+        tracing::debug!(
+            "🏷️  Renamed {} and removed {} properties of local objects",
+            renamer.renamed_count, renamer.removed_count
+        );
+    }
+    if renamer.inlined_count > 0 {
+        tracing::debug!(
+            "🔢 Inlined {} reads of {} enum-like objects",
+            renamer.inlined_count,
+            renamer.enums.len()
+        );
+    }
+
+    Ok(PropertyMinificationResult {
+        renamed_count: renamer.renamed_count,
+        removed_count: renamer.removed_count,
+        inlined_count: renamer.inlined_count,
+        renames: renamer
+            .objects
+            .into_iter()
+            .filter(|(_, object)| !object.renames.is_empty())
+            .map(|(name, object)| (name, object.renames))
+            .collect(),
+        warnings: Vec::new(),
     })
-}
\ No newline at end of file
+}
+
+/// Readable renames of `properties`: `foo` becomes `_$foo$_`
+///
+/// Characters that can't appear in an identifier become `_`, and a number is added
+/// when that makes a name equal to an earlier one or to an inherited property.
+fn debug_names<'a>(properties: impl IntoIterator<Item = &'a str>, inherited: &HashSet<&str>) -> BTreeMap<String, String> {
+    let mut taken: HashSet<String> = inherited.iter().map(|name| name.to_string()).collect();
+    properties
+        .into_iter()
+        .map(|property| {
+            let readable: String = property
+                .chars()
+                .map(|c| if c.is_alphanumeric() || c == '_' || c == '$' { c } else { '_' })
+                .collect();
+            let mut name = format!("_${}$_", readable);
+            let mut suffix = 1;
+            while taken.contains(&name) {
+                suffix += 1;
+                name = format!("_${}${}_", readable, suffix);
+            }
+            taken.insert(name.clone());
+            (property.to_string(), name)
+        })
+        .collect()
+}
+
+/// Finds the local objects worth inlining as enums, with the value of each property
+fn find_enums(
+    ast: &mut Program,
+    analysis: &SemanticAnalysis,
+    config: &TransformerConfig,
+) -> TransformResult<HashMap<String, HashMap<String, Expression>>> {
+    let candidates: HashSet<&str> = analysis
+        .local_objects
+        .iter()
+        .filter(|object| object.written_properties.is_empty() && object.read_properties.is_subset(&object.properties))
+        .map(|object| object.name.as_str())
+        .collect();
+    if candidates.is_empty() {
+        return Ok(HashMap::new());
+    }
+
+    let mut collector = EnumCollector {
+        candidates,
+        values:       HashMap::new(),
+        reads:        HashMap::new(),
+        disqualified: HashSet::new(),
+    };
+    plugin::run_plugin(&mut collector, ast, &mut PluginContext::new(analysis, config))?;
+
+    let enums = collector
+        .values
+        .into_iter()
+        .filter(|(name, _)| !collector.disqualified.contains(name))
+        .filter(|(name, values)| {
+            let reads = collector.reads.get(name);
+            let read_count = |property: &str| reads.and_then(|reads| reads.get(property)).copied().unwrap_or(0);
+            // `const name={key:value,...};` against the `name.key` accesses it replaces
+            let declaration = "const ={};".len()
+                + name.len()
+                + values.iter().map(|(key, value)| key.len() + 2 + literal_size(value).unwrap_or(0)).sum::<usize>();
+            let accesses: usize = values.keys().map(|key| read_count(key) * (name.len() + 1 + key.len())).sum();
+            let inlined: usize = values.iter().map(|(key, value)| read_count(key) * literal_size(value).unwrap_or(0)).sum();
+            inlined <= declaration + accesses
+        })
+        .collect();
+    Ok(enums)
+}
+
+/// Approximate printed size of a value that can be copied to every read, or `None` if
+/// it can't
+///
+/// Regular expression literals are excluded, since each evaluation creates a new object.
+pub(crate) fn literal_size(value: &Expression) -> Option<usize> {
+    match value {
+        Expression::Literal(Literal::Number(number)) => {
+            Some(number.raw.as_ref().map_or_else(|| number.value.to_string().len(), String::len))
+        }
+        Expression::Literal(Literal::String(string)) => Some(string.value.len() + 2),
+        Expression::Literal(Literal::Boolean(_)) => Some(2),
+        Expression::Literal(Literal::Null) => Some(4),
+        Expression::UnaryExpression { operator: UnaryOperator::Minus, argument, .. }
+            if matches!(argument.as_ref(), Expression::Literal(Literal::Number(_))) =>
+        {
+            literal_size(argument).map(|size| size + 1)
+        }
+        _ => None,
+    }
+}
+
+/// Collects the declarations and reads of enum candidates
+struct EnumCollector<'a> {
+    candidates:   HashSet<&'a str>,
+    /// Property values of the candidates declared with `const` and literal values
+    values:       HashMap<String, HashMap<String, Expression>>,
+    /// Number of reads of each property, by candidate
+    reads:        HashMap<String, HashMap<String, usize>>,
+    /// Candidates whose properties are updated or deleted, which reads can't tell apart
+    disqualified: HashSet<String>,
+}
+
+impl EnumCollector<'_> {
+    /// Candidate name and property of a static member access
+    fn access<'e>(&self, expr: &'e Expression) -> Option<(&'e str, &'e str)> {
+        let Expression::MemberExpression { object, property, computed, .. } = expr else {
+            return None;
+        };
+        let Expression::Identifier(id) = object.as_ref() else {
+            return None;
+        };
+        let property = static_property_name(property, *computed)?;
+        self.candidates.contains(id.name.as_str()).then_some((id.name.as_str(), property))
+    }
+}
+
+impl TransformPlugin for EnumCollector<'_> {
+    fn name(&self) -> &str {
+        "property_minification"
+    }
+
+    fn enter_statement(&mut self, stmt: &mut Statement, _ctx: &mut PluginContext) -> TransformResult<()> {
+        let Statement::VariableDeclaration { declarations, kind: VariableDeclarationKind::Const, .. } = stmt else {
+            return Ok(());
+        };
+        for declarator in declarations {
+            let (Pattern::Identifier(id), Some(Expression::ObjectExpression { properties })) =
+                (&declarator.id, &declarator.init)
+            else {
+                continue;
+            };
+            if !self.candidates.contains(id.name.as_str()) {
+                continue;
+            }
+            let values: Option<HashMap<String, Expression>> = properties
+                .iter()
+                .map(|property| match property {
+                    ObjectProperty::Property { key, value, .. } if literal_size(value).is_some() => {
+                        Some((static_key_name(key)?.to_string(), value.clone()))
+                    }
+                    _ => None,
+                })
+                .collect();
+            if let Some(values) = values {
+                self.values.insert(id.name.clone(), values);
+            }
+        }
+        Ok(())
+    }
+
+    fn enter_expression(&mut self, expr: &mut Expression, _ctx: &mut PluginContext) -> TransformResult<()> {
+        let target = match &*expr {
+            Expression::UpdateExpression { argument, .. }
+            | Expression::UnaryExpression { operator: UnaryOperator::Delete, argument, .. } => Some(argument.as_ref()),
+            _ => None,
+        };
+        if let Some((name, _)) = target.and_then(|target| self.access(target)) {
+            self.disqualified.insert(name.to_string());
+        } else if let Some((name, property)) = self.access(expr) {
+            *self.reads.entry(name.to_string()).or_default().entry(property.to_string()).or_default() += 1;
+        }
+        Ok(())
+    }
+}
+
+/// What happens to the properties of one local object
+struct ObjectRenames {
+    renames: BTreeMap<String, String>,
+    /// Properties the literal defines that are never read
+    unused:  HashSet<String>,
+}
+
+/// Rewrites the literals and static property accesses of local objects
+struct PropertyRenamer {
+    /// Local objects by variable name, which is unique in the program
+    objects:       HashMap<String, ObjectRenames>,
+    /// Property values of the enum-like objects to inline, by variable name
+    enums:         HashMap<String, HashMap<String, Expression>>,
+    renamed_count: u32,
+    removed_count: u32,
+    inlined_count: u32,
+}
+
+impl TransformPlugin for PropertyRenamer {
+    fn name(&self) -> &str {
+        "property_minification"
+    }
+
+    fn enter_statement(&mut self, stmt: &mut Statement, ctx: &mut PluginContext) -> TransformResult<()> {
+        let Statement::VariableDeclaration { declarations, .. } = stmt else {
+            return Ok(());
+        };
+        // Inlined enums have no uses left
+        declarations.retain(|declarator| !matches!(&declarator.id, Pattern::Identifier(id) if self.enums.contains_key(&id.name)));
+        if declarations.is_empty() {
+            *stmt = Statement::EmptyStatement;
+            return Ok(());
+        }
+        let Statement::VariableDeclaration { declarations, .. } = stmt else {
+            return Ok(());
+        };
+        for declarator in declarations {
+            let (Pattern::Identifier(id), Some(Expression::ObjectExpression { properties })) =
+                (&declarator.id, &mut declarator.init)
+            else {
+                continue;
+            };
+            let Some(object) = self.objects.get(&id.name) else {
+                continue;
+            };
+
+            let before = properties.len();
+            properties.retain(|property| match property {
+                ObjectProperty::Property { key, value, .. } => !static_key_name(key)
+                    .is_some_and(|name| object.unused.contains(name) && ctx.analysis.expression_purity(value).is_removable()),
+                ObjectProperty::SpreadElement { .. } => true,
+            });
+            self.removed_count += (before - properties.len()) as u32;
+
+            for property in properties.iter_mut() {
+                if let ObjectProperty::Property { key, shorthand, .. } = property
+                    && let Some(name) = static_key_name(key).and_then(|name| object.renames.get(name))
+                {
+                    *key = PropertyKey::Identifier(Identifier { name: name.clone(), span: None });
+                    *shorthand = false;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn enter_expression(&mut self, expr: &mut Expression, _ctx: &mut PluginContext) -> TransformResult<()> {
+        if let Expression::MemberExpression { object, property, computed, .. } = expr
+            && let Expression::Identifier(id) = object.as_ref()
+            && let Some(value) = self
+                .enums
+                .get(&id.name)
+                .and_then(|values| values.get(static_property_name(property, *computed)?))
+        {
+            *expr = value.clone();
+            self.inlined_count += 1;
+            return Ok(());
+        }
+        if let Expression::MemberExpression { object, property, computed, .. } = expr
+            && let Expression::Identifier(id) = object.as_ref()
+            && let Some(object) = self.objects.get(&id.name)
+            && let Some(name) = static_property_name(property, *computed).and_then(|name| object.renames.get(name))
+        {
+            **property = Expression::Identifier(Identifier { name: name.clone(), span: None });
+            *computed = false;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::transformer::tests::run_pass;
+
+    fn minify_source(source: &str) -> (String, PropertyMinificationResult) {
+        run_pass(source, &TransformerConfig::default(), minify_properties)
+    }
+
+    #[test]
+    fn test_renames_properties_of_local_objects() {
+        let (code, result) = minify_source(
+            "const size = { width: 1, height: 2 }; size.depth = 3; console.log(size.width + size['height'] + size.depth);",
+        );
+
+        assert_eq!(result.renamed_count, 3);
+        assert_eq!(result.renames["size"]["width"], "c");
+        // Names are assigned in property name order: depth, height, width
+        assert!(code.contains("const size={c:1,b:2}"), "{}", code);
+        assert!(code.contains("size.a=3"), "{}", code);
+        assert!(code.contains("size.c+size.b+size.a"), "{}", code);
+    }
+
+    #[test]
+    fn test_removes_unused_pure_properties() {
+        let (code, result) = minify_source("const o = { used: 1, unused: 2, effect: f() }; g(o.used);");
+
+        assert_eq!(result.removed_count, 1);
+        assert!(!code.contains(":2"), "{}", code);
+        assert!(code.contains("f()"), "{}", code);
+    }
+
+    #[test]
+    fn test_skips_objects_visible_from_dropped_code() {
+        // The converter drops `with` and sequence expressions, and with them the only
+        // reads of `alpha`
+        let (code, _) = minify_source(
+            "var o = { alpha: 1, beta: 2 }; function f() { with (o) { return alpha; } } g(o.beta, f()); \
+             function h() { var p = { gamma: 1, delta: 2 }; return p.gamma; }",
+        );
+        assert!(code.contains("var o={alpha:1,beta:2}"), "{}", code);
+        assert!(code.contains("o.beta"), "{}", code);
+        // `h` lost no code, so its object is still minified
+        assert!(code.contains("var p={b:1}"), "{}", code);
+
+        let (code, _) = minify_source("function f() { const o = { alpha: 1, beta: 2 }; return o.beta, () => (0, o.alpha); }");
+        assert!(code.contains("{alpha:1,beta:2}"), "{}", code);
+    }
+
+    #[test]
+    fn test_keeps_inherited_and_escaping_properties() {
+        let (code, result) = minify_source("const o = { c: 1 }; o.a(); o.c; const out = { x: 1 }; f(out);");
+
+        // `a` is read without being defined, so it is inherited and `c` can't become `a`
+        assert!(code.contains("o.a()"), "{}", code);
+        assert!(code.contains("const o={b:1}"), "{}", code);
+        assert!(code.contains("{x:1}"), "{}", code);
+        assert_eq!(result.renamed_count, 1);
+    }
+
+    #[test]
+    fn test_debug_names_keep_the_original_name_readable() {
+        let source = "const o = { width: 1, 'my-key': 2, my_key: 3 }; o.extra = 4; f(o.width, o['my-key'], o.my_key, o.extra);";
+        let config = TransformerConfig::default().with_debug_property_names(true);
+        let (code, result) = run_pass(source, &config, minify_properties);
+
+        assert_eq!(result.renamed_count, 4);
+        // Properties are renamed in name order, so `my-key` gets `_$my_key$_` first
+        assert!(code.contains("const o={_$width$_:1,_$my_key$_:2,_$my_key$2_:3}"), "{}", code);
+        assert!(code.contains("o._$extra$_=4"), "{}", code);
+        assert!(code.contains("f(o._$width$_,o._$my_key$_,o._$my_key$2_,o._$extra$_)"), "{}", code);
+    }
+
+    #[test]
+    fn test_inlines_enum_like_objects() {
+        let (code, result) = minify_source(
+            "const Direction = { Up: 0, Down: 1, Left: -1, Name: 'dir' };\n\
+             move(Direction.Up, Direction['Left'], Direction.Name);",
+        );
+
+        assert_eq!(result.inlined_count, 3);
+        assert_eq!(code, ";move(0,-1,'dir');");
+    }
+
+    #[test]
+    fn test_keeps_enums_that_are_modified_or_not_worth_inlining() {
+        let (code, result) = minify_source("const Count = { n: 0 }; f(Count.n); delete Count.n;");
+        assert_eq!(result.inlined_count, 0);
+        assert!(code.contains("delete Count.a"), "{}", code);
+
+        let (_, result) = minify_source("let Mutable = { A: 1 }; f(Mutable.A);");
+        assert_eq!(result.inlined_count, 0);
+
+        let long = "a".repeat(40);
+        let source = format!("const Text = {{ Long: '{}' }}; f(Text.Long, Text.Long, Text.Long);", long);
+        let (_, result) = minify_source(&source);
+        assert_eq!(result.inlined_count, 0);
+    }
+}
@@ -3,11 +3,37 @@
 //! Handles rollback of unsafe transformations that could change runtime behavior.
 //! This module implements safety checks and rollback mechanisms to ensure semantic
 //! preservation during aggressive optimization.
+//!
+//! ## Output Validation
+//!
+//! After a checkpointed pass changes the program, [`RollbackManager::check_transformation`]
+//! compares the result with the checkpoint:
+//!
+//! - The program is re-printed and re-parsed; output that no longer parses is rejected
+//!   (unless the checkpoint itself does not survive the round trip)
+//! - Calls to impure globals such as `Math.random()` must not disappear
+//! - Exported names must not disappear
+//!
+//! The transformer restores the checkpoint when a check fails and reports the reason as
+//! a warning.
 
 use crate::analyzer::SemanticAnalysis;
-use crate::parser::ast_types::{Expression, Program, Statement};
+use crate::generator::{Generator, GeneratorConfig};
+use crate::parser::ast_types::{
+    ExportSpecifier, Pattern, Program, ProgramSourceType, Statement,
+};
+use crate::parser::{parse_js, ParserConfig, SourceTypeConfig};
 use crate::transformer::{TransformError, TransformResult, TransformerConfig};
-use std::collections::HashMap;
+use serde_json::Value;
+
+/// Calls whose result differs between invocations, so they can never be folded away
+const IMPURE_GLOBAL_CALLS: &[(&str, &str)] = &[
+    ("Math", "random"),
+    ("Date", "now"),
+    ("performance", "now"),
+    ("crypto", "getRandomValues"),
+    ("crypto", "randomUUID"),
+];
 
 /// Stores the original state of a transformation for potential rollback
 #[derive(Debug, Clone)]
@@ -36,8 +62,6 @@ pub struct RollbackConfig {
     pub auto_rollback: bool,
     /// Maximum number of checkpoints to maintain
     pub max_checkpoints: usize,
-    /// Enable verbose rollback logging
-    pub verbose: bool,
 }
 
 impl Default for RollbackConfig {
@@ -45,7 +69,6 @@ impl Default for RollbackConfig {
         Self {
             auto_rollback: true,
             max_checkpoints: 10,
-            verbose: false,
         }
     }
 }
@@ -72,9 +95,7 @@ impl RollbackManager {
         pass_name: &str,
         reason: &str,
     ) {
-        if self.config.verbose {
-            println!("📍 Creating checkpoint for {}: {}", pass_name, reason);
-        }
+        tracing::trace!("📍 Creating checkpoint for {}: {}", pass_name, reason);
 
         let checkpoint = TransformationCheckpoint {
             original_ast: ast.clone(),
@@ -97,10 +118,8 @@ impl RollbackManager {
     /// Returns the original AST state or an error if no checkpoints exist
     pub fn rollback_to_last_checkpoint(&mut self) -> TransformResult<Program> {
         if let Some(checkpoint) = self.checkpoints.pop() {
-            if self.config.verbose {
-                println!("↩️ Rolling back transformation: {} ({})", 
-                    checkpoint.pass_name, checkpoint.reason);
-            }
+            tracing::debug!("↩️ Rolling back transformation: {} ({})", 
+                checkpoint.pass_name, checkpoint.reason);
             Ok(checkpoint.original_ast)
         } else {
             Err(TransformError::RollbackRequired(
@@ -121,10 +140,8 @@ impl RollbackManager {
             // Remove all checkpoints after this one
             self.checkpoints.truncate(pos);
             
-            if self.config.verbose {
-                println!("↩️ Rolling back to pass: {} ({})", 
-                    checkpoint.pass_name, checkpoint.reason);
-            }
+            tracing::debug!("↩️ Rolling back to pass: {} ({})", 
+                checkpoint.pass_name, checkpoint.reason);
             Ok(checkpoint.original_ast)
         } else {
             Err(TransformError::RollbackRequired(
@@ -133,6 +150,29 @@ impl RollbackManager {
         }
     }
 
+    /// Releases the checkpoint for a pass that completed successfully
+    ///
+    /// Each checkpoint holds a full copy of the AST, so dropping it as soon as the
+    /// pass is known to be safe keeps peak memory at roughly two ASTs instead of
+    /// one per retained checkpoint.
+    ///
+    /// # Returns
+    ///
+    /// Returns true if a checkpoint for the pass was found and released
+    pub fn release_checkpoint(&mut self, pass_name: &str) -> bool {
+        if let Some(pos) = self.checkpoints.iter().rposition(|cp| cp.pass_name == pass_name) {
+            self.checkpoints.remove(pos);
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Returns the checkpoint most recently created for `pass_name`
+    pub fn checkpoint(&self, pass_name: &str) -> Option<&TransformationCheckpoint> {
+        self.checkpoints.iter().rev().find(|cp| cp.pass_name == pass_name)
+    }
+
     /// Validates a transformation by checking for semantic violations
     ///
     /// # Arguments
@@ -150,35 +190,50 @@ impl RollbackManager {
         transformed_ast: &Program,
         analysis: &SemanticAnalysis,
     ) -> bool {
-        // Check for unsafe transformations
-        if self.has_unsafe_constant_folding(original_ast, transformed_ast) {
-            return false;
-        }
-
-        if self.has_unsafe_function_inlining(original_ast, transformed_ast, analysis) {
-            return false;
-        }
+        self.check_transformation(original_ast, transformed_ast, analysis).is_none()
+    }
 
-        if self.has_unsafe_variable_elimination(original_ast, transformed_ast, analysis) {
-            return false;
+    /// Checks a transformation for semantic violations
+    ///
+    /// # Returns
+    ///
+    /// Returns the reason the transformation must be rolled back, or `None` if it is safe
+    pub fn check_transformation(
+        &self,
+        original_ast: &Program,
+        transformed_ast: &Program,
+        analysis: &SemanticAnalysis,
+    ) -> Option<String> {
+        if let Some(problem) = round_trip_error(transformed_ast)
+            && round_trip_error(original_ast).is_none()
+        {
+            return Some(problem);
         }
 
-        true
+        self.has_unsafe_constant_folding(original_ast, transformed_ast)
+            .or_else(|| self.has_unsafe_function_inlining(original_ast, transformed_ast, analysis))
+            .or_else(|| self.has_unsafe_variable_elimination(original_ast, transformed_ast, analysis))
     }
 
     /// Checks for unsafe constant folding that could change runtime behavior
+    ///
+    /// Reports calls to [`IMPURE_GLOBAL_CALLS`] that disappeared from the program.
     fn has_unsafe_constant_folding(
         &self,
-        _original_ast: &Program,
-        _transformed_ast: &Program,
-    ) -> bool {
-        // TODO: Implement detection of unsafe constant folding
-        // Examples:
-        // - Math.random() should not be folded
-        // - Date.now() should not be folded
-        // - Division by zero should not be folded
-        // - NaN operations should be preserved
-        false
+        original_ast: &Program,
+        transformed_ast: &Program,
+    ) -> Option<String> {
+        // TODO: Detect folded division by zero and NaN operations
+        let (Ok(original), Ok(transformed)) = (serde_json::to_value(original_ast), serde_json::to_value(transformed_ast))
+        else {
+            return None;
+        };
+
+        IMPURE_GLOBAL_CALLS.iter().find_map(|&(object, property)| {
+            let before = count_member_calls(&original, object, property);
+            let after = count_member_calls(&transformed, object, property);
+            (after < before).then(|| format!("removed a call to impure {}.{}()", object, property))
+        })
     }
 
     /// Checks for unsafe function inlining
@@ -187,34 +242,57 @@ impl RollbackManager {
         _original_ast: &Program,
         _transformed_ast: &Program,
         _analysis: &SemanticAnalysis,
-    ) -> bool {
+    ) -> Option<String> {
         // TODO: Implement detection of unsafe function inlining
         // Examples:
         // - Functions with side effects should not be inlined
         // - Recursive functions should not be inlined
         // - Functions that access 'this' should be carefully handled
-        false
+        None
     }
 
     /// Checks for unsafe variable elimination
+    ///
+    /// Reports exported names that disappeared from the program, and removed declarations
+    /// of symbols annotated with `@__KEEP__`.
     fn has_unsafe_variable_elimination(
         &self,
-        _original_ast: &Program,
-        _transformed_ast: &Program,
-        _analysis: &SemanticAnalysis,
-    ) -> bool {
-        // TODO: Implement detection of unsafe variable elimination
-        // Examples:
-        // - Variables accessed by eval should not be eliminated
-        // - Variables in closures should be preserved
-        // - Exported variables should not be eliminated
-        false
+        original_ast: &Program,
+        transformed_ast: &Program,
+        analysis: &SemanticAnalysis,
+    ) -> Option<String> {
+        // TODO: Detect eliminated variables accessed by eval or captured by closures
+        let remaining = exported_names(transformed_ast);
+        if let Some(name) = exported_names(original_ast).into_iter().find(|name| !remaining.contains(name)) {
+            return Some(format!("removed export '{}'", name));
+        }
+
+        let mut kept: Vec<&str> = analysis
+            .symbol_table
+            .symbols
+            .values()
+            .filter(|symbol| symbol.is_kept)
+            .map(|symbol| analysis.symbol_table.name(symbol))
+            .collect();
+        if kept.is_empty() {
+            return None;
+        }
+        kept.sort_unstable();
+        kept.dedup();
+
+        let (Ok(original), Ok(transformed)) = (serde_json::to_value(original_ast), serde_json::to_value(transformed_ast))
+        else {
+            return None;
+        };
+        kept.into_iter()
+            .find(|name| count_declarations(&transformed, name) < count_declarations(&original, name))
+            .map(|name| format!("removed kept declaration '{}'", name))
     }
 
     /// Clears all checkpoints
     pub fn clear_checkpoints(&mut self) {
-        if self.config.verbose && !self.checkpoints.is_empty() {
-            println!("🗑️ Clearing {} checkpoints", self.checkpoints.len());
+        if !self.checkpoints.is_empty() {
+            tracing::debug!("🗑️ Clearing {} checkpoints", self.checkpoints.len());
         }
         self.checkpoints.clear();
     }
@@ -225,6 +303,101 @@ impl RollbackManager {
     }
 }
 
+/// Prints `program` and parses the output again, describing the first failure
+fn round_trip_error(program: &Program) -> Option<String> {
+    let generator = Generator::new(GeneratorConfig {
+        max_output_size: None,
+        ..GeneratorConfig::default()
+    });
+    let code = match generator.generate(program, None) {
+        Ok(result) => result.code,
+        Err(e) => return Some(format!("output could not be printed: {}", e)),
+    };
+
+    let parser_config = ParserConfig {
+        source_type: match program.source_type {
+            ProgramSourceType::Script => SourceTypeConfig::Script,
+            ProgramSourceType::Module => SourceTypeConfig::Module,
+        },
+        // Whatever proposals the input used were enabled when it was parsed
+        decorators: true,
+        import_attributes: true,
+        explicit_resource_management: true,
+        ..ParserConfig::default()
+    };
+    parse_js(&code, "rollback-check.js", &parser_config)
+        .errors
+        .first()
+        .map(|error| format!("output no longer parses: {}", error))
+}
+
+/// Counts calls of `object.property(...)` anywhere in the JSON form of a program
+fn count_member_calls(node: &Value, object: &str, property: &str) -> usize {
+    match node {
+        Value::Object(fields) => {
+            let is_match = fields.get("type").and_then(Value::as_str) == Some("CallExpression")
+                && fields.get("callee").is_some_and(|callee| {
+                    callee["type"] == "MemberExpression"
+                        && callee["computed"] == false
+                        && callee["object"]["name"] == object
+                        && callee["property"]["name"] == property
+                });
+            usize::from(is_match) + fields.values().map(|child| count_member_calls(child, object, property)).sum::<usize>()
+        }
+        Value::Array(items) => items.iter().map(|item| count_member_calls(item, object, property)).sum(),
+        _ => 0,
+    }
+}
+
+/// Counts function, class and variable declarations of `name` anywhere in the JSON form
+/// of a program
+fn count_declarations(node: &Value, name: &str) -> usize {
+    match node {
+        Value::Object(fields) => {
+            let declares = |id: &Value| id["name"] == name;
+            let is_match = match fields.get("type").and_then(Value::as_str) {
+                Some("FunctionDeclaration" | "ClassDeclaration") => fields.get("id").is_some_and(declares),
+                // Variable declarators are untagged structs
+                None => fields.contains_key("init") && fields.get("id").is_some_and(declares),
+                _ => false,
+            };
+            usize::from(is_match) + fields.values().map(|child| count_declarations(child, name)).sum::<usize>()
+        }
+        Value::Array(items) => items.iter().map(|item| count_declarations(item, name)).sum(),
+        _ => 0,
+    }
+}
+
+/// Names exported by the top-level `export` declarations of a program
+fn exported_names(program: &Program) -> Vec<String> {
+    let mut names = Vec::new();
+    for statement in &program.body {
+        if let Statement::ExportDefaultDeclaration { .. } = statement {
+            names.push("default".to_string());
+            continue;
+        }
+        let Statement::ExportNamedDeclaration { declaration, specifiers, .. } = statement else {
+            continue;
+        };
+        match declaration.as_deref() {
+            Some(Statement::VariableDeclaration { declarations, .. }) => {
+                for declarator in declarations {
+                    if let Pattern::Identifier(id) = &declarator.id {
+                        names.push(id.name.clone());
+                    }
+                }
+            }
+            Some(Statement::FunctionDeclaration { id: Some(id), .. })
+            | Some(Statement::ClassDeclaration { id: Some(id), .. }) => names.push(id.name.clone()),
+            _ => {}
+        }
+        names.extend(specifiers.iter().map(|specifier| match specifier {
+            ExportSpecifier::ExportSpecifier { exported, .. } => exported.name.clone(),
+        }));
+    }
+    names
+}
+
 /// Utility function to perform a safe transformation with automatic rollback
 ///
 /// # Arguments
@@ -263,17 +436,13 @@ where
                 Ok(ast)
             } else {
                 // Transformation is unsafe, rollback
-                if rollback_manager.config.verbose {
-                    println!("⚠️ Unsafe transformation detected in {}, rolling back", pass_name);
-                }
+                tracing::debug!("⚠️ Unsafe transformation detected in {}, rolling back", pass_name);
                 rollback_manager.rollback_to_last_checkpoint()
             }
         }
         Err(e) => {
             // Transformation failed, rollback
-            if rollback_manager.config.verbose {
-                println!("❌ Transformation failed in {}: {}, rolling back", pass_name, e);
-            }
+            tracing::debug!("❌ Transformation failed in {}: {}, rolling back", pass_name, e);
             rollback_manager.rollback_to_last_checkpoint()
         }
     }
@@ -282,6 +451,7 @@ where
 #[cfg(test)]
 mod tests {
     use super::*;
+    use std::collections::BTreeMap;
     use crate::analyzer::{SymbolTable, SemanticFlags, AnalysisMetadata, ScopeTree, ScopeType};
     use crate::parser::ast_types::{Program, ProgramSourceType};
 
@@ -297,9 +467,10 @@ mod tests {
             symbol_table: SymbolTable::new(),
             scope_tree: ScopeTree::new(ScopeType::Global),
             semantic_flags: SemanticFlags {
-                unsafe_scopes: HashMap::new(),
-                unsafe_symbols: HashMap::new(),
+                unsafe_scopes: BTreeMap::new(),
+                unsafe_symbols: BTreeMap::new(),
                 global_references: Vec::new(),
+                global_exports: Default::default(),
             },
             metadata: AnalysisMetadata {
                 scope_count: 1,
@@ -308,6 +479,10 @@ mod tests {
                 export_count: 0,
                 analysis_time_ms: 0,
             },
+            module_record: Default::default(),
+            purity: Default::default(),
+            local_objects: Vec::new(),
+            local_classes: Vec::new(),
         }
     }
 
@@ -361,6 +536,20 @@ mod tests {
         assert_eq!(manager.checkpoint_count(), 0);
     }
 
+    #[test]
+    fn test_release_checkpoint() {
+        let mut manager = RollbackManager::new(RollbackConfig::default());
+        let ast = create_test_ast();
+
+        manager.create_checkpoint(&ast, "pass1", "reason1");
+        manager.create_checkpoint(&ast, "pass2", "reason2");
+
+        assert!(manager.release_checkpoint("pass2"));
+        assert!(!manager.release_checkpoint("pass2"));
+        assert_eq!(manager.checkpoint_count(), 1);
+        assert!(manager.rollback_to_pass("pass1").is_ok());
+    }
+
     #[test]
     fn test_safe_transform_success() {
         let ast = create_test_ast();
@@ -424,4 +613,77 @@ mod tests {
         let is_valid = manager.validate_transformation(&ast, &ast, &analysis);
         assert!(is_valid);
     }
-}
\ No newline at end of file
+
+    fn parse(source: &str) -> Program {
+        parse_js(source, "test.js", &ParserConfig::default()).ast.unwrap()
+    }
+
+    #[test]
+    fn test_check_rejects_output_that_does_not_parse() {
+        let manager = RollbackManager::new(RollbackConfig::default());
+        let original = parse("let a = 1;");
+        let mut transformed = original.clone();
+        if let Statement::VariableDeclaration { declarations, .. } = &mut transformed.body[0] {
+            declarations[0].id = Pattern::Identifier(crate::parser::ast_types::Identifier { name: "if".to_string(), span: None });
+        }
+
+        let reason = manager.check_transformation(&original, &transformed, &create_test_analysis()).unwrap();
+        assert!(reason.starts_with("output no longer parses"), "{}", reason);
+    }
+
+    #[test]
+    fn test_check_rejects_removed_impure_calls_and_exports() {
+        use crate::parser::ast_types::Identifier;
+
+        let manager = RollbackManager::new(RollbackConfig::default());
+        let analysis = create_test_analysis();
+        let export = |name: &str| Statement::ExportNamedDeclaration {
+            declaration: None,
+            specifiers: vec![ExportSpecifier::ExportSpecifier {
+                local: Identifier { name: "seed".to_string(), span: None },
+                exported: Identifier { name: name.to_string(), span: None },
+            }],
+            source: None,
+            attributes: vec![],
+            span: None,
+        };
+        let mut original = parse("const seed = Math.random();");
+        original.body.push(export("other"));
+
+        let mut folded = parse("const seed = 0.5;");
+        folded.body.push(export("other"));
+        assert_eq!(
+            manager.check_transformation(&original, &folded, &analysis),
+            Some("removed a call to impure Math.random()".to_string())
+        );
+
+        let unexported = parse("const seed = Math.random();");
+        assert_eq!(
+            manager.check_transformation(&original, &unexported, &analysis),
+            Some("removed export 'other'".to_string())
+        );
+
+        let mut extended = parse("const seed = Math.random(), a = 1;");
+        extended.body.push(export("other"));
+        assert!(manager.validate_transformation(&original, &extended, &analysis));
+    }
+
+    #[test]
+    fn test_check_rejects_removed_kept_declarations() {
+        use crate::analyzer::{analyze_ast, AnalyzerConfig};
+
+        let manager = RollbackManager::new(RollbackConfig::default());
+        let original = parse("function hook() {}\nlet x = 1;");
+        let removed = parse("let x = 1;");
+
+        let config = AnalyzerConfig { kept_names: vec!["hook".to_string()], ..AnalyzerConfig::default() };
+        let analysis = analyze_ast(&original, &config).unwrap();
+        assert_eq!(
+            manager.check_transformation(&original, &removed, &analysis),
+            Some("removed kept declaration 'hook'".to_string())
+        );
+
+        let analysis = analyze_ast(&original, &AnalyzerConfig::default()).unwrap();
+        assert!(manager.validate_transformation(&original, &removed, &analysis));
+    }
+}
@@ -0,0 +1,230 @@
+//! # String Deduplication
+//!
+//! Hoists string literals that repeat across a module into one top-level declaration,
+//! replacing every occurrence with a short name, when
+//! `TransformerConfig::dedupe_strings` is set. It runs once, after the passes.
+//!
+//! ## Size Heuristic
+//!
+//! A string of quoted length `L` used `n` times gets a name of length `k`, which saves
+//! `n * (L - k)` bytes and costs `k + L + 2` in the declaration (`k='...',`). Strings
+//! are hoisted, best first, while the saving is at least the threshold. Gzip already
+//! shrinks repeats of a string to back-references of a few bytes, so the gain after
+//! compression is much smaller than the estimate; the default threshold
+//! ([`DEFAULT_DEDUPE_STRINGS_THRESHOLD`]) leaves out hoists that gzip would mostly erase.
+//!
+//! Directives (`'use strict'`) stay literals, and scripts are left alone since a
+//! top-level declaration there would be a global.
+
+use std::collections::{HashMap, HashSet};
+
+use serde_json::Value;
+
+use crate::analyzer::SemanticAnalysis;
+use crate::parser::ast_types::{
+    Expression, Identifier, Literal, Pattern, Program, ProgramSourceType, Statement, StringLiteral,
+    VariableDeclarationKind, VariableDeclarator,
+};
+use crate::target::Feature;
+use crate::transformer::identifier_renaming::{NameContext, NameGenerator};
+use crate::transformer::plugin::{self, PluginContext, TransformPlugin};
+use crate::transformer::{TransformResult, TransformerConfig};
+
+/// Minimum estimated saving, in bytes, for a string to be hoisted by default
+pub const DEFAULT_DEDUPE_STRINGS_THRESHOLD: usize = 32;
+
+/// Hoists repeated string literals of `program` into a top-level declaration
+///
+/// Returns the number of literals replaced by a name.
+pub fn dedupe_strings(
+    program: &mut Program,
+    analysis: &SemanticAnalysis,
+    config: &TransformerConfig,
+) -> TransformResult<u32> {
+    let Some(threshold) = config.dedupe_strings else {
+        return Ok(0);
+    };
+    if matches!(program.source_type, ProgramSourceType::Script) {
+        return Ok(0);
+    }
+
+    let mut counter = StringCounter { counts: HashMap::new(), in_directive: false };
+    plugin::run_plugin(&mut counter, program, &mut PluginContext::new(analysis, config))?;
+
+    let mut used = HashSet::new();
+    collect_names(&serde_json::to_value(&program.body).unwrap_or(Value::Null), &mut used);
+    let mut names = NameGenerator::new(NameContext::Module).with_seed(config.name_seed).filter(|name| !used.contains(name));
+
+    // Best candidates first, so they get the shortest names
+    let mut candidates: Vec<(String, usize)> = counter.counts.into_iter().filter(|(_, count)| *count > 1).collect();
+    candidates.sort_by(|(a, a_count), (b, b_count)| {
+        saving(b, *b_count, 1).cmp(&saving(a, *a_count, 1)).then_with(|| a.cmp(b))
+    });
+    let mut hoisted: HashMap<String, String> = HashMap::new();
+    let mut declarations = Vec::new();
+    let mut next_name = names.next().expect("name generator is unbounded");
+    for (value, count) in candidates {
+        if saving(&value, count, next_name.len()) < threshold as isize {
+            continue;
+        }
+        let name = std::mem::replace(&mut next_name, names.next().expect("name generator is unbounded"));
+        declarations.push(VariableDeclarator {
+            id:   Pattern::Identifier(Identifier { name: name.clone(), span: None }),
+            init: Some(Expression::Literal(Literal::String(StringLiteral::new(value.clone(), None)))),
+            span: None,
+        });
+        hoisted.insert(value, name);
+    }
+    if hoisted.is_empty() {
+        return Ok(0);
+    }
+
+    let mut replacer = StringReplacer { hoisted, in_directive: false, replaced_count: 0 };
+    plugin::run_plugin(&mut replacer, program, &mut PluginContext::new(analysis, config))?;
+
+    let kind = if config.target.supports(Feature::BlockScoping) {
+        VariableDeclarationKind::Const
+    } else {
+        VariableDeclarationKind::Var
+    };
+    let hoisted_count = declarations.len();
+    let position = program
+        .body
+        .iter()
+        .take_while(|statement| is_directive(statement) || matches!(statement, Statement::ImportDeclaration { .. }))
+        .count();
+    program.body.insert(position, Statement::VariableDeclaration { declarations, kind, span: None });
+
+    tracing::debug!("🔤 Hoisted {} repeated strings ({} uses)", hoisted_count, replacer.replaced_count);
+    Ok(replacer.replaced_count)
+}
+
+/// Estimated bytes saved by hoisting a string used `count` times into a name of `name_length`
+fn saving(value: &str, count: usize, name_length: usize) -> isize {
+    let quoted = value.len() as isize + 2;
+    let name_length = name_length as isize;
+    count as isize * (quoted - name_length) - (name_length + quoted + 2)
+}
+
+/// Whether `statement` is a directive such as `'use strict'`
+fn is_directive(statement: &Statement) -> bool {
+    matches!(
+        statement,
+        Statement::ExpressionStatement { expression: Expression::Literal(Literal::String(_)), .. }
+    )
+}
+
+/// Every name in the JSON form of a node, so generated names never shadow one
+pub(crate) fn collect_names(node: &Value, names: &mut HashSet<String>) {
+    match node {
+        Value::Object(fields) => {
+            if let Some(Value::String(name)) = fields.get("name") {
+                names.insert(name.clone());
+            }
+            fields.values().for_each(|child| collect_names(child, names));
+        }
+        Value::Array(items) => items.iter().for_each(|item| collect_names(item, names)),
+        _ => {}
+    }
+}
+
+/// Counts the string literals outside of directives
+struct StringCounter {
+    counts:       HashMap<String, usize>,
+    /// Whether the next expression is the literal of a directive
+    in_directive: bool,
+}
+
+impl TransformPlugin for StringCounter {
+    fn name(&self) -> &str {
+        "string_dedupe"
+    }
+
+    fn enter_statement(&mut self, stmt: &mut Statement, _ctx: &mut PluginContext) -> TransformResult<()> {
+        self.in_directive = is_directive(stmt);
+        Ok(())
+    }
+
+    fn enter_expression(&mut self, expr: &mut Expression, _ctx: &mut PluginContext) -> TransformResult<()> {
+        if std::mem::take(&mut self.in_directive) {
+            return Ok(());
+        }
+        // A string with lone surrogates spells its value differently, so it is left alone
+        if let Expression::Literal(Literal::String(string)) = expr
+            && !string.lone_surrogates
+        {
+            *self.counts.entry(string.value.clone()).or_default() += 1;
+        }
+        Ok(())
+    }
+}
+
+/// Replaces the hoisted string literals with their names
+struct StringReplacer {
+    /// Names of the hoisted strings, by value
+    hoisted:        HashMap<String, String>,
+    in_directive:   bool,
+    replaced_count: u32,
+}
+
+impl TransformPlugin for StringReplacer {
+    fn name(&self) -> &str {
+        "string_dedupe"
+    }
+
+    fn enter_statement(&mut self, stmt: &mut Statement, _ctx: &mut PluginContext) -> TransformResult<()> {
+        self.in_directive = is_directive(stmt);
+        Ok(())
+    }
+
+    fn enter_expression(&mut self, expr: &mut Expression, _ctx: &mut PluginContext) -> TransformResult<()> {
+        if std::mem::take(&mut self.in_directive) {
+            return Ok(());
+        }
+        if let Expression::Literal(Literal::String(string)) = expr
+            && !string.lone_surrogates
+            && let Some(name) = self.hoisted.get(&string.value)
+        {
+            *expr = Expression::Identifier(Identifier { name: name.clone(), span: None });
+            self.replaced_count += 1;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::transformer::tests::run_pass;
+
+    fn dedupe_source(source: &str, threshold: usize) -> (String, u32) {
+        let config = TransformerConfig::default().with_dedupe_strings(Some(threshold));
+        run_pass(source, &config, dedupe_strings)
+    }
+
+    #[test]
+    fn test_hoists_repeated_long_strings() {
+        let long = "a long repeated message";
+        let source = format!(
+            "import x from 'mod'; f('{long}'); g('{long}', 'short', 'short'); function a() {{ return '{long}'; }}"
+        );
+        let (code, replaced) = dedupe_source(&source, DEFAULT_DEDUPE_STRINGS_THRESHOLD);
+
+        assert_eq!(replaced, 3);
+        // `a` and `x` are taken, and imports stay first
+        assert!(code.starts_with(&format!("import x from'mod';const b='{long}';")), "{}", code);
+        assert!(code.contains("f(b);g(b,'short','short');"), "{}", code);
+        assert!(code.contains("return b;"), "{}", code);
+    }
+
+    #[test]
+    fn test_skips_strings_below_the_threshold() {
+        let (code, replaced) = dedupe_source("f('medium text'); f('medium text'); f('medium text');", 32);
+        assert_eq!(replaced, 0);
+        assert!(!code.contains("const"), "{}", code);
+
+        let (code, replaced) = dedupe_source("f('medium text'); f('medium text'); f('medium text');", 1);
+        assert_eq!(replaced, 3);
+        assert!(code.starts_with("const a='medium text';"), "{}", code);
+    }
+}
@@ -0,0 +1,615 @@
+//! # Syntax Lowering
+//!
+//! Rewrites syntax that `TransformerConfig::target` lacks into equivalent ES2019 code.
+//! It runs after the optimization passes, so they see the original syntax:
+//!
+//! - optional chains: `a?.b.c` becomes `a == null ? void 0 : a.b.c`, and `o.f?.(x)`
+//!   becomes `(_a = o.f) == null ? void 0 : _a.call(o, x)` to keep `this`; `delete a?.b`
+//!   becomes `a == null ? true : delete a.b`, since deleting the conditional would
+//!   delete nothing
+//! - nullish coalescing: `a ?? b` becomes `a != null ? a : b`
+//! - logical assignment: `a ??= b` becomes `a ?? (a = b)` (lowered further when `??`
+//!   is missing too), and likewise for `||=` and `&&=`
+//!
+//! Operands that would be evaluated twice are stored in temporaries (`_a`, `_b`, …)
+//! declared with `var` at the top of the enclosing function or program, unless they are
+//! identifiers or `this`. Temporary names avoid every declared and referenced name.
+//!
+//! As in the loose mode of other compilers, `== null` treats `document.all` as nullish,
+//! where `??` does not.
+
+use std::collections::HashSet;
+
+use crate::analyzer::SymbolTable;
+use crate::parser::ast_types::{
+    ArrowFunctionBody, AssignmentOperator, BinaryOperator, BlockStatement, BooleanLiteral, ClassElement,
+    Expression, ForInit, FunctionExpression, Identifier, Literal, NumberLiteral, ObjectPatternProperty,
+    ObjectProperty, Pattern, Program, Statement, UnaryOperator, VariableDeclarationKind, VariableDeclarator,
+};
+use crate::target::Feature;
+use crate::transformer::identifier_renaming::{NameContext, NameGenerator};
+use crate::transformer::TransformerConfig;
+
+/// Lowers the syntax `config.target` lacks throughout `program`
+///
+/// Returns the number of expressions rewritten.
+pub fn lower_syntax(program: &mut Program, symbol_table: &SymbolTable, config: &TransformerConfig) -> u32 {
+    let mut lowering = Lowering {
+        optional_chaining: !config.target.supports(Feature::OptionalChaining),
+        nullish_coalescing: !config.target.supports(Feature::NullishCoalescing),
+        logical_assignment: !config.target.supports(Feature::LogicalAssignment),
+        collecting: true,
+        used_names: HashSet::new(),
+        symbol_table,
+        names: NameGenerator::new(NameContext::for_program(program)).with_seed(config.name_seed),
+        temporaries: vec![Vec::new()],
+        lowered: 0,
+    };
+    if !(lowering.optional_chaining || lowering.nullish_coalescing || lowering.logical_assignment) {
+        return 0;
+    }
+
+    // The first walk only records referenced names, which may be undeclared globals
+    lowering.statements(&mut program.body);
+    lowering.collecting = false;
+    lowering.statements(&mut program.body);
+
+    let temporaries = lowering.temporaries.pop().unwrap_or_default();
+    declare_temporaries(&mut program.body, temporaries);
+    lowering.lowered
+}
+
+/// A member access or call of a flattened optional chain
+enum ChainLink {
+    Member { property: Expression, computed: bool, optional: bool },
+    Call { arguments: Vec<Expression>, optional: bool },
+}
+
+/// Traversal state
+struct Lowering<'a> {
+    optional_chaining: bool,
+    nullish_coalescing: bool,
+    logical_assignment: bool,
+    /// Whether this walk only collects referenced names
+    collecting: bool,
+    /// Names referenced anywhere, and temporaries handed out so far
+    used_names: HashSet<String>,
+    symbol_table: &'a SymbolTable,
+    names: NameGenerator,
+    /// Temporaries of each enclosing function, innermost last
+    temporaries: Vec<Vec<String>>,
+    lowered: u32,
+}
+
+impl Lowering<'_> {
+    fn statements(&mut self, statements: &mut [Statement]) {
+        for statement in statements {
+            self.statement(statement);
+        }
+    }
+
+    fn statement(&mut self, statement: &mut Statement) {
+        match statement {
+            Statement::VariableDeclaration { declarations, .. } => self.declarators(declarations),
+            Statement::FunctionDeclaration { params, body, .. } => {
+                self.patterns(params);
+                self.function_body(body);
+            }
+            Statement::ClassDeclaration { super_class, body, decorators, .. } => {
+                for decorator in decorators {
+                    self.expression(decorator);
+                }
+                if let Some(super_class) = super_class {
+                    self.expression(super_class);
+                }
+                for element in &mut body.body {
+                    match element {
+                        ClassElement::MethodDefinition { value, decorators, .. } => {
+                            for decorator in decorators {
+                                self.expression(decorator);
+                            }
+                            self.function(value);
+                        }
+                        ClassElement::PropertyDefinition { value, decorators, .. } => {
+                            for decorator in decorators {
+                                self.expression(decorator);
+                            }
+                            if let Some(value) = value {
+                                self.expression(value);
+                            }
+                        }
+                    }
+                }
+            }
+            Statement::ExpressionStatement { expression, .. } => self.expression(expression),
+            Statement::BlockStatement { body, .. } => self.statements(body),
+            Statement::ReturnStatement { argument, .. } => {
+                if let Some(argument) = argument {
+                    self.expression(argument);
+                }
+            }
+            Statement::IfStatement { test, consequent, alternate, .. } => {
+                self.expression(test);
+                self.statement(consequent);
+                if let Some(alternate) = alternate {
+                    self.statement(alternate);
+                }
+            }
+            Statement::WhileStatement { test, body, .. } => {
+                self.expression(test);
+                self.statement(body);
+            }
+            Statement::ForStatement { init, test, update, body, .. } => {
+                match init {
+                    Some(ForInit::VariableDeclaration { declarations, .. }) => self.declarators(declarations),
+                    Some(ForInit::Expression(expr)) => self.expression(expr),
+                    None => {}
+                }
+                for expr in [test, update].into_iter().flatten() {
+                    self.expression(expr);
+                }
+                self.statement(body);
+            }
+            Statement::ForOfStatement { left, right, body, .. } => {
+                if let ForInit::Expression(expr) = left {
+                    self.expression(expr);
+                }
+                self.expression(right);
+                self.statement(body);
+            }
+            Statement::ImportDeclaration { .. }
+            | Statement::ExportAllDeclaration { .. }
+            | Statement::EmptyStatement
+            | Statement::DebuggerStatement => {}
+            Statement::ExportNamedDeclaration { declaration, .. } => {
+                if let Some(declaration) = declaration {
+                    self.statement(declaration);
+                }
+            }
+            Statement::ExportDefaultDeclaration { declaration, .. } => self.statement(declaration),
+        }
+    }
+
+    fn declarators(&mut self, declarations: &mut [VariableDeclarator]) {
+        for declarator in declarations {
+            self.pattern(&mut declarator.id);
+            if let Some(init) = &mut declarator.init {
+                self.expression(init);
+            }
+        }
+    }
+
+    fn patterns(&mut self, patterns: &mut [Pattern]) {
+        for pattern in patterns {
+            self.pattern(pattern);
+        }
+    }
+
+    /// Walks the default values inside a binding pattern
+    fn pattern(&mut self, pattern: &mut Pattern) {
+        match pattern {
+            Pattern::Identifier(_) => {}
+            Pattern::ArrayPattern { elements } => {
+                for element in elements.iter_mut().flatten() {
+                    self.pattern(element);
+                }
+            }
+            Pattern::ObjectPattern { properties } => {
+                for property in properties {
+                    match property {
+                        ObjectPatternProperty::Property { value, .. } => self.pattern(value),
+                        ObjectPatternProperty::RestElement { argument } => self.pattern(argument),
+                    }
+                }
+            }
+            Pattern::AssignmentPattern { left, right } => {
+                self.pattern(left);
+                self.expression(right);
+            }
+            Pattern::RestElement { argument } => self.pattern(argument),
+        }
+    }
+
+    /// Walks a function body, declaring the temporaries it needs at its top
+    fn function_body(&mut self, body: &mut BlockStatement) {
+        self.temporaries.push(Vec::new());
+        self.statements(&mut body.body);
+        let temporaries = self.temporaries.pop().unwrap_or_default();
+        declare_temporaries(&mut body.body, temporaries);
+    }
+
+    /// Walks a function; parameter defaults can't see the body's `var`s, so their
+    /// temporaries belong to the enclosing function
+    fn function(&mut self, function: &mut FunctionExpression) {
+        self.patterns(&mut function.params);
+        self.function_body(&mut function.body);
+    }
+
+    /// Walks an expression bottom-up, lowering its operands before itself
+    fn expression(&mut self, expression: &mut Expression) {
+        match expression {
+            Expression::Identifier(_)
+            | Expression::Literal(_)
+            | Expression::ThisExpression
+            | Expression::MetaProperty { .. } => {}
+            Expression::BinaryExpression { left, right, .. } | Expression::AssignmentExpression { left, right, .. } => {
+                self.expression(left);
+                self.expression(right);
+            }
+            // The chain is lowered together with its `delete`
+            Expression::UnaryExpression { operator: UnaryOperator::Delete, argument, .. }
+                if matches!(argument.as_ref(), Expression::ChainExpression { .. }) =>
+            {
+                if let Expression::ChainExpression { expression: chain } = argument.as_mut() {
+                    self.expression(chain);
+                }
+            }
+            Expression::UnaryExpression { argument, .. }
+            | Expression::UpdateExpression { argument, .. }
+            | Expression::AwaitExpression { argument }
+            | Expression::ChainExpression { expression: argument }
+            | Expression::SpreadElement { argument } => self.expression(argument),
+            Expression::YieldExpression { argument, .. } => {
+                if let Some(argument) = argument {
+                    self.expression(argument);
+                }
+            }
+            Expression::CallExpression { callee, arguments, .. } | Expression::NewExpression { callee, arguments } => {
+                self.expression(callee);
+                for argument in arguments {
+                    self.expression(argument);
+                }
+            }
+            Expression::ImportExpression { source, options } => {
+                self.expression(source);
+                if let Some(options) = options {
+                    self.expression(options);
+                }
+            }
+            Expression::MemberExpression { object, property, .. } => {
+                self.expression(object);
+                self.expression(property);
+            }
+            Expression::FunctionExpression(function) => self.function(function),
+            Expression::ArrowFunctionExpression { params, body, .. } => {
+                self.patterns(params);
+                if let ArrowFunctionBody::Expression(expr) = body {
+                    // Temporaries need a body to be declared in
+                    self.temporaries.push(Vec::new());
+                    self.expression(expr);
+                    let temporaries = self.temporaries.pop().unwrap_or_default();
+                    if !temporaries.is_empty() {
+                        let expr = std::mem::replace(expr.as_mut(), Expression::ThisExpression);
+                        let mut block = BlockStatement { body: vec![Statement::ReturnStatement { argument: Some(expr), span: None }], span: None };
+                        declare_temporaries(&mut block.body, temporaries);
+                        *body = ArrowFunctionBody::BlockStatement(block);
+                    }
+                } else if let ArrowFunctionBody::BlockStatement(block) = body {
+                    self.function_body(block);
+                }
+            }
+            Expression::ObjectExpression { properties } => {
+                for property in properties {
+                    match property {
+                        ObjectProperty::Property { value, .. } => self.expression(value),
+                        ObjectProperty::SpreadElement { argument } => self.expression(argument),
+                    }
+                }
+            }
+            Expression::ArrayExpression { elements } => {
+                for element in elements.iter_mut().flatten() {
+                    self.expression(element);
+                }
+            }
+            Expression::TemplateLiteral { expressions, .. } => {
+                for expr in expressions {
+                    self.expression(expr);
+                }
+            }
+            Expression::TaggedTemplateExpression { tag, expressions, .. } => {
+                self.expression(tag);
+                for expr in expressions {
+                    self.expression(expr);
+                }
+            }
+            Expression::ConditionalExpression { test, consequent, alternate } => {
+                self.expression(test);
+                self.expression(consequent);
+                self.expression(alternate);
+            }
+        }
+        self.lower(expression);
+    }
+
+    /// Rewrites `expression` itself if the targets lack its syntax
+    fn lower(&mut self, expression: &mut Expression) {
+        if self.collecting {
+            if let Expression::Identifier(id) = expression {
+                self.used_names.insert(id.name.clone());
+            }
+            return;
+        }
+
+        match expression {
+            Expression::BinaryExpression { operator: BinaryOperator::NullishCoalescing, .. } if self.nullish_coalescing => {
+                let Expression::BinaryExpression { left, right, .. } = take(expression) else {
+                    unreachable!();
+                };
+                let (assign, reference) = self.memoize(*left);
+                *expression = Expression::ConditionalExpression {
+                    test: Box::new(binary(assign, BinaryOperator::NotEqual, Expression::Literal(Literal::Null))),
+                    consequent: Box::new(reference),
+                    alternate: right,
+                };
+                self.lowered += 1;
+            }
+            Expression::AssignmentExpression {
+                operator:
+                    AssignmentOperator::LogicalAndAssign
+                    | AssignmentOperator::LogicalOrAssign
+                    | AssignmentOperator::NullishCoalescingAssign,
+                ..
+            } if self.logical_assignment => {
+                let Expression::AssignmentExpression { left, operator, right } = take(expression) else {
+                    unreachable!();
+                };
+                let operator = match operator {
+                    AssignmentOperator::LogicalAndAssign => BinaryOperator::LogicalAnd,
+                    AssignmentOperator::LogicalOrAssign => BinaryOperator::LogicalOr,
+                    _ => BinaryOperator::NullishCoalescing,
+                };
+                let (read, write) = self.memoize_target(*left);
+                let assignment = Expression::AssignmentExpression {
+                    left: Box::new(write),
+                    operator: AssignmentOperator::Assign,
+                    right,
+                };
+                *expression = binary(read, operator, assignment);
+                self.lowered += 1;
+                self.lower(expression);
+            }
+            Expression::ChainExpression { .. } if self.optional_chaining => {
+                let Expression::ChainExpression { expression: chain } = take(expression) else {
+                    unreachable!();
+                };
+                *expression = self.lower_chain(*chain, false);
+                self.lowered += 1;
+            }
+            Expression::UnaryExpression { operator: UnaryOperator::Delete, argument, .. }
+                if self.optional_chaining && matches!(argument.as_ref(), Expression::ChainExpression { .. }) =>
+            {
+                let Expression::UnaryExpression { argument, .. } = take(expression) else {
+                    unreachable!();
+                };
+                let Expression::ChainExpression { expression: chain } = *argument else {
+                    unreachable!();
+                };
+                *expression = self.lower_chain(*chain, true);
+                self.lowered += 1;
+            }
+            _ => {}
+        }
+    }
+
+    /// Rewrites the contents of an optional chain into a conditional expression, which
+    /// deletes the chain's target instead of reading it when `delete` is set
+    fn lower_chain(&mut self, chain: Expression, delete: bool) -> Expression {
+        let mut links = Vec::new();
+        let mut current = flatten_chain(chain, &mut links);
+        let mut tests = Vec::new();
+
+        for link in links {
+            match link {
+                ChainLink::Member { property, computed, optional } => {
+                    if optional {
+                        let (assign, reference) = self.memoize(current);
+                        tests.push(is_nullish(assign));
+                        current = reference;
+                    }
+                    current = member(current, property, computed);
+                }
+                ChainLink::Call { arguments, optional: true } => {
+                    current = match current {
+                        // `o.f?.()` must still call `f` with `this` set to `o`
+                        Expression::MemberExpression { object, property, computed, .. } => {
+                            let (object_assign, object_reference) = self.memoize(*object);
+                            let (callee_assign, callee_reference) = self.memoize(member(object_assign, *property, computed));
+                            tests.push(is_nullish(callee_assign));
+                            let call = member(callee_reference, Expression::Identifier(identifier("call")), false);
+                            let arguments = std::iter::once(object_reference).chain(arguments).collect();
+                            Expression::CallExpression { callee: Box::new(call), arguments, optional: false }
+                        }
+                        callee => {
+                            let (assign, reference) = self.memoize(callee);
+                            tests.push(is_nullish(assign));
+                            Expression::CallExpression { callee: Box::new(reference), arguments, optional: false }
+                        }
+                    };
+                }
+                ChainLink::Call { arguments, optional: false } => {
+                    current = Expression::CallExpression { callee: Box::new(current), arguments, optional: false };
+                }
+            }
+        }
+
+        // A short-circuited `delete` evaluates to `true`
+        let (short_circuit, current) = if delete {
+            let delete = Expression::UnaryExpression { operator: UnaryOperator::Delete, argument: Box::new(current), prefix: true };
+            (Expression::Literal(Literal::Boolean(BooleanLiteral { value: true, span: None })), delete)
+        } else {
+            (void_zero(), current)
+        };
+        match tests.into_iter().reduce(|left, right| binary(left, BinaryOperator::LogicalOr, right)) {
+            Some(test) => Expression::ConditionalExpression {
+                test: Box::new(test),
+                consequent: Box::new(short_circuit),
+                alternate: Box::new(current),
+            },
+            None => current,
+        }
+    }
+
+    /// Splits `expression` into a form that evaluates it (the first) and one that reads
+    /// the value again (the second), through a temporary unless re-reading is free
+    fn memoize(&mut self, expression: Expression) -> (Expression, Expression) {
+        match expression {
+            Expression::Identifier(_) | Expression::ThisExpression => (expression.clone(), expression),
+            expression => {
+                let temporary = self.temporary();
+                let assign = Expression::AssignmentExpression {
+                    left: Box::new(Expression::Identifier(temporary.clone())),
+                    operator: AssignmentOperator::Assign,
+                    right: Box::new(expression),
+                };
+                (assign, Expression::Identifier(temporary))
+            }
+        }
+    }
+
+    /// Splits an assignment target into a read that evaluates its object and key once, and
+    /// the target to write to afterwards
+    fn memoize_target(&mut self, target: Expression) -> (Expression, Expression) {
+        match target {
+            Expression::MemberExpression { object, property, computed, .. } => {
+                let (object_assign, object_reference) = self.memoize(*object);
+                let (property_assign, property_reference) = match *property {
+                    property @ Expression::Literal(_) => (property.clone(), property),
+                    property if computed => self.memoize(property),
+                    property => (property.clone(), property),
+                };
+                (
+                    member(object_assign, property_assign, computed),
+                    member(object_reference, property_reference, computed),
+                )
+            }
+            target => (target.clone(), target),
+        }
+    }
+
+    /// Allocates a temporary in the innermost function
+    fn temporary(&mut self) -> Identifier {
+        loop {
+            let name = format!("_{}", self.names.next().expect("name generator is unbounded"));
+            if !self.used_names.contains(&name) && !self.symbol_table.is_declared(&name) {
+                self.used_names.insert(name.clone());
+                if let Some(temporaries) = self.temporaries.last_mut() {
+                    temporaries.push(name.clone());
+                }
+                return identifier(&name);
+            }
+        }
+    }
+}
+
+/// Splits a chain into its innermost object and the links applied to it, in order
+fn flatten_chain(expression: Expression, links: &mut Vec<ChainLink>) -> Expression {
+    match expression {
+        Expression::MemberExpression { object, property, computed, optional } => {
+            let base = flatten_chain(*object, links);
+            links.push(ChainLink::Member { property: *property, computed, optional });
+            base
+        }
+        Expression::CallExpression { callee, arguments, optional } => {
+            let base = flatten_chain(*callee, links);
+            links.push(ChainLink::Call { arguments, optional });
+            base
+        }
+        base => base,
+    }
+}
+
+/// Inserts `var <temporaries>` at the top of a body
+fn declare_temporaries(body: &mut Vec<Statement>, temporaries: Vec<String>) {
+    if temporaries.is_empty() {
+        return;
+    }
+    let declarations = temporaries
+        .iter()
+        .map(|name| VariableDeclarator { id: Pattern::Identifier(identifier(name)), init: None, span: None })
+        .collect();
+    body.insert(0, Statement::VariableDeclaration { declarations, kind: VariableDeclarationKind::Var, span: None });
+}
+
+/// Moves the expression out, leaving a placeholder
+fn take(expression: &mut Expression) -> Expression {
+    std::mem::replace(expression, Expression::ThisExpression)
+}
+
+fn identifier(name: &str) -> Identifier {
+    Identifier { name: name.to_string(), span: None }
+}
+
+fn binary(left: Expression, operator: BinaryOperator, right: Expression) -> Expression {
+    Expression::BinaryExpression { left: Box::new(left), operator, right: Box::new(right) }
+}
+
+fn member(object: Expression, property: Expression, computed: bool) -> Expression {
+    Expression::MemberExpression { object: Box::new(object), property: Box::new(property), computed, optional: false }
+}
+
+fn is_nullish(expression: Expression) -> Expression {
+    binary(expression, BinaryOperator::Equal, Expression::Literal(Literal::Null))
+}
+
+fn void_zero() -> Expression {
+    Expression::UnaryExpression {
+        operator: UnaryOperator::Void,
+        argument: Box::new(Expression::Literal(Literal::Number(NumberLiteral { value: 0.0, raw: None, span: None }))),
+        prefix: true,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::analyzer::{analyze_ast, AnalyzerConfig};
+    use crate::generator::{Generator, GeneratorConfig};
+    use crate::parser::{parse_js, ParserConfig};
+    use crate::target::Targets;
+
+    fn lower(source: &str, target: &str) -> (String, u32) {
+        let mut program = parse_js(source, "test.js", &ParserConfig::default()).ast.unwrap();
+        let analysis = analyze_ast(&program, &AnalyzerConfig::default()).unwrap();
+        let config = TransformerConfig::default().with_target(Targets::parse(target).unwrap());
+        let lowered = lower_syntax(&mut program, &analysis.symbol_table, &config);
+        let generator = Generator::new(GeneratorConfig { target: config.target.clone(), ..Default::default() });
+        (generator.generate(&program, None).unwrap().code, lowered)
+    }
+
+    #[test]
+    fn test_optional_chains_and_nullish_coalescing_are_lowered() {
+        let (code, lowered) = lower(
+            "const a = user?.profile.name;\nconst b = user.get?.(1);\nconst c = load()?.[key]?.();\nconst d = options.size ?? 10;\nconst e = x ?? y ?? z;\n",
+            "es2019",
+        );
+        assert_eq!(
+            code,
+            "var _a, _b, _c, _d, _e;const a=user==null?void 0:user.profile.name;\
+             const b=(_a=user.get)==null?void 0:_a.call(user,1);\
+             const c=(_b=load())==null||(_c=_b[key])==null?void 0:_c.call(_b);\
+             const d=(_d=options.size)!=null?_d:10;\
+             const e=(_e=x!=null?x:y)!=null?_e:z;"
+        );
+        assert_eq!(lowered, 6);
+
+        // Deleting through a chain deletes the target, and short-circuits to `true`
+        let (code, lowered) = lower("delete o?.x;\ndelete load()?.a.b;\n", "es2019");
+        assert_eq!(code, "var _a;o==null?true:delete o.x;(_a=load())==null?true:delete _a.a.b;");
+        assert_eq!(lowered, 2);
+
+        // Targets with the syntax keep it
+        let (code, lowered) = lower("const a = user?.profile ?? fallback;\n", "chrome90");
+        assert_eq!(code, "const a=user?.profile??fallback;");
+        assert_eq!(lowered, 0);
+    }
+
+    #[test]
+    fn test_logical_assignment_and_temporaries() {
+        let (code, _) = lower("function f(o, _a) { o.cache[key()] ??= 0; }\nx ||= 1;\n", "es2019");
+        assert_eq!(code, "function f(o, _a){var _b, _c, _d;(_d=(_b=o.cache)[_c=key()])!=null?_d:_b[_c]=0;}x||(x=1);");
+
+        // `??` is available, logical assignment is not (Chrome 80-84)
+        let (code, _) = lower("x ??= 1;\n", "chrome80");
+        assert_eq!(code, "x??(x=1);");
+    }
+}
@@ -3,9 +3,30 @@
 //! Tests for the complete transformation pipeline
 
 use super::*;
-use crate::analyzer::{SemanticAnalysis, SymbolTable, SemanticFlags, AnalysisMetadata, ScopeTree, ScopeType};
+use crate::analyzer::{analyze_ast, AnalyzerConfig, SemanticAnalysis, SymbolTable, SemanticFlags, AnalysisMetadata, ScopeTree, ScopeType};
+use crate::generator::{Generator, GeneratorConfig};
 use crate::parser::ast_types::{Program, ProgramSourceType, Statement, Expression};
-use std::collections::HashMap;
+use crate::parser::{parse_js, ParserConfig};
+use std::collections::BTreeMap;
+
+/// Parses and analyzes `source`, runs `pass` over it with `config` and prints the
+/// result, returning the output and what the pass reported
+///
+/// Shared by the unit tests of the individual passes.
+pub(crate) fn run_pass<R>(
+    source: &str,
+    config: &TransformerConfig,
+    pass: impl FnOnce(&mut Program, &SemanticAnalysis, &TransformerConfig) -> TransformResult<R>,
+) -> (String, R) {
+    let parsed = parse_js(source, "test.js", &ParserConfig::default());
+    let mut ast = parsed.ast.unwrap();
+    let analyzer_config =
+        AnalyzerConfig { dropped_spans: parsed.dropped_spans, names: parsed.names, ..AnalyzerConfig::default() };
+    let analysis = analyze_ast(&ast, &analyzer_config).unwrap();
+    let result = pass(&mut ast, &analysis, config).unwrap();
+    let code = Generator::new(GeneratorConfig::default()).generate(&ast, None).unwrap().code;
+    (code, result)
+}
 
 /// Helper function to create a test analysis result
 fn create_test_analysis() -> SemanticAnalysis {
@@ -13,9 +34,10 @@ fn create_test_analysis() -> SemanticAnalysis {
         symbol_table: SymbolTable::new(),
         scope_tree: ScopeTree::new(ScopeType::Global),
         semantic_flags: SemanticFlags {
-            unsafe_scopes: HashMap::new(),
-            unsafe_symbols: HashMap::new(),
+            unsafe_scopes: BTreeMap::new(),
+            unsafe_symbols: BTreeMap::new(),
             global_references: Vec::new(),
+            global_exports: Default::default(),
         },
         metadata: AnalysisMetadata {
             scope_count: 1,
@@ -24,6 +46,10 @@ fn create_test_analysis() -> SemanticAnalysis {
             export_count: 0,
             analysis_time_ms: 0,
         },
+        module_record: Default::default(),
+        purity: Default::default(),
+        local_objects: Vec::new(),
+        local_classes: Vec::new(),
     }
 }
 
@@ -33,7 +59,7 @@ fn test_transformer_initialization() {
     let analysis_result = create_test_analysis();
     
     let transformer = Transformer::new(config, analysis_result);
-    assert_eq!(transformer.count_enabled_passes(), 5);
+    assert_eq!(transformer.count_enabled_passes(), 9);
 }
 
 #[test]
@@ -56,7 +82,6 @@ fn test_empty_program_transformation() {
 fn test_transformer_with_rollback_enabled() {
     let config = TransformerConfig {
         enable_rollback: true,
-        verbose: true,
         ..TransformerConfig::default()
     };
     let analysis_result = create_test_analysis();
@@ -96,6 +121,10 @@ fn test_selective_pass_enablement() {
         enable_dead_code_elimination: true,
         enable_expression_simplification: false,
         enable_property_minification: true,
+        enable_collapse_vars: false,
+        enable_inline_captures: false,
+        enable_if_return: false,
+        enable_cleanup: false,
         enable_function_minification: false,
         ..TransformerConfig::default()
     };
@@ -112,6 +141,11 @@ fn test_all_passes_disabled() {
         enable_dead_code_elimination: false,
         enable_expression_simplification: false,
         enable_property_minification: false,
+        inline_enums: false,
+        enable_collapse_vars: false,
+        enable_inline_captures: false,
+        enable_if_return: false,
+        enable_cleanup: false,
         enable_function_minification: false,
         ..TransformerConfig::default()
     };
@@ -169,7 +203,7 @@ fn test_transformation_warnings_collection() {
     
     // Should have warnings about not-yet-implemented features
     assert!(!result.warnings.is_empty());
-    assert!(result.warnings.iter().any(|w| w.contains("not yet")));
+    assert!(result.warnings.iter().any(|w| w.message.contains("not yet")));
 }
 
 #[test]
@@ -227,25 +261,6 @@ fn test_conservative_optimization_mode() {
     assert_eq!(result.transformed_ast.body.len(), 0);
 }
 
-#[test]
-fn test_verbose_transformation_output() {
-    let config = TransformerConfig {
-        verbose: true,
-        ..TransformerConfig::default()
-    };
-    let analysis_result = create_test_analysis();
-    
-    let mut transformer = Transformer::new(config, analysis_result);
-    let ast = Program {
-        body: vec![],
-        source_type: ProgramSourceType::Script,
-    };
-    
-    // This test mainly verifies that verbose mode doesn't cause crashes
-    let result = transformer.transform(ast).unwrap();
-    assert_eq!(result.transformed_ast.body.len(), 0);
-}
-
 #[test]
 fn test_multi_pass_execution_order() {
     let config = TransformerConfig::default();
@@ -272,19 +287,12 @@ fn test_ast_preservation_during_transformation() {
     
     let mut transformer = Transformer::new(config, analysis_result);
     
-    // Create an AST with some content
-    let original_ast = Program {
-        body: vec![
-            Statement::BlockStatement {
-                body: vec![]
-            }
-        ],
-        source_type: ProgramSourceType::Script,
-    };
+    // Create an AST with some content (a block scoping its declaration is kept by cleanup)
+    let original_ast = parse_program("{ let x = 1; }");
     
     let result = transformer.transform(original_ast).unwrap();
     
-    // AST structure should be preserved (placeholders don't modify)
+    // AST structure should be preserved
     assert_eq!(result.transformed_ast.body.len(), 1);
     assert!(matches!(result.transformed_ast.body[0], Statement::BlockStatement { .. }));
 }
@@ -319,4 +327,373 @@ fn test_convenience_transform_function() {
     let transform_result = result.unwrap();
     assert_eq!(transform_result.transformed_ast.body.len(), 0);
     assert!(transform_result.stats.transformation_time_ms >= 0);
-}
\ No newline at end of file
+}
+
+#[test]
+fn test_transform_ast_with_config_respects_config() {
+    let config = TransformerConfig::default()
+        .with_identifier_renaming(false)
+        .with_dead_code_elimination(false)
+        .with_collapse_vars(false)
+        .with_inline_captures(false)
+        .with_if_return(false)
+        .with_cleanup(false)
+        .with_property_minification(false)
+        .with_inline_enums(false)
+        .with_function_minification(false)
+        .with_worker_threads(1);
+    assert!(!config.enable_identifier_renaming && config.enable_expression_simplification);
+
+    let result = transform_ast_with_config(
+        parse_program("let x = Math.max(1, 2);"),
+        create_test_analysis(),
+        config,
+    )
+    .unwrap();
+    let pass_names: Vec<&str> = result.stats.pass_timings.iter()
+        .map(|timing| timing.pass_name.as_str())
+        .collect();
+
+    assert_eq!(pass_names, vec!["expression_simplification"]);
+    assert_eq!(result.stats.expressions_simplified, 1);
+}
+
+#[test]
+fn test_pass_timings_recorded_for_enabled_passes() {
+    let config = TransformerConfig {
+        enable_identifier_renaming: false,
+        enable_property_minification: false,
+        inline_enums: false,
+        enable_collapse_vars: false,
+        enable_inline_captures: false,
+        enable_if_return: false,
+        enable_cleanup: false,
+        ..TransformerConfig::default()
+    };
+    let analysis_result = create_test_analysis();
+    
+    let mut transformer = Transformer::new(config, analysis_result);
+    let ast = Program {
+        body: vec![],
+        source_type: ProgramSourceType::Script,
+    };
+    
+    let result = transformer.transform(ast).unwrap();
+    let pass_names: Vec<&str> = result.stats.pass_timings.iter()
+        .map(|timing| timing.pass_name.as_str())
+        .collect();
+    
+    assert_eq!(pass_names, vec![
+        "dead_code_elimination",
+        "expression_simplification",
+        "function_minification",
+    ]);
+    assert!(result.stats.pass_timings.iter().all(|timing| timing.time_ms >= 0.0));
+}
+
+#[test]
+fn test_checkpoints_released_after_successful_passes() {
+    let analysis_result = create_test_analysis();
+    let mut transformer = Transformer::new(TransformerConfig::default(), analysis_result);
+    let ast = Program {
+        body: vec![],
+        source_type: ProgramSourceType::Script,
+    };
+    
+    transformer.transform(ast).unwrap();
+    assert_eq!(transformer.rollback_manager.checkpoint_count(), 0);
+}
+
+/// Parses `source` into an owned AST
+fn parse_program(source: &str) -> Program {
+    crate::parser::parse_js(source, "test.js", &crate::parser::ParserConfig::default())
+        .ast
+        .unwrap()
+}
+
+#[test]
+fn test_dead_code_elimination_reruns_after_simplification() {
+    let mut transformer = Transformer::new(TransformerConfig::default(), create_test_analysis());
+    let result = transformer.transform(parse_program("let x = Math.max(1, 2);")).unwrap();
+    let pass_names: Vec<&str> = result.stats.pass_timings.iter()
+        .map(|timing| timing.pass_name.as_str())
+        .collect();
+
+    assert_eq!(pass_names, vec![
+        "identifier_renaming",
+        "dead_code_elimination",
+        "expression_simplification",
+        "collapse_vars",
+        "inline_captures",
+        "if_return",
+        "cleanup",
+        "property_minification",
+        "function_minification",
+        "dead_code_elimination",
+    ]);
+    assert_eq!(result.stats.pass_iterations, 2);
+    assert_eq!(result.stats.expressions_simplified, 1);
+
+    // The folded output re-parses, so validation keeps it
+    assert_eq!(result.stats.rollbacks_performed, 0);
+    assert!(!result.warnings.iter().any(|w| w.message.starts_with("Rolled back")));
+
+    // Placeholder warnings are reported once even though the pass ran twice
+    let dce_warnings = result.warnings.iter().filter(|w| w.message.starts_with("Dead code")).count();
+    assert_eq!(dce_warnings, 1);
+}
+
+#[test]
+fn test_configured_pass_order() {
+    let config = TransformerConfig {
+        pass_order: vec![BuiltinPass::ExpressionSimplification, BuiltinPass::DeadCodeElimination],
+        enable_identifier_renaming: false,
+        enable_property_minification: false,
+        inline_enums: false,
+        enable_collapse_vars: false,
+        enable_inline_captures: false,
+        enable_if_return: false,
+        enable_cleanup: false,
+        enable_function_minification: false,
+        ..TransformerConfig::default()
+    };
+    let mut transformer = Transformer::new(config, create_test_analysis());
+    let result = transformer.transform(parse_program("let x = Math.max(1, 2);")).unwrap();
+    let pass_names: Vec<&str> = result.stats.pass_timings.iter()
+        .map(|timing| timing.pass_name.as_str())
+        .collect();
+
+    // Simplification ran first, so its invalidation of DCE is handled in the same round
+    assert_eq!(pass_names, vec!["expression_simplification", "dead_code_elimination"]);
+    assert_eq!(result.stats.pass_iterations, 1);
+
+    let invalid = TransformerConfig {
+        pass_order: vec![BuiltinPass::FunctionMinification],
+        ..TransformerConfig::default()
+    };
+    let mut transformer = Transformer::new(invalid, create_test_analysis());
+    assert!(matches!(
+        transformer.transform(Program { body: vec![], source_type: ProgramSourceType::Script }),
+        Err(TransformError::InvalidPassOrder(_))
+    ));
+}
+
+/// Runs the analyzer, transformer and generator over `source`
+fn minify_with(source: &str, config: TransformerConfig) -> String {
+    let ast = parse_program(source);
+    let analysis = crate::analyzer::analyze_ast(&ast, &crate::analyzer::AnalyzerConfig::default()).unwrap();
+    let result = Transformer::new(config, analysis).transform(ast).unwrap();
+    crate::generator::Generator::new(crate::generator::GeneratorConfig::default())
+        .generate(&result.transformed_ast, None)
+        .unwrap()
+        .code
+}
+
+#[test]
+fn test_output_is_deterministic_and_seedable() {
+    let source = "const label = 'a repeated label text'; export const first = label + 'a repeated label text';\n\
+                  function configure() { const options = { alpha: 1, beta: 2, gamma: 3 }; return options.alpha + options.beta + options.gamma; }\n\
+                  function settings() { const values = { width: 1, height: 2 }; return values.width * values.height; }\n\
+                  export function second() { return configure() + settings() + 'a repeated label text'; }";
+    let config = |seed| {
+        TransformerConfig::default()
+            .with_module_exports(crate::transformer::export_mangling::ModuleExports::Mangle)
+            .with_dedupe_strings(Some(1))
+            .with_name_seed(seed)
+    };
+
+    // Fresh hash maps use new random states, so any order leaking into names shows up
+    let unseeded = minify_with(source, config(None));
+    let seeded = minify_with(source, config(Some(1637)));
+    for _ in 0..8 {
+        assert_eq!(minify_with(source, config(None)), unseeded);
+        assert_eq!(minify_with(source, config(Some(1637))), seeded);
+    }
+    assert_ne!(seeded, unseeded);
+}
+
+#[test]
+fn test_star_reexports_keep_exported_names() {
+    let config = || TransformerConfig::default().with_module_exports(crate::transformer::export_mangling::ModuleExports::Mangle);
+    let transform = |source: &str| {
+        let ast = parse_program(source);
+        let analysis = crate::analyzer::analyze_ast(&ast, &crate::analyzer::AnalyzerConfig::default()).unwrap();
+        Transformer::new(config(), analysis).transform(ast).unwrap()
+    };
+
+    // `a` could be one of the names `./other.js` exports
+    let result = transform("export const value = 1;\nexport * from './other.js';\n");
+    assert!(result.export_map.is_empty(), "{:?}", result.export_map);
+    let warning = result.warnings.iter().find(|warning| warning.message.starts_with("Exports are not mangled")).unwrap();
+    assert_eq!(warning.severity, diagnostics::Severity::Warning);
+    assert!(warning.message.contains("'./other.js'"), "{}", warning.message);
+    assert_eq!(warning.span.as_ref().map(|span| span.start), Some(24));
+
+    // A namespace re-export names its export, and keeps that name
+    let result = transform("export const value = 1;\nexport * as a from './other.js';\n");
+    assert_eq!(result.export_map, BTreeMap::from([("value".to_string(), "b".to_string())]));
+}
+
+#[test]
+fn test_rjs_disable_directive_protects_statement() {
+    let source = "let x = Math.max(1, 2);\n// rjs-disable-next-line compress\nlet y = Math.max(3, 4);\n";
+    let allocator = crate::parser::Allocator::default();
+    let parsed = crate::parser::parse_js_in(source, "test.js", &allocator, &crate::parser::ParserConfig::default());
+    let directives = directives::PassDirectives::from_trivia(source, &parsed.trivia(), &parsed.statement_spans());
+
+    let mut transformer = Transformer::new(TransformerConfig::default(), create_test_analysis());
+    transformer.set_directives(directives);
+    let result = transformer.transform(parsed.to_owned_ast().unwrap()).unwrap();
+    let code = crate::generator::Generator::new(crate::generator::GeneratorConfig::default())
+        .generate(&result.transformed_ast, None)
+        .unwrap()
+        .code;
+
+    assert!(code.contains("let x=2;"), "{}", code);
+    assert!(code.contains("let y=Math.max(3,4);"), "{}", code);
+    assert_eq!(result.stats.rollbacks_performed, 0);
+}
+
+mod plugin_tests {
+    use super::*;
+    use crate::parser::ast_types::{Identifier, Literal, NumberLiteral};
+    use crate::transformer::plugin::{PluginContext, PluginPosition, TransformPlugin};
+
+    /// Replaces every `DEBUG` identifier with `0`
+    struct InlineDebug;
+
+    impl TransformPlugin for InlineDebug {
+        fn name(&self) -> &str {
+            "inline-debug"
+        }
+
+        fn enter_expression(&mut self, expr: &mut Expression, ctx: &mut PluginContext) -> TransformResult<()> {
+            if matches!(expr, Expression::Identifier(Identifier { name, .. }) if name == "DEBUG") {
+                *expr = Expression::Literal(Literal::Number(NumberLiteral { value: 0.0, raw: None, span: None }));
+                ctx.record_change();
+            }
+            Ok(())
+        }
+    }
+
+    /// Counts visited nodes and reports a warning when done
+    struct NodeCounter {
+        entered: u32,
+        exited: u32,
+    }
+
+    impl TransformPlugin for NodeCounter {
+        fn name(&self) -> &str {
+            "node-counter"
+        }
+
+        fn enter_statement(&mut self, _stmt: &mut Statement, _ctx: &mut PluginContext) -> TransformResult<()> {
+            self.entered += 1;
+            Ok(())
+        }
+
+        fn exit_expression(&mut self, _expr: &mut Expression, _ctx: &mut PluginContext) -> TransformResult<()> {
+            self.exited += 1;
+            Ok(())
+        }
+
+        fn exit_program(&mut self, _program: &mut Program, ctx: &mut PluginContext) -> TransformResult<()> {
+            ctx.warn(format!("{} statements, {} expressions", self.entered, self.exited));
+            Ok(())
+        }
+    }
+
+    struct Failing;
+
+    impl TransformPlugin for Failing {
+        fn name(&self) -> &str {
+            "failing"
+        }
+
+        fn enter_program(&mut self, _program: &mut Program, _ctx: &mut PluginContext) -> TransformResult<()> {
+            Err(TransformError::InvalidState("unsupported input".to_string()))
+        }
+    }
+
+    /// `if (x) { f(DEBUG + 1); }`
+    fn debug_program() -> Program {
+        let call = Expression::CallExpression {
+            callee: Box::new(Expression::Identifier(Identifier { name: "f".to_string(), span: None })),
+            arguments: vec![Expression::BinaryExpression {
+                left: Box::new(Expression::Identifier(Identifier { name: "DEBUG".to_string(), span: None })),
+                operator: crate::parser::ast_types::BinaryOperator::Add,
+                right: Box::new(Expression::Literal(Literal::Number(NumberLiteral { value: 1.0, raw: None, span: None }))),
+            }],
+            optional: false,
+        };
+        Program {
+            body: vec![Statement::IfStatement {
+                test: Expression::Identifier(Identifier { name: "x".to_string(), span: None }),
+                consequent: Box::new(Statement::BlockStatement {
+                    body: vec![Statement::ExpressionStatement { expression: call, span: None }],
+                    span: None,
+                }),
+                alternate: None,
+                span: None,
+            }],
+            source_type: ProgramSourceType::Script,
+        }
+    }
+
+    fn plugin_only_config() -> TransformerConfig {
+        TransformerConfig {
+            enable_identifier_renaming: false,
+            enable_dead_code_elimination: false,
+            enable_property_minification: false,
+            inline_enums: false,
+            enable_collapse_vars: false,
+            enable_inline_captures: false,
+            enable_if_return: false,
+            enable_cleanup: false,
+            enable_function_minification: false,
+            ..TransformerConfig::default()
+        }
+    }
+
+    #[test]
+    fn test_plugin_runs_at_registered_position() {
+        let mut transformer = Transformer::new(plugin_only_config(), create_test_analysis());
+        transformer.register_plugin(
+            Box::new(InlineDebug),
+            PluginPosition::Before(BuiltinPass::ExpressionSimplification),
+        );
+
+        let result = transformer.transform(debug_program()).unwrap();
+        let pass_names: Vec<&str> = result.stats.pass_timings.iter()
+            .map(|timing| timing.pass_name.as_str())
+            .collect();
+
+        assert_eq!(pass_names, vec!["inline-debug", "expression_simplification"]);
+        assert_eq!(result.stats.plugin_changes, 1);
+        assert!(!format!("{:?}", result.transformed_ast).contains("DEBUG"));
+    }
+
+    #[test]
+    fn test_plugin_visits_nested_nodes_and_reports_warnings() {
+        let mut transformer = Transformer::new(plugin_only_config(), create_test_analysis());
+        transformer.register_plugin(
+            Box::new(NodeCounter { entered: 0, exited: 0 }),
+            PluginPosition::After(BuiltinPass::FunctionMinification),
+        );
+
+        let result = transformer.transform(debug_program()).unwrap();
+
+        // if + block + expression statement; x, f, DEBUG, 1, the binary and the call
+        assert!(result.warnings.contains(&Diagnostic::warning("3 statements, 6 expressions").with_pass("node-counter")));
+    }
+
+    #[test]
+    fn test_plugin_errors_name_the_plugin() {
+        let mut transformer = Transformer::new(plugin_only_config(), create_test_analysis());
+        transformer.register_plugin(Box::new(Failing), PluginPosition::Before(BuiltinPass::IdentifierRenaming));
+
+        let error = transformer.transform(debug_program()).unwrap_err();
+        assert!(matches!(&error, TransformError::PluginError { plugin, .. } if plugin == "failing"));
+        assert!(error.to_string().contains("unsupported input"));
+    }
+}
@@ -0,0 +1,90 @@
+//! # Worker URLs
+//!
+//! Points the worker entries a module starts at their compiled outputs. The specifier of
+//! each `new Worker(new URL("./w.js", import.meta.url))` (see
+//! [`worker_entry`](crate::analyzer::module_record::worker_entry)) that is a key of
+//! `TransformerConfig::worker_urls` is replaced by the mapped URL, so the compiled module
+//! loads the compiled worker (`./w.min.js`) instead of its source.
+
+use std::collections::BTreeMap;
+
+use crate::analyzer::module_record::worker_entry;
+use crate::analyzer::SemanticAnalysis;
+use crate::parser::ast_types::{Expression, Literal, Program, StringLiteral};
+use crate::transformer::plugin::{self, PluginContext, TransformPlugin};
+use crate::transformer::{TransformResult, TransformerConfig};
+
+/// Replaces the specifiers of the worker entries in `config.worker_urls` with their
+/// URLs; returns how many were replaced
+///
+/// # Errors
+///
+/// Propagates the errors of the traversal.
+pub fn rewrite_worker_urls(program: &mut Program, analysis: &SemanticAnalysis, config: &TransformerConfig) -> TransformResult<usize> {
+    if config.worker_urls.is_empty() {
+        return Ok(0);
+    }
+    let mut ctx = PluginContext::new(analysis, config);
+    let mut rewriter = WorkerUrlRewriter { urls: &config.worker_urls };
+    plugin::run_plugin(&mut rewriter, program, &mut ctx)?;
+    Ok(ctx.changes() as usize)
+}
+
+/// Points each worker entry with a URL in `urls` at that URL
+struct WorkerUrlRewriter<'a> {
+    urls: &'a BTreeMap<String, String>,
+}
+
+impl TransformPlugin for WorkerUrlRewriter<'_> {
+    fn name(&self) -> &str {
+        "worker_urls"
+    }
+
+    fn enter_expression(&mut self, expr: &mut Expression, ctx: &mut PluginContext) -> TransformResult<()> {
+        let Some(url) = worker_entry(expr).and_then(|specifier| self.urls.get(&specifier.value)) else {
+            return Ok(());
+        };
+        // `worker_entry` matched `new Worker(new URL(specifier, …), …)`
+        if let Expression::NewExpression { arguments, .. } = expr
+            && let Some(Expression::NewExpression { arguments, .. }) = arguments.first_mut()
+            && let Some(Expression::Literal(Literal::String(specifier))) = arguments.first_mut()
+        {
+            *specifier = StringLiteral::new(url.clone(), specifier.span.clone());
+            ctx.record_change();
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::analyzer::{analyze_ast, AnalyzerConfig};
+    use crate::generator::{Generator, GeneratorConfig};
+    use crate::parser::{parse_js, ParserConfig};
+
+    #[test]
+    fn test_rewrites_worker_entries() {
+        let source = "const w = new Worker(new URL('./w.js', import.meta.url), { type: 'module' });\n\
+                      const s = () => new SharedWorker(new URL('./s.js', import.meta.url));\n\
+                      const o = new Worker(new URL('./other.js', import.meta.url));\n\
+                      const r = new Worker(new URL('./w.js', location.href));\n";
+        let mut program = parse_js(source, "app.mjs", &ParserConfig::default()).ast.unwrap();
+        let analysis = analyze_ast(&program, &AnalyzerConfig::default()).unwrap();
+        let config = TransformerConfig {
+            worker_urls: BTreeMap::from([
+                ("./w.js".to_string(), "./w.min.js".to_string()),
+                ("./s.js".to_string(), "./workers/s.min.js".to_string()),
+            ]),
+            ..TransformerConfig::default()
+        };
+
+        assert_eq!(rewrite_worker_urls(&mut program, &analysis, &config).unwrap(), 2);
+        let code = Generator::new(GeneratorConfig::default()).generate(&program, None).unwrap().code;
+        assert!(code.contains("new Worker(new URL('./w.min.js',import.meta.url),{type:'module'})"), "{}", code);
+        assert!(code.contains("()=>new SharedWorker(new URL('./workers/s.min.js',import.meta.url))"), "{}", code);
+        assert!(code.contains("new URL('./other.js',import.meta.url)"), "{}", code);
+        // Only URLs resolved against the module itself name a file next to it
+        assert!(code.contains("new URL('./w.js',location.href)"), "{}", code);
+    }
+}
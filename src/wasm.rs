@@ -0,0 +1,59 @@
+//! # WebAssembly Bindings
+//!
+//! JavaScript-facing API exported with `wasm-bindgen` when the `wasm` feature is
+//! enabled. Build the npm package with:
+//!
+//! ```bash
+//! wasm-pack build --target bundler -- --features wasm   # bundlers
+//! wasm-pack build --target nodejs -- --features wasm    # Node.js
+//! ```
+//!
+//! ```js
+//! import { minify } from "rjs-compiler";
+//!
+//! const { code, map, warnings } = minify("let answer = 42;", { sourceMap: true });
+//! ```
+
+use serde::Serialize;
+use wasm_bindgen::prelude::*;
+
+use crate::{MinifyOptions, MinifyOutput};
+
+#[wasm_bindgen(typescript_custom_section)]
+const TS_TYPES: &'static str = r#"
+export interface MinifyOptions {
+    filename?: string;
+    sourceMap?: boolean;
+    sourceMapIgnoreList?: string[];
+}
+
+export interface MinifyResult {
+    code: string;
+    map: string | null;
+    warnings: string[];
+}
+
+export function minify(code: string, options?: MinifyOptions): MinifyResult;
+"#;
+
+/// Minifies JavaScript source code
+///
+/// `options` may be `undefined`, `null` or a `MinifyOptions` object. Returns
+/// `{ code, map, warnings }`, or throws an `Error` when compilation fails.
+#[wasm_bindgen(js_name = minify, skip_typescript)]
+pub fn minify_js(code: &str, options: JsValue) -> Result<JsValue, JsError> {
+    let options: MinifyOptions = if options.is_undefined() || options.is_null() {
+        MinifyOptions::default()
+    } else {
+        serde_wasm_bindgen::from_value(options)
+            .map_err(|e| JsError::new(&format!("Invalid options: {}", e)))?
+    };
+
+    let output: MinifyOutput = crate::minify(code, &options)?;
+
+    // JSON-compatible serialization turns `None` into `null` rather than `undefined`
+    let serializer = serde_wasm_bindgen::Serializer::json_compatible();
+    output
+        .serialize(&serializer)
+        .map_err(|e| JsError::new(&e.to_string()))
+}
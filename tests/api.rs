@@ -0,0 +1,85 @@
+//! # Library API Tests
+//!
+//! Exercises the `minify` entry point shared by the Rust library and the WebAssembly
+//! bindings, including the camelCase option names used from JavaScript, and the
+//! cancellation of the pipeline phases.
+
+use std::time::Duration;
+
+use rjs_compiler::analyzer::{analyze_ast, AnalysisError, AnalyzerConfig};
+use rjs_compiler::cancel::{CancellationToken, Cancelled};
+use rjs_compiler::generator::{Generator, GeneratorConfig, GeneratorError};
+use rjs_compiler::parser::{parse_js, ParserConfig};
+use rjs_compiler::transformer::{transform_ast_with_config, TransformError, TransformerConfig};
+use rjs_compiler::{compile_source, minify, minify_with_cancellation, CompileError, MinifyOptions};
+
+const SOURCE: &str = "let answer = Math.max(40, 42);\nconsole.log(answer);\n";
+
+#[test]
+fn test_minify_matches_compile_source() {
+    let output = minify(SOURCE, &MinifyOptions::default()).unwrap();
+
+    assert_eq!(output.code, compile_source(SOURCE, "input.js").unwrap());
+    assert!(output.map.is_none());
+    // The notes about what the passes did are not warnings
+    assert!(output.warnings.is_empty(), "{:?}", output.warnings);
+}
+
+#[test]
+fn test_minify_options_from_javascript_names() {
+    let options: MinifyOptions =
+        serde_json::from_str(r#"{"filename": "app.js", "sourceMap": true}"#).unwrap();
+    assert_eq!(options.filename.as_deref(), Some("app.js"));
+
+    let output = minify(SOURCE, &options).unwrap();
+    let map: serde_json::Value = serde_json::from_str(output.map.as_deref().unwrap()).unwrap();
+    assert_eq!(map["version"], 3);
+    assert_eq!(map["sources"][0], "app.js");
+    assert!(!map["mappings"].as_str().unwrap().is_empty());
+
+    let defaults: MinifyOptions = serde_json::from_str("{}").unwrap();
+    assert!(!defaults.source_map);
+}
+
+#[test]
+fn test_source_map_ignore_list_marks_matching_sources() {
+    let options: MinifyOptions = serde_json::from_str(
+        r#"{"filename": "vendor/jquery.js", "sourceMap": true, "sourceMapIgnoreList": ["vendor/**"]}"#,
+    )
+    .unwrap();
+    let map: serde_json::Value = serde_json::from_str(minify(SOURCE, &options).unwrap().map.as_deref().unwrap()).unwrap();
+    assert_eq!(map["ignoreList"], serde_json::json!([0]));
+    assert_eq!(map["x_google_ignoreList"], serde_json::json!([0]));
+
+    let options = MinifyOptions { filename: Some("src/app.js".to_string()), ..options };
+    let map: serde_json::Value = serde_json::from_str(minify(SOURCE, &options).unwrap().map.as_deref().unwrap()).unwrap();
+    assert!(map.get("ignoreList").is_none());
+}
+
+#[test]
+fn test_minify_reports_syntax_errors() {
+    let result = minify("let = ;", &MinifyOptions::default());
+    assert!(matches!(result, Err(CompileError::Parse(_))));
+}
+
+#[test]
+fn test_cancelled_compilations_stop_with_an_error() {
+    let token = CancellationToken::new();
+    token.cancel();
+    let error = minify_with_cancellation(SOURCE, &MinifyOptions::default(), &token).unwrap_err();
+    assert_eq!(error.cancelled(), Some(Cancelled::Requested));
+    let expired = CancellationToken::new().with_timeout(Duration::ZERO);
+    let error = minify_with_cancellation(SOURCE, &MinifyOptions::default(), &expired).unwrap_err();
+    assert_eq!(error.cancelled(), Some(Cancelled::DeadlineExceeded { timeout_ms: 0 }));
+
+    // Each phase checks the token of its own configuration
+    let ast = parse_js(SOURCE, "input.js", &ParserConfig::default()).ast.unwrap();
+    let analyzer_config = AnalyzerConfig { cancellation: token.clone(), ..AnalyzerConfig::default() };
+    assert!(matches!(analyze_ast(&ast, &analyzer_config), Err(AnalysisError::Cancelled(Cancelled::Requested))));
+    let analysis = analyze_ast(&ast, &AnalyzerConfig::default()).unwrap();
+    let transformer_config = TransformerConfig::default().with_cancellation(token.clone());
+    let transformed = transform_ast_with_config(ast.clone(), analysis, transformer_config);
+    assert!(matches!(transformed, Err(TransformError::Cancelled(Cancelled::Requested))));
+    let generator = Generator::new(GeneratorConfig { cancellation: token, ..GeneratorConfig::default() });
+    assert!(matches!(generator.generate(&ast, None), Err(GeneratorError::Cancelled)));
+}
@@ -0,0 +1,1182 @@
+//! # Command-Line Interface Tests
+//!
+//! Runs the built `rjs-compiler` binary and checks the output of its subcommands.
+
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output};
+
+/// Path of the compiled binary under test
+const BINARY: &str = env!("CARGO_BIN_EXE_rjs-compiler");
+
+/// Writes `source` to a uniquely named file in the system temp directory
+fn write_temp_file(name: &str, source: &str) -> PathBuf {
+    let path = std::env::temp_dir().join(format!("rjs-cli-{}-{}", std::process::id(), name));
+    std::fs::write(&path, source).expect("temp file should be writable");
+    path
+}
+
+fn run(args: &[&str], file: &Path) -> Output {
+    Command::new(BINARY)
+        .args(args)
+        .arg(file)
+        .output()
+        .expect("binary should run")
+}
+
+#[test]
+fn test_analyze_json_dumps_semantic_model() {
+    let file = write_temp_file("analyze.js", "const base = 1;\nfunction read() { return base; }\n");
+    let output = run(&["analyze", "--json"], &file);
+    std::fs::remove_file(&file).ok();
+
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    let analysis: serde_json::Value = serde_json::from_slice(&output.stdout).expect("stdout should be JSON only");
+
+    let names: Vec<&str> = analysis["symbol_table"]["symbols"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|symbol| symbol["name"].as_str().unwrap())
+        .collect();
+    assert!(names.contains(&"base") && names.contains(&"read"));
+    assert_eq!(analysis["scope_tree"]["root_scope_id"], 0);
+    assert!(analysis["semantic_flags"]["unsafe_scopes"].is_object());
+}
+
+#[test]
+fn test_analyze_reports_syntax_errors() {
+    let file = write_temp_file("analyze-invalid.js", "let = ;");
+    let output = run(&["analyze"], &file);
+    std::fs::remove_file(&file).ok();
+
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("Parse error"));
+}
+
+#[test]
+fn test_ast_formats() {
+    let file = write_temp_file("ast.js", "let a = b;\n");
+    let pretty = run(&["ast"], &file);
+    let dot = run(&["ast", "--format", "dot"], &file);
+    let json = run(&["ast", "--format", "json"], &file);
+    std::fs::remove_file(&file).ok();
+
+    let pretty = String::from_utf8(pretty.stdout).unwrap();
+    assert!(pretty.starts_with("Program"));
+    assert!(pretty.contains("└── body[0]: VariableDeclaration"));
+
+    assert!(String::from_utf8(dot.stdout).unwrap().starts_with("digraph AST {"));
+
+    let json: serde_json::Value = serde_json::from_slice(&json.stdout).expect("stdout should be JSON only");
+    assert_eq!(json["body"][0]["type"], "VariableDeclaration");
+}
+
+#[test]
+fn test_pass_flags_select_transformer_passes() {
+    let file = write_temp_file("passes.js", "let a = Math.max(1, 2);\n");
+    let out = std::env::temp_dir().join(format!("rjs-cli-{}-passes.min.js", std::process::id()));
+    let out_arg = out.to_str().unwrap();
+    let default = run(&["-v", "-o", out_arg], &file);
+    let selected = run(&["-v", "--no-mangle", "--no-dce", "--mangle-props", "--aggressive", "-o", out_arg], &file);
+    std::fs::remove_file(&file).ok();
+    std::fs::remove_file(&out).ok();
+
+    assert!(default.status.success(), "{}", String::from_utf8_lossy(&default.stderr));
+    let default = String::from_utf8(default.stdout).unwrap();
+    assert!(default.contains(
+        "Transformer passes: identifier_renaming, dead_code_elimination, expression_simplification, collapse_vars, inline_captures, if_return, cleanup, property_minification, function_minification"
+    ));
+
+    assert!(selected.status.success(), "{}", String::from_utf8_lossy(&selected.stderr));
+    let selected = String::from_utf8(selected.stdout).unwrap();
+    assert!(selected.contains(
+        "Transformer passes: expression_simplification, collapse_vars, inline_captures, if_return, cleanup, property_minification, function_minification"
+    ));
+    assert!(selected.contains("Aggressive optimization: true"));
+    assert!(!selected.contains("Pass 2: Dead Code Elimination"));
+}
+
+#[test]
+fn test_explain_report() {
+    let file = write_temp_file("explain.js", "let a = 1;\nlet b = Math.max(1, 2);\n");
+    let out = std::env::temp_dir().join(format!("rjs-cli-{}-explain.min.js", std::process::id()));
+    let report = std::env::temp_dir().join(format!("rjs-cli-{}-explain.json", std::process::id()));
+    let output = run(
+        &["--explain", report.to_str().unwrap(), "--explain-format", "json", "-o", out.to_str().unwrap()],
+        &file,
+    );
+    let contents = std::fs::read_to_string(&report);
+    for path in [&file, &out, &report] {
+        std::fs::remove_file(path).ok();
+    }
+
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    let entries: serde_json::Value = serde_json::from_str(&contents.expect("report should be written")).unwrap();
+    assert_eq!(entries.as_array().unwrap().len(), 1);
+    assert_eq!(entries[0]["pass"], "expression_simplification");
+    assert_eq!(entries[0]["line"], 2);
+    assert_eq!(entries[0]["before"], "let b=Math.max(1,2);");
+    assert_eq!(entries[0]["after"], "let b=2;");
+}
+
+#[test]
+fn test_rjs_disable_directives() {
+    let file = write_temp_file(
+        "directives.js",
+        "/* rjs-disable compress */\nlet a = Math.max(1, 2);\n/* rjs-enable compress */\nlet b = Math.max(3, 4);\n",
+    );
+    let out = std::env::temp_dir().join(format!("rjs-cli-{}-directives.min.js", std::process::id()));
+    let output = run(&["-o", out.to_str().unwrap()], &file);
+    let minified = std::fs::read_to_string(&out);
+    for path in [&file, &out] {
+        std::fs::remove_file(path).ok();
+    }
+
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    let minified = minified.expect("output should be written");
+    assert!(minified.contains("let a=Math.max(1,2);"), "{}", minified);
+    assert!(minified.contains("let b=4;"), "{}", minified);
+}
+
+#[test]
+fn test_strict_mode_follows_the_source() {
+    let sloppy = write_temp_file("sloppy.js", "function pick(a, a) { return a; }\nconsole.log(pick(1, 2));\n");
+    let strict = write_temp_file("strict.js", "'use strict';\nfunction pick(a, a) { return a; }\n");
+    let out = std::env::temp_dir().join(format!("rjs-cli-{}-sloppy.min.js", std::process::id()));
+    let output = run(&["--no-mangle", "-o", out.to_str().unwrap()], &sloppy);
+    let rejected = run(&["-o", out.to_str().unwrap()], &strict);
+    let minified = std::fs::read_to_string(&out);
+    for path in [&sloppy, &strict, &out] {
+        std::fs::remove_file(path).ok();
+    }
+
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    assert!(minified.unwrap().contains("console.log(pick(1,2))"));
+    assert_eq!(rejected.status.code(), Some(4));
+    assert!(String::from_utf8_lossy(&rejected.stderr).contains("duplicate parameter names are not allowed in strict mode"));
+}
+
+#[test]
+fn test_diagnostics_format_locates_warnings() {
+    let file = write_temp_file("diagnostics.js", "let a = 1;\n// rjs-disable-next-line bogus\nlet b = a;\n");
+    let out = std::env::temp_dir().join(format!("rjs-cli-{}-diagnostics.min.js", std::process::id()));
+    let output = run(&["--diagnostics-format", "json", "-o", out.to_str().unwrap()], &file);
+    for path in [&file, &out] {
+        std::fs::remove_file(path).ok();
+    }
+
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    let diagnostics: serde_json::Value = serde_json::from_slice(&output.stderr).expect("stderr should be JSON only");
+    assert_eq!(diagnostics[0]["severity"], "warning");
+    assert_eq!(diagnostics[0]["line"], 2);
+    assert_eq!(diagnostics[0]["column"], 1);
+    assert!(diagnostics[0]["message"].as_str().unwrap().contains("bogus"));
+}
+
+#[test]
+fn test_hash_filenames_writes_manifest() {
+    let dir = std::env::temp_dir().join(format!("rjs-cli-{}-hashed", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let file = write_temp_file("hashed.js", "let a = Math.max(1, 2);\n");
+    let output = run(&["--hash-filenames", "-o", dir.join("app.js").to_str().unwrap()], &file);
+    let manifest = std::fs::read_to_string(dir.join("manifest.json"));
+    let entries: Vec<String> = std::fs::read_dir(&dir)
+        .map(|entries| entries.flatten().map(|entry| entry.file_name().to_string_lossy().into_owned()).collect())
+        .unwrap_or_default();
+    std::fs::remove_dir_all(&dir).ok();
+    std::fs::remove_file(&file).ok();
+
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    let manifest: serde_json::Value = serde_json::from_str(&manifest.expect("manifest should be written")).unwrap();
+    let hashed = manifest["app.js"].as_str().unwrap();
+    assert!(hashed.starts_with("app.") && hashed.ends_with(".js") && hashed.len() == "app.12345678.js".len());
+    assert!(entries.contains(&hashed.to_string()), "{:?}", entries);
+    assert!(!entries.contains(&"app.js".to_string()));
+}
+
+#[test]
+fn test_hash_filenames_rejects_corrupt_manifest() {
+    let dir = std::env::temp_dir().join(format!("rjs-cli-{}-corrupt-manifest", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    std::fs::write(dir.join("manifest.json"), "{ \"vendor.js\": ").unwrap();
+    let file = write_temp_file("corrupt-manifest.js", "let a = Math.max(1, 2);\n");
+    let output = run(&["--hash-filenames", "-o", dir.join("app.js").to_str().unwrap()], &file);
+    let manifest = std::fs::read_to_string(dir.join("manifest.json")).unwrap();
+    let renamed = dir.join("app.js").exists();
+    std::fs::remove_dir_all(&dir).ok();
+    std::fs::remove_file(&file).ok();
+
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("Failed to read manifest"));
+    assert_eq!(manifest, "{ \"vendor.js\": ");
+    assert!(renamed, "the output should keep its name when the manifest is corrupt");
+}
+
+#[test]
+fn test_target_gates_unsupported_syntax() {
+    let dir = std::env::temp_dir().join(format!("rjs-cli-{}-target", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let file = dir.join("input.js");
+    std::fs::write(&file, "let a = 2 ** 3;\n").unwrap();
+    let out = dir.join("out.js");
+    let out = out.to_str().unwrap();
+
+    let modern = run(&["--target", "chrome90,firefox88,node16", "-o", out], &file);
+    let old = run(&["--target", "chrome50", "-o", out], &file);
+    std::fs::write(dir.join(".browserslistrc"), "# legacy\nsafari >= 10\n").unwrap();
+    let from_rc = run(&["-o", out], &file);
+    std::fs::remove_dir_all(&dir).ok();
+
+    assert!(modern.status.success(), "{}", String::from_utf8_lossy(&modern.stderr));
+    assert!(!old.status.success());
+    assert!(String::from_utf8_lossy(&old.stderr).contains("exponentiation operator"));
+    assert!(!from_rc.status.success());
+    assert!(String::from_utf8_lossy(&from_rc.stderr).contains("safari 10"));
+}
+
+#[test]
+fn test_polyfill_report_and_imports() {
+    let dir = std::env::temp_dir().join(format!("rjs-cli-{}-polyfills", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let file = dir.join("input.js");
+    std::fs::write(&file, "var p = Promise.resolve(Object.entries(a));\n").unwrap();
+    let out = dir.join("out.js");
+    let report = dir.join("polyfills.json");
+
+    let output = run(
+        &[
+            "--target", "chrome50",
+            "--polyfill-report", report.to_str().unwrap(),
+            "--polyfill-module", "core-js/es",
+            "-o", out.to_str().unwrap(),
+        ],
+        &file,
+    );
+    let code = std::fs::read_to_string(&out).unwrap_or_default();
+    let report = std::fs::read_to_string(&report).unwrap_or_default();
+    std::fs::remove_dir_all(&dir).ok();
+
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    // Chrome 50 has Promise but not Object.entries (54)
+    assert!(code.starts_with("import \"core-js/es/object/entries\";\n"), "{}", code);
+    assert!(!code.contains("core-js/es/promise"));
+    let report: serde_json::Value = serde_json::from_str(&report).unwrap();
+    assert_eq!(report[out.to_str().unwrap()], serde_json::json!(["Object.entries"]));
+}
+
+#[test]
+fn test_keep_jsdoc_on_exports() {
+    let dir = std::env::temp_dir().join(format!("rjs-cli-{}-jsdoc", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let file = dir.join("lib.mjs");
+    std::fs::write(&file, "// Internal note\n/** Doubles `n`. */\nexport function double(n) { return n * 2; }\n").unwrap();
+    let out = dir.join("out.js");
+
+    let output = run(&["--keep-jsdoc", "-o", out.to_str().unwrap()], &file);
+    let code = std::fs::read_to_string(&out).unwrap_or_default();
+    std::fs::remove_dir_all(&dir).ok();
+
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    assert!(code.starts_with("/** Doubles `n`. */\nexport function double(n){\n"), "{}", code);
+    assert!(!code.contains("Internal note"));
+}
+
+#[test]
+fn test_keep_comments_next_to_statements() {
+    let dir = std::env::temp_dir().join(format!("rjs-cli-{}-comments", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let file = dir.join("input.js");
+    std::fs::write(&file, "// Greeting\nconsole.log('hi'); // says hi\n").unwrap();
+    let out = dir.join("out.js");
+
+    let output = run(&["--keep-comments", "-o", out.to_str().unwrap()], &file);
+    let code = std::fs::read_to_string(&out).unwrap_or_default();
+    std::fs::remove_dir_all(&dir).ok();
+
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    assert_eq!(code, "// Greeting\nconsole.log('hi');// says hi\n");
+}
+
+#[test]
+fn test_inline_enums_unless_disabled() {
+    let dir = std::env::temp_dir().join(format!("rjs-cli-{}-enums", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let file = dir.join("input.js");
+    std::fs::write(&file, "const Level = { Low: 1, High: 2 };\nconsole.log(Level.Low, Level.High);\n").unwrap();
+    let out = dir.join("out.js");
+
+    let output = run(&["-o", out.to_str().unwrap()], &file);
+    let inlined = std::fs::read_to_string(&out).unwrap_or_default();
+    run(&["--no-inline-enums", "-o", out.to_str().unwrap()], &file);
+    let kept = std::fs::read_to_string(&out).unwrap_or_default();
+    std::fs::remove_dir_all(&dir).ok();
+
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    assert_eq!(inlined, "console.log(1,2);");
+    assert!(kept.contains("Level.Low"), "{}", kept);
+}
+
+#[test]
+fn test_inline_captures_unless_disabled() {
+    let dir = std::env::temp_dir().join(format!("rjs-cli-{}-captures", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let file = dir.join("input.js");
+    std::fs::write(&file, "const LIMIT = 10;\nexport function check(x) { return () => x > LIMIT; }\n").unwrap();
+    let out = dir.join("out.js");
+
+    let output = run(&["-o", out.to_str().unwrap()], &file);
+    let inlined = std::fs::read_to_string(&out).unwrap_or_default();
+    run(&["--no-inline-captures", "-o", out.to_str().unwrap()], &file);
+    let kept = std::fs::read_to_string(&out).unwrap_or_default();
+    std::fs::remove_dir_all(&dir).ok();
+
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    assert_eq!(inlined, "export function check(x){return ()=>x>10;}");
+    assert!(kept.contains("LIMIT=10"), "{}", kept);
+}
+
+#[test]
+fn test_aggressive_hoists_loop_invariants() {
+    let dir = std::env::temp_dir().join(format!("rjs-cli-{}-invariants", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let file = dir.join("input.js");
+    std::fs::write(&file, "export function f(xs) { for (const x of xs) { use(x * Math.sqrt(2)); } }\n").unwrap();
+    let out = dir.join("out.js");
+
+    let output = run(&["--aggressive", "-o", out.to_str().unwrap()], &file);
+    let hoisted = std::fs::read_to_string(&out).unwrap_or_default();
+    run(&["-o", out.to_str().unwrap()], &file);
+    let kept = std::fs::read_to_string(&out).unwrap_or_default();
+    std::fs::remove_dir_all(&dir).ok();
+
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    assert!(hoisted.contains("const a=Math.sqrt(2);for(const x of xs){use(x*a);}"), "{}", hoisted);
+    assert!(kept.contains("use(x*Math.sqrt(2))"), "{}", kept);
+}
+
+#[test]
+fn test_dedupe_strings_threshold() {
+    let dir = std::env::temp_dir().join(format!("rjs-cli-{}-dedupe", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let file = dir.join("input.js");
+    std::fs::write(&file, "log('request failed'); log('request failed'); log('request failed');\n").unwrap();
+    let out = dir.join("out.js");
+
+    let output = run(&["--dedupe-strings-threshold", "4", "-o", out.to_str().unwrap()], &file);
+    let deduped = std::fs::read_to_string(&out).unwrap_or_default();
+    run(&["--dedupe-strings", "-o", out.to_str().unwrap()], &file);
+    let default = std::fs::read_to_string(&out).unwrap_or_default();
+    std::fs::remove_dir_all(&dir).ok();
+
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    assert_eq!(deduped, "const a='request failed';log(a);log(a);log(a);");
+    assert_eq!(default.matches("request failed").count(), 3, "{}", default);
+}
+
+#[test]
+fn test_output_format_wraps_modules() {
+    let dir = std::env::temp_dir().join(format!("rjs-cli-{}-output-format", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let file = dir.join("input.js");
+    std::fs::write(&file, "import { log } from 'logger';\nexport const ready = log('ready');\n").unwrap();
+    let out = dir.join("out.js");
+
+    let output = run(&["--output-format", "cjs", "-o", out.to_str().unwrap()], &file);
+    let cjs = std::fs::read_to_string(&out).unwrap_or_default();
+    let iife = run(&["--output-format", "iife", "-o", out.to_str().unwrap()], &file);
+    std::fs::remove_dir_all(&dir).ok();
+
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    assert!(cjs.starts_with("'use strict';"), "{}", cjs);
+    assert!(cjs.contains("const {log}=require('logger');"), "{}", cjs);
+    assert!(cjs.ends_with("exports.ready=ready;"), "{}", cjs);
+    // A browser script has nothing to import from
+    assert!(!iife.status.success());
+    assert!(String::from_utf8_lossy(&iife.stderr).contains("module loader"));
+}
+
+#[test]
+fn test_module_interop_converts_import_and_require() {
+    let dir = std::env::temp_dir().join(format!("rjs-cli-{}-module-interop", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let file = dir.join("input.js");
+    std::fs::write(
+        &file,
+        "const { join } = require('path');\nexport const load = name => import(join('./locales', name));\n",
+    )
+    .unwrap();
+    let out = dir.join("out.js");
+
+    let cjs = run(&["--output-format", "cjs", "--module-interop", "-o", out.to_str().unwrap()], &file);
+    let cjs_code = std::fs::read_to_string(&out).unwrap_or_default();
+    let esm = run(&["--module-interop", "-o", out.to_str().unwrap()], &file);
+    let esm_code = std::fs::read_to_string(&out).unwrap_or_default();
+    std::fs::remove_dir_all(&dir).ok();
+
+    assert!(cjs.status.success(), "{}", String::from_utf8_lossy(&cjs.stderr));
+    assert!(cjs_code.contains("Promise.resolve(require(join('./locales',name)))"), "{}", cjs_code);
+    assert!(esm.status.success(), "{}", String::from_utf8_lossy(&esm.stderr));
+    assert!(esm_code.starts_with("import{join}from'path';"), "{}", esm_code);
+    assert!(esm_code.contains("import(join('./locales',name))"), "{}", esm_code);
+}
+
+#[test]
+fn test_global_name_exposes_iife_exports() {
+    let dir = std::env::temp_dir().join(format!("rjs-cli-{}-global-name", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let file = dir.join("input.js");
+    std::fs::write(&file, "export function greet(name) { return 'hi ' + name; }\n").unwrap();
+    let out = dir.join("out.js");
+
+    let output = run(&["--output-format", "iife", "--global-name", "MyLib", "-o", out.to_str().unwrap()], &file);
+    let code = std::fs::read_to_string(&out).unwrap_or_default();
+    let collision = run(&["--output-format", "iife", "--global-name", "document", "-o", out.to_str().unwrap()], &file);
+    std::fs::remove_dir_all(&dir).ok();
+
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    assert!(code.starts_with("var MyLib=function(exports){'use strict';"), "{}", code);
+    assert!(code.ends_with("return exports}({});"), "{}", code);
+    assert!(collision.status.success());
+    assert!(String::from_utf8_lossy(&collision.stderr).contains("replaces the built-in global"));
+}
+
+#[test]
+fn test_api_report_lists_exports() {
+    let dir = std::env::temp_dir().join(format!("rjs-cli-{}-api-report", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let file = dir.join("input.js");
+    std::fs::write(
+        &file,
+        "export function add(a, b) { return a + b; }\nexport const VERSION = '1.0';\nexport default class {}\n",
+    )
+    .unwrap();
+    let out = dir.join("out.js");
+    let report_path = dir.join("api.json");
+
+    let output = run(&["--api-report", report_path.to_str().unwrap(), "-o", out.to_str().unwrap()], &file);
+    let report: serde_json::Value =
+        serde_json::from_str(&std::fs::read_to_string(&report_path).unwrap_or_default()).unwrap_or_default();
+    std::fs::remove_dir_all(&dir).ok();
+
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    assert_eq!(
+        report["output"]["exports"],
+        serde_json::json!([
+            { "name": "VERSION", "kind": "const" },
+            { "name": "add", "kind": "function", "arity": 2 },
+            { "name": "default", "kind": "class", "arity": 0 },
+        ])
+    );
+    assert_eq!(report["input"], report["output"]);
+    assert_eq!(report["changes"], serde_json::json!([]));
+}
+
+#[test]
+fn test_seed_gives_reproducible_names() {
+    let dir = std::env::temp_dir().join(format!("rjs-cli-{}-seed", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let file = dir.join("input.js");
+    std::fs::write(&file, "export function first() {}\nexport function second() {}\nexport const third = 3;\n").unwrap();
+    let out = dir.join("out.js");
+    let mut build = |args: &[&str]| {
+        let output = run(&[args, &["--module-exports", "mangle", "-o", out.to_str().unwrap()]].concat(), &file);
+        assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+        std::fs::read_to_string(&out).unwrap_or_default()
+    };
+
+    let unseeded = build(&[]);
+    let seeded = build(&["--seed", "99"]);
+    let again = build(&["--seed", "99"]);
+    std::fs::remove_dir_all(&dir).ok();
+
+    assert!(unseeded.contains("export{first as a}"), "{}", unseeded);
+    assert_eq!(seeded, again);
+    assert_ne!(seeded, unseeded);
+}
+
+#[test]
+fn test_threads_do_not_change_the_output() {
+    let dir = std::env::temp_dir().join(format!("rjs-cli-{}-threads", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let file = dir.join("input.js");
+    let source: String = (0..300).map(|i| format!("export const v{i} = Math.max({i}, 7) + \"ab\".length;\n")).collect();
+    std::fs::write(&file, source).unwrap();
+    let out = dir.join("out.js");
+    let build = |threads: &str| {
+        let output = run(&["--threads", threads, "-o", out.to_str().unwrap()], &file);
+        assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+        std::fs::read_to_string(&out).unwrap_or_default()
+    };
+
+    let sequential = build("1");
+    let parallel = build("4");
+    std::fs::remove_dir_all(&dir).ok();
+
+    assert!(sequential.contains("v299=299+2"), "{}", sequential);
+    assert_eq!(parallel, sequential);
+}
+
+#[test]
+fn test_build_info_comment() {
+    let dir = std::env::temp_dir().join(format!("rjs-cli-{}-build-info", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let file = dir.join("input.js");
+    std::fs::write(&file, "console.log(1 + 2);\n").unwrap();
+    let out = dir.join("out.js");
+    let mut build = |args: &[&str]| {
+        let output = run(&[args, &["-o", out.to_str().unwrap()]].concat(), &file);
+        assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+        std::fs::read_to_string(&out).unwrap_or_default()
+    };
+
+    let plain = build(&[]);
+    let tagged = build(&["--build-info"]);
+    let again = build(&["--build-info", "--verbose"]);
+    let other_options = build(&["--build-info", "--quote", "double"]);
+    std::fs::remove_dir_all(&dir).ok();
+
+    let comment = tagged.strip_prefix(&plain).expect("the comment follows the code");
+    assert!(comment.starts_with(&format!("/*! rjs-compiler {} cfg:", env!("CARGO_PKG_VERSION"))), "{}", comment);
+    assert!(comment.contains(" src:"), "{}", comment);
+    assert_eq!(again, tagged);
+    assert_ne!(other_options, tagged);
+}
+
+#[test]
+fn test_format_only_pretty_prints_without_minifying() {
+    let dir = std::env::temp_dir().join(format!("rjs-cli-{}-format-only", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let file = dir.join("input.js");
+    std::fs::write(&file, "function unusedHelper(first,second){return first+'x'}\n").unwrap();
+    let out = dir.join("out.js");
+
+    let output = run(&["--format-only", "--quote", "double", "--semicolons", "always", "-o", out.to_str().unwrap()], &file);
+    let code = std::fs::read_to_string(&out).unwrap_or_default();
+    std::fs::remove_dir_all(&dir).ok();
+
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    // Names and unused code are kept; only the layout changes
+    assert_eq!(code, "function unusedHelper(first, second) {\n  return first + \"x\";\n}\n");
+    assert!(String::from_utf8_lossy(&output.stdout).contains("Formatted JavaScript saved to"));
+}
+
+#[test]
+fn test_format_only_keeps_comments_and_refuses_unsupported_code() {
+    let commented = write_temp_file("format-comments.js", "// Adds one\nfunction inc(x) { return x + 1; } /* done */\n");
+    let unsupported = write_temp_file("format-unsupported.js", "f();\nfor (const k in x) {}\n");
+    let out = commented.with_extension("out.js");
+    let refused_out = unsupported.with_extension("out.js");
+
+    let formatted = run(&["--format-only", "-o", out.to_str().unwrap()], &commented);
+    let refused = run(&["--format-only", "-o", refused_out.to_str().unwrap()], &unsupported);
+    let code = std::fs::read_to_string(&out).unwrap_or_default();
+    let refused_written = refused_out.exists();
+    for path in [&commented, &unsupported, &out, &refused_out] {
+        std::fs::remove_file(path).ok();
+    }
+
+    assert!(formatted.status.success(), "{}", String::from_utf8_lossy(&formatted.stderr));
+    assert_eq!(code, "// Adds one\nfunction inc(x) {\n  return x + 1;\n} /* done */\n");
+    assert_eq!(refused.status.code(), Some(3));
+    assert!(String::from_utf8_lossy(&refused.stderr).contains("Unsupported syntax at line 2, column 1"));
+    assert!(!refused_written);
+}
+
+#[test]
+fn test_module_exports_mangle_writes_export_map() {
+    let dir = std::env::temp_dir().join(format!("rjs-cli-{}-export-map", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let file = dir.join("lib.mjs");
+    std::fs::write(&file, "export function parseConfig(text) { return text; }\nconst version = 1;\nexport { version as libraryVersion };\n").unwrap();
+    let out = dir.join("out.js");
+    let map = dir.join("exports.json");
+
+    let output = run(
+        &["--module-exports", "mangle", "--export-map", map.to_str().unwrap(), "-o", out.to_str().unwrap()],
+        &file,
+    );
+    let code = std::fs::read_to_string(&out).unwrap_or_default();
+    let export_map = std::fs::read_to_string(&map).unwrap_or_default();
+    let rejected = run(&["--export-map", map.to_str().unwrap(), "-o", out.to_str().unwrap()], &file);
+    std::fs::remove_dir_all(&dir).ok();
+
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    assert!(code.contains("export{parseConfig as a}"), "{}", code);
+    assert!(code.contains("export{version as b}"), "{}", code);
+    assert_eq!(export_map, "{\n  \"libraryVersion\": \"b\",\n  \"parseConfig\": \"a\"\n}\n");
+
+    // The map is only meaningful when exports are mangled
+    assert!(!rejected.status.success());
+    assert!(String::from_utf8_lossy(&rejected.stderr).contains("--export-map requires --module-exports mangle"));
+}
+
+#[test]
+fn test_exit_codes_distinguish_failures() {
+    let dir = std::env::temp_dir().join(format!("rjs-cli-{}-exit-codes", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let broken = dir.join("broken.js");
+    std::fs::write(&broken, "let a = ;\n").unwrap();
+    let warned = dir.join("warned.js");
+    std::fs::write(&warned, "let a = 1;\n// rjs-disable-next-line bogus\nlet b = a;\n").unwrap();
+    let out = dir.join("out.js");
+    let out = out.to_str().unwrap();
+
+    let parse = run(&["-o", out], &broken);
+    let missing = run(&["-o", out], &dir.join("missing.js"));
+    let invalid = run(&["--quote", "backtick", "-o", out], &warned);
+    let strict = run(&["--warnings-as-errors", "-o", out], &warned);
+    let within_limit = run(&["--max-warnings", "1", "-o", out], &warned);
+    std::fs::remove_dir_all(&dir).ok();
+
+    assert_eq!(parse.status.code(), Some(3));
+    assert_eq!(missing.status.code(), Some(5));
+    assert_eq!(invalid.status.code(), Some(2));
+    assert_eq!(strict.status.code(), Some(6));
+    let stderr = String::from_utf8_lossy(&strict.stderr);
+    assert!(stderr.contains("warned.js:2:1: warning: Unknown pass 'bogus'"), "{}", stderr);
+    assert!(stderr.contains("Too many warnings: 1 (limit 0)"), "{}", stderr);
+    assert!(within_limit.status.success(), "{}", String::from_utf8_lossy(&within_limit.stderr));
+}
+
+#[test]
+fn test_check_reports_correctness_problems() {
+    let broken = write_temp_file(
+        "check-broken.js",
+        "const limit = 1;\nlimit = 2;\nlet x;\nlet x;\nfunction f() {\n  return 1;\n  cleanup();\n}\nmissing();\n",
+    );
+    let clean = write_temp_file("check-clean.js", "const a = 1;\nconsole.log(a);\n");
+    let failed = run(&["--check"], &broken);
+    let json = run(&["--check", "--diagnostics-format", "json"], &broken);
+    let passed = run(&["--check"], &clean);
+    let strict = run(&["--check", "--max-warnings", "0"], &clean);
+    std::fs::remove_file(&broken).ok();
+    std::fs::remove_file(&clean).ok();
+
+    assert_eq!(failed.status.code(), Some(8));
+    let stdout = String::from_utf8_lossy(&failed.stdout);
+    assert!(stdout.contains("check-broken.js:2:1: error[const-assign]: Assignment to 'limit', which is a constant"), "{}", stdout);
+    assert!(stdout.contains("check-broken.js:4:5: error[duplicate-declaration]: 'x' has already been declared"), "{}", stdout);
+    assert!(stdout.contains("check-broken.js:7:3: warning[unreachable]: Unreachable code after `return`"), "{}", stdout);
+    assert!(stdout.contains("check-broken.js:9:1: warning[undeclared]: 'missing' is not declared"), "{}", stdout);
+
+    let diagnostics: serde_json::Value = serde_json::from_slice(&json.stdout).unwrap();
+    assert_eq!(diagnostics.as_array().map(Vec::len), Some(5));
+    assert_eq!(diagnostics[0]["pass"], "const-assign");
+
+    assert!(passed.status.success(), "{}", String::from_utf8_lossy(&passed.stderr));
+    assert!(!String::from_utf8_lossy(&passed.stdout).contains("warning["));
+    assert!(strict.status.success(), "{}", String::from_utf8_lossy(&strict.stderr));
+}
+
+#[test]
+fn test_log_levels_and_json_format() {
+    let file = write_temp_file("logging.js", "let a = 1;\nconsole.log(a);\n");
+    let out = std::env::temp_dir().join(format!("rjs-cli-{}-logging.min.js", std::process::id()));
+    let out = out.to_str().unwrap();
+    let quiet = run(&["-q", "-o", out], &file);
+    let trace = run(&["-vv", "-o", out], &file);
+    let filtered = run(&["--log-filter", "rjs_compiler::analyzer::scope_builder=trace", "-o", out], &file);
+    let json = run(&["--log-format", "json", "-o", out], &file);
+    std::fs::remove_file(&file).ok();
+    std::fs::remove_file(out).ok();
+
+    assert!(quiet.status.success(), "{}", String::from_utf8_lossy(&quiet.stderr));
+    assert!(quiet.stdout.is_empty(), "{}", String::from_utf8_lossy(&quiet.stdout));
+
+    let trace = String::from_utf8_lossy(&trace.stdout);
+    assert!(trace.contains("📊 Analysis Results:"), "{}", trace);
+    assert!(trace.contains("Declared symbol 'a' in scope 0"), "{}", trace);
+
+    let filtered = String::from_utf8_lossy(&filtered.stdout);
+    assert!(filtered.contains("Declared symbol 'a'"), "{}", filtered);
+    assert!(!filtered.contains("📊 Analysis Results:"), "{}", filtered);
+
+    assert!(json.stdout.is_empty(), "{}", String::from_utf8_lossy(&json.stdout));
+    let events: Vec<serde_json::Value> = String::from_utf8_lossy(&json.stderr)
+        .lines()
+        .map(|line| serde_json::from_str(line).expect("each stderr line should be a JSON event"))
+        .collect();
+    assert!(events.iter().any(|event| event["level"] == "INFO"
+        && event["fields"]["message"].as_str().unwrap().contains("saved to")));
+}
+
+#[test]
+fn test_multiple_inputs_print_summary_table() {
+    let dir = std::env::temp_dir().join(format!("rjs-cli-{}-multi", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    std::fs::write(dir.join("one.js"), "let first = 1;\nconsole.log(first);\n").unwrap();
+    std::fs::write(dir.join("two.js"), "let second = 2;\nconsole.log(second);\n").unwrap();
+    let one = dir.join("one.js");
+    let two = dir.join("two.js");
+
+    let output = Command::new(BINARY).arg(&one).arg(&two).output().expect("binary should run");
+    let rejected = Command::new(BINARY).args(["-o", "out.js"]).arg(&one).arg(&two).output().expect("binary should run");
+    let outputs = [dir.join("one.min.js").exists(), dir.join("two.min.js").exists()];
+    std::fs::remove_dir_all(&dir).ok();
+
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    assert_eq!(outputs, [true, true]);
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("📊 Build summary:"), "{}", stdout);
+    assert!(stdout.contains("total (2 files)"), "{}", stdout);
+    // Not a terminal, so no progress bar
+    assert!(!String::from_utf8_lossy(&output.stderr).contains("2/2"));
+    assert_eq!(rejected.status.code(), Some(2));
+}
+
+#[test]
+fn test_dry_run_writes_nothing() {
+    let dir = std::env::temp_dir().join(format!("rjs-cli-{}-dry-run", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let file = dir.join("input.js");
+    std::fs::write(&file, "let total = 1 + 2;\nconsole.log(total);\n").unwrap();
+    let out = dir.join("out.js");
+    let report = dir.join("explain.txt");
+
+    let output = run(&["--dry-run", "--explain", report.to_str().unwrap(), "-o", out.to_str().unwrap()], &file);
+    let over_budget = run(&["--dry-run", "--max-output-size", "4", "-o", out.to_str().unwrap()], &file);
+    let written = (out.exists(), report.exists());
+    std::fs::remove_dir_all(&dir).ok();
+
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    assert_eq!(written, (false, false));
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("🔎 Dry run: would write "), "{}", stdout);
+    assert!(stdout.contains(&format!("to {} (39 bytes in,", out.display())), "{}", stdout);
+    assert_eq!(over_budget.status.code(), Some(4));
+}
+
+#[test]
+fn test_size_budget_lists_largest_contributors() {
+    let dir = std::env::temp_dir().join(format!("rjs-cli-{}-size-budget", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let file = dir.join("input.js");
+    std::fs::write(
+        &file,
+        "function greet(user) {\n  return 'Hello there, ' + user.name + ', you have new messages waiting';\n}\n\
+         const answer = 42;\nexport { greet, answer };\n",
+    )
+    .unwrap();
+    let out = dir.join("out.js");
+
+    let over = run(&["--max-size", "40", "--max-gzip-size", "1K", "-o", out.to_str().unwrap()], &file);
+    let written = out.exists();
+    let within = run(&["--max-size", "1K", "--max-gzip-size", "1K", "-o", out.to_str().unwrap()], &file);
+    std::fs::remove_dir_all(&dir).ok();
+
+    assert_eq!(over.status.code(), Some(7));
+    assert!(!written);
+    let stderr = String::from_utf8_lossy(&over.stderr);
+    assert!(stderr.contains("exceeds the budget of 40 bytes"), "{}", stderr);
+    assert!(!stderr.contains("gzip size"), "{}", stderr);
+    let greet = stderr.find("function greet (line 1)").expect(&stderr);
+    let answer = stderr.find("const answer (line 4)").expect(&stderr);
+    assert!(greet < answer, "{}", stderr);
+    assert!(within.status.success(), "{}", String::from_utf8_lossy(&within.stderr));
+}
+
+#[test]
+fn test_bundle_report_attributes_output_bytes() {
+    let dir = std::env::temp_dir().join(format!("rjs-cli-{}-bundle-report", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let file = dir.join("input.js");
+    std::fs::write(&file, "function greet(user) {\n  return 'Hello, ' + user.name;\n}\nexport const answer = greet({ name: 'a' });\n")
+        .unwrap();
+    let out = dir.join("out.js");
+    let json = dir.join("report.json");
+    let html = dir.join("report.html");
+
+    let output = run(&["--bundle-report", json.to_str().unwrap(), "-o", out.to_str().unwrap()], &file);
+    let html_output = run(
+        &["--bundle-report", html.to_str().unwrap(), "--bundle-report-format", "html", "-o", out.to_str().unwrap()],
+        &file,
+    );
+    let code = std::fs::read_to_string(&out).unwrap_or_default();
+    let report = std::fs::read_to_string(&json).unwrap_or_default();
+    let page = std::fs::read_to_string(&html).unwrap_or_default();
+    std::fs::remove_dir_all(&dir).ok();
+
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    assert!(html_output.status.success(), "{}", String::from_utf8_lossy(&html_output.stderr));
+    let report: serde_json::Value = serde_json::from_str(&report).unwrap();
+    assert_eq!(report["name"], "out.js");
+    assert_eq!(report["value"], code.len());
+    let module = &report["children"][0];
+    assert_eq!(module["name"], file.display().to_string());
+    let names: Vec<&str> = module["children"].as_array().unwrap().iter().map(|node| node["name"].as_str().unwrap()).collect();
+    assert_eq!(names, ["function greet", "export const answer"]);
+    assert!(page.contains("<title>Bundle report: out.js</title>"), "{}", page);
+}
+
+#[test]
+fn test_mangle_props_debug_names() {
+    let dir = std::env::temp_dir().join(format!("rjs-cli-{}-mangle-props-debug", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let file = dir.join("input.js");
+    std::fs::write(&file, "const point = { x: 1, y: 2 };\nconsole.log(point.x + point.y);\n").unwrap();
+    let out = dir.join("out.js");
+
+    let output = run(&["--mangle-props-debug", "--no-inline-enums", "--no-mangle", "-o", out.to_str().unwrap()], &file);
+    let code = std::fs::read_to_string(&out).unwrap_or_default();
+    std::fs::remove_dir_all(&dir).ok();
+
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    assert!(code.contains("{_$x$_:1,_$y$_:2}"), "{}", code);
+    assert!(code.contains("point._$x$_+point._$y$_"), "{}", code);
+}
+
+#[test]
+fn test_emit_name_map_next_to_output() {
+    let dir = std::env::temp_dir().join(format!("rjs-cli-{}-name-map", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let file = dir.join("input.js");
+    std::fs::write(&file, "export function area(w, h) {\n  const size = { width: w, height: h };\n  return size.width * size.height;\n}\n")
+        .unwrap();
+    let out = dir.join("out.js");
+
+    let output = run(&["--emit-name-map", "--mangle-props", "--module-exports", "mangle", "-o", out.to_str().unwrap()], &file);
+    let map = std::fs::read_to_string(dir.join("out.js.names.json")).unwrap_or_default();
+    std::fs::remove_dir_all(&dir).ok();
+
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    let map: serde_json::Value = serde_json::from_str(&map).unwrap();
+    assert_eq!(map["properties"]["size"], serde_json::json!({ "height": "a", "width": "b" }));
+    assert_eq!(map["exports"]["area"], "a");
+    assert!(map["identifiers"].is_object());
+}
+
+#[test]
+fn test_beautify_restores_names_from_name_map() {
+    let dir = std::env::temp_dir().join(format!("rjs-cli-{}-beautify", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let file = dir.join("input.js");
+    std::fs::write(&file, "export function area(w, h) {\n  const size = { width: w, height: h };\n  return size.width * size.height;\n}\n")
+        .unwrap();
+    let out = dir.join("out.js");
+    let names = dir.join("out.js.names.json");
+
+    let minify = run(&["--emit-name-map", "--mangle-props", "--module-exports", "mangle", "-o", out.to_str().unwrap()], &file);
+    let output = run(&["beautify", "--name-map", names.to_str().unwrap()], &out);
+    std::fs::remove_dir_all(&dir).ok();
+
+    assert!(minify.status.success(), "{}", String::from_utf8_lossy(&minify.stderr));
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("width: w"), "{}", stdout);
+    assert!(stdout.contains("size.width * size.height"), "{}", stdout);
+    assert!(stdout.contains("export{area}"), "{}", stdout);
+}
+
+#[test]
+fn test_diff_reports_structural_changes() {
+    let before = write_temp_file("diff-a.js", "const a = 1;\nfunction f(x) { return x + 1; }\n");
+    let same = write_temp_file("diff-b.js", "const a=1;function f(x){return x+1}");
+    let changed = write_temp_file("diff-c.js", "const a = 1;\nfunction f(x) { return x - 1; }\nf(a);\n");
+    let identical = run(&["diff", before.to_str().unwrap()], &same);
+    let different = run(&["diff", before.to_str().unwrap()], &changed);
+    for file in [&before, &same, &changed] {
+        std::fs::remove_file(file).ok();
+    }
+
+    assert!(identical.status.success(), "{}", String::from_utf8_lossy(&identical.stderr));
+    assert_eq!(String::from_utf8_lossy(&identical.stdout), "No structural differences\n");
+    assert_eq!(different.status.code(), Some(1));
+    let stdout = String::from_utf8_lossy(&different.stdout);
+    assert!(stdout.contains("~ FunctionDeclaration f (line 2 -> 2)\n    body.body[0].argument.operator: \"Add\" -> \"Subtract\""), "{}", stdout);
+    assert!(stdout.contains("+ ExpressionStatement (line 3)"), "{}", stdout);
+}
+
+#[test]
+fn test_deep_nesting_fails_instead_of_overflowing() {
+    let dir = std::env::temp_dir().join(format!("rjs-cli-{}-max-depth", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let deep = dir.join("deep.js");
+    std::fs::write(&deep, format!("let r = {}1{};\n", "(".repeat(10_000), ")".repeat(10_000))).unwrap();
+    let nested = dir.join("nested.js");
+    std::fs::write(&nested, format!("{}f();{}\n", "{".repeat(1_000), "}".repeat(1_000))).unwrap();
+    let out = dir.join("out.js");
+    let out = out.to_str().unwrap();
+
+    let rejected = run(&["-o", out], &deep);
+    let over_default = run(&["-o", out], &nested);
+    let raised = run(&["--max-depth", "1100", "-o", out], &nested);
+    let too_high = run(&["--max-depth", "100000", "-o", out], &nested);
+    std::fs::remove_dir_all(&dir).ok();
+
+    assert_eq!(rejected.status.code(), Some(3));
+    let stderr = String::from_utf8_lossy(&rejected.stderr);
+    assert!(stderr.contains("Nesting deeper than 500 levels at line 1, column 509"), "{}", stderr);
+    assert_eq!(over_default.status.code(), Some(3));
+    assert!(raised.status.success(), "{}", String::from_utf8_lossy(&raised.stderr));
+    assert_eq!(too_high.status.code(), Some(2));
+    let stderr = String::from_utf8_lossy(&too_high.stderr);
+    assert!(stderr.contains("--max-depth 100000 is over the largest supported limit, 10000"), "{}", stderr);
+}
+
+#[test]
+fn test_verbose_preview_of_multibyte_output_does_not_panic() {
+    let dir = std::env::temp_dir().join(format!("rjs-cli-{}-preview", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let input = dir.join("accents.js");
+    std::fs::write(&input, format!("console.log(\"{}\");\n", "é".repeat(150))).unwrap();
+    let out = dir.join("out.js");
+
+    let output = run(&["--verbose", "-o", out.to_str().unwrap()], &input);
+    std::fs::remove_dir_all(&dir).ok();
+
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+}
+
+#[test]
+fn test_input_encodings_are_decoded_or_reported() {
+    let dir = std::env::temp_dir().join(format!("rjs-cli-{}-encoding", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let bom = dir.join("bom.js");
+    std::fs::write(&bom, b"\xEF\xBB\xBFconsole.log(1);\n").unwrap();
+    let utf16 = dir.join("utf16.js");
+    let utf16_bytes: Vec<u8> = "\u{FEFF}console.log(2);\n".encode_utf16().flat_map(u16::to_le_bytes).collect();
+    std::fs::write(&utf16, utf16_bytes).unwrap();
+    let latin1 = dir.join("latin1.js");
+    std::fs::write(&latin1, b"let a;\nlet s = \"caf\xE9\";\n").unwrap();
+    let out = dir.join("out.js");
+    let out_arg = out.to_str().unwrap();
+
+    let stripped = run(&["-o", out_arg], &bom);
+    let stripped_output = std::fs::read(&out).unwrap_or_default();
+    let rejected = run(&["-o", out_arg], &utf16);
+    let transcoded = run(&["--transcode-utf16", "-o", out_arg], &utf16);
+    let transcoded_output = std::fs::read_to_string(&out).unwrap_or_default();
+    let invalid = run(&["-o", out_arg], &latin1);
+    std::fs::remove_dir_all(&dir).ok();
+
+    assert!(stripped.status.success(), "{}", String::from_utf8_lossy(&stripped.stderr));
+    assert_eq!(stripped_output, b"console.log(1);");
+    assert_eq!(rejected.status.code(), Some(5));
+    assert!(String::from_utf8_lossy(&rejected.stderr).contains("UTF-16 LE encoded, not UTF-8"));
+    assert!(transcoded.status.success(), "{}", String::from_utf8_lossy(&transcoded.stderr));
+    assert_eq!(transcoded_output, "console.log(2);");
+    assert_eq!(invalid.status.code(), Some(5));
+    assert!(String::from_utf8_lossy(&invalid.stderr).contains("invalid UTF-8 at line 2, column 13"));
+}
+
+#[test]
+fn test_in_place_replaces_only_successfully_compiled_inputs() {
+    let dir = std::env::temp_dir().join(format!("rjs-cli-{}-in-place", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let input = dir.join("app.js");
+    std::fs::write(&input, "var answer = 42; console.log(answer);\n").unwrap();
+    let broken = dir.join("broken.js");
+    std::fs::write(&broken, "let = (;\n").unwrap();
+
+    let compiled = run(&["--in-place", "--backup"], &input);
+    let failed = run(&["--in-place"], &broken);
+    let output = std::fs::read_to_string(&input).unwrap();
+    let backup = std::fs::read_to_string(dir.join("app.js.bak")).unwrap_or_default();
+    let untouched = std::fs::read_to_string(&broken).unwrap();
+    let mut files: Vec<String> = std::fs::read_dir(&dir)
+        .unwrap()
+        .map(|entry| entry.unwrap().file_name().to_string_lossy().into_owned())
+        .collect();
+    files.sort();
+    std::fs::remove_dir_all(&dir).ok();
+
+    assert!(compiled.status.success(), "{}", String::from_utf8_lossy(&compiled.stderr));
+    assert_eq!(output, "console.log(42);");
+    assert_eq!(backup, "var answer = 42; console.log(answer);\n");
+    assert_eq!(failed.status.code(), Some(3));
+    assert_eq!(untouched, "let = (;\n");
+    // No temporary files are left behind
+    assert_eq!(files, ["app.js", "app.js.bak", "broken.js"]);
+}
+
+#[test]
+fn test_in_place_refuses_input_with_unsupported_code() {
+    let source = "for (const k in o) {}\n";
+    let input = write_temp_file("unsupported-in-place.js", source);
+
+    let compiled = run(&["--in-place"], &input);
+    let checked = run(&["--check"], &input);
+    let untouched = std::fs::read_to_string(&input).unwrap();
+    std::fs::remove_file(&input).ok();
+
+    assert_eq!(compiled.status.code(), Some(3));
+    assert!(
+        String::from_utf8_lossy(&compiled.stderr).contains("Unsupported syntax at line 1, column 1"),
+        "{}",
+        String::from_utf8_lossy(&compiled.stderr)
+    );
+    assert_eq!(untouched, source);
+    assert_eq!(checked.status.code(), Some(8));
+    assert!(String::from_utf8_lossy(&checked.stdout).contains("cannot represent this code"));
+}
+
+#[test]
+fn test_out_dir_mirrors_inputs_and_refuses_to_overwrite_them() {
+    let dir = std::env::temp_dir().join(format!("rjs-cli-{}-out-dir", std::process::id()));
+    std::fs::create_dir_all(dir.join("src").join("lib")).unwrap();
+    let app = dir.join("src").join("app.js");
+    std::fs::write(&app, "var answer = 42; console.log(answer);\n").unwrap();
+    let util = dir.join("src").join("lib").join("util.js");
+    std::fs::write(&util, "var half = 21; console.log(half);\n").unwrap();
+    let dist = dir.join("dist");
+
+    let output = Command::new(BINARY)
+        .args(["--out-dir", dist.to_str().unwrap()])
+        .args([&app, &util])
+        .output()
+        .expect("binary should run");
+    let app_output = std::fs::read_to_string(dist.join("app.min.js")).unwrap_or_default();
+    let util_output = std::fs::read_to_string(dist.join("lib").join("util.min.js")).unwrap_or_default();
+    let refused = run(&["--out-ext", ".js"], &app);
+    let unchanged = std::fs::read_to_string(&app).unwrap();
+    let forced = run(&["--out-ext", ".js", "--force"], &app);
+    let overwritten = std::fs::read_to_string(&app).unwrap();
+    std::fs::remove_dir_all(&dir).ok();
+
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    assert_eq!(app_output, "console.log(42);");
+    assert_eq!(util_output, "console.log(21);");
+    assert_eq!(refused.status.code(), Some(2));
+    assert!(String::from_utf8_lossy(&refused.stderr).contains("is an input file; pass --force to overwrite it"));
+    assert_eq!(unchanged, "var answer = 42; console.log(answer);\n");
+    assert!(forced.status.success(), "{}", String::from_utf8_lossy(&forced.stderr));
+    assert_eq!(overwritten, "console.log(42);");
+}
+
+#[test]
+fn test_workers_are_compiled_and_their_urls_rewritten() {
+    let dir = std::env::temp_dir().join(format!("rjs-cli-{}-workers", std::process::id()));
+    std::fs::create_dir_all(dir.join("src").join("workers")).unwrap();
+    let app = dir.join("src").join("app.js");
+    std::fs::write(
+        &app,
+        "const worker = new Worker(new URL('./workers/render.js', import.meta.url), { type: 'module' });\n\
+         worker.postMessage(1);\n",
+    )
+    .unwrap();
+    std::fs::write(dir.join("src").join("workers").join("render.js"), "self.onmessage = event => { console.log(event.data); };\n")
+        .unwrap();
+    let dist = dir.join("dist");
+
+    let output = run(&["--workers", "--out-dir", dist.to_str().unwrap()], &app);
+    let app_output = std::fs::read_to_string(dist.join("app.min.js")).unwrap_or_default();
+    let worker_output = std::fs::read_to_string(dist.join("workers").join("render.min.js")).unwrap_or_default();
+    std::fs::remove_dir_all(&dir).ok();
+
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    assert!(app_output.contains("new Worker(new URL('./workers/render.min.js',import.meta.url),{type:'module'})"), "{}", app_output);
+    assert!(worker_output.starts_with("self.onmessage="), "{}", worker_output);
+}
+
+#[test]
+fn test_declarations_are_copied_next_to_outputs() {
+    let dir = std::env::temp_dir().join(format!("rjs-cli-{}-declarations", std::process::id()));
+    std::fs::create_dir_all(dir.join("src").join("lib")).unwrap();
+    let index = dir.join("src").join("index.js");
+    std::fs::write(&index, "export { scale } from './lib/geometry.js';\n").unwrap();
+    std::fs::write(dir.join("src").join("index.d.ts"), "export { scale } from './lib/geometry.js';\n").unwrap();
+    let geometry = dir.join("src").join("lib").join("geometry.js");
+    std::fs::write(&geometry, "export function scale(size, by) { return size * by; }\n").unwrap();
+    std::fs::write(
+        dir.join("src").join("lib").join("geometry.d.ts"),
+        "import type { Size } from './size';\nexport declare function scale(size: Size, by: number): Size;\n",
+    )
+    .unwrap();
+    std::fs::write(dir.join("src").join("lib").join("size.d.ts"), "export type Size = number;\n").unwrap();
+    let dist = dir.join("dist");
+
+    let output = Command::new(BINARY)
+        .args(["--declarations", "--out-dir", dist.to_str().unwrap()])
+        .args([&index, &geometry])
+        .output()
+        .expect("binary should run");
+    let read = |path: &[&str]| std::fs::read_to_string(path.iter().fold(dist.clone(), |path, part| path.join(part))).unwrap_or_default();
+    let (index_types, geometry_types, size_types) =
+        (read(&["index.min.d.ts"]), read(&["lib", "geometry.min.d.ts"]), read(&["lib", "size.d.ts"]));
+    std::fs::remove_dir_all(&dir).ok();
+
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    assert_eq!(index_types, "export { scale } from './lib/geometry.min.js';\n");
+    assert!(geometry_types.starts_with("import type { Size } from './size';"), "{}", geometry_types);
+    assert_eq!(size_types, "export type Size = number;\n");
+}
+
+#[cfg(unix)]
+#[test]
+fn test_preserve_mode_keeps_scripts_executable() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let dir = std::env::temp_dir().join(format!("rjs-cli-{}-preserve-mode", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let script = dir.join("tool.js");
+    std::fs::write(&script, "#!/usr/bin/env node\nvar answer = 42; console.log(answer);\n").unwrap();
+    std::fs::set_permissions(&script, std::fs::Permissions::from_mode(0o751)).unwrap();
+    let modified = std::time::SystemTime::UNIX_EPOCH + std::time::Duration::from_secs(1_600_000_000);
+    std::fs::File::options().write(true).open(&script).unwrap().set_modified(modified).unwrap();
+    let output_path = dir.join("tool.min.js");
+
+    let output = run(&["--preserve-mode", "--preserve-times"], &script);
+    let code = std::fs::read_to_string(&output_path).unwrap_or_default();
+    let metadata = std::fs::metadata(&output_path).unwrap();
+    std::fs::remove_dir_all(&dir).ok();
+
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    assert_eq!(code, "#!/usr/bin/env node\nconsole.log(42);");
+    assert_eq!(metadata.permissions().mode() & 0o777, 0o751);
+    assert_eq!(metadata.modified().unwrap(), modified);
+}
+
+#[test]
+fn test_proposal_flag_enables_decorators_and_using() {
+    let file = write_temp_file(
+        "proposals.mjs",
+        "@sealed class Widget {}\nexport function f() { using handle = open(); }\nexport { Widget };\n",
+    );
+    let output_path = file.with_extension("min.js");
+    let rejected = run(&[], &file);
+    let output = run(&["--proposal", "decorators,using", "-o", output_path.to_str().unwrap()], &file);
+    let code = std::fs::read_to_string(&output_path).unwrap_or_default();
+    std::fs::remove_file(&file).ok();
+    std::fs::remove_file(&output_path).ok();
+
+    assert!(!rejected.status.success());
+    assert!(String::from_utf8_lossy(&rejected.stderr).contains("Stage-3 proposal not enabled at line 1, column 1: decorators"));
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    assert!(code.contains("@sealed class Widget{}"), "{}", code);
+    assert!(code.contains("using handle=open();"), "{}", code);
+}
+
+#[test]
+fn test_strip_types_removes_flow_annotations_and_type_comments() {
+    let file = write_temp_file(
+        "flow.js",
+        "// @flow\n/** @type {number} */\nconst ratio: number = 2;\n/** Scales a size */\nexport function scale(n: ?number): number { return (n ?? 0) * ratio; }\n",
+    );
+    let output_path = file.with_extension("min.js");
+    let rejected = run(&[], &file);
+    let output = run(&["--strip-types", "--keep-comments", "-o", output_path.to_str().unwrap()], &file);
+    let code = std::fs::read_to_string(&output_path).unwrap_or_default();
+    std::fs::remove_file(&file).ok();
+    std::fs::remove_file(&output_path).ok();
+
+    assert!(!rejected.status.success());
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    assert!(code.contains("/** Scales a size */"), "{}", code);
+    assert!(!code.contains("@flow") && !code.contains("@type") && !code.contains(": number"), "{}", code);
+}
+
+#[test]
+fn test_decorated_exports_follow_the_target() {
+    let file = write_temp_file("decorated.mjs", "export @sealed class Widget { @bound click() {} }\n");
+    let output_path = file.with_extension("min.js");
+    let output = run(&["--proposal", "decorators", "-o", output_path.to_str().unwrap()], &file);
+    let code = std::fs::read_to_string(&output_path).unwrap_or_default();
+    let old = run(&["--proposal", "decorators", "--target", "es2022", "-o", output_path.to_str().unwrap()], &file);
+    std::fs::remove_file(&file).ok();
+    std::fs::remove_file(&output_path).ok();
+
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    assert!(code.contains("@sealed export class Widget{@bound click(){}}"), "{}", code);
+    assert!(!old.status.success());
+    assert!(String::from_utf8_lossy(&old.stderr).contains("decorators"), "{}", String::from_utf8_lossy(&old.stderr));
+}
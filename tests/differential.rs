@@ -0,0 +1,150 @@
+//! # Differential Correctness Tests
+//!
+//! Executes every program in `tests/differential/` twice in a JavaScript engine — once
+//! as written and once after minification — and asserts that the observable behaviour
+//! (console output and exit status) is identical.
+//!
+//! ## Engine Selection
+//!
+//! The tests use the `node` binary on `PATH`, or the binary named by `RJS_NODE`. When no
+//! engine is available the tests are skipped with a notice rather than failing, so the
+//! suite still runs on machines without Node.js.
+//!
+//! ```bash
+//! RJS_NODE=/usr/local/bin/node cargo test --test differential
+//! ```
+
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+
+/// Environment variable overriding the JavaScript engine binary
+const ENGINE_ENV_VAR: &str = "RJS_NODE";
+
+/// Engine binary used when no override is given
+const DEFAULT_ENGINE: &str = "node";
+
+/// Observable behaviour of a single program execution
+#[derive(Debug, PartialEq)]
+struct Execution {
+    /// Whether the program exited successfully
+    success: bool,
+    /// Everything the program wrote to stdout
+    stdout: String,
+}
+
+/// Returns the directory containing the differential programs
+fn programs_dir() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("tests").join("differential")
+}
+
+/// Locates a working JavaScript engine, if any
+fn find_engine() -> Option<String> {
+    let engine = std::env::var(ENGINE_ENV_VAR).unwrap_or_else(|_| DEFAULT_ENGINE.to_string());
+
+    Command::new(&engine)
+        .arg("--version")
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status()
+        .ok()
+        .filter(|status| status.success())
+        .map(|_| engine)
+}
+
+/// Runs a script in the engine, feeding the source through stdin
+fn execute(engine: &str, source: &str) -> Execution {
+    let mut child = Command::new(engine)
+        .arg("-")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .expect("JavaScript engine should start");
+
+    child
+        .stdin
+        .take()
+        .expect("engine stdin should be piped")
+        .write_all(source.as_bytes())
+        .expect("source should be written to engine stdin");
+
+    let output = child.wait_with_output().expect("engine should finish");
+    Execution {
+        success: output.status.success(),
+        stdout: String::from_utf8_lossy(&output.stdout).into_owned(),
+    }
+}
+
+/// Collects all programs in the differential corpus
+fn collect_programs() -> Vec<PathBuf> {
+    let mut programs: Vec<PathBuf> = fs::read_dir(programs_dir())
+        .expect("tests/differential directory should exist")
+        .map(|entry| entry.expect("program entry should be readable").path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "js"))
+        .collect();
+
+    programs.sort();
+    programs
+}
+
+/// Compares the original and minified behaviour of one program
+///
+/// Returns a description of the divergence, or `None` when both runs agree
+fn check_program(engine: &str, program: &Path) -> Option<String> {
+    let name = program.file_name().unwrap().to_string_lossy().to_string();
+    let source = fs::read_to_string(program).expect("program should be readable");
+
+    let minified = match rjs_compiler::compile_source(&source, &name) {
+        Ok(code) => code,
+        Err(error) => return Some(format!("{}: compilation failed: {}", name, error)),
+    };
+
+    let original_run = execute(engine, &source);
+    if !original_run.success {
+        return Some(format!("{}: original program fails to run; fix the corpus", name));
+    }
+
+    let minified_run = execute(engine, &minified);
+    if original_run == minified_run {
+        return None;
+    }
+
+    Some(format!(
+        "{}: behaviour changed after minification\n  minified: {}\n  original output ({}):\n{}\n  minified output ({}):\n{}",
+        name,
+        minified,
+        if original_run.success { "ok" } else { "failed" },
+        original_run.stdout,
+        if minified_run.success { "ok" } else { "failed" },
+        minified_run.stdout
+    ))
+}
+
+#[test]
+fn test_minified_programs_behave_like_originals() {
+    let Some(engine) = find_engine() else {
+        eprintln!(
+            "skipping differential tests: no JavaScript engine found (set {} to enable)",
+            ENGINE_ENV_VAR
+        );
+        return;
+    };
+
+    let programs = collect_programs();
+    assert!(!programs.is_empty(), "no programs found in {}", programs_dir().display());
+
+    let failures: Vec<String> = programs
+        .iter()
+        .filter_map(|program| check_program(&engine, program))
+        .collect();
+
+    assert!(
+        failures.is_empty(),
+        "{} of {} programs diverged:\n\n{}",
+        failures.len(),
+        programs.len(),
+        failures.join("\n\n")
+    );
+}
@@ -0,0 +1,12 @@
+function add(a, b) {
+    return a + b;
+}
+
+function area(width, height) {
+    return width * height / 2;
+}
+
+let total = add(3, 4) * (10 - 2);
+let ratio = area(6, 4) % 5;
+console.log(total, ratio, -total, total ** 2);
+console.log(2 ** 3 ** 2, (2 ** 3) ** 2, 1 - (2 - 3));
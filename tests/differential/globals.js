@@ -0,0 +1,11 @@
+const largest = Math.max(1, 7, 3);
+const rounded = Math.round(-2.5);
+const truncated = Math.trunc(-4.7);
+const power = Math.pow(3, 4);
+const parsed = parseInt("42", 10);
+const converted = Number(" 17 ");
+const label = String(5);
+const size = "héllo".length;
+const emoji = "😀".length;
+console.log(largest, rounded, truncated, power, parsed, converted, label, size, emoji);
+console.log(typeof label, typeof size, Math.min(largest, rounded));
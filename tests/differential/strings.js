@@ -0,0 +1,9 @@
+function greet(name) {
+    return `Hello, ${name}!`;
+}
+
+let quoted = "it's";
+let doubled = 'say "hi"';
+let escaped = "tab\tnewline\nbackslash\\";
+console.log(greet("world"), quoted, doubled);
+console.log(escaped, escaped.length, "abc".length + 1);
@@ -0,0 +1,9 @@
+let value = 5;
+let negated = -value;
+let doubleNegated = - -value;
+let inverted = !value;
+let complement = ~value;
+let kind = typeof value;
+let nothing = void 0;
+console.log(negated, doubleNegated, inverted, complement, kind, nothing);
+console.log(-(-value), +"3" + 1, !!value);
@@ -0,0 +1,7 @@
+let total = (1 + 2) * 3;
+let negated = -total;
+let inverted = !negated;
+let kind = typeof total;
+let first = items[0];
+let nested = config.options.level;
+console.log(total, kind, first);
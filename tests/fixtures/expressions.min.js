@@ -0,0 +1 @@
+let total=(1+2)*3;let inverted=!-total;let kind=typeof total;let first=items[0];let nested=config.options.level;console.log(total,kind,first);
\ No newline at end of file
@@ -0,0 +1,15 @@
+function add(a, b) {
+    return a + b;
+}
+
+function greet(name) {
+    return `Hello, ${name}!`;
+}
+
+function compute(x, y) {
+    let sum = x + y;
+    let product = x * y;
+    return (sum - product) * 2;
+}
+
+export { add, greet, compute };
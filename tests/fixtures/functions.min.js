@@ -0,0 +1 @@
+function add(a, b){return a+b;}function greet(name){return `Hello, ${name}!`;}function compute(x, y){return (x+y-x*y)*2;}export{add,greet,compute};
\ No newline at end of file
@@ -0,0 +1,13 @@
+// Calls to pure globals with literal arguments are folded at compile time
+const largest = Math.max(1, 7, 3);
+const smallest = Math.min(4, -2);
+const absolute = Math.abs(-12);
+const rounded = Math.round(2.5);
+const power = Math.pow(2, 10);
+const parsed = parseInt("42", 10);
+const converted = Number("17");
+const label = String(5);
+const size = "hello".length;
+
+// Calls that depend on runtime state are left alone
+const jitter = Math.random();
@@ -0,0 +1 @@
+const largest=7;const smallest=-2;const absolute=12;const rounded=3;const power=1024;const parsed=42;const converted=17;const label='5';const size=5;const jitter=Math.random();
\ No newline at end of file
@@ -0,0 +1,7 @@
+// Plain declarations of every kind
+var count = 0;
+let name = "rjs";
+const limit = 100;
+let ratio = 0.5;
+let empty = null;
+let enabled = true;
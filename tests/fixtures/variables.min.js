@@ -0,0 +1 @@
+var count=0;let name='rjs';const limit=100;let ratio=.5;let empty=null;let enabled=true;
\ No newline at end of file
@@ -0,0 +1,84 @@
+//! # Fuzz Regression Tests
+//!
+//! Replays every input in `tests/fuzz_regressions/` through the pipeline the way the
+//! `fuzz/` targets do, and asserts that none of them panics: each must come back as
+//! output or as an error. `*.js` files are source for the `pipeline` target, read
+//! lossily so invalid UTF-8 is kept; `*.json` files are ASTs for the `ast` target.
+//!
+//! When fuzzing finds a panic, fix it and add the input here, named for what it
+//! exercises. The directory doubles as the seed corpus:
+//!
+//! ```bash
+//! cargo fuzz run pipeline tests/fuzz_regressions
+//! ```
+
+use std::fs;
+use std::panic;
+use std::path::{Path, PathBuf};
+
+use rjs_compiler::parser::ast_types::Program;
+use rjs_compiler::{analyzer, generator, parser, transformer, MinifyOptions};
+
+/// Returns the directory containing the regression inputs
+fn regressions_dir() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("tests").join("fuzz_regressions")
+}
+
+/// Analyzes, aggressively transforms and generates `ast` in both output formats
+fn run_phases(ast: Program, source: Option<&str>) {
+    let analyzer_config = analyzer::AnalyzerConfig { aggressive_optimization: true, ..Default::default() };
+    let Ok(analysis) = analyzer::analyze_ast(&ast, &analyzer_config) else {
+        return;
+    };
+    let transformer_config = transformer::TransformerConfig::default()
+        .with_aggressive_optimization(true)
+        .with_hoist_funs(true)
+        .with_hoist_vars(true)
+        .with_dedupe_strings(Some(0));
+    let Ok(result) = transformer::transform_ast_with_config(ast, analysis, transformer_config) else {
+        return;
+    };
+    for format in [generator::OutputFormat::Compact, generator::OutputFormat::Pretty] {
+        let generator = generator::Generator::new(generator::GeneratorConfig {
+            format,
+            source_map: generator::SourceMapMode::File,
+            max_line_len: Some(40),
+            ..Default::default()
+        });
+        let _ = generator.generate(&result.transformed_ast, source);
+    }
+}
+
+/// Runs one regression input through the pipeline its extension selects
+fn replay(path: &Path) {
+    let bytes = fs::read(path).expect("regression input should be readable");
+    if path.extension().is_some_and(|extension| extension == "json") {
+        let ast: Program = serde_json::from_slice(&bytes)
+            .unwrap_or_else(|e| panic!("{} is not a valid AST: {}", path.display(), e));
+        run_phases(ast.clone(), None);
+        run_phases(ast, Some(""));
+    } else {
+        let source = String::from_utf8_lossy(&bytes);
+        let _ = rjs_compiler::minify(&source, &MinifyOptions { source_map: true, ..MinifyOptions::default() });
+        if let Some(ast) = parser::parse_js(&source, "fuzz.js", &parser::ParserConfig::default()).ast {
+            run_phases(ast, Some(&source));
+        }
+    }
+}
+
+#[test]
+fn test_fuzz_regressions_do_not_panic() {
+    let mut inputs: Vec<PathBuf> = fs::read_dir(regressions_dir())
+        .expect("tests/fuzz_regressions directory should exist")
+        .map(|entry| entry.expect("regression entry should be readable").path())
+        .collect();
+    inputs.sort();
+    assert!(!inputs.is_empty(), "no regression inputs found");
+
+    let panicked: Vec<String> = inputs
+        .iter()
+        .filter(|path| panic::catch_unwind(|| replay(path)).is_err())
+        .map(|path| path.file_name().unwrap().to_string_lossy().into_owned())
+        .collect();
+    assert!(panicked.is_empty(), "the pipeline panicked on: {}", panicked.join(", "));
+}
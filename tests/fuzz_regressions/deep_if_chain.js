@@ -0,0 +1 @@
+if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) if (a) b();
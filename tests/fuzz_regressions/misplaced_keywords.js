@@ -0,0 +1 @@
+class A { #x; static { this.#x = new.target; } } super.x; import.meta; for await (x of y); yield = await;
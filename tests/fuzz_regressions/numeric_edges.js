@@ -0,0 +1 @@
+x = [1e400, -0, 0x, 07n, 0b2, .5e-7, 1_000, 1__0, 0.0000001, 123456789012345678901234567890n];
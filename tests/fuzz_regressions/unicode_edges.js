@@ -0,0 +1,5 @@
+let s = "\uD800" + '\udc00';
+let t = "a b c";
+<!-- html comment
+--> also a comment
+var \u{1F600};
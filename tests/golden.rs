@@ -0,0 +1,125 @@
+//! # Golden Snapshot Tests
+//!
+//! End-to-end regression tests for the complete compilation pipeline. Every `*.js` file
+//! in `tests/fixtures/` is compiled and compared against its `*.min.js` golden output.
+//!
+//! ## Updating Goldens
+//!
+//! After an intentional output change, regenerate the golden files and review the diff:
+//!
+//! ```bash
+//! RJS_BLESS=1 cargo test --test golden
+//! ```
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Environment variable that switches the harness from comparing to rewriting goldens
+const BLESS_ENV_VAR: &str = "RJS_BLESS";
+
+/// Extension used for golden output files
+const GOLDEN_EXTENSION: &str = "min.js";
+
+/// Returns the directory containing the fixture files
+fn fixtures_dir() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("tests").join("fixtures")
+}
+
+/// Returns true when goldens should be rewritten instead of compared
+fn bless_enabled() -> bool {
+    std::env::var(BLESS_ENV_VAR).is_ok_and(|value| !value.is_empty() && value != "0")
+}
+
+/// Collects all fixture inputs, skipping the golden outputs themselves
+fn collect_fixtures() -> Vec<PathBuf> {
+    let mut fixtures: Vec<PathBuf> = fs::read_dir(fixtures_dir())
+        .expect("tests/fixtures directory should exist")
+        .map(|entry| entry.expect("fixture entry should be readable").path())
+        .filter(|path| {
+            let name = path.file_name().unwrap().to_string_lossy();
+            name.ends_with(".js") && !name.ends_with(&format!(".{}", GOLDEN_EXTENSION))
+        })
+        .collect();
+
+    fixtures.sort();
+    fixtures
+}
+
+/// Returns the golden output path for a fixture input
+fn golden_path(fixture: &Path) -> PathBuf {
+    fixture.with_extension(GOLDEN_EXTENSION)
+}
+
+/// Compiles a fixture and compares (or blesses) its golden output
+///
+/// Returns a description of the mismatch, or `None` when the output matches
+fn check_fixture(fixture: &Path, bless: bool) -> Option<String> {
+    let name = fixture.file_name().unwrap().to_string_lossy().to_string();
+    let source = fs::read_to_string(fixture).expect("fixture should be readable");
+
+    let actual = match rjs_compiler::compile_source(&source, &name) {
+        Ok(code) => code,
+        Err(error) => return Some(format!("{}: compilation failed: {}", name, error)),
+    };
+
+    let golden = golden_path(fixture);
+    if bless {
+        fs::write(&golden, &actual).expect("golden file should be writable");
+        return None;
+    }
+
+    match fs::read_to_string(&golden) {
+        Ok(expected) if expected == actual => None,
+        Ok(expected) => Some(format!(
+            "{}: output differs from {}\n  expected: {}\n  actual:   {}",
+            name,
+            golden.file_name().unwrap().to_string_lossy(),
+            expected,
+            actual
+        )),
+        Err(_) => Some(format!(
+            "{}: missing golden file {} (run with {}=1 to create it)",
+            name,
+            golden.display(),
+            BLESS_ENV_VAR
+        )),
+    }
+}
+
+#[test]
+fn test_fixtures_match_golden_outputs() {
+    let fixtures = collect_fixtures();
+    assert!(!fixtures.is_empty(), "no fixtures found in {}", fixtures_dir().display());
+
+    let bless = bless_enabled();
+    let failures: Vec<String> = fixtures
+        .iter()
+        .filter_map(|fixture| check_fixture(fixture, bless))
+        .collect();
+
+    assert!(
+        failures.is_empty(),
+        "{} of {} fixtures failed (run with {}=1 to update goldens):\n\n{}",
+        failures.len(),
+        fixtures.len(),
+        BLESS_ENV_VAR,
+        failures.join("\n\n")
+    );
+}
+
+#[test]
+fn test_every_golden_has_a_fixture() {
+    let orphans: Vec<String> = fs::read_dir(fixtures_dir())
+        .expect("tests/fixtures directory should exist")
+        .map(|entry| entry.expect("fixture entry should be readable").path())
+        .filter(|path| path.to_string_lossy().ends_with(&format!(".{}", GOLDEN_EXTENSION)))
+        .filter(|golden| {
+            let name = golden.file_name().unwrap().to_string_lossy();
+            let stem = name.trim_end_matches(&format!(".{}", GOLDEN_EXTENSION));
+            !golden.with_file_name(format!("{}.js", stem)).exists()
+        })
+        .map(|golden| golden.display().to_string())
+        .collect();
+
+    assert!(orphans.is_empty(), "golden files without a fixture: {:?}", orphans);
+}
@@ -0,0 +1,229 @@
+//! # Parser → Printer Round-Trip Property Tests
+//!
+//! Generates random ASTs, prints them with the generator, re-parses the output and
+//! asserts that the re-parsed AST is structurally identical to the original. This
+//! catches precedence, ASI and escaping bugs without hand-written cases.
+//!
+//! The generators only produce node types that both the parser conversion layer and
+//! the printer support; extend them as coverage grows. Failing cases are shrunk and
+//! persisted by proptest under `tests/roundtrip.proptest-regressions`.
+//!
+//! ```bash
+//! PROPTEST_CASES=10000 cargo test --test roundtrip
+//! ```
+
+use proptest::prelude::*;
+
+use rjs_compiler::generator::{Generator, GeneratorConfig, OutputFormat};
+use rjs_compiler::parser::ast_types::{
+    BinaryOperator, Expression, Identifier, Literal, NumberLiteral, Pattern, Program,
+    ProgramSourceType, Statement, StringLiteral, UnaryOperator, VariableDeclarationKind,
+    VariableDeclarator,
+};
+use rjs_compiler::parser::{parse_js, ParserConfig};
+
+/// Identifiers used by generated programs (never reserved words)
+const IDENTIFIERS: &[&str] = &["a", "b", "foo", "bar", "$x", "_y", "x1", "length"];
+
+fn identifier() -> impl Strategy<Value = Identifier> {
+    proptest::sample::select(IDENTIFIERS).prop_map(|name| Identifier { name: name.to_string(), span: None })
+}
+
+fn number_literal() -> impl Strategy<Value = Expression> {
+    // Negative numbers are expressed through unary minus, as the parser produces them
+    prop_oneof![
+        (0u32..1000).prop_map(f64::from),
+        (0u32..10_000, 1u32..1000).prop_map(|(whole, frac)| whole as f64 + 1.0 / frac as f64),
+        any::<f64>().prop_filter("finite, non-negative", |v| v.is_finite() && *v >= 0.0),
+    ]
+    .prop_map(|value| Expression::Literal(Literal::Number(NumberLiteral { value, raw: None, span: None })))
+}
+
+fn string_literal() -> impl Strategy<Value = Expression> {
+    "[a-z '\"\\\\\n\r\t$`é😀]{0,8}"
+        .prop_map(|value| Expression::Literal(Literal::String(StringLiteral::new(value, None))))
+}
+
+fn leaf_expression() -> impl Strategy<Value = Expression> {
+    prop_oneof![
+        4 => identifier().prop_map(Expression::Identifier),
+        3 => number_literal(),
+        2 => string_literal(),
+        1 => Just(Expression::Literal(Literal::Null)),
+        1 => any::<bool>().prop_map(|value| {
+            Expression::Literal(Literal::Boolean(rjs_compiler::parser::ast_types::BooleanLiteral { value, span: None }))
+        }),
+        1 => Just(Expression::ThisExpression),
+    ]
+}
+
+fn binary_operator() -> impl Strategy<Value = BinaryOperator> {
+    proptest::sample::select(vec![
+        BinaryOperator::Add,
+        BinaryOperator::Subtract,
+        BinaryOperator::Multiply,
+        BinaryOperator::Divide,
+        BinaryOperator::Remainder,
+        BinaryOperator::Exponentiation,
+        BinaryOperator::Equal,
+        BinaryOperator::NotEqual,
+        BinaryOperator::StrictEqual,
+        BinaryOperator::StrictNotEqual,
+        BinaryOperator::LessThan,
+        BinaryOperator::LessThanEqual,
+        BinaryOperator::GreaterThan,
+        BinaryOperator::GreaterThanEqual,
+        BinaryOperator::LeftShift,
+        BinaryOperator::RightShift,
+        BinaryOperator::UnsignedRightShift,
+        BinaryOperator::BitwiseAnd,
+        BinaryOperator::BitwiseOr,
+        BinaryOperator::BitwiseXor,
+        BinaryOperator::LogicalAnd,
+        BinaryOperator::LogicalOr,
+        BinaryOperator::NullishCoalescing,
+        BinaryOperator::In,
+        BinaryOperator::Instanceof,
+    ])
+}
+
+fn unary_operator() -> impl Strategy<Value = UnaryOperator> {
+    // `delete` is excluded: deleting a plain identifier is a syntax error in modules
+    proptest::sample::select(vec![
+        UnaryOperator::Minus,
+        UnaryOperator::Plus,
+        UnaryOperator::LogicalNot,
+        UnaryOperator::BitwiseNot,
+        UnaryOperator::Typeof,
+        UnaryOperator::Void,
+    ])
+}
+
+fn expression() -> impl Strategy<Value = Expression> {
+    leaf_expression().prop_recursive(5, 48, 4, |inner| {
+        prop_oneof![
+            4 => (inner.clone(), binary_operator(), inner.clone()).prop_map(|(left, operator, right)| {
+                Expression::BinaryExpression { left: Box::new(left), operator, right: Box::new(right) }
+            }),
+            2 => (unary_operator(), inner.clone()).prop_map(|(operator, argument)| {
+                Expression::UnaryExpression { operator, argument: Box::new(argument), prefix: true }
+            }),
+            1 => (inner.clone(), identifier()).prop_map(|(object, property)| {
+                Expression::MemberExpression {
+                    object: Box::new(object),
+                    property: Box::new(Expression::Identifier(property)),
+                    computed: false,
+                    optional: false,
+                }
+            }),
+            1 => (inner.clone(), inner.clone()).prop_map(|(object, property)| {
+                Expression::MemberExpression {
+                    object: Box::new(object),
+                    property: Box::new(property),
+                    computed: true,
+                    optional: false,
+                }
+            }),
+            1 => (inner.clone(), prop::collection::vec(inner, 0..3)).prop_map(|(callee, arguments)| {
+                Expression::CallExpression { callee: Box::new(callee), arguments, optional: false }
+            }),
+        ]
+    })
+}
+
+fn statement(index: usize) -> impl Strategy<Value = Statement> {
+    prop_oneof![
+        3 => expression().prop_map(move |init| Statement::VariableDeclaration {
+            declarations: vec![VariableDeclarator {
+                id: Pattern::Identifier(Identifier { name: format!("v{}", index), span: None }),
+                init: Some(init),
+                span: None,
+            }],
+            kind: VariableDeclarationKind::Let,
+            span: None,
+        }),
+        // Expression statements start with a call so they cannot be read as directives
+        1 => (identifier(), prop::collection::vec(expression(), 0..3)).prop_map(|(callee, arguments)| {
+            Statement::ExpressionStatement {
+                expression: Expression::CallExpression {
+                    callee: Box::new(Expression::Identifier(callee)),
+                    arguments,
+                    optional: false,
+                },
+                span: None,
+            }
+        }),
+    ]
+}
+
+fn program() -> impl Strategy<Value = Program> {
+    (1usize..5)
+        .prop_flat_map(|count| (0..count).map(statement).collect::<Vec<_>>())
+        .prop_map(|body| Program { body, source_type: ProgramSourceType::Module })
+}
+
+fn output_format() -> impl Strategy<Value = OutputFormat> {
+    prop_oneof![
+        Just(OutputFormat::Compact),
+        Just(OutputFormat::Readable),
+        Just(OutputFormat::Pretty),
+    ]
+}
+
+/// Prints a program and parses it back, returning the printed code and re-parsed AST
+fn round_trip(program: &Program, format: OutputFormat) -> (String, Program) {
+    let config = GeneratorConfig { format, ..GeneratorConfig::default() };
+    let code = Generator::new(config)
+        .generate(program, None)
+        .expect("generated AST should print")
+        .code;
+
+    let parse_result = parse_js(&code, "roundtrip.js", &ParserConfig::default());
+    assert!(
+        parse_result.errors.is_empty(),
+        "printed code failed to parse: {:?}\n{}",
+        parse_result.errors,
+        code
+    );
+
+    (code, parse_result.ast.expect("parser should produce an AST"))
+}
+
+/// Serializes `program` without source spans and number literal source text, which only
+/// the parser records
+fn without_source_details(program: &Program) -> serde_json::Value {
+    fn strip(value: &mut serde_json::Value) {
+        match value {
+            serde_json::Value::Object(map) => {
+                map.remove("raw");
+                map.remove("span");
+                map.values_mut().for_each(strip);
+            }
+            serde_json::Value::Array(items) => items.iter_mut().for_each(strip),
+            _ => {}
+        }
+    }
+
+    let mut value = serde_json::to_value(program).unwrap();
+    strip(&mut value);
+    value
+}
+
+proptest! {
+    #[test]
+    fn test_print_parse_round_trip(program in program(), format in output_format()) {
+        let (code, reparsed) = round_trip(&program, format);
+
+        let expected = without_source_details(&program);
+        let actual = without_source_details(&reparsed);
+        prop_assert_eq!(expected, actual, "printed code: {}", code);
+    }
+
+    #[test]
+    fn test_printing_is_idempotent(program in program()) {
+        let (code, reparsed) = round_trip(&program, OutputFormat::Compact);
+        let (reprinted, _) = round_trip(&reparsed, OutputFormat::Compact);
+
+        prop_assert_eq!(code, reprinted);
+    }
+}